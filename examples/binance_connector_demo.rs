@@ -36,6 +36,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         secret_key: None,
         testnet: false, // 使用实盘URL
         rate_limit_per_minute: 1200,
+        websocket_url: None,
+        rest_api_url: None,
     };
     
     println!("📋 配置信息:");
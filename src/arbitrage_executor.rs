@@ -0,0 +1,1321 @@
+//! 跨交易所两腿套利执行
+//! `ArbitrageExecutor` 接收扫描器产生的 `CrossExchangeArb`，通过交易前风控后，
+//! 在报价处用 IOC 限价单同时下买卖两腿，并结合用户数据流推送的订单状态确认各腿成交量。
+//! 两腿成交量不一致时，按 `HedgeMode` 补齐落后的一腿（对冲）或反向平掉多出的部分（回滚），
+//! 每次重试加大价格偏移。每一步都记录在 `ExecutionReport` 中。
+//!
+//! 执行器内部的数量统一使用基础资产单位、价格使用每单位基础资产的归一化价格，
+//! 下单时再按合约乘数和交易规则换算成各交易所挂牌的价格与数量。
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::config::{get_config, ExecutionConfig, HedgeMode};
+use crate::connectors::common::data_channel::DataReceiver;
+use crate::connectors::common::instruments::{InstrumentRegistry, InstrumentSpec, PriceRounding};
+use crate::connectors::traits::ExchangeConnector;
+use crate::core::{base_asset, AppError, AppState, BALANCE_QUOTE_ASSET};
+use crate::cross_exchange::get_opportunity_key;
+use crate::depth_sizing::SizingLimits;
+use crate::error_handling::init_error_tracker;
+use crate::exchange_types::{CrossExchangeArb, Exchange};
+use crate::symbol_mapper::contract_multipliers;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{OrderUpdate, StandardizedMessage, UserData};
+use crate::types::orders::{OrderRequest, OrderResponse, OrderSide, OrderStatus, OrderType, TimeInForce};
+
+const QTY_EPSILON: f64 = 1e-12;
+/// 撤单后确认终态的最多查询轮数
+const SETTLE_QUERY_ATTEMPTS: u32 = 5;
+/// 第一轮查询后等待推送的时间，之后逐轮翻倍
+const SETTLE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// 订单状态是否为终态（大小写不敏感）
+pub fn is_terminal_status(status: &str) -> bool {
+    matches!(
+        status.to_uppercase().as_str(),
+        "FILLED" | "CANCELED" | "CANCELLED" | "REJECTED" | "EXPIRED" | "EXPIRED_IN_MATCH"
+    )
+}
+
+/// 用户数据流推送的订单状态，按 (交易所, 订单ID) 保存合并后的最新一条
+///
+/// 推送可能乱序到达：累计成交量只增不减，终态一旦出现不会被非终态覆盖，
+/// 成交均价取累计成交量最大的一条推送。
+#[derive(Debug, Default)]
+pub struct FillTracker {
+    orders: DashMap<(ExchangeType, String), OrderUpdate>,
+    notify: Notify,
+}
+
+impl FillTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一条用户数据消息，返回是否为订单更新
+    pub fn apply(&self, message: &StandardizedMessage) -> bool {
+        match message {
+            StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(update)) => {
+                self.update(update.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn update(&self, update: OrderUpdate) {
+        match self.orders.entry((update.exchange, update.order_id.clone())) {
+            Entry::Occupied(mut entry) => {
+                let current = entry.get_mut();
+                if update.filled_quantity >= current.filled_quantity && update.average_price.is_some() {
+                    current.average_price = update.average_price;
+                }
+                let filled = current.filled_quantity.max(update.filled_quantity);
+                if !is_terminal_status(&current.status) || is_terminal_status(&update.status) {
+                    current.status = update.status;
+                    current.remaining_quantity = update.remaining_quantity;
+                    current.timestamp = update.timestamp;
+                }
+                current.filled_quantity = filled;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(update);
+            }
+        }
+        self.notify.notify_waiters();
+    }
+
+    pub fn get(&self, exchange: ExchangeType, order_id: &str) -> Option<OrderUpdate> {
+        self.orders.get(&(exchange, order_id.to_string())).map(|update| update.clone())
+    }
+
+    /// 等待订单进入终态，超时返回 None
+    pub async fn wait_terminal(&self, exchange: ExchangeType, order_id: &str, timeout: Duration) -> Option<OrderUpdate> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(update) = self.get(exchange, order_id).filter(|update| is_terminal_status(&update.status)) {
+                return Some(update);
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return None;
+            }
+        }
+    }
+
+    /// 执行完成后丢弃订单记录
+    pub fn forget(&self, exchange: ExchangeType, order_id: &str) {
+        self.orders.remove(&(exchange, order_id.to_string()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+/// 订单在一次执行中的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LegRole {
+    /// 开仓的买腿或卖腿
+    Entry,
+    /// 补齐落后的一腿
+    Hedge,
+    /// 反向平掉多成交的部分
+    Unwind,
+}
+
+/// 执行报告中记录的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ExecutionAction {
+    RiskCheckPassed,
+    RiskCheckRejected,
+    OrderPlaced,
+    OrderFailed,
+    OrderFilled,
+    OrderCanceled,
+    Finished,
+}
+
+/// 执行过程中的一步
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionStep {
+    pub timestamp: i64,
+    pub action: ExecutionAction,
+    pub role: Option<LegRole>,
+    pub exchange: Option<Exchange>,
+    pub side: Option<OrderSide>,
+    pub order_id: Option<String>,
+    /// 基础资产数量
+    pub quantity: f64,
+    /// 每单位基础资产的价格
+    pub price: Option<f64>,
+    pub detail: String,
+}
+
+impl ExecutionStep {
+    fn note(action: ExecutionAction, detail: impl Into<String>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            action,
+            role: None,
+            exchange: None,
+            side: None,
+            order_id: None,
+            quantity: 0.0,
+            price: None,
+            detail: detail.into(),
+        }
+    }
+
+    fn order(action: ExecutionAction, order: &LegOrder, order_id: Option<&str>, quantity: f64, price: Option<f64>, detail: impl Into<String>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            action,
+            role: Some(order.role),
+            exchange: Some(order.leg.exchange),
+            side: Some(order.side),
+            order_id: order_id.map(str::to_string),
+            quantity,
+            price,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 执行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ExecutionStatus {
+    /// 未通过交易前风控，没有下单
+    Rejected,
+    /// 两腿都没有成交
+    NotFilled,
+    /// 两腿成交量一致，无需对冲
+    Completed,
+    /// 通过补齐落后的一腿恢复平衡
+    Hedged,
+    /// 通过反向平仓恢复平衡
+    Unwound,
+    /// 对冲与回滚后仍有超过阈值的净敞口
+    Unbalanced,
+}
+
+/// 一次两腿执行的完整记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionReport {
+    pub id: String,
+    pub symbol: String,
+    pub buy_exchange: Exchange,
+    pub sell_exchange: Exchange,
+    pub quoted_buy_price: f64,
+    pub quoted_sell_price: f64,
+    pub expected_net_profit_pct: f64,
+    /// 计划的每腿数量（基础资产）
+    pub planned_quantity: f64,
+    pub status: ExecutionStatus,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// 所有订单买入与卖出的基础资产总量
+    pub bought_quantity: f64,
+    pub sold_quantity: f64,
+    /// 剩余净敞口，买多为正
+    pub residual_quantity: f64,
+    /// 卖出金额减买入金额（不含手续费），净敞口为零时即已实现盈亏
+    pub cash_flow: f64,
+    pub steps: Vec<ExecutionStep>,
+}
+
+impl ExecutionReport {
+    fn new(id: String, arb: &CrossExchangeArb) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
+        Self {
+            id,
+            symbol: arb.symbol.clone(),
+            buy_exchange: arb.buy_exchange,
+            sell_exchange: arb.sell_exchange,
+            quoted_buy_price: arb.buy_price,
+            quoted_sell_price: arb.sell_price,
+            expected_net_profit_pct: arb.net_profit_pct,
+            planned_quantity: 0.0,
+            status: ExecutionStatus::NotFilled,
+            started_at: now,
+            finished_at: now,
+            bought_quantity: 0.0,
+            sold_quantity: 0.0,
+            residual_quantity: 0.0,
+            cash_flow: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    fn step(&mut self, action: ExecutionAction, detail: impl Into<String>) {
+        self.steps.push(ExecutionStep::note(action, detail));
+    }
+
+    fn record_fill(&mut self, side: OrderSide, quantity: f64, price: f64) {
+        match side {
+            OrderSide::Buy => {
+                self.bought_quantity += quantity;
+                self.cash_flow -= quantity * price;
+            }
+            OrderSide::Sell => {
+                self.sold_quantity += quantity;
+                self.cash_flow += quantity * price;
+            }
+        }
+        self.residual_quantity = self.bought_quantity - self.sold_quantity;
+    }
+}
+
+/// 一条腿在某交易所的挂牌信息
+#[derive(Debug, Clone)]
+struct Leg {
+    exchange: Exchange,
+    /// 交易所挂牌的交易对
+    symbol: String,
+    /// 挂牌价格相对每单位基础资产的倍数（如 1000PEPE 为 1000）
+    price_multiplier: f64,
+    spec: Option<Arc<InstrumentSpec>>,
+}
+
+impl Leg {
+    /// 每单位下单数量对应的基础资产数量
+    fn base_per_unit(&self) -> f64 {
+        let contract_multiplier = self.spec.as_ref()
+            .map_or(1.0, |spec| spec.base_quantity(1.0));
+        self.price_multiplier * contract_multiplier
+    }
+
+    fn order_quantity(&self, base_quantity: f64) -> f64 {
+        let quantity = base_quantity / self.base_per_unit();
+        self.spec.as_ref().map_or(quantity, |spec| spec.round_qty(quantity))
+    }
+
+    /// 开仓腿向远离盘口的方向取整，价格不劣于报价；
+    /// 对冲和回滚腿向穿越盘口的方向取整，取整不会削减已让出的价格偏移
+    fn order_price(&self, price: f64, side: OrderSide, role: LegRole) -> f64 {
+        let price = price * self.price_multiplier;
+        let rounding = match (role, side) {
+            (LegRole::Entry, OrderSide::Buy) | (LegRole::Hedge | LegRole::Unwind, OrderSide::Sell) => PriceRounding::Down,
+            (LegRole::Entry, OrderSide::Sell) | (LegRole::Hedge | LegRole::Unwind, OrderSide::Buy) => PriceRounding::Up,
+        };
+        self.spec.as_ref().map_or(price, |spec| spec.round_price(price, rounding))
+    }
+}
+
+/// 待下的一笔订单（基础资产单位）
+struct LegOrder {
+    leg: Leg,
+    role: LegRole,
+    side: OrderSide,
+    quantity: f64,
+    /// 每单位基础资产的限价，None 为市价单
+    price: Option<f64>,
+    reduce_only: bool,
+}
+
+/// 结算时合并的订单进度（交易所挂牌单位）
+struct OrderProgress {
+    filled: f64,
+    average_price: Option<f64>,
+    status: String,
+}
+
+impl OrderProgress {
+    /// 合并一次状态：均价只取累计成交量不少于当前的来源，终态不会被非终态覆盖
+    fn merge(&mut self, filled: f64, average_price: Option<f64>, status: &str) {
+        if let Some(price) = average_price.filter(|price| *price > 0.0 && filled >= self.filled) {
+            self.average_price = Some(price);
+        }
+        self.filled = self.filled.max(filled);
+        if !is_terminal_status(&self.status) || is_terminal_status(status) {
+            self.status = status.to_string();
+        }
+    }
+
+    fn merge_update(&mut self, update: &OrderUpdate) {
+        self.merge(update.filled_quantity, update.average_price, &update.status);
+    }
+
+    fn merge_status(&mut self, current: &OrderStatus) {
+        self.merge(current.filled_quantity, current.average_price, &current.status);
+    }
+}
+
+/// 一笔订单的最终成交（基础资产单位）
+struct LegFill {
+    quantity: f64,
+    price: f64,
+}
+
+/// 正在执行的机会，离开作用域时释放
+struct InFlight<'a> {
+    keys: &'a Mutex<HashSet<String>>,
+    key: String,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+    }
+}
+
+/// 两腿套利执行器
+pub struct ArbitrageExecutor {
+    app_state: Arc<AppState>,
+    config: ExecutionConfig,
+    connectors: HashMap<Exchange, Arc<dyn ExchangeConnector>>,
+    instruments: Option<Arc<InstrumentRegistry>>,
+    fills: Arc<FillTracker>,
+    in_flight: Mutex<HashSet<String>>,
+    reports: Mutex<Vec<ExecutionReport>>,
+    sequence: AtomicU64,
+}
+
+impl ArbitrageExecutor {
+    pub fn new(app_state: Arc<AppState>, config: ExecutionConfig) -> Self {
+        Self {
+            app_state,
+            config,
+            connectors: HashMap::new(),
+            instruments: None,
+            fills: Arc::new(FillTracker::new()),
+            in_flight: Mutex::new(HashSet::new()),
+            reports: Mutex::new(Vec::new()),
+            sequence: AtomicU64::new(1),
+        }
+    }
+
+    /// 注册用于下单的连接器，按其交易所类型索引
+    pub fn add_connector(&mut self, connector: Arc<dyn ExchangeConnector>) {
+        self.connectors.insert(connector.get_exchange_type().into(), connector);
+    }
+
+    /// 设置交易规则注册表，下单前按价格步长和数量步长取整并校验
+    pub fn set_instrument_registry(&mut self, instruments: Arc<InstrumentRegistry>) {
+        self.instruments = Some(instruments);
+    }
+
+    pub fn fills(&self) -> Arc<FillTracker> {
+        self.fills.clone()
+    }
+
+    /// 消费一个用户数据流，把订单更新写入成交跟踪器
+    pub fn attach_user_stream(&self, mut stream: DataReceiver<StandardizedMessage>) -> JoinHandle<()> {
+        let fills = self.fills.clone();
+        tokio::spawn(async move {
+            while let Some(message) = stream.recv().await {
+                fills.apply(&message);
+            }
+        })
+    }
+
+    /// 接管所有已注册连接器的用户数据流
+    ///
+    /// 连接器的用户数据流只有一个消费者，调用后其他模块不再收到这些连接器的用户数据。
+    pub fn attach_user_streams(&self) -> Vec<JoinHandle<()>> {
+        self.connectors
+            .values()
+            .map(|connector| self.attach_user_stream(connector.get_user_data_stream()))
+            .collect()
+    }
+
+    /// 取出尚未写出的执行报告
+    pub fn take_reports(&self) -> Vec<ExecutionReport> {
+        std::mem::take(&mut *self.reports.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 执行一个跨交易所机会，返回完整的执行报告（同时保存在执行器中等待写出）
+    pub async fn execute(&self, arb: &CrossExchangeArb) -> ExecutionReport {
+        let id = format!(
+            "arb{}{}",
+            chrono::Utc::now().timestamp_millis(),
+            self.sequence.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut report = ExecutionReport::new(id, arb);
+        self.run(arb, &mut report).await;
+
+        report.finished_at = chrono::Utc::now().timestamp_millis();
+        report.step(ExecutionAction::Finished, format!("{:?}", report.status));
+        info!(
+            "[执行] {} {} {}→{} 状态 {:?}，买入 {:.6} 卖出 {:.6}，净敞口 {:.6}，现金流 {:.4}",
+            report.id, report.symbol, report.buy_exchange, report.sell_exchange, report.status,
+            report.bought_quantity, report.sold_quantity, report.residual_quantity, report.cash_flow
+        );
+        self.reports.lock().unwrap_or_else(|e| e.into_inner()).push(report.clone());
+        report
+    }
+
+    async fn run(&self, arb: &CrossExchangeArb, report: &mut ExecutionReport) {
+        let buy_leg = self.resolve_leg(arb.buy_exchange, &arb.symbol);
+        let sell_leg = self.resolve_leg(arb.sell_exchange, &arb.symbol);
+        let quantity = self.plan_quantity(arb, &buy_leg, &sell_leg);
+        report.planned_quantity = quantity;
+
+        // 占用该机会直到本次执行结束
+        let _in_flight = match self.pre_trade_check(arb, quantity).await {
+            Ok(in_flight) => in_flight,
+            Err(reason) => {
+                warn!("[执行] {} {} 未通过风控: {reason}", report.id, arb.symbol);
+                report.status = ExecutionStatus::Rejected;
+                report.step(ExecutionAction::RiskCheckRejected, reason);
+                return;
+            }
+        };
+        report.step(ExecutionAction::RiskCheckPassed, format!("数量 {quantity:.6}，名义价值 {:.2}", quantity * arb.buy_price));
+
+        // 两腿同时以报价下 IOC 限价单
+        let buy_order = LegOrder {
+            leg: buy_leg.clone(),
+            role: LegRole::Entry,
+            side: OrderSide::Buy,
+            quantity,
+            price: Some(arb.buy_price),
+            reduce_only: false,
+        };
+        let sell_order = LegOrder {
+            leg: sell_leg.clone(),
+            role: LegRole::Entry,
+            side: OrderSide::Sell,
+            quantity,
+            price: Some(arb.sell_price),
+            reduce_only: false,
+        };
+        let buy_id = format!("{}-buy", report.id);
+        let sell_id = format!("{}-sell", report.id);
+        let (buy_placed, sell_placed) = tokio::join!(
+            self.place(&buy_order, &buy_id),
+            self.place(&sell_order, &sell_id)
+        );
+        let (buy_fill, sell_fill) = tokio::join!(
+            self.settle(&buy_order, buy_placed),
+            self.settle(&sell_order, sell_placed)
+        );
+        for (order, (fill, steps)) in [(&buy_order, buy_fill), (&sell_order, sell_fill)] {
+            report.steps.extend(steps);
+            if let Some(fill) = fill {
+                report.record_fill(order.side, fill.quantity, fill.price);
+            }
+        }
+
+        if report.bought_quantity <= QTY_EPSILON && report.sold_quantity <= QTY_EPSILON {
+            report.status = ExecutionStatus::NotFilled;
+            return;
+        }
+
+        let phases: &[LegRole] = match self.config.hedge_mode {
+            HedgeMode::Hedge => &[LegRole::Hedge],
+            HedgeMode::Unwind => &[LegRole::Unwind],
+            HedgeMode::HedgeThenUnwind => &[LegRole::Hedge, LegRole::Unwind],
+        };
+        let mut hedged = false;
+        let mut unwound = false;
+        for &role in phases {
+            for attempt in 0..self.config.max_hedge_attempts {
+                if self.is_dust(report.residual_quantity, arb.buy_price) {
+                    break;
+                }
+                let order = self.imbalance_order(arb, &buy_leg, &sell_leg, report.residual_quantity, role, attempt);
+                let client_id = format!("{}-{}{}", report.id, if role == LegRole::Hedge { "h" } else { "u" }, attempt + 1);
+                let placed = self.place(&order, &client_id).await;
+                let (fill, steps) = self.settle(&order, placed).await;
+                report.steps.extend(steps);
+                if let Some(fill) = fill.filter(|fill| fill.quantity > QTY_EPSILON) {
+                    report.record_fill(order.side, fill.quantity, fill.price);
+                    match role {
+                        LegRole::Unwind => unwound = true,
+                        _ => hedged = true,
+                    }
+                }
+            }
+        }
+
+        report.status = if !self.is_dust(report.residual_quantity, arb.buy_price) {
+            ExecutionStatus::Unbalanced
+        } else if unwound {
+            ExecutionStatus::Unwound
+        } else if hedged {
+            ExecutionStatus::Hedged
+        } else {
+            ExecutionStatus::Completed
+        };
+    }
+
+    /// 交易前风控，通过时占用该机会直到执行结束
+    async fn pre_trade_check(&self, arb: &CrossExchangeArb, quantity: f64) -> Result<InFlight<'_>, String> {
+        let age_ms = chrono::Utc::now().timestamp_millis() - arb.timestamp;
+        if age_ms > self.config.max_opportunity_age_ms {
+            return Err(format!("机会已过期 {age_ms}ms"));
+        }
+        if arb.net_profit_pct < self.config.min_net_profit_pct {
+            return Err(format!("净利润 {:.4}% 低于 {:.4}%", arb.net_profit_pct, self.config.min_net_profit_pct));
+        }
+
+        for exchange in [arb.buy_exchange, arb.sell_exchange] {
+            let Some(connector) = self.connectors.get(&exchange) else {
+                return Err(format!("{exchange} 没有可下单的连接器"));
+            };
+            if !connector.is_connected().await {
+                return Err(format!("{exchange} 连接器未连接"));
+            }
+            if init_error_tracker().is_circuit_open(exchange) {
+                return Err(format!("{exchange} 熔断中"));
+            }
+        }
+
+        let limits = SizingLimits::for_pair(&self.app_state, &arb.symbol, arb.buy_exchange, arb.sell_exchange);
+        let notional = quantity * arb.buy_price;
+        if quantity <= QTY_EPSILON || notional < limits.min_notional {
+            return Err(format!("名义价值 {notional:.2} 低于最小值 {:.2}", limits.min_notional));
+        }
+        // 余额未同步（没有账户接口或查询失败）时无法确认资金是否足够，不下单
+        match limits.buy_balance {
+            None => return Err(format!("{} 可用余额未知", arb.buy_exchange)),
+            Some(balance) if balance < notional => {
+                return Err(format!("{} 可用余额 {balance:.2} 不足 {notional:.2}", arb.buy_exchange));
+            }
+            Some(_) => {}
+        }
+        // 卖出腿按连接器的市场类型检查：现货需要持有足够的基础货币，合约需要足够的保证金
+        let sell_exchange = arb.sell_exchange;
+        if self.connectors[&sell_exchange].get_market_type() == MarketType::Spot {
+            let Some(asset) = base_asset(&arb.symbol) else {
+                return Err(format!("{} 无法确定基础货币", arb.symbol));
+            };
+            match self.app_state.available_balance(sell_exchange, asset) {
+                None => return Err(format!("{sell_exchange} {asset} 可用数量未知")),
+                Some(inventory) if inventory < quantity => {
+                    return Err(format!("{sell_exchange} {asset} 可用数量 {inventory} 不足 {quantity}"));
+                }
+                Some(_) => {}
+            }
+        } else {
+            match self.app_state.available_balance(sell_exchange, BALANCE_QUOTE_ASSET) {
+                None => return Err(format!("{sell_exchange} 可用余额未知")),
+                Some(balance) if balance < notional => {
+                    return Err(format!("{sell_exchange} 可用余额 {balance:.2} 不足 {notional:.2}"));
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(instruments) = &self.instruments {
+            for (leg, side, price) in [
+                (self.resolve_leg(arb.buy_exchange, &arb.symbol), OrderSide::Buy, arb.buy_price),
+                (self.resolve_leg(arb.sell_exchange, &arb.symbol), OrderSide::Sell, arb.sell_price),
+            ] {
+                let order = Self::order_request(&LegOrder {
+                    leg: leg.clone(),
+                    role: LegRole::Entry,
+                    side,
+                    quantity,
+                    price: Some(price),
+                    reduce_only: false,
+                }, String::new());
+                instruments
+                    .validate_order(leg.exchange, &order)
+                    .map_err(|e| format!("{} {} 不满足交易规则: {e}", leg.exchange, leg.symbol))?;
+            }
+        }
+
+        let key = get_opportunity_key(&arb.symbol, &arb.buy_exchange, &arb.sell_exchange);
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight.contains(&key) {
+            return Err("该机会正在执行".to_string());
+        }
+        if in_flight.len() >= self.config.max_concurrent_executions {
+            return Err(format!("并发执行数已达上限 {}", self.config.max_concurrent_executions));
+        }
+        in_flight.insert(key.clone());
+        Ok(InFlight { keys: &self.in_flight, key })
+    }
+
+    /// 查找交易对在某交易所的挂牌：优先使用订单簿里的挂牌代码，否则沿用统一代码
+    fn resolve_leg(&self, exchange: Exchange, symbol: &str) -> Leg {
+        let listing = self.app_state.book_store
+            .get(exchange, symbol)
+            .map_or_else(|| symbol.to_string(), |book| book.symbol);
+        let price_multiplier = contract_multipliers().multiplier(exchange, &listing);
+        let spec = self.instruments.as_ref().and_then(|instruments| instruments.get(exchange, &listing));
+        Leg {
+            exchange,
+            symbol: listing,
+            price_multiplier,
+            spec,
+        }
+    }
+
+    /// 计划数量：优先使用按深度求得的最优数量，并受单腿名义价值上限约束；
+    /// 两腿分别按数量步长取整后取较小者，保证两边代表相同的基础资产数量
+    fn plan_quantity(&self, arb: &CrossExchangeArb, buy_leg: &Leg, sell_leg: &Leg) -> f64 {
+        if arb.buy_price <= 0.0 {
+            return 0.0;
+        }
+        let mut quantity = if arb.optimal_size > 0.0 {
+            arb.optimal_size
+        } else {
+            get_config().arbitrage.default_trade_size_usd / arb.buy_price
+        };
+        quantity = quantity.min(self.config.max_notional_per_leg_usd / arb.buy_price);
+        [buy_leg, sell_leg]
+            .iter()
+            .map(|leg| leg.order_quantity(quantity) * leg.base_per_unit())
+            .fold(quantity, f64::min)
+    }
+
+    fn is_dust(&self, residual: f64, price: f64) -> bool {
+        residual.abs() <= QTY_EPSILON || residual.abs() * price < self.config.min_hedge_notional_usd
+    }
+
+    /// 根据净敞口生成对冲或回滚订单
+    ///
+    /// 买多（净敞口为正）时：对冲在卖出所补卖，回滚在买入所卖回；卖多时反之。
+    /// 参考价优先取当前订单簿，每次重试把价格偏移加大 `hedge_slippage_step_pct`。
+    fn imbalance_order(&self, arb: &CrossExchangeArb, buy_leg: &Leg, sell_leg: &Leg, residual: f64, role: LegRole, attempt: u32) -> LegOrder {
+        let side = if residual > 0.0 { OrderSide::Sell } else { OrderSide::Buy };
+        let (leg, quoted) = match (role, side) {
+            (LegRole::Unwind, OrderSide::Sell) => (buy_leg, arb.buy_price),
+            (LegRole::Unwind, OrderSide::Buy) => (sell_leg, arb.sell_price),
+            (_, OrderSide::Sell) => (sell_leg, arb.sell_price),
+            (_, OrderSide::Buy) => (buy_leg, arb.buy_price),
+        };
+
+        let reference = self.app_state.book_store
+            .get(leg.exchange, &arb.symbol)
+            .map(|book| match side {
+                OrderSide::Buy => book.best_ask,
+                OrderSide::Sell => book.best_bid,
+            })
+            .filter(|price| *price > 0.0)
+            .map_or(quoted, |price| price / leg.price_multiplier);
+        let slippage = (self.config.hedge_slippage_pct + self.config.hedge_slippage_step_pct * attempt as f64) / 100.0;
+        let price = match side {
+            OrderSide::Buy => reference * (1.0 + slippage),
+            OrderSide::Sell => reference * (1.0 - slippage),
+        };
+        let is_last = attempt + 1 >= self.config.max_hedge_attempts;
+
+        LegOrder {
+            leg: leg.clone(),
+            role,
+            side,
+            quantity: residual.abs(),
+            price: (!(is_last && self.config.market_on_last_attempt)).then_some(price),
+            // 回滚合约腿只减仓，避免反向开仓
+            reduce_only: role == LegRole::Unwind && !leg.exchange.is_spot(),
+        }
+    }
+
+    fn order_request(order: &LegOrder, client_order_id: String) -> OrderRequest {
+        OrderRequest {
+            symbol: order.leg.symbol.clone(),
+            exchange: order.leg.exchange.into(),
+            side: order.side,
+            order_type: if order.price.is_some() { OrderType::Limit } else { OrderType::Market },
+            quantity: order.leg.order_quantity(order.quantity),
+            price: order.price.map(|price| order.leg.order_price(price, order.side, order.role)),
+            time_in_force: order.price.map(|_| TimeInForce::IOC),
+            reduce_only: order.reduce_only.then_some(true),
+            close_position: None,
+            position_side: None,
+            client_order_id: (!client_order_id.is_empty()).then_some(client_order_id),
+        }
+    }
+
+    async fn place(&self, order: &LegOrder, client_order_id: &str) -> Result<OrderResponse, String> {
+        let Some(connector) = self.connectors.get(&order.leg.exchange) else {
+            return Err(format!("{} 没有可下单的连接器", order.leg.exchange));
+        };
+        let request = Self::order_request(order, client_order_id.to_string());
+        if request.quantity <= QTY_EPSILON {
+            return Err(format!("数量 {:.8} 按步长取整后为零", order.quantity));
+        }
+        connector.place_order(&request).await.map_err(|e| e.to_string())
+    }
+
+    /// 确认订单的最终成交：先看下单响应，再等用户数据流推送终态，
+    /// 超时则主动查询，仍未结束时撤单并查询到终态为止。
+    /// 成交价取推送或查询到的累计成交均价，都没有时才退回限价
+    async fn settle(&self, order: &LegOrder, placed: Result<OrderResponse, String>) -> (Option<LegFill>, Vec<ExecutionStep>) {
+        let mut steps = Vec::new();
+        let response = match placed {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("[执行] {} {:?} {:?} 下单失败: {e}", order.leg.exchange, order.role, order.side);
+                steps.push(ExecutionStep::order(ExecutionAction::OrderFailed, order, None, order.quantity, order.price, e));
+                return (None, steps);
+            }
+        };
+        steps.push(ExecutionStep::order(ExecutionAction::OrderPlaced, order, Some(&response.order_id), order.quantity, order.price, response.status.clone()));
+
+        let exchange_type = ExchangeType::from(order.leg.exchange);
+        let connector = self.connectors.get(&order.leg.exchange);
+        let mut progress = OrderProgress {
+            filled: 0.0,
+            average_price: None,
+            status: String::new(),
+        };
+        progress.merge(response.filled_quantity, response.average_price, &response.status);
+
+        if !is_terminal_status(&progress.status) {
+            let timeout = Duration::from_millis(self.config.fill_timeout_ms);
+            match self.fills.wait_terminal(exchange_type, &response.order_id, timeout).await {
+                Some(update) => progress.merge_update(&update),
+                None => {
+                    if let Some(connector) = connector {
+                        if let Ok(current) = connector.get_order_status(&response.order_id, &order.leg.symbol).await {
+                            progress.merge_status(&current);
+                        }
+                        if !is_terminal_status(&progress.status) {
+                            self.cancel_until_terminal(connector.as_ref(), order, &response.order_id, &mut progress, &mut steps).await;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(update) = self.fills.get(exchange_type, &response.order_id) {
+            progress.merge_update(&update);
+        }
+        self.fills.forget(exchange_type, &response.order_id);
+
+        let OrderProgress { filled, average_price, status } = progress;
+        let quantity = filled * order.leg.base_per_unit();
+        let price = average_price
+            .map(|price| price / order.leg.price_multiplier)
+            .or(order.price)
+            .unwrap_or(0.0);
+        steps.push(ExecutionStep::order(ExecutionAction::OrderFilled, order, Some(&response.order_id), quantity, Some(price), status));
+        (Some(LegFill { quantity, price }), steps)
+    }
+
+    /// 撤销确认超时的订单并查询到终态为止：撤单失败时每轮重新撤单，
+    /// 两次查询之间等待用户数据流推送，间隔逐次翻倍，`SETTLE_QUERY_ATTEMPTS` 轮后放弃
+    async fn cancel_until_terminal(
+        &self,
+        connector: &dyn ExchangeConnector,
+        order: &LegOrder,
+        order_id: &str,
+        progress: &mut OrderProgress,
+        steps: &mut Vec<ExecutionStep>,
+    ) {
+        let exchange_type = ExchangeType::from(order.leg.exchange);
+        let mut canceled = false;
+        let mut delay = SETTLE_RETRY_DELAY;
+        for attempt in 1..=SETTLE_QUERY_ATTEMPTS {
+            if !canceled {
+                let detail = match connector.cancel_order(order_id, &order.leg.symbol).await {
+                    Ok(_) => {
+                        canceled = true;
+                        "确认超时，撤销残留".to_string()
+                    }
+                    Err(e) => format!("确认超时，第{attempt}次撤单失败: {e}"),
+                };
+                steps.push(ExecutionStep::order(ExecutionAction::OrderCanceled, order, Some(order_id), 0.0, None, detail));
+            }
+            if let Ok(current) = connector.get_order_status(order_id, &order.leg.symbol).await {
+                progress.merge_status(&current);
+            }
+            if is_terminal_status(&progress.status) {
+                return;
+            }
+            if let Some(update) = self.fills.wait_terminal(exchange_type, order_id, delay).await {
+                progress.merge_update(&update);
+                return;
+            }
+            delay *= 2;
+        }
+        warn!(
+            "[执行] {} {} 订单 {order_id} 查询{SETTLE_QUERY_ATTEMPTS}次后仍未进入终态（{}），按已知成交量结算",
+            order.leg.exchange, order.leg.symbol, progress.status
+        );
+    }
+}
+
+/// 以 JSON Lines 追加写出执行报告，返回写出的条数
+pub async fn write_execution_reports(reports: &[ExecutionReport], filename: &str) -> Result<usize, AppError> {
+    if reports.is_empty() {
+        return Ok(0);
+    }
+    let mut lines = String::new();
+    for report in reports {
+        lines.push_str(&serde_json::to_string(report)?);
+        lines.push('\n');
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)
+        .await?;
+    file.write_all(lines.as_bytes()).await?;
+    Ok(reports.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::collections::VecDeque;
+    use crate::connectors::common::data_channel::data_channel;
+    use crate::types::account::AccountBalance;
+    use crate::types::config::ConnectionStatus;
+    use crate::types::errors::ConnectorError;
+    use crate::types::exchange::MarketType;
+    use crate::types::market_data::{StandardizedOrderBook, StandardizedTrade};
+    use crate::types::orders::OrderStatus;
+
+    /// 按预设比例立即成交的连接器，记录收到的全部订单；
+    /// `resting` 时下单只返回NEW，成交由测试推送或按 `queries` 依次返回查询结果
+    struct MockConnector {
+        exchange: ExchangeType,
+        market_type: MarketType,
+        fill_ratios: Mutex<VecDeque<f64>>,
+        resting: bool,
+        queries: Mutex<VecDeque<OrderStatus>>,
+        cancel_failures: Mutex<u32>,
+        orders: Mutex<Vec<OrderRequest>>,
+    }
+
+    impl MockConnector {
+        fn new(exchange: Exchange, fill_ratios: &[f64]) -> Arc<Self> {
+            Self::with_market(exchange, MarketType::Futures, fill_ratios)
+        }
+
+        fn spot(exchange: Exchange, fill_ratios: &[f64]) -> Arc<Self> {
+            Self::with_market(exchange, MarketType::Spot, fill_ratios)
+        }
+
+        fn with_market(exchange: Exchange, market_type: MarketType, fill_ratios: &[f64]) -> Arc<Self> {
+            Arc::new(Self {
+                exchange: exchange.into(),
+                market_type,
+                fill_ratios: Mutex::new(fill_ratios.iter().copied().collect()),
+                resting: false,
+                queries: Mutex::new(VecDeque::new()),
+                cancel_failures: Mutex::new(0),
+                orders: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn resting(exchange: Exchange) -> Arc<Self> {
+            Self::resting_with(exchange, &[], 0)
+        }
+
+        /// 挂单不成交，查询依次返回 (状态, 累计成交, 均价)，前 `cancel_failures` 次撤单失败
+        fn resting_with(exchange: Exchange, queries: &[(&str, f64, Option<f64>)], cancel_failures: u32) -> Arc<Self> {
+            let queries = queries
+                .iter()
+                .map(|(status, filled, average_price)| OrderStatus {
+                    order_id: String::new(),
+                    symbol: String::new(),
+                    status: status.to_string(),
+                    filled_quantity: *filled,
+                    remaining_quantity: 0.0,
+                    average_price: *average_price,
+                    timestamp: 0,
+                })
+                .collect();
+            Arc::new(Self {
+                exchange: exchange.into(),
+                market_type: MarketType::Futures,
+                fill_ratios: Mutex::new(VecDeque::new()),
+                resting: true,
+                queries: Mutex::new(queries),
+                cancel_failures: Mutex::new(cancel_failures),
+                orders: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn orders(&self) -> Vec<OrderRequest> {
+            self.orders.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl ExchangeConnector for MockConnector {
+        fn get_exchange_type(&self) -> ExchangeType {
+            self.exchange
+        }
+
+        fn get_market_type(&self) -> MarketType {
+            self.market_type
+        }
+
+        fn get_exchange_name(&self) -> &str {
+            "Mock"
+        }
+
+        async fn connect_websocket(&self) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn subscribe_orderbook(&self, _symbol: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn subscribe_trades(&self, _symbol: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+            data_channel(get_config().websocket_optimization.market_data_channel).1
+        }
+
+        fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+            data_channel(get_config().websocket_optimization.user_data_channel).1
+        }
+
+        async fn get_orderbook_snapshot(&self, _symbol: &str) -> Option<StandardizedOrderBook> {
+            None
+        }
+
+        async fn get_recent_trades_snapshot(&self, _symbol: &str, _limit: usize) -> Vec<StandardizedTrade> {
+            Vec::new()
+        }
+
+        async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+            let mut orders = self.orders.lock().unwrap();
+            orders.push(order.clone());
+            let order_id = format!("{}-{}", self.exchange, orders.len());
+            if self.resting {
+                return Ok(OrderResponse {
+                    order_id,
+                    client_order_id: order.client_order_id.clone(),
+                    symbol: order.symbol.clone(),
+                    status: "NEW".to_string(),
+                    filled_quantity: 0.0,
+                    remaining_quantity: order.quantity,
+                    average_price: None,
+                    timestamp: 0,
+                });
+            }
+            let ratio = self.fill_ratios.lock().unwrap().pop_front().unwrap_or(1.0);
+            let filled = order.quantity * ratio;
+            Ok(OrderResponse {
+                order_id,
+                client_order_id: order.client_order_id.clone(),
+                symbol: order.symbol.clone(),
+                status: if ratio >= 1.0 { "FILLED" } else { "CANCELED" }.to_string(),
+                filled_quantity: filled,
+                remaining_quantity: order.quantity - filled,
+                average_price: order.price,
+                timestamp: 0,
+            })
+        }
+
+        async fn cancel_order(&self, _order_id: &str, _symbol: &str) -> Result<bool, ConnectorError> {
+            let mut failures = self.cancel_failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(ConnectorError::NetworkError("cancel timed out".to_string()));
+            }
+            Ok(true)
+        }
+
+        async fn get_order_status(&self, _order_id: &str, _symbol: &str) -> Result<OrderStatus, ConnectorError> {
+            self.queries.lock().unwrap().pop_front().ok_or(ConnectorError::TradingNotImplemented)
+        }
+
+        async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+            Err(ConnectorError::TradingNotImplemented)
+        }
+
+        async fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn is_websocket_connected(&self) -> bool {
+            true
+        }
+
+        async fn get_connection_status(&self) -> ConnectionStatus {
+            ConnectionStatus::Connected
+        }
+    }
+
+    fn arb() -> CrossExchangeArb {
+        CrossExchangeArb {
+            symbol: "EXECTESTUSDT".to_string(),
+            buy_exchange: Exchange::XtCom,
+            sell_exchange: Exchange::TapBit,
+            buy_price: 100.0,
+            sell_price: 101.0,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            profit_pct: 1.0,
+            net_profit_pct: 0.8,
+            total_fees_pct: 0.2,
+            optimal_size: 2.0,
+            expected_pnl: 1.6,
+            profit_curve: Vec::new(),
+        }
+    }
+
+    fn executor(config: ExecutionConfig, buy: &Arc<MockConnector>, sell: &Arc<MockConnector>) -> ArbitrageExecutor {
+        let app_state = AppState::new();
+        app_state.set_available_balance(Exchange::XtCom, "USDT", 10_000.0);
+        app_state.set_available_balance(Exchange::TapBit, "USDT", 10_000.0);
+        let mut executor = ArbitrageExecutor::new(Arc::new(app_state), config);
+        executor.add_connector(buy.clone());
+        executor.add_connector(sell.clone());
+        executor
+    }
+
+    fn config(hedge_mode: HedgeMode) -> ExecutionConfig {
+        ExecutionConfig {
+            hedge_mode,
+            fill_timeout_ms: 50,
+            ..ExecutionConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_both_legs_filled() {
+        let buy = MockConnector::new(Exchange::XtCom, &[1.0]);
+        let sell = MockConnector::new(Exchange::TapBit, &[1.0]);
+        let executor = executor(config(HedgeMode::HedgeThenUnwind), &buy, &sell);
+
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Completed);
+        assert_eq!(report.planned_quantity, 2.0);
+        assert!(report.residual_quantity.abs() < 1e-9);
+        assert!((report.cash_flow - 2.0).abs() < 1e-9);
+
+        let buy_orders = buy.orders();
+        assert_eq!(buy_orders.len(), 1);
+        assert_eq!(buy_orders[0].side, OrderSide::Buy);
+        assert_eq!(buy_orders[0].time_in_force, Some(TimeInForce::IOC));
+        assert_eq!(buy_orders[0].price, Some(100.0));
+        assert_eq!(sell.orders()[0].price, Some(101.0));
+        assert_eq!(executor.take_reports().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_is_hedged_on_lagging_leg() {
+        let buy = MockConnector::new(Exchange::XtCom, &[1.0]);
+        let sell = MockConnector::new(Exchange::TapBit, &[0.25, 1.0]);
+        let executor = executor(config(HedgeMode::Hedge), &buy, &sell);
+
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Hedged);
+        assert!(report.residual_quantity.abs() < 1e-9);
+
+        let sell_orders = sell.orders();
+        assert_eq!(sell_orders.len(), 2);
+        assert_eq!(sell_orders[1].side, OrderSide::Sell);
+        assert!((sell_orders[1].quantity - 1.5).abs() < 1e-9);
+        // 第一次对冲在参考价基础上让出 hedge_slippage_pct
+        assert!((sell_orders[1].price.unwrap() - 101.0 * (1.0 - 0.0005)).abs() < 1e-9);
+        assert_eq!(buy.orders().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_is_unwound_on_filled_leg() {
+        let buy = MockConnector::new(Exchange::XtCom, &[1.0, 0.5, 1.0]);
+        let sell = MockConnector::new(Exchange::TapBit, &[0.25]);
+        let executor = executor(
+            ExecutionConfig { max_hedge_attempts: 2, ..config(HedgeMode::Unwind) },
+            &buy,
+            &sell,
+        );
+
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Unwound);
+        assert!(report.residual_quantity.abs() < 1e-9);
+
+        let buy_orders = buy.orders();
+        assert_eq!(buy_orders.len(), 3);
+        assert_eq!(buy_orders[1].side, OrderSide::Sell);
+        assert_eq!(buy_orders[1].reduce_only, Some(true));
+        assert!((buy_orders[1].quantity - 1.5).abs() < 1e-9);
+        // 最后一次回滚改用市价单
+        assert_eq!(buy_orders[2].order_type, OrderType::Market);
+        assert!((buy_orders[2].quantity - 0.75).abs() < 1e-9);
+        assert_eq!(sell.orders().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pre_trade_checks_reject_without_orders() {
+        let buy = MockConnector::new(Exchange::XtCom, &[]);
+        let sell = MockConnector::new(Exchange::TapBit, &[]);
+        let executor = executor(config(HedgeMode::Hedge), &buy, &sell);
+
+        let mut stale = arb();
+        stale.timestamp -= 10_000;
+        assert_eq!(executor.execute(&stale).await.status, ExecutionStatus::Rejected);
+
+        let mut thin = arb();
+        thin.net_profit_pct = 0.05;
+        assert_eq!(executor.execute(&thin).await.status, ExecutionStatus::Rejected);
+
+        let mut unknown = arb();
+        unknown.sell_exchange = Exchange::Hbit;
+        let report = executor.execute(&unknown).await;
+        assert_eq!(report.status, ExecutionStatus::Rejected);
+        assert_eq!(report.steps[0].action, ExecutionAction::RiskCheckRejected);
+
+        // 余额不足或未同步时不下单
+        executor.app_state.set_available_balance(Exchange::TapBit, "USDT", 50.0);
+        assert!(executor.execute(&arb()).await.steps[0].detail.contains("不足"));
+        executor.app_state.available_balances.remove(&(Exchange::TapBit, "USDT".to_string()));
+        assert!(executor.execute(&arb()).await.steps[0].detail.contains("未知"));
+
+        assert!(buy.orders().is_empty());
+        assert!(sell.orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spot_sell_leg_requires_base_inventory() {
+        let buy = MockConnector::new(Exchange::XtCom, &[1.0]);
+        let sell = MockConnector::spot(Exchange::TapBit, &[1.0]);
+        let executor = executor(config(HedgeMode::Hedge), &buy, &sell);
+
+        // 报价货币充足，但现货卖出所没有同步到基础货币持仓
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Rejected);
+        assert!(report.steps[0].detail.contains("EXECTEST 可用数量未知"));
+
+        executor.app_state.set_available_balance(Exchange::TapBit, "EXECTEST", 1.0);
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Rejected);
+        assert!(report.steps[0].detail.contains("不足"));
+        assert!(buy.orders().is_empty());
+        assert!(sell.orders().is_empty());
+
+        // 持仓足够时不再要求卖出所的报价货币
+        executor.app_state.set_available_balance(Exchange::TapBit, "EXECTEST", 2.0);
+        executor.app_state.available_balances.remove(&(Exchange::TapBit, "USDT".to_string()));
+        assert_eq!(executor.execute(&arb()).await.status, ExecutionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_fill_tracker_merges_out_of_order_updates() {
+        let tracker = Arc::new(FillTracker::new());
+        let update = |status: &str, filled: f64| OrderUpdate {
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            exchange: ExchangeType::OkxFutures,
+            status: status.to_string(),
+            filled_quantity: filled,
+            remaining_quantity: 2.0 - filled,
+            average_price: Some(100.0 + filled),
+            timestamp: 0,
+        };
+
+        let waiter = {
+            let tracker = tracker.clone();
+            tokio::spawn(async move { tracker.wait_terminal(ExchangeType::OkxFutures, "1", Duration::from_secs(5)).await })
+        };
+        tokio::task::yield_now().await;
+        tracker.update(update("PARTIALLY_FILLED", 1.0));
+        tracker.update(update("FILLED", 2.0));
+        // 迟到的部分成交推送不会覆盖终态和累计成交量
+        tracker.update(update("PARTIALLY_FILLED", 1.5));
+
+        let settled = waiter.await.unwrap().unwrap();
+        assert_eq!(settled.status, "FILLED");
+        let latest = tracker.get(ExchangeType::OkxFutures, "1").unwrap();
+        assert_eq!(latest.status, "FILLED");
+        assert_eq!(latest.filled_quantity, 2.0);
+        assert_eq!(latest.average_price, Some(102.0));
+
+        assert!(tracker
+            .wait_terminal(ExchangeType::OkxFutures, "2", Duration::from_millis(10))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fill_price_comes_from_terminal_update() {
+        let buy = MockConnector::resting(Exchange::XtCom);
+        let sell = MockConnector::new(Exchange::TapBit, &[1.0]);
+        let executor = executor(ExecutionConfig { fill_timeout_ms: 2_000, ..config(HedgeMode::Hedge) }, &buy, &sell);
+
+        // 买腿以低于限价的均价成交，成交价以推送的均价为准
+        let fills = executor.fills();
+        let order_id = format!("{}-1", ExchangeType::from(Exchange::XtCom));
+        let pusher = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            fills.update(OrderUpdate {
+                order_id,
+                symbol: "EXECTESTUSDT".to_string(),
+                exchange: ExchangeType::from(Exchange::XtCom),
+                status: "FILLED".to_string(),
+                filled_quantity: 2.0,
+                remaining_quantity: 0.0,
+                average_price: Some(99.5),
+                timestamp: 0,
+            });
+        });
+
+        let report = executor.execute(&arb()).await;
+        pusher.await.unwrap();
+        assert_eq!(report.status, ExecutionStatus::Completed);
+        assert!((report.cash_flow - 3.0).abs() < 1e-9, "{}", report.cash_flow);
+        let fill = report.steps.iter().find(|step| step.action == ExecutionAction::OrderFilled && step.side == Some(OrderSide::Buy)).unwrap();
+        assert_eq!(fill.price, Some(99.5));
+    }
+
+    #[tokio::test]
+    async fn test_failed_cancel_requeries_until_terminal() {
+        let buy = MockConnector::resting_with(
+            Exchange::XtCom,
+            &[("NEW", 0.0, None), ("NEW", 0.0, None), ("CANCELED", 1.5, Some(99.8))],
+            1,
+        );
+        let sell = MockConnector::new(Exchange::TapBit, &[0.75]);
+        let executor = executor(config(HedgeMode::Hedge), &buy, &sell);
+
+        let report = executor.execute(&arb()).await;
+        assert_eq!(report.status, ExecutionStatus::Completed);
+        assert!((report.bought_quantity - 1.5).abs() < 1e-9);
+        assert!(report.residual_quantity.abs() < 1e-9);
+
+        // 第一次撤单失败后重新撤单，再查询到终态
+        let cancels: Vec<_> = report.steps.iter().filter(|step| step.action == ExecutionAction::OrderCanceled).collect();
+        assert_eq!(cancels.len(), 2);
+        assert!(cancels[0].detail.contains("撤单失败"));
+        let fill = report.steps.iter().find(|step| step.action == ExecutionAction::OrderFilled && step.side == Some(OrderSide::Buy)).unwrap();
+        assert_eq!((fill.price, fill.detail.as_str()), (Some(99.8), "CANCELED"));
+        assert!(buy.queries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_imbalance_orders_round_toward_crossing() {
+        let leg = Leg {
+            exchange: Exchange::XtCom,
+            symbol: "EXECTESTUSDT".to_string(),
+            price_multiplier: 1.0,
+            spec: Some(Arc::new(InstrumentSpec::new(Exchange::XtCom, "EXECTESTUSDT", rust_decimal::Decimal::new(1, 1), rust_decimal::Decimal::new(1, 3)))),
+        };
+        assert_eq!(leg.order_price(100.07, OrderSide::Buy, LegRole::Entry), 100.0);
+        assert_eq!(leg.order_price(100.03, OrderSide::Sell, LegRole::Entry), 100.1);
+        for role in [LegRole::Hedge, LegRole::Unwind] {
+            assert_eq!(leg.order_price(100.03, OrderSide::Buy, role), 100.1);
+            assert_eq!(leg.order_price(100.07, OrderSide::Sell, role), 100.0);
+        }
+    }
+}
@@ -0,0 +1,645 @@
+//! 多跳套利图与增量负环检测
+//! 节点为 (交易所, 资产)，边权为 -ln(扣除手续费后的兑换率)：同一交易所内每个交易对给出买入边
+//! （计价 -> 基础，按卖盘）与卖出边（基础 -> 计价，按买盘），同一资产在不同交易所之间有按
+//! 划转成本加权的划转边。权重之和为负的环即为套利环。
+//!
+//! 负环用增量 SPFA 检测：距离与前驱在扫描之间保留，每次只从被更新订单簿触及的边开始松弛；
+//! 边权变大且该边是前驱边时，重置以其终点为根的前驱子树。
+
+use log::warn;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::book_store::{BookStore, BookWatcher};
+use crate::depth_sizing::book_levels;
+use crate::exchange_types::{Exchange, ExchangeFees, MultiHopArbitragePath, StandardOrderBook};
+use crate::symbol_mapper::contract_multipliers;
+
+/// 未配置手续费的交易所使用的默认吃单费率
+const DEFAULT_TAKER_FEE: f64 = 0.001; // 0.1%
+
+const WEIGHT_EPSILON: f64 = 1e-12;
+
+/// 常见计价货币，`USDT` 必须排在 `USD` 之前
+const QUOTE_ASSETS: [&str; 6] = ["USDT", "USD", "BTC", "ETH", "USDC", "BNB"];
+
+/// 环路优先从这些资产出发，容量与利润以其计价
+const PREFERRED_START_ASSETS: [&str; 3] = ["USDT", "USDC", "USD"];
+
+/// 按常见计价货币后缀拆分规范化交易对，如 `ETHBTC` -> (`ETH`, `BTC`)
+pub fn extract_trading_pair(symbol: &str) -> Option<(String, String)> {
+    QUOTE_ASSETS.iter().find_map(|&quote| {
+        (symbol.ends_with(quote) && symbol.len() > quote.len())
+            .then(|| (symbol[..symbol.len() - quote.len()].to_string(), quote.to_string()))
+    })
+}
+
+/// 边的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// 用计价货币买入基础货币，吃卖盘
+    Buy,
+    /// 卖出基础货币换取计价货币，吃买盘
+    Sell,
+    /// 同一资产在交易所之间划转
+    Transfer,
+}
+
+#[derive(Debug, Clone)]
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: EdgeKind,
+    exchange: Exchange,
+    /// 交易对（划转边为资产名）
+    symbol: String,
+    /// 盘口价格：买入边为卖一，卖出边为买一，划转边为 1
+    price: f64,
+    fee: f64,
+    weight: f64,
+    /// 已按合约乘数换算的深度档位，买入边按价格升序，卖出边按价格降序
+    levels: Vec<(f64, f64)>,
+}
+
+impl Edge {
+    fn rate(&self) -> f64 {
+        (-self.weight).exp()
+    }
+
+    /// 沿深度成交 `amount`（以起点资产计）得到的终点资产数量，深度不足时返回 None
+    fn output(&self, amount: f64) -> Option<f64> {
+        if self.kind == EdgeKind::Transfer {
+            return Some(amount * self.rate());
+        }
+        let mut remaining = amount;
+        let mut out = 0.0;
+        for &(price, quantity) in &self.levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            match self.kind {
+                EdgeKind::Buy => {
+                    let take = remaining.min(price * quantity);
+                    out += take / price;
+                    remaining -= take;
+                }
+                _ => {
+                    let take = remaining.min(quantity);
+                    out += take * price;
+                    remaining -= take;
+                }
+            }
+        }
+        (remaining <= amount * 1e-12).then_some(out * (1.0 - self.fee))
+    }
+
+    /// 全部深度可吃下的起点资产数量；划转边不限量
+    fn capacity(&self) -> f64 {
+        match self.kind {
+            EdgeKind::Buy => self.levels.iter().map(|(price, quantity)| price * quantity).sum(),
+            EdgeKind::Sell => self.levels.iter().map(|(_, quantity)| quantity).sum(),
+            EdgeKind::Transfer => f64::INFINITY,
+        }
+    }
+}
+
+fn weight_for(rate: f64) -> f64 {
+    if rate > 0.0 && rate.is_finite() {
+        -rate.ln()
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// 多跳套利图，跨扫描保留 SPFA 的距离与前驱
+#[derive(Default)]
+pub struct ArbitrageGraph {
+    nodes: Vec<(Exchange, String)>,
+    node_index: HashMap<(Exchange, String), usize>,
+    asset_nodes: HashMap<String, Vec<usize>>,
+    edges: Vec<Edge>,
+    edge_index: HashMap<(Exchange, String, EdgeKind), usize>,
+    symbol_exchanges: HashMap<String, HashSet<Exchange>>,
+    out_edges: Vec<Vec<usize>>,
+    in_edges: Vec<Vec<usize>>,
+    dist: Vec<f64>,
+    pred: Vec<Option<usize>>,
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+    transfer_cost: f64,
+    watcher: Option<BookWatcher>,
+}
+
+impl fmt::Debug for ArbitrageGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArbitrageGraph")
+            .field("nodes", &self.nodes.len())
+            .field("edges", &self.edges.len())
+            .field("pending", &self.queue.len())
+            .finish()
+    }
+}
+
+impl ArbitrageGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// 从订单簿存储同步变化的交易对，返回处理的交易对数量
+    ///
+    /// 首次调用时注册观察者并载入全部交易对，之后只处理观察者记录的变更。
+    /// `transfer_cost` 为小数形式的跨交易所划转成本。
+    pub fn refresh(
+        &mut self,
+        book_store: &BookStore,
+        exchange_fees: &HashMap<Exchange, ExchangeFees>,
+        transfer_cost: f64,
+    ) -> usize {
+        self.set_transfer_cost(transfer_cost);
+        let symbols = match &self.watcher {
+            Some(watcher) => watcher.take_changed(),
+            None => {
+                self.watcher = Some(book_store.watch());
+                book_store.symbols()
+            }
+        };
+
+        for symbol in &symbols {
+            let books = book_store.books_for(symbol);
+            let present: HashSet<Exchange> = books.iter().map(|book| book.exchange).collect();
+            let stale: Vec<Exchange> = self
+                .symbol_exchanges
+                .get(symbol)
+                .map(|exchanges| exchanges.difference(&present).copied().collect())
+                .unwrap_or_default();
+            for exchange in stale {
+                self.remove_book(exchange, symbol);
+            }
+            for book in &books {
+                let fee = exchange_fees
+                    .get(&book.exchange)
+                    .map(|fees| fees.taker_fee)
+                    .unwrap_or(DEFAULT_TAKER_FEE);
+                self.apply_book(symbol, book, fee);
+            }
+        }
+        symbols.len()
+    }
+
+    /// 写入一个订单簿：更新该交易所该交易对的买入边与卖出边
+    ///
+    /// `symbol` 为规范化交易对；价格与数量按合约乘数换算为每单位基础资产。
+    pub fn apply_book(&mut self, symbol: &str, book: &StandardOrderBook, taker_fee: f64) {
+        let Some((base, quote)) = extract_trading_pair(symbol) else {
+            return;
+        };
+        let multipliers = contract_multipliers();
+        let mut asks = book_levels(book, &multipliers, true);
+        let mut bids = book_levels(book, &multipliers, false);
+        let best_ask = multipliers.normalize_price(book.exchange, &book.symbol, book.best_ask);
+        let best_bid = multipliers.normalize_price(book.exchange, &book.symbol, book.best_bid);
+        drop(multipliers);
+        asks.sort_by(|a, b| a.0.total_cmp(&b.0));
+        bids.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let base_node = self.node(book.exchange, &base);
+        let quote_node = self.node(book.exchange, &quote);
+        let buy_rate = if best_ask > 0.0 { (1.0 - taker_fee) / best_ask } else { 0.0 };
+        let sell_rate = best_bid * (1.0 - taker_fee);
+        self.upsert_edge(quote_node, base_node, EdgeKind::Buy, book.exchange, symbol, best_ask, taker_fee, buy_rate, asks);
+        self.upsert_edge(base_node, quote_node, EdgeKind::Sell, book.exchange, symbol, best_bid, taker_fee, sell_rate, bids);
+        self.symbol_exchanges
+            .entry(symbol.to_string())
+            .or_default()
+            .insert(book.exchange);
+    }
+
+    /// 订单簿失效：该交易所该交易对的两条边不再可用
+    pub fn remove_book(&mut self, exchange: Exchange, symbol: &str) {
+        for kind in [EdgeKind::Buy, EdgeKind::Sell] {
+            if let Some(&e) = self.edge_index.get(&(exchange, symbol.to_string(), kind)) {
+                self.edges[e].levels.clear();
+                self.set_weight(e, f64::INFINITY);
+            }
+        }
+        if let Some(exchanges) = self.symbol_exchanges.get_mut(symbol) {
+            exchanges.remove(&exchange);
+        }
+    }
+
+    /// 运行增量 SPFA，返回本轮新发现的套利环（按净收益降序）
+    ///
+    /// 每发现一个负环就屏蔽闭合它的那条边直到本轮结束，所以同一环只在相关订单簿更新后才会再次上报。
+    /// 超过 `max_trade_hops` 笔交易（不含划转）的环不上报。
+    pub fn find_cycles(&mut self, max_trade_hops: usize, max_cycles: usize) -> Vec<MultiHopArbitragePath> {
+        let mut blocked = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut cycles = Vec::new();
+        let budget = (self.nodes.len() + 1) * (self.edges.len() + 1);
+        let mut relaxations = 0;
+
+        while let Some(u) = self.queue.pop_front() {
+            self.queued[u] = false;
+            for i in 0..self.out_edges[u].len() {
+                let e = self.out_edges[u][i];
+                if blocked.contains(&e) {
+                    continue;
+                }
+                let v = self.edges[e].to;
+                let candidate = self.dist[u] + self.edges[e].weight;
+                if candidate >= self.dist[v] - WEIGHT_EPSILON {
+                    continue;
+                }
+
+                if let Some(cycle) = self.closing_cycle(u, v, e) {
+                    blocked.insert(e);
+                    if cycles.len() < max_cycles {
+                        if let Some(path) = self.build_path(&cycle, max_trade_hops) {
+                            if seen.insert(path.path_id.clone()) {
+                                cycles.push(path);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                self.dist[v] = candidate;
+                self.pred[v] = Some(e);
+                self.enqueue(v);
+
+                relaxations += 1;
+                if relaxations > budget {
+                    warn!("多跳套利图松弛次数超过上限 {budget}，重置距离");
+                    self.reset();
+                    return cycles;
+                }
+            }
+        }
+
+        cycles.sort_by(|a, b| b.net_profit_pct.total_cmp(&a.net_profit_pct));
+        cycles
+    }
+
+    fn node(&mut self, exchange: Exchange, asset: &str) -> usize {
+        if let Some(&index) = self.node_index.get(&(exchange, asset.to_string())) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.nodes.push((exchange, asset.to_string()));
+        self.node_index.insert((exchange, asset.to_string()), index);
+        self.out_edges.push(Vec::new());
+        self.in_edges.push(Vec::new());
+        self.dist.push(0.0);
+        self.pred.push(None);
+        self.queued.push(false);
+
+        // 与其它交易所上的同一资产互连划转边
+        let peers = self.asset_nodes.entry(asset.to_string()).or_default().clone();
+        let rate = 1.0 - self.transfer_cost;
+        for peer in peers {
+            let peer_exchange = self.nodes[peer].0;
+            self.upsert_edge(index, peer, EdgeKind::Transfer, peer_exchange, asset, 1.0, 0.0, rate, Vec::new());
+            self.upsert_edge(peer, index, EdgeKind::Transfer, exchange, asset, 1.0, 0.0, rate, Vec::new());
+        }
+        self.asset_nodes.entry(asset.to_string()).or_default().push(index);
+        self.enqueue(index);
+        index
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn upsert_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        kind: EdgeKind,
+        exchange: Exchange,
+        symbol: &str,
+        price: f64,
+        fee: f64,
+        rate: f64,
+        levels: Vec<(f64, f64)>,
+    ) {
+        // 划转边以目的交易所和资产标识，同一资产可能有多条，需要带上起点区分
+        let key_symbol = match kind {
+            EdgeKind::Transfer => format!("{}>{}", self.nodes[from].0, symbol),
+            _ => symbol.to_string(),
+        };
+        let key = (exchange, key_symbol, kind);
+        let weight = weight_for(rate);
+        match self.edge_index.get(&key) {
+            Some(&e) => {
+                let edge = &mut self.edges[e];
+                edge.price = price;
+                edge.fee = fee;
+                edge.levels = levels;
+                self.set_weight(e, weight);
+            }
+            None => {
+                let e = self.edges.len();
+                self.edges.push(Edge {
+                    from,
+                    to,
+                    kind,
+                    exchange,
+                    symbol: symbol.to_string(),
+                    price,
+                    fee,
+                    weight,
+                    levels,
+                });
+                self.edge_index.insert(key, e);
+                self.out_edges[from].push(e);
+                self.in_edges[to].push(e);
+                self.enqueue(from);
+            }
+        }
+    }
+
+    fn set_transfer_cost(&mut self, transfer_cost: f64) {
+        if (transfer_cost - self.transfer_cost).abs() < f64::EPSILON {
+            return;
+        }
+        self.transfer_cost = transfer_cost;
+        let weight = weight_for(1.0 - transfer_cost);
+        let transfers: Vec<usize> = (0..self.edges.len())
+            .filter(|&e| self.edges[e].kind == EdgeKind::Transfer)
+            .collect();
+        for e in transfers {
+            self.set_weight(e, weight);
+        }
+    }
+
+    /// 更新边权：变小时从起点重新松弛；变大且是前驱边时，终点的子树距离失效
+    fn set_weight(&mut self, e: usize, weight: f64) {
+        let old = self.edges[e].weight;
+        self.edges[e].weight = weight;
+        if weight < old {
+            self.enqueue(self.edges[e].from);
+        } else if weight > old && self.pred[self.edges[e].to] == Some(e) {
+            self.invalidate_subtree(self.edges[e].to);
+        }
+    }
+
+    /// 重置以 `root` 为根的前驱子树：距离回到虚拟源点的 0，并重新松弛指向这些节点的边
+    fn invalidate_subtree(&mut self, root: usize) {
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (node, pred) in self.pred.iter().enumerate() {
+            if let Some(e) = pred {
+                children.entry(self.edges[*e].from).or_default().push(node);
+            }
+        }
+
+        let mut visited = HashSet::from([root]);
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            self.dist[node] = 0.0;
+            self.pred[node] = None;
+            self.enqueue(node);
+            for i in 0..self.in_edges[node].len() {
+                let from = self.edges[self.in_edges[node][i]].from;
+                self.enqueue(from);
+            }
+            for &child in children.get(&node).into_iter().flatten() {
+                if visited.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
+    }
+
+    /// 若 `v` 是 `u` 在前驱树中的祖先，边 `e`(u -> v) 会闭合一个环，返回环上的边（从 v 出发）
+    fn closing_cycle(&self, u: usize, v: usize, e: usize) -> Option<Vec<usize>> {
+        let mut cycle = vec![e];
+        let mut node = u;
+        for _ in 0..=self.nodes.len() {
+            if node == v {
+                cycle.reverse();
+                return Some(cycle);
+            }
+            let pred = self.pred[node]?;
+            cycle.push(pred);
+            node = self.edges[pred].from;
+        }
+        None
+    }
+
+    /// 把负环转换为套利路径，并沿深度求使利润最大的投入量
+    fn build_path(&self, cycle: &[usize], max_trade_hops: usize) -> Option<MultiHopArbitragePath> {
+        let trade_hops = cycle
+            .iter()
+            .filter(|&&e| self.edges[e].kind != EdgeKind::Transfer)
+            .count();
+        let total_weight: f64 = cycle.iter().map(|&e| self.edges[e].weight).sum();
+        if trade_hops == 0 || trade_hops > max_trade_hops || total_weight >= -WEIGHT_EPSILON {
+            return None;
+        }
+
+        // 从首选资产上的一笔交易出发；没有时从编号最小的节点出发，保证同一环的路径标识一致
+        let start = cycle
+            .iter()
+            .position(|&e| {
+                let edge = &self.edges[e];
+                edge.kind != EdgeKind::Transfer
+                    && PREFERRED_START_ASSETS.contains(&self.nodes[edge.from].1.as_str())
+            })
+            .or_else(|| (0..cycle.len()).min_by_key(|&i| self.edges[cycle[i]].from))
+            .unwrap_or(0);
+        let mut cycle = cycle.to_vec();
+        cycle.rotate_left(start);
+
+        let mut path = MultiHopArbitragePath::new(cycle.len());
+        path.asset_path.push(self.nodes[self.edges[cycle[0]].from].1.clone());
+        let mut gross_rate = 1.0;
+        for &e in &cycle {
+            let edge = &self.edges[e];
+            path.add_hop(edge.symbol.clone(), edge.exchange, edge.price);
+            path.asset_path.push(self.nodes[edge.to].1.clone());
+            path.total_fees_pct += edge.fee * 100.0;
+            gross_rate *= edge.rate() / (1.0 - edge.fee);
+        }
+        path.total_profit_pct = (gross_rate - 1.0) * 100.0;
+        path.net_profit_pct = ((-total_weight).exp() - 1.0) * 100.0;
+
+        if let Some((capacity, profit)) = self.optimal_capacity(&cycle) {
+            path.capacity = capacity;
+            path.expected_profit = profit;
+            path.total_slippage_pct = path.net_profit_pct - profit / capacity * 100.0;
+        }
+        path.generate_path_id();
+        Some(path)
+    }
+
+    fn cycle_output(&self, cycle: &[usize], amount: f64) -> Option<f64> {
+        cycle
+            .iter()
+            .try_fold(amount, |amount, &e| self.edges[e].output(amount))
+    }
+
+    /// 使环路净利润最大的投入量（起点资产计）及该投入下的利润；任一交易边缺少深度时返回 None
+    ///
+    /// 每一跳的产出是投入的分段线性凹函数，复合后利润仍是凹函数，先二分出深度允许的最大投入，再三分求最优。
+    fn optimal_capacity(&self, cycle: &[usize]) -> Option<(f64, f64)> {
+        if cycle
+            .iter()
+            .any(|&e| self.edges[e].kind != EdgeKind::Transfer && self.edges[e].levels.is_empty())
+        {
+            return None;
+        }
+
+        // 第一笔交易之前只有划转（兑换率不超过 1），其容量换算回起点是可投入量的上界
+        let mut upper = f64::INFINITY;
+        let mut carried = 1.0;
+        for &e in cycle {
+            let edge = &self.edges[e];
+            if edge.kind != EdgeKind::Transfer {
+                upper = edge.capacity() / carried;
+                break;
+            }
+            carried *= edge.rate();
+        }
+
+        let feasible = if self.cycle_output(cycle, upper).is_some() {
+            upper
+        } else {
+            let (mut lo, mut hi) = (0.0, upper);
+            for _ in 0..100 {
+                let mid = (lo + hi) / 2.0;
+                if self.cycle_output(cycle, mid).is_some() {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            lo
+        };
+
+        let profit = |amount: f64| self.cycle_output(cycle, amount).map(|out| out - amount).unwrap_or(f64::NEG_INFINITY);
+        let (mut lo, mut hi) = (0.0, feasible);
+        for _ in 0..100 {
+            let m1 = lo + (hi - lo) / 3.0;
+            let m2 = hi - (hi - lo) / 3.0;
+            if profit(m1) < profit(m2) {
+                lo = m1;
+            } else {
+                hi = m2;
+            }
+        }
+        let size = (lo + hi) / 2.0;
+        let best = profit(size);
+        (size > 0.0 && best > 0.0).then_some((size, best))
+    }
+
+    fn enqueue(&mut self, node: usize) {
+        if !self.queued[node] {
+            self.queued[node] = true;
+            self.queue.push_back(node);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.queue.clear();
+        for node in 0..self.nodes.len() {
+            self.dist[node] = 0.0;
+            self.pred[node] = None;
+            self.queued[node] = false;
+            self.enqueue(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(exchange: Exchange, symbol: &str, bid: (f64, f64), ask: (f64, f64)) -> StandardOrderBook {
+        StandardOrderBook::new_minimal(symbol, exchange, bid.0, ask.0, 0)
+            .with_depth(vec![bid], vec![ask])
+    }
+
+    fn triangle(graph: &mut ArbitrageGraph, eth_usdt_bid: f64) {
+        let exchange = Exchange::Binance;
+        graph.apply_book("BTCUSDT", &book(exchange, "BTCUSDT", (99.9, 10.0), (100.0, 10.0)), 0.0);
+        graph.apply_book("ETHBTC", &book(exchange, "ETHBTC", (0.0099, 1000.0), (0.01, 50.0)), 0.0);
+        graph.apply_book("ETHUSDT", &book(exchange, "ETHUSDT", (eth_usdt_bid, 1000.0), (1.2, 1000.0)), 0.0);
+    }
+
+    #[test]
+    fn test_finds_triangular_cycle_with_depth_capacity() {
+        let mut graph = ArbitrageGraph::new();
+        triangle(&mut graph, 1.1);
+
+        let cycles = graph.find_cycles(3, 10);
+        assert_eq!(cycles.len(), 1);
+        let path = &cycles[0];
+        assert_eq!(path.asset_path, vec!["USDT", "BTC", "ETH", "USDT"]);
+        assert_eq!(path.symbol_path, vec!["BTCUSDT", "ETHBTC", "ETHUSDT"]);
+        assert!((path.net_profit_pct - 10.0).abs() < 1e-6);
+        // ETHBTC 卖盘只有 50 ETH（0.5 BTC），即 50 USDT 的投入上限
+        assert!((path.capacity - 50.0).abs() < 1e-6);
+        assert!((path.expected_profit - 5.0).abs() < 1e-6);
+
+        // 订单簿未变化时不重复上报
+        assert!(graph.find_cycles(3, 10).is_empty());
+    }
+
+    #[test]
+    fn test_incremental_updates_open_and_close_cycles() {
+        let mut graph = ArbitrageGraph::new();
+        triangle(&mut graph, 0.99);
+        assert!(graph.find_cycles(3, 10).is_empty());
+
+        // 只更新一个订单簿即可发现新出现的环
+        graph.apply_book("ETHUSDT", &book(Exchange::Binance, "ETHUSDT", (1.05, 1000.0), (1.2, 1000.0)), 0.0);
+        let cycles = graph.find_cycles(3, 10);
+        assert_eq!(cycles.len(), 1);
+        assert!((cycles[0].net_profit_pct - 5.0).abs() < 1e-6);
+
+        // 价格回落后环消失，再次上涨又能重新发现
+        graph.apply_book("ETHUSDT", &book(Exchange::Binance, "ETHUSDT", (0.98, 1000.0), (1.2, 1000.0)), 0.0);
+        assert!(graph.find_cycles(3, 10).is_empty());
+        graph.apply_book("ETHUSDT", &book(Exchange::Binance, "ETHUSDT", (1.02, 1000.0), (1.2, 1000.0)), 0.0);
+        assert_eq!(graph.find_cycles(3, 10).len(), 1);
+
+        graph.remove_book(Exchange::Binance, "ETHBTC");
+        graph.apply_book("ETHUSDT", &book(Exchange::Binance, "ETHUSDT", (1.03, 1000.0), (1.2, 1000.0)), 0.0);
+        assert!(graph.find_cycles(3, 10).is_empty());
+    }
+
+    #[test]
+    fn test_fees_and_transfer_edges_across_exchanges() {
+        let mut graph = ArbitrageGraph::new();
+        graph.apply_book("BTCUSDT", &book(Exchange::Phemex, "BTCUSDT", (99.0, 1.0), (100.0, 1.0)), 0.001);
+        graph.apply_book("BTCUSDT", &book(Exchange::LBank, "BTCUSDT", (101.0, 1.0), (102.0, 1.0)), 0.001);
+
+        let cycles = graph.find_cycles(3, 10);
+        assert_eq!(cycles.len(), 1);
+        let path = &cycles[0];
+        assert_eq!(path.exchange_path, vec![Exchange::Phemex, Exchange::LBank, Exchange::LBank, Exchange::Phemex]);
+        assert_eq!(path.asset_path, vec!["USDT", "BTC", "BTC", "USDT", "USDT"]);
+        let expected = (101.0 / 100.0 * 0.999 * 0.999 - 1.0) * 100.0;
+        assert!((path.net_profit_pct - expected).abs() < 1e-9);
+        assert!((path.total_fees_pct - 0.2).abs() < 1e-9);
+
+        // 划转成本超过价差后不再构成套利
+        let mut costly = ArbitrageGraph::new();
+        costly.set_transfer_cost(0.005);
+        costly.apply_book("BTCUSDT", &book(Exchange::Phemex, "BTCUSDT", (99.0, 1.0), (100.0, 1.0)), 0.001);
+        costly.apply_book("BTCUSDT", &book(Exchange::LBank, "BTCUSDT", (101.0, 1.0), (102.0, 1.0)), 0.001);
+        assert!(costly.find_cycles(3, 10).is_empty());
+    }
+
+    #[test]
+    fn test_extract_trading_pair() {
+        assert_eq!(extract_trading_pair("ETHBTC"), Some(("ETH".to_string(), "BTC".to_string())));
+        assert_eq!(extract_trading_pair("BTCUSDT"), Some(("BTC".to_string(), "USDT".to_string())));
+        assert_eq!(extract_trading_pair("USDT"), None);
+    }
+}
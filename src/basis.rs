@@ -0,0 +1,381 @@
+//! 资金费率感知的期现基差扫描
+//! 将同一交易对的现货订单簿与永续合约订单簿配对（可跨交易所），按持有周期计算
+//! 基差收敛收益与预期资金费收入，扣除两条腿的开平仓手续费后换算为年化收益。
+//! 资金费率来自永续连接器推送的标记价格，保存在 `FundingStore` 中。
+
+use dashmap::DashMap;
+use log::warn;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::config::BasisConfig;
+use crate::core::{AppState, PERP_TAKER_FEE};
+use crate::exchange_types::{BasisArb, BasisDirection, Exchange, ExchangeFees, StandardOrderBook};
+use crate::symbol_mapper::contract_multipliers;
+use crate::types::market_data::MarkPriceUpdate;
+
+/// 未配置手续费的现货交易所使用的默认吃单费率
+pub const SPOT_TAKER_FEE: f64 = 0.001; // 0.1%
+
+const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+
+/// 某永续合约最新的标记价格与资金费率
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingSnapshot {
+    pub exchange: Exchange,
+    /// 规范化交易对（与 `BookStore` 的键一致）
+    pub symbol: String,
+    pub mark_price: f64,
+    pub index_price: f64,
+    /// 每个结算周期的资金费率（小数，正值表示多头付给空头）
+    pub funding_rate: f64,
+    /// 下次结算时间（毫秒），0 表示未知
+    pub next_funding_time: i64,
+    pub funding_interval_hours: f64,
+    /// 本地收到数据的时间（毫秒）
+    pub updated_at: i64,
+}
+
+/// 按 (交易所, 规范化交易对) 保存最新资金费率
+#[derive(Debug, Default)]
+pub struct FundingStore {
+    snapshots: DashMap<(Exchange, String), FundingSnapshot>,
+}
+
+impl FundingStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入资金费率快照，交易对按合约乘数表归入规范交易对
+    pub fn update(&self, mut snapshot: FundingSnapshot) {
+        snapshot.symbol = contract_multipliers().canonical_symbol(snapshot.exchange, &snapshot.symbol);
+        self.snapshots
+            .insert((snapshot.exchange, snapshot.symbol.clone()), snapshot);
+    }
+
+    /// 读取某交易所某交易对的最新资金费率
+    pub fn get(&self, exchange: Exchange, symbol: &str) -> Option<FundingSnapshot> {
+        let symbol = contract_multipliers().canonical_symbol(exchange, symbol);
+        self.snapshots.get(&(exchange, symbol)).map(|s| s.clone())
+    }
+
+    /// 应用 WebSocket 推送的标记价格更新
+    pub fn apply_mark_price(&self, exchange: Exchange, update: &MarkPriceUpdate, funding_interval_hours: f64) {
+        self.update(FundingSnapshot {
+            exchange,
+            symbol: update.symbol.clone(),
+            mark_price: update.mark_price,
+            index_price: update.index_price,
+            funding_rate: update.funding_rate,
+            next_funding_time: update.next_funding_time,
+            funding_interval_hours,
+            updated_at: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    /// 解析 REST premiumIndex 响应（单个对象或数组），返回写入的条数
+    pub fn apply_premium_index(&self, exchange: Exchange, data: &Value, funding_interval_hours: f64) -> usize {
+        let items = match data {
+            Value::Array(items) => items.iter().collect::<Vec<_>>(),
+            item => vec![item],
+        };
+        let updated_at = chrono::Utc::now().timestamp_millis();
+        let mut count = 0;
+        for item in items {
+            let field = |name: &str| {
+                item.get(name)
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| v.parse::<f64>().ok())
+            };
+            let (Some(symbol), Some(mark_price), Some(funding_rate)) = (
+                item.get("symbol").and_then(|s| s.as_str()),
+                field("markPrice"),
+                field("lastFundingRate"),
+            ) else {
+                continue;
+            };
+            self.update(FundingSnapshot {
+                exchange,
+                symbol: symbol.to_string(),
+                mark_price,
+                index_price: field("indexPrice").unwrap_or(0.0),
+                funding_rate,
+                next_funding_time: item.get("nextFundingTime").and_then(|t| t.as_i64()).unwrap_or(0),
+                funding_interval_hours,
+                updated_at,
+            });
+            count += 1;
+        }
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+/// 基差计算用的一条腿：已按合约乘数换算的最优买卖价及吃单费率
+#[derive(Debug, Clone, Copy)]
+pub struct BasisLeg {
+    pub exchange: Exchange,
+    pub bid: f64,
+    pub ask: f64,
+    pub taker_fee: f64,
+}
+
+impl BasisLeg {
+    /// 从订单簿构造，价格换算为每单位规范基础资产的价格
+    pub fn from_book(book: &StandardOrderBook, taker_fee: f64) -> Self {
+        let multipliers = contract_multipliers();
+        Self {
+            exchange: book.exchange,
+            bid: multipliers.normalize_price(book.exchange, &book.symbol, book.best_bid),
+            ask: multipliers.normalize_price(book.exchange, &book.symbol, book.best_ask),
+            taker_fee,
+        }
+    }
+}
+
+/// 持有周期内预计发生的资金费结算次数
+pub fn funding_events_within(funding: &FundingSnapshot, holding_hours: f64, now_ms: i64) -> u32 {
+    let interval = funding.funding_interval_hours;
+    if interval <= 0.0 || holding_hours <= 0.0 {
+        return 0;
+    }
+    if funding.next_funding_time <= 0 {
+        return (holding_hours / interval).floor() as u32;
+    }
+    // 已过期的下次结算时间按整周期向后推
+    let mut first = (funding.next_funding_time - now_ms) as f64 / 3_600_000.0;
+    if first < 0.0 {
+        first += ((-first) / interval).ceil() * interval;
+    }
+    if first > holding_hours {
+        return 0;
+    }
+    1 + ((holding_hours - first) / interval).floor() as u32
+}
+
+/// 评估一组现货/永续报价，收益达到入场阈值时返回机会
+///
+/// 正基差（永续高于现货）买现货、空永续；负基差且允许做空现货时反向。
+/// 收益 = 基差收敛到平仓阈值的部分 + 持有期内资金费 - 两条腿开平仓手续费。
+pub fn evaluate_basis(
+    symbol: &str,
+    spot: &BasisLeg,
+    perp: &BasisLeg,
+    funding: &FundingSnapshot,
+    config: &BasisConfig,
+    now_ms: i64,
+) -> Option<BasisArb> {
+    if spot.bid <= 0.0 || spot.ask <= 0.0 || perp.bid <= 0.0 || perp.ask <= 0.0 {
+        return None;
+    }
+    let max_age_ms = config.max_funding_age_secs as i64 * 1000;
+    if now_ms - funding.updated_at > max_age_ms {
+        return None;
+    }
+
+    let funding_events = funding_events_within(funding, config.holding_hours, now_ms);
+    let total_fees_pct = 2.0 * (spot.taker_fee + perp.taker_fee) * 100.0;
+    let annualize = HOURS_PER_YEAR / config.holding_hours;
+
+    let mut candidates = vec![(
+        BasisDirection::LongSpotShortPerp,
+        spot.ask,
+        perp.bid,
+        1.0,
+    )];
+    if config.allow_short_spot {
+        candidates.push((BasisDirection::ShortSpotLongPerp, spot.bid, perp.ask, -1.0));
+    }
+
+    candidates
+        .into_iter()
+        .map(|(direction, spot_price, perp_price, sign)| {
+            let basis_pct = (perp_price / spot_price - 1.0) * 100.0;
+            let convergence_pct = sign * basis_pct - config.exit_threshold_pct;
+            let expected_funding_pct = sign * funding.funding_rate * funding_events as f64 * 100.0;
+            let net_carry_pct = convergence_pct + expected_funding_pct - total_fees_pct;
+            BasisArb {
+                symbol: symbol.to_string(),
+                spot_exchange: spot.exchange,
+                perp_exchange: perp.exchange,
+                direction,
+                spot_price,
+                perp_price,
+                basis_pct,
+                annualized_basis_pct: convergence_pct * annualize,
+                funding_rate: funding.funding_rate,
+                funding_events,
+                expected_funding_pct,
+                holding_hours: config.holding_hours,
+                total_fees_pct,
+                net_carry_pct,
+                annualized_net_pct: net_carry_pct * annualize,
+                entry_threshold_pct: config.entry_threshold_pct,
+                exit_threshold_pct: config.exit_threshold_pct,
+                timestamp: now_ms,
+            }
+        })
+        .filter(|arb| arb.annualized_net_pct >= config.entry_threshold_pct)
+        .max_by(|a, b| a.annualized_net_pct.total_cmp(&b.annualized_net_pct))
+}
+
+fn taker_fee(exchange_fees: &HashMap<Exchange, ExchangeFees>, exchange: Exchange) -> f64 {
+    exchange_fees
+        .get(&exchange)
+        .map(|fees| fees.taker_fee)
+        .unwrap_or(if exchange.is_spot() { SPOT_TAKER_FEE } else { PERP_TAKER_FEE })
+}
+
+/// 扫描订单簿存储中所有同时有现货与永续报价的交易对，按年化净收益降序返回
+pub fn scan_basis_opportunities(
+    app_state: &AppState,
+    exchange_fees: &HashMap<Exchange, ExchangeFees>,
+    config: &BasisConfig,
+) -> Vec<BasisArb> {
+    if config.holding_hours <= 0.0 {
+        warn!("基差扫描的持有周期必须为正数: {}", config.holding_hours);
+        return Vec::new();
+    }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let mut opportunities = Vec::new();
+
+    for symbol in app_state.book_store.cross_exchange_symbols() {
+        let (spot_books, perp_books): (Vec<_>, Vec<_>) = app_state
+            .book_store
+            .books_for(&symbol)
+            .into_iter()
+            .partition(|book| book.exchange.is_spot());
+        if spot_books.is_empty() || perp_books.is_empty() {
+            continue;
+        }
+
+        for perp_book in &perp_books {
+            let Some(funding) = app_state.funding_store.get(perp_book.exchange, &perp_book.symbol) else {
+                continue;
+            };
+            let perp = BasisLeg::from_book(perp_book, taker_fee(exchange_fees, perp_book.exchange));
+            for spot_book in &spot_books {
+                let spot = BasisLeg::from_book(spot_book, taker_fee(exchange_fees, spot_book.exchange));
+                if let Some(arb) = evaluate_basis(&symbol, &spot, &perp, &funding, config, now_ms) {
+                    opportunities.push(arb);
+                }
+            }
+        }
+    }
+
+    opportunities.sort_by(|a, b| b.annualized_net_pct.total_cmp(&a.annualized_net_pct));
+    opportunities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: i64 = 1_700_000_000_000;
+    const HOUR_MS: i64 = 3_600_000;
+
+    fn funding(rate: f64, next_in_hours: i64) -> FundingSnapshot {
+        FundingSnapshot {
+            exchange: Exchange::BinanceFutures,
+            symbol: "BTCUSDT".to_string(),
+            mark_price: 100.0,
+            index_price: 100.0,
+            funding_rate: rate,
+            next_funding_time: NOW + next_in_hours * HOUR_MS,
+            funding_interval_hours: 8.0,
+            updated_at: NOW,
+        }
+    }
+
+    fn leg(exchange: Exchange, bid: f64, ask: f64, taker_fee: f64) -> BasisLeg {
+        BasisLeg { exchange, bid, ask, taker_fee }
+    }
+
+    fn config() -> BasisConfig {
+        BasisConfig {
+            holding_hours: 24.0,
+            entry_threshold_pct: 10.0,
+            exit_threshold_pct: 0.0,
+            ..BasisConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_counts_funding_events_from_next_settlement() {
+        // 2 小时后首次结算，之后每 8 小时一次：2h、10h、18h
+        assert_eq!(funding_events_within(&funding(0.0001, 2), 24.0, NOW), 3);
+        assert_eq!(funding_events_within(&funding(0.0001, 30), 24.0, NOW), 0);
+        // 过期的结算时间按周期向后推：-1h -> 7h、15h、23h
+        assert_eq!(funding_events_within(&funding(0.0001, -1), 24.0, NOW), 3);
+        let mut unknown = funding(0.0001, 0);
+        unknown.next_funding_time = 0;
+        assert_eq!(funding_events_within(&unknown, 24.0, NOW), 3);
+    }
+
+    #[test]
+    fn test_long_spot_short_perp_includes_funding_and_fees() {
+        let spot = leg(Exchange::Binance, 99.9, 100.0, 0.001);
+        let perp = leg(Exchange::BinanceFutures, 100.5, 100.6, 0.0005);
+        let arb = evaluate_basis("BTCUSDT", &spot, &perp, &funding(0.0003, 2), &config(), NOW).unwrap();
+
+        assert_eq!(arb.direction, BasisDirection::LongSpotShortPerp);
+        assert!((arb.basis_pct - 0.5).abs() < 1e-9);
+        assert_eq!(arb.funding_events, 3);
+        assert!((arb.expected_funding_pct - 0.09).abs() < 1e-9);
+        assert!((arb.total_fees_pct - 0.3).abs() < 1e-9);
+        assert!((arb.net_carry_pct - 0.29).abs() < 1e-9);
+        assert!((arb.annualized_net_pct - 0.29 * 365.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_negative_basis_requires_short_spot() {
+        let spot = leg(Exchange::Binance, 100.5, 100.6, 0.0);
+        let perp = leg(Exchange::BybitFutures, 99.9, 100.0, 0.0);
+        let negative_funding = funding(-0.0005, 2);
+
+        assert!(evaluate_basis("BTCUSDT", &spot, &perp, &negative_funding, &config(), NOW).is_none());
+
+        let short_config = BasisConfig { allow_short_spot: true, ..config() };
+        let arb = evaluate_basis("BTCUSDT", &spot, &perp, &negative_funding, &short_config, NOW).unwrap();
+        assert_eq!(arb.direction, BasisDirection::ShortSpotLongPerp);
+        assert!(arb.basis_pct < 0.0);
+        assert!(arb.expected_funding_pct > 0.0);
+    }
+
+    #[test]
+    fn test_rejects_stale_funding_and_low_returns() {
+        let spot = leg(Exchange::Binance, 99.9, 100.0, 0.001);
+        let perp = leg(Exchange::BinanceFutures, 100.5, 100.6, 0.0005);
+        let mut stale = funding(0.0003, 2);
+        stale.updated_at = NOW - 121_000;
+        assert!(evaluate_basis("BTCUSDT", &spot, &perp, &stale, &config(), NOW).is_none());
+
+        let strict = BasisConfig { entry_threshold_pct: 200.0, ..config() };
+        assert!(evaluate_basis("BTCUSDT", &spot, &perp, &funding(0.0003, 2), &strict, NOW).is_none());
+    }
+
+    #[test]
+    fn test_premium_index_seeds_store() {
+        let store = FundingStore::new();
+        let data = json!([
+            {"symbol": "ETHUSDT", "markPrice": "2000.5", "indexPrice": "2000.1",
+             "lastFundingRate": "0.00010000", "nextFundingTime": 1700000000000_i64},
+            {"symbol": "BROKEN"}
+        ]);
+
+        assert_eq!(store.apply_premium_index(Exchange::BinanceFutures, &data, 8.0), 1);
+        let snapshot = store.get(Exchange::BinanceFutures, "ETH_USDT").unwrap();
+        assert_eq!(snapshot.mark_price, 2000.5);
+        assert_eq!(snapshot.funding_rate, 0.0001);
+        assert_eq!(snapshot.next_funding_time, NOW);
+    }
+}
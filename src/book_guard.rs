@@ -0,0 +1,296 @@
+//! 套利订单簿的新鲜度与时钟偏差检查
+//! 冻结的行情会与正常行情形成虚假价差，因此两边订单簿在参与套利前需满足：
+//! 各自的年龄不超过该交易所的 `max_book_age_ms`，两本订单簿的时间差不超过 `max_book_skew_ms`。
+//! 拒绝按原因与交易所计数；某交易所连续因过期被拒达到阈值时，由调用方向其行情连接发出重连信号。
+
+use dashmap::DashMap;
+use serde::Serialize;
+use std::fmt;
+
+use crate::config::ArbitrageConfig;
+use crate::exchange_types::{CrossExchangeArb, Exchange};
+
+/// 套利机会被拒绝的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum RejectReason {
+    /// 净利润低于阈值
+    BelowThreshold,
+    /// 价格为零或负数
+    InvalidPrice,
+    /// 订单簿超过最大年龄
+    BookStale,
+    /// 两边订单簿时间差过大
+    BookSkew,
+    /// 归一化后价格仍存在固定比例，疑似合约乘数缺失
+    ScalingAnomaly,
+    /// 利润高得不合理
+    ProfitTooHigh,
+    /// 同一交易对短时间内机会过多
+    SuspiciousSymbol,
+}
+
+impl RejectReason {
+    /// 是否因订单簿时效被拒
+    pub fn is_freshness(&self) -> bool {
+        matches!(self, RejectReason::BookStale | RejectReason::BookSkew)
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RejectReason::BelowThreshold => write!(f, "BELOW_THRESHOLD"),
+            RejectReason::InvalidPrice => write!(f, "INVALID_PRICE"),
+            RejectReason::BookStale => write!(f, "BOOK_STALE"),
+            RejectReason::BookSkew => write!(f, "BOOK_SKEW"),
+            RejectReason::ScalingAnomaly => write!(f, "SCALING_ANOMALY"),
+            RejectReason::ProfitTooHigh => write!(f, "PROFIT_TOO_HIGH"),
+            RejectReason::SuspiciousSymbol => write!(f, "SUSPICIOUS_SYMBOL"),
+        }
+    }
+}
+
+/// 一次拒绝：原因与被归责的交易所
+/// 时效类拒绝只归责于过期或落后的一方，其余原因归责于两边
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejection {
+    pub reason: RejectReason,
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Rejection {
+    pub fn new(reason: RejectReason, exchanges: Vec<Exchange>) -> Self {
+        Self { reason, exchanges }
+    }
+
+    /// 归责于买卖两边
+    pub fn pair(reason: RejectReason, buy_exchange: Exchange, sell_exchange: Exchange) -> Self {
+        Self::new(reason, vec![buy_exchange, sell_exchange])
+    }
+}
+
+/// 检查两边订单簿的年龄与时间差
+///
+/// 时间戳为毫秒，小于等于 0 表示来源未提供时间，跳过对应检查。
+/// 交易所时钟快于本地时年龄按 0 计算；上限配置为 0 时不做该项检查。
+pub fn check_book_freshness(
+    config: &ArbitrageConfig,
+    buy_exchange: Exchange,
+    buy_book_ts: i64,
+    sell_exchange: Exchange,
+    sell_book_ts: i64,
+    now_ms: i64,
+) -> Result<(), Rejection> {
+    let stale: Vec<Exchange> = [(buy_exchange, buy_book_ts), (sell_exchange, sell_book_ts)]
+        .into_iter()
+        .filter(|&(exchange, ts)| {
+            let max_age = config.max_book_age_ms_for(exchange);
+            ts > 0 && max_age > 0 && (now_ms - ts).max(0) as u64 > max_age
+        })
+        .map(|(exchange, _)| exchange)
+        .collect();
+    if !stale.is_empty() {
+        return Err(Rejection::new(RejectReason::BookStale, stale));
+    }
+
+    if buy_book_ts > 0 && sell_book_ts > 0 && config.max_book_skew_ms > 0 {
+        let skew = buy_book_ts.abs_diff(sell_book_ts);
+        if skew > config.max_book_skew_ms {
+            // 归责于时间更早、即落后的一方
+            let lagging = if buy_book_ts < sell_book_ts { buy_exchange } else { sell_exchange };
+            return Err(Rejection::new(RejectReason::BookSkew, vec![lagging]));
+        }
+    }
+
+    Ok(())
+}
+
+/// 评估的前置检查：价格有效、两边订单簿时效、净利润达到阈值，按此顺序进行
+///
+/// 时效检查先于阈值检查，冻结但价差不大的行情同样按过期拒绝并计入连续过期次数。
+pub fn check_evaluation(
+    config: &ArbitrageConfig,
+    opportunity: &CrossExchangeArb,
+    buy_book_ts: i64,
+    sell_book_ts: i64,
+    now_ms: i64,
+) -> Result<(), Rejection> {
+    let (buy_exchange, sell_exchange) = (opportunity.buy_exchange, opportunity.sell_exchange);
+    if opportunity.buy_price <= 0.0 || opportunity.sell_price <= 0.0 {
+        return Err(Rejection::pair(RejectReason::InvalidPrice, buy_exchange, sell_exchange));
+    }
+    check_book_freshness(config, buy_exchange, buy_book_ts, sell_exchange, sell_book_ts, now_ms)?;
+    if opportunity.net_profit_pct < config.min_profit_threshold_pct {
+        return Err(Rejection::pair(RejectReason::BelowThreshold, buy_exchange, sell_exchange));
+    }
+    Ok(())
+}
+
+/// 按原因与交易所统计的拒绝次数，以及每个交易所连续因过期被拒的次数
+#[derive(Debug, Default)]
+pub struct RejectionStats {
+    counts: DashMap<(RejectReason, Exchange), u64>,
+    stale_streaks: DashMap<Exchange, u32>,
+}
+
+impl RejectionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次评估结果，返回连续过期次数达到 `reconnect_after` 的交易所（其计数随即清零）
+    ///
+    /// 通过了时效检查的一侧（包括低于阈值的评估）清零连续计数；价格无效的评估未做时效检查，不影响连续计数。
+    /// `reconnect_after` 为 0 时从不返回交易所。
+    pub fn record(
+        &self,
+        buy_exchange: Exchange,
+        sell_exchange: Exchange,
+        outcome: Result<(), &Rejection>,
+        reconnect_after: u32,
+    ) -> Vec<Exchange> {
+        let rejection = outcome.err();
+        if let Some(rejection) = rejection {
+            for &exchange in &rejection.exchanges {
+                *self.counts.entry((rejection.reason, exchange)).or_insert(0) += 1;
+            }
+            if rejection.reason == RejectReason::InvalidPrice {
+                return Vec::new();
+            }
+        }
+
+        let mut reconnect = Vec::new();
+        for exchange in [buy_exchange, sell_exchange] {
+            match rejection {
+                Some(r) if r.reason.is_freshness() && r.exchanges.contains(&exchange) => {
+                    if r.reason != RejectReason::BookStale {
+                        continue;
+                    }
+                    let mut streak = self.stale_streaks.entry(exchange).or_insert(0);
+                    *streak += 1;
+                    if reconnect_after > 0 && *streak >= reconnect_after {
+                        *streak = 0;
+                        reconnect.push(exchange);
+                    }
+                }
+                _ => {
+                    self.stale_streaks.remove(&exchange);
+                }
+            }
+        }
+        reconnect
+    }
+
+    pub fn count(&self, reason: RejectReason, exchange: Exchange) -> u64 {
+        self.counts.get(&(reason, exchange)).map(|c| *c).unwrap_or(0)
+    }
+
+    /// 当前连续因过期被拒的次数
+    pub fn stale_streak(&self, exchange: Exchange) -> u32 {
+        self.stale_streaks.get(&exchange).map(|s| *s).unwrap_or(0)
+    }
+
+    /// 全部计数，按次数从高到低排列
+    pub fn snapshot(&self) -> Vec<(RejectReason, Exchange, u64)> {
+        let mut counts: Vec<(RejectReason, Exchange, u64)> = self
+            .counts
+            .iter()
+            .map(|entry| (entry.key().0, entry.key().1, *entry.value()))
+            .collect();
+        counts.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::get_config;
+    use std::collections::HashMap;
+
+    fn config() -> ArbitrageConfig {
+        let mut config = get_config().arbitrage.clone();
+        config.max_book_age_ms = 2_000;
+        config.max_book_age_ms_by_exchange = HashMap::from([("LBANK".to_string(), 5_000)]);
+        config.max_book_skew_ms = 1_000;
+        config
+    }
+
+    #[test]
+    fn test_rejects_stale_and_skewed_books() {
+        let config = config();
+        let now = 100_000;
+
+        assert!(check_book_freshness(&config, Exchange::Phemex, now - 500, Exchange::XtCom, now - 900, now).is_ok());
+
+        let stale = check_book_freshness(&config, Exchange::Phemex, now - 3_000, Exchange::XtCom, now - 100, now).unwrap_err();
+        assert_eq!(stale, Rejection::new(RejectReason::BookStale, vec![Exchange::Phemex]));
+
+        // LBANK 单独放宽到 5 秒，只剩时间差超限，归责于落后的一方
+        let skewed = check_book_freshness(&config, Exchange::LBank, now - 3_000, Exchange::XtCom, now - 100, now).unwrap_err();
+        assert_eq!(skewed, Rejection::new(RejectReason::BookSkew, vec![Exchange::LBank]));
+
+        // 未知时间戳与本地时钟落后于交易所的情况不判为过期
+        assert!(check_book_freshness(&config, Exchange::Phemex, 0, Exchange::XtCom, now + 500, now).is_ok());
+    }
+
+    #[test]
+    fn test_counts_rejections_and_signals_after_stale_streak() {
+        let stats = RejectionStats::new();
+        let stale = Rejection::new(RejectReason::BookStale, vec![Exchange::Phemex]);
+
+        assert!(stats.record(Exchange::Phemex, Exchange::XtCom, Err(&stale), 3).is_empty());
+        assert!(stats.record(Exchange::Phemex, Exchange::XtCom, Err(&stale), 3).is_empty());
+        // 价格无效的评估没有做时效检查，不打断连续计数
+        let invalid = Rejection::pair(RejectReason::InvalidPrice, Exchange::Phemex, Exchange::XtCom);
+        assert!(stats.record(Exchange::Phemex, Exchange::XtCom, Err(&invalid), 3).is_empty());
+        assert_eq!(stats.record(Exchange::Phemex, Exchange::XtCom, Err(&stale), 3), vec![Exchange::Phemex]);
+        assert_eq!(stats.stale_streak(Exchange::Phemex), 0);
+
+        stats.record(Exchange::Phemex, Exchange::XtCom, Err(&stale), 3);
+        assert_eq!(stats.stale_streak(Exchange::Phemex), 1);
+        stats.record(Exchange::Phemex, Exchange::XtCom, Ok(()), 3);
+        assert_eq!(stats.stale_streak(Exchange::Phemex), 0);
+
+        // 低于阈值说明两边都通过了时效检查
+        stats.record(Exchange::Phemex, Exchange::XtCom, Err(&stale), 3);
+        let below = Rejection::pair(RejectReason::BelowThreshold, Exchange::Phemex, Exchange::XtCom);
+        stats.record(Exchange::Phemex, Exchange::XtCom, Err(&below), 3);
+        assert_eq!(stats.stale_streak(Exchange::Phemex), 0);
+
+        assert_eq!(stats.count(RejectReason::BookStale, Exchange::Phemex), 5);
+        assert_eq!(stats.count(RejectReason::BookStale, Exchange::XtCom), 0);
+        assert_eq!(stats.count(RejectReason::BelowThreshold, Exchange::XtCom), 1);
+        assert_eq!(stats.snapshot()[0], (RejectReason::BookStale, Exchange::Phemex, 5));
+    }
+
+    #[test]
+    fn test_quiet_stale_book_counts_toward_streak() {
+        let config = config();
+        let now = 100_000;
+        let opportunity = CrossExchangeArb {
+            symbol: "BTCUSDT".to_string(),
+            buy_exchange: Exchange::Phemex,
+            sell_exchange: Exchange::XtCom,
+            buy_price: 64000.0,
+            sell_price: 64001.0,
+            timestamp: now,
+            profit_pct: 0.0016,
+            net_profit_pct: config.min_profit_threshold_pct - 0.1,
+            total_fees_pct: 0.1,
+            optimal_size: 0.0,
+            expected_pnl: 0.0,
+            profit_curve: Vec::new(),
+        };
+
+        // 价差低于阈值，但冻结的一侧仍按过期拒绝
+        let rejection = check_evaluation(&config, &opportunity, now - 3_000, now - 100, now).unwrap_err();
+        assert_eq!(rejection, Rejection::new(RejectReason::BookStale, vec![Exchange::Phemex]));
+        let stats = RejectionStats::new();
+        stats.record(Exchange::Phemex, Exchange::XtCom, Err(&rejection), 3);
+        assert_eq!(stats.stale_streak(Exchange::Phemex), 1);
+
+        let fresh = check_evaluation(&config, &opportunity, now - 100, now - 100, now).unwrap_err();
+        assert_eq!(fresh.reason, RejectReason::BelowThreshold);
+    }
+}
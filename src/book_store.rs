@@ -0,0 +1,272 @@
+//! 合并订单簿存储
+//! 连接器把订单簿写入这里，每个 (交易所, 交易对) 只保留最新的一份；
+//! 扫描器通过 `BookWatcher` 获取自上次扫描以来发生变化的交易对，只重新评估这些交易对。
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use tokio::sync::Notify;
+
+use crate::exchange_types::{Exchange, StandardOrderBook};
+use crate::symbol_mapper::contract_multipliers;
+
+/// 规范化交易对：去掉分隔符并转为大写，与扫描器使用的形式一致
+pub fn normalize_book_symbol(symbol: &str) -> String {
+    symbol.replace(['-', '_', '/'], "").to_uppercase()
+}
+
+/// 按 (交易所, 交易对) 保存最新订单簿
+///
+/// 外层按规范化交易对分组，方便扫描器一次取出同一交易对在各交易所的订单簿。
+#[derive(Default)]
+pub struct BookStore {
+    books: DashMap<String, HashMap<Exchange, StandardOrderBook>>,
+    watchers: RwLock<Vec<Weak<WatcherState>>>,
+}
+
+impl fmt::Debug for BookStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BookStore")
+            .field("symbols", &self.books.len())
+            .field("watchers", &self.watcher_count())
+            .finish()
+    }
+}
+
+impl BookStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入订单簿，覆盖同一 (交易所, 交易对) 的旧值并通知所有观察者
+    ///
+    /// 按批量报价的品种（如 1000PEPEUSDT）归入规范交易对（PEPEUSDT）下，价格保持原样，
+    /// 扫描器比价时再按合约乘数换算。
+    pub fn update(&self, orderbook: StandardOrderBook) {
+        let symbol = contract_multipliers().canonical_symbol(orderbook.exchange, &orderbook.symbol);
+        self.books
+            .entry(symbol.clone())
+            .or_default()
+            .insert(orderbook.exchange, orderbook);
+        self.mark_dirty(&symbol);
+    }
+
+    /// 删除某交易所的订单簿（例如连接断开后数据失效）
+    pub fn remove(&self, exchange: Exchange, symbol: &str) -> Option<StandardOrderBook> {
+        let symbol = contract_multipliers().canonical_symbol(exchange, symbol);
+        let removed = self.books.get_mut(&symbol)?.remove(&exchange);
+        self.books.remove_if(&symbol, |_, books| books.is_empty());
+        if removed.is_some() {
+            self.mark_dirty(&symbol);
+        }
+        removed
+    }
+
+    /// 读取某交易所某交易对的最新订单簿
+    pub fn get(&self, exchange: Exchange, symbol: &str) -> Option<StandardOrderBook> {
+        self.books
+            .get(&contract_multipliers().canonical_symbol(exchange, symbol))
+            .and_then(|books| books.get(&exchange).cloned())
+    }
+
+    /// 读取某交易对在所有交易所的最新订单簿，按交易所名称排序
+    pub fn books_for(&self, symbol: &str) -> Vec<StandardOrderBook> {
+        let mut books: Vec<StandardOrderBook> = self
+            .books
+            .get(&normalize_book_symbol(symbol))
+            .map(|books| books.values().cloned().collect())
+            .unwrap_or_default();
+        books.sort_by_key(|book| book.exchange.to_string());
+        books
+    }
+
+    /// 所有已保存订单簿的交易对
+    pub fn symbols(&self) -> HashSet<String> {
+        self.books.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// 至少在两个交易所有订单簿的交易对
+    pub fn cross_exchange_symbols(&self) -> HashSet<String> {
+        self.books
+            .iter()
+            .filter(|entry| entry.value().len() >= 2)
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// 已保存订单簿的交易对数量
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// 注册一个观察者，之后的每次更新都会记入它的变更集合
+    pub fn watch(&self) -> BookWatcher {
+        let state = Arc::new(WatcherState::default());
+        let mut watchers = self.watchers.write().unwrap_or_else(|e| e.into_inner());
+        watchers.retain(|watcher| watcher.strong_count() > 0);
+        watchers.push(Arc::downgrade(&state));
+        BookWatcher { state }
+    }
+
+    fn watcher_count(&self) -> usize {
+        self.watchers
+            .read()
+            .map(|watchers| watchers.iter().filter(|watcher| watcher.strong_count() > 0).count())
+            .unwrap_or(0)
+    }
+
+    fn mark_dirty(&self, symbol: &str) {
+        let watchers = self.watchers.read().unwrap_or_else(|e| e.into_inner());
+        for watcher in watchers.iter().filter_map(Weak::upgrade) {
+            watcher.mark(symbol);
+        }
+    }
+}
+
+#[derive(Default)]
+struct WatcherState {
+    dirty: Mutex<HashSet<String>>,
+    notify: Notify,
+}
+
+impl WatcherState {
+    fn mark(&self, symbol: &str) {
+        let mut dirty = self.dirty.lock().unwrap_or_else(|e| e.into_inner());
+        if dirty.insert(symbol.to_string()) && dirty.len() == 1 {
+            // 只在集合由空变为非空时唤醒，合并期间的后续更新不重复通知
+            self.notify.notify_one();
+        }
+    }
+}
+
+/// 订单簿变更观察者
+///
+/// 每个扫描器持有自己的观察者，变更集合互不影响；观察者被丢弃后自动从存储中注销。
+pub struct BookWatcher {
+    state: Arc<WatcherState>,
+}
+
+impl BookWatcher {
+    /// 取出自上次调用以来发生变化的交易对（规范化形式）
+    pub fn take_changed(&self) -> HashSet<String> {
+        let mut dirty = self.state.dirty.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *dirty)
+    }
+
+    /// 是否有尚未取出的变更
+    pub fn has_changes(&self) -> bool {
+        !self.state.dirty.lock().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    /// 等待下一次变更；调用前已有未取出的变更时立即返回
+    pub async fn changed(&self) {
+        if self.has_changes() {
+            return;
+        }
+        self.state.notify.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::common::instruments::InstrumentSpec;
+    use crate::symbol_mapper::contract_multipliers_mut;
+    use rust_decimal::Decimal;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn book(exchange: Exchange, symbol: &str, best_bid: f64) -> StandardOrderBook {
+        StandardOrderBook::new_minimal(symbol, exchange, best_bid, best_bid + 1.0, 0)
+    }
+
+    #[test]
+    fn test_keeps_latest_book_per_exchange_and_symbol() {
+        let store = BookStore::new();
+        store.update(book(Exchange::Phemex, "BTC_USDT", 100.0));
+        store.update(book(Exchange::Phemex, "BTCUSDT", 101.0));
+        store.update(book(Exchange::LBank, "btc_usdt", 99.0));
+        store.update(book(Exchange::LBank, "ETH_USDT", 10.0));
+
+        assert_eq!(store.get(Exchange::Phemex, "BTC-USDT").unwrap().best_bid, 101.0);
+        let books = store.books_for("BTCUSDT");
+        assert_eq!(books.len(), 2);
+        assert_eq!(books[0].exchange, Exchange::LBank);
+        assert_eq!(books[1].exchange, Exchange::Phemex);
+        assert_eq!(store.cross_exchange_symbols(), HashSet::from(["BTCUSDT".to_string()]));
+
+        assert!(store.remove(Exchange::LBank, "ETHUSDT").is_some());
+        assert!(store.books_for("ETHUSDT").is_empty());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_bundled_listing_joins_canonical_symbol() {
+        // 从交易规则推导乘数：1000FLOKI 按 1000 个 FLOKI 报价
+        let mut spec = InstrumentSpec::new(Exchange::BinanceFutures, "1000FLOKIUSDT", Decimal::new(1, 5), Decimal::ONE);
+        spec.base_asset = "1000FLOKI".to_string();
+        spec.quote_asset = "USDT".to_string();
+        assert_eq!(contract_multipliers_mut().merge_instruments([&spec]), 1);
+
+        let store = BookStore::new();
+        store.update(book(Exchange::BinanceFutures, "1000FLOKIUSDT", 0.15));
+        store.update(book(Exchange::Phemex, "FLOKI_USDT", 0.00015));
+
+        let books = store.books_for("FLOKIUSDT");
+        assert_eq!(books.len(), 2);
+        assert_eq!(store.get(Exchange::BinanceFutures, "1000FLOKIUSDT").unwrap().best_bid, 0.15);
+
+        let multipliers = contract_multipliers();
+        let normalized: Vec<f64> = books
+            .iter()
+            .map(|book| multipliers.normalize_price(book.exchange, &book.symbol, book.best_bid))
+            .collect();
+        assert!((normalized[0] - normalized[1]).abs() < 1e-12);
+        assert_eq!(multipliers.multiplier(Exchange::Phemex, "FLOKIUSDT"), 1.0);
+    }
+
+    #[test]
+    fn test_each_watcher_tracks_its_own_changes() {
+        let store = BookStore::new();
+        let first = store.watch();
+        let second = store.watch();
+
+        store.update(book(Exchange::Phemex, "BTCUSDT", 100.0));
+        store.update(book(Exchange::LBank, "BTCUSDT", 100.0));
+        store.update(book(Exchange::Phemex, "ETHUSDT", 10.0));
+
+        assert_eq!(first.take_changed(), HashSet::from(["BTCUSDT".to_string(), "ETHUSDT".to_string()]));
+        assert!(first.take_changed().is_empty());
+        assert_eq!(second.take_changed().len(), 2);
+
+        drop(second);
+        store.update(book(Exchange::Phemex, "BTCUSDT", 101.0));
+        assert_eq!(store.watcher_count(), 1);
+        assert_eq!(first.take_changed(), HashSet::from(["BTCUSDT".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_on_update() {
+        let store = Arc::new(BookStore::new());
+        let watcher = store.watch();
+
+        let writer = store.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            writer.update(book(Exchange::Phemex, "BTCUSDT", 100.0));
+        });
+
+        timeout(Duration::from_secs(1), watcher.changed()).await.expect("应该收到变更通知");
+        assert_eq!(watcher.take_changed().len(), 1);
+
+        // 未取出的变更让changed立即返回
+        store.update(book(Exchange::LBank, "BTCUSDT", 100.0));
+        timeout(Duration::from_millis(50), watcher.changed()).await.expect("已有变更时应立即返回");
+    }
+}
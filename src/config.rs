@@ -8,7 +8,7 @@ use std::path::Path;
 use std::sync::OnceLock;
 use crate::exchange_types::Exchange;
 use once_cell::sync::Lazy;
-use crate::types::config::AdvancedConnectorConfig;
+use crate::types::config::{AdvancedConnectorConfig, ConnectorConfig, DataChannelConfig};
 
 /// Global configuration singleton
 pub static CONFIG: OnceLock<Config> = OnceLock::new();
@@ -58,6 +58,18 @@ pub struct Config {
     pub features: FeatureFlags,
     pub websocket_optimization: WebSocketOptimizationConfig,
     pub advanced_connectors: HashMap<String, AdvancedConnectorConfig>,
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    #[serde(default)]
+    pub clock_sync: ClockSyncConfig,
+    #[serde(default)]
+    pub instruments: InstrumentsConfig,
+    #[serde(default)]
+    pub contract_multipliers: Vec<ContractMultiplierConfig>,
+    #[serde(default)]
+    pub basis: BasisConfig,
+    #[serde(default)]
+    pub execution: ExecutionConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -88,6 +100,64 @@ pub struct ArbitrageConfig {
     pub default_slippage_pct: f64,
     pub large_order_slippage_pct: f64,
     pub max_path_length: usize,
+    /// Minimum notional (quote currency) for each leg of a depth-sized opportunity
+    #[serde(default = "ArbitrageConfig::default_min_trade_notional_usd")]
+    pub min_trade_notional_usd: f64,
+    /// How often account balances are re-queried between user stream balance updates (0 queries once)
+    #[serde(default = "ArbitrageConfig::default_balance_refresh_secs")]
+    pub balance_refresh_secs: u64,
+    /// Cost of moving an asset between exchanges, applied to the transfer edges of the multi-hop graph
+    #[serde(default)]
+    pub transfer_cost_pct: f64,
+    /// An open opportunity not re-observed for this long is closed as stale
+    #[serde(default = "ArbitrageConfig::default_opportunity_stale_ms")]
+    pub opportunity_stale_ms: u64,
+    /// Books older than this are not used for arbitrage (0 disables the check)
+    #[serde(default = "ArbitrageConfig::default_max_book_age_ms")]
+    pub max_book_age_ms: u64,
+    /// Per-exchange overrides of `max_book_age_ms`, keyed like `[exchanges]` (e.g. "LBANK")
+    #[serde(default)]
+    pub max_book_age_ms_by_exchange: HashMap<String, u64>,
+    /// Maximum difference between the timestamps of the two books of an opportunity (0 disables the check)
+    #[serde(default = "ArbitrageConfig::default_max_book_skew_ms")]
+    pub max_book_skew_ms: u64,
+    /// Consecutive staleness rejections of one exchange before its market data connection is told to reconnect
+    #[serde(default = "ArbitrageConfig::default_stale_rejections_before_reconnect")]
+    pub stale_rejections_before_reconnect: u32,
+}
+
+impl ArbitrageConfig {
+    fn default_min_trade_notional_usd() -> f64 {
+        10.0
+    }
+
+    fn default_balance_refresh_secs() -> u64 {
+        30
+    }
+
+    fn default_opportunity_stale_ms() -> u64 {
+        5000
+    }
+
+    fn default_max_book_age_ms() -> u64 {
+        2000
+    }
+
+    fn default_max_book_skew_ms() -> u64 {
+        1000
+    }
+
+    fn default_stale_rejections_before_reconnect() -> u32 {
+        20
+    }
+
+    /// Maximum book age for an exchange, falling back to `max_book_age_ms`
+    pub fn max_book_age_ms_for(&self, exchange: Exchange) -> u64 {
+        self.max_book_age_ms_by_exchange
+            .get(&exchange.to_string())
+            .copied()
+            .unwrap_or(self.max_book_age_ms)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -105,6 +175,22 @@ pub struct ExchangeConfig {
     pub supported_symbols: Option<Vec<String>>,
 }
 
+impl ExchangeConfig {
+    /// Build the connector-level configuration (millisecond timings) from this exchange block.
+    pub fn to_connector_config(&self) -> ConnectorConfig {
+        ConnectorConfig {
+            api_key: self.api_key.clone(),
+            secret_key: self.api_secret.clone(),
+            websocket_url: Some(self.websocket_url.clone()),
+            rest_api_url: self.api_url.clone(),
+            max_reconnect_attempts: self.max_retries as u32,
+            ping_interval: self.ping_interval_secs * 1000,
+            request_timeout: self.connection_timeout_secs * 1000,
+            ..ConnectorConfig::default()
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TokenConfig {
     pub price_scale: i32,
@@ -136,6 +222,180 @@ pub struct WebSocketOptimizationConfig {
     pub connection_quality_check_interval_ms: u64,
     pub latency_threshold_ms: f64,
     pub packet_loss_threshold: f64,
+    /// Bounded channel used by each connector's market data stream.
+    #[serde(default = "DataChannelConfig::market_data")]
+    pub market_data_channel: DataChannelConfig,
+    /// Bounded channel used by each connector's user data stream.
+    #[serde(default = "DataChannelConfig::user_data")]
+    pub user_data_channel: DataChannelConfig,
+    /// Bounded channel that merges all connectors' market data in the registry.
+    #[serde(default = "DataChannelConfig::merged_data")]
+    pub merged_data_channel: DataChannelConfig,
+}
+
+/// Market data recording to compressed segment files (see `connectors::replay`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    pub directory: String,
+    /// A new segment file is started once the current one reaches this size.
+    pub segment_max_bytes: u64,
+    /// Raw bytes buffered before a block is compressed and written.
+    pub block_bytes: usize,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: String::from("recordings"),
+            segment_max_bytes: 256 * 1024 * 1024,
+            block_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Background sampling of exchange server time (see `connectors::common::clock_sync`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockSyncConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Server time requests per exchange and round; the lowest-RTT sample wins.
+    pub samples_per_sync: usize,
+    /// `recvWindow` sent with signed requests, in milliseconds.
+    pub recv_window_ms: u64,
+    /// Log a warning when the estimated offset exceeds this many milliseconds.
+    pub offset_warn_ms: i64,
+}
+
+impl Default for ClockSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            samples_per_sync: 5,
+            recv_window_ms: 5000,
+            offset_warn_ms: 500,
+        }
+    }
+}
+
+/// Instrument metadata (tick size, lot size, min notional) loaded from each exchange
+/// (see `connectors::common::instruments`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InstrumentsConfig {
+    pub enabled: bool,
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for InstrumentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_secs: 3600,
+        }
+    }
+}
+
+/// Explicit price multiplier for a listing quoted per bundle of the base asset,
+/// e.g. `1000PEPEUSDT` on BINANCE_FUTURES is `PEPE_USDT` x1000.
+/// Entries here override multipliers derived from instrument metadata.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContractMultiplierConfig {
+    pub exchange: String,
+    pub symbol: String,
+    pub canonical: String,
+    pub multiplier: f64,
+}
+
+/// Spot/perpetual basis scanner (see `basis`).
+/// Thresholds are percentages; `entry_threshold_pct` is an annualized net return.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BasisConfig {
+    pub enabled: bool,
+    pub scan_interval_ms: u64,
+    pub holding_hours: f64,
+    pub default_funding_interval_hours: f64,
+    pub entry_threshold_pct: f64,
+    pub exit_threshold_pct: f64,
+    pub max_funding_age_secs: u64,
+    pub allow_short_spot: bool,
+}
+
+impl Default for BasisConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            scan_interval_ms: 1000,
+            holding_hours: 72.0,
+            default_funding_interval_hours: 8.0,
+            entry_threshold_pct: 10.0,
+            exit_threshold_pct: 0.02,
+            max_funding_age_secs: 120,
+            allow_short_spot: false,
+        }
+    }
+}
+
+/// How a two-leg execution resolves an imbalance between the filled quantities of its legs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HedgeMode {
+    /// Complete the lagging leg on its own exchange
+    Hedge,
+    /// Reverse the excess on the leg that filled more
+    Unwind,
+    /// Try to complete the lagging leg, then reverse whatever is left
+    HedgeThenUnwind,
+}
+
+/// Two-leg arbitrage execution (see `arbitrage_executor`).
+/// Slippage values are percentages applied to the reference price of each hedge order.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    /// Place orders for newly opened opportunities; when off the scanner only records them
+    pub enabled: bool,
+    pub max_notional_per_leg_usd: f64,
+    pub min_net_profit_pct: f64,
+    /// Opportunities older than this are not executed
+    pub max_opportunity_age_ms: i64,
+    pub max_concurrent_executions: usize,
+    /// How long to wait for the user stream to report a final order state before querying it
+    pub fill_timeout_ms: u64,
+    pub hedge_mode: HedgeMode,
+    /// Price offset of the first hedge order, widened by `hedge_slippage_step_pct` on each retry
+    pub hedge_slippage_pct: f64,
+    pub hedge_slippage_step_pct: f64,
+    /// Hedge orders per phase (hedge or unwind)
+    pub max_hedge_attempts: u32,
+    /// Imbalances below this notional are left as dust
+    pub min_hedge_notional_usd: f64,
+    /// Send the last hedge order of each phase as a market order
+    pub market_on_last_attempt: bool,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_notional_per_leg_usd: 1000.0,
+            min_net_profit_pct: 0.2,
+            max_opportunity_age_ms: 500,
+            max_concurrent_executions: 1,
+            fill_timeout_ms: 2000,
+            hedge_mode: HedgeMode::HedgeThenUnwind,
+            hedge_slippage_pct: 0.05,
+            hedge_slippage_step_pct: 0.05,
+            max_hedge_attempts: 3,
+            min_hedge_notional_usd: 5.0,
+            market_on_last_attempt: true,
+        }
+    }
 }
 
 /// Default configuration used when no config file is provided.
@@ -164,6 +424,14 @@ pub static DEFAULT_CONFIG: Lazy<Config> = Lazy::new(|| Config {
         default_slippage_pct: 0.001,
         large_order_slippage_pct: 0.003,
         max_path_length: 3,
+        min_trade_notional_usd: 10.0,
+        balance_refresh_secs: 30,
+        transfer_cost_pct: 0.0,
+        opportunity_stale_ms: 5000,
+        max_book_age_ms: 2000,
+        max_book_age_ms_by_exchange: HashMap::new(),
+        max_book_skew_ms: 1000,
+        stale_rejections_before_reconnect: 20,
     },
     exchanges: HashMap::new(),
     token_configs: HashMap::new(),
@@ -187,8 +455,17 @@ pub static DEFAULT_CONFIG: Lazy<Config> = Lazy::new(|| Config {
         connection_quality_check_interval_ms: 30000,
         latency_threshold_ms: 500.0,
         packet_loss_threshold: 0.05,
+        market_data_channel: DataChannelConfig::market_data(),
+        user_data_channel: DataChannelConfig::user_data(),
+        merged_data_channel: DataChannelConfig::merged_data(),
     },
     advanced_connectors: HashMap::new(),
+    recording: RecordingConfig::default(),
+    clock_sync: ClockSyncConfig::default(),
+    instruments: InstrumentsConfig::default(),
+    contract_multipliers: Vec::new(),
+    basis: BasisConfig::default(),
+    execution: ExecutionConfig::default(),
 });
 
 impl Config {
@@ -0,0 +1,14 @@
+//! Batonex连接器模块
+//! USDT永续合约行情（depth / trade 主题）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::BatonexProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// Batonex行情连接器
+pub type BatonexConnector = MarketStreamConnector<BatonexProtocol>;
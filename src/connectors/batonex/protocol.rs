@@ -0,0 +1,103 @@
+//! Batonex合约行情协议
+//! 订阅格式：`{"id":1,"topic":"depth","event":"sub","params":{"binary":false,"symbol":"BTC-SWAP-USDT"}}`，
+//! 多个交易对以逗号拼接；深度每次推送完整的前N档；心跳由客户端发送 `{"ping":ts}`。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::market_stream::{
+    build_orderbook, value_to_f64, value_to_i64, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// Batonex WebSocket默认地址
+pub const BATONEX_WS_URL: &str = "wss://wsapi.batonex.com/openapi/quote/ws/v2";
+
+/// Batonex合约行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatonexProtocol;
+
+impl MarketStreamProtocol for BatonexProtocol {
+    const NAME: &'static str = "Batonex";
+    const DEFAULT_WS_URL: &'static str = BATONEX_WS_URL;
+    const EXCHANGE: Exchange = Exchange::Batonex;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::Batonex;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}-SWAP-{}", info.base_currency, info.quote_currency)
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        exchange_symbol.to_uppercase().replace("-SWAP", "").replace(['-', '_', '/'], "")
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], request_id: u64) -> String {
+        json!({
+            "id": request_id,
+            "topic": match channel {
+                MarketChannel::OrderBook => "depth",
+                MarketChannel::Trades => "trade",
+            },
+            "event": if subscribe { "sub" } else { "cancel" },
+            "params": {
+                "binary": false,
+                "symbol": symbols.join(","),
+            },
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        Some(json!({"ping": chrono::Utc::now().timestamp_millis()}).to_string())
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析Batonex消息: {e}")))?;
+
+        if msg.get("pong").is_some() {
+            return Ok(vec![MarketEvent::Pong]);
+        }
+
+        let data = msg.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+        let topic_symbol = msg.get("symbol").and_then(|s| s.as_str()).unwrap_or_default();
+
+        match msg.get("topic").and_then(|t| t.as_str()) {
+            Some("depth") => Ok(data.iter()
+                .map(|book| {
+                    let symbol = book.get("s").and_then(|s| s.as_str()).unwrap_or(topic_symbol);
+                    let timestamp = book.get("t").and_then(value_to_i64).unwrap_or(0);
+                    MarketEvent::Book(build_orderbook(Self::EXCHANGE, Self::standard_symbol(symbol), book.get("b"), book.get("a"), timestamp))
+                })
+                .collect()),
+            Some("trade") => {
+                let symbol = Self::standard_symbol(topic_symbol);
+                let trades = data.iter()
+                    .filter_map(|trade| {
+                        Some(StandardizedTrade {
+                            symbol: symbol.clone(),
+                            exchange: Self::EXCHANGE_TYPE,
+                            price: value_to_f64(trade.get("p")?)?,
+                            quantity: value_to_f64(trade.get("q")?)?,
+                            // m: 买方是否为挂单方，为true时主动方是卖方
+                            side: if trade.get("m")?.as_bool()? { TradeSide::Sell } else { TradeSide::Buy },
+                            timestamp: value_to_i64(trade.get("t")?)?,
+                            trade_id: trade.get("v").map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())).unwrap_or_default(),
+                        })
+                    })
+                    .collect();
+                Ok(vec![MarketEvent::Trades(trades)])
+            }
+            _ => {
+                if msg.get("code").is_some() && msg.get("desc").is_some() {
+                    return Ok(vec![MarketEvent::Error(msg.to_string())]);
+                }
+                Ok(vec![MarketEvent::Other(msg)])
+            }
+        }
+    }
+}
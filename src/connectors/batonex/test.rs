@@ -0,0 +1,84 @@
+//! Batonex连接器测试模块
+//! 使用录制的推送帧测试协议解析、符号转换和批量订阅
+
+mod tests {
+    use super::super::{BatonexConnector, BatonexProtocol};
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::core::AppState;
+    use crate::types::{config::ConnectorConfig, market_data::TradeSide};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// 录制的Batonex深度帧
+    const RECORDED_DEPTH_FRAME: &str = r#"{"symbol":"BTC-SWAP-USDT","topic":"depth","params":{"realtimeInterval":"24h","binary":"false"},"data":[{"e":301,"s":"BTC-SWAP-USDT","t":1710755362722,"v":"112801745_18","b":[["64011.9","5321"],["64011.5","780"]],"a":[["64012.4","1532"],["64012.9","200"]]}],"f":true,"sendTime":1710755362730}"#;
+
+    /// 录制的Batonex成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"symbol":"BTC-SWAP-USDT","topic":"trade","params":{"realtimeInterval":"24h","binary":"false"},"data":[{"v":"1451726810413727744","t":1710755363460,"p":"64011.9","q":"15","m":true},{"v":"1451726810413727745","t":1710755363461,"p":"64012.9","q":"2","m":false}],"f":false,"sendTime":1710755363466}"#;
+
+    /// 录制的Batonex心跳回应和错误
+    const RECORDED_PONG_FRAME: &str = r#"{"pong":1710755362000}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"code":"-100010","desc":"Invalid Symbols!"}"#;
+
+    #[test]
+    fn test_batonex_parse_recorded_frames() {
+        let events = BatonexProtocol::parse_message(RECORDED_DEPTH_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Book(orderbook)] => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 64011.9);
+                assert_eq!(orderbook.best_ask, 64012.4);
+                assert_eq!(orderbook.timestamp, 1710755362722);
+            }
+            other => panic!("应该解析为深度: {other:?}"),
+        }
+
+        let events = BatonexProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades.len(), 2);
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[1].side, TradeSide::Buy);
+                assert_eq!(trades[0].trade_id, "1451726810413727744");
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        assert!(matches!(&BatonexProtocol::parse_message(RECORDED_PONG_FRAME).unwrap()[..], [MarketEvent::Pong]));
+        assert!(matches!(&BatonexProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+
+        let ping: Value = serde_json::from_str(&BatonexProtocol::ping_message().unwrap()).unwrap();
+        assert!(ping["ping"].is_i64());
+    }
+
+    #[tokio::test]
+    async fn test_batonex_symbol_conversion() {
+        let connector = BatonexConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        for symbol in ["BTCUSDT", "BTC_USDT", "BATONEX:BTC/USDT"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "BTC-SWAP-USDT");
+        }
+        assert_eq!(BatonexProtocol::standard_symbol("ETH-SWAP-USDT"), "ETHUSDT");
+    }
+
+    #[test]
+    fn test_batonex_batched_subscription() {
+        let connector = BatonexConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        let symbols: Vec<String> = ["BTC", "ETH", "SOL", "XRP", "DOGE", "ADA"].iter()
+            .map(|base| format!("{base}-SWAP-USDT"))
+            .collect();
+
+        let requests = connector.handler().subscription_requests(true, MarketChannel::OrderBook, &symbols, connector.handler().batch_size());
+        assert_eq!(requests.len(), 2);
+
+        let first: Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(first["topic"], "depth");
+        assert_eq!(first["event"], "sub");
+        assert_eq!(first["params"]["symbol"], "BTC-SWAP-USDT,ETH-SWAP-USDT,SOL-SWAP-USDT,XRP-SWAP-USDT,DOGE-SWAP-USDT");
+
+        let second: Value = serde_json::from_str(&requests[1]).unwrap();
+        assert_eq!(second["params"]["symbol"], "ADA-SWAP-USDT");
+
+        let cancel: Value = serde_json::from_str(&BatonexProtocol::subscription_message(false, MarketChannel::Trades, &symbols[..1], 9)).unwrap();
+        assert_eq!(cancel["topic"], "trade");
+        assert_eq!(cancel["event"], "cancel");
+    }
+}
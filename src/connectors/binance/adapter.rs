@@ -5,20 +5,26 @@
 use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::RwLock;
 use log::{info, warn};
 use chrono;
 
 use crate::connectors::traits::*;
 use crate::connectors::common::{
+    clock_sync::ClockSource,
+    instruments::InstrumentSource,
     emergency_ping::EmergencyPingManager,
     adaptive_timeout::AdaptiveTimeoutManager,
     batch_subscription::BatchSubscriptionManager,
+    data_channel::{data_channel, ChannelSendError, DataReceiver},
 };
+use crate::config::get_config;
 use crate::types::config::{SubscriptionResult, BatchSubscriptionResult, SubscriptionStatus, ConnectionQuality, ConnectionQualityLevel};
 use crate::types::*;
 use super::config::BinanceConfig;
+use super::rest::BinanceSpotRestClient;
 use super::spot::BinanceSpotConnector;
+use super::user_stream::BinanceUserDataStream;
 use super::websocket::BinanceWebSocketHandler;
 
 /// Binance现货连接器适配器
@@ -31,12 +37,17 @@ pub struct BinanceAdapter {
     websocket_handler: Arc<RwLock<Option<BinanceWebSocketHandler>>>,
     connection_status: Arc<RwLock<ConnectionStatus>>,
     app_state: Arc<crate::AppState>,
+    // 签名REST交易接口与用户数据流
+    rest_client: BinanceSpotRestClient,
+    user_stream: BinanceUserDataStream,
     // 存储待订阅的信息
     pending_subscriptions: Arc<RwLock<(Vec<String>, Vec<DataType>)>>,
     // WebSocket优化模块
     emergency_ping_manager: Arc<RwLock<EmergencyPingManager>>,
     adaptive_timeout_manager: Arc<RwLock<AdaptiveTimeoutManager>>,
     batch_subscription_manager: Arc<RwLock<BatchSubscriptionManager>>,
+    // 系统事件广播
+    event_sender: tokio::sync::broadcast::Sender<SystemEvent>,
 }
 
 impl BinanceAdapter {
@@ -45,20 +56,36 @@ impl BinanceAdapter {
         config: BinanceConfig,
         app_state: Arc<crate::AppState>,
     ) -> Result<Self, ConnectorError> {
+        let (event_sender, _) = tokio::sync::broadcast::channel(1000);
+        let rest_client = BinanceSpotRestClient::new(&config);
+        let user_stream = BinanceUserDataStream::new(&config, rest_client.clone());
         Ok(Self {
             config,
             spot_connector: Arc::new(RwLock::new(None)),
             websocket_handler: Arc::new(RwLock::new(None)),
             connection_status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             app_state,
+            rest_client,
+            user_stream,
             pending_subscriptions: Arc::new(RwLock::new((Vec::new(), Vec::new()))),
             // 初始化WebSocket优化模块
             emergency_ping_manager: Arc::new(RwLock::new(EmergencyPingManager::with_default_config())),
             adaptive_timeout_manager: Arc::new(RwLock::new(AdaptiveTimeoutManager::with_default_config())),
             batch_subscription_manager: Arc::new(RwLock::new(BatchSubscriptionManager::with_default_config())),
+            event_sender,
         })
     }
     
+    /// 获取REST客户端
+    pub fn rest_client(&self) -> &BinanceSpotRestClient {
+        &self.rest_client
+    }
+    
+    /// 获取用户数据流
+    pub fn user_stream(&self) -> &BinanceUserDataStream {
+        &self.user_stream
+    }
+    
     /// 添加待订阅的市场数据
     pub async fn add_pending_subscription(&self, symbols: Vec<String>, data_types: Vec<DataType>) {
         let mut pending = self.pending_subscriptions.write().await;
@@ -89,6 +116,17 @@ impl ExchangeConnector for BinanceAdapter {
         "Binance"
     }
     
+    fn clock_source(&self) -> Option<ClockSource> {
+        Some(ClockSource {
+            clock: self.rest_client.clock().clone(),
+            source: Arc::new(self.rest_client.clone()),
+        })
+    }
+    
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        Some(Arc::new(self.rest_client.clone()))
+    }
+    
     async fn connect_websocket(&self) -> Result<(), ConnectorError> {
         info!("[Binance] 开始连接WebSocket...");
         
@@ -114,6 +152,7 @@ impl ExchangeConnector for BinanceAdapter {
         ).await.map_err(|e| {
             ConnectorError::ConnectionFailed(format!("创建WebSocket处理器失败: {e}"))
         })?;
+        ws_handler.set_event_sender(self.event_sender.clone()).await;
         
         // 如果有待订阅的数据，先设置订阅信息
         if !symbols.is_empty() && !data_types.is_empty() {
@@ -152,6 +191,11 @@ impl ExchangeConnector for BinanceAdapter {
     async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
         info!("[Binance] 断开WebSocket连接...");
         
+        // 停止用户数据流并关闭listenKey
+        if self.user_stream.get_connection_status().await != ConnectionStatus::Disconnected {
+            self.user_stream.stop().await;
+        }
+        
         // 断开WebSocket连接
         if let Some(handler) = self.websocket_handler.write().await.take() {
             handler.disconnect().await.map_err(|e| {
@@ -206,28 +250,47 @@ impl ExchangeConnector for BinanceAdapter {
     }
     
     async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
-        warn!("[Binance] 用户数据流订阅暂未实现");
-        Err(ConnectorError::TradingNotImplemented)
+        info!("[Binance] 订阅用户数据流");
+        
+        if !self.rest_client.has_credentials() {
+            return Err(ConnectorError::InvalidCredentials("Binance API密钥未配置".to_string()));
+        }
+        
+        self.user_stream.start().await?;
+        info!("[Binance] 用户数据流订阅成功");
+        Ok(())
     }
     
-    fn get_market_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
         // 这里需要返回市场数据流接收器
         // 暂时创建一个空的接收器
-        let (_tx, rx) = mpsc::unbounded_channel();
+        let (_tx, rx) = data_channel(get_config().websocket_optimization.market_data_channel);
         rx
     }
     
-    fn get_user_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
-        // 这里需要返回用户数据流接收器
-        // 暂时创建一个空的接收器
-        let (_tx, rx) = mpsc::unbounded_channel();
-        rx
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        if !self.user_stream.try_set_message_sender(sender) {
+            warn!("[Binance] 用户数据发送器正被占用，返回的数据流不会收到消息");
+        }
+        receiver
+    }
+    
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        if let Some(channel) = self.user_stream.channel_stats() {
+            channel.apply_to(&mut stats);
+        }
+        stats
     }
     
     async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
         info!("[Binance] 获取订单簿快照: {symbol}");
-        // 暂时返回None，实际实现需要从本地缓存获取
-        None
+        let handler_guard = self.websocket_handler.read().await;
+        match handler_guard.as_ref() {
+            Some(handler) => handler.orderbook_manager().get_orderbook(symbol).await,
+            None => None,
+        }
     }
     
     async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
@@ -236,24 +299,22 @@ impl ExchangeConnector for BinanceAdapter {
         Vec::new()
     }
     
-    async fn place_order(&self, _order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
-        warn!("[Binance] 交易功能暂未实现");
-        Err(ConnectorError::TradingNotImplemented)
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        info!("[Binance] 下单: {} {:?} {:?} {}", order.symbol, order.side, order.order_type, order.quantity);
+        self.rest_client.place_order(order).await
     }
     
     async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
-        warn!("[Binance] 取消订单功能暂未实现: order_id={}, symbol={}", order_id, symbol);
-        Err(ConnectorError::TradingNotImplemented)
+        info!("[Binance] 撤单: {order_id} ({symbol})");
+        self.rest_client.cancel_order(order_id, symbol).await
     }
     
     async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
-        warn!("[Binance] 获取订单状态功能暂未实现: order_id={}, symbol={}", order_id, symbol);
-        Err(ConnectorError::TradingNotImplemented)
+        self.rest_client.get_order_status(order_id, symbol).await
     }
     
     async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
-        warn!("[Binance] 获取账户余额功能暂未实现");
-        Err(ConnectorError::TradingNotImplemented)
+        self.rest_client.get_account_balance().await
     }
     
     async fn is_connected(&self) -> bool {
@@ -355,64 +416,23 @@ impl ExchangeConnector for BinanceAdapter {
 
 #[async_trait]
 impl DataFlowManager for BinanceAdapter {
-    fn take_market_data_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<HighFrequencyData>> {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
         // 暂时返回None，实际实现需要返回高频数据接收器
         None
     }
     
     fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<SystemEvent> {
-        // 暂时创建一个空的接收器
-        let (_tx, rx) = tokio::sync::broadcast::channel(100);
-        rx
+        self.event_sender.subscribe()
     }
     
-    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), mpsc::error::SendError<HighFrequencyData>> {
+    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
         // 暂时返回成功，实际实现需要发送数据到队列
         Ok(())
     }
     
-    async fn send_event(&self, _event: SystemEvent) {
-        // 事件发送的简化实现
-        // 实际实现需要将事件发送到事件总线
-    }
-}
-
-#[async_trait]
-impl ConnectorManager for BinanceAdapter {
-    async fn add_connector(&mut self, _connector: Box<dyn ExchangeConnector>) -> Result<(), ConnectorError> {
-        // 暂时返回未实现错误
-        Err(ConnectorError::TradingNotImplemented)
-    }
-    
-    async fn remove_connector(&mut self, _exchange: ExchangeType, _market_type: MarketType) -> Result<(), ConnectorError> {
-        // 暂时返回未实现错误
-        Err(ConnectorError::TradingNotImplemented)
-    }
-    
-    fn get_connector(&self, exchange: ExchangeType, market_type: MarketType) -> Option<&dyn ExchangeConnector> {
-        if exchange == ExchangeType::Binance && market_type == MarketType::Spot {
-            Some(self)
-        } else {
-            None
-        }
-    }
-    
-    fn get_all_connectors(&self) -> Vec<&dyn ExchangeConnector> {
-        vec![self]
-    }
-    
-    async fn connect_all(&self) -> Result<(), ConnectorError> {
-        self.connect_websocket().await
-    }
-    
-    async fn disconnect_all(&self) -> Result<(), ConnectorError> {
-        self.disconnect_websocket().await
-    }
-    
-    async fn get_connection_status_all(&self) -> std::collections::HashMap<(ExchangeType, MarketType), ConnectionStatus> {
-        let mut status_map = std::collections::HashMap::new();
-        status_map.insert((ExchangeType::Binance, MarketType::Spot), self.get_connection_status().await);
-        status_map
+    async fn send_event(&self, event: SystemEvent) {
+        // 没有订阅者时发送失败是正常情况
+        let _ = self.event_sender.send(event);
     }
 }
 
@@ -447,7 +467,8 @@ impl BinanceAdapter {
             info!("[Binance] 市场数据订阅成功");
             Ok(())
         } else {
-            // 如果未连接，先建立WebSocket连接
+            // 如果未连接，先建立WebSocket连接（需先释放读锁，connect_websocket会获取写锁）
+            drop(handler_guard);
             info!("[Binance] WebSocket未连接，正在建立连接...");
             self.connect_websocket().await?;
             info!("[Binance] WebSocket连接已建立，订阅信息将自动生效");
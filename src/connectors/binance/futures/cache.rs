@@ -151,6 +151,14 @@ impl MarketDataCache {
         debug!("更新深度数据缓存: {symbol}");
     }
     
+    /// 移除深度数据（本地订单簿失效时调用，避免提供过期深度）
+    pub async fn remove_depth(&self, symbol: &str) {
+        let mut cache = self.depth_cache.write().await;
+        if cache.remove(symbol).is_some() {
+            debug!("移除深度数据缓存: {symbol}");
+        }
+    }
+    
     /// 获取行情数据
     pub async fn get_ticker(&self, symbol: &str) -> Option<Ticker> {
         let mut cache = self.ticker_cache.write().await;
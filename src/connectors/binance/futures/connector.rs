@@ -4,9 +4,20 @@
 
 use crate::connectors::binance::futures::config::{BinanceFuturesConfig, MarginType as ConfigMarginType};
 use crate::connectors::binance::futures::websocket::*;
-use crate::connectors::binance::futures::rest_api::{BinanceFuturesRestClient, MarginType as RestMarginType};
+use crate::connectors::binance::futures::rest_api::{BatchOrderResult, BinanceFuturesRestClient, MarginType as RestMarginType};
+use crate::connectors::binance::futures::dead_mans_switch::DeadMansSwitch;
+use crate::connectors::binance::futures::ws_api::BinanceFuturesWsApiClient;
 use crate::connectors::binance::futures::message_parser::*;
+use crate::connectors::binance::futures::orderbook::FuturesOrderBookManager;
+use crate::connectors::binance::futures::cache::MarketDataCache;
+use crate::connectors::binance::futures::performance_monitor::{MetricType, PerformanceMonitor};
 use crate::connectors::common::advanced_connection::{EmergencyPingManager, AdaptiveTimeoutManager};
+use crate::connectors::common::clock_sync::ClockSource;
+use crate::connectors::common::instruments::InstrumentSource;
+use crate::connectors::common::data_channel::{data_channel, DataReceiver, DataSender};
+use crate::config::get_config;
+use crate::basis::FundingStore;
+use crate::exchange_types::Exchange;
 use crate::types::market_data::*;
 use crate::types::trading::{*, TimeInForce as TradingTimeInForce, PositionSide as TradingPositionSide};
 use crate::core::AppError;
@@ -27,7 +38,8 @@ use tokio::sync::{mpsc, RwLock};
 use futures_util::StreamExt;
 use std::sync::Arc;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::future::Future;
+use std::time::{Duration, Instant};
 use log::{info, warn};
 use chrono::{DateTime, Utc};
 use serde_json::Value;
@@ -39,7 +51,7 @@ pub struct BinanceFuturesConnector {
     /// WebSocket处理器
     ws_handler: BinanceFuturesWebSocketHandler,
     /// REST API客户端
-    rest_client: BinanceFuturesRestClient,
+    rest_client: Arc<BinanceFuturesRestClient>,
     /// 消息解析器
     message_parser: BinanceFuturesMessageParser,
     /// 市场数据发送通道
@@ -65,17 +77,27 @@ pub struct BinanceFuturesConnector {
     
     // ExchangeConnector trait 所需的数据流
     /// 标准化市场数据流发送端
-    standardized_market_sender: Option<mpsc::UnboundedSender<StandardizedMessage>>,
+    standardized_market_sender: Option<DataSender<StandardizedMessage>>,
     /// 标准化市场数据流接收端
-    standardized_market_receiver: Option<mpsc::UnboundedReceiver<StandardizedMessage>>,
+    standardized_market_receiver: Option<DataReceiver<StandardizedMessage>>,
     /// 标准化用户数据流发送端
-    standardized_user_sender: Option<mpsc::UnboundedSender<StandardizedMessage>>,
+    standardized_user_sender: Option<DataSender<StandardizedMessage>>,
     /// 标准化用户数据流接收端
-    standardized_user_receiver: Option<mpsc::UnboundedReceiver<StandardizedMessage>>,
+    standardized_user_receiver: Option<DataReceiver<StandardizedMessage>>,
     /// 本地订单簿缓存
     orderbook_cache: Arc<RwLock<HashMap<String, StandardizedOrderBook>>>,
     /// 本地交易数据缓存
     trades_cache: Arc<RwLock<HashMap<String, Vec<StandardizedTrade>>>>,
+    /// 本地订单簿管理器（启用后使用增量深度流）
+    orderbook_manager: Option<FuturesOrderBookManager>,
+    /// 资金费率存储（启用后标记价格流写入其中）
+    funding_store: Option<Arc<FundingStore>>,
+    /// WebSocket API下单客户端（启用后交易请求优先走ws-fapi会话）
+    ws_api: Option<Arc<BinanceFuturesWsApiClient>>,
+    /// 记录各下单通道延迟的性能监控器
+    order_latency_monitor: Option<PerformanceMonitor>,
+    /// 倒计时撤单开关（启用后随连接启动和停止）
+    dead_mans_switch: Option<DeadMansSwitch>,
 }
 
 /// 连接状态
@@ -119,12 +141,13 @@ impl BinanceFuturesConnector {
     /// 创建新的期货连接器
     pub fn new(config: BinanceFuturesConfig) -> Self {
         let ws_handler = BinanceFuturesWebSocketHandler::new(config.clone());
-        let rest_client = BinanceFuturesRestClient::new(config.clone());
+        let rest_client = Arc::new(BinanceFuturesRestClient::new(config.clone()));
         let message_parser = BinanceFuturesMessageParser;
         
         // 创建标准化数据流通道
-        let (market_tx, market_rx) = mpsc::unbounded_channel::<StandardizedMessage>();
-        let (user_tx, user_rx) = mpsc::unbounded_channel::<StandardizedMessage>();
+        let channel_config = &get_config().websocket_optimization;
+        let (market_tx, market_rx) = data_channel::<StandardizedMessage>(channel_config.market_data_channel);
+        let (user_tx, user_rx) = data_channel::<StandardizedMessage>(channel_config.user_data_channel);
         
         // 初始化高级连接管理组件
         let emergency_ping_manager = EmergencyPingManager::new(5);
@@ -156,9 +179,84 @@ impl BinanceFuturesConnector {
             standardized_user_receiver: Some(user_rx),
             orderbook_cache: Arc::new(RwLock::new(HashMap::new())),
             trades_cache: Arc::new(RwLock::new(HashMap::new())),
+            orderbook_manager: None,
+            funding_store: None,
+            ws_api: None,
+            order_latency_monitor: None,
+            dead_mans_switch: None,
         }
     }
     
+    /// 使用自定义REST客户端（例如指向本地模拟服务器）
+    /// 
+    /// 需要在启用本地订单簿和WebSocket API下单之前调用。
+    pub fn with_rest_client(mut self, rest_client: BinanceFuturesRestClient) -> Self {
+        self.rest_client = Arc::new(rest_client);
+        self
+    }
+    
+    /// REST API客户端
+    pub fn rest_client(&self) -> &Arc<BinanceFuturesRestClient> {
+        &self.rest_client
+    }
+    
+    /// 启用本地订单簿
+    /// 
+    /// 之后订阅的交易对改用增量深度流，同步后的订单簿写入`cache`，
+    /// 同步统计记录到`monitor`。需要在订阅之前调用。
+    pub fn enable_local_orderbook(&mut self, cache: MarketDataCache, monitor: PerformanceMonitor) -> FuturesOrderBookManager {
+        // 快照请求与交易请求共用同一个限速器和服务器时钟
+        let manager = FuturesOrderBookManager::new(self.rest_client.clone(), cache, monitor);
+        self.ws_handler.set_orderbook_manager(manager.clone());
+        self.orderbook_manager = Some(manager.clone());
+        manager
+    }
+    
+    /// 启用资金费率跟踪
+    /// 
+    /// 标记价格流（`<symbol>@markPrice`与`!markPrice@arr`）的推送写入`store`，
+    /// 供基差扫描器读取。需要配合`subscribe_funding_rates`使用。
+    pub fn enable_funding_tracking(&mut self, store: Arc<FundingStore>) {
+        self.ws_handler.set_funding_store(store.clone());
+        self.funding_store = Some(store);
+    }
+    
+    /// 启用WebSocket API下单
+    /// 
+    /// 下单、撤单、改单和账户查询优先走`ws_api`会话，会话未连接时改走REST。
+    /// 每个请求的往返延迟以`MetricType::OrderLatency`记录到`monitor`，
+    /// 标签`path`为`ws_api`或`rest`、`method`为请求方法，便于比较两条通道。
+    /// 会话在`connect`时建立，也可以调用返回值的`start`单独建立。
+    /// `ws_api`应通过`BinanceFuturesWsApiClient::new(&config, connector.rest_client())`创建以共享限速额度。
+    pub fn enable_ws_order_entry(&mut self, ws_api: BinanceFuturesWsApiClient, monitor: PerformanceMonitor) -> Arc<BinanceFuturesWsApiClient> {
+        let ws_api = Arc::new(ws_api);
+        self.ws_api = Some(ws_api.clone());
+        self.order_latency_monitor = Some(monitor);
+        ws_api
+    }
+    
+    /// 启用倒计时撤单
+    /// 
+    /// 开关在`connect`时启动、`disconnect`时停止，进程异常退出时由交易所撤销受保护交易对的挂单。
+    /// `switch`应通过`DeadMansSwitch::new(connector.rest_client().clone(), emergency_stop, symbols)`创建。
+    pub fn enable_dead_mans_switch(&mut self, switch: DeadMansSwitch) {
+        self.dead_mans_switch = Some(switch);
+    }
+    
+    /// 通过REST premiumIndex接口拉取全部交易对的资金费率写入存储，返回写入的条数
+    /// 
+    /// WebSocket推送到来之前用于预热。
+    pub async fn seed_funding_rates(&self) -> Result<usize> {
+        let store = self.funding_store.as_ref().ok_or_else(|| {
+            AppError::ConfigError("资金费率跟踪未启用".to_string())
+        })?;
+        let data = self.rest_client.get_mark_price(None).await?;
+        let interval_hours = get_config().basis.default_funding_interval_hours;
+        let count = store.apply_premium_index(Exchange::BinanceFutures, &data, interval_hours);
+        info!("已加载 {count} 个交易对的资金费率");
+        Ok(count)
+    }
+    
     /// 设置市场数据发送通道
     pub fn set_market_data_sender(&mut self, sender: mpsc::UnboundedSender<MarketDataEvent>) {
         self.market_data_sender = Some(sender.clone());
@@ -211,6 +309,19 @@ impl BinanceFuturesConnector {
             }
         }
         
+        // 启用了WebSocket API下单时建立会话，失败时交易请求走REST
+        if let Some(ws_api) = &self.ws_api {
+            if let Err(e) = ws_api.start().await {
+                warn!("WebSocket API会话建立失败，交易请求将使用REST: {e}");
+            }
+        }
+        
+        if let Some(switch) = &self.dead_mans_switch {
+            if let Err(e) = switch.start().await {
+                warn!("倒计时撤单启动失败: {e}");
+            }
+        }
+        
         // 订阅配置中指定的交易对
         let symbols = self.config.subscribed_symbols.clone();
         for symbol in &symbols {
@@ -233,6 +344,14 @@ impl BinanceFuturesConnector {
             }
         }
         
+        if let Some(ws_api) = &self.ws_api {
+            ws_api.stop().await;
+        }
+        
+        if let Some(switch) = &self.dead_mans_switch {
+            switch.stop().await;
+        }
+        
         // 断开WebSocket连接
         self.ws_handler.disconnect().await?;
         
@@ -260,7 +379,11 @@ impl BinanceFuturesConnector {
         info!("订阅{symbol}的期货数据");
         
         // 订阅深度数据
-        self.ws_handler.subscribe_depth(symbol, Some(20)).await?;
+        if self.orderbook_manager.is_some() {
+            self.ws_handler.subscribe_diff_depth(symbol).await?;
+        } else {
+            self.ws_handler.subscribe_depth(symbol, Some(20)).await?;
+        }
         
         // 订阅交易数据
         self.ws_handler.subscribe_trades(symbol).await?;
@@ -290,7 +413,11 @@ impl BinanceFuturesConnector {
         info!("取消订阅{symbol}的期货数据");
         
         // 取消各种数据订阅
-        let depth_stream = format!("{}@depth20@100ms", symbol.to_lowercase());
+        let depth_stream = if self.orderbook_manager.is_some() {
+            format!("{}@depth@100ms", symbol.to_lowercase())
+        } else {
+            format!("{}@depth20@100ms", symbol.to_lowercase())
+        };
         let trade_stream = format!("{}@aggTrade", symbol.to_lowercase());
         let ticker_stream = format!("{}@ticker", symbol.to_lowercase());
         let mark_price_stream = format!("{}@markPrice@1s", symbol.to_lowercase());
@@ -407,7 +534,8 @@ impl BinanceFuturesConnector {
     
     /// 获取账户信息
     pub async fn get_account_info(&self) -> Result<Value> {
-        self.rest_client.get_account_info().await
+        let ws_request = self.ws_api.as_ref().map(|ws_api| ws_api.account_status());
+        self.route_trading_request("account.status", ws_request, self.rest_client.get_account_info()).await
     }
     
     /// 获取持仓信息
@@ -417,52 +545,56 @@ impl BinanceFuturesConnector {
     
     /// 下单
     pub async fn place_order(&self, request: &LocalOrderRequest) -> Result<Value> {
-        // 转换为 REST API 所需的类型
-         let rest_request = crate::connectors::binance::futures::rest_api::OrderRequest {
-             symbol: request.symbol.clone(),
-             side: match request.side.as_str() {
-                 "BUY" => OrderSide::Buy,
-                 "SELL" => OrderSide::Sell,
-                 _ => return Err(AppError::ParseError("Invalid order side".to_string())),
-             },
-             order_type: match request.order_type.as_str() {
-                 "MARKET" => OrderType::Market,
-                 "LIMIT" => OrderType::Limit,
-                 "STOP" => OrderType::Stop,
-                 "STOP_MARKET" => OrderType::StopMarket,
-                 "TAKE_PROFIT" => OrderType::TakeProfit,
-                 "TAKE_PROFIT_MARKET" => OrderType::TakeProfitMarket,
-                 _ => return Err(AppError::ParseError("Invalid order type".to_string())),
-             },
-             quantity: request.quantity,
-             price: request.price,
-             time_in_force: request.time_in_force.as_ref().map(|tif| match tif.as_str() {
-                 "GTC" => TradingTimeInForce::GTC,
-                 "IOC" => TradingTimeInForce::IOC,
-                 "FOK" => TradingTimeInForce::FOK,
-                 "GTX" => TradingTimeInForce::GTX,
-                 _ => TradingTimeInForce::GTC,
-             }),
-             position_side: request.position_side.as_ref().map(|ps| match ps.as_str() {
-                 "LONG" => TradingPositionSide::Long,
-                 "SHORT" => TradingPositionSide::Short,
-                 "BOTH" => TradingPositionSide::Both,
-                 _ => TradingPositionSide::Both,
-             }),
-             close_position: request.close_position,
-             activation_price: None,
-             callback_rate: None,
-             working_type: None,
-             price_protect: None,
-             reduce_only: request.reduce_only,
-             client_order_id: request.client_order_id.clone(), // 修正字段名
-         };
-        self.rest_client.place_order(&rest_request).await
+        let ws_request = self.ws_api.as_ref().map(|ws_api| ws_api.place_order(request));
+        let rest_request = async {
+            self.rest_client.place_order(&Self::to_rest_order_request(request)?).await
+        };
+        self.route_trading_request("order.place", ws_request, rest_request).await
     }
     
     /// 取消订单
     pub async fn cancel_order(&self, symbol: &str, order_id: Option<u64>, orig_client_order_id: Option<&str>) -> Result<Value> {
-        self.rest_client.cancel_order(symbol, order_id, orig_client_order_id).await
+        let ws_request = self.ws_api.as_ref().map(|ws_api| ws_api.cancel_order(symbol, order_id, orig_client_order_id));
+        let rest_request = self.rest_client.cancel_order(symbol, order_id, orig_client_order_id);
+        self.route_trading_request("order.cancel", ws_request, rest_request).await
+    }
+    
+    /// 修改限价单的价格和数量
+    pub async fn modify_order(&self, request: &ModifyOrderRequest) -> Result<Value> {
+        let ws_request = self.ws_api.as_ref().map(|ws_api| ws_api.modify_order(request));
+        let rest_request = async {
+            let side = match request.side.as_str() {
+                "BUY" => OrderSide::Buy,
+                "SELL" => OrderSide::Sell,
+                _ => return Err(AppError::ParseError("Invalid order side".to_string())),
+            };
+            self.rest_client.modify_order(
+                &request.symbol,
+                request.order_id,
+                request.orig_client_order_id.as_deref(),
+                side,
+                request.quantity,
+                request.price,
+            ).await
+        };
+        self.route_trading_request("order.modify", ws_request, rest_request).await
+    }
+    
+    /// 批量下单（REST `batchOrders`），一次最多5个订单，结果与请求一一对应
+    pub async fn place_batch_orders(&self, requests: &[LocalOrderRequest]) -> Result<Vec<BatchOrderResult>> {
+        let rest_requests = requests.iter()
+            .map(Self::to_rest_order_request)
+            .collect::<Result<Vec<_>>>()?;
+        
+        let started = Instant::now();
+        let results = self.rest_client.place_batch_orders(&rest_requests).await;
+        self.record_order_latency("rest", "batchOrders", started).await;
+        results
+    }
+    
+    /// 撤销交易对的全部挂单
+    pub async fn cancel_all_open_orders(&self, symbol: &str) -> Result<Value> {
+        self.rest_client.cancel_all_open_orders(symbol).await
     }
     
     /// 查询订单
@@ -470,6 +602,86 @@ impl BinanceFuturesConnector {
         self.rest_client.query_order(symbol, order_id, orig_client_order_id).await
     }
     
+    /// 按通道发送交易请求：启用了WebSocket API时先走ws-fapi会话，会话未连接（请求未发出）时改走REST
+    /// 
+    /// 请求已经发出后连接断开的错误直接返回，不改走REST，避免同一订单被重复提交。
+    async fn route_trading_request(
+        &self,
+        method: &str,
+        ws_request: Option<impl Future<Output = Result<Value>>>,
+        rest_request: impl Future<Output = Result<Value>>,
+    ) -> Result<Value> {
+        if let Some(ws_request) = ws_request {
+            let started = Instant::now();
+            match ws_request.await {
+                Err(AppError::ConnectionError(e)) => warn!("{method} 改走REST: {e}"),
+                result => {
+                    self.record_order_latency("ws_api", method, started).await;
+                    return result;
+                }
+            }
+        }
+        
+        let started = Instant::now();
+        let result = rest_request.await;
+        self.record_order_latency("rest", method, started).await;
+        result
+    }
+    
+    /// 记录一次交易请求的往返延迟
+    async fn record_order_latency(&self, path: &str, method: &str, started: Instant) {
+        if let Some(monitor) = &self.order_latency_monitor {
+            let tags = HashMap::from([
+                ("path".to_string(), path.to_string()),
+                ("method".to_string(), method.to_string()),
+            ]);
+            monitor.record_metric(MetricType::OrderLatency, started.elapsed().as_secs_f64() * 1000.0, tags).await;
+        }
+    }
+    
+    /// 转换为 REST API 所需的订单请求
+    fn to_rest_order_request(request: &LocalOrderRequest) -> Result<crate::connectors::binance::futures::rest_api::OrderRequest> {
+        Ok(crate::connectors::binance::futures::rest_api::OrderRequest {
+            symbol: request.symbol.clone(),
+            side: match request.side.as_str() {
+                "BUY" => OrderSide::Buy,
+                "SELL" => OrderSide::Sell,
+                _ => return Err(AppError::ParseError("Invalid order side".to_string())),
+            },
+            order_type: match request.order_type.as_str() {
+                "MARKET" => OrderType::Market,
+                "LIMIT" => OrderType::Limit,
+                "STOP" => OrderType::Stop,
+                "STOP_MARKET" => OrderType::StopMarket,
+                "TAKE_PROFIT" => OrderType::TakeProfit,
+                "TAKE_PROFIT_MARKET" => OrderType::TakeProfitMarket,
+                _ => return Err(AppError::ParseError("Invalid order type".to_string())),
+            },
+            quantity: request.quantity,
+            price: request.price,
+            time_in_force: request.time_in_force.as_ref().map(|tif| match tif.as_str() {
+                "GTC" => TradingTimeInForce::GTC,
+                "IOC" => TradingTimeInForce::IOC,
+                "FOK" => TradingTimeInForce::FOK,
+                "GTX" => TradingTimeInForce::GTX,
+                _ => TradingTimeInForce::GTC,
+            }),
+            position_side: request.position_side.as_ref().map(|ps| match ps.as_str() {
+                "LONG" => TradingPositionSide::Long,
+                "SHORT" => TradingPositionSide::Short,
+                "BOTH" => TradingPositionSide::Both,
+                _ => TradingPositionSide::Both,
+            }),
+            close_position: request.close_position,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+            reduce_only: request.reduce_only,
+            client_order_id: request.client_order_id.clone(),
+        })
+    }
+    
     /// 调整杠杆
     pub async fn change_leverage(&self, symbol: &str, leverage: u8) -> Result<Value> {
         self.rest_client.change_leverage(symbol, leverage).await
@@ -547,6 +759,17 @@ pub struct LocalOrderRequest {
     pub client_order_id: Option<String>,
 }
 
+/// 改单请求结构体
+#[derive(Debug, Clone)]
+pub struct ModifyOrderRequest {
+    pub symbol: String,
+    pub order_id: Option<u64>,
+    pub orig_client_order_id: Option<String>,
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+}
+
 // 实现 ExchangeConnector trait
 #[async_trait]
 impl ExchangeConnector for BinanceFuturesConnector {
@@ -563,6 +786,17 @@ impl ExchangeConnector for BinanceFuturesConnector {
         "Binance Futures"
     }
     
+    fn clock_source(&self) -> Option<ClockSource> {
+        Some(ClockSource {
+            clock: self.rest_client.clock().clone(),
+            source: self.rest_client.clone(),
+        })
+    }
+    
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        Some(self.rest_client.clone())
+    }
+    
     // WebSocket 连接管理
     async fn connect_websocket(&self) -> std::result::Result<(), ConnectorError> {
         // 由于trait要求&self，但ws_handler需要&mut，这里需要使用内部可变性
@@ -600,20 +834,24 @@ impl ExchangeConnector for BinanceFuturesConnector {
     }
     
     // 推送式数据流接口 - 暂时使用空实现，避免panic
-    fn get_market_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
         // 创建一个新的通道并返回接收端
-        let (_tx, rx) = mpsc::unbounded_channel();
+        let (_tx, rx) = data_channel(get_config().websocket_optimization.market_data_channel);
         rx
     }
     
-    fn get_user_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
         // 创建一个新的通道并返回接收端
-        let (_tx, rx) = mpsc::unbounded_channel();
+        let (_tx, rx) = data_channel(get_config().websocket_optimization.user_data_channel);
         rx
     }
     
     // 本地缓存快照读取
     async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        if let Some(ref manager) = self.orderbook_manager {
+            return manager.get_orderbook(symbol).await;
+        }
+        
         let cache = self.orderbook_cache.read().await;
         cache.get(symbol).cloned()
     }
@@ -643,19 +881,8 @@ impl ExchangeConnector for BinanceFuturesConnector {
             client_order_id: order.client_order_id.clone(),
         };
         
-        // 调用REST客户端下单方法
-        match self.rest_client.new_order(
-            &local_order.symbol,
-            &local_order.side,
-            &local_order.order_type,
-            local_order.quantity,
-            local_order.price,
-            local_order.time_in_force.as_deref(),
-            local_order.reduce_only,
-            local_order.close_position,
-            local_order.position_side.as_deref(),
-            local_order.client_order_id.as_deref(),
-        ).await {
+        // 按通道下单（WebSocket API会话不可用时走REST）
+        match BinanceFuturesConnector::place_order(self, &local_order).await {
             Ok(response) => {
                 // 解析响应并转换为标准格式
                 let order_id = response.get("orderId")
@@ -693,7 +920,7 @@ impl ExchangeConnector for BinanceFuturesConnector {
     }
     
     async fn cancel_order(&self, order_id: &str, symbol: &str) -> std::result::Result<bool, ConnectorError> {
-        match self.rest_client.cancel_order(symbol, Some(order_id.parse().unwrap_or(0)), None).await {
+        match BinanceFuturesConnector::cancel_order(self, symbol, Some(order_id.parse().unwrap_or(0)), None).await {
             Ok(_) => Ok(true),
             Err(e) => Err(ConnectorError::TradingError(format!("取消订单失败: {}", e)))
         }
@@ -737,7 +964,7 @@ impl ExchangeConnector for BinanceFuturesConnector {
     }
     
     async fn get_account_balance(&self) -> std::result::Result<AccountBalance, ConnectorError> {
-        match self.rest_client.get_account_info().await {
+        match BinanceFuturesConnector::get_account_info(self).await {
             Ok(response) => {
                 // 解析响应并转换为标准格式
                 let mut total_balance = 0.0;
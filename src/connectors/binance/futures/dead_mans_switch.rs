@@ -0,0 +1,245 @@
+//! Binance期货倒计时撤单（dead-man's switch）
+//!
+//! 心跳任务定时调用 `countdownCancelAll` 刷新倒计时；进程退出或与交易所失联超过倒计时，
+//! 交易所会撤销这些交易对的全部挂单。开关只在`EmergencyStop`未触发时启用：
+//! 紧急停止期间取消倒计时，避免人工处理的平仓单被撤销，解除后下一次心跳重新启用。
+
+use crate::connectors::binance::futures::rest_api::BinanceFuturesRestClient;
+use crate::connectors::binance::futures::risk_manager::EmergencyStop;
+use crate::core::AppError;
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+/// 默认倒计时
+pub const DEFAULT_COUNTDOWN: Duration = Duration::from_secs(60);
+/// 默认心跳间隔，不超过倒计时的一半，单次心跳失败不会触发撤单
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 开关状态
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeadMansSwitchStatus {
+    /// 交易所端的倒计时是否处于启用状态
+    pub armed: bool,
+    /// 最近一次成功心跳的时间
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    /// 累计失败的心跳次数
+    pub heartbeat_failures: u64,
+}
+
+/// 倒计时撤单开关
+#[derive(Clone)]
+pub struct DeadMansSwitch {
+    rest_client: Arc<BinanceFuturesRestClient>,
+    emergency_stop: EmergencyStop,
+    /// 受保护的交易对（倒计时按交易对设置）
+    symbols: Vec<String>,
+    countdown: Duration,
+    heartbeat_interval: Duration,
+    status: Arc<RwLock<DeadMansSwitchStatus>>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl DeadMansSwitch {
+    /// 创建开关，使用默认倒计时和心跳间隔
+    pub fn new(rest_client: Arc<BinanceFuturesRestClient>, emergency_stop: EmergencyStop, symbols: Vec<String>) -> Self {
+        Self {
+            rest_client,
+            emergency_stop,
+            symbols,
+            countdown: DEFAULT_COUNTDOWN,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+            status: Arc::new(RwLock::new(DeadMansSwitchStatus::default())),
+            task: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 设置倒计时和心跳间隔
+    pub fn with_timing(mut self, countdown: Duration, heartbeat_interval: Duration) -> Self {
+        self.countdown = countdown;
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// 当前状态
+    pub async fn status(&self) -> DeadMansSwitchStatus {
+        self.status.read().await.clone()
+    }
+
+    /// 启动心跳任务，第一次心跳立即发送
+    pub async fn start(&self) -> Result<()> {
+        if self.symbols.is_empty() {
+            return Err(AppError::ConfigError("倒计时撤单至少需要一个交易对".to_string()));
+        }
+        if self.heartbeat_interval * 2 > self.countdown {
+            return Err(AppError::ConfigError(format!(
+                "心跳间隔{:?}超过倒计时{:?}的一半，一次心跳失败就会撤单",
+                self.heartbeat_interval, self.countdown
+            )));
+        }
+
+        let mut task = self.task.lock().await;
+        if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return Ok(());
+        }
+
+        let switch = self.clone();
+        *task = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(switch.heartbeat_interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                switch.heartbeat().await;
+            }
+        }));
+        info!("倒计时撤单已启动: {:?}，倒计时{:?}，心跳间隔{:?}", self.symbols, self.countdown, self.heartbeat_interval);
+        Ok(())
+    }
+
+    /// 停止心跳并取消倒计时（正常退出时保留挂单）
+    pub async fn stop(&self) {
+        if let Some(handle) = self.task.lock().await.take() {
+            handle.abort();
+        }
+        if self.status.read().await.armed && self.send_countdown(Duration::ZERO).await {
+            self.status.write().await.armed = false;
+            info!("倒计时撤单已停止");
+        }
+    }
+
+    /// 发送一次心跳：未处于紧急停止时刷新倒计时，紧急停止期间取消倒计时
+    pub async fn heartbeat(&self) {
+        let arm = !self.emergency_stop.is_emergency_mode().await;
+        let was_armed = self.status.read().await.armed;
+        if !arm && !was_armed {
+            return;
+        }
+
+        let countdown = if arm { self.countdown } else { Duration::ZERO };
+        let succeeded = self.send_countdown(countdown).await;
+
+        let mut status = self.status.write().await;
+        if !succeeded {
+            status.heartbeat_failures += 1;
+            return;
+        }
+        status.armed = arm;
+        status.last_heartbeat = Some(Utc::now());
+        match (was_armed, arm) {
+            (false, true) => info!("倒计时撤单已启用"),
+            (true, false) => warn!("紧急停止中，倒计时撤单已取消"),
+            _ => {}
+        }
+    }
+
+    /// 为所有交易对设置倒计时，全部成功时返回true
+    async fn send_countdown(&self, countdown: Duration) -> bool {
+        let mut succeeded = true;
+        for symbol in &self.symbols {
+            let error = match self.rest_client.countdown_cancel_all(symbol, countdown).await {
+                Ok(response) if response.get("code").is_none() => continue,
+                Ok(response) => response.to_string(),
+                Err(e) => e.to_string(),
+            };
+            warn!("{symbol}倒计时撤单设置失败: {error}");
+            succeeded = false;
+        }
+        succeeded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::binance::futures::config::BinanceFuturesConfig;
+    use crate::connectors::test_support::{MockHttpServer, MockResponse};
+
+    /// 每个请求中的 `symbol=..&countdownTime=..`
+    fn countdowns(server: &MockHttpServer) -> Vec<String> {
+        server
+            .requests()
+            .iter()
+            .filter_map(|request| {
+                let params = format!("{}&{}", request.query(), request.body);
+                let start = params.find("symbol=")?;
+                Some(params[start..].split('&').take(2).collect::<Vec<_>>().join("&"))
+            })
+            .collect()
+    }
+
+    fn rest_client(url: String) -> Arc<BinanceFuturesRestClient> {
+        let config = BinanceFuturesConfig {
+            api_key: Some("test-key".to_string()),
+            secret_key: Some("test-secret".to_string()),
+            ..BinanceFuturesConfig::default()
+        };
+        Arc::new(BinanceFuturesRestClient::new(config).with_base_url(url))
+    }
+
+    #[tokio::test]
+    async fn test_switch_follows_emergency_stop() {
+        let server = MockHttpServer::respond_with(MockResponse::ok(r#"{"symbol":"BTCUSDT","countdownTime":"120000"}"#)).await;
+        let emergency_stop = EmergencyStop::new();
+        let switch = DeadMansSwitch::new(rest_client(server.url.clone()), emergency_stop.clone(), vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()])
+            .with_timing(Duration::from_secs(120), Duration::from_secs(30));
+
+        switch.heartbeat().await;
+        let status = switch.status().await;
+        assert!(status.armed && status.last_heartbeat.is_some());
+        assert_eq!(countdowns(&server), ["symbol=BTCUSDT&countdownTime=120000", "symbol=ETHUSDT&countdownTime=120000"]);
+
+        // 紧急停止：取消倒计时，之后的心跳不再发送请求
+        emergency_stop.trigger_emergency_stop("测试").await.unwrap();
+        switch.heartbeat().await;
+        switch.heartbeat().await;
+        assert!(!switch.status().await.armed);
+        assert_eq!(countdowns(&server)[2..], ["symbol=BTCUSDT&countdownTime=0", "symbol=ETHUSDT&countdownTime=0"]);
+
+        // 解除后重新启用
+        emergency_stop.clear_emergency_stop().await.unwrap();
+        switch.heartbeat().await;
+        assert!(switch.status().await.armed);
+        assert_eq!(server.requests().len(), 6);
+        assert_eq!(switch.status().await.heartbeat_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_task_and_stop() {
+        let server = MockHttpServer::respond_with(MockResponse::ok(r#"{"symbol":"BTCUSDT","countdownTime":"200"}"#)).await;
+        let switch = DeadMansSwitch::new(rest_client(server.url.clone()), EmergencyStop::new(), vec!["BTCUSDT".to_string()])
+            .with_timing(Duration::from_millis(200), Duration::from_millis(50));
+
+        switch.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(130)).await;
+        assert!(switch.status().await.armed);
+        switch.stop().await;
+
+        let requests = countdowns(&server);
+        assert!(requests.len() >= 3, "{requests:?}");
+        assert!(requests[..requests.len() - 1].iter().all(|r| r == "symbol=BTCUSDT&countdownTime=200"));
+        // 正常停止时取消倒计时
+        assert_eq!(requests.last().unwrap(), "symbol=BTCUSDT&countdownTime=0");
+        assert!(!switch.status().await.armed);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_heartbeat_counts_as_failure() {
+        let server = MockHttpServer::respond_with(MockResponse::ok(r#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#)).await;
+        let switch = DeadMansSwitch::new(rest_client(server.url.clone()), EmergencyStop::new(), vec!["BTCUSDT".to_string()]);
+
+        switch.heartbeat().await;
+        let status = switch.status().await;
+        assert_eq!((status.armed, status.heartbeat_failures, status.last_heartbeat), (false, 1, None));
+
+        // 心跳间隔超过倒计时一半时拒绝启动
+        let too_slow = switch.clone().with_timing(Duration::from_secs(10), Duration::from_secs(6));
+        assert!(matches!(too_slow.start().await, Err(AppError::ConfigError(_))));
+    }
+}
@@ -341,6 +341,23 @@ impl BinanceFuturesMessageParser {
     
     /// 解析标记价格更新
     fn parse_mark_price_update(data: &Value) -> Result<ParsedMessage> {
+        let mark_price_update = Self::mark_price_from_json(data)?;
+        Ok(ParsedMessage::MarketData(MarketDataEvent::MarkPriceUpdate(mark_price_update)))
+    }
+    
+    /// 解析标记价格推送，兼容单个交易对（`<symbol>@markPrice`）与全市场数组（`!markPrice@arr`），
+    /// 无法解析的条目会被跳过
+    pub fn parse_mark_price_updates(data: &Value) -> Vec<MarkPriceUpdate> {
+        match data.as_array() {
+            Some(items) => items
+                .iter()
+                .filter_map(|item| Self::mark_price_from_json(item).ok())
+                .collect(),
+            None => Self::mark_price_from_json(data).into_iter().collect(),
+        }
+    }
+    
+    fn mark_price_from_json(data: &Value) -> Result<MarkPriceUpdate> {
         let symbol = data.get("s")
             .and_then(|s| s.as_str())
             .ok_or_else(|| AppError::ParseError("缺少交易对字段".to_string()))?;
@@ -364,15 +381,13 @@ impl BinanceFuturesMessageParser {
             .and_then(|t| t.as_i64())
             .unwrap_or(0);
         
-        let mark_price_update = MarkPriceUpdate {
+        Ok(MarkPriceUpdate {
             symbol: symbol.to_string(),
             mark_price,
             index_price,
             funding_rate,
             next_funding_time,
-        };
-        
-        Ok(ParsedMessage::MarketData(MarketDataEvent::MarkPriceUpdate(mark_price_update)))
+        })
     }
     
     /// 解析持仓量更新
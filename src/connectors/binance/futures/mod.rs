@@ -6,25 +6,31 @@
 pub mod connector;
 pub mod websocket;
 pub mod rest_api;
+pub mod ws_api;
 pub mod message_parser;
 pub mod config;
 pub mod risk_manager;
 pub mod cache;
 pub mod performance_monitor;
+pub mod orderbook;
 pub mod test_framework;
 pub mod advanced_features;
+pub mod dead_mans_switch;
 
 // 重新导出主要类型
 pub use connector::BinanceFuturesConnector;
 pub use websocket::BinanceFuturesWebSocketHandler;
-pub use rest_api::BinanceFuturesRestClient;
+pub use rest_api::{BinanceFuturesRestClient, BatchOrderResult};
+pub use ws_api::BinanceFuturesWsApiClient;
 pub use message_parser::BinanceFuturesMessageParser;
 pub use config::{BinanceFuturesConfig, BinanceFuturesConfigBuilder, MarginType, PositionMode, PositionSide, FuturesOrderType, TimeInForce};
 pub use risk_manager::RiskManager;
 pub use cache::MarketDataCache;
-pub use performance_monitor::PerformanceMonitor;
+pub use performance_monitor::{PerformanceMonitor, OrderBookSyncStats};
+pub use orderbook::{FuturesOrderBookManager, FuturesOrderBookBuilder};
 pub use test_framework::{TestScenarioBuilder, TestEnvironment, MockMarketDataGenerator, MockTradeExecutor};
 pub use advanced_features::{AlgoTradingEngine, SmartRouter, AlgoStrategy, AlgoOrder};
+pub use dead_mans_switch::{DeadMansSwitch, DeadMansSwitchStatus};
 
 // 期货特有的常量
 pub mod constants {
@@ -36,6 +42,10 @@ pub mod constants {
     pub const BINANCE_FUTURES_API_URL: &str = "https://fapi.binance.com";
     pub const BINANCE_FUTURES_TESTNET_API_URL: &str = "https://testnet.binancefuture.com";
     
+    // Binance期货WebSocket API（下单）URLs
+    pub const BINANCE_FUTURES_WS_API_URL: &str = "wss://ws-fapi.binance.com/ws-fapi/v1";
+    pub const BINANCE_FUTURES_TESTNET_WS_API_URL: &str = "wss://testnet.binancefuture.com/ws-fapi/v1";
+    
     // API版本常量
     pub const FUTURES_API_VERSION: &str = "v1";
     pub const FUTURES_API_VERSION_V2: &str = "v2";
@@ -69,8 +79,10 @@ pub mod constants {
     pub const MAX_LEVERAGE: u8 = 125;
     pub const DEFAULT_MARGIN_TYPE: &str = "ISOLATED"; // ISOLATED 或 CROSSED
     pub const DEFAULT_POSITION_SIDE: &str = "BOTH"; // BOTH, LONG, SHORT
+    pub const MAX_BATCH_ORDERS: usize = 5; // 批量下单每次最多订单数
     
     // 速率限制
     pub const FUTURES_RATE_LIMIT_PER_MINUTE: u32 = 2400;
     pub const FUTURES_ORDER_RATE_LIMIT: u32 = 300; // 每10秒
+    pub const FUTURES_ORDER_RATE_LIMIT_PER_MINUTE: u32 = 1200;
 }
\ No newline at end of file
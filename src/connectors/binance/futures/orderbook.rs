@@ -0,0 +1,631 @@
+//! Binance期货本地订单簿构建模块
+//!
+//! 基于REST `/fapi/v1/depth` 快照与 `@depth@100ms` 增量事件维护U本位合约的完整订单簿。
+//! 期货的同步规则与现货不同，需要使用 `pu` 字段校验连续性：
+//! 1. 先缓存增量事件，再拉取快照；
+//! 2. 丢弃 `u < lastUpdateId` 的事件；
+//! 3. 第一个应用的事件需满足 `U <= lastUpdateId <= u`；
+//! 4. 之后每个事件的 `pu` 必须等于上一个事件的 `u`，否则视为缺口并重新同步。
+
+use crate::connectors::binance::futures::rest_api::BinanceFuturesRestClient;
+use crate::connectors::binance::futures::cache::MarketDataCache;
+use crate::connectors::binance::futures::performance_monitor::PerformanceMonitor;
+use crate::connectors::common::local_orderbook::{LocalOrderBook, parse_price_levels};
+use crate::exchange_types::{Exchange, StandardOrderBook};
+use crate::types::market_data::{DepthUpdate, MarketDataEvent};
+use crate::core::AppError;
+
+// 定义Result类型别名
+pub type Result<T> = std::result::Result<T, AppError>;
+
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
+use log::{info, warn, error, debug};
+
+/// 快照请求的默认档位数量
+pub const DEFAULT_FUTURES_SNAPSHOT_LIMIT: u16 = 1000;
+
+/// 写入缓存和推送的默认档位数量
+pub const DEFAULT_FUTURES_PUBLISH_DEPTH: usize = 100;
+
+/// 等待快照期间最多缓存的增量事件数量
+const MAX_BUFFERED_EVENTS: usize = 10_000;
+
+/// 单次重新同步最多拉取快照的次数
+const MAX_SNAPSHOT_ATTEMPTS: u32 = 5;
+
+/// 快照重试间隔
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// 期货深度增量事件
+#[derive(Debug, Clone)]
+pub struct FuturesDepthEvent {
+    pub symbol: String,
+    pub first_update_id: i64,
+    pub final_update_id: i64,
+    pub prev_final_update_id: i64,
+    pub event_time: i64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl FuturesDepthEvent {
+    /// 从 `depthUpdate` 事件解析，缺少 `pu` 字段时返回None
+    pub fn from_json(data: &Value) -> Option<Self> {
+        if data.get("e").and_then(|e| e.as_str()) != Some("depthUpdate") {
+            return None;
+        }
+
+        Some(Self {
+            symbol: data.get("s")?.as_str()?.to_uppercase(),
+            first_update_id: data.get("U")?.as_i64()?,
+            final_update_id: data.get("u")?.as_i64()?,
+            prev_final_update_id: data.get("pu")?.as_i64()?,
+            event_time: data.get("E").and_then(|e| e.as_i64()).unwrap_or(0),
+            bids: parse_price_levels(data.get("b")),
+            asks: parse_price_levels(data.get("a")),
+        })
+    }
+}
+
+/// 期货REST深度快照
+#[derive(Debug, Clone)]
+pub struct FuturesDepthSnapshot {
+    pub last_update_id: i64,
+    pub event_time: i64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl FuturesDepthSnapshot {
+    /// 从 `/fapi/v1/depth` 响应解析
+    pub fn from_json(data: &Value) -> Option<Self> {
+        Some(Self {
+            last_update_id: data.get("lastUpdateId")?.as_i64()?,
+            event_time: data.get("E").and_then(|e| e.as_i64()).unwrap_or(0),
+            bids: parse_price_levels(data.get("bids")),
+            asks: parse_price_levels(data.get("asks")),
+        })
+    }
+}
+
+/// 应用增量事件的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuturesDiffOutcome {
+    /// 已应用到订单簿
+    Applied,
+    /// 尚未同步，事件已缓存；`snapshot_required`为true时需要拉取快照
+    Buffered { snapshot_required: bool },
+    /// 事件早于当前订单簿，已丢弃
+    Stale,
+    /// 连续性校验失败，订单簿已失效，需要拉取快照
+    Gap { expected: i64, received: i64 },
+}
+
+/// 应用快照的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FuturesSnapshotOutcome {
+    /// 同步完成，`replayed`为回放的缓存事件数量
+    Synced { replayed: usize },
+    /// 快照早于缓存事件，无法衔接，需要重新拉取
+    SnapshotTooOld,
+    /// 缓存事件之间存在缺口，需要重新拉取
+    Gap { expected: i64, received: i64 },
+}
+
+/// 单个交易对的期货订单簿构建器
+#[derive(Debug)]
+pub struct FuturesOrderBookBuilder {
+    book: LocalOrderBook,
+    synced: bool,
+    /// 快照已应用但尚未衔接第一条增量事件
+    awaiting_first_event: bool,
+    snapshot_in_flight: bool,
+    buffer: VecDeque<FuturesDepthEvent>,
+    resync_count: u64,
+    gap_detected_at: Option<Instant>,
+}
+
+impl FuturesOrderBookBuilder {
+    /// 创建未同步的构建器
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            book: LocalOrderBook::new(symbol),
+            synced: false,
+            awaiting_first_event: false,
+            snapshot_in_flight: false,
+            buffer: VecDeque::new(),
+            resync_count: 0,
+            gap_detected_at: None,
+        }
+    }
+
+    /// 当前订单簿
+    pub fn book(&self) -> &LocalOrderBook {
+        &self.book
+    }
+
+    /// 是否已与交易所同步
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// 因缺口触发的重新同步次数
+    pub fn resync_count(&self) -> u64 {
+        self.resync_count
+    }
+
+    /// 应用一条增量事件
+    pub fn apply_diff(&mut self, event: FuturesDepthEvent) -> FuturesDiffOutcome {
+        if !self.synced {
+            if self.buffer.len() >= MAX_BUFFERED_EVENTS {
+                self.buffer.pop_front();
+            }
+            self.buffer.push_back(event);
+            let snapshot_required = !self.snapshot_in_flight;
+            self.snapshot_in_flight = true;
+            return FuturesDiffOutcome::Buffered { snapshot_required };
+        }
+
+        let last_update_id = self.book.last_update_id();
+
+        if self.awaiting_first_event {
+            // 第一条事件需要覆盖快照的lastUpdateId
+            if event.final_update_id < last_update_id {
+                return FuturesDiffOutcome::Stale;
+            }
+            if event.first_update_id > last_update_id {
+                let outcome = FuturesDiffOutcome::Gap {
+                    expected: last_update_id,
+                    received: event.first_update_id,
+                };
+                self.begin_resync(event);
+                return outcome;
+            }
+            self.awaiting_first_event = false;
+        } else {
+            if event.final_update_id <= last_update_id {
+                return FuturesDiffOutcome::Stale;
+            }
+            if event.prev_final_update_id != last_update_id {
+                let outcome = FuturesDiffOutcome::Gap {
+                    expected: last_update_id,
+                    received: event.prev_final_update_id,
+                };
+                self.begin_resync(event);
+                return outcome;
+            }
+        }
+
+        self.book.apply_diff(&event.bids, &event.asks, event.final_update_id, event.event_time);
+        FuturesDiffOutcome::Applied
+    }
+
+    /// 应用快照并回放缓存的事件
+    pub fn apply_snapshot(&mut self, snapshot: FuturesDepthSnapshot) -> FuturesSnapshotOutcome {
+        // 丢弃早于快照的缓存事件
+        while self.buffer.front().is_some_and(|e| e.final_update_id < snapshot.last_update_id) {
+            self.buffer.pop_front();
+        }
+
+        if let Some(first) = self.buffer.front() {
+            if first.first_update_id > snapshot.last_update_id {
+                return FuturesSnapshotOutcome::SnapshotTooOld;
+            }
+        }
+
+        let event_time = self.buffer.front().map(|e| e.event_time).unwrap_or(snapshot.event_time);
+        self.book.reset(&snapshot.bids, &snapshot.asks, snapshot.last_update_id, event_time);
+        self.synced = true;
+        self.awaiting_first_event = true;
+        self.snapshot_in_flight = false;
+
+        let mut replayed = 0;
+        while let Some(event) = self.buffer.pop_front() {
+            match self.apply_diff(event) {
+                FuturesDiffOutcome::Applied => replayed += 1,
+                FuturesDiffOutcome::Gap { expected, received } => {
+                    return FuturesSnapshotOutcome::Gap { expected, received };
+                }
+                _ => {}
+            }
+        }
+
+        FuturesSnapshotOutcome::Synced { replayed }
+    }
+
+    /// 快照拉取失败时释放标记，下一条事件会重新触发拉取
+    pub fn snapshot_failed(&mut self) {
+        self.snapshot_in_flight = false;
+    }
+
+    /// 连接重建后丢弃现有状态，等待重新同步
+    pub fn invalidate(&mut self) {
+        self.book.clear();
+        self.synced = false;
+        self.awaiting_first_event = false;
+        self.snapshot_in_flight = false;
+        self.buffer.clear();
+        self.gap_detected_at = None;
+    }
+
+    /// 取出从发现缺口到完成同步的耗时
+    pub fn take_resync_latency(&mut self) -> Option<Duration> {
+        self.gap_detected_at.take().map(|t| t.elapsed())
+    }
+
+    fn begin_resync(&mut self, event: FuturesDepthEvent) {
+        self.book.clear();
+        self.synced = false;
+        self.awaiting_first_event = false;
+        self.snapshot_in_flight = true;
+        self.buffer.clear();
+        self.buffer.push_back(event);
+        self.resync_count += 1;
+        self.gap_detected_at.get_or_insert_with(Instant::now);
+    }
+}
+
+/// Binance期货订单簿管理器
+///
+/// 为每个跟踪的交易对维护构建器，同步后的订单簿写入`MarketDataCache`，
+/// 缺口、重新同步次数和耗时记录到`PerformanceMonitor`。
+#[derive(Clone)]
+pub struct FuturesOrderBookManager {
+    rest_client: Arc<BinanceFuturesRestClient>,
+    builders: Arc<Mutex<HashMap<String, FuturesOrderBookBuilder>>>,
+    cache: MarketDataCache,
+    monitor: PerformanceMonitor,
+    snapshot_limit: u16,
+    publish_depth: usize,
+}
+
+impl FuturesOrderBookManager {
+    /// 创建订单簿管理器
+    pub fn new(
+        rest_client: Arc<BinanceFuturesRestClient>,
+        cache: MarketDataCache,
+        monitor: PerformanceMonitor,
+    ) -> Self {
+        Self {
+            rest_client,
+            builders: Arc::new(Mutex::new(HashMap::new())),
+            cache,
+            monitor,
+            snapshot_limit: DEFAULT_FUTURES_SNAPSHOT_LIMIT,
+            publish_depth: DEFAULT_FUTURES_PUBLISH_DEPTH,
+        }
+    }
+
+    /// 开始跟踪交易对
+    pub async fn track_symbol(&self, symbol: &str) {
+        let symbol = symbol.to_uppercase();
+        self.builders.lock().await
+            .entry(symbol.clone())
+            .or_insert_with(|| FuturesOrderBookBuilder::new(&symbol));
+    }
+
+    /// 停止跟踪交易对
+    pub async fn untrack_symbol(&self, symbol: &str) {
+        let symbol = symbol.to_uppercase();
+        self.builders.lock().await.remove(&symbol);
+        self.cache.remove_depth(&symbol).await;
+    }
+
+    /// 交易对是否由本地订单簿维护
+    pub async fn is_tracking(&self, symbol: &str) -> bool {
+        self.builders.lock().await.contains_key(&symbol.to_uppercase())
+    }
+
+    /// 市场数据缓存
+    pub fn cache(&self) -> &MarketDataCache {
+        &self.cache
+    }
+
+    /// 性能监控器
+    pub fn monitor(&self) -> &PerformanceMonitor {
+        &self.monitor
+    }
+
+    /// 处理一条增量事件
+    pub async fn handle_diff_event(
+        &self,
+        event: FuturesDepthEvent,
+        data_sender: &Option<mpsc::UnboundedSender<MarketDataEvent>>,
+    ) {
+        let symbol = event.symbol.clone();
+        let first_update_id = event.first_update_id;
+        if event.event_time > 0 {
+            let latency_ms = self.rest_client.clock().latency_ms(event.event_time);
+            self.monitor.record_market_data_latency(&symbol, latency_ms as f64).await;
+        }
+
+        let (outcome, update) = {
+            let mut builders = self.builders.lock().await;
+            let Some(builder) = builders.get_mut(&symbol) else { return };
+            let outcome = builder.apply_diff(event);
+            let update = match outcome {
+                FuturesDiffOutcome::Applied => Some(builder.book().to_depth_update(first_update_id, self.publish_depth)),
+                _ => None,
+            };
+            (outcome, update)
+        };
+
+        match outcome {
+            FuturesDiffOutcome::Applied => {
+                if let Some(update) = update {
+                    self.publish(update, data_sender).await;
+                }
+            }
+            FuturesDiffOutcome::Buffered { snapshot_required: true } => {
+                info!("{symbol} 开始同步期货本地订单簿");
+                self.spawn_resync(symbol, data_sender.clone());
+            }
+            FuturesDiffOutcome::Gap { expected, received } => {
+                warn!("{symbol} 期货深度序列缺口: 期望pu={expected}, 实际pu={received}，重新同步");
+                self.cache.remove_depth(&symbol).await;
+                self.monitor.record_orderbook_gap(&symbol).await;
+                self.spawn_resync(symbol, data_sender.clone());
+            }
+            FuturesDiffOutcome::Buffered { snapshot_required: false } | FuturesDiffOutcome::Stale => {}
+        }
+    }
+
+    /// 应用快照，同步成功时写入缓存并返回最新深度
+    pub async fn apply_snapshot(&self, symbol: &str, snapshot: FuturesDepthSnapshot) -> (FuturesSnapshotOutcome, Option<DepthUpdate>) {
+        let (outcome, update, latency) = {
+            let mut builders = self.builders.lock().await;
+            let Some(builder) = builders.get_mut(symbol) else {
+                return (FuturesSnapshotOutcome::SnapshotTooOld, None);
+            };
+            let outcome = builder.apply_snapshot(snapshot);
+            match outcome {
+                FuturesSnapshotOutcome::Synced { .. } => {
+                    let last_update_id = builder.book().last_update_id();
+                    let update = builder.book().to_depth_update(last_update_id, self.publish_depth);
+                    (outcome, Some(update), builder.take_resync_latency())
+                }
+                _ => (outcome, None, None),
+            }
+        };
+
+        if let Some(ref update) = update {
+            self.cache.update_depth(symbol, update.clone()).await;
+        }
+        if let Some(latency) = latency {
+            self.monitor.record_orderbook_resync(symbol, latency).await;
+        }
+
+        (outcome, update)
+    }
+
+    /// 获取已同步的订单簿
+    pub async fn get_orderbook(&self, symbol: &str) -> Option<StandardOrderBook> {
+        let builders = self.builders.lock().await;
+        builders.get(&symbol.to_uppercase())
+            .filter(|builder| builder.is_synced())
+            .map(|builder| builder.book().to_standardized(Exchange::Binance, self.publish_depth))
+    }
+
+    /// 获取交易对因缺口触发的重新同步次数
+    pub async fn get_resync_count(&self, symbol: &str) -> u64 {
+        let builders = self.builders.lock().await;
+        builders.get(&symbol.to_uppercase()).map(|builder| builder.resync_count()).unwrap_or(0)
+    }
+
+    /// 连接重建时使所有订单簿失效
+    pub async fn invalidate_all(&self) {
+        let mut builders = self.builders.lock().await;
+        for (symbol, builder) in builders.iter_mut() {
+            builder.invalidate();
+            self.cache.remove_depth(symbol).await;
+        }
+    }
+
+    fn spawn_resync(&self, symbol: String, data_sender: Option<mpsc::UnboundedSender<MarketDataEvent>>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.resync(&symbol, &data_sender).await;
+        });
+    }
+
+    /// 拉取快照并回放缓存事件，直到同步成功或重试次数耗尽
+    async fn resync(&self, symbol: &str, data_sender: &Option<mpsc::UnboundedSender<MarketDataEvent>>) {
+        for attempt in 1..=MAX_SNAPSHOT_ATTEMPTS {
+            let snapshot = match self.fetch_snapshot(symbol).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!("{symbol} 获取期货深度快照失败 (尝试 {attempt}/{MAX_SNAPSHOT_ATTEMPTS}): {e}");
+                    tokio::time::sleep(SNAPSHOT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+
+            match self.apply_snapshot(symbol, snapshot).await {
+                (FuturesSnapshotOutcome::Synced { replayed }, update) => {
+                    info!("{symbol} 期货本地订单簿已同步，回放 {replayed} 条缓存事件");
+                    if let Some(update) = update {
+                        Self::send_update(update, data_sender);
+                    }
+                    return;
+                }
+                (FuturesSnapshotOutcome::SnapshotTooOld, _) => {
+                    debug!("{symbol} 期货快照无法衔接缓存事件，重新拉取");
+                    tokio::time::sleep(SNAPSHOT_RETRY_DELAY).await;
+                }
+                (FuturesSnapshotOutcome::Gap { expected, received }, _) => {
+                    warn!("{symbol} 期货缓存事件存在缺口: 期望pu={expected}, 实际pu={received}，重新拉取快照");
+                    self.monitor.record_orderbook_gap(symbol).await;
+                }
+            }
+        }
+
+        error!("{symbol} 多次尝试后仍未能同步期货订单簿，等待下一条事件重试");
+        if let Some(builder) = self.builders.lock().await.get_mut(symbol) {
+            builder.snapshot_failed();
+        }
+    }
+
+    async fn fetch_snapshot(&self, symbol: &str) -> Result<FuturesDepthSnapshot> {
+        let data = self.rest_client.get_depth(symbol, Some(self.snapshot_limit)).await?;
+        FuturesDepthSnapshot::from_json(&data)
+            .ok_or_else(|| AppError::ParseError("期货深度快照缺少lastUpdateId".to_string()))
+    }
+
+    async fn publish(&self, update: DepthUpdate, data_sender: &Option<mpsc::UnboundedSender<MarketDataEvent>>) {
+        self.cache.update_depth(&update.symbol, update.clone()).await;
+        Self::send_update(update, data_sender);
+    }
+
+    fn send_update(update: DepthUpdate, data_sender: &Option<mpsc::UnboundedSender<MarketDataEvent>>) {
+        if let Some(sender) = data_sender {
+            if let Err(e) = sender.send(MarketDataEvent::DepthUpdate(update)) {
+                error!("发送深度数据失败: {e:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::binance::futures::config::BinanceFuturesConfig;
+    use crate::connectors::binance::futures::performance_monitor::MetricType;
+    use serde_json::json;
+
+    fn diff(first: i64, last: i64, prev: i64, bids: Value, asks: Value) -> FuturesDepthEvent {
+        FuturesDepthEvent::from_json(&json!({
+            "e": "depthUpdate", "E": 1700000000000i64, "T": 1700000000000i64, "s": "BTCUSDT",
+            "U": first, "u": last, "pu": prev, "b": bids, "a": asks
+        })).unwrap()
+    }
+
+    fn snapshot(last_update_id: i64) -> FuturesDepthSnapshot {
+        FuturesDepthSnapshot::from_json(&json!({
+            "lastUpdateId": last_update_id,
+            "E": 1700000000000i64,
+            "bids": [["100.0", "1.0"], ["99.0", "2.0"]],
+            "asks": [["101.0", "1.0"], ["102.0", "2.0"]]
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_event_without_pu_is_rejected() {
+        let data = json!({"e": "depthUpdate", "s": "BTCUSDT", "U": 1, "u": 2, "b": [], "a": []});
+        assert!(FuturesDepthEvent::from_json(&data).is_none());
+    }
+
+    #[test]
+    fn test_buffer_until_snapshot_then_replay() {
+        let mut builder = FuturesOrderBookBuilder::new("BTCUSDT");
+
+        assert_eq!(builder.apply_diff(diff(90, 95, 89, json!([]), json!([]))), FuturesDiffOutcome::Buffered { snapshot_required: true });
+        assert_eq!(builder.apply_diff(diff(96, 102, 95, json!([["100.0", "0"]]), json!([]))), FuturesDiffOutcome::Buffered { snapshot_required: false });
+        assert_eq!(builder.apply_diff(diff(103, 104, 102, json!([]), json!([["100.5", "3"]]))), FuturesDiffOutcome::Buffered { snapshot_required: false });
+
+        // 快照lastUpdateId=100：第一个事件被丢弃，第二个事件满足 U <= 100 <= u
+        assert_eq!(builder.apply_snapshot(snapshot(100)), FuturesSnapshotOutcome::Synced { replayed: 2 });
+        assert!(builder.is_synced());
+        assert_eq!(builder.book().last_update_id(), 104);
+        assert_eq!(builder.book().best_bid(), Some(99.0));
+        assert_eq!(builder.book().best_ask(), Some(100.5));
+    }
+
+    #[test]
+    fn test_snapshot_that_cannot_bridge_buffer_is_rejected() {
+        let mut builder = FuturesOrderBookBuilder::new("BTCUSDT");
+        builder.apply_diff(diff(200, 210, 199, json!([]), json!([])));
+
+        assert_eq!(builder.apply_snapshot(snapshot(150)), FuturesSnapshotOutcome::SnapshotTooOld);
+        assert!(!builder.is_synced());
+        assert_eq!(builder.apply_snapshot(snapshot(205)), FuturesSnapshotOutcome::Synced { replayed: 1 });
+        assert_eq!(builder.book().last_update_id(), 210);
+    }
+
+    #[test]
+    fn test_pu_mismatch_triggers_resync() {
+        let mut builder = FuturesOrderBookBuilder::new("BTCUSDT");
+        builder.apply_diff(diff(99, 101, 98, json!([]), json!([])));
+        builder.apply_snapshot(snapshot(100));
+
+        // 期货的U不一定等于上一个u+1，只要pu衔接即可
+        assert_eq!(builder.apply_diff(diff(108, 110, 101, json!([]), json!([]))), FuturesDiffOutcome::Applied);
+        assert_eq!(builder.apply_diff(diff(105, 109, 104, json!([]), json!([]))), FuturesDiffOutcome::Stale);
+        assert_eq!(builder.apply_diff(diff(115, 120, 112, json!([["200.5", "2"]]), json!([]))), FuturesDiffOutcome::Gap { expected: 110, received: 112 });
+        assert!(!builder.is_synced());
+        assert_eq!(builder.resync_count(), 1);
+        assert!(builder.book().is_empty());
+
+        // 缺口后的事件已缓存，新快照可以衔接
+        assert_eq!(builder.apply_snapshot(snapshot(118)), FuturesSnapshotOutcome::Synced { replayed: 1 });
+        assert_eq!(builder.book().last_update_id(), 120);
+        assert_eq!(builder.book().best_bid(), Some(200.5));
+        assert!(builder.take_resync_latency().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_manager_serves_synced_book_from_cache() {
+        let rest_client = Arc::new(BinanceFuturesRestClient::new(BinanceFuturesConfig::default()));
+        let manager = FuturesOrderBookManager::new(rest_client, MarketDataCache::new(), PerformanceMonitor::new());
+        manager.track_symbol("btcusdt").await;
+        assert!(manager.is_tracking("BTCUSDT").await);
+
+        // 模拟已同步后出现缺口，再由新快照恢复
+        {
+            let mut builders = manager.builders.lock().await;
+            let builder = builders.get_mut("BTCUSDT").unwrap();
+            builder.apply_diff(diff(99, 101, 98, json!([]), json!([])));
+        }
+        let (outcome, _) = manager.apply_snapshot("BTCUSDT", snapshot(100)).await;
+        assert_eq!(outcome, FuturesSnapshotOutcome::Synced { replayed: 1 });
+        assert_eq!(manager.cache().get_depth("BTCUSDT").await.unwrap().final_update_id, 101);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sender = Some(tx);
+        manager.handle_diff_event(diff(102, 103, 101, json!([["100.5", "1"]]), json!([])), &sender).await;
+        match rx.try_recv().unwrap() {
+            MarketDataEvent::DepthUpdate(update) => assert_eq!(update.best_bid_price, 100.5),
+            other => panic!("意外的事件: {other:?}"),
+        }
+
+        {
+            let mut builders = manager.builders.lock().await;
+            builders.get_mut("BTCUSDT").unwrap().apply_diff(diff(110, 112, 108, json!([]), json!([])));
+        }
+        let (outcome, _) = manager.apply_snapshot("BTCUSDT", snapshot(111)).await;
+        assert_eq!(outcome, FuturesSnapshotOutcome::Synced { replayed: 1 });
+        assert_eq!(manager.get_resync_count("BTCUSDT").await, 1);
+
+        let stats = manager.monitor().get_orderbook_sync_stats("BTCUSDT").await.unwrap();
+        assert_eq!(stats.resync_count, 1);
+        assert!(stats.last_resync_latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_diff_event_latency_uses_server_clock_offset() {
+        let rest_client = Arc::new(BinanceFuturesRestClient::new(BinanceFuturesConfig::default()));
+        // 服务器时钟比本地快2秒：offset = 2000ms，RTT 10ms
+        let local_us = chrono::Utc::now().timestamp_millis() * 1000;
+        rest_client.clock().record_sample(local_us - 10_000, local_us / 1000 + 1995, local_us);
+        assert_eq!(rest_client.clock().offset_ms(), 2000);
+
+        let manager = FuturesOrderBookManager::new(rest_client.clone(), MarketDataCache::new(), PerformanceMonitor::new());
+        manager.track_symbol("BTCUSDT").await;
+
+        // 事件时间按服务器时钟是30ms前，未校正时会得到 -1970ms
+        let event = FuturesDepthEvent::from_json(&json!({
+            "e": "depthUpdate", "E": rest_client.clock().now_ms() - 30, "T": 0, "s": "BTCUSDT",
+            "U": 1, "u": 2, "pu": 0, "b": [], "a": []
+        })).unwrap();
+        manager.handle_diff_event(event, &None).await;
+
+        let latencies = manager.monitor().get_recent_metrics(&MetricType::MarketDataLatency, Duration::from_secs(60)).await;
+        assert_eq!(latencies.len(), 1);
+        assert!((30.0..100.0).contains(&latencies[0].value), "延迟换算错误: {}", latencies[0].value);
+        assert_eq!(latencies[0].tags["symbol"], "BTCUSDT");
+    }
+}
@@ -31,6 +31,12 @@ pub enum MetricType {
     MemoryUsage,
     /// CPU使用
     CpuUsage,
+    /// 订单簿序列缺口
+    OrderBookGap,
+    /// 订单簿重新同步
+    OrderBookResync,
+    /// 订单簿重新同步耗时
+    OrderBookResyncLatency,
 }
 
 /// 性能指标数据点
@@ -90,6 +96,19 @@ impl PerformanceStats {
     }
 }
 
+/// 单个交易对的订单簿同步统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderBookSyncStats {
+    /// 检测到的序列缺口次数
+    pub gap_count: u64,
+    /// 完成的重新同步次数
+    pub resync_count: u64,
+    /// 最近一次重新同步耗时（毫秒）
+    pub last_resync_latency_ms: Option<f64>,
+    /// 最大重新同步耗时（毫秒）
+    pub max_resync_latency_ms: f64,
+}
+
 /// 延迟测量器
 #[derive(Debug)]
 pub struct LatencyMeasurer {
@@ -139,6 +158,8 @@ pub struct PerformanceMonitor {
     config: MonitorConfig,
     /// 告警阈值
     alert_thresholds: Arc<RwLock<HashMap<MetricType, AlertThreshold>>>,
+    /// 订单簿同步统计（按交易对）
+    orderbook_sync_stats: Arc<RwLock<HashMap<String, OrderBookSyncStats>>>,
 }
 
 /// 监控配置
@@ -204,6 +225,7 @@ impl PerformanceMonitor {
             stats_cache: Arc::new(RwLock::new(HashMap::new())),
             config: config.clone(),
             alert_thresholds: Arc::new(RwLock::new(HashMap::new())),
+            orderbook_sync_stats: Arc::new(RwLock::new(HashMap::new())),
         };
         
         // 启动定期统计计算任务
@@ -260,6 +282,50 @@ impl PerformanceMonitor {
         self.record_metric(metric_type, value, tags).await;
     }
     
+    /// 记录订单簿序列缺口
+    pub async fn record_orderbook_gap(&self, symbol: &str) {
+        self.orderbook_sync_stats.write().await
+            .entry(symbol.to_string())
+            .or_default()
+            .gap_count += 1;
+        
+        let tags = HashMap::from([("symbol".to_string(), symbol.to_string())]);
+        self.increment_counter(MetricType::OrderBookGap, tags).await;
+    }
+    
+    /// 记录订单簿重新同步完成及其耗时
+    pub async fn record_orderbook_resync(&self, symbol: &str, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        
+        {
+            let mut sync_stats = self.orderbook_sync_stats.write().await;
+            let entry = sync_stats.entry(symbol.to_string()).or_default();
+            entry.resync_count += 1;
+            entry.last_resync_latency_ms = Some(latency_ms);
+            entry.max_resync_latency_ms = entry.max_resync_latency_ms.max(latency_ms);
+        }
+        
+        let tags = HashMap::from([("symbol".to_string(), symbol.to_string())]);
+        self.increment_counter(MetricType::OrderBookResync, tags.clone()).await;
+        self.record_metric(MetricType::OrderBookResyncLatency, latency_ms, tags).await;
+    }
+    
+    /// 记录行情延迟：交易所事件时间（已按服务器时钟偏移换算为本地时间）到本地处理的间隔
+    pub async fn record_market_data_latency(&self, symbol: &str, latency_ms: f64) {
+        let tags = HashMap::from([("symbol".to_string(), symbol.to_string())]);
+        self.record_metric(MetricType::MarketDataLatency, latency_ms, tags).await;
+    }
+    
+    /// 获取交易对的订单簿同步统计
+    pub async fn get_orderbook_sync_stats(&self, symbol: &str) -> Option<OrderBookSyncStats> {
+        self.orderbook_sync_stats.read().await.get(symbol).cloned()
+    }
+    
+    /// 获取所有交易对的订单簿同步统计
+    pub async fn get_all_orderbook_sync_stats(&self) -> HashMap<String, OrderBookSyncStats> {
+        self.orderbook_sync_stats.read().await.clone()
+    }
+    
     /// 获取性能统计
     pub async fn get_stats(&self, metric_type: &MetricType) -> Option<PerformanceStats> {
         self.stats_cache.read().await.get(metric_type).cloned()
@@ -441,6 +507,7 @@ impl Clone for PerformanceMonitor {
             stats_cache: Arc::clone(&self.stats_cache),
             config: self.config.clone(),
             alert_thresholds: Arc::clone(&self.alert_thresholds),
+            orderbook_sync_stats: Arc::clone(&self.orderbook_sync_stats),
         }
     }
 }
@@ -515,4 +582,25 @@ mod tests {
         
         // 这里应该触发告警，但在测试中我们只验证不会panic
     }
+    
+    #[tokio::test]
+    async fn test_orderbook_sync_stats() {
+        let monitor = PerformanceMonitor::new();
+        
+        monitor.record_orderbook_gap("BTCUSDT").await;
+        monitor.record_orderbook_resync("BTCUSDT", Duration::from_millis(30)).await;
+        monitor.record_orderbook_resync("BTCUSDT", Duration::from_millis(10)).await;
+        
+        let stats = monitor.get_orderbook_sync_stats("BTCUSDT").await.unwrap();
+        assert_eq!(stats.gap_count, 1);
+        assert_eq!(stats.resync_count, 2);
+        assert_eq!(stats.last_resync_latency_ms, Some(10.0));
+        assert_eq!(stats.max_resync_latency_ms, 30.0);
+        assert!(monitor.get_orderbook_sync_stats("ETHUSDT").await.is_none());
+        
+        let latencies = monitor
+            .get_recent_metrics(&MetricType::OrderBookResyncLatency, Duration::from_secs(60))
+            .await;
+        assert_eq!(latencies.len(), 2);
+    }
 }
\ No newline at end of file
@@ -10,11 +10,20 @@ use crate::core::AppError;
 // 定义Result类型别名
 pub type Result<T> = std::result::Result<T, AppError>;
 
-use reqwest::{Client, Method, Response};
+use crate::connectors::common::rate_limiter::{RateLimitKind, RateLimitRule, RateLimiter, RequestCost};
+use crate::connectors::common::clock_sync::{ExchangeClock, ServerTimeSource};
+use crate::connectors::common::instruments::{positive_decimal_field, InstrumentSource, InstrumentSpec, InstrumentStatus};
+use crate::exchange_types::Exchange;
+use crate::connectors::binance::rest::urlencode;
+use crate::config::get_config;
+use crate::types::errors::ConnectorError;
+
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder};
 use serde_json::Value;
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
-use chrono::Utc;
+use std::sync::Arc;
 use std::time::Duration;
 
 type HmacSha256 = Hmac<Sha256>;
@@ -27,13 +36,19 @@ pub struct BinanceFuturesRestClient {
     config: BinanceFuturesConfig,
     /// API基础URL
     base_url: String,
+    /// 请求限速器（可与同一账户的其他客户端共享）
+    rate_limiter: Arc<RateLimiter>,
+    /// 服务器时钟，签名请求的timestamp按估计的偏移校正
+    clock: Arc<ExchangeClock>,
 }
 
 /// API响应结果
 #[derive(Debug)]
 pub struct ApiResponse<T> {
     pub data: T,
+    /// 当前窗口剩余的请求权重
     pub rate_limit_remaining: Option<u32>,
+    /// 请求权重窗口重置的时间（毫秒时间戳）
     pub rate_limit_reset: Option<u64>,
 }
 
@@ -56,6 +71,32 @@ pub struct OrderRequest {
     pub reduce_only: Option<bool>,
 }
 
+/// 批量下单中单个订单的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchOrderResult {
+    /// 下单成功，交易所返回的订单信息
+    Accepted(Value),
+    /// 被交易所拒绝
+    Rejected { code: i64, msg: String },
+}
+
+impl BatchOrderResult {
+    fn from_item(item: &Value) -> Self {
+        match item.get("code").and_then(|code| code.as_i64()) {
+            Some(code) if item.get("orderId").is_none() => BatchOrderResult::Rejected {
+                code,
+                msg: item.get("msg").and_then(|msg| msg.as_str()).unwrap_or_default().to_string(),
+            },
+            _ => BatchOrderResult::Accepted(item.clone()),
+        }
+    }
+    
+    /// 是否下单成功
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, BatchOrderResult::Accepted(_))
+    }
+}
+
 /// 杠杆调整请求
 #[derive(Debug, Clone)]
 pub struct LeverageRequest {
@@ -95,6 +136,12 @@ impl MarginType {
 impl BinanceFuturesRestClient {
     /// 创建新的REST API客户端
     pub fn new(config: BinanceFuturesConfig) -> Self {
+        let rate_limiter = Arc::new(Self::default_rate_limiter(&config));
+        Self::with_rate_limiter(config, rate_limiter)
+    }
+    
+    /// 创建与其他客户端共享限速器的REST API客户端
+    pub fn with_rate_limiter(config: BinanceFuturesConfig, rate_limiter: Arc<RateLimiter>) -> Self {
         let base_url = if config.testnet {
             BINANCE_FUTURES_TESTNET_API_URL.to_string()
         } else {
@@ -110,21 +157,47 @@ impl BinanceFuturesRestClient {
             client,
             config,
             base_url,
+            rate_limiter,
+            clock: Arc::new(ExchangeClock::new(Exchange::BinanceFutures, get_config().clock_sync.recv_window_ms)),
         }
     }
     
+    /// 按配置的额度创建限速器：请求权重/分钟、下单次数/10秒、下单次数/分钟
+    pub fn default_rate_limiter(config: &BinanceFuturesConfig) -> RateLimiter {
+        RateLimiter::new(Exchange::BinanceFutures, vec![
+            RateLimitRule::request_weight(Duration::from_secs(60), config.rate_limit_per_minute),
+            RateLimitRule::orders(Duration::from_secs(10), config.order_rate_limit_per_10s),
+            RateLimitRule::orders(Duration::from_secs(60), FUTURES_ORDER_RATE_LIMIT_PER_MINUTE),
+        ])
+    }
+    
+    /// 使用自定义API地址（例如本地模拟服务器）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+    
+    /// 请求限速器
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+    
+    /// 使用与其他客户端共享的服务器时钟
+    pub fn with_clock(mut self, clock: Arc<ExchangeClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// 服务器时钟
+    pub fn clock(&self) -> &Arc<ExchangeClock> {
+        &self.clock
+    }
+    
     /// 获取服务器时间
     pub async fn get_server_time(&self) -> Result<u64> {
         let url = format!("{}/fapi/v1/time", self.base_url);
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        let data: Value = response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))?;
+        let data = self.send_public_request(&url, RequestCost::weight(1)).await?.data;
         
         data.get("serverTime")
             .and_then(|t| t.as_u64())
@@ -135,14 +208,8 @@ impl BinanceFuturesRestClient {
     pub async fn get_exchange_info(&self) -> Result<Value> {
         let url = format!("{}{}", self.base_url, FUTURES_EXCHANGE_INFO_PATH);
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 获取深度数据
@@ -157,14 +224,8 @@ impl BinanceFuturesRestClient {
         url.push('?');
         url.push_str(&self.build_query_string(&params));
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(depth_weight(limit))).await
+            .map(|response| response.data)
     }
     
     /// 获取24小时价格统计
@@ -175,14 +236,8 @@ impl BinanceFuturesRestClient {
             url.push_str(&format!("?symbol={symbol}"));
         }
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(if symbol.is_some() { 1 } else { 40 })).await
+            .map(|response| response.data)
     }
     
     /// 获取标记价格
@@ -193,14 +248,8 @@ impl BinanceFuturesRestClient {
             url.push_str(&format!("?symbol={symbol}"));
         }
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 获取资金费率历史
@@ -221,34 +270,25 @@ impl BinanceFuturesRestClient {
         
         let url = format!("{}/fapi/v1/fundingRate?{}", self.base_url, self.build_query_string(&params));
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 获取持仓量
     pub async fn get_open_interest(&self, symbol: &str) -> Result<Value> {
         let url = format!("{}/fapi/v1/openInterest?symbol={}", self.base_url, symbol);
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_public_request(&url, RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 获取账户信息
     pub async fn get_account_info(&self) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
-        let mut params = vec![("timestamp", timestamp.to_string())];
+        let timestamp = self.clock.now_ms();
+        let mut params = vec![
+            ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
         
         let query_string = self.build_query_string(&params);
         let signature = self.sign(&query_string)?;
@@ -256,16 +296,17 @@ impl BinanceFuturesRestClient {
         
         let url = format!("{}/fapi/v2/account?{}", self.base_url, self.build_query_string(&params));
         
-        let response = self.send_signed_request(Method::GET, &url, None).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::GET, &url, None, RequestCost::weight(5)).await
+            .map(|response| response.data)
     }
     
     /// 获取持仓信息
     pub async fn get_position_info(&self, symbol: Option<&str>) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
-        let mut params = vec![("timestamp", timestamp.to_string())];
+        let timestamp = self.clock.now_ms();
+        let mut params = vec![
+            ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
         
         if let Some(symbol) = symbol {
             params.push(("symbol", symbol.to_string()));
@@ -277,21 +318,71 @@ impl BinanceFuturesRestClient {
         
         let url = format!("{}/fapi/v2/positionRisk?{}", self.base_url, self.build_query_string(&params));
         
-        let response = self.send_signed_request(Method::GET, &url, None).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::GET, &url, None, RequestCost::weight(5)).await
+            .map(|response| response.data)
     }
     
     /// 下单
     pub async fn place_order(&self, request: &OrderRequest) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let mut params = Self::order_params(request);
+        params.push(("timestamp", self.clock.now_ms().to_string()));
+        params.push(("recvWindow", self.clock.recv_window_ms().to_string()));
+        
+        let query_string = self.build_query_string(&params);
+        let signature = self.sign(&query_string)?;
+        params.push(("signature", signature));
+        
+        let url = format!("{}/fapi/v1/order", self.base_url);
+        let body = self.build_query_string(&params);
+        
+        self.send_signed_request(Method::POST, &url, Some(body), RequestCost::order(0)).await
+            .map(|response| response.data)
+    }
+    
+    /// 批量下单，一次最多5个订单
+    /// 
+    /// 结果与请求一一对应；单个订单被拒绝不影响其他订单。
+    pub async fn place_batch_orders(&self, requests: &[OrderRequest]) -> Result<Vec<BatchOrderResult>> {
+        if requests.is_empty() || requests.len() > MAX_BATCH_ORDERS {
+            return Err(AppError::ParseError(format!("批量下单需要1到{MAX_BATCH_ORDERS}个订单，实际{}个", requests.len())));
+        }
+        
+        let batch: Vec<Value> = requests.iter()
+            .map(|request| Value::Object(Self::order_params(request).into_iter()
+                .map(|(key, value)| (key.to_string(), Value::String(value)))
+                .collect()))
+            .collect();
+        let mut params = vec![
+            ("batchOrders", urlencode(&Value::Array(batch).to_string())),
+            ("timestamp", self.clock.now_ms().to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
+        
+        let query_string = self.build_query_string(&params);
+        let signature = self.sign(&query_string)?;
+        params.push(("signature", signature));
+        
+        let url = format!("{}/fapi/v1/batchOrders", self.base_url);
+        let body = self.build_query_string(&params);
+        let cost = RequestCost { weight: 5, orders: requests.len() as u32 };
+        
+        let data = self.send_signed_request(Method::POST, &url, Some(body), cost).await?.data;
+        let items = data.as_array()
+            .ok_or_else(|| AppError::ParseError(format!("批量下单响应格式错误: {data}")))?;
+        if items.len() != requests.len() {
+            return Err(AppError::ParseError(format!("批量下单返回{}个结果，提交了{}个订单", items.len(), requests.len())));
+        }
+        
+        Ok(items.iter().map(BatchOrderResult::from_item).collect())
+    }
+    
+    /// 下单参数（不含timestamp和签名），单笔下单和批量下单共用
+    fn order_params(request: &OrderRequest) -> Vec<(&'static str, String)> {
         let mut params = vec![
             ("symbol", request.symbol.clone()),
             ("side", request.side.to_api_string().to_string()),
             ("type", request.order_type.to_api_string().to_string()),
             ("quantity", request.quantity.to_string()),
-            ("timestamp", timestamp.to_string()),
         ];
         
         if let Some(price) = request.price {
@@ -318,25 +409,16 @@ impl BinanceFuturesRestClient {
             params.push(("newClientOrderId", client_order_id.clone()));
         }
         
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string)?;
-        params.push(("signature", signature));
-        
-        let url = format!("{}/fapi/v1/order", self.base_url);
-        let body = self.build_query_string(&params);
-        
-        let response = self.send_signed_request(Method::POST, &url, Some(body)).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        params
     }
     
     /// 取消订单
     pub async fn cancel_order(&self, symbol: &str, order_id: Option<u64>, orig_client_order_id: Option<&str>) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
         let mut params = vec![
             ("symbol", symbol.to_string()),
             ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
         ];
         
         if let Some(order_id) = order_id {
@@ -354,18 +436,17 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/order", self.base_url);
         let body = self.build_query_string(&params);
         
-        let response = self.send_signed_request(Method::DELETE, &url, Some(body)).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::DELETE, &url, Some(body), RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 查询订单
     pub async fn query_order(&self, symbol: &str, order_id: Option<u64>, orig_client_order_id: Option<&str>) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
         let mut params = vec![
             ("symbol", symbol.to_string()),
             ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
         ];
         
         if let Some(order_id) = order_id {
@@ -382,19 +463,95 @@ impl BinanceFuturesRestClient {
         
         let url = format!("{}/fapi/v1/order?{}", self.base_url, self.build_query_string(&params));
         
-        let response = self.send_signed_request(Method::GET, &url, None).await?;
+        self.send_signed_request(Method::GET, &url, None, RequestCost::weight(1)).await
+            .map(|response| response.data)
+    }
+    
+    /// 修改限价单的价格和数量
+    pub async fn modify_order(
+        &self,
+        symbol: &str,
+        order_id: Option<u64>,
+        orig_client_order_id: Option<&str>,
+        side: OrderSide,
+        quantity: f64,
+        price: f64,
+    ) -> Result<Value> {
+        let mut params = vec![
+            ("symbol", symbol.to_string()),
+            ("side", side.to_api_string().to_string()),
+            ("quantity", quantity.to_string()),
+            ("price", price.to_string()),
+            ("timestamp", self.clock.now_ms().to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
+        
+        if let Some(order_id) = order_id {
+            params.push(("orderId", order_id.to_string()));
+        }
+        
+        if let Some(orig_client_order_id) = orig_client_order_id {
+            params.push(("origClientOrderId", orig_client_order_id.to_string()));
+        }
+        
+        let query_string = self.build_query_string(&params);
+        let signature = self.sign(&query_string)?;
+        params.push(("signature", signature));
         
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        let url = format!("{}/fapi/v1/order", self.base_url);
+        let body = self.build_query_string(&params);
+        
+        self.send_signed_request(Method::PUT, &url, Some(body), RequestCost::order(1)).await
+            .map(|response| response.data)
+    }
+    
+    /// 撤销交易对的全部挂单
+    pub async fn cancel_all_open_orders(&self, symbol: &str) -> Result<Value> {
+        let mut params = vec![
+            ("symbol", symbol.to_string()),
+            ("timestamp", self.clock.now_ms().to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
+        
+        let query_string = self.build_query_string(&params);
+        let signature = self.sign(&query_string)?;
+        params.push(("signature", signature));
+        
+        let url = format!("{}/fapi/v1/allOpenOrders", self.base_url);
+        let body = self.build_query_string(&params);
+        
+        self.send_signed_request(Method::DELETE, &url, Some(body), RequestCost::weight(1)).await
+            .map(|response| response.data)
+    }
+    
+    /// 倒计时撤单：`countdown`后撤销交易对的全部挂单，期间再次调用会重新计时，传0取消倒计时
+    pub async fn countdown_cancel_all(&self, symbol: &str, countdown: Duration) -> Result<Value> {
+        let mut params = vec![
+            ("symbol", symbol.to_string()),
+            ("countdownTime", countdown.as_millis().to_string()),
+            ("timestamp", self.clock.now_ms().to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
+        ];
+        
+        let query_string = self.build_query_string(&params);
+        let signature = self.sign(&query_string)?;
+        params.push(("signature", signature));
+        
+        let url = format!("{}/fapi/v1/countdownCancelAll", self.base_url);
+        let body = self.build_query_string(&params);
+        
+        self.send_signed_request(Method::POST, &url, Some(body), RequestCost::weight(10)).await
+            .map(|response| response.data)
     }
     
     /// 调整杠杆
     pub async fn change_leverage(&self, symbol: &str, leverage: u8) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
         let params = vec![
             ("symbol", symbol.to_string()),
             ("leverage", leverage.to_string()),
             ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
         ];
         
         let query_string = self.build_query_string(&params);
@@ -405,19 +562,18 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/leverage", self.base_url);
         let body = self.build_query_string(&signed_params);
         
-        let response = self.send_signed_request(Method::POST, &url, Some(body)).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::POST, &url, Some(body), RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 调整保证金模式
     pub async fn change_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
         let params = vec![
             ("symbol", symbol.to_string()),
             ("marginType", margin_type.as_str().to_string()),
             ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
         ];
         
         let query_string = self.build_query_string(&params);
@@ -428,18 +584,17 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/marginType", self.base_url);
         let body = self.build_query_string(&signed_params);
         
-        let response = self.send_signed_request(Method::POST, &url, Some(body)).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::POST, &url, Some(body), RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 调整持仓模式
     pub async fn change_position_mode(&self, dual_side_position: bool) -> Result<Value> {
-        let timestamp = Utc::now().timestamp_millis();
+        let timestamp = self.clock.now_ms();
         let params = vec![
             ("dualSidePosition", dual_side_position.to_string()),
             ("timestamp", timestamp.to_string()),
+            ("recvWindow", self.clock.recv_window_ms().to_string()),
         ];
         
         let query_string = self.build_query_string(&params);
@@ -450,20 +605,15 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/positionSide/dual", self.base_url);
         let body = self.build_query_string(&signed_params);
         
-        let response = self.send_signed_request(Method::POST, &url, Some(body)).await?;
-        
-        response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))
+        self.send_signed_request(Method::POST, &url, Some(body), RequestCost::weight(1)).await
+            .map(|response| response.data)
     }
     
     /// 启动用户数据流
     pub async fn start_user_data_stream(&self) -> Result<String> {
         let url = format!("{}/fapi/v1/listenKey", self.base_url);
         
-        let response = self.send_signed_request(Method::POST, &url, None).await?;
-        
-        let data: Value = response.json().await
-            .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))?;
+        let data = self.send_signed_request(Method::POST, &url, None, RequestCost::weight(1)).await?.data;
         
         data.get("listenKey")
             .and_then(|k| k.as_str())
@@ -476,7 +626,7 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/listenKey", self.base_url);
         let body = format!("listenKey={listen_key}");
         
-        let _response = self.send_signed_request(Method::PUT, &url, Some(body)).await?;
+        self.send_signed_request(Method::PUT, &url, Some(body), RequestCost::weight(1)).await?;
         
         Ok(())
     }
@@ -486,7 +636,7 @@ impl BinanceFuturesRestClient {
         let url = format!("{}/fapi/v1/listenKey", self.base_url);
         let body = format!("listenKey={listen_key}");
         
-        let _response = self.send_signed_request(Method::DELETE, &url, Some(body)).await?;
+        self.send_signed_request(Method::DELETE, &url, Some(body), RequestCost::weight(1)).await?;
         
         Ok(())
     }
@@ -549,8 +699,13 @@ impl BinanceFuturesRestClient {
         self.place_order(&order_request).await
     }
     
+    /// 发送公开请求
+    async fn send_public_request(&self, url: &str, cost: RequestCost) -> Result<ApiResponse<Value>> {
+        self.execute(self.client.get(url), cost).await
+    }
+    
     /// 发送签名请求
-    async fn send_signed_request(&self, method: Method, url: &str, body: Option<String>) -> Result<Response> {
+    async fn send_signed_request(&self, method: Method, url: &str, body: Option<String>, cost: RequestCost) -> Result<ApiResponse<Value>> {
         let mut request = self.client.request(method, url);
         
         // 添加API密钥头
@@ -564,8 +719,36 @@ impl BinanceFuturesRestClient {
                 .body(body);
         }
         
-        request.send().await
-            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))
+        self.execute(request, cost).await
+    }
+    
+    /// 经过限速器发送请求，并用响应头同步已用额度
+    async fn execute(&self, request: RequestBuilder, cost: RequestCost) -> Result<ApiResponse<Value>> {
+        self.rate_limiter.acquire(cost).await?;
+        
+        let response = request.send().await
+            .map_err(|e| AppError::ConnectionError(format!("请求失败: {e}")))?;
+        
+        if let Some(error) = self.rate_limiter.observe_response(response.status().as_u16(), response.headers()) {
+            return Err(error.into());
+        }
+        
+        let body = response.text().await
+            .map_err(|e| AppError::ConnectionError(format!("读取响应失败: {e}")))?;
+        // listenKey续期等接口可能返回空响应体
+        let data = if body.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&body)
+                .map_err(|e| AppError::ParseError(format!("解析响应失败: {e}")))?
+        };
+        
+        let (remaining, reset) = self.rate_limiter.remaining(RateLimitKind::RequestWeight).unzip();
+        Ok(ApiResponse {
+            data,
+            rate_limit_remaining: remaining,
+            rate_limit_reset: reset.map(|reset| reset.max(0) as u64),
+        })
     }
     
     /// 构建查询字符串
@@ -590,3 +773,258 @@ impl BinanceFuturesRestClient {
         Ok(hex::encode(result.into_bytes()))
     }
 }
+
+#[async_trait]
+impl ServerTimeSource for BinanceFuturesRestClient {
+    async fn server_time_ms(&self) -> std::result::Result<i64, ConnectorError> {
+        self.get_server_time().await
+            .map(|server_time| server_time as i64)
+            .map_err(|e| ConnectorError::NetworkError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl InstrumentSource for BinanceFuturesRestClient {
+    fn exchange(&self) -> Exchange {
+        Exchange::BinanceFutures
+    }
+    
+    async fn load_instruments(&self) -> std::result::Result<Vec<InstrumentSpec>, ConnectorError> {
+        let data = self.get_exchange_info().await
+            .map_err(|e| ConnectorError::NetworkError(e.to_string()))?;
+        Ok(parse_exchange_info(Exchange::BinanceFutures, &data))
+    }
+}
+
+/// 解析exchangeInfo中各交易对的过滤器（现货与U本位合约格式相同）
+/// 
+/// 只保留永续合约；现货没有 `contractType` 字段。
+pub fn parse_exchange_info(exchange: Exchange, data: &Value) -> Vec<InstrumentSpec> {
+    let Some(symbols) = data.get("symbols").and_then(|s| s.as_array()) else {
+        return Vec::new();
+    };
+    
+    symbols.iter().filter_map(|item| {
+        if item.get("contractType").and_then(|t| t.as_str()).is_some_and(|t| t != "PERPETUAL") {
+            return None;
+        }
+        let symbol = item.get("symbol")?.as_str()?;
+        let filter = |filter_type: &str| item.get("filters")?.as_array()?.iter()
+            .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some(filter_type));
+        
+        let price_filter = filter("PRICE_FILTER")?;
+        let lot_size = filter("LOT_SIZE")?;
+        let market_lot_size = filter("MARKET_LOT_SIZE");
+        // 期货使用 MIN_NOTIONAL.notional，现货使用 NOTIONAL/MIN_NOTIONAL.minNotional
+        let min_notional = filter("MIN_NOTIONAL").or_else(|| filter("NOTIONAL"))
+            .and_then(|f| positive_decimal_field(f, "notional").or_else(|| positive_decimal_field(f, "minNotional")));
+        
+        let mut spec = InstrumentSpec::new(
+            exchange,
+            symbol,
+            positive_decimal_field(price_filter, "tickSize")?,
+            positive_decimal_field(lot_size, "stepSize")?,
+        );
+        spec.base_asset = item.get("baseAsset").and_then(|a| a.as_str()).unwrap_or_default().to_string();
+        spec.quote_asset = item.get("quoteAsset").and_then(|a| a.as_str()).unwrap_or_default().to_string();
+        spec.status = InstrumentStatus::from_exchange(item.get("status").and_then(|s| s.as_str()).unwrap_or_default());
+        spec.min_price = positive_decimal_field(price_filter, "minPrice");
+        spec.max_price = positive_decimal_field(price_filter, "maxPrice");
+        spec.min_qty = positive_decimal_field(lot_size, "minQty").unwrap_or_default();
+        spec.max_qty = positive_decimal_field(lot_size, "maxQty");
+        spec.market_step_size = market_lot_size.and_then(|f| positive_decimal_field(f, "stepSize"));
+        spec.market_min_qty = market_lot_size.and_then(|f| positive_decimal_field(f, "minQty"));
+        spec.market_max_qty = market_lot_size.and_then(|f| positive_decimal_field(f, "maxQty"));
+        spec.min_notional = min_notional;
+        Some(spec)
+    }).collect()
+}
+
+/// 深度接口的请求权重随档位数变化
+fn depth_weight(limit: Option<u16>) -> u32 {
+    match limit.unwrap_or(500) {
+        0..=50 => 2,
+        51..=100 => 5,
+        101..=500 => 10,
+        _ => 20,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::error_handling::{init_error_tracker, ErrorCategory};
+    use crate::connectors::test_support::{MockHttpServer, MockResponse};
+
+    fn signed_client(url: String) -> BinanceFuturesRestClient {
+        let config = BinanceFuturesConfig {
+            api_key: Some("test-key".to_string()),
+            secret_key: Some("test-secret".to_string()),
+            ..BinanceFuturesConfig::default()
+        };
+        BinanceFuturesRestClient::new(config).with_base_url(url)
+    }
+
+    fn limit_order(symbol: &str, quantity: f64, reduce_only: Option<bool>) -> OrderRequest {
+        OrderRequest {
+            symbol: symbol.to_string(),
+            side: OrderSide::Sell,
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(43000.5),
+            time_in_force: Some(TradingTimeInForce::GTC),
+            position_side: None,
+            close_position: None,
+            activation_price: None,
+            callback_rate: None,
+            working_type: None,
+            price_protect: None,
+            client_order_id: None,
+            reduce_only,
+        }
+    }
+
+    fn client_with_limiter(url: String, max_queue_wait: Duration) -> BinanceFuturesRestClient {
+        let config = BinanceFuturesConfig::default();
+        let rate_limiter = Arc::new(BinanceFuturesRestClient::default_rate_limiter(&config).with_max_queue_wait(max_queue_wait));
+        BinanceFuturesRestClient::with_rate_limiter(config, rate_limiter).with_base_url(url)
+    }
+
+    #[test]
+    fn test_depth_weight_follows_limit() {
+        assert_eq!(depth_weight(Some(20)), 2);
+        assert_eq!(depth_weight(Some(100)), 5);
+        assert_eq!(depth_weight(None), 10);
+        assert_eq!(depth_weight(Some(1000)), 20);
+    }
+
+    #[tokio::test]
+    async fn test_used_weight_header_limits_next_request() {
+        let server = MockHttpServer::respond_with(
+            MockResponse::ok(r#"{"serverTime":1710755362722}"#)
+                .header("X-MBX-USED-WEIGHT-1M", "2395")
+                .header("X-MBX-ORDER-COUNT-10S", "3"),
+        ).await;
+        let client = client_with_limiter(server.url.clone(), Duration::ZERO);
+
+        let response = client.send_public_request(&format!("{}/fapi/v1/time", client.base_url), RequestCost::weight(1)).await.unwrap();
+        assert_eq!(response.data["serverTime"], 1710755362722u64);
+        assert_eq!(response.rate_limit_remaining, Some(5));
+        assert!(response.rate_limit_reset.unwrap() > Utc::now().timestamp_millis() as u64);
+
+        // 默认500档深度需要10个权重，剩余额度不足，请求在发出前被拒绝
+        let error = client.get_depth("BTCUSDT", None).await.unwrap_err();
+        assert_eq!(ErrorCategory::from(&error), ErrorCategory::RateLimitExceeded);
+        assert_eq!(client.get_depth("BTCUSDT", Some(20)).await.unwrap()["serverTime"], 1710755362722u64);
+        assert_eq!(client.rate_limiter().rejected_requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ban_response_trips_circuit_breaker() {
+        let server = MockHttpServer::respond_with(
+            MockResponse::with_status("418 I'm a teapot", r#"{"code":-1003,"msg":"Way too many requests; IP banned."}"#).header("Retry-After", "1"),
+        ).await;
+        let client = client_with_limiter(server.url.clone(), Duration::from_secs(5));
+
+        let error = client.get_exchange_info().await.unwrap_err();
+        assert_eq!(ErrorCategory::from(&error), ErrorCategory::RateLimitExceeded);
+        assert!(init_error_tracker().is_circuit_open(Exchange::BinanceFutures));
+
+        // 封禁期间的请求不再发出
+        assert!(client.rate_limiter().is_blocked());
+        assert!(client.get_server_time().await.is_err());
+        assert_eq!(client.rate_limiter().rejected_requests(), 1);
+    }
+    
+    /// 录制的exchangeInfo（节选）：一个永续合约、一个交割合约
+    const RECORDED_EXCHANGE_INFO: &str = r#"{"timezone":"UTC","serverTime":1710755362722,"symbols":[{"symbol":"BTCUSDT","pair":"BTCUSDT","contractType":"PERPETUAL","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT","pricePrecision":2,"quantityPrecision":3,"filters":[{"minPrice":"556.80","maxPrice":"4529764","filterType":"PRICE_FILTER","tickSize":"0.10"},{"stepSize":"0.001","filterType":"LOT_SIZE","maxQty":"1000","minQty":"0.001"},{"stepSize":"0.001","filterType":"MARKET_LOT_SIZE","maxQty":"120","minQty":"0.001"},{"limit":200,"filterType":"MAX_NUM_ORDERS"},{"notional":"100","filterType":"MIN_NOTIONAL"},{"multiplierDown":"0.9500","multiplierUp":"1.0500","multiplierDecimal":"4","filterType":"PERCENT_PRICE"}]},{"symbol":"BTCUSDT_240628","pair":"BTCUSDT","contractType":"CURRENT_QUARTER","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT","filters":[{"minPrice":"576.30","maxPrice":"1000000","filterType":"PRICE_FILTER","tickSize":"0.10"},{"stepSize":"0.001","filterType":"LOT_SIZE","maxQty":"500","minQty":"0.001"}]},{"symbol":"1000PEPEUSDT","pair":"1000PEPEUSDT","contractType":"PERPETUAL","status":"SETTLING","baseAsset":"1000PEPE","quoteAsset":"USDT","filters":[{"minPrice":"0.0000010","maxPrice":"200","filterType":"PRICE_FILTER","tickSize":"0.0000001"},{"stepSize":"1","filterType":"LOT_SIZE","maxQty":"80000000","minQty":"1"},{"notional":"5","filterType":"MIN_NOTIONAL"}]}]}"#;
+    
+    #[tokio::test]
+    async fn test_load_instruments_from_exchange_info() {
+        let server = MockHttpServer::respond_with(MockResponse::ok(RECORDED_EXCHANGE_INFO)).await;
+        let client = client_with_limiter(server.url.clone(), Duration::from_secs(5));
+        
+        let mut specs = client.load_instruments().await.unwrap();
+        specs.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        assert_eq!(specs.iter().map(|spec| spec.symbol.as_str()).collect::<Vec<_>>(), ["1000PEPEUSDT", "BTCUSDT"]);
+        
+        let btc = &specs[1];
+        assert_eq!((btc.base_asset.as_str(), btc.quote_asset.as_str()), ("BTC", "USDT"));
+        assert_eq!(btc.status, InstrumentStatus::Trading);
+        assert_eq!(btc.tick_size.to_string(), "0.10");
+        assert_eq!(btc.step_size.to_string(), "0.001");
+        assert_eq!(btc.market_max_qty.map(|q| q.to_string()).as_deref(), Some("120"));
+        assert_eq!(btc.min_notional.map(|n| n.to_string()).as_deref(), Some("100"));
+        assert_eq!(btc.price_precision(), 1);
+        
+        let pepe = &specs[0];
+        assert_eq!(pepe.status, InstrumentStatus::Halted);
+        assert_eq!(pepe.round_price(0.00123456, crate::connectors::common::instruments::PriceRounding::Down), 0.0012345);
+        assert_eq!(pepe.round_qty(1234.9), 1234.0);
+    }
+    
+    #[tokio::test]
+    async fn test_batch_orders_return_per_item_results() {
+        let server = MockHttpServer::sequence(vec![
+            r#"[{"orderId":22542179,"symbol":"BTCUSDT","status":"NEW","clientOrderId":"a1","origQty":"0.1"},{"code":-2022,"msg":"ReduceOnly Order is rejected."}]"#.to_string(),
+        ]).await;
+        let client = signed_client(server.url.clone());
+        
+        let results = client.place_batch_orders(&[
+            limit_order("BTCUSDT", 0.1, None),
+            limit_order("ETHUSDT", 2.0, Some(true)),
+        ]).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[0], BatchOrderResult::Accepted(order) if order["orderId"] == 22542179));
+        assert_eq!(results[1], BatchOrderResult::Rejected { code: -2022, msg: "ReduceOnly Order is rejected.".to_string() });
+        
+        let request = server.request(0).await;
+        assert_eq!((request.method.as_str(), request.path()), ("POST", "/fapi/v1/batchOrders"));
+        // batchOrders是URL编码的JSON数组，签名覆盖编码后的参数
+        let (payload, signature) = request.body.rsplit_once("&signature=").unwrap();
+        assert_eq!(signature, client.sign(payload).unwrap());
+        let encoded = payload.strip_prefix("batchOrders=").unwrap().split('&').next().unwrap();
+        assert!(!encoded.contains('{') && !encoded.contains('"'));
+        
+        // 超过5个订单在发出请求前拒绝
+        let too_many = vec![limit_order("BTCUSDT", 0.1, None); 6];
+        assert!(matches!(client.place_batch_orders(&too_many).await, Err(AppError::ParseError(_))));
+        assert_eq!(server.requests().len(), 1);
+    }
+    
+    #[test]
+    fn test_batch_order_items_use_single_order_params() {
+        let item: Value = Value::Object(BinanceFuturesRestClient::order_params(&limit_order("ETHUSDT", 2.0, Some(true))).into_iter()
+            .map(|(key, value)| (key.to_string(), Value::String(value)))
+            .collect());
+        assert_eq!(item, serde_json::json!({
+            "symbol": "ETHUSDT", "side": "SELL", "type": "LIMIT", "quantity": "2",
+            "price": "43000.5", "timeInForce": "GTC", "reduceOnly": "true",
+        }));
+    }
+    
+    #[tokio::test]
+    async fn test_modify_cancel_all_and_countdown_requests() {
+        let server = MockHttpServer::sequence(vec![
+            r#"{"orderId":20072994037,"symbol":"BTCUSDT","status":"NEW","price":"43100.5","origQty":"0.2"}"#.to_string(),
+            r#"{"code":200,"msg":"The operation of cancel all open order is done."}"#.to_string(),
+            r#"{"symbol":"BTCUSDT","countdownTime":"120000"}"#.to_string(),
+        ]).await;
+        let client = signed_client(server.url.clone());
+        
+        let modified = client.modify_order("BTCUSDT", Some(20072994037), None, OrderSide::Buy, 0.2, 43100.5).await.unwrap();
+        assert_eq!(modified["price"], "43100.5");
+        client.cancel_all_open_orders("BTCUSDT").await.unwrap();
+        assert_eq!(client.countdown_cancel_all("BTCUSDT", Duration::from_secs(120)).await.unwrap()["countdownTime"], "120000");
+        
+        let requests = server.requests();
+        let routes: Vec<_> = requests.iter()
+            .map(|request| format!("{} {}", request.method, request.path()))
+            .collect();
+        assert_eq!(routes, ["PUT /fapi/v1/order", "DELETE /fapi/v1/allOpenOrders", "POST /fapi/v1/countdownCancelAll"]);
+        assert!(requests[0].body.starts_with("symbol=BTCUSDT&side=BUY&quantity=0.2&price=43100.5&"));
+        assert!(requests[0].body.contains("&orderId=20072994037&signature="));
+        assert!(requests[2].body.starts_with("symbol=BTCUSDT&countdownTime=120000&"));
+    }
+}
@@ -1,9 +1,11 @@
 //! Binance期货风险管理模块
 //! 
-//! 实现仓位限制检查、保证金充足性验证、价格偏离保护和紧急停止机制
+//! 实现仓位限制检查、保证金充足性验证、价格偏离保护、交易规则校验和紧急停止机制
 
 use crate::core::AppError;
 use crate::connectors::binance::futures::websocket::{FuturesPosition, FuturesBalance};
+use crate::connectors::common::instruments::InstrumentRegistry;
+use crate::exchange_types::Exchange;
 use crate::types::OrderRequest;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -526,6 +528,8 @@ pub struct RiskManager {
     price_protection: PriceProtection,
     /// 紧急停止机制
     emergency_stop: EmergencyStop,
+    /// 交易规则（价格步长、数量步长、最小名义价值）
+    instruments: Option<Arc<InstrumentRegistry>>,
     /// 是否启用风险检查
     enabled: bool,
 }
@@ -538,6 +542,7 @@ impl RiskManager {
             margin_checker: MarginChecker::new(),
             price_protection: PriceProtection::new(),
             emergency_stop: EmergencyStop::new(),
+            instruments: None,
             enabled: true,
         }
     }
@@ -567,6 +572,11 @@ impl RiskManager {
         &mut self.emergency_stop
     }
     
+    /// 设置交易规则注册表，之后下单前会校验价格步长、数量步长和最小名义价值
+    pub fn set_instrument_registry(&mut self, instruments: Arc<InstrumentRegistry>) {
+        self.instruments = Some(instruments);
+    }
+    
     /// 综合风险检查 (下单前)
     pub async fn check_order_risk(&self, order: &OrderRequest, balances: &[FuturesBalance], positions: &[FuturesPosition]) -> Result<()> {
         if !self.enabled {
@@ -583,6 +593,12 @@ impl RiskManager {
             }
         }
         
+        // 检查交易规则
+        if let Some(instruments) = &self.instruments {
+            instruments.validate_order(Exchange::BinanceFutures, order)
+                .map_err(|e| AppError::RiskError(format!("订单不满足交易规则: {e}")))?;
+        }
+        
         // 检查订单限制
         self.position_checker.check_order_limit(&order.symbol, order.quantity)?;
         
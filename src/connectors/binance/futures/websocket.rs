@@ -4,6 +4,10 @@
 
 use crate::connectors::binance::futures::config::{BinanceFuturesConfig, PositionSide};
 use crate::connectors::binance::futures::constants::*;
+use crate::connectors::binance::futures::orderbook::{FuturesOrderBookManager, FuturesDepthEvent};
+use crate::connectors::binance::futures::message_parser::BinanceFuturesMessageParser;
+use crate::basis::FundingStore;
+use crate::exchange_types::Exchange;
 use crate::types::market_data::*;
 use crate::types::trading::*;
 use crate::core::AppError;
@@ -42,6 +46,10 @@ pub struct BinanceFuturesWebSocketHandler {
     is_connected: Arc<RwLock<bool>>,
     /// 最后心跳时间
     last_heartbeat: Arc<RwLock<DateTime<Utc>>>,
+    /// 本地订单簿管理器（处理增量深度流）
+    orderbook_manager: Option<FuturesOrderBookManager>,
+    /// 资金费率存储（处理标记价格流）
+    funding_store: Option<Arc<FundingStore>>,
 }
 
 /// 订阅信息
@@ -170,6 +178,8 @@ impl BinanceFuturesWebSocketHandler {
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             is_connected: Arc::new(RwLock::new(false)),
             last_heartbeat: Arc::new(RwLock::new(Utc::now())),
+            orderbook_manager: None,
+            funding_store: None,
         }
     }
     
@@ -188,6 +198,21 @@ impl BinanceFuturesWebSocketHandler {
         self.account_sender = Some(sender);
     }
     
+    /// 设置本地订单簿管理器
+    pub fn set_orderbook_manager(&mut self, manager: FuturesOrderBookManager) {
+        self.orderbook_manager = Some(manager);
+    }
+    
+    /// 获取本地订单簿管理器
+    pub fn orderbook_manager(&self) -> Option<&FuturesOrderBookManager> {
+        self.orderbook_manager.as_ref()
+    }
+    
+    /// 设置资金费率存储，标记价格推送会写入其中
+    pub fn set_funding_store(&mut self, store: Arc<FundingStore>) {
+        self.funding_store = Some(store);
+    }
+    
     /// 连接WebSocket
     pub async fn connect(&mut self) -> Result<()> {
         let ws_url = if self.config.testnet {
@@ -218,6 +243,11 @@ impl BinanceFuturesWebSocketHandler {
         *self.is_connected.write().await = true;
         *self.last_heartbeat.write().await = Utc::now();
         
+        // 新连接的增量事件无法与旧订单簿衔接，需要重新同步
+        if let Some(ref manager) = self.orderbook_manager {
+            manager.invalidate_all().await;
+        }
+        
         info!("Binance期货WebSocket连接成功");
         Ok(())
     }
@@ -230,6 +260,18 @@ impl BinanceFuturesWebSocketHandler {
         self.subscribe_stream(&stream_name, symbol, StreamType::Depth).await
     }
     
+    /// 订阅增量深度数据，由本地订单簿管理器维护完整订单簿
+    pub async fn subscribe_diff_depth(&mut self, symbol: &str) -> Result<()> {
+        let manager = self.orderbook_manager.clone().ok_or_else(|| {
+            AppError::WebSocketError("未设置本地订单簿管理器".to_string())
+        })?;
+        let stream_name = format!("{}@depth@100ms", symbol.to_lowercase());
+        
+        // 先开始跟踪，确保订阅后的第一条事件就被缓存
+        manager.track_symbol(symbol).await;
+        self.subscribe_stream(&stream_name, symbol, StreamType::Depth).await
+    }
+    
     /// 订阅交易数据
     pub async fn subscribe_trades(&mut self, symbol: &str) -> Result<()> {
         let stream_name = format!("{}@aggTrade", symbol.to_lowercase());
@@ -335,7 +377,14 @@ impl BinanceFuturesWebSocketHandler {
         }
         
         // 移除订阅信息
-        self.subscriptions.write().await.remove(stream_name);
+        let removed = self.subscriptions.write().await.remove(stream_name);
+        
+        // 增量深度流取消后停止维护本地订单簿
+        if let (Some(info), Some(manager)) = (removed, self.orderbook_manager.as_ref()) {
+            if info.stream_type == StreamType::Depth && Self::is_diff_depth_stream(stream_name) {
+                manager.untrack_symbol(&info.symbol).await;
+            }
+        }
         
         info!("已取消订阅期货流: {stream_name}");
         Ok(())
@@ -395,6 +444,8 @@ impl BinanceFuturesWebSocketHandler {
         let subscriptions = self.subscriptions.clone();
         let is_connected = self.is_connected.clone();
         let last_heartbeat = self.last_heartbeat.clone();
+        let orderbook_manager = self.orderbook_manager.clone();
+        let funding_store = self.funding_store.clone();
         
         // 获取已经存在的ws_sink引用
         let ws_sink = self.ws_sink.clone().ok_or_else(|| {
@@ -455,6 +506,8 @@ impl BinanceFuturesWebSocketHandler {
                             &trade_sender,
                             &account_sender,
                             &subscriptions,
+                            &orderbook_manager,
+                            &funding_store,
                         ).await {
                             error!("处理WebSocket消息失败: {e:?}");
                         }
@@ -559,6 +612,8 @@ impl BinanceFuturesWebSocketHandler {
         _trade_sender: &Option<mpsc::UnboundedSender<TradeEvent>>,
         _account_sender: &Option<mpsc::UnboundedSender<AccountEvent>>,
         _subscriptions: &Arc<RwLock<HashMap<String, SubscriptionInfo>>>,
+        orderbook_manager: &Option<FuturesOrderBookManager>,
+        funding_store: &Option<Arc<FundingStore>>,
     ) -> Result<()> {
         debug!("收到WebSocket消息: {text}");
         
@@ -577,15 +632,20 @@ impl BinanceFuturesWebSocketHandler {
             if let Some(data) = msg.get("data") {
                 if stream.contains("@depth") {
                     debug!("处理深度数据: {data:?}");
-                    Self::process_depth_data(stream, data, data_sender).await?;
+                    match (orderbook_manager, FuturesDepthEvent::from_json(data)) {
+                        (Some(manager), Some(event)) if Self::is_diff_depth_stream(stream) => {
+                            manager.handle_diff_event(event, data_sender).await;
+                        }
+                        _ => Self::process_depth_data(stream, data, data_sender).await?,
+                    }
                 } else if stream.contains("@aggTrade") {
                     // TODO: 处理交易数据
                 } else if stream.contains("@kline") {
                     // TODO: 处理K线数据
                 } else if stream.contains("@ticker") {
                     // TODO: 处理24小时价格统计
-                } else if stream.contains("@markPrice") {
-                    // TODO: 处理标记价格
+                } else if stream.contains("@markPrice") || stream.starts_with("!markPrice@arr") {
+                    Self::process_mark_price_data(data, data_sender, funding_store);
                 }
             }
         } else {
@@ -593,6 +653,13 @@ impl BinanceFuturesWebSocketHandler {
             debug!("检查是否为直接深度数据格式: {msg:?}");
             if msg.get("e").and_then(|e| e.as_str()) == Some("depthUpdate") {
                 debug!("处理直接深度更新数据");
+                // 直接格式无法从流名称区分增量与部分深度，按交易对是否被本地订单簿跟踪判断
+                if let (Some(manager), Some(event)) = (orderbook_manager, FuturesDepthEvent::from_json(&msg)) {
+                    if manager.is_tracking(&event.symbol).await {
+                        manager.handle_diff_event(event, data_sender).await;
+                        return Ok(());
+                    }
+                }
                 Self::process_direct_depth_data(&msg, data_sender).await?;
             }
         }
@@ -600,6 +667,30 @@ impl BinanceFuturesWebSocketHandler {
         Ok(())
     }
     
+    /// 处理标记价格数据（单个交易对或全市场数组），更新资金费率存储并转发
+    fn process_mark_price_data(
+        data: &Value,
+        data_sender: &Option<mpsc::UnboundedSender<MarketDataEvent>>,
+        funding_store: &Option<Arc<FundingStore>>,
+    ) {
+        let funding_interval_hours = crate::config::get_config().basis.default_funding_interval_hours;
+        for update in BinanceFuturesMessageParser::parse_mark_price_updates(data) {
+            if let Some(store) = funding_store {
+                store.apply_mark_price(Exchange::BinanceFutures, &update, funding_interval_hours);
+            }
+            if let Some(sender) = data_sender {
+                if let Err(e) = sender.send(MarketDataEvent::MarkPriceUpdate(update)) {
+                    warn!("发送标记价格数据失败: {e}");
+                }
+            }
+        }
+    }
+    
+    /// 是否为增量深度流（`@depth`或`@depth@100ms`，不含档位数）
+    fn is_diff_depth_stream(stream: &str) -> bool {
+        stream.contains("@depth@") || stream.ends_with("@depth")
+    }
+    
     /// 处理深度数据
     async fn process_depth_data(
         stream: &str,
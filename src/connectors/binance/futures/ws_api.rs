@@ -0,0 +1,649 @@
+//! Binance期货WebSocket API下单模块
+//!
+//! 通过 ws-fapi 长连接发送交易请求（`order.place`、`order.cancel`、`order.modify`、`account.status`），
+//! 按请求id匹配响应，省去每笔HTTPS请求的往返握手。
+//! HMAC密钥不支持 `session.logon`，每个请求都单独带apiKey和签名；
+//! 限速额度、服务器时钟与REST客户端共享（交易所对两条通道合并计数）。
+
+use crate::connectors::binance::futures::config::BinanceFuturesConfig;
+use crate::connectors::binance::futures::connector::{LocalOrderRequest, ModifyOrderRequest};
+use crate::connectors::binance::futures::constants::*;
+use crate::connectors::binance::futures::rest_api::BinanceFuturesRestClient;
+use crate::connectors::common::clock_sync::ExchangeClock;
+use crate::connectors::common::rate_limiter::{RateLimiter, RequestCost};
+use crate::core::AppError;
+use crate::types::config::ConnectionStatus;
+
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use log::{debug, info, warn};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+pub type Result<T> = std::result::Result<T, AppError>;
+
+type HmacSha256 = Hmac<Sha256>;
+type WsApiStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+type PendingRequests = DashMap<String, oneshot::Sender<Result<Value>>>;
+type WsApiSink = futures_util::stream::SplitSink<WsApiStream, Message>;
+type WsApiSource = futures_util::stream::SplitStream<WsApiStream>;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Binance期货WebSocket API客户端
+pub struct BinanceFuturesWsApiClient {
+    /// ws-fapi地址
+    url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    /// 与REST客户端共享的限速器
+    rate_limiter: Arc<RateLimiter>,
+    /// 与REST客户端共享的服务器时钟
+    clock: Arc<ExchangeClock>,
+    /// 等待单个响应的超时时间
+    request_timeout: Duration,
+    /// 断线后重连的间隔
+    reconnect_interval: Duration,
+    next_id: AtomicU64,
+    /// 已发送、等待响应的请求
+    pending: Arc<PendingRequests>,
+    /// 当前会话的发送通道，会话断开时为None
+    outbound: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BinanceFuturesWsApiClient {
+    /// 创建WebSocket API客户端，与`rest_client`共享限速器和服务器时钟
+    pub fn new(config: &BinanceFuturesConfig, rest_client: &BinanceFuturesRestClient) -> Self {
+        let url = if config.testnet {
+            BINANCE_FUTURES_TESTNET_WS_API_URL
+        } else {
+            BINANCE_FUTURES_WS_API_URL
+        };
+
+        Self {
+            url: url.to_string(),
+            api_key: config.api_key.clone(),
+            secret_key: config.secret_key.clone(),
+            rate_limiter: rest_client.rate_limiter().clone(),
+            clock: rest_client.clock().clone(),
+            request_timeout: Duration::from_secs(config.rest_timeout),
+            reconnect_interval: Duration::from_secs(config.ws_reconnect_interval),
+            next_id: AtomicU64::new(1),
+            pending: Arc::new(DashMap::new()),
+            outbound: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 使用自定义地址（例如本地模拟服务器）
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+
+    /// 设置等待响应的超时时间
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// 设置断线重连间隔
+    pub fn with_reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+
+    /// 会话状态
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 会话是否可以发送请求
+    pub async fn is_connected(&self) -> bool {
+        self.outbound.read().await.is_some()
+    }
+
+    /// 建立会话并启动后台读写任务；之后断线会自动重连
+    pub async fn start(&self) -> Result<()> {
+        if self.api_key.is_none() || self.secret_key.is_none() {
+            return Err(AppError::ConfigError("WebSocket API下单需要API密钥".to_string()));
+        }
+
+        let mut task = self.task.lock().await;
+        if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+            return Ok(());
+        }
+
+        *self.status.write().await = ConnectionStatus::Connecting;
+        let ws_stream = match Self::connect(&self.url).await {
+            Ok(ws_stream) => ws_stream,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Disconnected;
+                return Err(e);
+            }
+        };
+        info!("Binance期货WebSocket API会话已建立: {}", self.url);
+
+        let session = Session {
+            url: self.url.clone(),
+            reconnect_interval: self.reconnect_interval,
+            rate_limiter: self.rate_limiter.clone(),
+            pending: self.pending.clone(),
+            outbound: self.outbound.clone(),
+            status: self.status.clone(),
+        };
+        // 返回前就可以发送请求
+        let connection = session.attach(ws_stream).await;
+        *task = Some(tokio::spawn(session.run(connection)));
+        Ok(())
+    }
+
+    /// 关闭会话；尚未收到响应的请求按连接断开处理
+    pub async fn stop(&self) {
+        if let Some(handle) = self.task.lock().await.take() {
+            handle.abort();
+        }
+        *self.outbound.write().await = None;
+        Session::fail_pending(&self.pending);
+        *self.status.write().await = ConnectionStatus::Disconnected;
+    }
+
+    /// 下单（`order.place`）
+    pub async fn place_order(&self, request: &LocalOrderRequest) -> Result<Value> {
+        let mut params = BTreeMap::from([
+            ("symbol", json!(request.symbol)),
+            ("side", json!(request.side)),
+            ("type", json!(request.order_type)),
+            ("quantity", json!(request.quantity.to_string())),
+        ]);
+
+        if let Some(price) = request.price {
+            params.insert("price", json!(price.to_string()));
+        }
+        if let Some(time_in_force) = &request.time_in_force {
+            params.insert("timeInForce", json!(time_in_force));
+        }
+        if let Some(reduce_only) = request.reduce_only {
+            params.insert("reduceOnly", json!(reduce_only.to_string()));
+        }
+        if let Some(close_position) = request.close_position {
+            params.insert("closePosition", json!(close_position.to_string()));
+        }
+        if let Some(position_side) = &request.position_side {
+            params.insert("positionSide", json!(position_side));
+        }
+        if let Some(client_order_id) = &request.client_order_id {
+            params.insert("newClientOrderId", json!(client_order_id));
+        }
+
+        self.signed_request("order.place", params, RequestCost::order(0)).await
+    }
+
+    /// 撤单（`order.cancel`）
+    pub async fn cancel_order(&self, symbol: &str, order_id: Option<u64>, orig_client_order_id: Option<&str>) -> Result<Value> {
+        let mut params = BTreeMap::from([("symbol", json!(symbol))]);
+        Self::insert_order_ref(&mut params, order_id, orig_client_order_id)?;
+
+        self.signed_request("order.cancel", params, RequestCost::weight(1)).await
+    }
+
+    /// 改单（`order.modify`），只能修改限价单的价格和数量
+    pub async fn modify_order(&self, request: &ModifyOrderRequest) -> Result<Value> {
+        let mut params = BTreeMap::from([
+            ("symbol", json!(request.symbol)),
+            ("side", json!(request.side)),
+            ("quantity", json!(request.quantity.to_string())),
+            ("price", json!(request.price.to_string())),
+        ]);
+        Self::insert_order_ref(&mut params, request.order_id, request.orig_client_order_id.as_deref())?;
+
+        self.signed_request("order.modify", params, RequestCost::order(1)).await
+    }
+
+    /// 账户信息（`account.status`），与REST `/fapi/v2/account` 内容相同
+    pub async fn account_status(&self) -> Result<Value> {
+        self.signed_request("account.status", BTreeMap::new(), RequestCost::weight(5)).await
+    }
+
+    /// 订单id与客户端订单id至少需要一个
+    fn insert_order_ref(params: &mut BTreeMap<&'static str, Value>, order_id: Option<u64>, orig_client_order_id: Option<&str>) -> Result<()> {
+        if order_id.is_none() && orig_client_order_id.is_none() {
+            return Err(AppError::ParseError("orderId和origClientOrderId不能同时为空".to_string()));
+        }
+        if let Some(order_id) = order_id {
+            params.insert("orderId", json!(order_id));
+        }
+        if let Some(orig_client_order_id) = orig_client_order_id {
+            params.insert("origClientOrderId", json!(orig_client_order_id));
+        }
+        Ok(())
+    }
+
+    /// 签名并发送请求，等待同一id的响应
+    ///
+    /// 会话未连接时返回`AppError::ConnectionError`，此时请求没有发出，调用方可以安全地改走REST；
+    /// 请求发出后连接断开返回`AppError::WebSocketError`，订单是否生效未知，不能直接重发。
+    async fn signed_request(&self, method: &str, mut params: BTreeMap<&'static str, Value>, cost: RequestCost) -> Result<Value> {
+        let sender = self.outbound.read().await.clone()
+            .ok_or_else(|| AppError::ConnectionError("WebSocket API会话未连接".to_string()))?;
+        let api_key = self.api_key.as_ref()
+            .ok_or_else(|| AppError::ConfigError("缺少API密钥".to_string()))?;
+
+        self.rate_limiter.acquire(cost).await?;
+
+        params.insert("apiKey", json!(api_key));
+        params.insert("recvWindow", json!(self.clock.recv_window_ms()));
+        params.insert("timestamp", json!(self.clock.now_ms()));
+        let signature = self.sign(&Self::signature_payload(&params))?;
+        params.insert("signature", json!(signature));
+
+        let id = format!("tf-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let frame = json!({"id": id, "method": method, "params": params}).to_string();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending.insert(id.clone(), response_tx);
+        if sender.send(Message::Text(frame)).is_err() {
+            self.pending.remove(&id);
+            return Err(AppError::ConnectionError("WebSocket API会话已断开".to_string()));
+        }
+        debug!("WebSocket API请求已发送: {method} id={id}");
+
+        match timeout(self.request_timeout, response_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(AppError::WebSocketError(format!("{method} 请求发出后连接断开，结果未知"))),
+            Err(_) => {
+                self.pending.remove(&id);
+                Err(AppError::TimeoutError)
+            }
+        }
+    }
+
+    /// 签名原文：除signature外的参数按名称排序后拼接
+    fn signature_payload(params: &BTreeMap<&str, Value>) -> String {
+        params.iter()
+            .map(|(key, value)| match value {
+                Value::String(value) => format!("{key}={value}"),
+                value => format!("{key}={value}"),
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn sign(&self, payload: &str) -> Result<String> {
+        let secret_key = self.secret_key.as_ref()
+            .ok_or_else(|| AppError::ConfigError("缺少密钥".to_string()))?;
+
+        let mut mac = HmacSha256::new_from_slice(secret_key.as_bytes())
+            .map_err(|e| AppError::CryptoError(format!("HMAC初始化失败: {e}")))?;
+        mac.update(payload.as_bytes());
+
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    async fn connect(url: &str) -> Result<WsApiStream> {
+        let (ws_stream, _) = timeout(CONNECT_TIMEOUT, connect_async(url)).await
+            .map_err(|_| AppError::ConnectionError(format!("连接WebSocket API超时: {url}")))?
+            .map_err(|e| AppError::ConnectionError(format!("连接WebSocket API失败: {e}")))?;
+        Ok(ws_stream)
+    }
+}
+
+/// 后台会话任务持有的共享状态
+struct Session {
+    url: String,
+    reconnect_interval: Duration,
+    rate_limiter: Arc<RateLimiter>,
+    pending: Arc<PendingRequests>,
+    outbound: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+}
+
+impl Session {
+    /// 启用新连接的发送通道
+    async fn attach(&self, ws_stream: WsApiStream) -> (WsApiSink, WsApiSource, mpsc::UnboundedReceiver<Message>) {
+        let (sink, stream) = ws_stream.split();
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+        *self.outbound.write().await = Some(outbound_tx);
+        *self.status.write().await = ConnectionStatus::Connected;
+        (sink, stream, outbound_rx)
+    }
+
+    /// 读写循环；连接断开（包括交易所24小时后主动断开）后重连
+    async fn run(self, connection: (WsApiSink, WsApiSource, mpsc::UnboundedReceiver<Message>)) {
+        let (mut sink, mut stream, mut outbound_rx) = connection;
+        loop {
+            loop {
+                tokio::select! {
+                    message = outbound_rx.recv() => {
+                        let Some(message) = message else { break };
+                        if let Err(e) = sink.send(message).await {
+                            warn!("WebSocket API发送失败: {e}");
+                            break;
+                        }
+                    }
+                    frame = stream.next() => match frame {
+                        Some(Ok(Message::Text(text))) => self.dispatch(&text),
+                        Some(Ok(Message::Ping(payload))) => {
+                            let _ = sink.send(Message::Pong(payload)).await;
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            info!("WebSocket API会话被关闭: {frame:?}");
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            warn!("WebSocket API读取失败: {e}");
+                            break;
+                        }
+                        None => break,
+                        Some(Ok(_)) => {}
+                    }
+                }
+            }
+
+            *self.outbound.write().await = None;
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+            Self::fail_pending(&self.pending);
+
+            let ws_stream = loop {
+                tokio::time::sleep(self.reconnect_interval).await;
+                match BinanceFuturesWsApiClient::connect(&self.url).await {
+                    Ok(ws_stream) => {
+                        info!("Binance期货WebSocket API会话已重连");
+                        break ws_stream;
+                    }
+                    Err(e) => warn!("WebSocket API重连失败: {e}"),
+                }
+            };
+            (sink, stream, outbound_rx) = self.attach(ws_stream).await;
+        }
+    }
+
+    /// 把响应交给等待同一id的请求
+    fn dispatch(&self, text: &str) {
+        let response: Value = match serde_json::from_str(text) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("无法解析WebSocket API响应: {e}");
+                return;
+            }
+        };
+        let Some(id) = response.get("id").and_then(|id| id.as_str()) else {
+            debug!("忽略没有id的WebSocket API消息: {text}");
+            return;
+        };
+        let Some((_, responder)) = self.pending.remove(id) else {
+            debug!("忽略已超时的WebSocket API响应: id={id}");
+            return;
+        };
+
+        let _ = responder.send(self.parse_response(&response));
+    }
+
+    /// 响应中的`rateLimits`按REST响应头的格式同步到限速器，429/418触发同样的退避
+    fn parse_response(&self, response: &Value) -> Result<Value> {
+        let status = response.get("status").and_then(|s| s.as_u64()).unwrap_or(0) as u16;
+
+        let headers: HeaderMap = response.get("rateLimits").and_then(|l| l.as_array()).into_iter().flatten()
+            .filter_map(|limit| {
+                let prefix = match limit.get("rateLimitType")?.as_str()? {
+                    "REQUEST_WEIGHT" => "x-mbx-used-weight",
+                    "ORDERS" => "x-mbx-order-count",
+                    _ => return None,
+                };
+                let unit = match limit.get("interval")?.as_str()? {
+                    "SECOND" => "s",
+                    "MINUTE" => "m",
+                    "HOUR" => "h",
+                    "DAY" => "d",
+                    _ => return None,
+                };
+                let name = format!("{prefix}-{}{unit}", limit.get("intervalNum")?.as_u64()?);
+                let count = limit.get("count")?.as_u64()?;
+                Some((HeaderName::try_from(name).ok()?, HeaderValue::from(count)))
+            })
+            .collect();
+        if let Some(error) = self.rate_limiter.observe_response(status, &headers) {
+            return Err(error.into());
+        }
+
+        if status == 200 {
+            return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+        }
+        let error = response.get("error");
+        let code = error.and_then(|e| e.get("code")).and_then(|c| c.as_i64()).unwrap_or_default();
+        let msg = error.and_then(|e| e.get("msg")).and_then(|m| m.as_str()).unwrap_or_default();
+        Err(AppError::Other(format!("Binance WebSocket API错误 {code} (HTTP {status}): {msg}")))
+    }
+
+    /// 等待中的请求全部按连接断开返回
+    fn fail_pending(pending: &PendingRequests) {
+        let ids: Vec<String> = pending.iter().map(|entry| entry.key().clone()).collect();
+        for id in ids {
+            if let Some((_, responder)) = pending.remove(&id) {
+                let _ = responder.send(Err(AppError::WebSocketError("连接断开，请求结果未知".to_string())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::binance::futures::connector::BinanceFuturesConnector;
+    use crate::connectors::binance::futures::performance_monitor::{MetricType, PerformanceMonitor};
+    use crate::connectors::common::rate_limiter::RateLimitKind;
+    use crate::connectors::test_support::{MockHttpServer, MockResponse, MockWsServer};
+
+    const SECRET: &str = "test-secret";
+
+    /// 按文档的签名规则校验请求（除signature外的参数按名称排序）
+    fn verify_signature(params: &serde_json::Map<String, Value>) -> bool {
+        let sorted: BTreeMap<&str, Value> = params.iter()
+            .filter(|(key, _)| key.as_str() != "signature")
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect();
+        let mut mac = HmacSha256::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(BinanceFuturesWsApiClient::signature_payload(&sorted).as_bytes());
+        params.get("signature").and_then(|s| s.as_str()) == Some(hex::encode(mac.finalize().into_bytes()).as_str())
+    }
+
+    /// 按方法返回录制的响应（节选自ws-fapi文档）；None表示断开连接
+    fn respond(request: &Value) -> Option<Value> {
+        let id = request["id"].clone();
+        let params = request["params"].as_object().unwrap();
+        let rate_limits = json!([
+            {"rateLimitType": "REQUEST_WEIGHT", "interval": "MINUTE", "intervalNum": 1, "limit": 2400, "count": 1000},
+            {"rateLimitType": "ORDERS", "interval": "SECOND", "intervalNum": 10, "limit": 300, "count": 1},
+        ]);
+        if !verify_signature(params) {
+            return Some(json!({"id": id, "status": 400, "error": {"code": -1022, "msg": "Signature for this request is not valid."}}));
+        }
+
+        let result = match request["method"].as_str().unwrap() {
+            "order.place" if params["newClientOrderId"] == "drop" => return None,
+            "order.place" if params["quantity"] == "5" => {
+                return Some(json!({"id": id, "status": 400, "error": {"code": -2019, "msg": "Margin is insufficient."}, "rateLimits": rate_limits}));
+            }
+            "order.place" => json!({
+                "orderId": 325078477, "symbol": params["symbol"], "status": "NEW", "clientOrderId": params["newClientOrderId"],
+                "price": params["price"], "origQty": params["quantity"], "executedQty": "0.000",
+                "type": params["type"], "side": params["side"], "updateTime": 1703439070685i64,
+            }),
+            "order.cancel" => json!({"orderId": params["orderId"], "symbol": params["symbol"], "status": "CANCELED"}),
+            "order.modify" => json!({"orderId": params["orderId"], "price": params["price"], "origQty": params["quantity"], "status": "NEW"}),
+            "account.status" => json!({
+                "totalWalletBalance": "103.12345678",
+                "assets": [{"asset": "USDT", "walletBalance": "103.12345678", "availableBalance": "100.12345678"}],
+            }),
+            method => panic!("unexpected method {method}"),
+        };
+        Some(json!({"id": id, "status": 200, "result": result, "rateLimits": rate_limits}))
+    }
+
+    /// 模拟ws-fapi服务器：记录收到的请求；clientOrderId为`slow`的下单延迟响应，为`drop`的下单直接断开连接
+    async fn start_mock_ws_api_server() -> MockWsServer {
+        MockWsServer::start(|ws, connection| async move {
+            let (mut sink, mut stream) = ws.split();
+            let (response_tx, mut response_rx) = mpsc::unbounded_channel::<Value>();
+            tokio::spawn(async move {
+                while let Some(response) = response_rx.recv().await {
+                    if sink.send(Message::Text(response.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            while let Some(Ok(Message::Text(text))) = stream.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                connection.record(&request);
+                let Some(response) = respond(&request) else { return };
+                let delay = if request["params"]["newClientOrderId"] == "slow" { 50 } else { 0 };
+                let response_tx = response_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    let _ = response_tx.send(response);
+                });
+            }
+        }).await
+    }
+
+    /// 模拟REST服务器：任何请求都返回同一个订单
+    async fn start_mock_rest_server() -> MockHttpServer {
+        MockHttpServer::respond_with(MockResponse::ok(r#"{"orderId":1001,"symbol":"BTCUSDT","status":"NEW"}"#)).await
+    }
+
+    fn test_config() -> BinanceFuturesConfig {
+        BinanceFuturesConfig {
+            api_key: Some("test-key".to_string()),
+            secret_key: Some(SECRET.to_string()),
+            ..BinanceFuturesConfig::default()
+        }
+    }
+
+    fn limit_order(quantity: f64, client_order_id: &str) -> LocalOrderRequest {
+        LocalOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: "BUY".to_string(),
+            order_type: "LIMIT".to_string(),
+            quantity,
+            price: Some(43187.0),
+            time_in_force: Some("GTC".to_string()),
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: Some(client_order_id.to_string()),
+        }
+    }
+
+    fn ws_api_client(config: &BinanceFuturesConfig, rest_client: &BinanceFuturesRestClient, url: &str) -> BinanceFuturesWsApiClient {
+        BinanceFuturesWsApiClient::new(config, rest_client)
+            .with_url(url)
+            .with_request_timeout(Duration::from_secs(2))
+            .with_reconnect_interval(Duration::from_millis(20))
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_signed_and_matched_by_id() {
+        let mut server = start_mock_ws_api_server().await;
+        let config = test_config();
+        let rest_client = BinanceFuturesRestClient::new(config.clone());
+        let client = ws_api_client(&config, &rest_client, &server.url);
+
+        // 会话建立之前请求不会发出
+        assert!(matches!(client.place_order(&limit_order(0.1, "early")).await, Err(AppError::ConnectionError(_))));
+        client.start().await.unwrap();
+        assert_eq!(client.get_connection_status().await, ConnectionStatus::Connected);
+
+        // 先发出的请求后收到响应，按id对应回各自的调用方
+        let (slow, fast) = (limit_order(0.1, "slow"), limit_order(0.2, "fast"));
+        let (slow_order, fast_order) = tokio::join!(client.place_order(&slow), client.place_order(&fast));
+        assert_eq!((slow_order.unwrap()["clientOrderId"].as_str(), fast_order.unwrap()["origQty"].as_str()), (Some("slow"), Some("0.2")));
+        let (_, _, first) = server.expect(|_, _, _| true).await;
+        let (_, _, second) = server.expect(|_, _, _| true).await;
+        assert_ne!(first["id"], second["id"]);
+        let params = &first["params"];
+        assert_eq!((params["apiKey"].as_str(), params["type"].as_str(), params["timeInForce"].as_str()), (Some("test-key"), Some("LIMIT"), Some("GTC")));
+        assert!(params["timestamp"].is_u64() && params["recvWindow"].is_u64());
+
+        let error = client.place_order(&limit_order(5.0, "big")).await.unwrap_err();
+        assert!(matches!(&error, AppError::Other(msg) if msg.contains("-2019")), "{error:?}");
+
+        let cancelled = client.cancel_order("BTCUSDT", Some(325078477), None).await.unwrap();
+        assert_eq!((cancelled["orderId"].as_u64(), cancelled["status"].as_str()), (Some(325078477), Some("CANCELED")));
+        assert!(matches!(client.cancel_order("BTCUSDT", None, None).await, Err(AppError::ParseError(_))));
+
+        let modified = client.modify_order(&ModifyOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            order_id: Some(325078477),
+            orig_client_order_id: None,
+            side: "BUY".to_string(),
+            quantity: 0.1,
+            price: 43100.5,
+        }).await.unwrap();
+        assert_eq!(modified["price"].as_str(), Some("43100.5"));
+        assert_eq!(client.account_status().await.unwrap()["totalWalletBalance"].as_str(), Some("103.12345678"));
+
+        // 响应中的rateLimits同步到与REST共享的限速器
+        let (remaining, _) = rest_client.rate_limiter().remaining(RateLimitKind::RequestWeight).unwrap();
+        assert!(remaining <= 1400, "{remaining}");
+
+        client.stop().await;
+        assert_eq!(client.get_connection_status().await, ConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_connector_falls_back_to_rest_and_records_latency_per_path() {
+        let ws_server = start_mock_ws_api_server().await;
+        let rest_server = start_mock_rest_server().await;
+        let config = test_config();
+        let monitor = PerformanceMonitor::new();
+
+        let mut connector = BinanceFuturesConnector::new(config.clone())
+            .with_rest_client(BinanceFuturesRestClient::new(config.clone()).with_base_url(rest_server.url.clone()));
+        let ws_api = ws_api_client(&config, connector.rest_client(), &ws_server.url);
+        let ws_api = connector.enable_ws_order_entry(ws_api, monitor.clone());
+
+        // 会话未建立时走REST
+        assert_eq!(connector.place_order(&limit_order(0.1, "a")).await.unwrap()["orderId"], 1001);
+        assert_eq!(rest_server.requests().len(), 1);
+
+        ws_api.start().await.unwrap();
+        assert_eq!(connector.place_order(&limit_order(0.1, "b")).await.unwrap()["orderId"], 325078477);
+
+        // 请求发出后连接断开：结果未知，不能改走REST重复下单
+        let error = connector.place_order(&limit_order(0.1, "drop")).await.unwrap_err();
+        assert!(matches!(error, AppError::WebSocketError(_)), "{error:?}");
+        assert_eq!(rest_server.requests().len(), 1);
+
+        // 会话自动重连后继续走WebSocket API
+        timeout(Duration::from_secs(2), async {
+            while !ws_api.is_connected().await {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        assert_eq!(connector.cancel_order("BTCUSDT", Some(7), None).await.unwrap()["status"], "CANCELED");
+
+        ws_api.stop().await;
+        assert_eq!(connector.cancel_order("BTCUSDT", Some(7), None).await.unwrap()["orderId"], 1001);
+        assert_eq!(rest_server.requests().len(), 2);
+
+        let paths: Vec<_> = monitor.get_recent_metrics(&MetricType::OrderLatency, Duration::from_secs(60)).await
+            .into_iter()
+            .map(|point| format!("{} {}", point.tags["path"], point.tags["method"]))
+            .collect();
+        assert_eq!(paths, ["rest order.place", "ws_api order.place", "ws_api order.place", "ws_api order.cancel", "rest order.cancel"]);
+    }
+}
@@ -7,6 +7,9 @@ pub mod spot;
 pub mod futures;
 pub mod adapter;
 pub mod websocket;
+pub mod orderbook;
+pub mod rest;
+pub mod user_stream;
 pub mod test;
 
 // 重新导出主要类型
@@ -14,6 +17,9 @@ pub use spot::BinanceSpotConnector;
 pub use futures::BinanceFuturesConnector;
 pub use adapter::BinanceAdapter;
 pub use websocket::BinanceWebSocketHandler;
+pub use orderbook::BinanceOrderBookManager;
+pub use rest::BinanceSpotRestClient;
+pub use user_stream::BinanceUserDataStream;
 
 // Binance特定的配置和常量
 pub mod config {
@@ -25,6 +31,12 @@ pub mod config {
         pub secret_key: Option<String>,
         pub testnet: bool,
         pub rate_limit_per_minute: u32,
+        /// 覆盖默认的WebSocket基础地址（如 wss://stream.binance.com:9443）
+        #[serde(default)]
+        pub websocket_url: Option<String>,
+        /// 覆盖默认的REST API地址
+        #[serde(default)]
+        pub rest_api_url: Option<String>,
     }
     
     impl Default for BinanceConfig {
@@ -34,6 +46,8 @@ pub mod config {
                 secret_key: None,
                 testnet: false,
                 rate_limit_per_minute: 1200,
+                websocket_url: None,
+                rest_api_url: None,
             }
         }
     }
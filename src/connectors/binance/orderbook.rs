@@ -0,0 +1,489 @@
+//! Binance现货本地订单簿同步
+//!
+//! 基于REST `/api/v3/depth` 快照与 `@depth@100ms` 增量事件维护完整订单簿，
+//! 同步规则参考Binance官方文档及Hummingbot的 `binance_order_book.py`：
+//! 1. 先缓存增量事件，再拉取快照；
+//! 2. 丢弃 `u <= lastUpdateId` 的事件；
+//! 3. 第一个应用的事件需满足 `U <= lastUpdateId + 1 <= u`；
+//! 4. 之后每个事件的 `U` 必须等于上一个事件的 `u + 1`，否则视为缺口并重新同步。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use log::{info, warn, error, debug};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use tokio::sync::{broadcast, Mutex, RwLock};
+
+use crate::connectors::common::local_orderbook::{LocalOrderBook, parse_price_levels};
+use crate::exchange_types::{Exchange, StandardOrderBook};
+use crate::types::common::{ExchangeType as EventExchangeType, MarketType as EventMarketType};
+use crate::types::events::SystemEvent;
+use crate::types::market_data::DepthUpdate;
+use super::config::DEPTH_PATH;
+
+/// 快照请求的默认档位数量
+pub const DEFAULT_SNAPSHOT_LIMIT: u32 = 1000;
+
+/// 推送到深度队列的默认档位数量
+pub const DEFAULT_PUBLISH_DEPTH: usize = 100;
+
+/// 等待快照期间最多缓存的增量事件数量
+const MAX_BUFFERED_EVENTS: usize = 10_000;
+
+/// 单次重新同步最多拉取快照的次数
+const MAX_SNAPSHOT_ATTEMPTS: u32 = 5;
+
+/// 快照重试间隔
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// 深度增量事件（`depthUpdate`）
+#[derive(Debug, Clone)]
+pub struct DepthDiffEvent {
+    pub symbol: String,
+    pub first_update_id: i64,
+    pub final_update_id: i64,
+    pub event_time: i64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl DepthDiffEvent {
+    /// 从 `depthUpdate` 事件解析
+    pub fn from_json(data: &Value) -> Option<Self> {
+        if data.get("e").and_then(|e| e.as_str()) != Some("depthUpdate") {
+            return None;
+        }
+
+        Some(Self {
+            symbol: data.get("s")?.as_str()?.to_uppercase(),
+            first_update_id: data.get("U")?.as_i64()?,
+            final_update_id: data.get("u")?.as_i64()?,
+            event_time: data.get("E").and_then(|e| e.as_i64()).unwrap_or(0),
+            bids: parse_price_levels(data.get("b")),
+            asks: parse_price_levels(data.get("a")),
+        })
+    }
+}
+
+/// REST深度快照
+#[derive(Debug, Clone)]
+pub struct DepthSnapshot {
+    pub last_update_id: i64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+impl DepthSnapshot {
+    /// 从 `/api/v3/depth` 响应解析
+    pub fn from_json(data: &Value) -> Option<Self> {
+        Some(Self {
+            last_update_id: data.get("lastUpdateId")?.as_i64()?,
+            bids: parse_price_levels(data.get("bids")),
+            asks: parse_price_levels(data.get("asks")),
+        })
+    }
+}
+
+/// 应用增量事件的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffOutcome {
+    /// 已应用到订单簿
+    Applied,
+    /// 尚未同步，事件已缓存；`snapshot_required`为true时需要拉取快照
+    Buffered { snapshot_required: bool },
+    /// 事件早于当前订单簿，已丢弃
+    Stale,
+    /// 序列号出现缺口，订单簿已失效，需要拉取快照
+    Gap { expected: i64, received: i64 },
+}
+
+/// 应用快照的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotOutcome {
+    /// 同步完成，`replayed`为回放的缓存事件数量
+    Synced { replayed: usize },
+    /// 快照早于第一个缓存事件，需要重新拉取
+    SnapshotTooOld,
+    /// 缓存事件之间存在缺口，需要重新拉取
+    Gap { expected: i64, received: i64 },
+}
+
+/// 单个交易对的同步状态机
+#[derive(Debug)]
+pub struct SpotOrderBookSync {
+    book: LocalOrderBook,
+    synced: bool,
+    snapshot_in_flight: bool,
+    buffer: VecDeque<DepthDiffEvent>,
+    resync_count: u64,
+    gap_detected_at: Option<Instant>,
+}
+
+impl SpotOrderBookSync {
+    /// 创建未同步的状态机
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            book: LocalOrderBook::new(symbol),
+            synced: false,
+            snapshot_in_flight: false,
+            buffer: VecDeque::new(),
+            resync_count: 0,
+            gap_detected_at: None,
+        }
+    }
+
+    /// 当前订单簿
+    pub fn book(&self) -> &LocalOrderBook {
+        &self.book
+    }
+
+    /// 是否已与交易所同步
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// 因缺口触发的重新同步次数
+    pub fn resync_count(&self) -> u64 {
+        self.resync_count
+    }
+
+    /// 应用一条增量事件
+    pub fn apply_diff(&mut self, event: DepthDiffEvent) -> DiffOutcome {
+        if !self.synced {
+            if self.buffer.len() >= MAX_BUFFERED_EVENTS {
+                self.buffer.pop_front();
+            }
+            self.buffer.push_back(event);
+            let snapshot_required = !self.snapshot_in_flight;
+            self.snapshot_in_flight = true;
+            return DiffOutcome::Buffered { snapshot_required };
+        }
+
+        let last_update_id = self.book.last_update_id();
+        if event.final_update_id <= last_update_id {
+            return DiffOutcome::Stale;
+        }
+
+        if event.first_update_id > last_update_id + 1 {
+            let outcome = DiffOutcome::Gap {
+                expected: last_update_id + 1,
+                received: event.first_update_id,
+            };
+            self.begin_resync(event);
+            return outcome;
+        }
+
+        self.book.apply_diff(&event.bids, &event.asks, event.final_update_id, event.event_time);
+        DiffOutcome::Applied
+    }
+
+    /// 应用快照并回放缓存的事件
+    pub fn apply_snapshot(&mut self, snapshot: DepthSnapshot) -> SnapshotOutcome {
+        if let Some(first) = self.buffer.front() {
+            if snapshot.last_update_id + 1 < first.first_update_id {
+                return SnapshotOutcome::SnapshotTooOld;
+            }
+        }
+
+        let event_time = self.buffer.front().map(|e| e.event_time).unwrap_or(0);
+        self.book.reset(&snapshot.bids, &snapshot.asks, snapshot.last_update_id, event_time);
+        self.synced = true;
+        self.snapshot_in_flight = false;
+
+        let mut replayed = 0;
+        while let Some(event) = self.buffer.pop_front() {
+            match self.apply_diff(event) {
+                DiffOutcome::Applied => replayed += 1,
+                DiffOutcome::Gap { expected, received } => {
+                    return SnapshotOutcome::Gap { expected, received };
+                }
+                _ => {}
+            }
+        }
+
+        SnapshotOutcome::Synced { replayed }
+    }
+
+    /// 快照拉取失败时释放标记，下一条事件会重新触发拉取
+    pub fn snapshot_failed(&mut self) {
+        self.snapshot_in_flight = false;
+    }
+
+    /// 连接重建后丢弃现有状态，等待重新同步
+    pub fn invalidate(&mut self) {
+        self.book.clear();
+        self.synced = false;
+        self.snapshot_in_flight = false;
+        self.buffer.clear();
+        self.gap_detected_at = None;
+    }
+
+    /// 取出从发现缺口到完成同步的耗时
+    pub fn take_resync_latency(&mut self) -> Option<Duration> {
+        self.gap_detected_at.take().map(|t| t.elapsed())
+    }
+
+    fn begin_resync(&mut self, event: DepthDiffEvent) {
+        self.book.clear();
+        self.synced = false;
+        self.snapshot_in_flight = true;
+        self.buffer.clear();
+        self.buffer.push_back(event);
+        self.resync_count += 1;
+        self.gap_detected_at.get_or_insert_with(Instant::now);
+    }
+}
+
+/// Binance现货订单簿管理器
+///
+/// 为每个交易对维护同步状态机，负责拉取快照、发布深度以及上报数据质量事件。
+#[derive(Clone)]
+pub struct BinanceOrderBookManager {
+    rest_api_url: String,
+    client: reqwest::Client,
+    snapshot_limit: u32,
+    publish_depth: usize,
+    books: Arc<Mutex<HashMap<String, SpotOrderBookSync>>>,
+    app_state: Arc<crate::AppState>,
+    event_sender: Arc<RwLock<Option<broadcast::Sender<SystemEvent>>>>,
+}
+
+impl BinanceOrderBookManager {
+    /// 创建订单簿管理器
+    pub fn new(rest_api_url: &str, app_state: Arc<crate::AppState>) -> Self {
+        Self {
+            rest_api_url: rest_api_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            snapshot_limit: DEFAULT_SNAPSHOT_LIMIT,
+            publish_depth: DEFAULT_PUBLISH_DEPTH,
+            books: Arc::new(Mutex::new(HashMap::new())),
+            app_state,
+            event_sender: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 设置系统事件发送器
+    pub async fn set_event_sender(&self, sender: broadcast::Sender<SystemEvent>) {
+        *self.event_sender.write().await = Some(sender);
+    }
+
+    /// 处理一条增量事件
+    pub async fn handle_diff_event(&self, event: DepthDiffEvent) {
+        let symbol = event.symbol.clone();
+        let first_update_id = event.first_update_id;
+
+        let (outcome, update) = {
+            let mut books = self.books.lock().await;
+            let sync = books.entry(symbol.clone()).or_insert_with(|| SpotOrderBookSync::new(&symbol));
+            let outcome = sync.apply_diff(event);
+            let update = match outcome {
+                DiffOutcome::Applied => Some(sync.book().to_depth_update(first_update_id, self.publish_depth)),
+                _ => None,
+            };
+            (outcome, update)
+        };
+
+        match outcome {
+            DiffOutcome::Applied => {
+                if let Some(update) = update {
+                    self.publish(update);
+                }
+            }
+            DiffOutcome::Buffered { snapshot_required: true } => {
+                info!("[Binance] {symbol} 开始同步本地订单簿");
+                self.spawn_resync(symbol);
+            }
+            DiffOutcome::Gap { expected, received } => {
+                warn!("[Binance] {symbol} 深度序列缺口: 期望U<={expected}, 实际U={received}，重新同步");
+                self.spawn_resync(symbol);
+            }
+            DiffOutcome::Buffered { snapshot_required: false } | DiffOutcome::Stale => {}
+        }
+    }
+
+    /// 获取已同步的订单簿
+    pub async fn get_orderbook(&self, symbol: &str) -> Option<StandardOrderBook> {
+        let books = self.books.lock().await;
+        books.get(&symbol.to_uppercase())
+            .filter(|sync| sync.is_synced())
+            .map(|sync| sync.book().to_standardized(Exchange::Binance, self.publish_depth))
+    }
+
+    /// 获取交易对因缺口触发的重新同步次数
+    pub async fn get_resync_count(&self, symbol: &str) -> u64 {
+        let books = self.books.lock().await;
+        books.get(&symbol.to_uppercase()).map(|sync| sync.resync_count()).unwrap_or(0)
+    }
+
+    /// 连接重建时使所有订单簿失效
+    pub async fn invalidate_all(&self) {
+        let mut books = self.books.lock().await;
+        for sync in books.values_mut() {
+            sync.invalidate();
+        }
+    }
+
+    fn spawn_resync(&self, symbol: String) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.resync(&symbol).await;
+        });
+    }
+
+    /// 拉取快照并回放缓存事件，直到同步成功或重试次数耗尽
+    async fn resync(&self, symbol: &str) {
+        for attempt in 1..=MAX_SNAPSHOT_ATTEMPTS {
+            let snapshot = match self.fetch_snapshot(symbol).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    warn!("[Binance] {symbol} 获取深度快照失败 (尝试 {attempt}/{MAX_SNAPSHOT_ATTEMPTS}): {e}");
+                    tokio::time::sleep(SNAPSHOT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+
+            let (outcome, update, latency) = {
+                let mut books = self.books.lock().await;
+                let Some(sync) = books.get_mut(symbol) else { return };
+                let outcome = sync.apply_snapshot(snapshot);
+                match outcome {
+                    SnapshotOutcome::Synced { .. } => {
+                        let last_update_id = sync.book().last_update_id();
+                        let update = sync.book().to_depth_update(last_update_id, self.publish_depth);
+                        (outcome, Some(update), sync.take_resync_latency())
+                    }
+                    _ => (outcome, None, None),
+                }
+            };
+
+            match outcome {
+                SnapshotOutcome::Synced { replayed } => {
+                    info!("[Binance] {symbol} 本地订单簿已同步，回放 {replayed} 条缓存事件");
+                    if let Some(update) = update {
+                        self.publish(update);
+                    }
+                    if let Some(latency) = latency {
+                        self.emit_data_quality(symbol, latency).await;
+                    }
+                    return;
+                }
+                SnapshotOutcome::SnapshotTooOld => {
+                    debug!("[Binance] {symbol} 快照早于缓存事件，重新拉取");
+                    tokio::time::sleep(SNAPSHOT_RETRY_DELAY).await;
+                }
+                SnapshotOutcome::Gap { expected, received } => {
+                    warn!("[Binance] {symbol} 缓存事件存在缺口: 期望U<={expected}, 实际U={received}，重新拉取快照");
+                }
+            }
+        }
+
+        error!("[Binance] {symbol} 多次尝试后仍未能同步订单簿，等待下一条事件重试");
+        if let Some(sync) = self.books.lock().await.get_mut(symbol) {
+            sync.snapshot_failed();
+        }
+    }
+
+    async fn fetch_snapshot(&self, symbol: &str) -> Result<DepthSnapshot, String> {
+        let url = format!("{}{}?symbol={}&limit={}", self.rest_api_url, DEPTH_PATH, symbol, self.snapshot_limit);
+        let response = self.client.get(&url).send().await
+            .map_err(|e| format!("请求失败: {e}"))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP状态码: {}", response.status()));
+        }
+
+        let body: Value = response.json().await
+            .map_err(|e| format!("解析响应失败: {e}"))?;
+        DepthSnapshot::from_json(&body).ok_or_else(|| "快照缺少lastUpdateId".to_string())
+    }
+
+    fn publish(&self, update: DepthUpdate) {
+        if let Some(ref sender) = self.app_state.depth_queue {
+            if let Err(e) = sender.send(update) {
+                error!("[Binance] 发送深度数据到队列失败: {e}");
+            }
+        }
+    }
+
+    async fn emit_data_quality(&self, symbol: &str, latency: Duration) {
+        if let Some(sender) = self.event_sender.read().await.as_ref() {
+            let _ = sender.send(SystemEvent::DataQuality {
+                exchange: EventExchangeType::Binance,
+                market_type: EventMarketType::Spot,
+                symbol: symbol.to_string(),
+                latency_ms: latency.as_millis() as u64,
+                timestamp: SystemTime::now(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn diff(first: i64, last: i64, bids: Value, asks: Value) -> DepthDiffEvent {
+        DepthDiffEvent::from_json(&json!({
+            "e": "depthUpdate", "E": 1700000000000i64, "s": "BTCUSDT",
+            "U": first, "u": last, "b": bids, "a": asks
+        })).unwrap()
+    }
+
+    fn snapshot(last_update_id: i64) -> DepthSnapshot {
+        DepthSnapshot::from_json(&json!({
+            "lastUpdateId": last_update_id,
+            "bids": [["100.0", "1.0"], ["99.0", "2.0"]],
+            "asks": [["101.0", "1.0"], ["102.0", "2.0"]]
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_buffer_until_snapshot_then_replay() {
+        let mut sync = SpotOrderBookSync::new("BTCUSDT");
+
+        assert_eq!(sync.apply_diff(diff(95, 99, json!([]), json!([]))), DiffOutcome::Buffered { snapshot_required: true });
+        assert_eq!(sync.apply_diff(diff(100, 102, json!([["100.0", "0"]]), json!([]))), DiffOutcome::Buffered { snapshot_required: false });
+        assert_eq!(sync.apply_diff(diff(103, 104, json!([]), json!([["100.5", "3"]]))), DiffOutcome::Buffered { snapshot_required: false });
+
+        // 快照lastUpdateId=100：第一个事件被丢弃，第二个事件满足 U <= 101 <= u
+        assert_eq!(sync.apply_snapshot(snapshot(100)), SnapshotOutcome::Synced { replayed: 2 });
+        assert!(sync.is_synced());
+        assert_eq!(sync.book().last_update_id(), 104);
+        assert_eq!(sync.book().best_bid(), Some(99.0));
+        assert_eq!(sync.book().best_ask(), Some(100.5));
+    }
+
+    #[test]
+    fn test_snapshot_older_than_buffer_is_rejected() {
+        let mut sync = SpotOrderBookSync::new("BTCUSDT");
+        sync.apply_diff(diff(200, 210, json!([]), json!([])));
+
+        assert_eq!(sync.apply_snapshot(snapshot(150)), SnapshotOutcome::SnapshotTooOld);
+        assert!(!sync.is_synced());
+        assert_eq!(sync.apply_snapshot(snapshot(205)), SnapshotOutcome::Synced { replayed: 1 });
+    }
+
+    #[test]
+    fn test_gap_triggers_resync() {
+        let mut sync = SpotOrderBookSync::new("BTCUSDT");
+        sync.apply_diff(diff(101, 101, json!([]), json!([])));
+        sync.apply_snapshot(snapshot(100));
+
+        assert_eq!(sync.apply_diff(diff(102, 105, json!([]), json!([]))), DiffOutcome::Applied);
+        assert_eq!(sync.apply_diff(diff(100, 104, json!([]), json!([]))), DiffOutcome::Stale);
+        assert_eq!(sync.apply_diff(diff(110, 112, json!([]), json!([]))), DiffOutcome::Gap { expected: 106, received: 110 });
+        assert!(!sync.is_synced());
+        assert_eq!(sync.resync_count(), 1);
+        assert!(sync.book().is_empty());
+
+        // 缺口后的事件已缓存，新快照可以衔接
+        assert_eq!(sync.apply_snapshot(snapshot(111)), SnapshotOutcome::Synced { replayed: 1 });
+        assert_eq!(sync.book().last_update_id(), 112);
+        assert!(sync.take_resync_latency().is_some());
+    }
+}
@@ -0,0 +1,476 @@
+//! Binance现货REST客户端
+//!
+//! 实现HMAC-SHA256签名的现货交易接口（下单、撤单、查询订单、当前挂单、账户余额、成交历史）
+//! 以及用户数据流listenKey的创建、续期和关闭
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::debug;
+use reqwest::{Client, Method, RequestBuilder};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::config::get_config;
+use crate::connectors::binance::futures::rest_api::parse_exchange_info;
+use crate::connectors::common::clock_sync::{ExchangeClock, ServerTimeSource};
+use crate::connectors::common::instruments::{InstrumentSource, InstrumentSpec};
+use crate::connectors::common::rate_limiter::{RateLimitRule, RateLimiter, RequestCost};
+use crate::exchange_types::Exchange;
+use crate::types::account::{AccountBalance, CurrencyBalance};
+use crate::types::errors::ConnectorError;
+use crate::types::orders::{ExecutionRecord, OrderRequest, OrderResponse, OrderSide, OrderStatus, OrderType, TimeInForce};
+use crate::types::exchange::ExchangeType;
+use super::config::{BinanceConfig, BINANCE_SPOT_API_URL, BINANCE_SPOT_TESTNET_API_URL, EXCHANGE_INFO_PATH, SERVER_TIME_PATH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ORDER_PATH: &str = "/api/v3/order";
+const OPEN_ORDERS_PATH: &str = "/api/v3/openOrders";
+const ACCOUNT_PATH: &str = "/api/v3/account";
+const MY_TRADES_PATH: &str = "/api/v3/myTrades";
+const USER_DATA_STREAM_PATH: &str = "/api/v3/userDataStream";
+
+/// 现货下单次数限制（每10秒）
+pub const SPOT_ORDER_RATE_LIMIT_PER_10S: u32 = 50;
+/// 现货下单次数限制（每天）
+pub const SPOT_ORDER_RATE_LIMIT_PER_DAY: u32 = 160_000;
+
+/// Binance现货REST客户端
+#[derive(Clone)]
+pub struct BinanceSpotRestClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    /// 请求限速器，按响应头中的已用权重校正
+    rate_limiter: Arc<RateLimiter>,
+    /// 服务器时钟，签名请求的timestamp按估计的偏移校正
+    clock: Arc<ExchangeClock>,
+}
+
+impl BinanceSpotRestClient {
+    /// 根据Binance配置创建REST客户端，`rest_api_url` 可指向本地模拟服务器
+    pub fn new(config: &BinanceConfig) -> Self {
+        let base_url = match config.rest_api_url.as_deref() {
+            Some(url) => url,
+            None if config.testnet => BINANCE_SPOT_TESTNET_API_URL,
+            None => BINANCE_SPOT_API_URL,
+        };
+
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone().filter(|k| !k.is_empty()),
+            secret_key: config.secret_key.clone().filter(|k| !k.is_empty()),
+            rate_limiter: Arc::new(Self::default_rate_limiter(config)),
+            clock: Arc::new(ExchangeClock::new(Exchange::Binance, get_config().clock_sync.recv_window_ms)),
+        }
+    }
+
+    /// 按配置的额度创建限速器：请求权重/分钟、下单次数/10秒、下单次数/天
+    pub fn default_rate_limiter(config: &BinanceConfig) -> RateLimiter {
+        RateLimiter::new(Exchange::Binance, vec![
+            RateLimitRule::request_weight(Duration::from_secs(60), config.rate_limit_per_minute),
+            RateLimitRule::orders(Duration::from_secs(10), SPOT_ORDER_RATE_LIMIT_PER_10S),
+            RateLimitRule::orders(Duration::from_secs(86_400), SPOT_ORDER_RATE_LIMIT_PER_DAY),
+        ])
+    }
+
+    /// 使用与其他客户端共享的限速器
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// 请求限速器
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// 服务器时钟
+    pub fn clock(&self) -> &Arc<ExchangeClock> {
+        &self.clock
+    }
+
+    /// 是否配置了API密钥
+    pub fn has_credentials(&self) -> bool {
+        self.api_key.is_some() && self.secret_key.is_some()
+    }
+
+    /// 获取服务器时间（毫秒）
+    pub async fn get_server_time(&self) -> Result<i64, ConnectorError> {
+        let data = self.public_request(SERVER_TIME_PATH, RequestCost::weight(1)).await?;
+        data.get("serverTime")
+            .and_then(|t| t.as_i64())
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("Binance服务器时间无效: {data}")))
+    }
+
+    /// 计算签名：HMAC_SHA256(secret_key, 完整的查询字符串)，十六进制小写
+    pub fn sign(secret_key: &str, payload: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 下单，使用FULL响应类型以便直接得到成交数量和均价
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        let params = Self::build_order_params(order)?;
+        let data = self.signed_request(Method::POST, ORDER_PATH, params, RequestCost::order(1)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderPlacementFailed(msg),
+                other => other,
+            })?;
+        Self::parse_order_response(&data)
+    }
+
+    /// 撤单
+    pub async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
+        let params = vec![
+            ("symbol", Self::to_binance_symbol(symbol)),
+            ("orderId", order_id.to_string()),
+        ];
+        let data = self.signed_request(Method::DELETE, ORDER_PATH, params, RequestCost::weight(1)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderCancellationFailed(msg),
+                other => other,
+            })?;
+        Ok(data.get("status").and_then(|s| s.as_str()).map(Self::normalize_order_status) == Some("CANCELED"))
+    }
+
+    /// 查询订单状态
+    pub async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        let params = vec![
+            ("symbol", Self::to_binance_symbol(symbol)),
+            ("orderId", order_id.to_string()),
+        ];
+        let data = self.signed_request(Method::GET, ORDER_PATH, params, RequestCost::weight(4)).await?;
+        Self::parse_order_status(&data)
+    }
+
+    /// 查询当前挂单，不指定交易对时返回全部交易对的挂单（权重更高）
+    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OrderStatus>, ConnectorError> {
+        let params: Vec<_> = symbol.map(|s| ("symbol", Self::to_binance_symbol(s))).into_iter().collect();
+        let cost = RequestCost::weight(if symbol.is_some() { 6 } else { 80 });
+        let data = self.signed_request(Method::GET, OPEN_ORDERS_PATH, params, cost).await?;
+        data.as_array()
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("Binance挂单响应不是数组: {data}")))?
+            .iter()
+            .map(Self::parse_order_status)
+            .collect()
+    }
+
+    /// 查询账户余额
+    pub async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        let params = vec![("omitZeroBalances", "true".to_string())];
+        let data = self.signed_request(Method::GET, ACCOUNT_PATH, params, RequestCost::weight(20)).await?;
+        Ok(Self::parse_account(&data))
+    }
+
+    /// 查询交易对的成交历史
+    pub async fn get_my_trades(&self, symbol: &str, limit: Option<u16>) -> Result<Vec<ExecutionRecord>, ConnectorError> {
+        let mut params = vec![("symbol", Self::to_binance_symbol(symbol))];
+        if let Some(limit) = limit {
+            params.push(("limit", limit.to_string()));
+        }
+        let data = self.signed_request(Method::GET, MY_TRADES_PATH, params, RequestCost::weight(20)).await?;
+        Ok(data.as_array().into_iter().flatten().filter_map(Self::parse_trade).collect())
+    }
+
+    /// 创建用户数据流，返回listenKey（只需API Key，无需签名）
+    pub async fn start_user_data_stream(&self) -> Result<String, ConnectorError> {
+        let data = self.api_key_request(Method::POST, USER_DATA_STREAM_PATH, &[]).await?;
+        data.get("listenKey")
+            .and_then(|k| k.as_str())
+            .map(|k| k.to_string())
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("Binance listenKey响应无效: {data}")))
+    }
+
+    /// 续期用户数据流，listenKey 60分钟内未续期会失效
+    pub async fn keepalive_user_data_stream(&self, listen_key: &str) -> Result<(), ConnectorError> {
+        self.api_key_request(Method::PUT, USER_DATA_STREAM_PATH, &[("listenKey", listen_key.to_string())]).await?;
+        Ok(())
+    }
+
+    /// 关闭用户数据流
+    pub async fn close_user_data_stream(&self, listen_key: &str) -> Result<(), ConnectorError> {
+        self.api_key_request(Method::DELETE, USER_DATA_STREAM_PATH, &[("listenKey", listen_key.to_string())]).await?;
+        Ok(())
+    }
+
+    /// 将统一订单请求转换为下单参数（不含timestamp和签名）
+    pub fn build_order_params(order: &OrderRequest) -> Result<Vec<(&'static str, String)>, ConnectorError> {
+        if order.reduce_only == Some(true) || order.close_position == Some(true) {
+            return Err(ConnectorError::InvalidOrderParameters("现货不支持只减仓/平仓订单".to_string()));
+        }
+
+        let mut params = vec![
+            ("symbol", Self::to_binance_symbol(&order.symbol)),
+            ("side", order.side.as_str().to_string()),
+        ];
+        match order.order_type {
+            OrderType::Market => {
+                params.push(("type", "MARKET".to_string()));
+                params.push(("quantity", order.quantity.to_string()));
+            }
+            OrderType::Limit => {
+                let price = order.price.ok_or_else(|| {
+                    ConnectorError::InvalidOrderParameters("限价单必须指定价格".to_string())
+                })?;
+                // 只做挂单在现货上是独立的订单类型，不带timeInForce
+                match order.time_in_force {
+                    Some(TimeInForce::GTX) => params.push(("type", "LIMIT_MAKER".to_string())),
+                    Some(TimeInForce::GTD) => {
+                        return Err(ConnectorError::InvalidOrderParameters("Binance现货不支持GTD订单".to_string()));
+                    }
+                    time_in_force => {
+                        params.push(("type", "LIMIT".to_string()));
+                        params.push(("timeInForce", time_in_force.unwrap_or(TimeInForce::GTC).as_str().to_string()));
+                    }
+                }
+                params.push(("quantity", order.quantity.to_string()));
+                params.push(("price", price.to_string()));
+            }
+            OrderType::StopMarket | OrderType::StopLimit => {
+                return Err(ConnectorError::InvalidOrderParameters("Binance现货连接器暂不支持条件单".to_string()));
+            }
+        }
+        if let Some(client_order_id) = &order.client_order_id {
+            params.push(("newClientOrderId", client_order_id.clone()));
+        }
+        params.push(("newOrderRespType", "FULL".to_string()));
+        Ok(params)
+    }
+
+    /// 解析下单响应，均价由累计成交额/累计成交量计算
+    pub fn parse_order_response(data: &Value) -> Result<OrderResponse, ConnectorError> {
+        let status = Self::parse_order_status(data)?;
+        Ok(OrderResponse {
+            order_id: status.order_id,
+            client_order_id: data.get("clientOrderId").and_then(|v| v.as_str())
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string()),
+            symbol: status.symbol,
+            status: status.status,
+            filled_quantity: status.filled_quantity,
+            remaining_quantity: status.remaining_quantity,
+            average_price: status.average_price,
+            timestamp: Self::field_f64(data, "transactTime").map(|t| t as u64).unwrap_or(status.timestamp),
+        })
+    }
+
+    /// 解析订单详情（查询订单、当前挂单、撤单响应格式相同）
+    pub fn parse_order_status(data: &Value) -> Result<OrderStatus, ConnectorError> {
+        let order_id = data.get("orderId")
+            .and_then(|v| v.as_u64().map(|id| id.to_string()).or_else(|| v.as_str().map(|id| id.to_string())))
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("Binance订单缺少orderId: {data}")))?;
+        let executed = Self::field_f64(data, "executedQty").unwrap_or(0.0);
+        let original = Self::field_f64(data, "origQty").unwrap_or(executed);
+        let quote = Self::field_f64(data, "cummulativeQuoteQty").unwrap_or(0.0);
+
+        Ok(OrderStatus {
+            order_id,
+            symbol: data.get("symbol").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            status: Self::normalize_order_status(data.get("status").and_then(|v| v.as_str()).unwrap_or_default()).to_string(),
+            filled_quantity: executed,
+            remaining_quantity: (original - executed).max(0.0),
+            average_price: (executed > 0.0 && quote > 0.0).then(|| quote / executed),
+            timestamp: ["updateTime", "transactTime", "time"].iter()
+                .find_map(|key| Self::field_f64(data, key))
+                .map(|t| t as u64)
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64),
+        })
+    }
+
+    /// 解析账户信息中的余额
+    pub fn parse_account(data: &Value) -> AccountBalance {
+        let mut balances = HashMap::new();
+        for item in data.get("balances").and_then(|b| b.as_array()).into_iter().flatten() {
+            let Some(asset) = item.get("asset").and_then(|a| a.as_str()) else { continue };
+            let free = Self::field_f64(item, "free").unwrap_or(0.0);
+            let locked = Self::field_f64(item, "locked").unwrap_or(0.0);
+            if free == 0.0 && locked == 0.0 {
+                continue;
+            }
+            balances.insert(asset.to_string(), CurrencyBalance {
+                currency: asset.to_string(),
+                total: free + locked,
+                available: free,
+                frozen: locked,
+            });
+        }
+
+        // 现货账户没有统一计价的总额，汇总字段按计价资产USDT填写
+        let usdt = balances.get("USDT");
+        AccountBalance {
+            total: usdt.map_or(0.0, |b| b.total),
+            available: usdt.map_or(0.0, |b| b.available),
+            frozen: usdt.map_or(0.0, |b| b.frozen),
+            balances,
+        }
+    }
+
+    /// 解析一条成交记录
+    pub fn parse_trade(data: &Value) -> Option<ExecutionRecord> {
+        Some(ExecutionRecord {
+            execution_id: data.get("id")?.as_u64()?.to_string(),
+            order_id: data.get("orderId")?.as_u64()?.to_string(),
+            symbol: data.get("symbol")?.as_str()?.to_string(),
+            exchange: ExchangeType::Binance,
+            side: if data.get("isBuyer")?.as_bool()? { OrderSide::Buy } else { OrderSide::Sell },
+            quantity: Self::field_f64(data, "qty")?,
+            price: Self::field_f64(data, "price")?,
+            commission: Self::field_f64(data, "commission").unwrap_or(0.0),
+            timestamp: data.get("time").and_then(|t| t.as_i64()).unwrap_or_default(),
+        })
+    }
+
+    /// Binance订单状态转换为统一的大写状态
+    pub fn normalize_order_status(status: &str) -> &'static str {
+        match status {
+            "NEW" | "PENDING_NEW" => "NEW",
+            "PARTIALLY_FILLED" => "PARTIALLY_FILLED",
+            "FILLED" => "FILLED",
+            "CANCELED" | "PENDING_CANCEL" => "CANCELED",
+            "REJECTED" => "REJECTED",
+            "EXPIRED" | "EXPIRED_IN_MATCH" => "EXPIRED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// 去掉交易所前缀和分隔符，得到Binance交易对（BTCUSDT）
+    pub fn to_binance_symbol(symbol: &str) -> String {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        let symbol = symbol.strip_prefix("BINANCE_").unwrap_or(symbol);
+        symbol.replace(['_', '-', '/'], "").to_uppercase()
+    }
+
+    /// 读取字符串或数字格式的数值字段
+    pub fn field_f64(value: &Value, key: &str) -> Option<f64> {
+        let field = value.get(key)?;
+        field.as_f64().or_else(|| field.as_str().and_then(|s| s.parse::<f64>().ok()))
+    }
+
+    /// 发送公开请求
+    async fn public_request(&self, path: &str, cost: RequestCost) -> Result<Value, ConnectorError> {
+        let url = format!("{}{}", self.base_url, path);
+        self.execute(self.client.get(&url), cost).await
+    }
+
+    /// 发送只需API Key的请求（用户数据流）
+    async fn api_key_request(&self, method: Method, path: &str, params: &[(&str, String)]) -> Result<Value, ConnectorError> {
+        let Some(api_key) = &self.api_key else {
+            return Err(ConnectorError::InvalidCredentials("Binance API密钥未配置".to_string()));
+        };
+        let url = format!("{}{}", self.base_url, path);
+        let request = self.client.request(method, &url)
+            .header("X-MBX-APIKEY", api_key)
+            .query(params);
+        self.execute(request, RequestCost::weight(2)).await
+    }
+
+    /// 发送签名请求：参数追加timestamp和recvWindow后签名，全部放在查询字符串中
+    async fn signed_request(
+        &self,
+        method: Method,
+        path: &str,
+        mut params: Vec<(&'static str, String)>,
+        cost: RequestCost,
+    ) -> Result<Value, ConnectorError> {
+        let (Some(api_key), Some(secret_key)) = (&self.api_key, &self.secret_key) else {
+            return Err(ConnectorError::InvalidCredentials("Binance API密钥未配置".to_string()));
+        };
+
+        params.push(("recvWindow", self.clock.recv_window_ms().to_string()));
+        params.push(("timestamp", self.clock.now_ms().to_string()));
+        let query_string = params.iter()
+            .map(|(k, v)| format!("{k}={}", urlencode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signature = Self::sign(secret_key, &query_string);
+
+        let url = format!("{}{}?{}&signature={}", self.base_url, path, query_string, signature);
+        debug!("[Binance] {method} {}{}", self.base_url, path);
+
+        let request = self.client.request(method, &url).header("X-MBX-APIKEY", api_key);
+        self.execute(request, cost).await
+    }
+
+    /// 经过限速器发送请求，用响应头同步已用额度并转换错误码
+    async fn execute(&self, request: RequestBuilder, cost: RequestCost) -> Result<Value, ConnectorError> {
+        self.rate_limiter.acquire(cost).await
+            .map_err(|e| ConnectorError::RateLimitExceeded(e.to_string()))?;
+
+        let response = request.send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("Binance请求失败: {e}")))?;
+        let status = response.status();
+        if let Some(error) = self.rate_limiter.observe_response(status.as_u16(), response.headers()) {
+            return Err(ConnectorError::RateLimitExceeded(error.to_string()));
+        }
+
+        let body = response.text().await
+            .map_err(|e| ConnectorError::NetworkError(format!("读取Binance响应失败: {e}")))?;
+        // listenKey续期和关闭返回空对象
+        let data: Value = if body.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&body)
+                .map_err(|e| ConnectorError::DataParsingError(format!("解析Binance响应失败 (HTTP {status}): {e}")))?
+        };
+
+        if status.is_success() {
+            return Ok(data);
+        }
+        match data.get("code").and_then(|c| c.as_i64()) {
+            Some(code) => Err(Self::map_error(code, data.get("msg").and_then(|m| m.as_str()).unwrap_or_default())),
+            None => Err(ConnectorError::InvalidResponse(format!("Binance HTTP {status}: {body}"))),
+        }
+    }
+
+    /// 将Binance错误码映射为连接器错误
+    fn map_error(code: i64, message: &str) -> ConnectorError {
+        let detail = format!("Binance错误 {code}: {message}");
+        match code {
+            -1022 | -2014 | -2015 => ConnectorError::AuthenticationFailed(detail),
+            -1003 | -1015 => ConnectorError::RateLimitExceeded(detail),
+            -1121 => ConnectorError::InvalidSymbol(detail),
+            -2010 if message.to_lowercase().contains("insufficient balance") => ConnectorError::InsufficientBalance(detail),
+            -1013 | -1102..=-1100 | -1106..=-1104 | -1117..=-1111 => ConnectorError::InvalidOrderParameters(detail),
+            _ => ConnectorError::TradingError(detail),
+        }
+    }
+}
+
+/// 百分号编码查询参数的值（客户端订单ID等可能包含保留字符）
+pub(crate) fn urlencode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect()
+}
+
+#[async_trait]
+impl ServerTimeSource for BinanceSpotRestClient {
+    async fn server_time_ms(&self) -> Result<i64, ConnectorError> {
+        self.get_server_time().await
+    }
+}
+
+#[async_trait]
+impl InstrumentSource for BinanceSpotRestClient {
+    fn exchange(&self) -> Exchange {
+        Exchange::Binance
+    }
+
+    async fn load_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+        let data = self.public_request(EXCHANGE_INFO_PATH, RequestCost::weight(20)).await?;
+        Ok(parse_exchange_info(Exchange::Binance, &data))
+    }
+}
@@ -47,10 +47,10 @@ impl BinanceSpotConnector {
     
     /// 获取组合流WebSocket URL
     pub fn get_combined_stream_url(&self, streams: &[String]) -> String {
-        let base_url = if self.config.testnet {
-            "wss://testnet.binance.vision:9443"
-        } else {
-            "wss://stream.binance.com:9443"
+        let base_url = match self.config.websocket_url.as_deref() {
+            Some(url) => url.trim_end_matches('/'),
+            None if self.config.testnet => "wss://testnet.binance.vision:9443",
+            None => "wss://stream.binance.com:9443",
         };
         
         if streams.is_empty() {
@@ -62,8 +62,10 @@ impl BinanceSpotConnector {
     }
     
     /// 获取REST API URL
-    pub fn get_api_url(&self) -> &'static str {
-        if self.config.testnet {
+    pub fn get_api_url(&self) -> &str {
+        if let Some(url) = self.config.rest_api_url.as_deref() {
+            url
+        } else if self.config.testnet {
             super::config::BINANCE_SPOT_TESTNET_API_URL
         } else {
             super::config::BINANCE_SPOT_API_URL
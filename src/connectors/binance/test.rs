@@ -28,6 +28,8 @@ mod tests {
             secret_key: None,
             testnet: use_testnet, // 默认使用生产环境，提高连接稳定性
             rate_limit_per_minute: 1200,
+            websocket_url: None,
+            rest_api_url: None,
         }
     }
     
@@ -154,7 +156,9 @@ mod tests {
     }
     
     #[tokio::test]
-    async fn test_binance_trading_not_implemented() {
+    async fn test_binance_trading_requires_credentials() {
+        use crate::types::errors::ConnectorError;
+
         let _ = env_logger::try_init();
         
         let config = create_test_config();
@@ -162,7 +166,7 @@ mod tests {
         let app_state = Arc::new(AppState::new());
         let adapter = BinanceAdapter::new(config, app_state).await.unwrap();
         
-        // 测试交易功能（应该返回未实现错误）
+        // 未配置API密钥时，签名接口在发出请求前返回凭证错误
         let order = OrderRequest {
             symbol: "BTCUSDT".to_string(),
             exchange: ExchangeType::Binance,
@@ -178,15 +182,18 @@ mod tests {
         };
         
         let result = adapter.place_order(&order).await;
-        assert!(result.is_err(), "交易功能应该返回未实现错误");
+        assert!(matches!(result, Err(ConnectorError::InvalidCredentials(_))), "下单应该返回凭证错误");
         
         let cancel_result = adapter.cancel_order("test_order", "BTCUSDT").await;
-        assert!(cancel_result.is_err(), "取消订单功能应该返回未实现错误");
+        assert!(matches!(cancel_result, Err(ConnectorError::InvalidCredentials(_))), "撤单应该返回凭证错误");
         
         let balance_result = adapter.get_account_balance().await;
-        assert!(balance_result.is_err(), "获取余额功能应该返回未实现错误");
+        assert!(matches!(balance_result, Err(ConnectorError::InvalidCredentials(_))), "查询余额应该返回凭证错误");
+        
+        let user_stream_result = adapter.subscribe_user_stream().await;
+        assert!(matches!(user_stream_result, Err(ConnectorError::InvalidCredentials(_))), "订阅用户数据流应该返回凭证错误");
         
-        info!("✅ Binance连接器交易功能未实现测试通过");
+        info!("✅ Binance连接器交易凭证检查测试通过");
     }
     
     #[tokio::test]
@@ -231,6 +238,8 @@ mod integration_tests {
             secret_key: None,
             testnet: true,
             rate_limit_per_minute: 1200,
+            websocket_url: None,
+            rest_api_url: None,
         }
     }
     
@@ -298,4 +307,403 @@ mod integration_tests {
         
         info!("✅ Binance真实连接集成测试完成");
     }
-}
\ No newline at end of file
+}
+
+/// 本地订单簿同步测试（使用本地模拟的WebSocket与REST服务器）
+#[cfg(test)]
+mod orderbook_sync_tests {
+    use super::super::adapter::BinanceAdapter;
+    use super::super::config::BinanceConfig;
+    use crate::connectors::test_support::{MockHttpServer, MockResponse, MockWsServer};
+    use crate::connectors::traits::{DataFlowManager, ExchangeConnector};
+    use crate::core::AppState;
+    use crate::types::{common::DataType, events::SystemEvent};
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+    use tokio::time::{sleep, timeout, Duration};
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn depth_update(first: i64, last: i64, bids: Value, asks: Value) -> String {
+        json!({
+            "stream": "btcusdt@depth@100ms",
+            "data": {
+                "e": "depthUpdate", "E": 1700000000000i64, "s": "BTCUSDT",
+                "U": first, "u": last, "b": bids, "a": asks
+            }
+        }).to_string()
+    }
+
+    /// 模拟REST服务器：按请求顺序依次返回快照，并通知已完成的请求
+    async fn start_mock_rest_server(snapshots: Vec<Value>, served: Arc<Notify>) -> MockHttpServer {
+        let counter = AtomicUsize::new(0);
+        MockHttpServer::start(move |_| {
+            let index = counter.fetch_add(1, Ordering::SeqCst).min(snapshots.len() - 1);
+            served.notify_one();
+            MockResponse::ok(snapshots[index].to_string())
+        }).await
+    }
+
+    /// 模拟WebSocket服务器：先推送可衔接的增量事件，首个快照返回后推送存在缺口的事件
+    async fn start_mock_ws_server(served: Arc<Notify>) -> MockWsServer {
+        MockWsServer::start(move |mut ws, _| {
+            let served = served.clone();
+            async move {
+                ws.send(Message::Text(depth_update(95, 101, json!([["100.0", "0"]]), json!([])))).await.unwrap();
+                ws.send(Message::Text(depth_update(102, 105, json!([]), json!([["100.5", "3"]])))).await.unwrap();
+
+                // 等待首次同步完成后再制造缺口
+                served.notified().await;
+                sleep(Duration::from_millis(200)).await;
+                ws.send(Message::Text(depth_update(110, 112, json!([["200.5", "2"]]), json!([])))).await.unwrap();
+
+                while let Some(Ok(_)) = ws.next().await {}
+            }
+        }).await
+    }
+
+    #[tokio::test]
+    async fn test_binance_orderbook_resync_after_gap() {
+        let _ = env_logger::try_init();
+
+        let served = Arc::new(Notify::new());
+        let rest_server = start_mock_rest_server(
+            vec![
+                json!({"lastUpdateId": 100, "bids": [["100.0", "1"], ["99.0", "2"]], "asks": [["101.0", "1"]]}),
+                json!({"lastUpdateId": 111, "bids": [["200.0", "1"]], "asks": [["201.0", "1"]]}),
+            ],
+            served.clone(),
+        ).await;
+        let mut ws_server = start_mock_ws_server(served).await;
+
+        let config = BinanceConfig {
+            api_key: None,
+            secret_key: None,
+            testnet: false,
+            rate_limit_per_minute: 1200,
+            websocket_url: Some(ws_server.url.clone()),
+            rest_api_url: Some(rest_server.url.clone()),
+        };
+        let adapter = BinanceAdapter::new(config, Arc::new(AppState::new())).await.unwrap();
+        let mut events = adapter.subscribe_events();
+
+        // 未连接时订阅会自动建立连接
+        adapter.subscribe_market_data(vec!["BTCUSDT".to_string()], vec![DataType::OrderBook]).await.expect("连接模拟服务器失败");
+
+        let ws_path = ws_server.next_connection().await;
+        assert!(ws_path.contains("btcusdt@depth@100ms"), "应订阅增量深度流: {ws_path}");
+
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("等待数据质量事件超时")
+            .unwrap();
+        match event {
+            SystemEvent::DataQuality { symbol, .. } => assert_eq!(symbol, "BTCUSDT"),
+            other => panic!("意外的事件: {other:?}"),
+        }
+
+        // 首次同步与缺口后的重新同步各请求一次快照
+        for index in 0..2 {
+            let path = rest_server.request(index).await.target;
+            assert!(path.starts_with("/api/v3/depth?symbol=BTCUSDT"), "快照请求路径错误: {path}");
+        }
+
+        let orderbook = adapter.get_orderbook_snapshot("BTCUSDT").await.expect("订单簿应已同步");
+        assert_eq!(orderbook.best_bid, 200.5);
+        assert_eq!(orderbook.best_ask, 201.0);
+        assert_eq!(orderbook.depth_bids, vec![(200.5, 2.0), (200.0, 1.0)]);
+
+        adapter.disconnect_websocket().await.unwrap();
+    }
+}
+
+/// 现货签名交易与用户数据流测试（使用本地模拟的REST与WebSocket服务器）
+#[cfg(test)]
+mod trading_tests {
+    use super::super::adapter::BinanceAdapter;
+    use super::super::config::BinanceConfig;
+    use super::super::rest::BinanceSpotRestClient;
+    use super::super::user_stream::{parse_user_event, BinanceUserDataStream, BinanceUserEvent};
+    use crate::config::get_config;
+    use crate::connectors::common::data_channel::data_channel;
+    use crate::connectors::test_support::{MockHttpServer, MockResponse, MockWsServer, RecordedRequest};
+    use crate::connectors::traits::ExchangeConnector;
+    use crate::core::AppState;
+    use crate::types::{
+        config::ConnectionStatus,
+        errors::ConnectorError,
+        exchange::ExchangeType,
+        market_data::{StandardizedMessage, UserData},
+        orders::{OrderRequest, OrderSide, OrderType, TimeInForce},
+    };
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::time::{sleep, timeout, Duration};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// 按方法和路径返回录制的响应（节选自Binance现货API文档）
+    fn respond(request: &RecordedRequest, listen_keys: &AtomicUsize) -> MockResponse {
+        let order = |status: &str| json!({
+            "symbol": "BTCUSDT", "orderId": 28, "orderListId": -1, "clientOrderId": "tf1",
+            "transactTime": 1507725176595i64, "updateTime": 1507725180000i64,
+            "price": "50000.00", "origQty": "0.002", "executedQty": "0.001", "cummulativeQuoteQty": "49.9",
+            "status": status, "timeInForce": "GTC", "type": "LIMIT", "side": "BUY",
+        });
+        let (status, body) = match (request.method.as_str(), request.path()) {
+            ("POST", "/api/v3/order") if request.query().contains("quantity=5&") => (
+                "400 Bad Request",
+                json!({"code": -2010, "msg": "Account has insufficient balance for requested action."}),
+            ),
+            ("POST", "/api/v3/order") => {
+                let mut response = order("PARTIALLY_FILLED");
+                response["fills"] = json!([{"price": "49900.00", "qty": "0.001", "commission": "0.000001", "commissionAsset": "BTC", "tradeId": 56}]);
+                ("200 OK", response)
+            }
+            ("DELETE", "/api/v3/order") => ("200 OK", order("CANCELED")),
+            ("GET", "/api/v3/order") => ("200 OK", order("PARTIALLY_FILLED")),
+            ("GET", "/api/v3/openOrders") => ("200 OK", json!([order("NEW")])),
+            ("GET", "/api/v3/account") => ("200 OK", json!({
+                "makerCommission": 10, "takerCommission": 10, "canTrade": true, "accountType": "SPOT",
+                "balances": [
+                    {"asset": "BTC", "free": "0.50000000", "locked": "0.10000000"},
+                    {"asset": "USDT", "free": "1000.00000000", "locked": "50.00000000"},
+                    {"asset": "ETH", "free": "0.00000000", "locked": "0.00000000"},
+                ],
+            })),
+            ("GET", "/api/v3/myTrades") => ("200 OK", json!([{
+                "symbol": "BTCUSDT", "id": 28457, "orderId": 100234, "orderListId": -1,
+                "price": "4.00000100", "qty": "12.00000000", "quoteQty": "48.000012",
+                "commission": "10.10000000", "commissionAsset": "BNB", "time": 1499865549590i64,
+                "isBuyer": true, "isMaker": false, "isBestMatch": true,
+            }])),
+            ("POST", "/api/v3/userDataStream") => {
+                let index = listen_keys.fetch_add(1, Ordering::SeqCst) + 1;
+                ("200 OK", json!({"listenKey": format!("key{index}")}))
+            }
+            ("PUT", "/api/v3/userDataStream") | ("DELETE", "/api/v3/userDataStream") => ("200 OK", json!({})),
+            _ => ("404 Not Found", json!({"code": -1000, "msg": "unknown route"})),
+        };
+        MockResponse::with_status(status, body.to_string())
+    }
+
+    /// 模拟REST服务器：记录每个请求并按路由返回响应
+    async fn start_mock_rest_server() -> MockHttpServer {
+        let listen_keys = AtomicUsize::new(0);
+        MockHttpServer::start(move |request| respond(request, &listen_keys)).await
+    }
+
+    fn execution_report(order_id: u64, execution: &str, status: &str, filled: &str, client_ids: (&str, &str)) -> String {
+        json!({
+            "e": "executionReport", "E": 1499405658658i64, "s": "BTCUSDT", "c": client_ids.0, "S": "BUY",
+            "o": "LIMIT", "f": "GTC", "q": "0.002", "p": "50000.00", "C": client_ids.1, "x": execution, "X": status,
+            "r": "NONE", "i": order_id, "l": filled, "z": filled, "L": "49900.00", "n": "0.000002", "N": "BTC",
+            "T": 1499405658700i64, "t": if execution == "TRADE" { 56 } else { -1 }, "Z": "99.8",
+        }).to_string()
+    }
+
+    /// 模拟用户数据流服务器：第一个listenKey推送订单与余额事件后宣布失效，第二个listenKey推送撤单
+    async fn start_mock_user_stream_server() -> MockWsServer {
+        MockWsServer::start(|mut ws, connection| async move {
+            let frames = if connection.path == "/ws/key1" {
+                vec![
+                    execution_report(28, "NEW", "NEW", "0", ("tf1", "")),
+                    execution_report(28, "TRADE", "FILLED", "0.002", ("tf1", "")),
+                    json!({"e": "outboundAccountPosition", "E": 1564034571105i64, "u": 1564034571073i64,
+                           "B": [{"a": "BTC", "f": "0.502", "l": "0.0"}]}).to_string(),
+                    json!({"e": "balanceUpdate", "E": 1573200697110i64, "a": "USDT", "d": "100.00000000",
+                           "T": 1573200697068i64}).to_string(),
+                    json!({"e": "listenKeyExpired", "E": 1576653824250i64, "listenKey": "key1"}).to_string(),
+                ]
+            } else {
+                vec![execution_report(29, "CANCELED", "CANCELED", "0", ("cancel2", "tf2"))]
+            };
+            for frame in frames {
+                ws.send(Message::Text(frame)).await.unwrap();
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        }).await
+    }
+
+    fn test_config(rest_url: &str, ws_url: Option<String>) -> BinanceConfig {
+        BinanceConfig {
+            api_key: Some("test-key".to_string()),
+            secret_key: Some("test-secret".to_string()),
+            testnet: false,
+            rate_limit_per_minute: 1200,
+            websocket_url: ws_url,
+            rest_api_url: Some(rest_url.to_string()),
+        }
+    }
+
+    fn limit_order(quantity: f64, time_in_force: TimeInForce) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            exchange: ExchangeType::Binance,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(50000.0),
+            time_in_force: Some(time_in_force),
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: Some("tf1".to_string()),
+        }
+    }
+
+    /// 检查签名是对签名前完整查询字符串的HMAC
+    fn assert_signed(request: &RecordedRequest) {
+        let (payload, signature) = request.query().rsplit_once("&signature=").expect("请求缺少签名");
+        assert_eq!(signature, BinanceSpotRestClient::sign("test-secret", payload));
+        assert!(payload.contains("timestamp=") && payload.contains("recvWindow="));
+        assert_eq!(request.header("X-MBX-APIKEY"), Some("test-key"));
+    }
+
+    #[test]
+    fn test_signature_matches_documented_example() {
+        let payload = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(
+            BinanceSpotRestClient::sign("NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j", payload),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[test]
+    fn test_parse_execution_report_from_combined_stream() {
+        let frame = json!({"stream": "listenKey", "data": serde_json::from_str::<Value>(
+            &execution_report(29, "CANCELED", "CANCELED", "0.001", ("cancel2", "tf2"))
+        ).unwrap()}).to_string();
+        let BinanceUserEvent::ExecutionReport(report) = parse_user_event(&frame).unwrap() else {
+            panic!("应解析为executionReport");
+        };
+        // 撤单事件使用原订单的客户端ID
+        assert_eq!((report.order_id.as_str(), report.client_order_id.as_str()), ("29", "tf2"));
+        assert_eq!((report.execution_type.as_str(), report.status.as_str()), ("CANCELED", "CANCELED"));
+        assert_eq!((report.commission, report.commission_asset.as_deref()), (0.000002, Some("BTC")));
+        assert_eq!((report.trade_id, report.reject_reason.clone()), (None, None));
+
+        let update = report.to_order_update();
+        assert_eq!((update.filled_quantity, update.remaining_quantity, update.timestamp), (0.001, 0.001, 1499405658700));
+        assert!(matches!(parse_user_event(r#"{"e":"listenKeyExpired","E":1}"#).unwrap(), BinanceUserEvent::ListenKeyExpired));
+    }
+
+    #[tokio::test]
+    async fn test_signed_rest_requests_against_mock_server() {
+        let server = start_mock_rest_server().await;
+        let adapter = BinanceAdapter::new(test_config(&server.url, None), Arc::new(AppState::new())).await.unwrap();
+
+        let response = adapter.place_order(&limit_order(0.002, TimeInForce::GTC)).await.unwrap();
+        assert_eq!((response.order_id.as_str(), response.status.as_str()), ("28", "PARTIALLY_FILLED"));
+        assert_eq!(response.client_order_id.as_deref(), Some("tf1"));
+        assert_eq!((response.filled_quantity, response.remaining_quantity), (0.001, 0.001));
+        assert!((response.average_price.unwrap() - 49900.0).abs() < 1e-6);
+        assert_eq!(response.timestamp, 1507725176595);
+        {
+            let requests = server.requests();
+            let request = requests.last().unwrap();
+            assert_eq!((request.method.as_str(), request.path()), ("POST", "/api/v3/order"));
+            assert!(request.query().starts_with("symbol=BTCUSDT&side=BUY&type=LIMIT&timeInForce=GTC&quantity=0.002&price=50000&newClientOrderId=tf1&newOrderRespType=FULL&"));
+            assert_signed(request);
+        }
+
+        // 只做挂单使用LIMIT_MAKER，不带timeInForce
+        adapter.place_order(&limit_order(0.002, TimeInForce::GTX)).await.unwrap();
+        {
+            let requests = server.requests();
+            let query = requests.last().unwrap().query();
+            assert!(query.contains("type=LIMIT_MAKER") && !query.contains("timeInForce"), "{query}");
+        }
+
+        let error = adapter.place_order(&limit_order(5.0, TimeInForce::GTC)).await.unwrap_err();
+        assert!(matches!(error, ConnectorError::InsufficientBalance(_)), "{error:?}");
+        let reduce_only = OrderRequest { reduce_only: Some(true), ..limit_order(0.002, TimeInForce::GTC) };
+        assert!(matches!(adapter.place_order(&reduce_only).await, Err(ConnectorError::InvalidOrderParameters(_))));
+
+        assert!(adapter.cancel_order("28", "BTCUSDT").await.unwrap());
+        let status = adapter.get_order_status("28", "BTCUSDT").await.unwrap();
+        assert_eq!((status.status.as_str(), status.timestamp), ("PARTIALLY_FILLED", 1507725180000));
+        let open_orders = adapter.rest_client().get_open_orders(Some("BTCUSDT")).await.unwrap();
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!((open_orders[0].status.as_str(), open_orders[0].remaining_quantity), ("NEW", 0.001));
+
+        let balance = adapter.get_account_balance().await.unwrap();
+        assert_eq!((balance.balances["BTC"].available, balance.balances["BTC"].frozen), (0.5, 0.1));
+        assert!(!balance.balances.contains_key("ETH"));
+        assert_eq!((balance.total, balance.available), (1050.0, 1000.0));
+
+        let trades = adapter.rest_client().get_my_trades("BTCUSDT", Some(10)).await.unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!((trades[0].execution_id.as_str(), trades[0].order_id.as_str()), ("28457", "100234"));
+        assert_eq!((trades[0].side, trades[0].price, trades[0].quantity), (OrderSide::Buy, 4.000001, 12.0));
+
+        let requests = server.requests();
+        let routes: Vec<_> = requests.iter().map(|r| format!("{} {}", r.method, r.path())).collect();
+        assert_eq!(routes, [
+            "POST /api/v3/order", "POST /api/v3/order", "POST /api/v3/order",
+            "DELETE /api/v3/order", "GET /api/v3/order", "GET /api/v3/openOrders",
+            "GET /api/v3/account", "GET /api/v3/myTrades",
+        ]);
+        requests.iter().for_each(assert_signed);
+    }
+
+    #[tokio::test]
+    async fn test_user_stream_renews_listen_key_and_emits_user_data() {
+        let _ = env_logger::try_init();
+        let rest_server = start_mock_rest_server().await;
+        let mut ws_server = start_mock_user_stream_server().await;
+        let config = test_config(&rest_server.url, Some(ws_server.url.clone()));
+
+        let stream = BinanceUserDataStream::new(&config, BinanceSpotRestClient::new(&config))
+            .with_keepalive_interval(Duration::from_millis(100))
+            .with_reconnect_interval(Duration::from_millis(20));
+        let (sender, mut receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        assert!(stream.try_set_message_sender(sender));
+        stream.start().await.unwrap();
+        assert_eq!(stream.get_connection_status().await, ConnectionStatus::Connected);
+
+        let mut updates = Vec::new();
+        while updates.len() < 5 {
+            let message = timeout(Duration::from_secs(5), receiver.recv()).await.expect("等待用户数据超时").unwrap();
+            let StandardizedMessage::UserDataUpdate(update) = message else { panic!("意外的消息") };
+            updates.push(update);
+        }
+        let order_states: Vec<_> = updates.iter().filter_map(|update| match update {
+            UserData::OrderUpdate(order) => Some((order.order_id.clone(), order.status.clone(), order.filled_quantity, order.remaining_quantity)),
+            _ => None,
+        }).collect();
+        assert_eq!(order_states, [
+            ("28".to_string(), "NEW".to_string(), 0.0, 0.002),
+            ("28".to_string(), "FILLED".to_string(), 0.002, 0.0),
+            ("29".to_string(), "CANCELED".to_string(), 0.0, 0.002),
+        ]);
+        // balanceUpdate只有变动量，按启动时账户快照中的USDT余额换算
+        let balances: Vec<_> = updates.iter().filter_map(|update| match update {
+            UserData::BalanceUpdate(balance) => Some((balance.asset.clone(), balance.free, balance.locked)),
+            _ => None,
+        }).collect();
+        assert_eq!(balances, [("BTC".to_string(), 0.502, 0.0), ("USDT".to_string(), 1100.0, 50.0)]);
+
+        // listenKey失效后重新创建并连接到新的listenKey
+        assert_eq!(ws_server.next_connection().await, "/ws/key1");
+        assert_eq!(ws_server.next_connection().await, "/ws/key2");
+        assert_eq!(stream.listen_key().await.as_deref(), Some("key2"));
+
+        sleep(Duration::from_millis(250)).await;
+        stream.stop().await;
+        assert_eq!(stream.get_connection_status().await, ConnectionStatus::Disconnected);
+
+        let requests = rest_server.requests();
+        let listen_key_calls: Vec<_> = requests.iter()
+            .filter(|r| r.path() == "/api/v3/userDataStream")
+            .map(|r| format!("{} {}", r.method, r.query()))
+            .collect();
+        assert_eq!(&listen_key_calls[..2], ["POST ", "POST "]);
+        assert!(listen_key_calls.contains(&"PUT listenKey=key2".to_string()), "{listen_key_calls:?}");
+        assert_eq!(listen_key_calls.last().map(String::as_str), Some("DELETE listenKey=key2"));
+        assert!(requests.iter().filter(|r| r.path() == "/api/v3/userDataStream").all(|r| r.header("X-MBX-APIKEY") == Some("test-key")));
+    }
+}
@@ -0,0 +1,423 @@
+//! Binance现货用户数据流
+//!
+//! 通过REST创建listenKey并连接 `/ws/<listenKey>`，定时续期；listenKey失效或续期失败时重新创建并重连。
+//! `executionReport`、`outboundAccountPosition`、`balanceUpdate` 转换为 `UserData` 消息推送。
+//! `balanceUpdate` 只包含变动量，按启动时的账户快照和后续推送维护的本地余额换算成最新可用余额。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, error, info, warn};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::connectors::common::data_channel::{ChannelStats, DataSender};
+use crate::types::config::ConnectionStatus;
+use crate::types::errors::ConnectorError;
+use crate::types::exchange::ExchangeType;
+use crate::types::market_data::{BalanceUpdate, OrderUpdate, StandardizedMessage, UserData};
+use crate::types::orders::OrderSide;
+use super::config::BinanceConfig;
+use super::rest::BinanceSpotRestClient;
+
+type BinanceWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// listenKey续期间隔，交易所60分钟未续期即失效
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const DEFAULT_RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 订单执行报告（`executionReport`）
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub symbol: String,
+    pub order_id: String,
+    pub client_order_id: String,
+    pub side: OrderSide,
+    pub order_type: String,
+    /// 本次事件的类型：NEW、TRADE、CANCELED、REJECTED、EXPIRED等
+    pub execution_type: String,
+    /// 统一后的订单状态
+    pub status: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub last_filled_quantity: f64,
+    pub last_filled_price: f64,
+    pub cumulative_filled_quantity: f64,
+    pub cumulative_quote_quantity: f64,
+    pub commission: f64,
+    pub commission_asset: Option<String>,
+    pub trade_id: Option<i64>,
+    pub reject_reason: Option<String>,
+    pub timestamp: i64,
+}
+
+impl ExecutionReport {
+    /// 转换为统一的订单更新
+    pub fn to_order_update(&self) -> OrderUpdate {
+        OrderUpdate {
+            order_id: self.order_id.clone(),
+            symbol: self.symbol.clone(),
+            exchange: ExchangeType::Binance,
+            status: self.status.clone(),
+            filled_quantity: self.cumulative_filled_quantity,
+            remaining_quantity: (self.quantity - self.cumulative_filled_quantity).max(0.0),
+            average_price: (self.cumulative_filled_quantity > 0.0)
+                .then(|| self.cumulative_quote_quantity / self.cumulative_filled_quantity),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// 用户数据流事件
+#[derive(Debug, Clone)]
+pub enum BinanceUserEvent {
+    ExecutionReport(Box<ExecutionReport>),
+    /// 账户余额快照（只含发生变化的资产）
+    AccountPosition(Vec<BalanceUpdate>),
+    /// 充值、提现、划转引起的余额变动
+    BalanceDelta { asset: String, delta: f64, timestamp: i64 },
+    /// listenKey已失效，需要重新创建
+    ListenKeyExpired,
+    Other(Value),
+}
+
+/// 解析一条用户数据流消息，兼容组合流 `{"stream","data"}` 和WebSocket API `{"event"}` 包装
+pub fn parse_user_event(text: &str) -> Result<BinanceUserEvent, ConnectorError> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| ConnectorError::DataParsingError(format!("解析Binance用户数据失败: {e}")))?;
+    let event = value.get("data").or_else(|| value.get("event")).unwrap_or(&value);
+    let field_f64 = |key: &str| BinanceSpotRestClient::field_f64(event, key);
+    let field_str = |key: &str| event.get(key).and_then(|v| v.as_str());
+    let event_time = event.get("E").and_then(|t| t.as_i64()).unwrap_or_default();
+
+    match field_str("e") {
+        Some("executionReport") => {
+            let missing = |key: &str| ConnectorError::DataParsingError(format!("executionReport缺少字段 {key}"));
+            let symbol = field_str("s").ok_or_else(|| missing("s"))?;
+            let order_id = event.get("i").and_then(|v| v.as_u64()).ok_or_else(|| missing("i"))?;
+            let execution_type = field_str("x").unwrap_or_default();
+            // 撤单事件的 `c` 是撤单请求的ID，原订单的客户端ID在 `C` 中
+            let client_order_id = field_str("C").filter(|id| !id.is_empty()).or_else(|| field_str("c")).unwrap_or_default();
+            Ok(BinanceUserEvent::ExecutionReport(Box::new(ExecutionReport {
+                symbol: symbol.to_string(),
+                order_id: order_id.to_string(),
+                client_order_id: client_order_id.to_string(),
+                side: if field_str("S") == Some("SELL") { OrderSide::Sell } else { OrderSide::Buy },
+                order_type: field_str("o").unwrap_or_default().to_string(),
+                execution_type: execution_type.to_string(),
+                status: BinanceSpotRestClient::normalize_order_status(field_str("X").unwrap_or_default()).to_string(),
+                quantity: field_f64("q").unwrap_or(0.0),
+                price: field_f64("p").unwrap_or(0.0),
+                last_filled_quantity: field_f64("l").unwrap_or(0.0),
+                last_filled_price: field_f64("L").unwrap_or(0.0),
+                cumulative_filled_quantity: field_f64("z").unwrap_or(0.0),
+                cumulative_quote_quantity: field_f64("Z").unwrap_or(0.0),
+                commission: field_f64("n").unwrap_or(0.0),
+                commission_asset: field_str("N").map(|a| a.to_string()),
+                trade_id: event.get("t").and_then(|t| t.as_i64()).filter(|id| *id >= 0),
+                reject_reason: field_str("r").filter(|r| *r != "NONE").map(|r| r.to_string()),
+                timestamp: event.get("T").and_then(|t| t.as_i64()).unwrap_or(event_time),
+            })))
+        }
+        Some("outboundAccountPosition") => {
+            let balances = event.get("B").and_then(|b| b.as_array()).into_iter().flatten()
+                .filter_map(|balance| Some(BalanceUpdate {
+                    asset: balance.get("a")?.as_str()?.to_string(),
+                    exchange: ExchangeType::Binance,
+                    free: BinanceSpotRestClient::field_f64(balance, "f")?,
+                    locked: BinanceSpotRestClient::field_f64(balance, "l").unwrap_or(0.0),
+                    timestamp: event_time,
+                }))
+                .collect();
+            Ok(BinanceUserEvent::AccountPosition(balances))
+        }
+        Some("balanceUpdate") => Ok(BinanceUserEvent::BalanceDelta {
+            asset: field_str("a").unwrap_or_default().to_string(),
+            delta: field_f64("d").unwrap_or(0.0),
+            timestamp: event.get("T").and_then(|t| t.as_i64()).unwrap_or(event_time),
+        }),
+        Some("listenKeyExpired") | Some("eventStreamTerminated") => Ok(BinanceUserEvent::ListenKeyExpired),
+        _ => Ok(BinanceUserEvent::Other(value.clone())),
+    }
+}
+
+/// 连接会话结束原因
+enum SessionEnd {
+    /// listenKey失效或续期失败，需要重新创建
+    Expired,
+    /// 连接断开，沿用原listenKey重连
+    Lost,
+}
+
+/// Binance现货用户数据流
+#[derive(Clone)]
+pub struct BinanceUserDataStream {
+    rest_client: BinanceSpotRestClient,
+    ws_base_url: String,
+    keepalive_interval: Duration,
+    reconnect_interval: Duration,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    /// 资产 -> (可用, 冻结)
+    balances: Arc<Mutex<HashMap<String, (f64, f64)>>>,
+    listen_key: Arc<RwLock<Option<String>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+    task: Arc<tokio::sync::Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl BinanceUserDataStream {
+    /// 创建用户数据流，`websocket_url` 配置视为基础地址
+    pub fn new(config: &BinanceConfig, rest_client: BinanceSpotRestClient) -> Self {
+        let ws_base_url = match config.websocket_url.as_deref() {
+            Some(url) => url,
+            None if config.testnet => "wss://testnet.binance.vision:9443",
+            None => "wss://stream.binance.com:9443",
+        };
+
+        Self {
+            rest_client,
+            ws_base_url: ws_base_url.trim_end_matches('/').to_string(),
+            keepalive_interval: DEFAULT_KEEPALIVE_INTERVAL,
+            reconnect_interval: DEFAULT_RECONNECT_INTERVAL,
+            message_sender: Arc::new(RwLock::new(None)),
+            balances: Arc::new(Mutex::new(HashMap::new())),
+            listen_key: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            task: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// 设置listenKey续期间隔
+    pub fn with_keepalive_interval(mut self, keepalive_interval: Duration) -> Self {
+        self.keepalive_interval = keepalive_interval;
+        self
+    }
+
+    /// 设置断线重连间隔
+    pub fn with_reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+
+    /// 同步设置消息发送器（用于非异步上下文，例如获取数据流）
+    pub fn try_set_message_sender(&self, sender: DataSender<StandardizedMessage>) -> bool {
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => {
+                *message_sender = Some(sender);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 当前消息通道的计数快照
+    pub fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
+    /// 当前使用的listenKey
+    pub async fn listen_key(&self) -> Option<String> {
+        self.listen_key.read().await.clone()
+    }
+
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 启动用户数据流
+    ///
+    /// 先拉取账户快照作为 `balanceUpdate` 的基准，再创建listenKey并连接；首次连接失败直接返回错误。
+    pub async fn start(&self) -> Result<(), ConnectorError> {
+        let mut task = self.task.lock().await;
+        if task.as_ref().is_some_and(|task| !task.is_finished()) {
+            debug!("[Binance] 用户数据流已在运行，跳过启动");
+            return Ok(());
+        }
+        *self.status.write().await = ConnectionStatus::Connecting;
+
+        let opened = async {
+            let account = self.rest_client.get_account_balance().await?;
+            *self.balances.lock().unwrap_or_else(|e| e.into_inner()) = account.balances.values()
+                .map(|balance| (balance.currency.clone(), (balance.available, balance.frozen)))
+                .collect();
+
+            let listen_key = self.rest_client.start_user_data_stream().await?;
+            let ws_stream = self.open(&listen_key).await?;
+            Ok::<_, ConnectorError>((listen_key, ws_stream))
+        }.await;
+        let (listen_key, ws_stream) = match opened {
+            Ok(opened) => opened,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Error;
+                return Err(e);
+            }
+        };
+
+        *self.listen_key.write().await = Some(listen_key.clone());
+        *self.status.write().await = ConnectionStatus::Connected;
+        *task = Some(tokio::spawn(self.clone().connection_loop(ws_stream, listen_key)));
+        info!("[Binance] 用户数据流已连接");
+        Ok(())
+    }
+
+    /// 停止用户数据流并关闭listenKey
+    pub async fn stop(&self) {
+        if let Some(task) = self.task.lock().await.take() {
+            task.abort();
+        }
+        if let Some(listen_key) = self.listen_key.write().await.take() {
+            if let Err(e) = self.rest_client.close_user_data_stream(&listen_key).await {
+                warn!("[Binance] 关闭listenKey失败: {e}");
+            }
+        }
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        info!("[Binance] 用户数据流已停止");
+    }
+
+    async fn open(&self, listen_key: &str) -> Result<BinanceWsStream, ConnectorError> {
+        let url = format!("{}/ws/{listen_key}", self.ws_base_url);
+        let (ws_stream, _) = timeout(CONNECT_TIMEOUT, connect_async(&url))
+            .await
+            .map_err(|_| ConnectorError::TimeoutError("连接Binance用户数据流超时".to_string()))?
+            .map_err(|e| ConnectorError::WebSocketError(format!("连接Binance用户数据流失败: {e}")))?;
+        Ok(ws_stream)
+    }
+
+    /// 连接维护循环：会话结束后按需重新创建listenKey并重连，直到被停止
+    async fn connection_loop(self, mut ws_stream: BinanceWsStream, mut listen_key: String) {
+        loop {
+            let mut expired = matches!(self.run_session(ws_stream, &listen_key).await, SessionEnd::Expired);
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+
+            ws_stream = loop {
+                tokio::time::sleep(self.reconnect_interval).await;
+                if expired {
+                    match self.rest_client.start_user_data_stream().await {
+                        Ok(key) => {
+                            info!("[Binance] 已重新创建listenKey");
+                            listen_key = key;
+                            *self.listen_key.write().await = Some(listen_key.clone());
+                            expired = false;
+                        }
+                        Err(e) => {
+                            warn!("[Binance] 重新创建listenKey失败: {e}");
+                            continue;
+                        }
+                    }
+                }
+                match self.open(&listen_key).await {
+                    Ok(stream) => break stream,
+                    Err(e) => warn!("[Binance] 用户数据流重连失败: {e}"),
+                }
+            };
+            *self.status.write().await = ConnectionStatus::Connected;
+            info!("[Binance] 用户数据流重连成功");
+        }
+    }
+
+    /// 运行单次连接会话，期间定时续期listenKey
+    async fn run_session(&self, ws_stream: BinanceWsStream, listen_key: &str) -> SessionEnd {
+        let (mut write, mut read) = ws_stream.split();
+        let mut keepalive_timer = tokio::time::interval(self.keepalive_interval);
+        keepalive_timer.tick().await;
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => {
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            if self.handle_text(&text).await {
+                                warn!("[Binance] listenKey已失效，重新创建");
+                                return SessionEnd::Expired;
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            if let Err(e) = write.send(Message::Pong(payload)).await {
+                                error!("[Binance] 用户数据流发送Pong失败: {e}");
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("[Binance] 用户数据流被服务器关闭");
+                            return SessionEnd::Lost;
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(e)) => {
+                            error!("[Binance] 用户数据流错误: {e}");
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+                _ = keepalive_timer.tick() => {
+                    match self.rest_client.keepalive_user_data_stream(listen_key).await {
+                        Ok(()) => debug!("[Binance] listenKey已续期"),
+                        Err(e) => {
+                            warn!("[Binance] listenKey续期失败: {e}");
+                            return SessionEnd::Expired;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理一条文本消息，返回listenKey是否已失效
+    async fn handle_text(&self, text: &str) -> bool {
+        let event = match parse_user_event(text) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("[Binance] {e}");
+                return false;
+            }
+        };
+        if let BinanceUserEvent::ListenKeyExpired = event {
+            return true;
+        }
+        for message in self.apply_event(event) {
+            self.forward(message).await;
+        }
+        false
+    }
+
+    /// 将事件转换为统一消息，同时维护本地余额
+    pub fn apply_event(&self, event: BinanceUserEvent) -> Vec<StandardizedMessage> {
+        let mut balances = self.balances.lock().unwrap_or_else(|e| e.into_inner());
+        let updates = match event {
+            BinanceUserEvent::ExecutionReport(report) => vec![UserData::OrderUpdate(report.to_order_update())],
+            BinanceUserEvent::AccountPosition(updates) => updates.into_iter()
+                .map(|update| {
+                    balances.insert(update.asset.clone(), (update.free, update.locked));
+                    UserData::BalanceUpdate(update)
+                })
+                .collect(),
+            BinanceUserEvent::BalanceDelta { asset, delta, timestamp } => {
+                let balance = balances.entry(asset.clone()).or_insert((0.0, 0.0));
+                balance.0 += delta;
+                vec![UserData::BalanceUpdate(BalanceUpdate {
+                    asset,
+                    exchange: ExchangeType::Binance,
+                    free: balance.0,
+                    locked: balance.1,
+                    timestamp,
+                })]
+            }
+            BinanceUserEvent::ListenKeyExpired | BinanceUserEvent::Other(_) => Vec::new(),
+        };
+        updates.into_iter().map(StandardizedMessage::UserDataUpdate).collect()
+    }
+
+    async fn forward(&self, message: StandardizedMessage) {
+        // 先取出发送端再发送，阻塞策略等待消费时不占用锁
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[Binance] 用户数据接收端已关闭");
+            }
+        }
+    }
+}
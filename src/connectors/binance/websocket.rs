@@ -15,6 +15,7 @@ use crate::types::*;
 use crate::types::market_data::PriceLevel;
 use super::config::BinanceConfig;
 use super::spot::BinanceSpotConnector;
+use super::orderbook::{BinanceOrderBookManager, DepthDiffEvent};
 
 /// Binance WebSocket处理器
 /// 
@@ -26,6 +27,7 @@ pub struct BinanceWebSocketHandler {
     should_reconnect: Arc<RwLock<bool>>,
     app_state: Arc<crate::AppState>,
     connection_id: String,
+    orderbook_manager: BinanceOrderBookManager,
 }
 
 impl BinanceWebSocketHandler {
@@ -39,6 +41,7 @@ impl BinanceWebSocketHandler {
         );
         
         let connection_id = format!("binance-ws-{}", chrono::Utc::now().timestamp_millis());
+        let orderbook_manager = BinanceOrderBookManager::new(spot_connector.get_api_url(), app_state.clone());
         
         Ok(Self {
             config,
@@ -47,9 +50,20 @@ impl BinanceWebSocketHandler {
             should_reconnect: Arc::new(RwLock::new(true)),
             app_state,
             connection_id,
+            orderbook_manager,
         })
     }
     
+    /// 设置系统事件发送器（订单簿重新同步时上报数据质量事件）
+    pub async fn set_event_sender(&self, sender: tokio::sync::broadcast::Sender<SystemEvent>) {
+        self.orderbook_manager.set_event_sender(sender).await;
+    }
+    
+    /// 获取本地订单簿管理器
+    pub fn orderbook_manager(&self) -> &BinanceOrderBookManager {
+        &self.orderbook_manager
+    }
+    
     /// 连接WebSocket
     pub async fn connect(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("[Binance] {} 开始连接WebSocket...", self.connection_id);
@@ -79,8 +93,9 @@ impl BinanceWebSocketHandler {
             for data_type in &data_types {
                 match data_type {
                     crate::types::DataType::OrderBook => {
-                        streams.push(format!("{symbol_lower}@depth20@100ms"));
-                        info!("[Binance] {} 添加深度流: {}@depth20@100ms", self.connection_id, symbol_lower);
+                        // 使用增量深度流，配合REST快照维护完整的本地订单簿
+                        streams.push(format!("{symbol_lower}@depth@100ms"));
+                        info!("[Binance] {} 添加深度流: {}@depth@100ms", self.connection_id, symbol_lower);
                     }
                     crate::types::DataType::Trade => {
                         streams.push(format!("{symbol_lower}@trade"));
@@ -108,6 +123,7 @@ impl BinanceWebSocketHandler {
         let should_reconnect = self.should_reconnect.clone();
         let app_state = self.app_state.clone();
         let connection_id = self.connection_id.clone();
+        let orderbook_manager = self.orderbook_manager.clone();
         
         tokio::spawn(async move {
             Self::connection_loop(
@@ -117,6 +133,7 @@ impl BinanceWebSocketHandler {
                 should_reconnect,
                 app_state,
                 connection_id,
+                orderbook_manager,
             ).await;
         });
         
@@ -235,6 +252,7 @@ impl BinanceWebSocketHandler {
         should_reconnect: Arc<RwLock<bool>>,
         app_state: Arc<crate::AppState>,
         connection_id: String,
+        orderbook_manager: BinanceOrderBookManager,
     ) {
         let mut retry_count = 0;
         let max_retries = 15; // 增加最大重试次数，提高实盘环境的连接稳定性
@@ -256,6 +274,9 @@ impl BinanceWebSocketHandler {
                     info!("[Binance] {connection_id} WebSocket连接成功");
                     retry_count = 0; // 重置重试计数
                     
+                    // 新连接的增量事件无法与旧订单簿衔接，需要重新同步
+                    orderbook_manager.invalidate_all().await;
+                    
                     // 更新连接状态
                     {
                         let mut status = connection_status.write().await;
@@ -313,7 +334,7 @@ impl BinanceWebSocketHandler {
                                 info!("[Binance] {} 收到消息: {}", connection_id, 
                                     if text.len() > 200 { format!("{}...({}字符)", &text[..200], text.len()) } else { text.clone() });
                                 
-                                if let Err(e) = Self::process_message(&spot_connector, &text, &app_state, &connection_id).await {
+                                if let Err(e) = Self::process_message(&spot_connector, &orderbook_manager, &text, &app_state, &connection_id).await {
                                     error!("[Binance] {connection_id} 处理消息失败: {e}");
                                 }
                             }
@@ -444,6 +465,7 @@ impl BinanceWebSocketHandler {
     /// 处理WebSocket消息
     async fn process_message(
         spot_connector: &Arc<BinanceSpotConnector>,
+        orderbook_manager: &BinanceOrderBookManager,
         text: &str,
         app_state: &Arc<crate::AppState>,
         connection_id: &str,
@@ -454,9 +476,14 @@ impl BinanceWebSocketHandler {
         // 检查是否是组合流格式（包含stream字段）
         if let Some(stream) = json_value.get("stream").and_then(|s| s.as_str()) {
             if stream.contains("@depth") {
-                // 解析深度数据
                 if let Some(data) = json_value.get("data") {
-                    Self::process_depth_data(data, app_state, connection_id).await?;
+                    if let Some(event) = DepthDiffEvent::from_json(data) {
+                        // 增量深度事件交给本地订单簿同步
+                        orderbook_manager.handle_diff_event(event).await;
+                    } else {
+                        // 部分深度快照直接转发
+                        Self::process_depth_data(data, app_state, connection_id).await?;
+                    }
                 }
             }
         }
@@ -0,0 +1,302 @@
+//! Bybit连接器适配器
+//! 将Bybit V5 WebSocket处理器和REST客户端包装成标准的ExchangeConnector接口（USDT永续合约）
+
+use async_trait::async_trait;
+use log::{info, warn};
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::core::AppState;
+use crate::config::get_config;
+use crate::connectors::common::clock_sync::ClockSource;
+use crate::connectors::common::instruments::InstrumentSource;
+use crate::connectors::common::data_channel::{data_channel, ChannelSendError, DataReceiver};
+use crate::connectors::traits::{ExchangeConnector, DataFlowManager};
+use crate::types::{
+    config::{ConnectorConfig, ConnectionStatus, ConnectionQuality},
+    market_data::{StandardizedMessage, StandardizedOrderBook, StandardizedTrade},
+    orders::{OrderRequest, OrderResponse, OrderStatus},
+    account::AccountBalance,
+    exchange::{ExchangeType, MarketType},
+    errors::ConnectorError,
+    events::{SystemEvent, HighFrequencyData, DataFlowStats},
+};
+use super::rest::BybitRestClient;
+use super::websocket::{BybitStream, BybitWebSocketHandler};
+
+/// Bybit USDT永续合约连接器
+/// 公共行情和私有数据分别使用独立的WebSocket连接，交易走签名REST接口
+#[derive(Clone)]
+pub struct BybitConnector {
+    config: ConnectorConfig,
+    app_state: Arc<AppState>,
+    public_handler: BybitWebSocketHandler,
+    private_handler: BybitWebSocketHandler,
+    rest_client: BybitRestClient,
+    event_sender: broadcast::Sender<SystemEvent>,
+}
+
+impl BybitConnector {
+    /// 创建新的Bybit连接器实例
+    pub fn new(config: ConnectorConfig, app_state: Arc<AppState>) -> Self {
+        let public_handler = BybitWebSocketHandler::new(&config, BybitStream::PublicLinear, app_state.clone());
+        let private_handler = BybitWebSocketHandler::new(&config, BybitStream::Private, app_state.clone());
+        let rest_client = BybitRestClient::new(&config);
+        let (event_sender, _) = broadcast::channel(1000);
+
+        Self {
+            config,
+            app_state,
+            public_handler,
+            private_handler,
+            rest_client,
+            event_sender,
+        }
+    }
+
+    /// 获取公共行情WebSocket处理器
+    pub fn public_handler(&self) -> &BybitWebSocketHandler {
+        &self.public_handler
+    }
+
+    /// 获取私有数据WebSocket处理器
+    pub fn private_handler(&self) -> &BybitWebSocketHandler {
+        &self.private_handler
+    }
+
+    /// 获取REST客户端
+    pub fn rest_client(&self) -> &BybitRestClient {
+        &self.rest_client
+    }
+
+    /// 是否使用测试网
+    pub fn is_testnet(&self) -> bool {
+        self.config.testnet
+    }
+
+    /// 取消订阅交易对的行情
+    pub async fn unsubscribe(&self, symbol: &str) -> Result<(), ConnectorError> {
+        self.public_handler.unsubscribe(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to unsubscribe {symbol}: {e}")))
+    }
+
+    /// 健康检查
+    pub async fn health_check(&self) -> Result<bool, ConnectorError> {
+        Ok(self.is_connected().await)
+    }
+
+    /// 获取连接统计信息 (消息数, 价格更新数)
+    pub fn get_connection_stats(&self) -> (u64, u64) {
+        let messages = self.app_state.websocket_messages.load(std::sync::atomic::Ordering::Relaxed);
+        let updates = self.app_state.price_updates.load(std::sync::atomic::Ordering::Relaxed);
+        (messages, updates)
+    }
+}
+
+#[async_trait]
+impl ExchangeConnector for BybitConnector {
+    // 基础信息
+    fn get_exchange_type(&self) -> ExchangeType {
+        ExchangeType::BybitFutures
+    }
+
+    fn get_market_type(&self) -> MarketType {
+        MarketType::Futures
+    }
+
+    fn get_exchange_name(&self) -> &str {
+        "Bybit"
+    }
+
+    fn clock_source(&self) -> Option<ClockSource> {
+        Some(ClockSource {
+            clock: self.rest_client.clock().clone(),
+            source: Arc::new(self.rest_client.clone()),
+        })
+    }
+
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        Some(Arc::new(self.rest_client.clone()))
+    }
+
+    // WebSocket 连接管理
+    async fn connect_websocket(&self) -> Result<(), ConnectorError> {
+        info!("Connecting to Bybit WebSocket");
+
+        // 断线重连由处理器在后台维护
+        self.public_handler.start().await
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("Failed to connect Bybit WebSocket: {e}")))?;
+
+        info!("Bybit WebSocket connected successfully");
+        Ok(())
+    }
+
+    async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
+        info!("Disconnecting from Bybit WebSocket");
+
+        self.public_handler.stop().await
+            .map_err(|e| ConnectorError::ConnectionError(format!("Failed to disconnect Bybit WebSocket: {e}")))?;
+        self.private_handler.stop().await
+            .map_err(|e| ConnectorError::ConnectionError(format!("Failed to disconnect Bybit private WebSocket: {e}")))?;
+
+        info!("Bybit WebSocket disconnected successfully");
+        Ok(())
+    }
+
+    async fn subscribe_orderbook(&self, symbol: &str) -> Result<(), ConnectorError> {
+        info!("Subscribing to orderbook for symbol: {}", symbol);
+
+        if !self.public_handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+
+        self.public_handler.subscribe(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to orderbook: {e}")))?;
+
+        info!("Successfully subscribed to orderbook for {}", symbol);
+        Ok(())
+    }
+
+    async fn subscribe_trades(&self, symbol: &str) -> Result<(), ConnectorError> {
+        info!("Subscribing to trades for symbol: {}", symbol);
+
+        if !self.public_handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+
+        self.public_handler.subscribe_trades(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to trades: {e}")))?;
+
+        info!("Successfully subscribed to trades for {}", symbol);
+        Ok(())
+    }
+
+    async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
+        info!("Subscribing to Bybit user stream");
+
+        if !self.rest_client.has_credentials() {
+            return Err(ConnectorError::InvalidCredentials("Bybit API密钥未配置".to_string()));
+        }
+
+        // 先登记私有topic，连接鉴权成功后统一发送
+        self.private_handler.subscribe_private().await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to user stream: {e}")))?;
+        self.private_handler.start().await
+            .map_err(|e| ConnectorError::AuthenticationFailed(format!("Failed to connect Bybit private WebSocket: {e}")))?;
+
+        info!("Successfully subscribed to Bybit user stream");
+        Ok(())
+    }
+
+    // 推送式数据流接口
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.market_data_channel);
+        if !self.public_handler.try_set_message_sender(sender) {
+            warn!("[Bybit] 行情消息发送器正被占用，返回的数据流不会收到消息");
+        }
+        receiver
+    }
+
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        if !self.private_handler.try_set_message_sender(sender) {
+            warn!("[Bybit] 用户数据发送器正被占用，返回的数据流不会收到消息");
+        }
+        receiver
+    }
+
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        let channels = [self.public_handler.channel_stats(), self.private_handler.channel_stats()];
+        for channel in channels.into_iter().flatten() {
+            channel.apply_to(&mut stats);
+        }
+        stats
+    }
+
+    // 本地缓存快照读取
+    async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.public_handler.get_orderbook(symbol)
+    }
+
+    async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.public_handler.get_recent_trades(symbol, limit)
+    }
+
+    // 交易相关操作 (REST API)
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        info!("[Bybit] 下单: {} {:?} {:?} {}", order.symbol, order.side, order.order_type, order.quantity);
+        self.rest_client.place_order(order).await
+    }
+
+    async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
+        info!("[Bybit] 撤单: {order_id} ({symbol})");
+        self.rest_client.cancel_order(order_id, symbol).await
+    }
+
+    async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        self.rest_client.get_order_status(order_id, symbol).await
+    }
+
+    async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        self.rest_client.get_wallet_balance().await
+    }
+
+    // 连接状态
+    async fn is_connected(&self) -> bool {
+        self.public_handler.is_connected().await
+    }
+
+    async fn is_websocket_connected(&self) -> bool {
+        self.public_handler.is_connected().await
+    }
+
+    async fn get_connection_status(&self) -> ConnectionStatus {
+        self.public_handler.get_connection_status().await
+    }
+
+    async fn get_connection_quality(&self) -> Result<ConnectionQuality, ConnectorError> {
+        let (latency_ms, packet_loss_rate, stability_score) = match self.get_connection_status().await {
+            ConnectionStatus::Connected => (50.0, 0.0, 0.9),
+            ConnectionStatus::Connecting | ConnectionStatus::Reconnecting => (250.0, 0.15, 0.25),
+            _ => (1200.0, 0.6, 0.05),
+        };
+
+        Ok(ConnectionQuality {
+            latency_ms,
+            packet_loss_rate,
+            stability_score,
+            last_updated: chrono::Utc::now(),
+        })
+    }
+
+    async fn emergency_ping(&self) -> Result<Duration, ConnectorError> {
+        if self.public_handler.is_connected().await {
+            Ok(Duration::from_millis(50))
+        } else {
+            Err(ConnectorError::ConnectionLost("WebSocket未连接".to_string()))
+        }
+    }
+}
+
+#[async_trait]
+impl DataFlowManager for BybitConnector {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
+        // 行情通过 get_market_data_stream 推送
+        None
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent> {
+        self.event_sender.subscribe()
+    }
+
+    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
+        Ok(())
+    }
+
+    async fn send_event(&self, event: SystemEvent) {
+        let _ = self.event_sender.send(event);
+    }
+}
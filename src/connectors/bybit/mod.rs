@@ -0,0 +1,13 @@
+//! Bybit连接器模块
+//! 实现Bybit V5 USDT永续合约的行情、私有数据流和交易接口
+
+pub mod adapter;
+pub mod rest;
+pub mod websocket;
+
+#[cfg(test)]
+mod test;
+
+pub use adapter::BybitConnector;
+pub use rest::BybitRestClient;
+pub use websocket::{BybitFrame, BybitStream, BybitWebSocketHandler};
@@ -0,0 +1,458 @@
+//! Bybit V5 REST客户端
+//! 实现USDT永续合约（category=linear）的签名下单、撤单、查询以及钱包余额查询
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::debug;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::config::get_config;
+use crate::connectors::common::clock_sync::{ExchangeClock, ServerTimeSource};
+use crate::connectors::common::instruments::{positive_decimal_field, InstrumentSource, InstrumentSpec, InstrumentStatus};
+use crate::exchange_types::Exchange;
+use crate::types::account::{AccountBalance, CurrencyBalance};
+use crate::types::config::ConnectorConfig;
+use crate::types::errors::ConnectorError;
+use crate::types::orders::{OrderRequest, OrderResponse, OrderSide, OrderStatus, OrderType, PositionSide, TimeInForce};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Bybit主网REST地址
+pub const BYBIT_REST_URL: &str = "https://api.bybit.com";
+
+/// Bybit测试网REST地址
+pub const BYBIT_TESTNET_REST_URL: &str = "https://api-testnet.bybit.com";
+
+/// USDT永续合约的产品类型
+pub const BYBIT_LINEAR_CATEGORY: &str = "linear";
+
+const ORDER_CREATE_PATH: &str = "/v5/order/create";
+const ORDER_CANCEL_PATH: &str = "/v5/order/cancel";
+const ORDER_REALTIME_PATH: &str = "/v5/order/realtime";
+const ORDER_HISTORY_PATH: &str = "/v5/order/history";
+const WALLET_BALANCE_PATH: &str = "/v5/account/wallet-balance";
+const SERVER_TIME_PATH: &str = "/v5/market/time";
+const INSTRUMENTS_INFO_PATH: &str = "/v5/market/instruments-info";
+
+/// Bybit V5 REST客户端
+#[derive(Clone)]
+pub struct BybitRestClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    /// 服务器时钟，签名时间戳和接收窗口都取自这里
+    clock: Arc<ExchangeClock>,
+}
+
+impl BybitRestClient {
+    /// 根据连接器配置创建REST客户端
+    pub fn new(config: &ConnectorConfig) -> Self {
+        let base_url = config.rest_api_url.clone().unwrap_or_else(|| {
+            if config.testnet { BYBIT_TESTNET_REST_URL } else { BYBIT_REST_URL }.to_string()
+        });
+
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_millis(config.request_timeout.max(1)))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone().filter(|k| !k.is_empty()),
+            secret_key: config.secret_key.clone().filter(|k| !k.is_empty()),
+            clock: Arc::new(ExchangeClock::new(Exchange::BybitFutures, get_config().clock_sync.recv_window_ms)),
+        }
+    }
+
+    /// 服务器时钟
+    pub fn clock(&self) -> &Arc<ExchangeClock> {
+        &self.clock
+    }
+
+    /// 获取服务器时间（毫秒）
+    pub async fn get_server_time(&self) -> Result<i64, ConnectorError> {
+        let url = format!("{}{}", self.base_url, SERVER_TIME_PATH);
+        let data: Value = self.client.get(&url).send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("Bybit请求失败: {e}")))?
+            .json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析Bybit服务器时间失败: {e}")))?;
+        Self::parse_server_time(&data)
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("Bybit服务器时间无效: {data}")))
+    }
+
+    /// 解析服务器时间，优先使用纳秒精度的 `result.timeNano`
+    pub fn parse_server_time(data: &Value) -> Option<i64> {
+        data.get("result")
+            .and_then(|result| Self::field_f64(result, "timeNano"))
+            .map(|nanos| (nanos / 1_000_000.0) as i64)
+            .or_else(|| data.get("time").and_then(|t| t.as_i64()))
+    }
+
+    /// 是否配置了API密钥
+    pub fn has_credentials(&self) -> bool {
+        self.api_key.is_some() && self.secret_key.is_some()
+    }
+
+    /// 计算V5签名：HMAC_SHA256(timestamp + api_key + recv_window + payload)
+    pub fn sign(secret_key: &str, timestamp: u64, api_key: &str, recv_window: u64, payload: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("{timestamp}{api_key}{recv_window}{payload}").as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 获取全部USDT永续合约的交易规则，按游标翻页
+    pub async fn get_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+        let mut specs = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let mut query = vec![("category", BYBIT_LINEAR_CATEGORY.to_string()), ("limit", "1000".to_string())];
+            if !cursor.is_empty() {
+                query.push(("cursor", cursor.clone()));
+            }
+            let result = self.public_request(INSTRUMENTS_INFO_PATH, &query).await?;
+            specs.extend(Self::parse_instruments(&result));
+
+            cursor = result.get("nextPageCursor").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            if cursor.is_empty() {
+                return Ok(specs);
+            }
+        }
+    }
+
+    /// 解析 instruments-info 的 `result.list`，只保留永续合约
+    pub fn parse_instruments(result: &Value) -> Vec<InstrumentSpec> {
+        let Some(list) = result.get("list").and_then(|l| l.as_array()) else {
+            return Vec::new();
+        };
+
+        list.iter().filter_map(|item| {
+            if item.get("contractType").and_then(|t| t.as_str()).is_some_and(|t| t != "LinearPerpetual") {
+                return None;
+            }
+            let price_filter = item.get("priceFilter")?;
+            let lot_size = item.get("lotSizeFilter")?;
+
+            let mut spec = InstrumentSpec::new(
+                Exchange::BybitFutures,
+                item.get("symbol")?.as_str()?,
+                positive_decimal_field(price_filter, "tickSize")?,
+                positive_decimal_field(lot_size, "qtyStep")?,
+            );
+            spec.base_asset = item.get("baseCoin").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            spec.quote_asset = item.get("quoteCoin").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            spec.status = InstrumentStatus::from_exchange(item.get("status").and_then(|s| s.as_str()).unwrap_or_default());
+            spec.min_price = positive_decimal_field(price_filter, "minPrice");
+            spec.max_price = positive_decimal_field(price_filter, "maxPrice");
+            spec.min_qty = positive_decimal_field(lot_size, "minOrderQty").unwrap_or_default();
+            spec.max_qty = positive_decimal_field(lot_size, "maxOrderQty");
+            spec.market_max_qty = positive_decimal_field(lot_size, "maxMktOrderQty");
+            spec.min_notional = positive_decimal_field(lot_size, "minNotionalValue");
+            Some(spec)
+        }).collect()
+    }
+
+    /// 下单
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        let body = Self::build_order_body(order)?;
+        let result = self.signed_request(Method::POST, ORDER_CREATE_PATH, &[], Some(body)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderPlacementFailed(msg),
+                other => other,
+            })?;
+
+        let order_id = result.get("orderId").and_then(|v| v.as_str())
+            .ok_or_else(|| ConnectorError::InvalidResponse("Bybit下单响应缺少orderId".to_string()))?;
+        let client_order_id = result.get("orderLinkId").and_then(|v| v.as_str())
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+            .or_else(|| order.client_order_id.clone());
+
+        // 下单接口只返回订单ID，成交信息需通过查询或私有推送获取
+        Ok(OrderResponse {
+            order_id: order_id.to_string(),
+            client_order_id,
+            symbol: Self::to_bybit_symbol(&order.symbol),
+            status: "NEW".to_string(),
+            filled_quantity: 0.0,
+            remaining_quantity: order.quantity,
+            average_price: None,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        })
+    }
+
+    /// 撤单
+    pub async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
+        let body = json!({
+            "category": BYBIT_LINEAR_CATEGORY,
+            "symbol": Self::to_bybit_symbol(symbol),
+            "orderId": order_id,
+        });
+        self.signed_request(Method::POST, ORDER_CANCEL_PATH, &[], Some(body)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderCancellationFailed(msg),
+                other => other,
+            })?;
+        Ok(true)
+    }
+
+    /// 查询订单状态，活动订单查不到时回退到历史订单
+    pub async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        let symbol = Self::to_bybit_symbol(symbol);
+        let query = [
+            ("category", BYBIT_LINEAR_CATEGORY.to_string()),
+            ("symbol", symbol.clone()),
+            ("orderId", order_id.to_string()),
+        ];
+
+        for path in [ORDER_REALTIME_PATH, ORDER_HISTORY_PATH] {
+            let result = self.signed_request(Method::GET, path, &query, None).await?;
+            if let Some(order) = result.get("list").and_then(|l| l.as_array()).and_then(|l| l.first()) {
+                return Self::parse_order_status(order);
+            }
+        }
+
+        Err(ConnectorError::InvalidResponse(format!("Bybit未找到订单 {order_id} ({symbol})")))
+    }
+
+    /// 查询统一账户钱包余额
+    pub async fn get_wallet_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        let query = [("accountType", "UNIFIED".to_string())];
+        let result = self.signed_request(Method::GET, WALLET_BALANCE_PATH, &query, None).await?;
+        let account = result.get("list").and_then(|l| l.as_array()).and_then(|l| l.first())
+            .ok_or_else(|| ConnectorError::InvalidResponse("Bybit钱包余额响应为空".to_string()))?;
+        Ok(Self::parse_wallet_balance(account))
+    }
+
+    /// 将统一订单请求转换为V5下单参数
+    pub fn build_order_body(order: &OrderRequest) -> Result<Value, ConnectorError> {
+        let order_type = match order.order_type {
+            OrderType::Market => "Market",
+            OrderType::Limit => "Limit",
+            OrderType::StopMarket | OrderType::StopLimit => {
+                return Err(ConnectorError::InvalidOrderParameters(
+                    "Bybit连接器暂不支持条件单".to_string(),
+                ));
+            }
+        };
+
+        let mut body = json!({
+            "category": BYBIT_LINEAR_CATEGORY,
+            "symbol": Self::to_bybit_symbol(&order.symbol),
+            "side": match order.side {
+                OrderSide::Buy => "Buy",
+                OrderSide::Sell => "Sell",
+            },
+            "orderType": order_type,
+            "qty": order.quantity.to_string(),
+            "positionIdx": match order.position_side {
+                Some(PositionSide::Long) => 1,
+                Some(PositionSide::Short) => 2,
+                Some(PositionSide::Both) | None => 0,
+            },
+        });
+
+        if order.order_type == OrderType::Limit {
+            let price = order.price.ok_or_else(|| {
+                ConnectorError::InvalidOrderParameters("限价单必须指定价格".to_string())
+            })?;
+            body["price"] = json!(price.to_string());
+            body["timeInForce"] = json!(match order.time_in_force {
+                Some(TimeInForce::IOC) => "IOC",
+                Some(TimeInForce::FOK) => "FOK",
+                Some(TimeInForce::GTX) => "PostOnly",
+                Some(TimeInForce::GTC) | None => "GTC",
+                Some(TimeInForce::GTD) => {
+                    return Err(ConnectorError::InvalidOrderParameters(
+                        "Bybit不支持GTD订单".to_string(),
+                    ));
+                }
+            });
+        }
+        if let Some(reduce_only) = order.reduce_only {
+            body["reduceOnly"] = json!(reduce_only);
+        }
+        if let Some(ref client_order_id) = order.client_order_id {
+            body["orderLinkId"] = json!(client_order_id);
+        }
+
+        Ok(body)
+    }
+
+    /// 解析订单详情
+    pub fn parse_order_status(order: &Value) -> Result<OrderStatus, ConnectorError> {
+        let order_id = order.get("orderId").and_then(|v| v.as_str())
+            .ok_or_else(|| ConnectorError::InvalidResponse("Bybit订单缺少orderId".to_string()))?;
+        let status = order.get("orderStatus").and_then(|v| v.as_str()).unwrap_or_default();
+
+        Ok(OrderStatus {
+            order_id: order_id.to_string(),
+            symbol: order.get("symbol").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            status: Self::normalize_order_status(status).to_string(),
+            filled_quantity: Self::field_f64(order, "cumExecQty").unwrap_or(0.0),
+            remaining_quantity: Self::field_f64(order, "leavesQty").unwrap_or(0.0),
+            average_price: Self::field_f64(order, "avgPrice").filter(|p| *p > 0.0),
+            timestamp: Self::field_f64(order, "updatedTime").map(|t| t as u64)
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64),
+        })
+    }
+
+    /// 解析统一账户余额
+    pub fn parse_wallet_balance(account: &Value) -> AccountBalance {
+        let mut balances = HashMap::new();
+        for coin in account.get("coin").and_then(|c| c.as_array()).into_iter().flatten() {
+            let Some(currency) = coin.get("coin").and_then(|c| c.as_str()) else { continue };
+            let total = Self::field_f64(coin, "walletBalance").unwrap_or(0.0);
+            let frozen = Self::field_f64(coin, "locked").unwrap_or(0.0)
+                + Self::field_f64(coin, "totalOrderIM").unwrap_or(0.0)
+                + Self::field_f64(coin, "totalPositionIM").unwrap_or(0.0);
+            balances.insert(currency.to_string(), CurrencyBalance {
+                currency: currency.to_string(),
+                total,
+                available: (total - frozen).max(0.0),
+                frozen,
+            });
+        }
+
+        let total = Self::field_f64(account, "totalWalletBalance").unwrap_or(0.0);
+        let available = Self::field_f64(account, "totalAvailableBalance").unwrap_or(0.0);
+        AccountBalance {
+            total,
+            available,
+            frozen: (total - available).max(0.0),
+            balances,
+        }
+    }
+
+    /// Bybit订单状态转换为统一的大写状态
+    pub fn normalize_order_status(status: &str) -> &'static str {
+        match status {
+            "New" | "Created" | "Untriggered" | "Triggered" => "NEW",
+            "PartiallyFilled" => "PARTIALLY_FILLED",
+            "Filled" => "FILLED",
+            "Cancelled" | "PartiallyFilledCanceled" | "Deactivated" => "CANCELED",
+            "Rejected" => "REJECTED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// 去掉交易所前缀和分隔符，得到Bybit合约符号（BTCUSDT）
+    pub fn to_bybit_symbol(symbol: &str) -> String {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        symbol.replace(['_', '-', '/'], "").to_uppercase()
+    }
+
+    /// 读取字符串或数字格式的数值字段
+    pub fn field_f64(value: &Value, key: &str) -> Option<f64> {
+        let field = value.get(key)?;
+        field.as_f64().or_else(|| field.as_str().and_then(|s| s.parse::<f64>().ok()))
+    }
+
+    /// 发送公开请求并返回 `result` 字段
+    async fn public_request(&self, path: &str, query: &[(&str, String)]) -> Result<Value, ConnectorError> {
+        let url = format!("{}{}", self.base_url, path);
+        debug!("[Bybit] GET {url}");
+        let response = self.client.get(&url).query(query).send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("Bybit请求失败: {e}")))?;
+        let status = response.status();
+        let data: Value = response.json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析Bybit响应失败 (HTTP {status}): {e}")))?;
+        Self::into_result(data)
+    }
+
+    /// 发送签名请求并返回 `result` 字段
+    async fn signed_request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Value, ConnectorError> {
+        let (Some(api_key), Some(secret_key)) = (&self.api_key, &self.secret_key) else {
+            return Err(ConnectorError::InvalidCredentials("Bybit API密钥未配置".to_string()));
+        };
+
+        let query_string = query.iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let body_string = body.map(|b| b.to_string()).unwrap_or_default();
+        let payload = if method == Method::GET { &query_string } else { &body_string };
+
+        let timestamp = self.clock.now_ms() as u64;
+        let recv_window = self.clock.recv_window_ms();
+        let signature = Self::sign(secret_key, timestamp, api_key, recv_window, payload);
+
+        let mut url = format!("{}{}", self.base_url, path);
+        if !query_string.is_empty() {
+            url.push('?');
+            url.push_str(&query_string);
+        }
+        debug!("[Bybit] {method} {url}");
+
+        let mut request = self.client.request(method.clone(), &url)
+            .header("X-BAPI-API-KEY", api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-RECV-WINDOW", recv_window.to_string())
+            .header("X-BAPI-SIGN", signature);
+        if method != Method::GET {
+            request = request.header("Content-Type", "application/json").body(body_string);
+        }
+
+        let response = request.send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("Bybit请求失败: {e}")))?;
+        let status = response.status();
+        let data: Value = response.json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析Bybit响应失败 (HTTP {status}): {e}")))?;
+
+        Self::into_result(data)
+    }
+
+    /// 检查 `retCode` 并取出 `result` 字段
+    fn into_result(data: Value) -> Result<Value, ConnectorError> {
+        match data.get("retCode").and_then(|c| c.as_i64()) {
+            Some(0) => Ok(data.get("result").cloned().unwrap_or(Value::Null)),
+            Some(code) => {
+                let message = data.get("retMsg").and_then(|m| m.as_str()).unwrap_or_default();
+                Err(Self::map_error(code, message))
+            }
+            None => Err(ConnectorError::InvalidResponse(format!("Bybit响应缺少retCode: {data}"))),
+        }
+    }
+
+    /// 将Bybit错误码映射为连接器错误
+    fn map_error(code: i64, message: &str) -> ConnectorError {
+        let detail = format!("Bybit错误 {code}: {message}");
+        match code {
+            10003 | 10004 | 10005 | 33004 => ConnectorError::AuthenticationFailed(detail),
+            10006 | 10018 => ConnectorError::RateLimitExceeded(detail),
+            110004 | 110007 | 110012 => ConnectorError::InsufficientBalance(detail),
+            10001 | 110003 | 110017 => ConnectorError::InvalidOrderParameters(detail),
+            _ => ConnectorError::TradingError(detail),
+        }
+    }
+}
+
+#[async_trait]
+impl ServerTimeSource for BybitRestClient {
+    async fn server_time_ms(&self) -> Result<i64, ConnectorError> {
+        self.get_server_time().await
+    }
+}
+
+#[async_trait]
+impl InstrumentSource for BybitRestClient {
+    fn exchange(&self) -> Exchange {
+        Exchange::BybitFutures
+    }
+
+    async fn load_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+        self.get_instruments().await
+    }
+}
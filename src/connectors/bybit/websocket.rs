@@ -0,0 +1,873 @@
+//! Bybit WebSocket处理器
+//! 实现Bybit V5 WebSocket协议：公共深度/成交频道（快照+增量）、私有订单/仓位/钱包频道、心跳以及断线重连
+
+use crate::core::{AppState, AppError, OrderbookUpdate};
+use crate::connectors::common::data_channel::{ChannelStats, DataSender};
+use crate::connectors::common::local_orderbook::{parse_price_levels, LocalOrderBook};
+use crate::exchange_types::Exchange;
+use crate::types::config::{ConnectorConfig, ConnectionStatus};
+use crate::types::exchange::ExchangeType;
+use crate::types::market_data::{
+    BalanceUpdate, OrderUpdate, PositionUpdate, StandardizedMessage, StandardizedOrderBook,
+    StandardizedTrade, TradeSide, UserData,
+};
+use super::rest::BybitRestClient;
+use chrono::Utc;
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use log::{info, warn, error, debug};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// Bybit主网WebSocket地址
+pub const BYBIT_WS_URL: &str = "wss://stream.bybit.com";
+
+/// Bybit测试网WebSocket地址
+pub const BYBIT_TESTNET_WS_URL: &str = "wss://stream-testnet.bybit.com";
+
+/// 默认订阅的深度档位
+const DEFAULT_DEPTH_LEVELS: u32 = 50;
+
+/// 每个交易对保留的最近成交条数
+const RECENT_TRADES_CAPACITY: usize = 200;
+
+/// 单条订阅请求最多携带的topic数量
+const MAX_TOPICS_PER_REQUEST: usize = 10;
+
+/// 私有频道鉴权签名的有效期（毫秒）
+const AUTH_EXPIRES_MS: i64 = 10_000;
+
+/// 私有频道topic
+const PRIVATE_TOPICS: [&str; 3] = ["order", "position", "wallet"];
+
+type BybitWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Bybit WebSocket连接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BybitStream {
+    /// USDT永续公共行情
+    PublicLinear,
+    /// 私有数据（需鉴权）
+    Private,
+}
+
+impl BybitStream {
+    fn path(&self) -> &'static str {
+        match self {
+            BybitStream::PublicLinear => "/v5/public/linear",
+            BybitStream::Private => "/v5/private",
+        }
+    }
+
+    fn connection_id(&self) -> &'static str {
+        match self {
+            BybitStream::PublicLinear => "bybit-public",
+            BybitStream::Private => "bybit-private",
+        }
+    }
+}
+
+/// 订单簿推送（快照或增量）
+#[derive(Debug, Clone)]
+pub struct BybitOrderBookData {
+    pub symbol: String,
+    /// 是否为全量快照
+    pub is_snapshot: bool,
+    /// 更新ID，u=1表示服务重启后的新快照
+    pub update_id: i64,
+    /// 撮合序号
+    pub sequence: i64,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+    pub timestamp: i64,
+}
+
+/// 解析后的Bybit消息帧
+#[derive(Debug, Clone)]
+pub enum BybitFrame {
+    /// 心跳回应
+    Pong,
+    /// 订阅/鉴权等指令的回应
+    CommandResponse { op: String, success: bool, message: String },
+    /// 订单簿快照或增量
+    OrderBook(BybitOrderBookData),
+    /// 成交记录
+    Trades(Vec<StandardizedTrade>),
+    /// 订单更新
+    Orders(Vec<OrderUpdate>),
+    /// 仓位更新
+    Positions(Vec<PositionUpdate>),
+    /// 钱包余额更新
+    Wallet(Vec<BalanceUpdate>),
+    /// 其他消息
+    Other(Value),
+}
+
+/// 连接会话结束原因
+enum SessionEnd {
+    /// 调用方主动停止
+    Shutdown,
+    /// 连接断开或被要求重连
+    Lost,
+}
+
+/// Bybit WebSocket处理器
+#[derive(Clone)]
+pub struct BybitWebSocketHandler {
+    app_state: Arc<AppState>,
+    stream: BybitStream,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    ws_url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    ping_interval: Duration,
+    reconnect_interval: Duration,
+    max_reconnect_attempts: u32,
+    connect_timeout: Duration,
+    status: Arc<RwLock<ConnectionStatus>>,
+    should_run: Arc<RwLock<bool>>,
+    command_sender: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    subscriptions: Arc<RwLock<HashSet<String>>>,
+    orderbooks: Arc<DashMap<String, LocalOrderBook>>,
+    recent_trades: Arc<DashMap<String, VecDeque<StandardizedTrade>>>,
+}
+
+impl BybitWebSocketHandler {
+    /// 创建新的Bybit WebSocket处理器
+    ///
+    /// `websocket_url` 配置视为基础地址，连接时追加对应频道的路径。
+    pub fn new(config: &ConnectorConfig, stream: BybitStream, app_state: Arc<AppState>) -> Self {
+        let base_url = config.websocket_url.clone().unwrap_or_else(|| {
+            if config.testnet { BYBIT_TESTNET_WS_URL } else { BYBIT_WS_URL }.to_string()
+        });
+        if stream == BybitStream::PublicLinear {
+            // 行情连接登记到AppState，套利扫描器发现订单簿持续过期时据此发出重连信号
+            app_state.register_market_connection(Exchange::BybitFutures, stream.connection_id());
+        }
+
+        Self {
+            app_state,
+            stream,
+            message_sender: Arc::new(RwLock::new(None)),
+            ws_url: format!("{}{}", base_url.trim_end_matches('/'), stream.path()),
+            api_key: config.api_key.clone().filter(|k| !k.is_empty()),
+            secret_key: config.secret_key.clone().filter(|k| !k.is_empty()),
+            ping_interval: Duration::from_millis(config.ping_interval.max(1)),
+            reconnect_interval: Duration::from_millis(config.reconnect_interval),
+            max_reconnect_attempts: config.max_reconnect_attempts,
+            connect_timeout: Duration::from_millis(config.request_timeout.max(1)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            should_run: Arc::new(RwLock::new(false)),
+            command_sender: Arc::new(RwLock::new(None)),
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            orderbooks: Arc::new(DashMap::new()),
+            recent_trades: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// 连接地址
+    pub fn url(&self) -> &str {
+        &self.ws_url
+    }
+
+    /// 设置消息发送器
+    pub async fn set_message_sender(&self, sender: DataSender<StandardizedMessage>) {
+        let mut message_sender = self.message_sender.write().await;
+        *message_sender = Some(sender);
+    }
+
+    /// 同步设置消息发送器（用于非异步上下文，例如获取数据流）
+    pub fn try_set_message_sender(&self, sender: DataSender<StandardizedMessage>) -> bool {
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => {
+                *message_sender = Some(sender);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 当前消息通道的计数快照
+    pub fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
+    /// 启动WebSocket连接
+    ///
+    /// 首次连接（私有频道含鉴权）失败直接返回错误；连接建立后在后台维护心跳和断线重连。
+    pub async fn start(&self) -> Result<(), AppError> {
+        {
+            let status = self.status.read().await;
+            if matches!(*status, ConnectionStatus::Connected | ConnectionStatus::Connecting | ConnectionStatus::Reconnecting) {
+                debug!("[Bybit] {} 连接已在运行，跳过启动", self.connection_id());
+                return Ok(());
+            }
+        }
+
+        info!("[Bybit] {} 连接到 {}", self.connection_id(), self.ws_url);
+        *self.status.write().await = ConnectionStatus::Connecting;
+        *self.should_run.write().await = true;
+
+        let ws_stream = match self.open().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Error;
+                *self.should_run.write().await = false;
+                return Err(e);
+            }
+        };
+
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let command_tx_owner = command_tx.clone();
+        *self.command_sender.write().await = Some(command_tx);
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.app_state.update_connection_timestamp(self.connection_id());
+        self.app_state.clear_reconnect_signal(self.connection_id());
+
+        let handler = self.clone();
+        tokio::spawn(async move {
+            handler.connection_loop(ws_stream, command_tx_owner, command_rx).await;
+        });
+
+        info!("[Bybit] {} WebSocket连接成功", self.connection_id());
+        Ok(())
+    }
+
+    /// 停止WebSocket连接，不再重连
+    pub async fn stop(&self) -> Result<(), AppError> {
+        *self.should_run.write().await = false;
+
+        if let Some(sender) = self.command_sender.write().await.take() {
+            let _ = sender.send(Message::Close(None));
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.app_state.mark_connection_unhealthy(self.connection_id());
+        info!("[Bybit] {} WebSocket已停止", self.connection_id());
+        Ok(())
+    }
+
+    /// 订阅深度数据
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        let topics = symbols.iter()
+            .map(|symbol| Self::orderbook_topic(&BybitRestClient::to_bybit_symbol(symbol)))
+            .collect();
+        self.subscribe_topics(topics).await
+    }
+
+    /// 订阅成交数据
+    pub async fn subscribe_trades(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        let topics = symbols.iter()
+            .map(|symbol| Self::trade_topic(&BybitRestClient::to_bybit_symbol(symbol)))
+            .collect();
+        self.subscribe_topics(topics).await
+    }
+
+    /// 订阅私有的订单、仓位和钱包频道
+    pub async fn subscribe_private(&self) -> Result<(), AppError> {
+        if self.stream != BybitStream::Private {
+            return Err(AppError::ConfigError("公共连接不能订阅私有频道".to_string()));
+        }
+        self.subscribe_topics(PRIVATE_TOPICS.iter().map(|t| t.to_string()).collect()).await
+    }
+
+    /// 取消订阅交易对的全部频道
+    pub async fn unsubscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        let mut removed = Vec::new();
+        {
+            let mut subscriptions = self.subscriptions.write().await;
+            for symbol in &symbols {
+                let symbol = BybitRestClient::to_bybit_symbol(symbol);
+                for topic in [Self::orderbook_topic(&symbol), Self::trade_topic(&symbol)] {
+                    if subscriptions.remove(&topic) {
+                        removed.push(topic);
+                    }
+                }
+                self.orderbooks.remove(&symbol);
+            }
+        }
+
+        for chunk in removed.chunks(MAX_TOPICS_PER_REQUEST) {
+            self.send_command(Self::topics_message("unsubscribe", chunk)).await?;
+        }
+        info!("[Bybit] 已取消订阅: {symbols:?}");
+        Ok(())
+    }
+
+    /// 检查连接状态
+    pub async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    /// 获取当前连接状态
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 获取当前的订阅topic列表
+    pub async fn get_subscriptions(&self) -> Vec<String> {
+        self.subscriptions.read().await.iter().cloned().collect()
+    }
+
+    /// 获取本地维护的订单簿
+    pub fn get_orderbook(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        let book = self.orderbooks.get(&BybitRestClient::to_bybit_symbol(symbol))?;
+        if book.is_empty() {
+            return None;
+        }
+        Some(book.to_standardized(Exchange::BybitFutures, DEFAULT_DEPTH_LEVELS as usize))
+    }
+
+    /// 获取本地缓存的最近成交（按时间从新到旧）
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.recent_trades
+            .get(&BybitRestClient::to_bybit_symbol(symbol))
+            .map(|trades| trades.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 计算私有频道鉴权签名：HMAC_SHA256(secret, "GET/realtime" + expires)
+    pub fn auth_signature(secret_key: &str, expires: i64) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("GET/realtime{expires}").as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 解析一条Bybit V5文本消息
+    pub fn parse_message(text: &str) -> Result<BybitFrame, AppError> {
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("解析Bybit消息失败: {e}")))?;
+
+        if let Some(op) = msg.get("op").and_then(|o| o.as_str()) {
+            let ret_msg = msg.get("ret_msg").and_then(|m| m.as_str()).unwrap_or_default();
+            // 公共频道回应 {"op":"ping","ret_msg":"pong"}，私有频道回应 {"op":"pong"}
+            if op == "pong" || (op == "ping" && ret_msg == "pong") {
+                return Ok(BybitFrame::Pong);
+            }
+            return Ok(BybitFrame::CommandResponse {
+                op: op.to_string(),
+                success: msg.get("success").and_then(|s| s.as_bool()).unwrap_or(false),
+                message: ret_msg.to_string(),
+            });
+        }
+
+        let topic = msg.get("topic").and_then(|t| t.as_str()).unwrap_or_default();
+        if topic.starts_with("orderbook.") {
+            Self::parse_orderbook(&msg).map(BybitFrame::OrderBook)
+        } else if topic.starts_with("publicTrade.") {
+            Self::parse_trades(&msg).map(BybitFrame::Trades)
+        } else {
+            match topic {
+                "order" => Ok(BybitFrame::Orders(Self::parse_orders(&msg))),
+                "position" => Ok(BybitFrame::Positions(Self::parse_positions(&msg))),
+                "wallet" => Ok(BybitFrame::Wallet(Self::parse_wallet(&msg))),
+                _ => Ok(BybitFrame::Other(msg)),
+            }
+        }
+    }
+
+    /// 解析订单簿快照/增量
+    fn parse_orderbook(msg: &Value) -> Result<BybitOrderBookData, AppError> {
+        let data = msg.get("data")
+            .ok_or_else(|| AppError::ParseError("Bybit深度消息缺少data字段".to_string()))?;
+        let symbol = data.get("s").and_then(|s| s.as_str())
+            .ok_or_else(|| AppError::ParseError("Bybit深度消息缺少s字段".to_string()))?;
+        let update_id = data.get("u").and_then(|u| u.as_i64())
+            .ok_or_else(|| AppError::ParseError("Bybit深度消息缺少u字段".to_string()))?;
+
+        Ok(BybitOrderBookData {
+            symbol: symbol.to_string(),
+            is_snapshot: msg.get("type").and_then(|t| t.as_str()) == Some("snapshot"),
+            update_id,
+            sequence: data.get("seq").and_then(|s| s.as_i64()).unwrap_or_default(),
+            bids: parse_price_levels(data.get("b")),
+            asks: parse_price_levels(data.get("a")),
+            timestamp: msg.get("ts").and_then(|t| t.as_i64()).unwrap_or_else(|| Utc::now().timestamp_millis()),
+        })
+    }
+
+    /// 解析成交记录
+    fn parse_trades(msg: &Value) -> Result<Vec<StandardizedTrade>, AppError> {
+        let trades = msg.get("data").and_then(|d| d.as_array())
+            .ok_or_else(|| AppError::ParseError("Bybit成交消息缺少data字段".to_string()))?;
+
+        trades.iter()
+            .map(|trade| {
+                let price = Self::value_f64(trade, "p")
+                    .ok_or_else(|| AppError::ParseError("Bybit成交消息缺少p".to_string()))?;
+                let quantity = Self::value_f64(trade, "v")
+                    .ok_or_else(|| AppError::ParseError("Bybit成交消息缺少v".to_string()))?;
+                Ok(StandardizedTrade {
+                    symbol: trade.get("s").and_then(|s| s.as_str()).unwrap_or_default().to_string(),
+                    exchange: ExchangeType::BybitFutures,
+                    price,
+                    quantity,
+                    side: match trade.get("S").and_then(|s| s.as_str()) {
+                        Some("Sell") => TradeSide::Sell,
+                        _ => TradeSide::Buy,
+                    },
+                    timestamp: trade.get("T").and_then(|t| t.as_i64()).unwrap_or_default(),
+                    trade_id: trade.get("i").and_then(|i| i.as_str()).unwrap_or_default().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析订单推送
+    fn parse_orders(msg: &Value) -> Vec<OrderUpdate> {
+        Self::data_items(msg)
+            .filter_map(|order| {
+                Some(OrderUpdate {
+                    order_id: order.get("orderId")?.as_str()?.to_string(),
+                    symbol: order.get("symbol")?.as_str()?.to_string(),
+                    exchange: ExchangeType::BybitFutures,
+                    status: BybitRestClient::normalize_order_status(
+                        order.get("orderStatus").and_then(|s| s.as_str()).unwrap_or_default(),
+                    ).to_string(),
+                    filled_quantity: Self::value_f64(order, "cumExecQty").unwrap_or(0.0),
+                    remaining_quantity: Self::value_f64(order, "leavesQty").unwrap_or(0.0),
+                    average_price: Self::value_f64(order, "avgPrice").filter(|price| *price > 0.0),
+                    timestamp: Self::value_f64(order, "updatedTime").map(|t| t as i64)
+                        .unwrap_or_else(|| Utc::now().timestamp_millis()),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析仓位推送，空头仓位数量为负
+    fn parse_positions(msg: &Value) -> Vec<PositionUpdate> {
+        Self::data_items(msg)
+            .filter_map(|position| {
+                let size = Self::value_f64(position, "size").unwrap_or(0.0);
+                let size = match position.get("side").and_then(|s| s.as_str()) {
+                    Some("Sell") => -size,
+                    _ => size,
+                };
+                Some(PositionUpdate {
+                    symbol: position.get("symbol")?.as_str()?.to_string(),
+                    exchange: ExchangeType::BybitFutures,
+                    size,
+                    entry_price: Self::value_f64(position, "entryPrice").unwrap_or(0.0),
+                    unrealized_pnl: Self::value_f64(position, "unrealisedPnl").unwrap_or(0.0),
+                    timestamp: Self::value_f64(position, "updatedTime").map(|t| t as i64)
+                        .unwrap_or_else(|| Utc::now().timestamp_millis()),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析钱包推送
+    fn parse_wallet(msg: &Value) -> Vec<BalanceUpdate> {
+        let timestamp = msg.get("creationTime").and_then(|t| t.as_i64())
+            .unwrap_or_else(|| Utc::now().timestamp_millis());
+        Self::data_items(msg)
+            .flat_map(|account| BybitRestClient::parse_wallet_balance(account).balances.into_values())
+            .map(|balance| BalanceUpdate {
+                asset: balance.currency,
+                exchange: ExchangeType::BybitFutures,
+                free: balance.available,
+                locked: balance.frozen,
+                timestamp,
+            })
+            .collect()
+    }
+
+    fn data_items(msg: &Value) -> impl Iterator<Item = &Value> {
+        msg.get("data").and_then(|d| d.as_array()).into_iter().flatten()
+    }
+
+    fn value_f64(value: &Value, key: &str) -> Option<f64> {
+        BybitRestClient::field_f64(value, key)
+    }
+
+    fn orderbook_topic(symbol: &str) -> String {
+        format!("orderbook.{DEFAULT_DEPTH_LEVELS}.{symbol}")
+    }
+
+    fn trade_topic(symbol: &str) -> String {
+        format!("publicTrade.{symbol}")
+    }
+
+    fn connection_id(&self) -> &'static str {
+        self.stream.connection_id()
+    }
+
+    fn topics_message(op: &str, topics: &[String]) -> Message {
+        Message::Text(json!({"op": op, "args": topics}).to_string())
+    }
+
+    async fn subscribe_topics(&self, topics: Vec<String>) -> Result<(), AppError> {
+        info!("[Bybit] {} 订阅: {topics:?}", self.connection_id());
+
+        let mut added = Vec::new();
+        {
+            let mut subscriptions = self.subscriptions.write().await;
+            for topic in topics {
+                if subscriptions.insert(topic.clone()) {
+                    added.push(topic);
+                }
+            }
+        }
+
+        // 未连接时只记录订阅，连接建立后统一发送
+        if self.is_connected().await {
+            for chunk in added.chunks(MAX_TOPICS_PER_REQUEST) {
+                self.send_command(Self::topics_message("subscribe", chunk)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_command(&self, message: Message) -> Result<(), AppError> {
+        match self.command_sender.read().await.as_ref() {
+            Some(sender) => sender
+                .send(message)
+                .map_err(|e| AppError::WebSocketError(format!("Bybit发送队列已关闭: {e}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// 建立连接，私有频道在返回前完成鉴权
+    async fn open(&self) -> Result<BybitWsStream, AppError> {
+        let (mut ws_stream, _) = timeout(self.connect_timeout, connect_async(&self.ws_url))
+            .await
+            .map_err(|_| AppError::WebSocketError(format!("连接Bybit WebSocket超时: {}", self.ws_url)))?
+            .map_err(|e| AppError::WebSocketError(format!("连接Bybit WebSocket失败: {e}")))?;
+
+        if self.stream == BybitStream::Private {
+            self.authenticate(&mut ws_stream).await?;
+        }
+        Ok(ws_stream)
+    }
+
+    /// 发送鉴权请求并等待服务器确认
+    async fn authenticate(&self, ws_stream: &mut BybitWsStream) -> Result<(), AppError> {
+        let (Some(api_key), Some(secret_key)) = (&self.api_key, &self.secret_key) else {
+            return Err(AppError::ConfigError("Bybit私有频道需要API密钥".to_string()));
+        };
+
+        let expires = Utc::now().timestamp_millis() + AUTH_EXPIRES_MS;
+        let auth = json!({
+            "op": "auth",
+            "args": [api_key, expires, Self::auth_signature(secret_key, expires)],
+        });
+        ws_stream
+            .send(Message::Text(auth.to_string()))
+            .await
+            .map_err(|e| AppError::WebSocketError(format!("发送Bybit鉴权请求失败: {e}")))?;
+
+        timeout(self.connect_timeout, async {
+            while let Some(message) = ws_stream.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(_) => continue,
+                    Err(e) => return Err(AppError::WebSocketError(format!("等待Bybit鉴权回应失败: {e}"))),
+                };
+                if let Ok(BybitFrame::CommandResponse { op, success, message }) = Self::parse_message(&text) {
+                    if op == "auth" {
+                        return if success {
+                            info!("[Bybit] {} 鉴权成功", self.connection_id());
+                            Ok(())
+                        } else {
+                            Err(AppError::ConnectionError(format!("Bybit鉴权失败: {message}")))
+                        };
+                    }
+                }
+            }
+            Err(AppError::WebSocketError("Bybit鉴权前连接被关闭".to_string()))
+        })
+        .await
+        .map_err(|_| AppError::WebSocketError("等待Bybit鉴权回应超时".to_string()))?
+    }
+
+    /// 连接维护循环：处理单次会话，断线后按配置重连（私有频道重新鉴权）并恢复订阅
+    async fn connection_loop(
+        self,
+        mut ws_stream: BybitWsStream,
+        command_tx: mpsc::UnboundedSender<Message>,
+        mut command_rx: mpsc::UnboundedReceiver<Message>,
+    ) {
+        loop {
+            if let Err(e) = self.resubscribe_all(&mut ws_stream).await {
+                warn!("[Bybit] {} 恢复订阅失败: {e}", self.connection_id());
+            }
+
+            if let SessionEnd::Shutdown = self.run_session(ws_stream, &mut command_rx).await {
+                break;
+            }
+            if !*self.should_run.read().await {
+                break;
+            }
+
+            // 断线期间的增量无法补齐，等待新连接推送快照
+            self.orderbooks.clear();
+            self.app_state.mark_connection_unhealthy(self.connection_id());
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+
+            let mut reconnected = None;
+            for attempt in 1..=self.max_reconnect_attempts {
+                tokio::time::sleep(self.reconnect_interval).await;
+                if !*self.should_run.read().await {
+                    break;
+                }
+
+                info!("[Bybit] {} 重连 (尝试 {}/{})", self.connection_id(), attempt, self.max_reconnect_attempts);
+                match self.open().await {
+                    Ok(stream) => {
+                        reconnected = Some(stream);
+                        break;
+                    }
+                    Err(e) => warn!("[Bybit] {} 重连失败: {e}", self.connection_id()),
+                }
+            }
+
+            match reconnected {
+                Some(stream) => {
+                    ws_stream = stream;
+                    *self.status.write().await = ConnectionStatus::Connected;
+                    self.app_state.update_connection_timestamp(self.connection_id());
+                    self.app_state.clear_reconnect_signal(self.connection_id());
+                    info!("[Bybit] {} 重连成功", self.connection_id());
+                }
+                None => {
+                    if *self.should_run.read().await {
+                        error!("[Bybit] {} 重连次数耗尽，停止连接", self.connection_id());
+                        *self.status.write().await = ConnectionStatus::Error;
+                        *self.should_run.write().await = false;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // 只清理属于本次连接的状态，避免影响停止后重新启动的新连接
+        let mut command_sender = self.command_sender.write().await;
+        if command_sender.as_ref().is_some_and(|sender| sender.same_channel(&command_tx)) {
+            command_sender.take();
+            let mut status = self.status.write().await;
+            if *status != ConnectionStatus::Error {
+                *status = ConnectionStatus::Disconnected;
+            }
+        }
+        drop(command_sender);
+        info!("[Bybit] {} 连接循环已退出", self.connection_id());
+    }
+
+    async fn resubscribe_all(&self, ws_stream: &mut BybitWsStream) -> Result<(), AppError> {
+        let mut topics = self.get_subscriptions().await;
+        topics.sort();
+        for chunk in topics.chunks(MAX_TOPICS_PER_REQUEST) {
+            ws_stream
+                .send(Self::topics_message("subscribe", chunk))
+                .await
+                .map_err(|e| AppError::WebSocketError(format!("发送订阅失败: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// 运行单次连接会话，直到连接断开或收到停止指令
+    async fn run_session(&self, ws_stream: BybitWsStream, command_rx: &mut mpsc::UnboundedReceiver<Message>) -> SessionEnd {
+        let (mut write, mut read) = ws_stream.split();
+        let mut ping_timer = tokio::time::interval(self.ping_interval);
+        ping_timer.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => {
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            last_message = Instant::now();
+                            self.handle_text(&text).await;
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            last_message = Instant::now();
+                            if let Err(e) = write.send(Message::Pong(payload)).await {
+                                error!("[Bybit] {} 发送Pong失败: {e}", self.connection_id());
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("[Bybit] {} WebSocket连接被服务器关闭", self.connection_id());
+                            return SessionEnd::Lost;
+                        }
+                        Some(Ok(_)) => {
+                            last_message = Instant::now();
+                        }
+                        Some(Err(e)) => {
+                            error!("[Bybit] {} WebSocket错误: {e}", self.connection_id());
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+                command = command_rx.recv() => {
+                    match command {
+                        Some(Message::Close(frame)) => {
+                            let _ = write.send(Message::Close(frame)).await;
+                            return SessionEnd::Shutdown;
+                        }
+                        Some(message) => {
+                            if let Err(e) = write.send(message).await {
+                                error!("[Bybit] {} 发送消息失败: {e}", self.connection_id());
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        None => return SessionEnd::Shutdown,
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if !*self.should_run.read().await {
+                        return SessionEnd::Shutdown;
+                    }
+                    if self.app_state.should_reconnect(self.connection_id()) {
+                        warn!("[Bybit] {} 收到重连信号", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+                    if last_message.elapsed() > self.ping_interval * 3 {
+                        warn!("[Bybit] {} 长时间未收到消息，重连", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+
+                    let ping = json!({"op": "ping"});
+                    if let Err(e) = write.send(Message::Text(ping.to_string())).await {
+                        error!("[Bybit] {} 发送心跳失败: {e}", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理文本消息
+    async fn handle_text(&self, text: &str) {
+        self.app_state.increment_websocket_messages(1);
+        self.app_state.update_connection_timestamp(self.connection_id());
+
+        let frame = match Self::parse_message(text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("[Bybit] {} {e}", self.connection_id());
+                return;
+            }
+        };
+
+        match frame {
+            BybitFrame::Pong => {
+                debug!("[Bybit] {} 收到pong", self.connection_id());
+            }
+            BybitFrame::CommandResponse { op, success, message } => {
+                if success {
+                    debug!("[Bybit] {} {op} 成功", self.connection_id());
+                } else {
+                    warn!("[Bybit] {} {op} 失败: {message}", self.connection_id());
+                }
+            }
+            BybitFrame::OrderBook(data) => {
+                if let Some(orderbook) = self.apply_orderbook(data) {
+                    self.publish_orderbook(orderbook).await;
+                }
+            }
+            BybitFrame::Trades(trades) => {
+                for trade in trades {
+                    self.publish_trade(trade).await;
+                }
+            }
+            BybitFrame::Orders(orders) => {
+                for order in orders {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(order))).await;
+                }
+            }
+            BybitFrame::Positions(positions) => {
+                for position in positions {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::PositionUpdate(position))).await;
+                }
+            }
+            BybitFrame::Wallet(balances) => {
+                for balance in balances {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(balance))).await;
+                }
+            }
+            BybitFrame::Other(msg) => {
+                debug!("[Bybit] {} 收到其他消息: {msg}", self.connection_id());
+            }
+        }
+    }
+
+    /// 将快照/增量应用到本地订单簿，返回更新后的标准化订单簿
+    ///
+    /// 快照或 u=1 的消息整体替换订单簿；收到快照前的增量和过期增量直接丢弃。
+    pub fn apply_orderbook(&self, data: BybitOrderBookData) -> Option<StandardizedOrderBook> {
+        let mut book = self.orderbooks
+            .entry(data.symbol.clone())
+            .or_insert_with(|| LocalOrderBook::new(&data.symbol));
+
+        if data.is_snapshot || data.update_id == 1 {
+            book.reset(&data.bids, &data.asks, data.update_id, data.timestamp);
+        } else if book.last_update_id() == 0 {
+            debug!("[Bybit] {} 尚未收到快照，丢弃增量 u={}", data.symbol, data.update_id);
+            return None;
+        } else if data.update_id <= book.last_update_id() {
+            debug!("[Bybit] {} 丢弃过期增量 u={} (当前 {})", data.symbol, data.update_id, book.last_update_id());
+            return None;
+        } else {
+            book.apply_diff(&data.bids, &data.asks, data.update_id, data.timestamp);
+        }
+
+        Some(book.to_standardized(Exchange::BybitFutures, DEFAULT_DEPTH_LEVELS as usize))
+    }
+
+    async fn publish_orderbook(&self, orderbook: StandardizedOrderBook) {
+        self.app_state.book_store.update(orderbook.clone());
+
+        if let Some(tx) = &self.app_state.orderbook_queue {
+            let update = OrderbookUpdate {
+                symbol: format!("{}:{}", Exchange::BybitFutures, orderbook.symbol),
+                best_ask: orderbook.best_ask,
+                best_bid: orderbook.best_bid,
+                timestamp: orderbook.timestamp,
+                scale: 8,
+                is_synthetic: false,
+                leg1: None,
+                leg2: None,
+                depth_asks: Some(orderbook.depth_asks.clone()),
+                depth_bids: Some(orderbook.depth_bids.clone()),
+            };
+            if let Err(e) = tx.send(update) {
+                error!("[Bybit] 发送订单簿更新失败: {e}");
+            }
+        }
+
+        self.forward(StandardizedMessage::OrderBookUpdate(orderbook)).await;
+    }
+
+    async fn publish_trade(&self, trade: StandardizedTrade) {
+        {
+            let mut trades = self.recent_trades.entry(trade.symbol.clone()).or_default();
+            if trades.len() >= RECENT_TRADES_CAPACITY {
+                trades.pop_front();
+            }
+            trades.push_back(trade.clone());
+        }
+
+        self.forward(StandardizedMessage::TradeUpdate(trade)).await;
+    }
+
+    async fn forward(&self, message: StandardizedMessage) {
+        // 先取出发送端再发送，阻塞策略等待消费时不占用锁
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[Bybit] {} 消息接收端已关闭", self.connection_id());
+            }
+        }
+    }
+}
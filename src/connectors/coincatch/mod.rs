@@ -0,0 +1,14 @@
+//! CoinCatch连接器模块
+//! USDT永续合约行情（books15 / trade 频道）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::CoinCatchProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// CoinCatch行情连接器
+pub type CoinCatchConnector = MarketStreamConnector<CoinCatchProtocol>;
@@ -0,0 +1,114 @@
+//! CoinCatch合约行情协议
+//! 订阅格式：`{"op":"subscribe","args":[{"instType":"mc","channel":"books15","instId":"BTCUSDT"}]}`；
+//! books15每次推送完整的前15档；心跳为纯文本 `ping` / `pong`。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::market_stream::{
+    build_orderbook, value_to_f64, value_to_i64, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// CoinCatch WebSocket默认地址
+pub const COINCATCH_WS_URL: &str = "wss://ws.coincatch.com/public/v1/stream";
+
+/// USDT永续合约的产品类型
+const INST_TYPE: &str = "mc";
+
+const ORDERBOOK_CHANNEL: &str = "books15";
+const TRADE_CHANNEL: &str = "trade";
+
+/// CoinCatch合约行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoinCatchProtocol;
+
+impl MarketStreamProtocol for CoinCatchProtocol {
+    const NAME: &'static str = "CoinCatch";
+    const DEFAULT_WS_URL: &'static str = COINCATCH_WS_URL;
+    const EXCHANGE: Exchange = Exchange::CoinCatch;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::CoinCatch;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}{}", info.base_currency, info.quote_currency)
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        exchange_symbol.to_uppercase()
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], _request_id: u64) -> String {
+        let channel = match channel {
+            MarketChannel::OrderBook => ORDERBOOK_CHANNEL,
+            MarketChannel::Trades => TRADE_CHANNEL,
+        };
+        let args: Vec<Value> = symbols.iter()
+            .map(|symbol| json!({"instType": INST_TYPE, "channel": channel, "instId": symbol}))
+            .collect();
+        json!({
+            "op": if subscribe { "subscribe" } else { "unsubscribe" },
+            "args": args,
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        Some("ping".to_string())
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        if text == "pong" {
+            return Ok(vec![MarketEvent::Pong]);
+        }
+
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析CoinCatch消息: {e}")))?;
+
+        if msg.get("event").and_then(|e| e.as_str()) == Some("error") {
+            return Ok(vec![MarketEvent::Error(msg.to_string())]);
+        }
+        if msg.get("action").is_none() {
+            return Ok(vec![MarketEvent::Other(msg)]);
+        }
+
+        let arg = &msg["arg"];
+        let symbol = match arg.get("instId").and_then(|s| s.as_str()) {
+            Some(inst_id) => Self::standard_symbol(inst_id),
+            None => return Err(AppError::ParseError("CoinCatch推送缺少instId".to_string())),
+        };
+        let data = msg.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+
+        match arg.get("channel").and_then(|c| c.as_str()) {
+            Some(ORDERBOOK_CHANNEL) => Ok(data.iter()
+                .map(|book| {
+                    let timestamp = book.get("ts").and_then(value_to_i64).unwrap_or(0);
+                    MarketEvent::Book(build_orderbook(Self::EXCHANGE, symbol.clone(), book.get("bids"), book.get("asks"), timestamp))
+                })
+                .collect()),
+            Some(TRADE_CHANNEL) => {
+                // [时间戳, 价格, 数量, 方向]，成交推送没有成交ID
+                let trades = data.iter()
+                    .filter_map(|trade| {
+                        let fields = trade.as_array()?;
+                        let timestamp = value_to_i64(fields.first()?)?;
+                        Some(StandardizedTrade {
+                            symbol: symbol.clone(),
+                            exchange: Self::EXCHANGE_TYPE,
+                            price: value_to_f64(fields.get(1)?)?,
+                            quantity: value_to_f64(fields.get(2)?)?,
+                            side: if fields.get(3)?.as_str()? == "sell" { TradeSide::Sell } else { TradeSide::Buy },
+                            timestamp,
+                            trade_id: timestamp.to_string(),
+                        })
+                    })
+                    .collect();
+                Ok(vec![MarketEvent::Trades(trades)])
+            }
+            _ => Ok(vec![MarketEvent::Other(msg)]),
+        }
+    }
+}
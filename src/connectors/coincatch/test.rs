@@ -0,0 +1,80 @@
+//! CoinCatch连接器测试模块
+//! 使用录制的推送帧测试协议解析、符号转换和批量订阅
+
+mod tests {
+    use super::super::{CoinCatchConnector, CoinCatchProtocol};
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::core::AppState;
+    use crate::types::{config::ConnectorConfig, market_data::TradeSide};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// 录制的CoinCatch深度帧
+    const RECORDED_DEPTH_FRAME: &str = r#"{"action":"snapshot","arg":{"instType":"mc","channel":"books15","instId":"BTCUSDT"},"data":[{"asks":[["64012.4","1.532"],["64012.9","0.200"]],"bids":[["64011.9","5.321"],["64011.5","0.780"]],"checksum":0,"ts":"1710755362722"}]}"#;
+
+    /// 录制的CoinCatch成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"action":"update","arg":{"instType":"mc","channel":"trade","instId":"BTCUSDT"},"data":[["1710755363460","64011.9","0.015","sell"],["1710755363461","64012.9","0.200","buy"]]}"#;
+
+    /// 录制的CoinCatch订阅确认和错误
+    const RECORDED_SUBSCRIBE_ACK_FRAME: &str = r#"{"event":"subscribe","arg":{"instType":"mc","channel":"books15","instId":"BTCUSDT"}}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"event":"error","code":30001,"msg":"instType:mc,channel:books15,instId:FOOUSDT doesn't exist"}"#;
+
+    #[test]
+    fn test_coincatch_parse_recorded_frames() {
+        let events = CoinCatchProtocol::parse_message(RECORDED_DEPTH_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Book(orderbook)] => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 64011.9);
+                assert_eq!(orderbook.best_ask, 64012.4);
+                assert_eq!(orderbook.timestamp, 1710755362722);
+            }
+            other => panic!("应该解析为深度: {other:?}"),
+        }
+
+        let events = CoinCatchProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades.len(), 2);
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[0].price, 64011.9);
+                assert_eq!(trades[1].side, TradeSide::Buy);
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        assert!(matches!(&CoinCatchProtocol::parse_message("pong").unwrap()[..], [MarketEvent::Pong]));
+        assert!(matches!(&CoinCatchProtocol::parse_message(RECORDED_SUBSCRIBE_ACK_FRAME).unwrap()[..], [MarketEvent::Other(_)]));
+        assert!(matches!(&CoinCatchProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_coincatch_symbol_conversion() {
+        let connector = CoinCatchConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        for symbol in ["BTCUSDT", "btc_usdt", "COINCATCH:BTC-USDT"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "BTCUSDT");
+        }
+    }
+
+    #[test]
+    fn test_coincatch_batched_subscription() {
+        let connector = CoinCatchConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        let symbols: Vec<String> = ["BTC", "ETH", "SOL", "XRP", "DOGE", "ADA"].iter()
+            .map(|base| format!("{base}USDT"))
+            .collect();
+
+        let requests = connector.handler().subscription_requests(true, MarketChannel::OrderBook, &symbols, connector.handler().batch_size());
+        assert_eq!(requests.len(), 2);
+
+        let first: Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(first["op"], "subscribe");
+        assert_eq!(first["args"].as_array().unwrap().len(), 5);
+        assert_eq!(first["args"][0]["channel"], "books15");
+        assert_eq!(first["args"][0]["instType"], "mc");
+        assert_eq!(first["args"][0]["instId"], "BTCUSDT");
+
+        let unsubscribe: Value = serde_json::from_str(&CoinCatchProtocol::subscription_message(false, MarketChannel::Trades, &symbols[..1], 0)).unwrap();
+        assert_eq!(unsubscribe["op"], "unsubscribe");
+        assert_eq!(unsubscribe["args"][0]["channel"], "trade");
+    }
+}
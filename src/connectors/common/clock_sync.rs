@@ -0,0 +1,327 @@
+//! 交易所服务器时间同步
+//! 定期请求各交易所的服务器时间，按NTP的方式估计本地时钟偏移和往返延迟：
+//! `偏移 = 服务器时间 - (发送时间 + 接收时间) / 2`，保留最近的样本并采用往返延迟最小的一个。
+//!
+//! 签名请求使用 `ExchangeClock::now_ms` 作为时间戳，避免本地时钟漂移导致的 -1021 错误；
+//! 行情事件时间通过同一偏移换算成本地时间后再计算延迟。
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use log::{debug, info, warn};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::config::ClockSyncConfig;
+use crate::exchange_types::Exchange;
+use crate::types::errors::ConnectorError;
+
+/// 每个交易所保留的样本数
+const MAX_SAMPLES: usize = 16;
+
+/// 服务器时间来源，通常是交易所REST客户端
+#[async_trait]
+pub trait ServerTimeSource: Send + Sync {
+    /// 服务器当前时间（毫秒时间戳）
+    async fn server_time_ms(&self) -> Result<i64, ConnectorError>;
+}
+
+/// 一次时间同步样本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    /// 服务器时间减本地时间（毫秒）
+    pub offset_ms: i64,
+    /// 请求往返延迟（毫秒）
+    pub rtt_ms: i64,
+    /// 采样时的本地时间（毫秒时间戳）
+    pub sampled_at_ms: i64,
+}
+
+/// 单个交易所的时钟，可在REST客户端和行情处理之间共享
+#[derive(Debug)]
+pub struct ExchangeClock {
+    exchange: Exchange,
+    recv_window_ms: u64,
+    offset_ms: AtomicI64,
+    synced: AtomicBool,
+    samples: Mutex<VecDeque<ClockSample>>,
+}
+
+impl ExchangeClock {
+    /// 创建未同步的时钟，同步前偏移为0（即使用本地时间）
+    pub fn new(exchange: Exchange, recv_window_ms: u64) -> Self {
+        Self {
+            exchange,
+            recv_window_ms,
+            offset_ms: AtomicI64::new(0),
+            synced: AtomicBool::new(false),
+            samples: Mutex::new(VecDeque::with_capacity(MAX_SAMPLES)),
+        }
+    }
+
+    pub fn exchange(&self) -> Exchange {
+        self.exchange
+    }
+
+    /// 签名请求携带的 `recvWindow`（毫秒）
+    pub fn recv_window_ms(&self) -> u64 {
+        self.recv_window_ms
+    }
+
+    /// 当前估计的偏移（服务器时间减本地时间，毫秒）
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// 当前采用的样本
+    pub fn best_sample(&self) -> Option<ClockSample> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples.iter().min_by_key(|sample| (sample.rtt_ms, -sample.sampled_at_ms)).copied()
+    }
+
+    /// 是否至少成功同步过一次
+    pub fn is_synced(&self) -> bool {
+        self.synced.load(Ordering::Relaxed)
+    }
+
+    /// 按服务器时钟的当前时间（毫秒时间戳），用作签名请求的timestamp
+    pub fn now_ms(&self) -> i64 {
+        local_now_us() / 1000 + self.offset_ms()
+    }
+
+    /// 把服务器时间换算成本地时间
+    pub fn to_local_ms(&self, server_time_ms: i64) -> i64 {
+        server_time_ms - self.offset_ms()
+    }
+
+    /// 交易所事件从产生到本地处理的延迟（毫秒）
+    pub fn latency_ms(&self, event_time_ms: i64) -> i64 {
+        local_now_us() / 1000 - self.to_local_ms(event_time_ms)
+    }
+
+    /// 记录一次样本：`sent_us`/`received_us` 为请求前后的本地时间（微秒）
+    pub fn record_sample(&self, sent_us: i64, server_time_ms: i64, received_us: i64) -> ClockSample {
+        let midpoint_us = sent_us + (received_us - sent_us) / 2;
+        let sample = ClockSample {
+            offset_ms: (server_time_ms * 1000 - midpoint_us) / 1000,
+            rtt_ms: (received_us - sent_us).max(0) / 1000,
+            sampled_at_ms: received_us / 1000,
+        };
+
+        let best = {
+            let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+            if samples.len() >= MAX_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+            // 往返延迟最小的样本误差最小，延迟相同时取较新的
+            samples.iter().min_by_key(|sample| (sample.rtt_ms, -sample.sampled_at_ms)).copied().unwrap_or(sample)
+        };
+
+        self.offset_ms.store(best.offset_ms, Ordering::Relaxed);
+        self.synced.store(true, Ordering::Relaxed);
+        sample
+    }
+
+    /// 向时间来源请求一次服务器时间并记录样本
+    pub async fn sample(&self, source: &dyn ServerTimeSource) -> Result<ClockSample, ConnectorError> {
+        let sent_us = local_now_us();
+        let server_time_ms = source.server_time_ms().await?;
+        let received_us = local_now_us();
+        Ok(self.record_sample(sent_us, server_time_ms, received_us))
+    }
+}
+
+/// 注册到同步服务的时钟及其时间来源
+#[derive(Clone)]
+pub struct ClockSource {
+    pub clock: Arc<ExchangeClock>,
+    pub source: Arc<dyn ServerTimeSource>,
+}
+
+/// 后台时间同步服务
+pub struct ClockSyncService {
+    config: ClockSyncConfig,
+    sources: RwLock<Vec<ClockSource>>,
+}
+
+impl ClockSyncService {
+    pub fn new(config: ClockSyncConfig) -> Self {
+        Self {
+            config,
+            sources: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 注册一个交易所时钟，同一交易所只保留最后一次注册
+    pub fn register(&self, source: ClockSource) {
+        let mut sources = self.sources.write().unwrap_or_else(|e| e.into_inner());
+        sources.retain(|registered| registered.clock.exchange() != source.clock.exchange());
+        sources.push(source);
+    }
+
+    /// 某交易所的时钟
+    pub fn clock(&self, exchange: Exchange) -> Option<Arc<ExchangeClock>> {
+        self.sources
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|source| source.clock.exchange() == exchange)
+            .map(|source| source.clock.clone())
+    }
+
+    /// 已注册的交易所数量
+    pub fn len(&self) -> usize {
+        self.sources.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 对所有交易所各采样 `samples_per_sync` 次，交易所之间并行
+    ///
+    /// 返回成功同步的交易所数量；单个交易所失败只记录日志。
+    pub async fn sync_once(&self) -> usize {
+        let sources = self.sources.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let samples_per_sync = self.config.samples_per_sync.max(1);
+
+        let results = join_all(sources.iter().map(|source| async move {
+            let mut last_error = None;
+            let mut sampled = 0;
+            for _ in 0..samples_per_sync {
+                match source.clock.sample(source.source.as_ref()).await {
+                    Ok(sample) => {
+                        sampled += 1;
+                        debug!("[ClockSync] {} 样本: 偏移 {}ms, RTT {}ms", source.clock.exchange(), sample.offset_ms, sample.rtt_ms);
+                    }
+                    Err(e) => last_error = Some(e),
+                }
+            }
+            (source.clock.clone(), sampled, last_error)
+        }))
+        .await;
+
+        let mut synced = 0;
+        for (clock, sampled, last_error) in results {
+            if sampled == 0 {
+                if let Some(e) = last_error {
+                    warn!("[ClockSync] {} 获取服务器时间失败: {e}", clock.exchange());
+                }
+                continue;
+            }
+
+            synced += 1;
+            let offset_ms = clock.offset_ms();
+            let rtt_ms = clock.best_sample().map_or(0, |sample| sample.rtt_ms);
+            if offset_ms.abs() > self.config.offset_warn_ms {
+                warn!("[ClockSync] {} 本地时钟偏移 {offset_ms}ms (RTT {rtt_ms}ms)，签名时间戳已校正", clock.exchange());
+            } else {
+                debug!("[ClockSync] {} 时钟偏移 {offset_ms}ms (RTT {rtt_ms}ms)", clock.exchange());
+            }
+        }
+        synced
+    }
+
+    /// 启动后台同步任务：立即同步一次，之后每 `interval_secs` 秒同步一次
+    pub fn start(self: &Arc<Self>) -> JoinHandle<()> {
+        let service = self.clone();
+        let interval = Duration::from_secs(self.config.interval_secs.max(1));
+        info!("[ClockSync] 启动时间同步服务: {} 个交易所, 间隔 {interval:?}", self.len());
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                service.sync_once().await;
+            }
+        })
+    }
+}
+
+fn local_now_us() -> i64 {
+    chrono::Utc::now().timestamp_micros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// 模拟服务器时钟：比本地快 `skew_ms`，每次请求耗时 `delay`
+    struct SkewedSource {
+        skew_ms: i64,
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ServerTimeSource for SkewedSource {
+        async fn server_time_ms(&self) -> Result<i64, ConnectorError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(self.delay / 2).await;
+            let server_time = local_now_us() / 1000 + self.skew_ms;
+            tokio::time::sleep(self.delay / 2).await;
+            Ok(server_time)
+        }
+    }
+
+    struct FailingSource;
+
+    #[async_trait]
+    impl ServerTimeSource for FailingSource {
+        async fn server_time_ms(&self) -> Result<i64, ConnectorError> {
+            Err(ConnectorError::NetworkError("connection refused".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_lowest_rtt_sample_wins() {
+        let clock = ExchangeClock::new(Exchange::BinanceFutures, 5000);
+        assert!(!clock.is_synced());
+        assert_eq!(clock.offset_ms(), 0);
+
+        // 本地在1_000_000发出、1_000_400收到，服务器回报1_000_700 -> 偏移500ms，RTT 400ms
+        let sample = clock.record_sample(1_000_000_000, 1_000_700, 1_000_400_000);
+        assert_eq!(sample, ClockSample { offset_ms: 500, rtt_ms: 400, sampled_at_ms: 1_000_400 });
+        assert_eq!(clock.offset_ms(), 500);
+
+        // 往返更快的样本更准确
+        clock.record_sample(2_000_000_000, 2_000_530, 2_000_040_000);
+        assert_eq!(clock.offset_ms(), 510);
+        // 往返更慢的样本不会覆盖
+        clock.record_sample(3_000_000_000, 3_002_000, 3_001_000_000);
+        assert_eq!(clock.offset_ms(), 510);
+        assert_eq!(clock.best_sample().unwrap().rtt_ms, 40);
+
+        assert!(clock.is_synced());
+        assert_eq!(clock.to_local_ms(10_510), 10_000);
+    }
+
+    #[tokio::test]
+    async fn test_service_corrects_skewed_clock() {
+        let source = Arc::new(SkewedSource { skew_ms: -1500, delay: Duration::from_millis(10), calls: AtomicUsize::new(0) });
+        let clock = Arc::new(ExchangeClock::new(Exchange::BybitFutures, 5000));
+
+        let service = ClockSyncService::new(ClockSyncConfig { samples_per_sync: 3, ..ClockSyncConfig::default() });
+        service.register(ClockSource { clock: clock.clone(), source: source.clone() });
+        service.register(ClockSource {
+            clock: Arc::new(ExchangeClock::new(Exchange::OkxFutures, 5000)),
+            source: Arc::new(FailingSource),
+        });
+        assert_eq!(service.len(), 2);
+
+        assert_eq!(service.sync_once().await, 1);
+        assert_eq!(source.calls.load(Ordering::Relaxed), 3);
+        assert!((clock.offset_ms() + 1500).abs() <= 5, "偏移估计错误: {}", clock.offset_ms());
+        assert!(!service.clock(Exchange::OkxFutures).unwrap().is_synced());
+
+        // 服务器时间换算回本地后，刚产生的事件延迟接近0
+        let event_time = local_now_us() / 1000 - 1500;
+        assert!(clock.latency_ms(event_time).abs() <= 5);
+        assert!((clock.now_ms() - (local_now_us() / 1000 - 1500)).abs() <= 5);
+    }
+}
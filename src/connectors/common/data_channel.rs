@@ -0,0 +1,499 @@
+//! 有界数据通道
+//! 连接器推送行情/用户数据使用的有界队列，满载时按 `ChannelPolicy` 处理：
+//! 丢弃最旧消息、按交易对合并订单簿，或者阻塞生产者等待消费。
+//! 丢弃和合并的数量会记录下来，通过 `DataFlowStats` 对外暴露。
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+use crate::types::config::{ChannelPolicy, DataChannelConfig};
+use crate::types::events::{DataFlowStats, HighFrequencyData};
+use crate::types::market_data::StandardizedMessage;
+
+/// 可合并消息的键
+///
+/// 返回Some的消息在 `ConflateLatest` 策略下只保留同一键的最新一条，
+/// 返回None的消息（成交、用户数据等）不会被合并。
+pub trait ConflationKey {
+    fn conflation_key(&self) -> Option<String>;
+}
+
+impl ConflationKey for StandardizedMessage {
+    fn conflation_key(&self) -> Option<String> {
+        match self {
+            StandardizedMessage::OrderBookUpdate(orderbook) => Some(format!("book:{}:{}", orderbook.exchange, orderbook.symbol)),
+            StandardizedMessage::TickerUpdate(ticker) => Some(format!("ticker:{}:{}", ticker.exchange, ticker.symbol)),
+            _ => None,
+        }
+    }
+}
+
+impl ConflationKey for HighFrequencyData {
+    fn conflation_key(&self) -> Option<String> {
+        match self {
+            HighFrequencyData::OrderBookUpdate { orderbook, market_type, .. } => {
+                Some(format!("book:{}:{:?}:{}", orderbook.exchange, market_type, orderbook.symbol))
+            }
+            HighFrequencyData::DepthChange { exchange, market_type, symbol, .. } => {
+                Some(format!("depth:{exchange:?}:{market_type:?}:{symbol}"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 发送失败原因，携带未能发送的消息
+#[derive(PartialEq, Eq)]
+pub enum ChannelSendError<T> {
+    /// 接收端已关闭
+    Closed(T),
+    /// 队列已满（仅 `Block` 策略的非阻塞发送）
+    Full(T),
+}
+
+impl<T> ChannelSendError<T> {
+    /// 取回未能发送的消息
+    pub fn into_inner(self) -> T {
+        match self {
+            ChannelSendError::Closed(value) | ChannelSendError::Full(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ChannelSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelSendError::Closed(_) => write!(f, "Closed(..)"),
+            ChannelSendError::Full(_) => write!(f, "Full(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for ChannelSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelSendError::Closed(_) => write!(f, "数据通道接收端已关闭"),
+            ChannelSendError::Full(_) => write!(f, "数据通道已满"),
+        }
+    }
+}
+
+impl<T> std::error::Error for ChannelSendError<T> {}
+
+/// 通道计数快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChannelStats {
+    /// 成功写入的消息数
+    pub sent: u64,
+    /// 被丢弃的消息数（包含被合并的消息）
+    pub dropped: u64,
+    /// 被同一键的新消息合并掉的消息数
+    pub conflated: u64,
+    /// 当前排队的消息数
+    pub queued: usize,
+    pub capacity: usize,
+}
+
+impl ChannelStats {
+    /// 把通道计数写入数据流统计
+    pub fn apply_to(&self, stats: &mut DataFlowStats) {
+        stats.total_messages += self.sent;
+        stats.dropped_messages += self.dropped;
+        stats.conflated_messages += self.conflated;
+        stats.queued_messages += self.queued;
+    }
+}
+
+/// 队列中的一项：普通消息直接存放，可合并消息只存放键，值在 `latest` 中
+enum Slot<T> {
+    Value(T),
+    Keyed(String),
+}
+
+struct State<T> {
+    queue: VecDeque<Slot<T>>,
+    latest: HashMap<String, T>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    capacity: usize,
+    policy: ChannelPolicy,
+    /// 有新消息或发送端全部关闭时唤醒接收端
+    recv_notify: Notify,
+    /// 队列腾出空间或接收端关闭时唤醒阻塞的发送端
+    send_notify: Notify,
+    senders: AtomicUsize,
+    receiver_closed: AtomicBool,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    conflated: AtomicU64,
+}
+
+impl<T> Shared<T> {
+    fn stats(&self) -> ChannelStats {
+        let queued = self.state.lock().map(|state| state.queue.len()).unwrap_or(0);
+        ChannelStats {
+            sent: self.sent.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            conflated: self.conflated.load(Ordering::Relaxed),
+            queued,
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// 创建有界数据通道
+pub fn data_channel<T: ConflationKey>(config: DataChannelConfig) -> (DataSender<T>, DataReceiver<T>) {
+    let capacity = config.capacity.max(1);
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(capacity.min(1024)),
+            latest: HashMap::new(),
+        }),
+        capacity,
+        policy: config.policy,
+        recv_notify: Notify::new(),
+        send_notify: Notify::new(),
+        senders: AtomicUsize::new(1),
+        receiver_closed: AtomicBool::new(false),
+        sent: AtomicU64::new(0),
+        dropped: AtomicU64::new(0),
+        conflated: AtomicU64::new(0),
+    });
+
+    (DataSender { shared: shared.clone() }, DataReceiver { shared })
+}
+
+/// 有界数据通道发送端
+pub struct DataSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for DataSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for DataSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.recv_notify.notify_one();
+        }
+    }
+}
+
+impl<T: ConflationKey> DataSender<T> {
+    /// 非阻塞发送
+    ///
+    /// `DropOldest` / `ConflateLatest` 策略总是成功（必要时丢弃旧消息），
+    /// `Block` 策略在队列已满时返回 `Full`。
+    pub fn try_send(&self, value: T) -> Result<(), ChannelSendError<T>> {
+        if self.is_closed() {
+            return Err(ChannelSendError::Closed(value));
+        }
+
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if self.shared.policy == ChannelPolicy::ConflateLatest {
+            if let Some(key) = value.conflation_key() {
+                if let Some(existing) = state.latest.get_mut(&key) {
+                    // 队列中已有该交易对的消息，原位替换为最新值
+                    *existing = value;
+                    self.shared.conflated.fetch_add(1, Ordering::Relaxed);
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    self.shared.sent.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                if !self.make_room(&mut state) {
+                    return Err(ChannelSendError::Full(value));
+                }
+                state.queue.push_back(Slot::Keyed(key.clone()));
+                state.latest.insert(key, value);
+                return self.finish_send(state);
+            }
+        }
+
+        if !self.make_room(&mut state) {
+            return Err(ChannelSendError::Full(value));
+        }
+        state.queue.push_back(Slot::Value(value));
+        self.finish_send(state)
+    }
+
+    /// 发送消息，`Block` 策略在队列已满时等待消费者腾出空间
+    pub async fn send(&self, value: T) -> Result<(), ChannelSendError<T>> {
+        let mut value = value;
+        loop {
+            let notified = self.shared.send_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.try_send(value) {
+                Err(ChannelSendError::Full(returned)) => {
+                    value = returned;
+                    notified.await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 接收端是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.shared.receiver_closed.load(Ordering::Acquire)
+    }
+
+    /// 通道计数快照
+    pub fn stats(&self) -> ChannelStats {
+        self.shared.stats()
+    }
+
+    /// 为新消息腾出空间；`Block` 策略下队列已满时返回false
+    fn make_room(&self, state: &mut State<T>) -> bool {
+        while state.queue.len() >= self.shared.capacity {
+            if self.shared.policy == ChannelPolicy::Block {
+                return false;
+            }
+            if let Some(Slot::Keyed(key)) = state.queue.pop_front() {
+                state.latest.remove(&key);
+            }
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        true
+    }
+
+    fn finish_send(&self, state: std::sync::MutexGuard<'_, State<T>>) -> Result<(), ChannelSendError<T>> {
+        drop(state);
+        self.shared.sent.fetch_add(1, Ordering::Relaxed);
+        self.shared.recv_notify.notify_one();
+        Ok(())
+    }
+}
+
+/// 有界数据通道接收端
+pub struct DataReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Drop for DataReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        self.shared.send_notify.notify_waiters();
+    }
+}
+
+impl<T> DataReceiver<T> {
+    /// 接收下一条消息，所有发送端关闭且队列为空时返回None
+    pub async fn recv(&mut self) -> Option<T> {
+        let shared = self.shared.clone();
+        loop {
+            let notified = shared.recv_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.try_recv() {
+                return Some(value);
+            }
+            if shared.senders.load(Ordering::Acquire) == 0 {
+                // 最后一个发送端关闭前可能刚写入消息
+                return self.try_recv();
+            }
+            notified.await;
+        }
+    }
+
+    /// 非阻塞接收
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        let value = match state.queue.pop_front()? {
+            Slot::Value(value) => value,
+            Slot::Keyed(key) => state.latest.remove(&key)?,
+        };
+        drop(state);
+
+        self.shared.send_notify.notify_one();
+        Some(value)
+    }
+
+    /// 当前排队的消息数
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().map(|state| state.queue.len()).unwrap_or(0)
+    }
+
+    /// 队列是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 通道使用的满载策略
+    pub fn policy(&self) -> ChannelPolicy {
+        self.shared.policy
+    }
+
+    /// 通道计数快照
+    pub fn stats(&self) -> ChannelStats {
+        self.shared.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange_types::{Exchange, StandardOrderBook};
+    use crate::types::exchange::ExchangeType;
+    use crate::types::market_data::{StandardizedTrade, TradeSide};
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn book(symbol: &str, best_bid: f64) -> StandardizedMessage {
+        StandardizedMessage::OrderBookUpdate(StandardOrderBook::new_minimal(symbol, Exchange::Phemex, best_bid, best_bid + 1.0, 0))
+    }
+
+    fn trade(id: u32) -> StandardizedMessage {
+        StandardizedMessage::TradeUpdate(StandardizedTrade {
+            symbol: "BTCUSDT".to_string(),
+            exchange: ExchangeType::Phemex,
+            price: 100.0,
+            quantity: 1.0,
+            side: TradeSide::Buy,
+            timestamp: 0,
+            trade_id: id.to_string(),
+        })
+    }
+
+    fn config(capacity: usize, policy: ChannelPolicy) -> DataChannelConfig {
+        DataChannelConfig { capacity, policy }
+    }
+
+    fn trade_id(message: Option<StandardizedMessage>) -> String {
+        match message {
+            Some(StandardizedMessage::TradeUpdate(trade)) => trade.trade_id,
+            other => panic!("收到意外的消息: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_drop_oldest_evicts_front() {
+        let (sender, mut receiver) = data_channel(config(2, ChannelPolicy::DropOldest));
+        for id in 1..=3 {
+            sender.try_send(trade(id)).unwrap();
+        }
+
+        assert_eq!(trade_id(receiver.try_recv()), "2");
+        assert_eq!(trade_id(receiver.try_recv()), "3");
+        assert!(receiver.try_recv().is_none());
+
+        let stats = receiver.stats();
+        assert_eq!(stats.sent, 3);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.conflated, 0);
+    }
+
+    #[test]
+    fn test_conflate_latest_keeps_position_and_latest_book() {
+        let (sender, mut receiver) = data_channel(config(10, ChannelPolicy::ConflateLatest));
+        sender.try_send(book("BTCUSDT", 100.0)).unwrap();
+        sender.try_send(trade(1)).unwrap();
+        sender.try_send(book("ETHUSDT", 10.0)).unwrap();
+        sender.try_send(book("BTCUSDT", 101.0)).unwrap();
+        sender.try_send(trade(2)).unwrap();
+
+        // BTCUSDT保留在原来的位置，但内容是最新的
+        match receiver.try_recv() {
+            Some(StandardizedMessage::OrderBookUpdate(orderbook)) => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 101.0);
+            }
+            other => panic!("收到意外的消息: {other:?}"),
+        }
+        assert_eq!(trade_id(receiver.try_recv()), "1");
+        assert!(matches!(receiver.try_recv(), Some(StandardizedMessage::OrderBookUpdate(ob)) if ob.symbol == "ETHUSDT"));
+        assert_eq!(trade_id(receiver.try_recv()), "2");
+        assert!(receiver.is_empty());
+
+        let stats = sender.stats();
+        assert_eq!(stats.sent, 5);
+        assert_eq!(stats.conflated, 1);
+        assert_eq!(stats.dropped, 1);
+    }
+
+    #[test]
+    fn test_conflate_latest_evicts_when_full() {
+        let (sender, mut receiver) = data_channel(config(2, ChannelPolicy::ConflateLatest));
+        sender.try_send(book("BTCUSDT", 100.0)).unwrap();
+        sender.try_send(book("ETHUSDT", 10.0)).unwrap();
+        sender.try_send(book("SOLUSDT", 1.0)).unwrap();
+
+        // 最旧的BTCUSDT被挤出，之后的BTCUSDT不应再被合并到已删除的条目
+        sender.try_send(book("BTCUSDT", 102.0)).unwrap();
+        let symbols: Vec<String> = std::iter::from_fn(|| receiver.try_recv())
+            .map(|message| match message {
+                StandardizedMessage::OrderBookUpdate(orderbook) => orderbook.symbol,
+                other => panic!("收到意外的消息: {other:?}"),
+            })
+            .collect();
+        assert_eq!(symbols, vec!["SOLUSDT", "BTCUSDT"]);
+        assert_eq!(receiver.stats().dropped, 2);
+        assert_eq!(receiver.stats().conflated, 0);
+    }
+
+    #[tokio::test]
+    async fn test_block_policy_applies_backpressure() {
+        let (sender, mut receiver) = data_channel(config(1, ChannelPolicy::Block));
+        sender.send(trade(1)).await.unwrap();
+        assert!(matches!(sender.try_send(trade(2)), Err(ChannelSendError::Full(_))));
+
+        let blocked = sender.clone();
+        let pending = tokio::spawn(async move { blocked.send(trade(3)).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!pending.is_finished());
+
+        assert_eq!(trade_id(receiver.recv().await), "1");
+        timeout(Duration::from_secs(1), pending).await.unwrap().unwrap().unwrap();
+        assert_eq!(trade_id(receiver.recv().await), "3");
+
+        let stats = receiver.stats();
+        assert_eq!(stats.sent, 2);
+        assert_eq!(stats.dropped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_close_semantics() {
+        let (sender, mut receiver) = data_channel::<StandardizedMessage>(config(4, ChannelPolicy::DropOldest));
+        sender.try_send(trade(1)).unwrap();
+        drop(sender);
+
+        // 发送端全部关闭后仍能取完剩余消息
+        assert_eq!(trade_id(receiver.recv().await), "1");
+        assert!(timeout(Duration::from_secs(1), receiver.recv()).await.unwrap().is_none());
+
+        let (sender, receiver) = data_channel(config(1, ChannelPolicy::Block));
+        sender.try_send(trade(1)).unwrap();
+        let blocked = sender.clone();
+        let pending = tokio::spawn(async move { blocked.send(trade(2)).await });
+        drop(receiver);
+
+        let result = timeout(Duration::from_secs(1), pending).await.unwrap().unwrap();
+        assert!(matches!(result, Err(ChannelSendError::Closed(_))));
+        assert!(sender.is_closed());
+    }
+
+    #[test]
+    fn test_stats_apply_to_data_flow_stats() {
+        let (sender, _receiver) = data_channel(config(1, ChannelPolicy::DropOldest));
+        sender.try_send(trade(1)).unwrap();
+        sender.try_send(trade(2)).unwrap();
+
+        let mut stats = DataFlowStats::default();
+        sender.stats().apply_to(&mut stats);
+        assert_eq!(stats.total_messages, 2);
+        assert_eq!(stats.dropped_messages, 1);
+        assert_eq!(stats.queued_messages, 1);
+    }
+}
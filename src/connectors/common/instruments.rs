@@ -0,0 +1,653 @@
+//! 交易品种元数据
+//! 从各交易所的 exchangeInfo / instruments 接口加载价格步长、数量步长、最小名义价值和合约乘数，
+//! 下单前据此取整和校验，避免因精度或最小下单量被交易所拒单。
+//!
+//! 价格和数量在内部使用 `Decimal` 计算，避免浮点取整误差；对外接口沿用订单中的 `f64`。
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use log::{debug, info, warn};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::exchange_types::Exchange;
+use crate::symbol_mapper::contract_multipliers_mut;
+use crate::types::errors::ConnectorError;
+use crate::types::orders::{OrderRequest, OrderType};
+
+/// 交易品种状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstrumentStatus {
+    /// 正常交易
+    Trading,
+    /// 尚未开盘
+    PreTrading,
+    /// 暂停交易、交割或结算中
+    Halted,
+    /// 已下架
+    Delisted,
+}
+
+impl InstrumentStatus {
+    /// 把交易所返回的状态映射为统一状态，无法识别的状态按暂停交易处理
+    pub fn from_exchange(status: &str) -> Self {
+        match status.to_ascii_uppercase().as_str() {
+            "TRADING" | "LIVE" => InstrumentStatus::Trading,
+            "PENDING_TRADING" | "PRE_TRADING" | "PRELAUNCH" | "PREOPEN" => InstrumentStatus::PreTrading,
+            "CLOSE" | "CLOSED" | "DELISTED" | "EXPIRED" => InstrumentStatus::Delisted,
+            _ => InstrumentStatus::Halted,
+        }
+    }
+}
+
+impl fmt::Display for InstrumentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstrumentStatus::Trading => write!(f, "TRADING"),
+            InstrumentStatus::PreTrading => write!(f, "PRE_TRADING"),
+            InstrumentStatus::Halted => write!(f, "HALTED"),
+            InstrumentStatus::Delisted => write!(f, "DELISTED"),
+        }
+    }
+}
+
+/// 价格取整方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceRounding {
+    /// 向下取整（买单不会因取整变得更激进）
+    Down,
+    /// 向上取整（卖单不会因取整变得更激进）
+    Up,
+    /// 取最近的价格档位
+    Nearest,
+}
+
+/// 单个交易品种的交易规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentSpec {
+    pub exchange: Exchange,
+    /// 统一格式的交易对，例如 BTCUSDT
+    pub symbol: String,
+    /// 交易所原始代码，例如 BTC-USDT-SWAP
+    pub exchange_symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub status: InstrumentStatus,
+    /// 价格步长（PRICE_FILTER.tickSize）
+    pub tick_size: Decimal,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    /// 数量步长（LOT_SIZE.stepSize）
+    pub step_size: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Option<Decimal>,
+    /// 市价单的数量限制（MARKET_LOT_SIZE），未提供时沿用限价单的限制
+    pub market_step_size: Option<Decimal>,
+    pub market_min_qty: Option<Decimal>,
+    pub market_max_qty: Option<Decimal>,
+    /// 最小名义价值（计价货币）
+    pub min_notional: Option<Decimal>,
+    /// 每单位下单数量对应的基础资产数量：现货和U本位合约为1，OKX合约为面值 `ctVal * ctMult`
+    pub contract_multiplier: Decimal,
+}
+
+impl InstrumentSpec {
+    /// 只设置步长的交易规则，其余限制为空
+    pub fn new(exchange: Exchange, exchange_symbol: &str, tick_size: Decimal, step_size: Decimal) -> Self {
+        Self {
+            exchange,
+            symbol: instrument_key(exchange_symbol),
+            exchange_symbol: exchange_symbol.to_string(),
+            base_asset: String::new(),
+            quote_asset: String::new(),
+            status: InstrumentStatus::Trading,
+            tick_size,
+            min_price: None,
+            max_price: None,
+            step_size,
+            min_qty: Decimal::ZERO,
+            max_qty: None,
+            market_step_size: None,
+            market_min_qty: None,
+            market_max_qty: None,
+            min_notional: None,
+            contract_multiplier: Decimal::ONE,
+        }
+    }
+
+    pub fn is_trading(&self) -> bool {
+        self.status == InstrumentStatus::Trading
+    }
+
+    /// 价格小数位数
+    pub fn price_precision(&self) -> u32 {
+        self.tick_size.normalize().scale()
+    }
+
+    /// 数量小数位数
+    pub fn quantity_precision(&self) -> u32 {
+        self.step_size.normalize().scale()
+    }
+
+    /// 按价格步长取整
+    pub fn round_price(&self, price: f64, rounding: PriceRounding) -> f64 {
+        let Some(value) = to_decimal(price) else { return price };
+        let strategy = match rounding {
+            PriceRounding::Down => RoundingStrategy::ToNegativeInfinity,
+            PriceRounding::Up => RoundingStrategy::ToPositiveInfinity,
+            PriceRounding::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        };
+        to_f64(round_to_step(value, self.tick_size, strategy))
+    }
+
+    /// 按数量步长向下取整，取整后不会超过原数量
+    pub fn round_qty(&self, quantity: f64) -> f64 {
+        self.round_qty_with_step(quantity, self.step_size)
+    }
+
+    /// 按市价单的数量步长向下取整
+    pub fn round_market_qty(&self, quantity: f64) -> f64 {
+        self.round_qty_with_step(quantity, self.market_step_size.unwrap_or(self.step_size))
+    }
+
+    /// 价格是否落在价格档位上
+    pub fn is_price_on_tick(&self, price: Decimal) -> bool {
+        is_multiple_of(price, self.tick_size)
+    }
+
+    /// 数量是否是数量步长的整数倍
+    pub fn is_qty_on_step(&self, quantity: Decimal) -> bool {
+        is_multiple_of(quantity, self.step_size)
+    }
+
+    /// 订单数量对应的基础资产数量
+    pub fn base_quantity(&self, quantity: f64) -> f64 {
+        to_decimal(quantity).map_or(quantity, |quantity| to_f64(quantity * self.contract_multiplier))
+    }
+
+    /// 订单的名义价值（计价货币）
+    pub fn notional(&self, price: f64, quantity: f64) -> f64 {
+        price * self.base_quantity(quantity)
+    }
+
+    /// 校验订单是否满足交易规则
+    ///
+    /// 市价单没有价格时跳过价格和最小名义价值检查。
+    pub fn validate(&self, price: Option<f64>, quantity: f64, is_market: bool) -> Result<(), InstrumentError> {
+        if !self.is_trading() {
+            return Err(InstrumentError::NotTrading { symbol: self.symbol.clone(), status: self.status });
+        }
+
+        let quantity = to_decimal(quantity).ok_or(InstrumentError::InvalidNumber(quantity))?;
+        let (step_size, min_qty, max_qty) = if is_market {
+            (
+                self.market_step_size.unwrap_or(self.step_size),
+                self.market_min_qty.unwrap_or(self.min_qty),
+                self.market_max_qty.or(self.max_qty),
+            )
+        } else {
+            (self.step_size, self.min_qty, self.max_qty)
+        };
+        if quantity <= Decimal::ZERO || quantity < min_qty || max_qty.is_some_and(|max| quantity > max) {
+            return Err(InstrumentError::QuantityOutOfRange { quantity, min: min_qty, max: max_qty });
+        }
+        if !is_multiple_of(quantity, step_size) {
+            return Err(InstrumentError::QuantityNotOnStep { quantity, step_size });
+        }
+
+        let Some(price) = price else { return Ok(()) };
+        let price = to_decimal(price).ok_or(InstrumentError::InvalidNumber(price))?;
+        if !is_market {
+            if price <= Decimal::ZERO
+                || self.min_price.is_some_and(|min| price < min)
+                || self.max_price.is_some_and(|max| price > max)
+            {
+                return Err(InstrumentError::PriceOutOfRange { price, min: self.min_price, max: self.max_price });
+            }
+            if !self.is_price_on_tick(price) {
+                return Err(InstrumentError::PriceNotOnTick { price, tick_size: self.tick_size });
+            }
+        }
+
+        if let Some(min_notional) = self.min_notional {
+            let notional = price * quantity * self.contract_multiplier;
+            if notional < min_notional {
+                return Err(InstrumentError::BelowMinNotional { notional, min_notional });
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验标准订单请求
+    pub fn validate_order(&self, order: &OrderRequest) -> Result<(), InstrumentError> {
+        let is_market = matches!(order.order_type, OrderType::Market | OrderType::StopMarket);
+        self.validate(order.price, order.quantity, is_market)
+    }
+
+    fn round_qty_with_step(&self, quantity: f64, step_size: Decimal) -> f64 {
+        let Some(value) = to_decimal(quantity) else { return quantity };
+        to_f64(round_to_step(value, step_size, RoundingStrategy::ToZero))
+    }
+}
+
+/// 订单不满足交易规则的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstrumentError {
+    /// 没有该交易品种的元数据
+    UnknownInstrument { exchange: Exchange, symbol: String },
+    /// 交易品种当前不可交易
+    NotTrading { symbol: String, status: InstrumentStatus },
+    /// 价格或数量不是有限数
+    InvalidNumber(f64),
+    PriceOutOfRange { price: Decimal, min: Option<Decimal>, max: Option<Decimal> },
+    PriceNotOnTick { price: Decimal, tick_size: Decimal },
+    QuantityOutOfRange { quantity: Decimal, min: Decimal, max: Option<Decimal> },
+    QuantityNotOnStep { quantity: Decimal, step_size: Decimal },
+    BelowMinNotional { notional: Decimal, min_notional: Decimal },
+}
+
+impl fmt::Display for InstrumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstrumentError::UnknownInstrument { exchange, symbol } => write!(f, "{exchange} 没有 {symbol} 的交易规则"),
+            InstrumentError::NotTrading { symbol, status } => write!(f, "{symbol} 当前不可交易 ({status})"),
+            InstrumentError::InvalidNumber(value) => write!(f, "无效的数值: {value}"),
+            InstrumentError::PriceOutOfRange { price, min, max } => {
+                write!(f, "价格 {price} 超出范围 [{}, {}]", display_limit(min), display_limit(max))
+            }
+            InstrumentError::PriceNotOnTick { price, tick_size } => write!(f, "价格 {price} 不是价格步长 {tick_size} 的整数倍"),
+            InstrumentError::QuantityOutOfRange { quantity, min, max } => {
+                write!(f, "数量 {quantity} 超出范围 [{min}, {}]", display_limit(max))
+            }
+            InstrumentError::QuantityNotOnStep { quantity, step_size } => write!(f, "数量 {quantity} 不是数量步长 {step_size} 的整数倍"),
+            InstrumentError::BelowMinNotional { notional, min_notional } => write!(f, "名义价值 {notional} 低于最小值 {min_notional}"),
+        }
+    }
+}
+
+impl std::error::Error for InstrumentError {}
+
+impl From<InstrumentError> for ConnectorError {
+    fn from(error: InstrumentError) -> Self {
+        match error {
+            InstrumentError::UnknownInstrument { .. } => ConnectorError::InvalidSymbol(error.to_string()),
+            _ => ConnectorError::InvalidOrderParameters(error.to_string()),
+        }
+    }
+}
+
+fn display_limit(limit: &Option<Decimal>) -> String {
+    limit.map_or_else(|| "-".to_string(), |limit| limit.to_string())
+}
+
+/// 交易品种元数据来源，通常是交易所REST客户端
+#[async_trait]
+pub trait InstrumentSource: Send + Sync {
+    /// 元数据所属的交易所
+    fn exchange(&self) -> Exchange;
+
+    /// 加载该交易所全部交易品种的交易规则
+    async fn load_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError>;
+}
+
+/// 各交易所交易规则的注册表
+///
+/// 刷新时按交易所整体替换；加载失败时保留上一次的数据。
+pub struct InstrumentRegistry {
+    instruments: RwLock<HashMap<Exchange, HashMap<String, Arc<InstrumentSpec>>>>,
+    sources: RwLock<Vec<Arc<dyn InstrumentSource>>>,
+}
+
+impl Default for InstrumentRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for InstrumentRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let instruments = self.instruments.read().unwrap_or_else(|e| e.into_inner());
+        let counts: HashMap<&Exchange, usize> = instruments.iter().map(|(exchange, specs)| (exchange, specs.len())).collect();
+        f.debug_struct("InstrumentRegistry").field("instruments", &counts).finish()
+    }
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self {
+            instruments: RwLock::new(HashMap::new()),
+            sources: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 注册元数据来源，同一交易所只保留最后一次注册
+    pub fn register_source(&self, source: Arc<dyn InstrumentSource>) {
+        let mut sources = self.sources.write().unwrap_or_else(|e| e.into_inner());
+        sources.retain(|registered| registered.exchange() != source.exchange());
+        sources.push(source);
+    }
+
+    /// 已注册的元数据来源数量
+    pub fn source_count(&self) -> usize {
+        self.sources.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// 替换某交易所的全部交易规则，返回加载的数量
+    pub fn replace_exchange(&self, exchange: Exchange, specs: Vec<InstrumentSpec>) -> usize {
+        let specs: HashMap<String, Arc<InstrumentSpec>> = specs
+            .into_iter()
+            .filter(|spec| spec.exchange == exchange)
+            .map(|spec| (spec.symbol.clone(), Arc::new(spec)))
+            .collect();
+        let count = specs.len();
+        self.instruments.write().unwrap_or_else(|e| e.into_inner()).insert(exchange, specs);
+        count
+    }
+
+    /// 添加或更新单个交易品种
+    pub fn insert(&self, spec: InstrumentSpec) {
+        self.instruments
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(spec.exchange)
+            .or_default()
+            .insert(spec.symbol.clone(), Arc::new(spec));
+    }
+
+    /// 查询交易规则，交易对可以是 BTCUSDT、BTC-USDT、BTC_USDT 或 BTC-USDT-SWAP 等格式
+    pub fn get(&self, exchange: Exchange, symbol: &str) -> Option<Arc<InstrumentSpec>> {
+        self.instruments
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&exchange)?
+            .get(&instrument_key(symbol))
+            .cloned()
+    }
+
+    /// 某交易所的全部交易规则
+    pub fn instruments(&self, exchange: Exchange) -> Vec<Arc<InstrumentSpec>> {
+        self.instruments
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&exchange)
+            .map(|specs| specs.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 某交易所已加载的交易品种数量
+    pub fn instrument_count(&self, exchange: Exchange) -> usize {
+        self.instruments
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&exchange)
+            .map_or(0, HashMap::len)
+    }
+
+    fn require(&self, exchange: Exchange, symbol: &str) -> Result<Arc<InstrumentSpec>, InstrumentError> {
+        self.get(exchange, symbol)
+            .ok_or_else(|| InstrumentError::UnknownInstrument { exchange, symbol: symbol.to_string() })
+    }
+
+    /// 按价格步长取整
+    pub fn round_price(&self, exchange: Exchange, symbol: &str, price: f64, rounding: PriceRounding) -> Result<f64, InstrumentError> {
+        Ok(self.require(exchange, symbol)?.round_price(price, rounding))
+    }
+
+    /// 按数量步长向下取整
+    pub fn round_qty(&self, exchange: Exchange, symbol: &str, quantity: f64) -> Result<f64, InstrumentError> {
+        Ok(self.require(exchange, symbol)?.round_qty(quantity))
+    }
+
+    /// 校验订单是否满足交易规则，没有元数据的交易品种视为不合法
+    pub fn validate_order(&self, exchange: Exchange, order: &OrderRequest) -> Result<(), InstrumentError> {
+        self.require(exchange, &order.symbol)?.validate_order(order)
+    }
+
+    /// 从所有来源重新加载交易规则，交易所之间并行
+    ///
+    /// 返回加载成功的交易所数量；失败或返回空列表时保留原有数据。
+    pub async fn refresh(&self) -> usize {
+        let sources = self.sources.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let results = join_all(sources.iter().map(|source| async move {
+            (source.exchange(), source.load_instruments().await)
+        }))
+        .await;
+
+        let mut refreshed = 0;
+        for (exchange, result) in results {
+            match result {
+                Ok(specs) if specs.is_empty() => {
+                    warn!("[Instruments] {exchange} 返回空的交易规则列表，保留原有数据");
+                }
+                Ok(specs) => {
+                    // 带数量前缀的品种（如 1000PEPE）同步到合约乘数表，供跨所比价换算
+                    let bundled = contract_multipliers_mut().merge_instruments(&specs);
+                    if bundled > 0 {
+                        debug!("[Instruments] {exchange} 有 {bundled} 个按批量报价的品种");
+                    }
+                    let count = self.replace_exchange(exchange, specs);
+                    debug!("[Instruments] {exchange} 已加载 {count} 个交易品种");
+                    refreshed += 1;
+                }
+                Err(e) => warn!("[Instruments] {exchange} 加载交易规则失败: {e}"),
+            }
+        }
+        refreshed
+    }
+
+    /// 启动后台刷新任务：立即加载一次，之后按间隔刷新
+    pub fn start_refresh(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let registry = self.clone();
+        let interval = interval.max(Duration::from_secs(1));
+        info!("[Instruments] 启动交易规则刷新: {} 个交易所, 间隔 {interval:?}", self.source_count());
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                registry.refresh().await;
+            }
+        })
+    }
+}
+
+/// 注册表中使用的交易对键：大写并去掉分隔符和 `-SWAP` 后缀
+pub fn instrument_key(symbol: &str) -> String {
+    let symbol = symbol.to_ascii_uppercase();
+    symbol
+        .strip_suffix("-SWAP")
+        .unwrap_or(&symbol)
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | '/'))
+        .collect()
+}
+
+/// 读取字符串或数字格式的十进制字段
+pub fn decimal_field(value: &Value, key: &str) -> Option<Decimal> {
+    match value.get(key)? {
+        Value::String(text) => text.trim().parse().ok(),
+        Value::Number(number) => number.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 读取正数字段，交易所用 "0" 表示没有限制
+pub fn positive_decimal_field(value: &Value, key: &str) -> Option<Decimal> {
+    decimal_field(value, key).filter(|value| *value > Decimal::ZERO)
+}
+
+/// f64按最短表示转换成十进制，避免二进制误差（例如 0.1 不会变成 0.1000000000000000055）
+fn to_decimal(value: f64) -> Option<Decimal> {
+    if !value.is_finite() {
+        return None;
+    }
+    value.to_string().parse().ok()
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.normalize().to_f64().unwrap_or_default()
+}
+
+fn round_to_step(value: Decimal, step: Decimal, strategy: RoundingStrategy) -> Decimal {
+    if step <= Decimal::ZERO {
+        return value;
+    }
+    (value / step).round_dp_with_strategy(0, strategy) * step
+}
+
+fn is_multiple_of(value: Decimal, step: Decimal) -> bool {
+    step <= Decimal::ZERO || (value % step).is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::exchange::ExchangeType;
+    use crate::types::orders::OrderSide;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn btcusdt() -> InstrumentSpec {
+        InstrumentSpec {
+            min_price: Some(dec("556.80")),
+            max_price: Some(dec("4529764")),
+            min_qty: dec("0.001"),
+            max_qty: Some(dec("1000")),
+            market_step_size: Some(dec("0.001")),
+            market_max_qty: Some(dec("120")),
+            min_notional: Some(dec("100")),
+            ..InstrumentSpec::new(Exchange::BinanceFutures, "BTCUSDT", dec("0.10"), dec("0.001"))
+        }
+    }
+
+    fn order(order_type: OrderType, price: Option<f64>, quantity: f64) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            exchange: ExchangeType::Binance,
+            side: OrderSide::Buy,
+            order_type,
+            quantity,
+            price,
+            time_in_force: None,
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn test_rounding_follows_tick_and_step() {
+        let spec = btcusdt();
+        assert_eq!(spec.price_precision(), 1);
+        assert_eq!(spec.quantity_precision(), 3);
+
+        assert_eq!(spec.round_price(64000.17, PriceRounding::Down), 64000.1);
+        assert_eq!(spec.round_price(64000.11, PriceRounding::Up), 64000.2);
+        assert_eq!(spec.round_price(64000.15, PriceRounding::Nearest), 64000.2);
+        // 已经在档位上的价格不变
+        assert_eq!(spec.round_price(64000.1, PriceRounding::Up), 64000.1);
+
+        assert_eq!(spec.round_qty(0.0129), 0.012);
+        assert_eq!(spec.round_qty(0.3), 0.3);
+        assert!(spec.is_price_on_tick(dec("64000.1")));
+        assert!(!spec.is_qty_on_step(dec("0.0125")));
+    }
+
+    #[test]
+    fn test_validate_order_checks_filters() {
+        let spec = btcusdt();
+        assert_eq!(spec.validate_order(&order(OrderType::Limit, Some(64000.1), 0.002)), Ok(()));
+
+        let result = spec.validate_order(&order(OrderType::Limit, Some(64000.15), 0.002));
+        assert!(matches!(result, Err(InstrumentError::PriceNotOnTick { .. })), "{result:?}");
+        let result = spec.validate_order(&order(OrderType::Limit, Some(100.0), 2.0));
+        assert!(matches!(result, Err(InstrumentError::PriceOutOfRange { .. })), "{result:?}");
+        let result = spec.validate_order(&order(OrderType::Limit, Some(64000.0), 0.0125));
+        assert!(matches!(result, Err(InstrumentError::QuantityNotOnStep { .. })), "{result:?}");
+        let result = spec.validate_order(&order(OrderType::Limit, Some(64000.0), 0.0005));
+        assert!(matches!(result, Err(InstrumentError::QuantityOutOfRange { .. })), "{result:?}");
+        // 0.001 * 64000 = 64 USDT，低于最小名义价值100
+        let result = spec.validate_order(&order(OrderType::Limit, Some(64000.0), 0.001));
+        assert_eq!(result, Err(InstrumentError::BelowMinNotional { notional: dec("64.000"), min_notional: dec("100") }));
+
+        // 市价单使用 MARKET_LOT_SIZE，没有价格时跳过名义价值检查
+        assert_eq!(spec.validate_order(&order(OrderType::Market, None, 0.001)), Ok(()));
+        let result = spec.validate_order(&order(OrderType::Market, None, 500.0));
+        assert!(matches!(result, Err(InstrumentError::QuantityOutOfRange { .. })), "{result:?}");
+
+        let halted = InstrumentSpec { status: InstrumentStatus::Halted, ..btcusdt() };
+        assert!(matches!(halted.validate(Some(64000.0), 0.01, false), Err(InstrumentError::NotTrading { .. })));
+    }
+
+    #[test]
+    fn test_contract_multiplier_scales_notional() {
+        // OKX BTC-USDT-SWAP：每张0.01 BTC
+        let spec = InstrumentSpec {
+            contract_multiplier: dec("0.01"),
+            min_qty: dec("0.01"),
+            min_notional: Some(dec("5")),
+            ..InstrumentSpec::new(Exchange::OkxFutures, "BTC-USDT-SWAP", dec("0.1"), dec("0.01"))
+        };
+        assert_eq!(spec.symbol, "BTCUSDT");
+        assert_eq!(spec.base_quantity(3.0), 0.03);
+        assert_eq!(spec.notional(64000.0, 3.0), 1920.0);
+        // 0.01张 = 0.0001 BTC = 6.4 USDT
+        assert_eq!(spec.validate(Some(64000.0), 0.01, false), Ok(()));
+        assert!(spec.validate(Some(40000.0), 0.01, false).is_err());
+    }
+
+    struct MockSource {
+        exchange: Exchange,
+        loads: AtomicUsize,
+        fail_after: usize,
+    }
+
+    #[async_trait]
+    impl InstrumentSource for MockSource {
+        fn exchange(&self) -> Exchange {
+            self.exchange
+        }
+
+        async fn load_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+            if self.loads.fetch_add(1, Ordering::Relaxed) >= self.fail_after {
+                return Err(ConnectorError::NetworkError("connection reset".to_string()));
+            }
+            Ok(vec![btcusdt(), InstrumentSpec::new(Exchange::BinanceFutures, "ETHUSDT", dec("0.01"), dec("0.001"))])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_refresh_keeps_data_on_failure() {
+        let registry = InstrumentRegistry::new();
+        let source = Arc::new(MockSource { exchange: Exchange::BinanceFutures, loads: AtomicUsize::new(0), fail_after: 1 });
+        registry.register_source(source.clone());
+
+        let result = registry.validate_order(Exchange::BinanceFutures, &order(OrderType::Limit, Some(64000.1), 0.002));
+        assert!(matches!(result, Err(InstrumentError::UnknownInstrument { .. })));
+
+        assert_eq!(registry.refresh().await, 1);
+        assert_eq!(registry.instrument_count(Exchange::BinanceFutures), 2);
+        assert!(registry.get(Exchange::BinanceFutures, "eth-usdt").is_some());
+        assert!(registry.get(Exchange::BybitFutures, "ETHUSDT").is_none());
+        assert_eq!(registry.round_qty(Exchange::BinanceFutures, "BTC_USDT", 1.23456), Ok(1.234));
+        assert!(registry.validate_order(Exchange::BinanceFutures, &order(OrderType::Limit, Some(64000.1), 0.002)).is_ok());
+
+        // 第二次加载失败，保留原有数据
+        assert_eq!(registry.refresh().await, 0);
+        assert_eq!(source.loads.load(Ordering::Relaxed), 2);
+        assert_eq!(registry.instrument_count(Exchange::BinanceFutures), 2);
+    }
+}
@@ -0,0 +1,253 @@
+//! 本地订单簿
+//! 基于快照+增量维护完整深度，供各交易所的同步器复用
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde_json::Value;
+use crate::exchange_types::{Exchange, StandardOrderBook};
+use crate::types::market_data::{DepthUpdate, PriceLevel};
+
+/// 本地维护的完整订单簿
+///
+/// 价格使用Decimal作为键，避免浮点误差导致同一档位被拆成多条。
+#[derive(Debug, Clone)]
+pub struct LocalOrderBook {
+    symbol: String,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    last_update_id: i64,
+    event_time: i64,
+}
+
+impl LocalOrderBook {
+    /// 创建空订单簿
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: 0,
+            event_time: 0,
+        }
+    }
+
+    /// 交易对
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// 最后应用的更新ID
+    pub fn last_update_id(&self) -> i64 {
+        self.last_update_id
+    }
+
+    /// 最后应用的事件时间（毫秒）
+    pub fn event_time(&self) -> i64 {
+        self.event_time
+    }
+
+    /// 是否没有任何档位
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    /// 用快照整体替换订单簿
+    pub fn reset(&mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)], last_update_id: i64, event_time: i64) {
+        self.bids.clear();
+        self.asks.clear();
+        Self::apply_side(&mut self.bids, bids);
+        Self::apply_side(&mut self.asks, asks);
+        self.last_update_id = last_update_id;
+        self.event_time = event_time;
+    }
+
+    /// 应用增量，数量为0表示删除该档位
+    pub fn apply_diff(&mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)], last_update_id: i64, event_time: i64) {
+        Self::apply_side(&mut self.bids, bids);
+        Self::apply_side(&mut self.asks, asks);
+        self.last_update_id = last_update_id;
+        self.event_time = event_time;
+    }
+
+    /// 清空订单簿（等待重新同步）
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.last_update_id = 0;
+    }
+
+    fn apply_side(side: &mut BTreeMap<Decimal, Decimal>, levels: &[(Decimal, Decimal)]) {
+        for (price, quantity) in levels {
+            if quantity.is_zero() {
+                side.remove(price);
+            } else {
+                side.insert(*price, *quantity);
+            }
+        }
+    }
+
+    /// 最优买价
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.keys().next_back().and_then(|p| p.to_f64())
+    }
+
+    /// 最优卖价
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().and_then(|p| p.to_f64())
+    }
+
+    /// 买卖盘是否交叉（数据异常）
+    pub fn is_crossed(&self) -> bool {
+        match (self.bids.keys().next_back(), self.asks.keys().next()) {
+            (Some(bid), Some(ask)) => bid >= ask,
+            _ => false,
+        }
+    }
+
+    /// 买盘（价格从高到低），最多`depth`档
+    pub fn bids(&self, depth: usize) -> Vec<(f64, f64)> {
+        self.bids.iter().rev().take(depth).filter_map(Self::level_to_f64).collect()
+    }
+
+    /// 卖盘（价格从低到高），最多`depth`档
+    pub fn asks(&self, depth: usize) -> Vec<(f64, f64)> {
+        self.asks.iter().take(depth).filter_map(Self::level_to_f64).collect()
+    }
+
+    /// 买盘原始Decimal档位（价格从高到低），用于需要保留精度的校验计算
+    pub fn bid_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.bids.iter().rev().take(depth).map(|(p, q)| (*p, *q)).collect()
+    }
+
+    /// 卖盘原始Decimal档位（价格从低到高）
+    pub fn ask_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect()
+    }
+
+    /// 买卖档位数量
+    pub fn level_counts(&self) -> (usize, usize) {
+        (self.bids.len(), self.asks.len())
+    }
+
+    fn level_to_f64((price, quantity): (&Decimal, &Decimal)) -> Option<(f64, f64)> {
+        Some((price.to_f64()?, quantity.to_f64()?))
+    }
+
+    /// 转换为深度更新，`first_update_id`为本次合并的首个更新ID
+    pub fn to_depth_update(&self, first_update_id: i64, depth: usize) -> DepthUpdate {
+        let to_levels = |levels: Vec<(f64, f64)>| -> Vec<PriceLevel> {
+            levels.into_iter().map(|(price, quantity)| PriceLevel { price, quantity }).collect()
+        };
+
+        DepthUpdate {
+            symbol: self.symbol.clone(),
+            first_update_id,
+            final_update_id: self.last_update_id,
+            event_time: self.event_time,
+            best_bid_price: self.best_bid().unwrap_or(0.0),
+            best_ask_price: self.best_ask().unwrap_or(0.0),
+            depth_bids: to_levels(self.bids(depth)),
+            depth_asks: to_levels(self.asks(depth)),
+        }
+    }
+
+    /// 转换为标准化订单簿
+    pub fn to_standardized(&self, exchange: Exchange, depth: usize) -> StandardOrderBook {
+        StandardOrderBook {
+            symbol: self.symbol.clone(),
+            exchange,
+            best_bid: self.best_bid().unwrap_or(0.0),
+            best_ask: self.best_ask().unwrap_or(0.0),
+            depth_bids: self.bids(depth),
+            depth_asks: self.asks(depth),
+            timestamp: self.event_time,
+        }
+    }
+}
+
+/// 解析 `[["价格","数量"], ...]` 格式的档位，兼容字符串和数字
+pub fn parse_price_levels(levels: Option<&Value>) -> Vec<(Decimal, Decimal)> {
+    levels
+        .and_then(|l| l.as_array())
+        .map(|levels| {
+            levels.iter()
+                .filter_map(|level| {
+                    let level = level.as_array()?;
+                    Some((parse_decimal(level.first()?)?, parse_decimal(level.get(1)?)?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 将JSON值解析为Decimal
+pub fn parse_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::String(s) => Decimal::from_str(s).ok(),
+        Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn levels(raw: Value) -> Vec<(Decimal, Decimal)> {
+        parse_price_levels(Some(&raw))
+    }
+
+    #[test]
+    fn test_reset_and_apply_diff() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        book.reset(
+            &levels(json!([["100.0", "1"], ["99.5", "2"]])),
+            &levels(json!([["100.5", "1"], ["101", "3"]])),
+            10,
+            1000,
+        );
+        assert_eq!(book.best_bid(), Some(100.0));
+        assert_eq!(book.best_ask(), Some(100.5));
+
+        // 删除最优买价，新增更优卖价
+        book.apply_diff(
+            &levels(json!([["100.0", "0"]])),
+            &levels(json!([["100.2", "0.5"]])),
+            11,
+            1100,
+        );
+        assert_eq!(book.best_bid(), Some(99.5));
+        assert_eq!(book.best_ask(), Some(100.2));
+        assert_eq!(book.last_update_id(), 11);
+        assert_eq!(book.level_counts(), (1, 3));
+        assert!(!book.is_crossed());
+    }
+
+    #[test]
+    fn test_depth_ordering_and_limit() {
+        let mut book = LocalOrderBook::new("ETHUSDT");
+        book.reset(
+            &levels(json!([["1", "1"], ["3", "1"], ["2", "1"]])),
+            &levels(json!([["6", "1"], ["4", "1"], ["5", "1"]])),
+            1,
+            0,
+        );
+        assert_eq!(book.bids(2), vec![(3.0, 1.0), (2.0, 1.0)]);
+        assert_eq!(book.asks(2), vec![(4.0, 1.0), (5.0, 1.0)]);
+
+        let update = book.to_depth_update(1, 10);
+        assert_eq!(update.depth_bids.len(), 3);
+        assert_eq!(update.best_bid_price, 3.0);
+        assert_eq!(update.best_ask_price, 4.0);
+    }
+
+    #[test]
+    fn test_parse_numeric_levels() {
+        let parsed = levels(json!([[64000.5, 0.25], ["bad", "1"]]));
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].0, Decimal::from_str("64000.5").unwrap());
+    }
+}
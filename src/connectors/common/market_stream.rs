@@ -0,0 +1,985 @@
+//! 通用行情WebSocket连接器
+//! 只提供行情数据的交易所共用一套连接维护逻辑（心跳、断线重连、批量订阅、本地订单簿），
+//! 各交易所只需实现 `MarketStreamProtocol` 描述自己的订阅格式、心跳和消息解析。
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn, error, debug};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::config::{get_config, ExchangeConfig};
+use crate::core::{AppState, AppError, OrderbookUpdate};
+use crate::connectors::common::data_channel::{data_channel, ChannelSendError, ChannelStats, DataReceiver, DataSender};
+use crate::connectors::common::local_orderbook::LocalOrderBook;
+use crate::connectors::common::symbol_converter::{SymbolConverter, SymbolInfo};
+use crate::connectors::traits::{ExchangeConnector, DataFlowManager};
+use crate::exchange_types::Exchange;
+use crate::types::{
+    config::{ConnectorConfig, ConnectionStatus, ConnectionQuality, BatchSubscriptionResult},
+    market_data::{StandardizedMessage, StandardizedOrderBook, StandardizedTrade},
+    orders::{OrderRequest, OrderResponse, OrderStatus},
+    account::AccountBalance,
+    exchange::{ExchangeType, MarketType},
+    errors::ConnectorError,
+    events::{SystemEvent, HighFrequencyData, DataFlowStats},
+};
+
+/// 每个交易对保留的最近成交条数
+const RECENT_TRADES_CAPACITY: usize = 200;
+
+/// 增量订单簿对外发布的深度档位数
+const PUBLISH_DEPTH: usize = 50;
+
+/// 未配置时单条订阅请求携带的交易对数量
+pub const DEFAULT_BATCH_SIZE: usize = 10;
+
+type MarketWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// 行情频道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MarketChannel {
+    /// 深度
+    OrderBook,
+    /// 逐笔成交
+    Trades,
+}
+
+/// 增量深度推送
+#[derive(Debug, Clone)]
+pub struct BookDiff {
+    /// 标准符号（BTCUSDT）
+    pub symbol: String,
+    /// 为true时用本条推送重置本地订单簿
+    pub is_snapshot: bool,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+    /// 交易所的序列号，用于丢弃过期增量
+    pub update_id: i64,
+    pub timestamp: i64,
+}
+
+/// 协议解析出的行情事件
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    /// 全量深度（每次推送完整的前N档）
+    Book(StandardizedOrderBook),
+    /// 快照+增量形式的深度
+    BookDiff(BookDiff),
+    /// 成交记录
+    Trades(Vec<StandardizedTrade>),
+    /// 需要回复给服务器的消息（例如服务器发起的心跳）
+    Reply(String),
+    /// 心跳回应
+    Pong,
+    /// 服务器返回的错误
+    Error(String),
+    /// 订阅确认等其他消息
+    Other(Value),
+}
+
+/// 交易所行情协议
+///
+/// 所有方法都是无状态的，连接、订阅状态和本地缓存由 `MarketStreamHandler` 维护。
+pub trait MarketStreamProtocol: Clone + Send + Sync + 'static {
+    /// 日志和连接器名称
+    const NAME: &'static str;
+    /// 未配置地址时使用的WebSocket地址
+    const DEFAULT_WS_URL: &'static str;
+    const EXCHANGE: Exchange;
+    const EXCHANGE_TYPE: ExchangeType;
+    const MARKET_TYPE: MarketType;
+    /// 协议允许单条订阅请求携带的最多交易对数量，实际批量取该值与配置batch_size的较小值
+    const MAX_SYMBOLS_PER_REQUEST: usize = usize::MAX;
+
+    /// 由解析后的交易对生成交易所格式的符号
+    fn exchange_symbol(info: &SymbolInfo) -> String;
+
+    /// 交易所格式的符号转换为标准符号（BTCUSDT）
+    fn standard_symbol(exchange_symbol: &str) -> String;
+
+    /// 生成订阅或退订请求
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], request_id: u64) -> String;
+
+    /// 客户端心跳消息，服务器主动发起心跳的协议返回None
+    fn ping_message() -> Option<String>;
+
+    /// 解析一条推送
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError>;
+}
+
+/// 连接会话结束原因
+enum SessionEnd {
+    /// 调用方主动停止
+    Shutdown,
+    /// 连接断开或被要求重连
+    Lost,
+}
+
+/// 通用行情WebSocket处理器
+#[derive(Clone)]
+pub struct MarketStreamHandler<P: MarketStreamProtocol> {
+    app_state: Arc<AppState>,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    ws_url: String,
+    ping_interval: Duration,
+    reconnect_interval: Duration,
+    max_reconnect_attempts: u32,
+    connect_timeout: Duration,
+    batch_size: usize,
+    connection_id: String,
+    status: Arc<RwLock<ConnectionStatus>>,
+    should_run: Arc<RwLock<bool>>,
+    command_sender: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    subscriptions: Arc<RwLock<HashSet<(MarketChannel, String)>>>,
+    orderbooks: Arc<DashMap<String, StandardizedOrderBook>>,
+    local_books: Arc<DashMap<String, LocalOrderBook>>,
+    recent_trades: Arc<DashMap<String, VecDeque<StandardizedTrade>>>,
+    request_id: Arc<AtomicU64>,
+    symbol_converter: SymbolConverter,
+    _protocol: PhantomData<P>,
+}
+
+impl<P: MarketStreamProtocol> MarketStreamHandler<P> {
+    /// 创建处理器，`batch_size` 为单条订阅请求携带的交易对数量
+    pub fn new(config: &ConnectorConfig, batch_size: usize, app_state: Arc<AppState>) -> Self {
+        let connection_id = format!("{}-1", P::EXCHANGE.to_string().to_lowercase());
+        // 行情连接登记到AppState，套利扫描器发现订单簿持续过期时据此发出重连信号
+        app_state.register_market_connection(P::EXCHANGE, &connection_id);
+
+        Self {
+            app_state,
+            message_sender: Arc::new(RwLock::new(None)),
+            ws_url: config.websocket_url.clone().unwrap_or_else(|| P::DEFAULT_WS_URL.to_string()),
+            ping_interval: Duration::from_millis(config.ping_interval.max(1)),
+            reconnect_interval: Duration::from_millis(config.reconnect_interval),
+            max_reconnect_attempts: config.max_reconnect_attempts,
+            connect_timeout: Duration::from_millis(config.request_timeout.max(1)),
+            batch_size: batch_size.clamp(1, P::MAX_SYMBOLS_PER_REQUEST.max(1)),
+            connection_id,
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            should_run: Arc::new(RwLock::new(false)),
+            command_sender: Arc::new(RwLock::new(None)),
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            orderbooks: Arc::new(DashMap::new()),
+            local_books: Arc::new(DashMap::new()),
+            recent_trades: Arc::new(DashMap::new()),
+            request_id: Arc::new(AtomicU64::new(1)),
+            symbol_converter: SymbolConverter::with_default_config(),
+            _protocol: PhantomData,
+        }
+    }
+
+    /// 连接标识（用于AppState中的健康状态和重连信号）
+    pub fn connection_id(&self) -> &str {
+        &self.connection_id
+    }
+
+    /// 实际使用的订阅批量大小
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// 设置消息发送器
+    pub async fn set_message_sender(&self, sender: DataSender<StandardizedMessage>) {
+        let mut message_sender = self.message_sender.write().await;
+        *message_sender = Some(sender);
+    }
+
+    /// 同步设置消息发送器（用于非异步上下文，例如获取数据流）
+    pub fn try_set_message_sender(&self, sender: DataSender<StandardizedMessage>) -> bool {
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => {
+                *message_sender = Some(sender);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 当前消息通道的计数快照
+    pub fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
+    /// 启动WebSocket连接
+    ///
+    /// 首次连接失败直接返回错误；连接建立后在后台维护心跳和断线重连。
+    pub async fn start(&self) -> Result<(), AppError> {
+        {
+            let status = self.status.read().await;
+            if matches!(*status, ConnectionStatus::Connected | ConnectionStatus::Connecting | ConnectionStatus::Reconnecting) {
+                debug!("[{}] {} 连接已在运行，跳过启动", P::NAME, self.connection_id);
+                return Ok(());
+            }
+        }
+
+        info!("[{}] {} 连接到 {}", P::NAME, self.connection_id, self.ws_url);
+        *self.status.write().await = ConnectionStatus::Connecting;
+        *self.should_run.write().await = true;
+
+        let ws_stream = match self.open().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Error;
+                *self.should_run.write().await = false;
+                return Err(e);
+            }
+        };
+
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let command_tx_owner = command_tx.clone();
+        *self.command_sender.write().await = Some(command_tx);
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.app_state.update_connection_timestamp(&self.connection_id);
+        self.app_state.clear_reconnect_signal(&self.connection_id);
+
+        let handler = self.clone();
+        tokio::spawn(async move {
+            handler.connection_loop(ws_stream, command_tx_owner, command_rx).await;
+        });
+
+        info!("[{}] {} WebSocket连接成功", P::NAME, self.connection_id);
+        Ok(())
+    }
+
+    /// 停止WebSocket连接，不再重连
+    pub async fn stop(&self) -> Result<(), AppError> {
+        *self.should_run.write().await = false;
+
+        if let Some(sender) = self.command_sender.write().await.take() {
+            let _ = sender.send(Message::Close(None));
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.app_state.mark_connection_unhealthy(&self.connection_id);
+        info!("[{}] {} WebSocket已停止", P::NAME, self.connection_id);
+        Ok(())
+    }
+
+    /// 订阅深度数据
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_channel(MarketChannel::OrderBook, symbols, self.batch_size).await
+    }
+
+    /// 订阅成交数据
+    pub async fn subscribe_trades(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_channel(MarketChannel::Trades, symbols, self.batch_size).await
+    }
+
+    /// 订阅频道，新增的交易对按 `batch_size` 分批发送
+    pub async fn subscribe_channel(&self, channel: MarketChannel, symbols: Vec<String>, batch_size: usize) -> Result<(), AppError> {
+        info!("[{}] 订阅{:?}频道: {} 个交易对", P::NAME, channel, symbols.len());
+
+        let mut added = Vec::new();
+        for symbol in &symbols {
+            let exchange_symbol = self.to_exchange_symbol(symbol).await?;
+            if self.subscriptions.write().await.insert((channel, exchange_symbol.clone())) {
+                added.push(exchange_symbol);
+            }
+        }
+
+        // 未连接时只记录订阅，连接建立后统一发送
+        if self.is_connected().await {
+            for request in self.subscription_requests(true, channel, &added, batch_size) {
+                self.send_command(Message::Text(request)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 取消订阅交易对的全部频道
+    pub async fn unsubscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        for symbol in symbols {
+            let exchange_symbol = self.to_exchange_symbol(&symbol).await?;
+            for channel in [MarketChannel::OrderBook, MarketChannel::Trades] {
+                let removed = self.subscriptions.write().await.remove(&(channel, exchange_symbol.clone()));
+                if removed {
+                    for request in self.subscription_requests(false, channel, std::slice::from_ref(&exchange_symbol), 1) {
+                        self.send_command(Message::Text(request)).await?;
+                    }
+                }
+            }
+
+            let standard = P::standard_symbol(&exchange_symbol);
+            self.orderbooks.remove(&standard);
+            self.local_books.remove(&standard);
+            info!("[{}] 已取消订阅: {exchange_symbol}", P::NAME);
+        }
+        Ok(())
+    }
+
+    /// 检查连接状态
+    pub async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    /// 获取当前连接状态
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 获取当前的订阅列表 (频道, 交易所符号)
+    pub async fn get_subscriptions(&self) -> Vec<(MarketChannel, String)> {
+        self.subscriptions.read().await.iter().cloned().collect()
+    }
+
+    /// 获取本地缓存的最新深度
+    pub fn get_orderbook(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.orderbooks.get(&Self::normalize_symbol(symbol)).map(|entry| entry.value().clone())
+    }
+
+    /// 获取本地缓存的最近成交（按时间从新到旧）
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.recent_trades
+            .get(&Self::normalize_symbol(symbol))
+            .map(|trades| trades.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 去掉交易所前缀并统一为标准符号
+    fn normalize_symbol(symbol: &str) -> String {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        P::standard_symbol(symbol).replace(['_', '-', '/'], "").to_uppercase()
+    }
+
+    /// 标准符号转换为交易所格式
+    pub async fn to_exchange_symbol(&self, symbol: &str) -> Result<String, AppError> {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        let info = self.symbol_converter
+            .parse_symbol(symbol)
+            .await
+            .map_err(|e| AppError::ParseError(format!("无法转换{}交易对 {symbol}: {e}", P::NAME)))?;
+        Ok(P::exchange_symbol(&info))
+    }
+
+    /// 按批量大小生成订阅或退订请求（交易所格式的符号）
+    pub fn subscription_requests(&self, subscribe: bool, channel: MarketChannel, symbols: &[String], batch_size: usize) -> Vec<String> {
+        let batch_size = batch_size.clamp(1, P::MAX_SYMBOLS_PER_REQUEST.max(1));
+        symbols
+            .chunks(batch_size)
+            .map(|chunk| {
+                let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+                P::subscription_message(subscribe, channel, chunk, request_id)
+            })
+            .collect()
+    }
+
+    async fn send_command(&self, message: Message) -> Result<(), AppError> {
+        match self.command_sender.read().await.as_ref() {
+            Some(sender) => sender
+                .send(message)
+                .map_err(|e| AppError::WebSocketError(format!("{}发送队列已关闭: {e}", P::NAME))),
+            None => Ok(()),
+        }
+    }
+
+    async fn open(&self) -> Result<MarketWsStream, AppError> {
+        let (ws_stream, _) = timeout(self.connect_timeout, connect_async(&self.ws_url))
+            .await
+            .map_err(|_| AppError::WebSocketError(format!("连接{} WebSocket超时: {}", P::NAME, self.ws_url)))?
+            .map_err(|e| AppError::WebSocketError(format!("连接{} WebSocket失败: {e}", P::NAME)))?;
+        Ok(ws_stream)
+    }
+
+    /// 连接维护循环：处理单次会话，断线后按配置重连并恢复订阅
+    async fn connection_loop(
+        self,
+        mut ws_stream: MarketWsStream,
+        command_tx: mpsc::UnboundedSender<Message>,
+        mut command_rx: mpsc::UnboundedReceiver<Message>,
+    ) {
+        loop {
+            if let Err(e) = self.resubscribe_all(&mut ws_stream).await {
+                warn!("[{}] {} 恢复订阅失败: {e}", P::NAME, self.connection_id);
+            }
+
+            if let SessionEnd::Shutdown = self.run_session(ws_stream, &mut command_rx).await {
+                break;
+            }
+            if !*self.should_run.read().await {
+                break;
+            }
+
+            // 断线期间的增量无法补齐，等待新连接推送快照
+            self.local_books.clear();
+            self.app_state.mark_connection_unhealthy(&self.connection_id);
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+
+            let mut reconnected = None;
+            for attempt in 1..=self.max_reconnect_attempts {
+                tokio::time::sleep(self.reconnect_interval).await;
+                if !*self.should_run.read().await {
+                    break;
+                }
+
+                info!("[{}] {} 重连 (尝试 {}/{})", P::NAME, self.connection_id, attempt, self.max_reconnect_attempts);
+                match self.open().await {
+                    Ok(stream) => {
+                        reconnected = Some(stream);
+                        break;
+                    }
+                    Err(e) => warn!("[{}] {} 重连失败: {e}", P::NAME, self.connection_id),
+                }
+            }
+
+            match reconnected {
+                Some(stream) => {
+                    ws_stream = stream;
+                    *self.status.write().await = ConnectionStatus::Connected;
+                    self.app_state.update_connection_timestamp(&self.connection_id);
+                    self.app_state.clear_reconnect_signal(&self.connection_id);
+                    info!("[{}] {} 重连成功", P::NAME, self.connection_id);
+                }
+                None => {
+                    if *self.should_run.read().await {
+                        error!("[{}] {} 重连次数耗尽，停止连接", P::NAME, self.connection_id);
+                        *self.status.write().await = ConnectionStatus::Error;
+                        *self.should_run.write().await = false;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // 只清理属于本次连接的状态，避免影响停止后重新启动的新连接
+        let mut command_sender = self.command_sender.write().await;
+        if command_sender.as_ref().is_some_and(|sender| sender.same_channel(&command_tx)) {
+            command_sender.take();
+            let mut status = self.status.write().await;
+            if *status != ConnectionStatus::Error {
+                *status = ConnectionStatus::Disconnected;
+            }
+        }
+        drop(command_sender);
+        info!("[{}] {} 连接循环已退出", P::NAME, self.connection_id);
+    }
+
+    async fn resubscribe_all(&self, ws_stream: &mut MarketWsStream) -> Result<(), AppError> {
+        let mut subscriptions = self.get_subscriptions().await;
+        subscriptions.sort();
+
+        for channel in [MarketChannel::OrderBook, MarketChannel::Trades] {
+            let symbols: Vec<String> = subscriptions.iter()
+                .filter(|(c, _)| *c == channel)
+                .map(|(_, symbol)| symbol.clone())
+                .collect();
+            for request in self.subscription_requests(true, channel, &symbols, self.batch_size) {
+                ws_stream
+                    .send(Message::Text(request))
+                    .await
+                    .map_err(|e| AppError::WebSocketError(format!("发送订阅失败: {e}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 运行单次连接会话，直到连接断开或收到停止指令
+    async fn run_session(&self, ws_stream: MarketWsStream, command_rx: &mut mpsc::UnboundedReceiver<Message>) -> SessionEnd {
+        let (mut write, mut read) = ws_stream.split();
+        let mut ping_timer = tokio::time::interval(self.ping_interval);
+        ping_timer.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => {
+                    let text = match incoming {
+                        Some(Ok(Message::Text(text))) => text,
+                        // 部分交易所以二进制帧推送JSON文本
+                        Some(Ok(Message::Binary(data))) => match String::from_utf8(data) {
+                            Ok(text) => text,
+                            Err(_) => {
+                                warn!("[{}] {} 收到无法识别的二进制消息", P::NAME, self.connection_id);
+                                last_message = Instant::now();
+                                continue;
+                            }
+                        },
+                        Some(Ok(Message::Ping(payload))) => {
+                            last_message = Instant::now();
+                            if let Err(e) = write.send(Message::Pong(payload)).await {
+                                error!("[{}] {} 发送Pong失败: {e}", P::NAME, self.connection_id);
+                                return SessionEnd::Lost;
+                            }
+                            continue;
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("[{}] {} WebSocket连接被服务器关闭", P::NAME, self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                        Some(Ok(_)) => {
+                            last_message = Instant::now();
+                            continue;
+                        }
+                        Some(Err(e)) => {
+                            error!("[{}] {} WebSocket错误: {e}", P::NAME, self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                    };
+
+                    last_message = Instant::now();
+                    for reply in self.handle_text(&text).await {
+                        if let Err(e) = write.send(Message::Text(reply)).await {
+                            error!("[{}] {} 回复服务器失败: {e}", P::NAME, self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+                command = command_rx.recv() => {
+                    match command {
+                        Some(Message::Close(frame)) => {
+                            let _ = write.send(Message::Close(frame)).await;
+                            return SessionEnd::Shutdown;
+                        }
+                        Some(message) => {
+                            if let Err(e) = write.send(message).await {
+                                error!("[{}] {} 发送消息失败: {e}", P::NAME, self.connection_id);
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        None => return SessionEnd::Shutdown,
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if !*self.should_run.read().await {
+                        return SessionEnd::Shutdown;
+                    }
+                    if self.app_state.should_reconnect(&self.connection_id) {
+                        warn!("[{}] {} 收到重连信号", P::NAME, self.connection_id);
+                        return SessionEnd::Lost;
+                    }
+                    if last_message.elapsed() > self.ping_interval * 3 {
+                        warn!("[{}] {} 长时间未收到消息，重连", P::NAME, self.connection_id);
+                        return SessionEnd::Lost;
+                    }
+
+                    if let Some(ping) = P::ping_message() {
+                        if let Err(e) = write.send(Message::Text(ping)).await {
+                            error!("[{}] {} 发送心跳失败: {e}", P::NAME, self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理文本消息，返回需要回复给服务器的消息
+    async fn handle_text(&self, text: &str) -> Vec<String> {
+        self.app_state.increment_websocket_messages(1);
+        self.app_state.update_connection_timestamp(&self.connection_id);
+
+        let events = match P::parse_message(text) {
+            Ok(events) => events,
+            Err(e) => {
+                warn!("[{}] {} {e}", P::NAME, self.connection_id);
+                return Vec::new();
+            }
+        };
+
+        let mut replies = Vec::new();
+        for event in events {
+            match event {
+                MarketEvent::Book(orderbook) => self.publish_orderbook(orderbook).await,
+                MarketEvent::BookDiff(diff) => {
+                    if let Some(orderbook) = self.apply_book_diff(diff) {
+                        self.publish_orderbook(orderbook).await;
+                    }
+                }
+                MarketEvent::Trades(trades) => {
+                    for trade in trades {
+                        self.publish_trade(trade).await;
+                    }
+                }
+                MarketEvent::Reply(reply) => replies.push(reply),
+                MarketEvent::Pong => debug!("[{}] {} 收到pong", P::NAME, self.connection_id),
+                MarketEvent::Error(message) => warn!("[{}] {} 服务器返回错误: {message}", P::NAME, self.connection_id),
+                MarketEvent::Other(msg) => debug!("[{}] {} 收到其他消息: {msg}", P::NAME, self.connection_id),
+            }
+        }
+        replies
+    }
+
+    /// 应用增量深度，返回更新后的订单簿
+    ///
+    /// 快照之前的增量和序列号未递增的过期增量会被丢弃。
+    pub fn apply_book_diff(&self, diff: BookDiff) -> Option<StandardizedOrderBook> {
+        let mut book = self.local_books
+            .entry(diff.symbol.clone())
+            .or_insert_with(|| LocalOrderBook::new(&diff.symbol));
+
+        if diff.is_snapshot {
+            book.reset(&diff.bids, &diff.asks, diff.update_id, diff.timestamp);
+        } else if book.is_empty() {
+            debug!("[{}] {} 尚未收到快照，丢弃增量", P::NAME, diff.symbol);
+            return None;
+        } else if diff.update_id != 0 && diff.update_id <= book.last_update_id() {
+            debug!("[{}] {} 丢弃过期增量 {}", P::NAME, diff.symbol, diff.update_id);
+            return None;
+        } else {
+            book.apply_diff(&diff.bids, &diff.asks, diff.update_id, diff.timestamp);
+        }
+
+        Some(book.to_standardized(P::EXCHANGE, PUBLISH_DEPTH))
+    }
+
+    async fn publish_orderbook(&self, orderbook: StandardizedOrderBook) {
+        self.orderbooks.insert(orderbook.symbol.clone(), orderbook.clone());
+        self.app_state.book_store.update(orderbook.clone());
+
+        if let Some(tx) = &self.app_state.orderbook_queue {
+            let update = OrderbookUpdate {
+                symbol: format!("{}:{}", P::EXCHANGE, orderbook.symbol),
+                best_ask: orderbook.best_ask,
+                best_bid: orderbook.best_bid,
+                timestamp: orderbook.timestamp,
+                scale: 8,
+                is_synthetic: false,
+                leg1: None,
+                leg2: None,
+                depth_asks: Some(orderbook.depth_asks.clone()),
+                depth_bids: Some(orderbook.depth_bids.clone()),
+            };
+            if let Err(e) = tx.send(update) {
+                error!("[{}] 发送订单簿更新失败: {e}", P::NAME);
+            }
+        }
+
+        self.forward(StandardizedMessage::OrderBookUpdate(orderbook)).await;
+    }
+
+    async fn publish_trade(&self, trade: StandardizedTrade) {
+        {
+            let mut trades = self.recent_trades.entry(trade.symbol.clone()).or_default();
+            if trades.len() >= RECENT_TRADES_CAPACITY {
+                trades.pop_front();
+            }
+            trades.push_back(trade.clone());
+        }
+
+        self.forward(StandardizedMessage::TradeUpdate(trade)).await;
+    }
+
+    async fn forward(&self, message: StandardizedMessage) {
+        // 先取出发送端再发送，阻塞策略等待消费时不占用锁
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[{}] {} 消息接收端已关闭", P::NAME, self.connection_id);
+            }
+        }
+    }
+}
+
+/// 只提供行情数据的通用连接器
+/// 实现ExchangeConnector trait，交易接口统一返回未实现错误
+#[derive(Clone)]
+pub struct MarketStreamConnector<P: MarketStreamProtocol> {
+    config: ConnectorConfig,
+    app_state: Arc<AppState>,
+    handler: MarketStreamHandler<P>,
+    event_sender: broadcast::Sender<SystemEvent>,
+}
+
+impl<P: MarketStreamProtocol> MarketStreamConnector<P> {
+    /// 创建连接器实例
+    pub fn new(config: ConnectorConfig, batch_size: usize, app_state: Arc<AppState>) -> Self {
+        let handler = MarketStreamHandler::new(&config, batch_size, app_state.clone());
+        let (event_sender, _) = broadcast::channel(1000);
+
+        Self {
+            config,
+            app_state,
+            handler,
+            event_sender,
+        }
+    }
+
+    /// 根据config.toml中的 `[exchanges.*]` 配置创建连接器
+    pub fn from_exchange_config(exchange_config: &ExchangeConfig, app_state: Arc<AppState>) -> Self {
+        Self::new(exchange_config.to_connector_config(), exchange_config.batch_size, app_state)
+    }
+
+    /// 获取WebSocket处理器
+    pub fn handler(&self) -> &MarketStreamHandler<P> {
+        &self.handler
+    }
+
+    /// 获取连接器配置
+    pub fn config(&self) -> &ConnectorConfig {
+        &self.config
+    }
+
+    /// 取消订阅交易对的行情
+    pub async fn unsubscribe(&self, symbol: &str) -> Result<(), ConnectorError> {
+        self.handler.unsubscribe(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to unsubscribe {symbol}: {e}")))
+    }
+
+    /// 健康检查
+    pub async fn health_check(&self) -> Result<bool, ConnectorError> {
+        Ok(self.is_connected().await)
+    }
+
+    /// 获取连接统计信息 (消息数, 价格更新数)
+    pub fn get_connection_stats(&self) -> (u64, u64) {
+        let messages = self.app_state.websocket_messages.load(Ordering::Relaxed);
+        let updates = self.app_state.price_updates.load(Ordering::Relaxed);
+        (messages, updates)
+    }
+}
+
+#[async_trait]
+impl<P: MarketStreamProtocol> ExchangeConnector for MarketStreamConnector<P> {
+    // 基础信息
+    fn get_exchange_type(&self) -> ExchangeType {
+        P::EXCHANGE_TYPE
+    }
+
+    fn get_market_type(&self) -> MarketType {
+        P::MARKET_TYPE
+    }
+
+    fn get_exchange_name(&self) -> &str {
+        P::NAME
+    }
+
+    // WebSocket 连接管理
+    async fn connect_websocket(&self) -> Result<(), ConnectorError> {
+        info!("Connecting to {} WebSocket", P::NAME);
+
+        // 断线重连由处理器在后台维护
+        self.handler.start().await
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("Failed to connect {} WebSocket: {e}", P::NAME)))?;
+
+        info!("{} WebSocket connected successfully", P::NAME);
+        Ok(())
+    }
+
+    async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
+        info!("Disconnecting from {} WebSocket", P::NAME);
+
+        self.handler.stop().await
+            .map_err(|e| ConnectorError::ConnectionError(format!("Failed to disconnect {} WebSocket: {e}", P::NAME)))?;
+
+        info!("{} WebSocket disconnected successfully", P::NAME);
+        Ok(())
+    }
+
+    async fn subscribe_orderbook(&self, symbol: &str) -> Result<(), ConnectorError> {
+        info!("Subscribing to {} orderbook for symbol: {}", P::NAME, symbol);
+
+        if !self.handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+
+        self.handler.subscribe(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to orderbook: {e}")))
+    }
+
+    async fn subscribe_trades(&self, symbol: &str) -> Result<(), ConnectorError> {
+        info!("Subscribing to {} trades for symbol: {}", P::NAME, symbol);
+
+        if !self.handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+
+        self.handler.subscribe_trades(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to trades: {e}")))
+    }
+
+    async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
+        // 行情连接器不提供用户数据流
+        warn!("User stream subscription not implemented for {}", P::NAME);
+        Ok(())
+    }
+
+    // 推送式数据流接口
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.market_data_channel);
+        if !self.handler.try_set_message_sender(sender) {
+            warn!("[{}] 消息发送器正被占用，返回的数据流不会收到消息", P::NAME);
+        }
+        receiver
+    }
+
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (_, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        receiver
+    }
+
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        if let Some(channel) = self.handler.channel_stats() {
+            channel.apply_to(&mut stats);
+        }
+        stats
+    }
+
+    // 本地缓存快照读取
+    async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.handler.get_orderbook(symbol)
+    }
+
+    async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.handler.get_recent_trades(symbol, limit)
+    }
+
+    // 交易相关操作 (REST API)
+    async fn place_order(&self, _order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        Err(ConnectorError::OrderPlacementFailed(format!("Trading not implemented for {} connector", P::NAME)))
+    }
+
+    async fn cancel_order(&self, _order_id: &str, _symbol: &str) -> Result<bool, ConnectorError> {
+        Err(ConnectorError::OrderCancellationFailed(format!("Trading not implemented for {} connector", P::NAME)))
+    }
+
+    async fn get_order_status(&self, _order_id: &str, _symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        Err(ConnectorError::InvalidResponse(format!("Trading not implemented for {} connector", P::NAME)))
+    }
+
+    async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        Err(ConnectorError::DataParsingError(format!("Account balance not implemented for {} connector", P::NAME)))
+    }
+
+    // 连接状态
+    async fn is_connected(&self) -> bool {
+        self.handler.is_connected().await
+    }
+
+    async fn is_websocket_connected(&self) -> bool {
+        self.handler.is_connected().await
+    }
+
+    async fn get_connection_status(&self) -> ConnectionStatus {
+        self.handler.get_connection_status().await
+    }
+
+    async fn get_connection_quality(&self) -> Result<ConnectionQuality, ConnectorError> {
+        let (latency_ms, packet_loss_rate, stability_score) = match self.get_connection_status().await {
+            ConnectionStatus::Connected => (60.0, 0.0, 0.85),
+            ConnectionStatus::Connecting | ConnectionStatus::Reconnecting => (250.0, 0.15, 0.25),
+            _ => (1200.0, 0.6, 0.05),
+        };
+
+        Ok(ConnectionQuality {
+            latency_ms,
+            packet_loss_rate,
+            stability_score,
+            last_updated: chrono::Utc::now(),
+        })
+    }
+
+    async fn emergency_ping(&self) -> Result<Duration, ConnectorError> {
+        if self.handler.is_connected().await {
+            Ok(Duration::from_millis(60))
+        } else {
+            Err(ConnectorError::ConnectionLost("WebSocket未连接".to_string()))
+        }
+    }
+
+    async fn subscribe_batch(
+        &self,
+        symbols: Vec<String>,
+        batch_size: usize,
+    ) -> Result<BatchSubscriptionResult, ConnectorError> {
+        info!("[{}] 批量订阅: {} 个符号", P::NAME, symbols.len());
+
+        if !self.handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+
+        // 先逐个转换符号，无法识别的单独记为失败，其余按批量合并发送
+        let total_requested = symbols.len();
+        let mut accepted = Vec::new();
+        let mut failed_symbols = Vec::new();
+        for symbol in symbols {
+            match self.handler.to_exchange_symbol(&symbol).await {
+                Ok(_) => accepted.push(symbol),
+                Err(e) => failed_symbols.push((symbol, e.to_string())),
+            }
+        }
+
+        let successful = accepted.len();
+        self.handler.subscribe_channel(MarketChannel::OrderBook, accepted, batch_size).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe batch: {e}")))?;
+
+        info!("[{}] 批量订阅完成: {}/{} 成功", P::NAME, successful, total_requested);
+        Ok(BatchSubscriptionResult {
+            total_requested,
+            successful,
+            failed: failed_symbols.len(),
+            pending: 0,
+            failed_symbols,
+            results: Vec::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl<P: MarketStreamProtocol> DataFlowManager for MarketStreamConnector<P> {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
+        // 行情通过 get_market_data_stream 推送
+        None
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent> {
+        self.event_sender.subscribe()
+    }
+
+    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
+        Ok(())
+    }
+
+    async fn send_event(&self, event: SystemEvent) {
+        let _ = self.event_sender.send(event);
+    }
+}
+
+/// 读取字符串或数字格式的数值
+pub fn value_to_f64(value: &Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok()))
+}
+
+/// 读取字符串或数字格式的整数（毫秒时间戳、序列号等）
+pub fn value_to_i64(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.as_str().and_then(|s| s.parse::<i64>().ok()))
+}
+
+/// 由 `[["价格","数量"], ...]` 格式的全量档位构建标准订单簿
+pub fn build_orderbook(exchange: Exchange, symbol: String, bids: Option<&Value>, asks: Option<&Value>, timestamp: i64) -> StandardizedOrderBook {
+    let parse = |levels: Option<&Value>| -> Vec<(f64, f64)> {
+        levels
+            .and_then(|l| l.as_array())
+            .map(|levels| {
+                levels.iter()
+                    .filter_map(|level| {
+                        let level = level.as_array()?;
+                        Some((value_to_f64(level.first()?)?, value_to_f64(level.get(1)?)?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let depth_bids = parse(bids);
+    let depth_asks = parse(asks);
+    StandardizedOrderBook {
+        symbol,
+        exchange,
+        best_bid: depth_bids.first().map(|(p, _)| *p).unwrap_or(0.0),
+        best_ask: depth_asks.first().map(|(p, _)| *p).unwrap_or(0.0),
+        depth_bids,
+        depth_asks,
+        timestamp,
+    }
+}
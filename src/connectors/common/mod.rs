@@ -10,11 +10,16 @@ pub mod batch_subscription;
 pub mod symbol_converter;
 pub mod orderbook_validator;
 pub mod smart_error_recovery;
+pub mod local_orderbook;
+pub mod market_stream;
+pub mod data_channel;
+pub mod rate_limiter;
+pub mod clock_sync;
+pub mod instruments;
 
 // 预留通用功能模块
 // pub mod health_checker;
 // pub mod message_parser;
-// pub mod rate_limiter;
 // pub mod reconnect_handler;
 
 // 重新导出主要类型
@@ -74,4 +79,28 @@ pub use smart_error_recovery::{
     RecoveryStrategy,
     RecoveryResult,
     RecoveryStats,
-};
\ No newline at end of file
+};
+
+pub use local_orderbook::{
+    LocalOrderBook,
+    parse_price_levels,
+    parse_decimal,
+};
+
+pub use market_stream::{
+    MarketStreamProtocol,
+    MarketStreamHandler,
+    MarketStreamConnector,
+    MarketChannel,
+    MarketEvent,
+    BookDiff,
+};
+
+pub use data_channel::{
+    data_channel,
+    DataSender,
+    DataReceiver,
+    ChannelSendError,
+    ChannelStats,
+    ConflationKey,
+};
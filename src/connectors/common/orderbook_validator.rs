@@ -6,7 +6,9 @@ use std::time::{Duration, SystemTime};
 use log::{debug, warn, error};
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
+use crate::connectors::common::instruments::InstrumentSpec;
 use crate::exchange_types::StandardOrderBook as OrderBook;
+use std::sync::Arc;
 use chrono;
 
 /// 订单簿档位
@@ -26,6 +28,8 @@ pub struct OrderbookValidator {
     stats: ValidationStats,
     /// 最后验证时间
     last_validation_time: Option<SystemTime>,
+    /// 交易规则，设置后价格和数量按步长而不是小数位数校验
+    instrument: Option<Arc<InstrumentSpec>>,
 }
 
 /// 订单簿验证器配置
@@ -232,9 +236,17 @@ impl OrderbookValidator {
             config,
             stats: ValidationStats::default(),
             last_validation_time: None,
+            instrument: None,
         }
     }
 
+    /// 使用交易品种的价格步长和数量步长校验精度
+    pub fn set_instrument(&mut self, instrument: Arc<InstrumentSpec>) {
+        self.config.price_precision = instrument.price_precision();
+        self.config.quantity_precision = instrument.quantity_precision();
+        self.instrument = Some(instrument);
+    }
+
     /// 使用默认配置创建验证器
     pub fn with_default_config() -> Self {
         Self::new(OrderbookValidatorConfig::default())
@@ -684,12 +696,18 @@ impl OrderbookValidator {
 
     /// 检查价格精度
     fn check_price_precision(&self, price: Decimal) -> bool {
+        if let Some(instrument) = &self.instrument {
+            return instrument.is_price_on_tick(price);
+        }
         let scale = price.scale();
         scale <= self.config.price_precision
     }
 
     /// 检查数量精度
     fn check_quantity_precision(&self, quantity: Decimal) -> bool {
+        if let Some(instrument) = &self.instrument {
+            return instrument.is_qty_on_step(quantity);
+        }
         let scale = quantity.scale();
         scale <= self.config.quantity_precision
     }
@@ -1121,4 +1139,27 @@ mod tests {
         assert!(result.is_valid);
         assert!(result.errors.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_instrument_tick_size_validation() {
+        use crate::connectors::common::instruments::InstrumentSpec;
+        use crate::exchange_types::Exchange;
+
+        let mut orderbook = create_test_orderbook();
+        orderbook.depth_bids[1] = (49999.3, 2.0);
+        orderbook.depth_asks[2] = (50003.0, 0.0015);
+
+        // 两位小数以内，按小数位数检查可以通过
+        let mut validator = OrderbookValidator::with_default_config();
+        assert!(validator.validate_orderbook(&orderbook).is_valid);
+
+        // 价格步长0.5、数量步长0.001时，49999.3和0.0015都不合法
+        let instrument = InstrumentSpec::new(Exchange::Binance, "BTCUSDT", Decimal::new(5, 1), Decimal::new(1, 3));
+        validator.set_instrument(Arc::new(instrument));
+        let result = validator.validate_orderbook(&orderbook);
+        assert!(!result.is_valid);
+        let error_types: Vec<_> = result.errors.iter().map(|e| e.error_type.clone()).collect();
+        assert_eq!(error_types, vec![ValidationErrorType::PricePrecision, ValidationErrorType::QuantityPrecision]);
+        assert_eq!(result.errors[0].level_index, Some(1));
+    }
+}
@@ -0,0 +1,511 @@
+//! REST请求限速器
+//! 按交易所公布的规则（请求权重、下单次数）在本地计数，每次请求前预占额度；
+//! 额度不足时排队等待窗口重置，需要等待太久则直接拒绝，尽量不触发429/418。
+//!
+//! 计数窗口与交易所一样按整点对齐（例如每分钟从第0秒开始）。
+//! 响应头中的已用额度（Binance的 `X-MBX-USED-WEIGHT-1M`、`X-MBX-ORDER-COUNT-10S` 等）
+//! 会回写到本地计数，使多个进程共用同一IP或账户时也能保持一致。
+
+use log::{error, warn};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::core::AppError;
+use crate::error_handling::{init_error_tracker, ErrorCategory};
+use crate::exchange_types::Exchange;
+
+/// 默认最长排队时间
+const DEFAULT_MAX_QUEUE_WAIT: Duration = Duration::from_secs(5);
+
+/// 429响应没有 `Retry-After` 时的退避时间
+const DEFAULT_THROTTLE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// 418响应没有 `Retry-After` 时的封禁时间（Binance最短封禁为2分钟）
+const DEFAULT_BAN_DURATION: Duration = Duration::from_secs(120);
+
+/// 限速规则类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKind {
+    /// 按请求权重计数（通常按IP）
+    RequestWeight,
+    /// 按下单次数计数（通常按账户）
+    Orders,
+}
+
+impl fmt::Display for RateLimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitKind::RequestWeight => write!(f, "REQUEST_WEIGHT"),
+            RateLimitKind::Orders => write!(f, "ORDERS"),
+        }
+    }
+}
+
+/// 一条限速规则：每个 `interval` 窗口内最多使用 `limit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitRule {
+    pub kind: RateLimitKind,
+    pub interval: Duration,
+    pub limit: u32,
+}
+
+impl RateLimitRule {
+    pub fn request_weight(interval: Duration, limit: u32) -> Self {
+        Self { kind: RateLimitKind::RequestWeight, interval, limit }
+    }
+
+    pub fn orders(interval: Duration, limit: u32) -> Self {
+        Self { kind: RateLimitKind::Orders, interval, limit }
+    }
+}
+
+/// 一次请求消耗的额度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestCost {
+    pub weight: u32,
+    pub orders: u32,
+}
+
+impl RequestCost {
+    /// 只消耗请求权重
+    pub const fn weight(weight: u32) -> Self {
+        Self { weight, orders: 0 }
+    }
+
+    /// 下单类请求：消耗一次下单次数和给定的请求权重
+    pub const fn order(weight: u32) -> Self {
+        Self { weight, orders: 1 }
+    }
+
+    fn of(&self, kind: RateLimitKind) -> u32 {
+        match kind {
+            RateLimitKind::RequestWeight => self.weight,
+            RateLimitKind::Orders => self.orders,
+        }
+    }
+}
+
+/// 请求被限速器拒绝的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitError {
+    /// 额度不足且需要等待的时间超过排队上限
+    WouldExceed { kind: RateLimitKind, interval: Duration, retry_after: Duration },
+    /// 交易所返回429，退避期间的请求
+    Throttled { retry_after: Duration },
+    /// 交易所返回418，IP被封禁
+    Banned { retry_after: Duration },
+}
+
+impl RateLimitError {
+    /// 最早可以重试的时间
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            RateLimitError::WouldExceed { retry_after, .. }
+            | RateLimitError::Throttled { retry_after }
+            | RateLimitError::Banned { retry_after } => *retry_after,
+        }
+    }
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::WouldExceed { kind, interval, retry_after } => {
+                write!(f, "{kind} 额度 ({interval:?}) 已用完，{retry_after:?} 后重试")
+            }
+            RateLimitError::Throttled { retry_after } => write!(f, "交易所限流(429)，{retry_after:?} 后重试"),
+            RateLimitError::Banned { retry_after } => write!(f, "IP已被交易所封禁(418)，{retry_after:?} 后解除"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+impl From<RateLimitError> for AppError {
+    fn from(error: RateLimitError) -> Self {
+        // "rate limit" 前缀让 ErrorCategory 归类为 RateLimitExceeded
+        AppError::Other(format!("rate limit: {error}"))
+    }
+}
+
+/// 某条规则当前窗口的使用情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitUsage {
+    pub rule: RateLimitRule,
+    pub used: u32,
+    /// 当前窗口结束的时间（毫秒时间戳）
+    pub window_end_ms: i64,
+}
+
+impl RateLimitUsage {
+    pub fn remaining(&self) -> u32 {
+        self.rule.limit.saturating_sub(self.used)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BlockReason {
+    Throttled,
+    Banned,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    windows: Vec<RateLimitUsage>,
+    blocked_until: Option<(Instant, BlockReason)>,
+}
+
+impl LimiterState {
+    /// 把已经结束的窗口滚动到当前窗口
+    fn roll(&mut self, now_ms: i64) {
+        for window in &mut self.windows {
+            if now_ms >= window.window_end_ms {
+                let interval_ms = window.rule.interval.as_millis().max(1) as i64;
+                window.window_end_ms = now_ms - now_ms.rem_euclid(interval_ms) + interval_ms;
+                window.used = 0;
+            }
+        }
+    }
+}
+
+/// 交易所REST限速器，可在同一交易所的多个客户端间共享
+#[derive(Debug)]
+pub struct RateLimiter {
+    exchange: Exchange,
+    state: Mutex<LimiterState>,
+    max_queue_wait: Duration,
+    queued_requests: AtomicU64,
+    rejected_requests: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(exchange: Exchange, rules: Vec<RateLimitRule>) -> Self {
+        let windows = rules
+            .into_iter()
+            .map(|rule| RateLimitUsage { rule, used: 0, window_end_ms: 0 })
+            .collect();
+
+        Self {
+            exchange,
+            state: Mutex::new(LimiterState { windows, blocked_until: None }),
+            max_queue_wait: DEFAULT_MAX_QUEUE_WAIT,
+            queued_requests: AtomicU64::new(0),
+            rejected_requests: AtomicU64::new(0),
+        }
+    }
+
+    /// 设置最长排队时间，为零时额度不足立即拒绝
+    pub fn with_max_queue_wait(mut self, max_queue_wait: Duration) -> Self {
+        self.max_queue_wait = max_queue_wait;
+        self
+    }
+
+    pub fn exchange(&self) -> Exchange {
+        self.exchange
+    }
+
+    /// 预占一次请求的额度，额度不足时排队等待窗口重置
+    pub async fn acquire(&self, cost: RequestCost) -> Result<(), RateLimitError> {
+        let deadline = Instant::now() + self.max_queue_wait;
+        let mut queued = false;
+
+        loop {
+            let wait = match self.try_reserve(cost) {
+                Ok(()) => return Ok(()),
+                Err(Reservation::Wait(wait)) => wait,
+                Err(Reservation::Reject(error)) => return Err(self.reject(error)),
+            };
+
+            if Instant::now() + wait > deadline {
+                let error = self.over_limit_error(cost, wait);
+                return Err(self.reject(error));
+            }
+            if !queued {
+                queued = true;
+                self.queued_requests.fetch_add(1, Ordering::Relaxed);
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// 根据响应更新本地计数；429/418时开始退避并返回对应错误
+    pub fn observe_response(&self, status: u16, headers: &HeaderMap) -> Option<RateLimitError> {
+        self.update_from_headers(headers);
+
+        let retry_after = headers
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        match status {
+            429 => {
+                let retry_after = retry_after.unwrap_or(DEFAULT_THROTTLE_BACKOFF);
+                warn!("[RateLimiter] {} 返回429，{retry_after:?} 内暂停请求", self.exchange);
+                self.block(retry_after, BlockReason::Throttled);
+                Some(RateLimitError::Throttled { retry_after })
+            }
+            418 => {
+                let retry_after = retry_after.unwrap_or(DEFAULT_BAN_DURATION);
+                error!("[RateLimiter] {} 返回418，IP被封禁 {retry_after:?}", self.exchange);
+                self.block(retry_after, BlockReason::Banned);
+                init_error_tracker().trip_circuit_breaker(self.exchange, ErrorCategory::RateLimitExceeded, retry_after);
+                Some(RateLimitError::Banned { retry_after })
+            }
+            _ => None,
+        }
+    }
+
+    /// 用响应头中的已用额度校正本地计数
+    ///
+    /// 识别 `X-MBX-USED-WEIGHT-<间隔>` 和 `X-MBX-ORDER-COUNT-<间隔>`，
+    /// 间隔形如 `1M`、`10S`、`1D`，只更新间隔相同的规则。
+    pub fn update_from_headers(&self, headers: &HeaderMap) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(now_ms());
+
+        for (name, value) in headers {
+            let Some((kind, interval)) = parse_usage_header(name.as_str()) else {
+                continue;
+            };
+            let Some(used) = value.to_str().ok().and_then(|value| value.trim().parse::<u32>().ok()) else {
+                continue;
+            };
+            for window in state.windows.iter_mut().filter(|window| window.rule.kind == kind && window.rule.interval == interval) {
+                // 本地可能还有尚未被交易所计入的在途请求，取较大值
+                window.used = window.used.max(used);
+            }
+        }
+    }
+
+    /// 当前各规则的使用情况
+    pub fn usage(&self) -> Vec<RateLimitUsage> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.roll(now_ms());
+        state.windows.clone()
+    }
+
+    /// 某类规则中最紧的剩余额度及其窗口结束时间（毫秒时间戳）
+    pub fn remaining(&self, kind: RateLimitKind) -> Option<(u32, i64)> {
+        self.usage()
+            .into_iter()
+            .filter(|usage| usage.rule.kind == kind)
+            .map(|usage| (usage.remaining(), usage.window_end_ms))
+            .min()
+    }
+
+    /// 是否处于429退避或418封禁期间
+    pub fn is_blocked(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.blocked_until.is_some_and(|(until, _)| until > Instant::now())
+    }
+
+    /// 曾经排队等待的请求数
+    pub fn queued_requests(&self) -> u64 {
+        self.queued_requests.load(Ordering::Relaxed)
+    }
+
+    /// 被拒绝的请求数
+    pub fn rejected_requests(&self) -> u64 {
+        self.rejected_requests.load(Ordering::Relaxed)
+    }
+
+    fn try_reserve(&self, cost: RequestCost) -> Result<(), Reservation> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        if let Some((until, reason)) = state.blocked_until {
+            if until > now {
+                let retry_after = until - now;
+                return Err(match reason {
+                    BlockReason::Throttled => Reservation::Wait(retry_after),
+                    BlockReason::Banned => Reservation::Reject(RateLimitError::Banned { retry_after }),
+                });
+            }
+            state.blocked_until = None;
+        }
+
+        let now_ms = now_ms();
+        state.roll(now_ms);
+
+        let mut wait = Duration::ZERO;
+        for window in &state.windows {
+            let needed = cost.of(window.rule.kind);
+            if needed == 0 {
+                continue;
+            }
+            if needed > window.rule.limit {
+                // 单次请求超过整个窗口的额度，等待也没有用
+                return Err(Reservation::Reject(RateLimitError::WouldExceed {
+                    kind: window.rule.kind,
+                    interval: window.rule.interval,
+                    retry_after: Duration::MAX,
+                }));
+            }
+            if window.used + needed > window.rule.limit {
+                wait = wait.max(Duration::from_millis((window.window_end_ms - now_ms).max(1) as u64));
+            }
+        }
+        if !wait.is_zero() {
+            return Err(Reservation::Wait(wait));
+        }
+
+        for window in &mut state.windows {
+            window.used += cost.of(window.rule.kind);
+        }
+        Ok(())
+    }
+
+    fn over_limit_error(&self, cost: RequestCost, retry_after: Duration) -> RateLimitError {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.blocked_until.is_some() {
+            return RateLimitError::Throttled { retry_after };
+        }
+        let rule = state
+            .windows
+            .iter()
+            .find(|window| window.used + cost.of(window.rule.kind) > window.rule.limit)
+            .map(|window| window.rule)
+            .unwrap_or_else(|| RateLimitRule::request_weight(Duration::ZERO, 0));
+        RateLimitError::WouldExceed { kind: rule.kind, interval: rule.interval, retry_after }
+    }
+
+    fn reject(&self, error: RateLimitError) -> RateLimitError {
+        self.rejected_requests.fetch_add(1, Ordering::Relaxed);
+        warn!("[RateLimiter] {} 请求被拒绝: {error}", self.exchange);
+        error
+    }
+
+    fn block(&self, duration: Duration, reason: BlockReason) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let until = Instant::now() + duration;
+        // 已有更长的封禁时保持不变
+        if state.blocked_until.is_none_or(|(current, _)| current < until) {
+            state.blocked_until = Some((until, reason));
+        }
+    }
+}
+
+enum Reservation {
+    Wait(Duration),
+    Reject(RateLimitError),
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// 解析额度响应头名称，返回规则类型和窗口长度
+fn parse_usage_header(name: &str) -> Option<(RateLimitKind, Duration)> {
+    let name = name.to_ascii_lowercase();
+    let (kind, interval) = if let Some(interval) = name.strip_prefix("x-mbx-used-weight-") {
+        (RateLimitKind::RequestWeight, interval)
+    } else if let Some(interval) = name.strip_prefix("x-mbx-order-count-") {
+        (RateLimitKind::Orders, interval)
+    } else {
+        return None;
+    };
+
+    let unit_secs = match interval.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let count: u64 = interval[..interval.len() - 1].parse().ok()?;
+    Some((kind, Duration::from_secs(count * unit_secs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_parse_usage_headers() {
+        assert_eq!(parse_usage_header("X-MBX-USED-WEIGHT-1M"), Some((RateLimitKind::RequestWeight, Duration::from_secs(60))));
+        assert_eq!(parse_usage_header("x-mbx-order-count-10s"), Some((RateLimitKind::Orders, Duration::from_secs(10))));
+        assert_eq!(parse_usage_header("x-mbx-order-count-1d"), Some((RateLimitKind::Orders, Duration::from_secs(86_400))));
+        assert_eq!(parse_usage_header("x-mbx-used-weight"), None);
+        assert_eq!(parse_usage_header("content-length"), None);
+    }
+
+    #[tokio::test]
+    async fn test_headers_sync_usage_and_reject_when_exhausted() {
+        let limiter = RateLimiter::new(Exchange::BinanceFutures, vec![
+            RateLimitRule::request_weight(Duration::from_secs(60), 100),
+            RateLimitRule::orders(Duration::from_secs(10), 5),
+        ])
+        .with_max_queue_wait(Duration::ZERO);
+
+        limiter.acquire(RequestCost::weight(10)).await.unwrap();
+        assert_eq!(limiter.remaining(RateLimitKind::RequestWeight).unwrap().0, 90);
+
+        // 交易所报告的用量更高时以交易所为准
+        limiter.update_from_headers(&headers(&[("x-mbx-used-weight-1m", "95"), ("x-mbx-order-count-10s", "5")]));
+        assert_eq!(limiter.remaining(RateLimitKind::RequestWeight).unwrap().0, 5);
+
+        limiter.acquire(RequestCost::weight(5)).await.unwrap();
+        match limiter.acquire(RequestCost::weight(1)).await {
+            Err(RateLimitError::WouldExceed { kind: RateLimitKind::RequestWeight, .. }) => {}
+            other => panic!("意外的结果: {other:?}"),
+        }
+        // 下单次数用完时即使权重为0也会被拒绝
+        match limiter.acquire(RequestCost::order(0)).await {
+            Err(RateLimitError::WouldExceed { kind: RateLimitKind::Orders, .. }) => {}
+            other => panic!("意外的结果: {other:?}"),
+        }
+        assert_eq!(limiter.rejected_requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_queues_until_window_resets() {
+        let limiter = RateLimiter::new(Exchange::Phemex, vec![RateLimitRule::request_weight(Duration::from_millis(200), 3)])
+            .with_max_queue_wait(Duration::from_secs(1));
+
+        // 7个请求至少跨越3个窗口，其中至少一个需要排队等待窗口重置
+        for _ in 0..7 {
+            limiter.acquire(RequestCost::weight(1)).await.unwrap();
+        }
+        assert!(limiter.queued_requests() >= 1);
+        assert_eq!(limiter.rejected_requests(), 0);
+        assert!(limiter.usage()[0].used <= 3);
+
+        // 超过整个窗口额度的请求直接拒绝
+        assert!(limiter.acquire(RequestCost::weight(4)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_429_backs_off_and_418_trips_circuit_breaker() {
+        let limiter = RateLimiter::new(Exchange::Hbit, vec![RateLimitRule::request_weight(Duration::from_secs(60), 1000)])
+            .with_max_queue_wait(Duration::from_millis(50));
+
+        let throttled = limiter.observe_response(429, &headers(&[("retry-after", "1")]));
+        assert_eq!(throttled, Some(RateLimitError::Throttled { retry_after: Duration::from_secs(1) }));
+        assert!(limiter.is_blocked());
+        // 退避时间超过排队上限，请求被拒绝
+        assert!(matches!(limiter.acquire(RequestCost::weight(1)).await, Err(RateLimitError::Throttled { .. })));
+
+        assert!(!init_error_tracker().is_circuit_open(Exchange::Hbit));
+        let banned = limiter.observe_response(418, &headers(&[("retry-after", "2")]));
+        assert_eq!(banned, Some(RateLimitError::Banned { retry_after: Duration::from_secs(2) }));
+        assert!(init_error_tracker().is_circuit_open(Exchange::Hbit));
+        assert!(matches!(limiter.acquire(RequestCost::weight(1)).await, Err(RateLimitError::Banned { .. })));
+
+        let error: AppError = RateLimitError::Banned { retry_after: Duration::from_secs(2) }.into();
+        assert_eq!(ErrorCategory::from(&error), ErrorCategory::RateLimitExceeded);
+    }
+}
@@ -136,6 +136,8 @@ pub enum ErrorType {
     ResourceExhaustion,
     /// 协议错误
     ProtocolError,
+    /// 数据校验失败（如订单簿校验和不一致）
+    ChecksumMismatch,
 }
 
 /// 错误严重程度
@@ -467,6 +469,7 @@ impl SmartErrorRecovery {
             ErrorType::RateLimitError => RecoveryStrategy::ExponentialBackoffRetry,
             ErrorType::ServerError => RecoveryStrategy::SwitchServer,
             ErrorType::ResourceExhaustion => RecoveryStrategy::ClearCache,
+            ErrorType::ChecksumMismatch => RecoveryStrategy::Resubscribe,
             _ => self.select_default_strategy(error_type),
         }
     }
@@ -486,6 +489,7 @@ impl SmartErrorRecovery {
             ErrorType::ResourceExhaustion => RecoveryStrategy::ClearCache,
             ErrorType::ProtocolError => RecoveryStrategy::Reconnect,
             ErrorType::UnknownError => RecoveryStrategy::DelayedRetry,
+            ErrorType::ChecksumMismatch => RecoveryStrategy::Resubscribe,
         }
     }
 
@@ -766,6 +770,8 @@ impl SmartErrorRecovery {
             RecoveryStrategy::ExponentialBackoffRetry
         } else if pattern.contains(&ErrorType::AuthenticationError) {
             RecoveryStrategy::Reauthenticate
+        } else if pattern.contains(&ErrorType::ChecksumMismatch) {
+            RecoveryStrategy::Resubscribe
         } else {
             RecoveryStrategy::DelayedRetry
         }
@@ -8,6 +8,9 @@ use log::{debug, warn, error};
 use serde::{Deserialize, Serialize};
 use regex::Regex;
 
+use crate::exchange_types::Exchange;
+use crate::symbol_mapper::contract_multipliers;
+
 /// 符号转换器
 /// 负责在不同交易所之间转换交易对符号格式
 #[derive(Debug, Clone)]
@@ -87,6 +90,13 @@ pub struct SymbolInfo {
     pub normalized_symbol: String,
     /// 检测到的格式
     pub detected_format: SymbolFormat,
+    /// 合约乘数：一个报价单位包含多少个基础货币（1000PEPEUSDT 为 1000）
+    #[serde(default = "default_contract_multiplier")]
+    pub contract_multiplier: f64,
+}
+
+fn default_contract_multiplier() -> f64 {
+    1.0
 }
 
 /// 转换结果
@@ -277,6 +287,28 @@ impl SymbolConverter {
             original_symbol: symbol.to_string(),
             normalized_symbol,
             detected_format: format,
+            contract_multiplier: 1.0,
+        })
+    }
+
+    /// 解析某交易所的具体品种，按合约乘数表换算到规范交易对
+    ///
+    /// 例如 BINANCE_FUTURES 的 `1000PEPEUSDT` 解析为 `PEPE_USDT`，合约乘数 1000；
+    /// 不在乘数表中的品种与 `parse_symbol` 结果相同。
+    pub async fn parse_listing(&self, exchange: Exchange, symbol: &str) -> Result<SymbolInfo, ConversionError> {
+        let listing = contract_multipliers().get(exchange, symbol).cloned();
+
+        let Some(listing) = listing else {
+            return self.parse_symbol(symbol).await;
+        };
+
+        Ok(SymbolInfo {
+            normalized_symbol: format!("{}_{}", listing.base, listing.quote),
+            base_currency: listing.base,
+            quote_currency: listing.quote,
+            original_symbol: symbol.to_string(),
+            detected_format: self.detect_format(symbol).await?,
+            contract_multiplier: listing.multiplier,
         })
     }
 
@@ -604,6 +636,7 @@ pub struct CacheStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::symbol_mapper::contract_multipliers_mut;
 
     #[tokio::test]
     async fn test_symbol_converter_creation() {
@@ -704,6 +737,23 @@ mod tests {
         assert_eq!(result1.converted_symbol, result2.converted_symbol);
     }
 
+    #[tokio::test]
+    async fn test_parse_listing_applies_contract_multiplier() {
+        contract_multipliers_mut().insert(Exchange::BybitFutures, "1000BONKUSDT", "BONK", "USDT", 1000.0);
+        let converter = SymbolConverter::with_default_config();
+
+        let info = converter.parse_listing(Exchange::BybitFutures, "1000BONKUSDT").await.unwrap();
+        assert_eq!(info.normalized_symbol, "BONK_USDT");
+        assert_eq!(info.base_currency, "BONK");
+        assert_eq!(info.original_symbol, "1000BONKUSDT");
+        assert_eq!(info.contract_multiplier, 1000.0);
+
+        // 其他交易所的同名品种不受影响
+        let info = converter.parse_listing(Exchange::OkxFutures, "BONK-USDT").await.unwrap();
+        assert_eq!(info.normalized_symbol, "BONK_USDT");
+        assert_eq!(info.contract_multiplier, 1.0);
+    }
+
     #[tokio::test]
     async fn test_validate_symbol() {
         let converter = SymbolConverter::with_default_config();
@@ -0,0 +1,68 @@
+//! 连接器工厂
+//! 根据config.toml中的 `[exchanges.*]` 配置块创建对应的连接器
+
+use log::warn;
+use std::sync::Arc;
+
+use crate::config::ExchangeConfig;
+use crate::core::AppState;
+use crate::connectors::traits::ExchangeConnector;
+use crate::connectors::binance::{config::BinanceConfig, BinanceAdapter, BinanceFuturesConnector};
+use crate::connectors::binance::futures::BinanceFuturesConfig;
+use crate::connectors::bybit::BybitConnector;
+use crate::connectors::okx::OkxConnector;
+use crate::connectors::lbank::LBankConnector;
+use crate::connectors::phemex::PhemexConnector;
+use crate::connectors::xtcom::XtComConnector;
+use crate::connectors::tapbit::TapBitConnector;
+use crate::connectors::hbit::HbitConnector;
+use crate::connectors::batonex::BatonexConnector;
+use crate::connectors::coincatch::CoinCatchConnector;
+use crate::types::errors::ConnectorError;
+
+/// 按配置名创建连接器
+///
+/// `name` 为 `[exchanges.NAME]` 中的名称（PHEMEX、LBANK、BYBIT_FUTURES等），
+/// 没有对应实现的名称返回 `Ok(None)`。
+pub async fn build_connector(
+    name: &str,
+    exchange_config: &ExchangeConfig,
+    app_state: Arc<AppState>,
+) -> Result<Option<Box<dyn ExchangeConnector>>, ConnectorError> {
+    let connector: Box<dyn ExchangeConnector> = match name.to_uppercase().as_str() {
+        "PHEMEX" => Box::new(PhemexConnector::from_exchange_config(exchange_config, app_state)),
+        "XTCOM" => Box::new(XtComConnector::from_exchange_config(exchange_config, app_state)),
+        "TAPBIT" => Box::new(TapBitConnector::from_exchange_config(exchange_config, app_state)),
+        "HBIT" => Box::new(HbitConnector::from_exchange_config(exchange_config, app_state)),
+        "BATONEX" => Box::new(BatonexConnector::from_exchange_config(exchange_config, app_state)),
+        "COINCATCH" => Box::new(CoinCatchConnector::from_exchange_config(exchange_config, app_state)),
+        "LBANK" => Box::new(LBankConnector::new(exchange_config.to_connector_config(), app_state)),
+        "BYBIT_FUTURES" => Box::new(BybitConnector::new(exchange_config.to_connector_config(), app_state)),
+        "OKX_FUTURES" => Box::new(OkxConnector::new(exchange_config.to_connector_config(), app_state)),
+        "BINANCE" => {
+            let config = BinanceConfig {
+                api_key: exchange_config.api_key.clone(),
+                secret_key: exchange_config.api_secret.clone(),
+                websocket_url: Some(exchange_config.websocket_url.clone()),
+                rest_api_url: exchange_config.api_url.clone(),
+                ..BinanceConfig::default()
+            };
+            Box::new(BinanceAdapter::new(config, app_state).await?)
+        }
+        "BINANCE_FUTURES" => {
+            let config = BinanceFuturesConfig {
+                api_key: exchange_config.api_key.clone(),
+                secret_key: exchange_config.api_secret.clone(),
+                rest_timeout: exchange_config.connection_timeout_secs,
+                ..BinanceFuturesConfig::default()
+            };
+            Box::new(BinanceFuturesConnector::new(config))
+        }
+        other => {
+            warn!("[ConnectorFactory] 没有 {other} 的连接器实现，跳过");
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(connector))
+}
@@ -0,0 +1,14 @@
+//! Hbit连接器模块
+//! USDT永续合约行情（market.*.depth / market.*.trade.detail 频道）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::HbitProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// Hbit行情连接器
+pub type HbitConnector = MarketStreamConnector<HbitProtocol>;
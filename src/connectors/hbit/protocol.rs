@@ -0,0 +1,110 @@
+//! Hbit合约行情协议
+//! 订阅格式：`{"sub":"market.btcusdt.depth.step0","id":"1"}`，每条请求只能订阅一个主题；
+//! 深度每次推送完整的前N档；心跳由服务器发起 `{"ping":ts}`，客户端回复 `{"pong":ts}`。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::market_stream::{
+    build_orderbook, value_to_f64, value_to_i64, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// Hbit合约WebSocket默认地址
+pub const HBIT_WS_URL: &str = "wss://fapi.hibt0.com/v2/ws";
+
+/// Hbit合约行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HbitProtocol;
+
+impl HbitProtocol {
+    fn topic(channel: MarketChannel, symbol: &str) -> String {
+        match channel {
+            MarketChannel::OrderBook => format!("market.{symbol}.depth.step0"),
+            MarketChannel::Trades => format!("market.{symbol}.trade.detail"),
+        }
+    }
+}
+
+impl MarketStreamProtocol for HbitProtocol {
+    const NAME: &'static str = "Hbit";
+    const DEFAULT_WS_URL: &'static str = HBIT_WS_URL;
+    const EXCHANGE: Exchange = Exchange::Hbit;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::Hbit;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+    const MAX_SYMBOLS_PER_REQUEST: usize = 1;
+
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}{}", info.base_currency, info.quote_currency).to_lowercase()
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        exchange_symbol.to_uppercase()
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], request_id: u64) -> String {
+        let topic = symbols.first().map(|symbol| Self::topic(channel, symbol)).unwrap_or_default();
+        let action = if subscribe { "sub" } else { "unsub" };
+        json!({
+            action: topic,
+            "id": request_id.to_string(),
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        None
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析Hbit消息: {e}")))?;
+
+        if let Some(ping) = msg.get("ping") {
+            return Ok(vec![MarketEvent::Reply(json!({"pong": ping}).to_string())]);
+        }
+
+        let Some(channel) = msg.get("ch").and_then(|c| c.as_str()) else {
+            if msg.get("status").and_then(|s| s.as_str()) == Some("error") {
+                return Ok(vec![MarketEvent::Error(msg.to_string())]);
+            }
+            return Ok(vec![MarketEvent::Other(msg)]);
+        };
+
+        // market.btcusdt.depth.step0 / market.btcusdt.trade.detail
+        let mut parts = channel.split('.');
+        let symbol = match (parts.next(), parts.next()) {
+            (Some("market"), Some(symbol)) => Self::standard_symbol(symbol),
+            _ => return Ok(vec![MarketEvent::Other(msg)]),
+        };
+        let tick = &msg["tick"];
+
+        match parts.next() {
+            Some("depth") => {
+                let timestamp = tick.get("ts").or(msg.get("ts")).and_then(value_to_i64).unwrap_or(0);
+                Ok(vec![MarketEvent::Book(build_orderbook(Self::EXCHANGE, symbol, tick.get("bids"), tick.get("asks"), timestamp))])
+            }
+            Some("trade") => {
+                let trades = tick.get("data").and_then(|d| d.as_array()).map(Vec::as_slice).unwrap_or_default()
+                    .iter()
+                    .filter_map(|trade| {
+                        Some(StandardizedTrade {
+                            symbol: symbol.clone(),
+                            exchange: Self::EXCHANGE_TYPE,
+                            price: value_to_f64(trade.get("price")?)?,
+                            quantity: value_to_f64(trade.get("amount")?)?,
+                            side: if trade.get("direction")?.as_str()? == "sell" { TradeSide::Sell } else { TradeSide::Buy },
+                            timestamp: value_to_i64(trade.get("ts")?)?,
+                            trade_id: trade.get("id").map(|id| id.as_str().map(str::to_string).unwrap_or_else(|| id.to_string())).unwrap_or_default(),
+                        })
+                    })
+                    .collect();
+                Ok(vec![MarketEvent::Trades(trades)])
+            }
+            _ => Ok(vec![MarketEvent::Other(msg)]),
+        }
+    }
+}
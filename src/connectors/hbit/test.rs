@@ -0,0 +1,80 @@
+//! Hbit连接器测试模块
+//! 使用录制的推送帧测试协议解析、服务器心跳回复和符号转换
+
+mod tests {
+    use super::super::{HbitConnector, HbitProtocol};
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::core::AppState;
+    use crate::types::{config::ConnectorConfig, market_data::TradeSide};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// 录制的Hbit深度帧
+    const RECORDED_DEPTH_FRAME: &str = r#"{"ch":"market.btcusdt.depth.step0","ts":1710755362730,"tick":{"bids":[[64011.9,5.321],[64011.5,0.78]],"asks":[[64012.4,1.532],[64012.9,0.2]],"ts":1710755362722,"version":1710755362}}"#;
+
+    /// 录制的Hbit成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"ch":"market.btcusdt.trade.detail","ts":1710755363466,"tick":{"id":1710755363,"ts":1710755363460,"data":[{"id":102938475610,"price":64011.9,"amount":0.015,"direction":"sell","ts":1710755363460}]}}"#;
+
+    /// 录制的Hbit服务器心跳、订阅确认和错误
+    const RECORDED_PING_FRAME: &str = r#"{"ping":1710755362000}"#;
+    const RECORDED_SUBSCRIBE_ACK_FRAME: &str = r#"{"id":"1","status":"ok","subbed":"market.btcusdt.depth.step0","ts":1710755361000}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"id":"2","status":"error","err-code":"bad-request","err-msg":"invalid topic","ts":1710755361000}"#;
+
+    #[test]
+    fn test_hbit_parse_recorded_frames() {
+        let events = HbitProtocol::parse_message(RECORDED_DEPTH_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Book(orderbook)] => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 64011.9);
+                assert_eq!(orderbook.best_ask, 64012.4);
+                assert_eq!(orderbook.timestamp, 1710755362722);
+            }
+            other => panic!("应该解析为深度: {other:?}"),
+        }
+
+        let events = HbitProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades.len(), 1);
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[0].quantity, 0.015);
+                assert_eq!(trades[0].trade_id, "102938475610");
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        // 服务器心跳必须原样回复pong
+        match &HbitProtocol::parse_message(RECORDED_PING_FRAME).unwrap()[..] {
+            [MarketEvent::Reply(reply)] => {
+                let reply: Value = serde_json::from_str(reply).unwrap();
+                assert_eq!(reply["pong"], 1710755362000i64);
+            }
+            other => panic!("应该回复pong: {other:?}"),
+        }
+
+        assert!(matches!(&HbitProtocol::parse_message(RECORDED_SUBSCRIBE_ACK_FRAME).unwrap()[..], [MarketEvent::Other(_)]));
+        assert!(matches!(&HbitProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+        assert!(HbitProtocol::ping_message().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hbit_symbol_conversion_and_subscription() {
+        let connector = HbitConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        for symbol in ["BTCUSDT", "BTC_USDT", "HBIT:BTC/USDT"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "btcusdt");
+        }
+
+        // 每条请求只能订阅一个主题，配置的batch_size不生效
+        assert_eq!(connector.handler().batch_size(), 1);
+        let symbols = vec!["btcusdt".to_string(), "ethusdt".to_string()];
+        let requests = connector.handler().subscription_requests(true, MarketChannel::OrderBook, &symbols, 5);
+        assert_eq!(requests.len(), 2);
+
+        let first: Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(first["sub"], "market.btcusdt.depth.step0");
+        let unsub: Value = serde_json::from_str(&HbitProtocol::subscription_message(false, MarketChannel::Trades, &symbols[1..], 3)).unwrap();
+        assert_eq!(unsub["unsub"], "market.ethusdt.trade.detail");
+        assert_eq!(unsub["id"], "3");
+    }
+}
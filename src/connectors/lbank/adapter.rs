@@ -7,9 +7,11 @@ use chrono;
 
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{RwLock, mpsc, broadcast};
+use tokio::sync::{RwLock, broadcast};
 
 use crate::core::AppState;
+use crate::config::get_config;
+use crate::connectors::common::data_channel::{data_channel, ChannelSendError, DataReceiver, DataSender};
 use crate::connectors::traits::{ExchangeConnector, DataFlowManager};
 use crate::connectors::common::{
     emergency_ping::EmergencyPingManager,
@@ -24,9 +26,10 @@ use crate::types::{
     account::{AccountBalance},
     exchange::{ExchangeType, MarketType},
     errors::ConnectorError,
-    events::{SystemEvent, HighFrequencyData},
+    events::{SystemEvent, HighFrequencyData, DataFlowStats},
 };
 use crate::exchange_types::Exchange;
+use crate::utils::ensure_exchange_prefix;
 use super::websocket::LBankWebSocketHandler;
 
 /// LBank连接器
@@ -36,9 +39,8 @@ pub struct LBankConnector {
     config: ConnectorConfig,
     app_state: Arc<AppState>,
     websocket_handler: LBankWebSocketHandler,
-    status: Arc<RwLock<ConnectionStatus>>,
-    market_data_sender: Arc<RwLock<Option<mpsc::UnboundedSender<StandardizedMessage>>>>,
-    user_data_sender: Arc<RwLock<Option<mpsc::UnboundedSender<StandardizedMessage>>>>,
+    market_data_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    user_data_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
     event_sender: Arc<RwLock<Option<broadcast::Sender<SystemEvent>>>>,
     // WebSocket优化模块
     emergency_ping_manager: Arc<RwLock<EmergencyPingManager>>,
@@ -49,13 +51,12 @@ pub struct LBankConnector {
 impl LBankConnector {
     /// 创建新的LBank连接器实例
     pub fn new(config: ConnectorConfig, app_state: Arc<AppState>) -> Self {
-        let websocket_handler = LBankWebSocketHandler::new(app_state.clone());
+        let websocket_handler = LBankWebSocketHandler::new(&config, app_state.clone());
         
         Self {
             config,
             app_state,
             websocket_handler,
-            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
             market_data_sender: Arc::new(RwLock::new(None)),
             user_data_sender: Arc::new(RwLock::new(None)),
             event_sender: Arc::new(RwLock::new(None)),
@@ -97,16 +98,9 @@ impl ExchangeConnector for LBankConnector {
             timeout_manager.reset().await;
         }
         
-        // 更新连接状态为已连接（模拟连接成功）
-        {
-            let mut status = self.status.write().await;
-            *status = ConnectionStatus::Connected;
-        }
-        
-        // 启动优化模块的监控任务
-        {
-            // 超时管理器已初始化，无需额外启动
-        }
+        // 建立真实的WebSocket连接，断线重连由处理器在后台维护
+        self.websocket_handler.start().await
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("Failed to connect LBank WebSocket: {e}")))?;
         
         info!("LBank WebSocket connected successfully，优化模块已启动");
         Ok(())
@@ -115,11 +109,8 @@ impl ExchangeConnector for LBankConnector {
     async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
         info!("Disconnecting from LBank WebSocket");
         
-        // 更新连接状态
-        {
-            let mut status = self.status.write().await;
-            *status = ConnectionStatus::Disconnected;
-        }
+        self.websocket_handler.stop().await
+            .map_err(|e| ConnectorError::ConnectionError(format!("Failed to disconnect LBank WebSocket: {e}")))?;
         
         info!("LBank WebSocket disconnected successfully");
         Ok(())
@@ -129,8 +120,7 @@ impl ExchangeConnector for LBankConnector {
         info!("Subscribing to orderbook for symbol: {}", symbol);
         
         // 检查连接状态
-        let status = self.status.read().await;
-        if *status != ConnectionStatus::Connected {
+        if !self.websocket_handler.is_connected().await {
             return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
         }
         
@@ -145,8 +135,14 @@ impl ExchangeConnector for LBankConnector {
     async fn subscribe_trades(&self, symbol: &str) -> Result<(), ConnectorError> {
         info!("Subscribing to trades for symbol: {}", symbol);
         
-        // LBank WebSocket主要提供订单簿数据，交易数据订阅暂不支持
-        warn!("Trade subscription not implemented for LBank");
+        if !self.websocket_handler.is_connected().await {
+            return Err(ConnectorError::ConnectionLost("WebSocket not connected".to_string()));
+        }
+        
+        self.websocket_handler.subscribe_trades(vec![symbol.to_string()]).await
+            .map_err(|e| ConnectorError::SubscriptionFailed(format!("Failed to subscribe to trades: {e}")))?;
+        
+        info!("Successfully subscribed to trades for {}", symbol);
         Ok(())
     }
     
@@ -159,27 +155,40 @@ impl ExchangeConnector for LBankConnector {
     }
     
     // 推送式数据流接口
-    fn get_market_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
-        let (_sender, receiver) = mpsc::unbounded_channel();
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.market_data_channel);
         
-        // 将发送器存储起来，以便后续使用
-        tokio::spawn(async move {
-            // 这里应该从WebSocket处理器获取数据并转发
-            // 目前返回一个空的接收器
-        });
+        // WebSocket处理器直接向该通道推送标准化消息
+        if !self.websocket_handler.try_set_message_sender(sender) {
+            warn!("[LBank] 消息发送器正被占用，返回的数据流不会收到消息");
+        }
         
         receiver
     }
     
-    fn get_user_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage> {
-        let (_, receiver) = mpsc::unbounded_channel();
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (_, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
         receiver
     }
     
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        if let Some(channel) = self.websocket_handler.channel_stats() {
+            channel.apply_to(&mut stats);
+        }
+        stats
+    }
+    
     // 本地缓存快照读取
     async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
-        // 从app_state中获取最新的订单簿数据
-        if let Some(price_data) = self.app_state.price_data.get(symbol) {
+        // 优先使用WebSocket处理器维护的本地快照
+        if let Some(orderbook) = self.websocket_handler.get_orderbook(symbol) {
+            return Some(orderbook);
+        }
+        
+        // 回退到app_state中的最新价格数据
+        let key = ensure_exchange_prefix(symbol, "LBANK");
+        if let Some(price_data) = self.app_state.price_data.get(&key) {
             let data = price_data.value();
             
             Some(StandardizedOrderBook {
@@ -196,9 +205,8 @@ impl ExchangeConnector for LBankConnector {
         }
     }
     
-    async fn get_recent_trades_snapshot(&self, _symbol: &str, _limit: usize) -> Vec<StandardizedTrade> {
-        // LBank连接器暂不支持交易数据
-        Vec::new()
+    async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.websocket_handler.get_recent_trades(symbol, limit)
     }
     
     // 交易相关操作 (REST API)
@@ -221,16 +229,15 @@ impl ExchangeConnector for LBankConnector {
     // 连接状态
     async fn is_connected(&self) -> bool {
         // 检查WebSocket处理器的连接状态
-        self.websocket_handler.is_connected()
+        self.websocket_handler.is_connected().await
     }
     
     async fn is_websocket_connected(&self) -> bool {
-        self.websocket_handler.is_connected()
+        self.websocket_handler.is_connected().await
     }
     
     async fn get_connection_status(&self) -> ConnectionStatus {
-        let status = self.status.read().await;
-        *status
+        self.websocket_handler.get_connection_status().await
     }
     
     // WebSocket优化功能实现
@@ -273,7 +280,7 @@ impl ExchangeConnector for LBankConnector {
         let ping_manager = self.emergency_ping_manager.read().await;
         if ping_manager.should_send_emergency_ping().await {
             // 执行ping操作
-            if self.websocket_handler.is_connected() {
+            if self.websocket_handler.is_connected().await {
                 // 这里可以发送ping消息或执行健康检查
                 info!("[LBank] 紧急ping执行成功");
                 Ok(Duration::from_millis(60))
@@ -322,7 +329,7 @@ impl ExchangeConnector for LBankConnector {
 #[async_trait]
 impl DataFlowManager for LBankConnector {
     // 高频数据流管理
-    fn take_market_data_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<HighFrequencyData>> {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
         // 暂不实现高频数据流
         None
     }
@@ -332,7 +339,7 @@ impl DataFlowManager for LBankConnector {
         receiver
     }
     
-    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), mpsc::error::SendError<HighFrequencyData>> {
+    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
         // 暂不实现高频数据发送
         Ok(())
     }
@@ -359,7 +366,8 @@ impl LBankConnector {
     }
     
     /// 设置消息发送器
-    pub async fn set_message_sender(&mut self, sender: mpsc::UnboundedSender<StandardizedMessage>) {
+    pub async fn set_message_sender(&mut self, sender: DataSender<StandardizedMessage>) {
+        self.websocket_handler.set_message_sender(sender.clone()).await;
         let mut market_data_sender = self.market_data_sender.write().await;
         *market_data_sender = Some(sender);
     }
@@ -1,69 +1,254 @@
 //! LBank WebSocket处理器
-//! 包装现有的LBank WebSocket处理函数
+//! 实现LBank V2 WebSocket协议：深度/成交订阅、ping/pong心跳以及断线重连
 
-use crate::core::{AppState, AppError};
-// use crate::network::lbank_websocket::lbank_websocket_handler; // 已移除network模块
-use crate::types::market_data::StandardizedMessage;
-use log::{info, error};
+use crate::core::{AppState, AppError, OrderbookUpdate};
+use crate::connectors::common::data_channel::{ChannelStats, DataSender};
+use crate::connectors::common::symbol_converter::SymbolConverter;
+use crate::exchange_types::Exchange;
+use crate::types::config::{ConnectorConfig, ConnectionStatus};
+use crate::types::exchange::ExchangeType;
+use crate::types::market_data::{StandardizedMessage, StandardizedOrderBook, StandardizedTrade, TradeSide};
+use chrono::{NaiveDateTime, Utc};
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn, error, debug};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// LBank V2 WebSocket默认地址
+pub const LBANK_WS_URL: &str = "wss://www.lbkex.net/ws/V2/";
+
+/// 默认订阅的深度档位
+const DEFAULT_DEPTH_LEVELS: u32 = 50;
+
+/// 每个交易对保留的最近成交条数
+const RECENT_TRADES_CAPACITY: usize = 200;
+
+/// LBank服务器时间（TS字段）使用的时区偏移（UTC+8）
+const LBANK_TS_OFFSET_MS: i64 = 8 * 60 * 60 * 1000;
+
+type LBankWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// LBank订阅频道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LBankChannel {
+    /// 深度快照推送
+    Depth,
+    /// 逐笔成交推送
+    Trade,
+}
+
+impl LBankChannel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LBankChannel::Depth => "depth",
+            LBankChannel::Trade => "trade",
+        }
+    }
+}
+
+/// 解析后的LBank消息帧
+#[derive(Debug, Clone)]
+pub enum LBankFrame {
+    /// 服务器心跳，需要回复相同id的pong
+    Ping(String),
+    /// 服务器对客户端ping的回应
+    Pong(String),
+    /// 深度快照
+    Depth(StandardizedOrderBook),
+    /// 成交记录
+    Trade(StandardizedTrade),
+    /// 订阅确认、错误等其他消息
+    Other(Value),
+}
+
+/// 连接会话结束原因
+enum SessionEnd {
+    /// 调用方主动停止
+    Shutdown,
+    /// 连接断开或被要求重连
+    Lost,
+}
 
 /// LBank WebSocket处理器
-/// 包装现有的lbank_websocket_handler函数
 #[derive(Clone)]
 pub struct LBankWebSocketHandler {
     app_state: Arc<AppState>,
-    message_sender: Arc<RwLock<Option<mpsc::UnboundedSender<StandardizedMessage>>>>,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    ws_url: String,
+    ping_interval: Duration,
+    reconnect_interval: Duration,
+    max_reconnect_attempts: u32,
+    connect_timeout: Duration,
+    connection_id: String,
+    status: Arc<RwLock<ConnectionStatus>>,
+    should_run: Arc<RwLock<bool>>,
+    command_sender: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    subscriptions: Arc<RwLock<HashSet<(LBankChannel, String)>>>,
+    orderbooks: Arc<DashMap<String, StandardizedOrderBook>>,
+    recent_trades: Arc<DashMap<String, VecDeque<StandardizedTrade>>>,
+    symbol_converter: SymbolConverter,
 }
 
 impl LBankWebSocketHandler {
     /// 创建新的LBank WebSocket处理器
-    pub fn new(app_state: Arc<AppState>) -> Self {
+    pub fn new(config: &ConnectorConfig, app_state: Arc<AppState>) -> Self {
+        let connection_id = "lbank-1".to_string();
+        // 行情连接登记到AppState，套利扫描器发现订单簿持续过期时据此发出重连信号
+        app_state.register_market_connection(Exchange::LBank, &connection_id);
+
         Self {
             app_state,
             message_sender: Arc::new(RwLock::new(None)),
+            ws_url: config.websocket_url.clone().unwrap_or_else(|| LBANK_WS_URL.to_string()),
+            ping_interval: Duration::from_millis(config.ping_interval.max(1)),
+            reconnect_interval: Duration::from_millis(config.reconnect_interval),
+            max_reconnect_attempts: config.max_reconnect_attempts,
+            connect_timeout: Duration::from_millis(config.request_timeout.max(1)),
+            connection_id,
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            should_run: Arc::new(RwLock::new(false)),
+            command_sender: Arc::new(RwLock::new(None)),
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            orderbooks: Arc::new(DashMap::new()),
+            recent_trades: Arc::new(DashMap::new()),
+            symbol_converter: SymbolConverter::with_default_config(),
         }
     }
 
     /// 设置消息发送器
-    pub async fn set_message_sender(&self, sender: mpsc::UnboundedSender<StandardizedMessage>) {
+    pub async fn set_message_sender(&self, sender: DataSender<StandardizedMessage>) {
         let mut message_sender = self.message_sender.write().await;
         *message_sender = Some(sender);
     }
 
+    /// 同步设置消息发送器（用于非异步上下文，例如获取数据流）
+    pub fn try_set_message_sender(&self, sender: DataSender<StandardizedMessage>) -> bool {
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => {
+                *message_sender = Some(sender);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 当前消息通道的计数快照
+    pub fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
     /// 启动WebSocket连接
+    ///
+    /// 首次连接失败直接返回错误；连接建立后在后台维护心跳和断线重连。
     pub async fn start(&self) -> Result<(), AppError> {
-        info!("Starting LBank WebSocket handler");
-        
-        // TODO: 实现新的LBank WebSocket连接逻辑
-        // 原有的lbank_websocket_handler已随network模块一起移除
-        info!("LBank WebSocket handler - 待重构实现");
+        {
+            let status = self.status.read().await;
+            if matches!(*status, ConnectionStatus::Connected | ConnectionStatus::Connecting | ConnectionStatus::Reconnecting) {
+                debug!("[LBank] {} 连接已在运行，跳过启动", self.connection_id);
+                return Ok(());
+            }
+        }
+
+        info!("[LBank] {} 连接到 {}", self.connection_id, self.ws_url);
+        *self.status.write().await = ConnectionStatus::Connecting;
+        *self.should_run.write().await = true;
+
+        let ws_stream = match Self::open(&self.ws_url, self.connect_timeout).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Error;
+                *self.should_run.write().await = false;
+                return Err(e);
+            }
+        };
+
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let command_tx_owner = command_tx.clone();
+        *self.command_sender.write().await = Some(command_tx);
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.app_state.update_connection_timestamp(&self.connection_id);
+        self.app_state.clear_reconnect_signal(&self.connection_id);
+
+        let handler = self.clone();
+        tokio::spawn(async move {
+            handler.connection_loop(ws_stream, command_tx_owner, command_rx).await;
+        });
+
+        info!("[LBank] {} WebSocket连接成功", self.connection_id);
         Ok(())
     }
 
-    /// 订阅市场数据
-    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
-        info!("Subscribing to {} symbols on LBank", symbols.len());
-        
-        // 在测试环境中，我们不启动真实的WebSocket连接
-        // 只是模拟订阅成功
-        info!("LBank subscription simulated for symbols: {symbols:?}");
+    /// 停止WebSocket连接，不再重连
+    pub async fn stop(&self) -> Result<(), AppError> {
+        *self.should_run.write().await = false;
+
+        if let Some(sender) = self.command_sender.write().await.take() {
+            let _ = sender.send(Message::Close(None));
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.app_state.mark_connection_unhealthy(&self.connection_id);
+        info!("[LBank] {} WebSocket已停止", self.connection_id);
         Ok(())
     }
 
-    /// 取消订阅
-    pub async fn unsubscribe(&self, _symbols: Vec<String>) -> Result<(), AppError> {
-        // 注意：现有的lbank_websocket_handler不支持动态取消订阅
-        // 这里只是一个占位符实现
-        info!("Unsubscribe requested for LBank (not implemented in legacy handler)");
+    /// 订阅深度数据
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_channel(LBankChannel::Depth, symbols).await
+    }
+
+    /// 订阅成交数据
+    pub async fn subscribe_trades(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_channel(LBankChannel::Trade, symbols).await
+    }
+
+    /// 取消订阅交易对的全部频道
+    pub async fn unsubscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        for symbol in symbols {
+            let pair = self.to_lbank_pair(&symbol).await?;
+            for channel in [LBankChannel::Depth, LBankChannel::Trade] {
+                let removed = self.subscriptions.write().await.remove(&(channel, pair.clone()));
+                if removed {
+                    self.send_command(Self::subscription_message("unsubscribe", channel, &pair)).await?;
+                }
+            }
+            info!("[LBank] 已取消订阅: {pair}");
+        }
         Ok(())
     }
 
     /// 检查连接状态
-    pub fn is_connected(&self) -> bool {
-        // 通过检查连接健康状态来判断是否连接
-        // 这里使用一个通用的连接ID
-        self.app_state.is_connection_healthy("lbank-1")
+    pub async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    /// 获取当前连接状态
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 获取当前的订阅列表 (频道, LBank交易对)
+    pub async fn get_subscriptions(&self) -> Vec<(LBankChannel, String)> {
+        self.subscriptions.read().await.iter().cloned().collect()
+    }
+
+    /// 获取本地缓存的最新深度快照
+    pub fn get_orderbook(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.orderbooks.get(&Self::normalize_symbol(symbol)).map(|entry| entry.value().clone())
+    }
+
+    /// 获取本地缓存的最近成交（按时间从新到旧）
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.recent_trades
+            .get(&Self::normalize_symbol(symbol))
+            .map(|trades| trades.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
     }
 
     /// 获取连接统计信息
@@ -72,4 +257,421 @@ impl LBankWebSocketHandler {
         let updates = self.app_state.price_updates.load(std::sync::atomic::Ordering::Relaxed);
         (messages, updates)
     }
-}
\ No newline at end of file
+
+    /// 解析一条LBank V2文本消息
+    pub fn parse_message(text: &str) -> Result<LBankFrame, AppError> {
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("解析LBank消息失败: {e}")))?;
+
+        match msg.get("action").and_then(|a| a.as_str()) {
+            Some("ping") => {
+                let id = msg.get("ping").and_then(|p| p.as_str()).unwrap_or_default();
+                return Ok(LBankFrame::Ping(id.to_string()));
+            }
+            Some("pong") => {
+                let id = msg.get("pong").and_then(|p| p.as_str()).unwrap_or_default();
+                return Ok(LBankFrame::Pong(id.to_string()));
+            }
+            _ => {}
+        }
+
+        let pair = msg.get("pair").and_then(|p| p.as_str()).unwrap_or_default();
+        match msg.get("type").and_then(|t| t.as_str()) {
+            Some("depth") => Self::parse_depth(&msg, pair).map(LBankFrame::Depth),
+            Some("trade") => Self::parse_trade(&msg, pair).map(LBankFrame::Trade),
+            _ => Ok(LBankFrame::Other(msg)),
+        }
+    }
+
+    /// 解析深度快照
+    fn parse_depth(msg: &Value, pair: &str) -> Result<StandardizedOrderBook, AppError> {
+        let depth = msg.get("depth")
+            .ok_or_else(|| AppError::ParseError("LBank深度消息缺少depth字段".to_string()))?;
+
+        let depth_bids = Self::parse_levels(depth.get("bids"));
+        let depth_asks = Self::parse_levels(depth.get("asks"));
+
+        Ok(StandardizedOrderBook {
+            symbol: Self::pair_to_symbol(pair),
+            exchange: Exchange::LBank,
+            best_bid: depth_bids.first().map(|(p, _)| *p).unwrap_or(0.0),
+            best_ask: depth_asks.first().map(|(p, _)| *p).unwrap_or(0.0),
+            depth_bids,
+            depth_asks,
+            timestamp: Self::parse_timestamp(msg.get("TS")),
+        })
+    }
+
+    /// 解析成交记录
+    fn parse_trade(msg: &Value, pair: &str) -> Result<StandardizedTrade, AppError> {
+        let trade = msg.get("trade")
+            .ok_or_else(|| AppError::ParseError("LBank成交消息缺少trade字段".to_string()))?;
+
+        let price = trade.get("price").and_then(Self::value_to_f64)
+            .ok_or_else(|| AppError::ParseError("LBank成交消息缺少price".to_string()))?;
+        let quantity = trade.get("volume").and_then(Self::value_to_f64)
+            .ok_or_else(|| AppError::ParseError("LBank成交消息缺少volume".to_string()))?;
+        let side = match trade.get("direction").and_then(|d| d.as_str()) {
+            Some(direction) if direction.starts_with("sell") => TradeSide::Sell,
+            _ => TradeSide::Buy,
+        };
+        let timestamp = Self::parse_timestamp(trade.get("TS").or_else(|| msg.get("TS")));
+
+        Ok(StandardizedTrade {
+            symbol: Self::pair_to_symbol(pair),
+            exchange: ExchangeType::LBank,
+            price,
+            quantity,
+            side,
+            timestamp,
+            // LBank成交推送不带成交ID，使用交易对+时间+价格+数量组合
+            trade_id: format!("{pair}-{timestamp}-{price}-{quantity}"),
+        })
+    }
+
+    /// 解析价格档位，兼容数字和字符串两种格式
+    fn parse_levels(levels: Option<&Value>) -> Vec<(f64, f64)> {
+        levels
+            .and_then(|l| l.as_array())
+            .map(|levels| {
+                levels.iter()
+                    .filter_map(|level| {
+                        let level = level.as_array()?;
+                        let price = Self::value_to_f64(level.first()?)?;
+                        let quantity = Self::value_to_f64(level.get(1)?)?;
+                        Some((price, quantity))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn value_to_f64(value: &Value) -> Option<f64> {
+        value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok()))
+    }
+
+    /// 解析LBank的TS字段（UTC+8本地时间，如 2019-06-28T17:49:22.722）为UTC毫秒
+    fn parse_timestamp(ts: Option<&Value>) -> i64 {
+        ts.and_then(|t| t.as_str())
+            .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S%.f").ok())
+            .map(|t| t.and_utc().timestamp_millis() - LBANK_TS_OFFSET_MS)
+            .unwrap_or_else(|| Utc::now().timestamp_millis())
+    }
+
+    /// LBank交易对（btc_usdt）转换为标准符号（BTCUSDT）
+    fn pair_to_symbol(pair: &str) -> String {
+        pair.replace('_', "").to_uppercase()
+    }
+
+    /// 去掉交易所前缀并统一为标准符号
+    fn normalize_symbol(symbol: &str) -> String {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        let symbol = symbol.strip_prefix("LBANK_").unwrap_or(symbol);
+        Self::pair_to_symbol(symbol)
+    }
+
+    /// 标准符号转换为LBank交易对格式
+    async fn to_lbank_pair(&self, symbol: &str) -> Result<String, AppError> {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol);
+        let symbol = symbol.strip_prefix("LBANK_").unwrap_or(symbol);
+        self.symbol_converter
+            .convert_to_underscore_format(symbol)
+            .await
+            .map(|result| result.converted_symbol.to_lowercase())
+            .map_err(|e| AppError::ParseError(format!("无法转换LBank交易对 {symbol}: {e}")))
+    }
+
+    fn subscription_message(action: &str, channel: LBankChannel, pair: &str) -> Message {
+        let msg = match channel {
+            LBankChannel::Depth => json!({
+                "action": action,
+                "subscribe": channel.as_str(),
+                "depth": DEFAULT_DEPTH_LEVELS.to_string(),
+                "pair": pair,
+            }),
+            LBankChannel::Trade => json!({
+                "action": action,
+                "subscribe": channel.as_str(),
+                "pair": pair,
+            }),
+        };
+        Message::Text(msg.to_string())
+    }
+
+    async fn subscribe_channel(&self, channel: LBankChannel, symbols: Vec<String>) -> Result<(), AppError> {
+        info!("[LBank] 订阅{}频道: {} 个交易对", channel.as_str(), symbols.len());
+
+        for symbol in symbols {
+            let pair = self.to_lbank_pair(&symbol).await?;
+            let inserted = self.subscriptions.write().await.insert((channel, pair.clone()));
+            // 未连接时只记录订阅，连接建立后统一发送
+            if inserted && self.is_connected().await {
+                self.send_command(Self::subscription_message("subscribe", channel, &pair)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_command(&self, message: Message) -> Result<(), AppError> {
+        match self.command_sender.read().await.as_ref() {
+            Some(sender) => sender
+                .send(message)
+                .map_err(|e| AppError::WebSocketError(format!("LBank发送队列已关闭: {e}"))),
+            None => Ok(()),
+        }
+    }
+
+    async fn open(url: &str, connect_timeout: Duration) -> Result<LBankWsStream, AppError> {
+        let (ws_stream, _) = timeout(connect_timeout, connect_async(url))
+            .await
+            .map_err(|_| AppError::WebSocketError(format!("连接LBank WebSocket超时: {url}")))?
+            .map_err(|e| AppError::WebSocketError(format!("连接LBank WebSocket失败: {e}")))?;
+        Ok(ws_stream)
+    }
+
+    /// 连接维护循环：处理单次会话，断线后按配置重连并恢复订阅
+    async fn connection_loop(
+        self,
+        mut ws_stream: LBankWsStream,
+        command_tx: mpsc::UnboundedSender<Message>,
+        mut command_rx: mpsc::UnboundedReceiver<Message>,
+    ) {
+        loop {
+            if let Err(e) = self.resubscribe_all(&mut ws_stream).await {
+                warn!("[LBank] {} 恢复订阅失败: {e}", self.connection_id);
+            }
+
+            if let SessionEnd::Shutdown = self.run_session(ws_stream, &mut command_rx).await {
+                break;
+            }
+            if !*self.should_run.read().await {
+                break;
+            }
+
+            self.app_state.mark_connection_unhealthy(&self.connection_id);
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+
+            let mut reconnected = None;
+            for attempt in 1..=self.max_reconnect_attempts {
+                tokio::time::sleep(self.reconnect_interval).await;
+                if !*self.should_run.read().await {
+                    break;
+                }
+
+                info!("[LBank] {} 重连 (尝试 {}/{})", self.connection_id, attempt, self.max_reconnect_attempts);
+                match Self::open(&self.ws_url, self.connect_timeout).await {
+                    Ok(stream) => {
+                        reconnected = Some(stream);
+                        break;
+                    }
+                    Err(e) => warn!("[LBank] {} 重连失败: {e}", self.connection_id),
+                }
+            }
+
+            match reconnected {
+                Some(stream) => {
+                    ws_stream = stream;
+                    *self.status.write().await = ConnectionStatus::Connected;
+                    self.app_state.update_connection_timestamp(&self.connection_id);
+                    self.app_state.clear_reconnect_signal(&self.connection_id);
+                    info!("[LBank] {} 重连成功", self.connection_id);
+                }
+                None => {
+                    if *self.should_run.read().await {
+                        error!("[LBank] {} 重连次数耗尽，停止连接", self.connection_id);
+                        *self.status.write().await = ConnectionStatus::Error;
+                        *self.should_run.write().await = false;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // 只清理属于本次连接的状态，避免影响停止后重新启动的新连接
+        let mut command_sender = self.command_sender.write().await;
+        if command_sender.as_ref().is_some_and(|sender| sender.same_channel(&command_tx)) {
+            command_sender.take();
+            let mut status = self.status.write().await;
+            if *status != ConnectionStatus::Error {
+                *status = ConnectionStatus::Disconnected;
+            }
+        }
+        drop(command_sender);
+        info!("[LBank] {} 连接循环已退出", self.connection_id);
+    }
+
+    async fn resubscribe_all(&self, ws_stream: &mut LBankWsStream) -> Result<(), AppError> {
+        let subscriptions = self.get_subscriptions().await;
+        for (channel, pair) in subscriptions {
+            ws_stream
+                .send(Self::subscription_message("subscribe", channel, &pair))
+                .await
+                .map_err(|e| AppError::WebSocketError(format!("发送订阅失败: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// 运行单次连接会话，直到连接断开或收到停止指令
+    async fn run_session(&self, ws_stream: LBankWsStream, command_rx: &mut mpsc::UnboundedReceiver<Message>) -> SessionEnd {
+        let (mut write, mut read) = ws_stream.split();
+        let mut ping_timer = tokio::time::interval(self.ping_interval);
+        ping_timer.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => {
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            last_message = Instant::now();
+                            if let Some(reply) = self.handle_text(&text).await {
+                                if let Err(e) = write.send(reply).await {
+                                    error!("[LBank] {} 发送pong失败: {e}", self.connection_id);
+                                    return SessionEnd::Lost;
+                                }
+                            }
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            last_message = Instant::now();
+                            if let Err(e) = write.send(Message::Pong(payload)).await {
+                                error!("[LBank] {} 发送Pong失败: {e}", self.connection_id);
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("[LBank] {} WebSocket连接被服务器关闭", self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                        Some(Ok(_)) => {
+                            last_message = Instant::now();
+                        }
+                        Some(Err(e)) => {
+                            error!("[LBank] {} WebSocket错误: {e}", self.connection_id);
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+                command = command_rx.recv() => {
+                    match command {
+                        Some(Message::Close(frame)) => {
+                            let _ = write.send(Message::Close(frame)).await;
+                            return SessionEnd::Shutdown;
+                        }
+                        Some(message) => {
+                            if let Err(e) = write.send(message).await {
+                                error!("[LBank] {} 发送消息失败: {e}", self.connection_id);
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        None => return SessionEnd::Shutdown,
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if !*self.should_run.read().await {
+                        return SessionEnd::Shutdown;
+                    }
+                    if self.app_state.should_reconnect(&self.connection_id) {
+                        warn!("[LBank] {} 收到重连信号", self.connection_id);
+                        return SessionEnd::Lost;
+                    }
+                    if last_message.elapsed() > self.ping_interval * 3 {
+                        warn!("[LBank] {} 长时间未收到消息，重连", self.connection_id);
+                        return SessionEnd::Lost;
+                    }
+
+                    let ping = json!({"action": "ping", "ping": uuid::Uuid::new_v4().to_string()});
+                    if let Err(e) = write.send(Message::Text(ping.to_string())).await {
+                        error!("[LBank] {} 发送心跳失败: {e}", self.connection_id);
+                        return SessionEnd::Lost;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理文本消息，返回需要回复给服务器的消息
+    async fn handle_text(&self, text: &str) -> Option<Message> {
+        self.app_state.increment_websocket_messages(1);
+        self.app_state.update_connection_timestamp(&self.connection_id);
+
+        let frame = match Self::parse_message(text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("[LBank] {} {e}", self.connection_id);
+                return None;
+            }
+        };
+
+        match frame {
+            LBankFrame::Ping(id) => {
+                debug!("[LBank] {} 收到ping: {id}", self.connection_id);
+                let pong = json!({"action": "pong", "pong": id});
+                return Some(Message::Text(pong.to_string()));
+            }
+            LBankFrame::Pong(id) => {
+                debug!("[LBank] {} 收到pong: {id}", self.connection_id);
+            }
+            LBankFrame::Depth(orderbook) => {
+                self.publish_orderbook(orderbook).await;
+            }
+            LBankFrame::Trade(trade) => {
+                self.publish_trade(trade).await;
+            }
+            LBankFrame::Other(msg) => {
+                if msg.get("status").and_then(|s| s.as_str()) == Some("error") {
+                    warn!("[LBank] {} 服务器返回错误: {msg}", self.connection_id);
+                } else {
+                    debug!("[LBank] {} 收到其他消息: {msg}", self.connection_id);
+                }
+            }
+        }
+        None
+    }
+
+    async fn publish_orderbook(&self, orderbook: StandardizedOrderBook) {
+        self.orderbooks.insert(orderbook.symbol.clone(), orderbook.clone());
+        self.app_state.book_store.update(orderbook.clone());
+
+        if let Some(tx) = &self.app_state.orderbook_queue {
+            let update = OrderbookUpdate {
+                symbol: format!("{}:{}", Exchange::LBank, orderbook.symbol),
+                best_ask: orderbook.best_ask,
+                best_bid: orderbook.best_bid,
+                timestamp: orderbook.timestamp,
+                scale: 8,
+                is_synthetic: false,
+                leg1: None,
+                leg2: None,
+                depth_asks: Some(orderbook.depth_asks.clone()),
+                depth_bids: Some(orderbook.depth_bids.clone()),
+            };
+            if let Err(e) = tx.send(update) {
+                error!("[LBank] 发送订单簿更新失败: {e}");
+            }
+        }
+
+        self.forward(StandardizedMessage::OrderBookUpdate(orderbook)).await;
+    }
+
+    async fn publish_trade(&self, trade: StandardizedTrade) {
+        {
+            let mut trades = self.recent_trades.entry(trade.symbol.clone()).or_default();
+            if trades.len() >= RECENT_TRADES_CAPACITY {
+                trades.pop_front();
+            }
+            trades.push_back(trade.clone());
+        }
+
+        self.forward(StandardizedMessage::TradeUpdate(trade)).await;
+    }
+
+    async fn forward(&self, message: StandardizedMessage) {
+        // 先取出发送端再发送，阻塞策略等待消费时不占用锁
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[LBank] {} 消息接收端已关闭", self.connection_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,711 @@
+//! 连接器注册表
+//! 统一持有所有交易所连接器，提供并行连接/断开、汇总连接状态，
+//! 并把各连接器的行情数据流合并到同一个高频数据通道，用户数据流合并到同一个用户数据通道
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use log::{debug, info, warn, error};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use crate::config::{get_config, ClockSyncConfig, Config};
+use crate::core::AppState;
+use crate::exchange_types::Exchange;
+use crate::connectors::common::clock_sync::ClockSyncService;
+use crate::connectors::common::instruments::InstrumentRegistry;
+use crate::connectors::common::data_channel::{data_channel, ChannelSendError, DataReceiver, DataSender};
+use crate::connectors::factory::build_connector;
+use crate::connectors::replay::{MarketDataRecorder, RecordingSummary};
+use crate::connectors::traits::{ConnectorManager, DataFlowManager, ExchangeConnector};
+use crate::types::{
+    config::{ConnectionStatus, BatchSubscriptionResult, DataChannelConfig},
+    market_data::StandardizedMessage,
+    exchange::{ExchangeType, MarketType},
+    errors::ConnectorError,
+    events::{SystemEvent, HighFrequencyData, DataFlowStats},
+};
+
+/// 连接器在注册表中的键
+pub type ConnectorKey = (ExchangeType, MarketType);
+
+/// 配置中为连接器指定的订阅 (交易对, 批量大小)
+type ConfiguredSubscription = (Vec<String>, usize);
+
+/// 连接器注册表
+///
+/// 每个 (交易所, 市场类型) 只能注册一个连接器。注册时即接管该连接器的行情数据流和用户数据流，
+/// 所有行情统一通过 `take_market_data_receiver` 取出，用户数据通过 `take_user_data_receiver` 取出。
+pub struct ConnectorRegistry {
+    connectors: HashMap<ConnectorKey, Arc<dyn ExchangeConnector>>,
+    forwarders: HashMap<ConnectorKey, JoinHandle<()>>,
+    user_data_forwarders: HashMap<ConnectorKey, JoinHandle<()>>,
+    subscriptions: HashMap<ConnectorKey, ConfiguredSubscription>,
+    market_data_sender: DataSender<HighFrequencyData>,
+    market_data_receiver: Option<DataReceiver<HighFrequencyData>>,
+    user_data_sender: DataSender<StandardizedMessage>,
+    user_data_receiver: Option<DataReceiver<StandardizedMessage>>,
+    event_sender: broadcast::Sender<SystemEvent>,
+    event_forwarders: Vec<JoinHandle<()>>,
+    recorder: Option<Arc<MarketDataRecorder>>,
+    clock_sync: Option<(Arc<ClockSyncService>, JoinHandle<()>)>,
+    instruments: Option<(Arc<InstrumentRegistry>, JoinHandle<()>)>,
+    balance_refresh: Option<JoinHandle<()>>,
+}
+
+impl Default for ConnectorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectorRegistry {
+    /// 创建空的注册表，合并通道使用全局配置的 `merged_data_channel`
+    pub fn new() -> Self {
+        Self::with_channel_config(get_config().websocket_optimization.merged_data_channel)
+    }
+
+    /// 使用指定的合并通道配置创建空的注册表
+    pub fn with_channel_config(channel_config: DataChannelConfig) -> Self {
+        let (market_data_sender, market_data_receiver) = data_channel(channel_config);
+        let (user_data_sender, user_data_receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        let (event_sender, _) = broadcast::channel(1000);
+
+        Self {
+            connectors: HashMap::new(),
+            forwarders: HashMap::new(),
+            user_data_forwarders: HashMap::new(),
+            subscriptions: HashMap::new(),
+            market_data_sender,
+            market_data_receiver: Some(market_data_receiver),
+            user_data_sender,
+            user_data_receiver: Some(user_data_receiver),
+            event_sender,
+            event_forwarders: Vec::new(),
+            recorder: None,
+            clock_sync: None,
+            instruments: None,
+            balance_refresh: None,
+        }
+    }
+
+    /// 根据配置文件中的 `[exchanges.*]` 创建并注册所有连接器
+    ///
+    /// 没有对应实现的交易所会被跳过，`supported_symbols` 记录为该连接器的待订阅交易对。
+    pub async fn from_config(config: &Config, app_state: Arc<AppState>) -> Result<Self, ConnectorError> {
+        let mut registry = Self::with_channel_config(config.websocket_optimization.merged_data_channel);
+        if config.recording.enabled {
+            let recorder = MarketDataRecorder::from_config(&config.recording)
+                .map_err(|e| ConnectorError::InternalError(format!("无法开始行情录制: {e}")))?;
+            registry.set_recorder(Arc::new(recorder));
+        }
+
+        let mut names: Vec<&String> = config.exchanges.keys().collect();
+        names.sort();
+        for name in names {
+            let exchange_config = &config.exchanges[name];
+            let Some(connector) = build_connector(name, exchange_config, app_state.clone()).await? else {
+                continue;
+            };
+
+            let key = (connector.get_exchange_type(), connector.get_market_type());
+            registry.add_connector(connector).await?;
+            if let Some(symbols) = exchange_config.supported_symbols.clone() {
+                registry.subscriptions.insert(key, (symbols, exchange_config.batch_size.max(1)));
+            }
+        }
+
+        if config.clock_sync.enabled {
+            registry.start_clock_sync(config.clock_sync.clone());
+        }
+        if config.instruments.enabled {
+            registry.start_instrument_refresh(Duration::from_secs(config.instruments.refresh_interval_secs));
+        }
+
+        info!("[ConnectorRegistry] 已从配置创建 {} 个连接器", registry.len());
+        Ok(registry)
+    }
+
+    /// 为已注册的连接器启动服务器时间同步，没有提供服务器时钟的连接器会被跳过
+    ///
+    /// 重复调用会停止之前的同步任务。
+    pub fn start_clock_sync(&mut self, config: ClockSyncConfig) -> Arc<ClockSyncService> {
+        let service = Arc::new(ClockSyncService::new(config));
+        for connector in self.connectors.values() {
+            if let Some(source) = connector.clock_source() {
+                service.register(source);
+            }
+        }
+
+        let task = service.start();
+        if let Some((_, previous)) = self.clock_sync.replace((service.clone(), task)) {
+            previous.abort();
+        }
+        service
+    }
+
+    /// 正在运行的时间同步服务
+    pub fn clock_sync(&self) -> Option<&Arc<ClockSyncService>> {
+        self.clock_sync.as_ref().map(|(service, _)| service)
+    }
+
+    /// 从已注册的连接器加载交易规则，并按间隔在后台刷新
+    ///
+    /// 重复调用会停止之前的刷新任务。
+    pub fn start_instrument_refresh(&mut self, interval: Duration) -> Arc<InstrumentRegistry> {
+        let instruments = Arc::new(InstrumentRegistry::new());
+        for connector in self.connectors.values() {
+            if let Some(source) = connector.instrument_source() {
+                instruments.register_source(source);
+            }
+        }
+
+        let task = instruments.start_refresh(interval);
+        if let Some((_, previous)) = self.instruments.replace((instruments.clone(), task)) {
+            previous.abort();
+        }
+        instruments
+    }
+
+    /// 交易规则注册表
+    pub fn instruments(&self) -> Option<&Arc<InstrumentRegistry>> {
+        self.instruments.as_ref().map(|(instruments, _)| instruments)
+    }
+
+    /// 已注册连接器的共享句柄，供下单执行器等需要长期持有连接器的模块使用
+    pub fn connector_handles(&self) -> Vec<Arc<dyn ExchangeConnector>> {
+        self.connectors.values().cloned().collect()
+    }
+
+    /// 同步各连接器的账户余额：查询成功的连接器订阅用户数据流，并按间隔在后台重新查询
+    ///
+    /// 余额写入 `AppState` 的可用余额（见 `AppState::apply_account_balance`），限制每条腿的下单量；
+    /// 没有API密钥或不支持账户查询的连接器会被跳过。间隔为0时只查询一次，重复调用会停止之前的刷新任务。
+    /// 返回账户同步成功的连接器。
+    pub async fn start_balance_sync(&mut self, app_state: Arc<AppState>, interval: Duration) -> Vec<ConnectorKey> {
+        let mut accounts = Vec::new();
+        for (key, connector) in &self.connectors {
+            match connector.get_account_balance().await {
+                Ok(balance) => {
+                    app_state.set_account_market(Exchange::from(key.0), key.1);
+                    app_state.apply_account_balance(Exchange::from(key.0), &balance);
+                    if let Err(e) = connector.subscribe_user_stream().await {
+                        warn!("[ConnectorRegistry] {}/{} 用户数据流订阅失败，余额只按间隔刷新: {e}", key.0, key.1);
+                    }
+                    accounts.push((*key, connector.clone()));
+                }
+                Err(e) => debug!("[ConnectorRegistry] {}/{} 跳过账户同步: {e}", key.0, key.1),
+            }
+        }
+
+        let keys: Vec<ConnectorKey> = accounts.iter().map(|(key, _)| *key).collect();
+        if let Some(previous) = self.balance_refresh.take() {
+            previous.abort();
+        }
+        info!("[ConnectorRegistry] {} 个连接器已开启账户余额同步", keys.len());
+        if interval.is_zero() {
+            return keys;
+        }
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                for (key, connector) in &accounts {
+                    match connector.get_account_balance().await {
+                        Ok(balance) => app_state.apply_account_balance(Exchange::from(key.0), &balance),
+                        Err(e) => warn!("[ConnectorRegistry] {}/{} 余额刷新失败: {e}", key.0, key.1),
+                    }
+                }
+            }
+        });
+        self.balance_refresh = Some(task);
+        keys
+    }
+
+    /// 取出合并后的用户数据流（订单与余额更新），只能取出一次
+    pub fn take_user_data_receiver(&mut self) -> Option<DataReceiver<StandardizedMessage>> {
+        self.user_data_receiver.take()
+    }
+
+    /// 把其他模块广播的系统事件（例如套利机会的开启与关闭）并入注册表的事件通道，
+    /// 之后通过 `subscribe_events` 统一订阅
+    pub fn forward_events(&mut self, mut events: broadcast::Receiver<SystemEvent>) {
+        let sender = self.event_sender.clone();
+        self.event_forwarders.push(tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let _ = sender.send(event);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("[ConnectorRegistry] 转发系统事件滞后，丢弃 {skipped} 条");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }));
+    }
+
+    /// 设置行情录制器，之后注册的连接器推送的所有消息都会被录制
+    pub fn set_recorder(&mut self, recorder: Arc<MarketDataRecorder>) {
+        self.recorder = Some(recorder);
+    }
+
+    /// 停止录制并等待数据写入磁盘，未开启录制时返回 None
+    pub async fn finish_recording(&mut self) -> Option<std::io::Result<RecordingSummary>> {
+        let recorder = self.recorder.take()?;
+        Some(recorder.finish().await)
+    }
+
+    /// 已注册的连接器数量
+    pub fn len(&self) -> usize {
+        self.connectors.len()
+    }
+
+    /// 注册表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.connectors.is_empty()
+    }
+
+    /// 已注册的连接器键
+    pub fn keys(&self) -> Vec<ConnectorKey> {
+        self.connectors.keys().copied().collect()
+    }
+
+    /// 按配置中的 `supported_symbols` 批量订阅深度，各连接器并行执行
+    pub async fn subscribe_configured(&self) -> HashMap<ConnectorKey, Result<BatchSubscriptionResult, ConnectorError>> {
+        let tasks = self.subscriptions.iter().filter_map(|(key, (symbols, batch_size))| {
+            let connector = self.connectors.get(key)?;
+            Some(async move { (*key, connector.subscribe_batch(symbols.clone(), *batch_size).await) })
+        });
+
+        join_all(tasks).await.into_iter().collect()
+    }
+
+    /// 合并通道的数据流统计
+    pub fn data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        self.market_data_sender.stats().apply_to(&mut stats);
+        stats
+    }
+
+    /// 各连接器自身数据通道的统计
+    pub fn connector_data_flow_stats(&self) -> HashMap<ConnectorKey, DataFlowStats> {
+        self.connectors
+            .iter()
+            .map(|(key, connector)| (*key, connector.get_data_flow_stats()))
+            .collect()
+    }
+
+    /// 把连接器的行情数据流转发到合并通道
+    fn spawn_forwarder(&self, key: ConnectorKey, connector: &dyn ExchangeConnector) -> JoinHandle<()> {
+        let mut stream = connector.get_market_data_stream();
+        let sender = self.market_data_sender.clone();
+        let recorder = self.recorder.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = stream.recv().await {
+                if let Some(recorder) = &recorder {
+                    recorder.record(key.0, key.1, message.clone()).await;
+                }
+                let Some(data) = to_high_frequency_data(key, message) else {
+                    continue;
+                };
+                if sender.send(data).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// 把连接器的用户数据流转发到合并的用户数据通道
+    fn spawn_user_data_forwarder(&self, connector: &dyn ExchangeConnector) -> JoinHandle<()> {
+        let mut stream = connector.get_user_data_stream();
+        let sender = self.user_data_sender.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = stream.recv().await {
+                if sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    async fn emit_connection_event(&self, key: ConnectorKey, connected: bool) {
+        self.send_event(SystemEvent::Connection {
+            exchange: key.0.into(),
+            market_type: key.1.into(),
+            connected,
+            timestamp: SystemTime::now(),
+        })
+        .await;
+    }
+
+    async fn emit_error_event(&self, key: ConnectorKey, error: &ConnectorError) {
+        self.send_event(SystemEvent::Error {
+            exchange: key.0.into(),
+            market_type: key.1.into(),
+            error: error.to_string(),
+            timestamp: SystemTime::now(),
+        })
+        .await;
+    }
+}
+
+/// 标准化消息转换为高频数据，只转发订单簿和成交
+fn to_high_frequency_data((exchange, market_type): ConnectorKey, message: StandardizedMessage) -> Option<HighFrequencyData> {
+    match message {
+        StandardizedMessage::OrderBookUpdate(orderbook) => Some(HighFrequencyData::OrderBookUpdate {
+            exchange: exchange.into(),
+            market_type: market_type.into(),
+            orderbook,
+            timestamp: SystemTime::now(),
+        }),
+        StandardizedMessage::TradeUpdate(trade) => Some(HighFrequencyData::TradeUpdate {
+            exchange: exchange.into(),
+            market_type: market_type.into(),
+            trade,
+            timestamp: SystemTime::now(),
+        }),
+        _ => None,
+    }
+}
+
+impl Drop for ConnectorRegistry {
+    fn drop(&mut self) {
+        for forwarder in self.forwarders.values().chain(self.user_data_forwarders.values()).chain(&self.event_forwarders) {
+            forwarder.abort();
+        }
+        if let Some(task) = &self.balance_refresh {
+            task.abort();
+        }
+        if let Some((_, task)) = &self.clock_sync {
+            task.abort();
+        }
+        if let Some((_, task)) = &self.instruments {
+            task.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl ConnectorManager for ConnectorRegistry {
+    async fn add_connector(&mut self, connector: Box<dyn ExchangeConnector>) -> Result<(), ConnectorError> {
+        let key = (connector.get_exchange_type(), connector.get_market_type());
+        if self.connectors.contains_key(&key) {
+            return Err(ConnectorError::InternalError(format!("连接器 {}/{} 已注册", key.0, key.1)));
+        }
+
+        let forwarder = self.spawn_forwarder(key, connector.as_ref());
+        self.forwarders.insert(key, forwarder);
+        let user_data_forwarder = self.spawn_user_data_forwarder(connector.as_ref());
+        self.user_data_forwarders.insert(key, user_data_forwarder);
+        self.connectors.insert(key, Arc::from(connector));
+        info!("[ConnectorRegistry] 注册连接器: {}/{}", key.0, key.1);
+        Ok(())
+    }
+
+    async fn remove_connector(&mut self, exchange: ExchangeType, market_type: MarketType) -> Result<(), ConnectorError> {
+        let key = (exchange, market_type);
+        let connector = self.connectors.remove(&key)
+            .ok_or_else(|| ConnectorError::ExchangeNotFound(format!("{exchange}/{market_type}")))?;
+
+        if let Err(e) = connector.disconnect_websocket().await {
+            warn!("[ConnectorRegistry] 移除 {exchange}/{market_type} 时断开连接失败: {e}");
+        }
+        for forwarder in [self.forwarders.remove(&key), self.user_data_forwarders.remove(&key)].into_iter().flatten() {
+            forwarder.abort();
+        }
+        self.subscriptions.remove(&key);
+        info!("[ConnectorRegistry] 移除连接器: {exchange}/{market_type}");
+        Ok(())
+    }
+
+    fn get_connector(&self, exchange: ExchangeType, market_type: MarketType) -> Option<&dyn ExchangeConnector> {
+        self.connectors.get(&(exchange, market_type)).map(|connector| connector.as_ref())
+    }
+
+    fn get_all_connectors(&self) -> Vec<&dyn ExchangeConnector> {
+        self.connectors.values().map(|connector| connector.as_ref()).collect()
+    }
+
+    /// 并行连接所有连接器，部分失败时其余连接器保持连接并返回汇总错误
+    async fn connect_all(&self) -> Result<(), ConnectorError> {
+        let results = join_all(self.connectors.iter().map(|(key, connector)| async move {
+            (*key, connector.connect_websocket().await)
+        }))
+        .await;
+
+        let mut failures = Vec::new();
+        for (key, result) in results {
+            match result {
+                Ok(()) => self.emit_connection_event(key, true).await,
+                Err(e) => {
+                    error!("[ConnectorRegistry] {}/{} 连接失败: {e}", key.0, key.1);
+                    self.emit_error_event(key, &e).await;
+                    failures.push(format!("{}/{}: {e}", key.0, key.1));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            info!("[ConnectorRegistry] {} 个连接器全部连接成功", self.connectors.len());
+            Ok(())
+        } else {
+            Err(ConnectorError::ConnectionFailed(failures.join("; ")))
+        }
+    }
+
+    async fn disconnect_all(&self) -> Result<(), ConnectorError> {
+        let results = join_all(self.connectors.iter().map(|(key, connector)| async move {
+            (*key, connector.disconnect_websocket().await)
+        }))
+        .await;
+
+        let mut failures = Vec::new();
+        for (key, result) in results {
+            match result {
+                Ok(()) => self.emit_connection_event(key, false).await,
+                Err(e) => failures.push(format!("{}/{}: {e}", key.0, key.1)),
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(ConnectorError::ConnectionError(failures.join("; ")))
+        }
+    }
+
+    async fn get_connection_status_all(&self) -> HashMap<(ExchangeType, MarketType), ConnectionStatus> {
+        join_all(self.connectors.iter().map(|(key, connector)| async move {
+            (*key, connector.get_connection_status().await)
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+}
+
+#[async_trait]
+impl DataFlowManager for ConnectorRegistry {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
+        self.market_data_receiver.take()
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent> {
+        self.event_sender.subscribe()
+    }
+
+    fn send_market_data(&self, data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
+        self.market_data_sender.try_send(data)
+    }
+
+    async fn send_event(&self, event: SystemEvent) {
+        // 没有订阅者时发送失败是正常情况
+        let _ = self.event_sender.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::coincatch::CoinCatchConnector;
+    use crate::connectors::test_support::MockWsServer;
+    use crate::connectors::xtcom::XtComConnector;
+    use crate::types::config::ConnectorConfig;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio::time::{timeout, Duration};
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// 录制的XT.com和CoinCatch深度帧
+    const XTCOM_DEPTH_FRAME: &str = r#"{"topic":"depth","event":"depth@btc_usdt,20","data":{"s":"btc_usdt","a":[["64012.4","1532"]],"b":[["64011.9","5321"]],"t":1710755362722}}"#;
+    const COINCATCH_DEPTH_FRAME: &str = r#"{"action":"snapshot","arg":{"instType":"mc","channel":"books15","instId":"BTCUSDT"},"data":[{"asks":[["64012.5","1.5"]],"bids":[["64011.8","5.3"]],"ts":"1710755362722"}]}"#;
+
+    /// 启动模拟行情服务器：收到任意订阅请求后回放 `frame`
+    async fn start_mock_server(frame: &'static str) -> String {
+        let server = MockWsServer::start(move |mut ws, _| async move {
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text.contains("subscribe") {
+                    ws.send(Message::Text(frame.to_string())).await.unwrap();
+                }
+            }
+        }).await;
+        server.url
+    }
+
+    fn mock_config(url: &str) -> ConnectorConfig {
+        ConnectorConfig {
+            websocket_url: Some(url.to_string()),
+            reconnect_interval: 50,
+            max_reconnect_attempts: 1,
+            request_timeout: 2000,
+            ..ConnectorConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_merges_market_data_streams() {
+        let app_state = Arc::new(AppState::new());
+        let mut registry = ConnectorRegistry::new();
+        registry.add_connector(Box::new(XtComConnector::new(mock_config(&start_mock_server(XTCOM_DEPTH_FRAME).await), 5, app_state.clone()))).await.unwrap();
+        registry.add_connector(Box::new(CoinCatchConnector::new(mock_config(&start_mock_server(COINCATCH_DEPTH_FRAME).await), 5, app_state.clone()))).await.unwrap();
+
+        // 同一 (交易所, 市场类型) 不能重复注册
+        let duplicate = XtComConnector::new(ConnectorConfig::default(), 5, app_state.clone());
+        assert!(registry.add_connector(Box::new(duplicate)).await.is_err());
+        assert_eq!(registry.len(), 2);
+
+        let mut receiver = registry.take_market_data_receiver().expect("首次获取应该返回接收端");
+        assert!(registry.take_market_data_receiver().is_none());
+
+        registry.connect_all().await.expect("全部连接应该成功");
+        let statuses = registry.get_connection_status_all().await;
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.values().all(|status| *status == ConnectionStatus::Connected));
+
+        for connector in registry.get_all_connectors() {
+            connector.subscribe_orderbook("BTCUSDT").await.unwrap();
+        }
+
+        // 两个交易所的深度都进入同一个通道
+        let mut exchanges = std::collections::HashSet::new();
+        timeout(Duration::from_secs(5), async {
+            while exchanges.len() < 2 {
+                if let Some(HighFrequencyData::OrderBookUpdate { orderbook, .. }) = receiver.recv().await {
+                    assert_eq!(orderbook.symbol, "BTCUSDT");
+                    exchanges.insert(orderbook.exchange);
+                }
+            }
+        })
+        .await
+        .expect("等待合并行情超时");
+
+        registry.remove_connector(ExchangeType::XtCom, MarketType::Futures).await.unwrap();
+        assert!(registry.get_connector(ExchangeType::XtCom, MarketType::Futures).is_none());
+        assert!(registry.get_connector(ExchangeType::CoinCatch, MarketType::Futures).is_some());
+
+        registry.disconnect_all().await.unwrap();
+        assert_eq!(registry.get_connection_status_all().await[&(ExchangeType::CoinCatch, MarketType::Futures)], ConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_registry_connect_all_reports_failures() {
+        // 绑定后立即释放端口，保证连接被拒绝
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_url = format!("ws://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let app_state = Arc::new(AppState::new());
+        let mut registry = ConnectorRegistry::new();
+        let mut events = registry.subscribe_events();
+        registry.add_connector(Box::new(XtComConnector::new(mock_config(&start_mock_server(XTCOM_DEPTH_FRAME).await), 5, app_state.clone()))).await.unwrap();
+        registry.add_connector(Box::new(CoinCatchConnector::new(mock_config(&dead_url), 5, app_state))).await.unwrap();
+
+        match registry.connect_all().await {
+            Err(ConnectorError::ConnectionFailed(message)) => assert!(message.contains("COINCATCH")),
+            other => panic!("部分连接失败时应该返回汇总错误: {other:?}"),
+        }
+
+        // 失败的连接器不影响其他连接器
+        let statuses = registry.get_connection_status_all().await;
+        assert_eq!(statuses[&(ExchangeType::XtCom, MarketType::Futures)], ConnectionStatus::Connected);
+        assert_eq!(statuses[&(ExchangeType::CoinCatch, MarketType::Futures)], ConnectionStatus::Error);
+
+        let mut saw_error = false;
+        while let Ok(event) = events.try_recv() {
+            saw_error |= matches!(event, SystemEvent::Error { .. });
+        }
+        assert!(saw_error, "连接失败应该广播错误事件");
+
+        registry.disconnect_all().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registry_forwards_external_events() {
+        use crate::exchange_types::{CrossExchangeArb, Exchange};
+        use crate::opportunity_tracker::OpportunityTracker;
+        use crate::types::events::OpportunityStage;
+
+        let tracker = OpportunityTracker::new();
+        let mut registry = ConnectorRegistry::new();
+        registry.forward_events(tracker.subscribe());
+        let mut events = registry.subscribe_events();
+
+        tracker.observe(&CrossExchangeArb {
+            symbol: "BTCUSDT".to_string(),
+            buy_exchange: Exchange::Phemex,
+            sell_exchange: Exchange::LBank,
+            buy_price: 100.0,
+            sell_price: 101.0,
+            timestamp: 0,
+            profit_pct: 1.0,
+            net_profit_pct: 0.8,
+            total_fees_pct: 0.2,
+            optimal_size: 1.0,
+            expected_pnl: 0.8,
+            profit_curve: Vec::new(),
+        });
+
+        match timeout(Duration::from_secs(2), events.recv()).await {
+            Ok(Ok(SystemEvent::ArbitrageOpportunity { symbol, stage, .. })) => {
+                assert_eq!((symbol.as_str(), stage), ("BTCUSDT", OpportunityStage::Opened));
+            }
+            other => panic!("机会事件应该出现在注册表的事件通道: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_syncs_balances_into_app_state() {
+        use crate::connectors::simulated::{SimulatedExchangeConfig, SimulatedExchangeConnector};
+        use crate::exchange_types::{Exchange, ExchangeFees};
+        use crate::types::market_data::UserData;
+        use crate::types::orders::{OrderRequest, OrderSide, OrderType};
+
+        let app_state = Arc::new(AppState::new());
+        let mut registry = ConnectorRegistry::new();
+        let config = SimulatedExchangeConfig::new(ExchangeType::XtCom, MarketType::Spot, ExchangeFees::new(Exchange::XtCom, 0.001, 0.001))
+            .with_balance("USDT", 1_000.0)
+            .with_balance("ETH", 2.0);
+        registry.add_connector(Box::new(SimulatedExchangeConnector::new(config, None))).await.unwrap();
+        // 行情连接器没有账户接口，不参与同步
+        registry.add_connector(Box::new(XtComConnector::new(mock_config("ws://127.0.0.1:1"), 5, app_state.clone()))).await.unwrap();
+
+        let synced = registry.start_balance_sync(app_state.clone(), Duration::from_secs(60)).await;
+        assert_eq!(synced, vec![(ExchangeType::XtCom, MarketType::Spot)]);
+        assert_eq!(app_state.available_balance(Exchange::XtCom, "USDT"), Some(1_000.0));
+        // 现货账户的基础货币持仓单独记录，用于限制卖出腿
+        assert_eq!(app_state.available_balance(Exchange::XtCom, "ETH"), Some(2.0));
+        assert_eq!(app_state.account_market(Exchange::XtCom), Some(MarketType::Spot));
+
+        // 挂单冻结的资金（含手续费）通过用户数据流更新可用余额
+        let mut user_data = registry.take_user_data_receiver().unwrap();
+        let request = OrderRequest {
+            symbol: "ETHUSDT".to_string(),
+            exchange: ExchangeType::XtCom,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity: 10.0,
+            price: Some(40.0),
+            time_in_force: None,
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: None,
+        };
+        registry.connectors[&(ExchangeType::XtCom, MarketType::Spot)].place_order(&request).await.unwrap();
+        while app_state.available_balance(Exchange::XtCom, "USDT") == Some(1_000.0) {
+            match timeout(Duration::from_secs(2), user_data.recv()).await {
+                Ok(Some(StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(update)))) => app_state.apply_balance_update(&update),
+                Ok(Some(_)) => {}
+                other => panic!("应该收到余额更新: {other:?}"),
+            }
+        }
+        assert_eq!(app_state.available_balance(Exchange::XtCom, "USDT"), Some(599.6));
+    }
+}
@@ -9,16 +9,29 @@ pub use traits::*;
 // 连接器实现模块
 pub mod lbank;
 pub mod binance;
-// pub mod xtcom;
-// pub mod tapbit;
-// pub mod hbit;
-// pub mod batonex;
-// pub mod coincatch;
+pub mod phemex;
+pub mod xtcom;
+pub mod tapbit;
+pub mod hbit;
+pub mod batonex;
+pub mod coincatch;
 // pub mod binance;
-// pub mod bybit;
-// pub mod okx;
+pub mod bybit;
+pub mod okx;
 
-// 预留管理器和工厂
-// pub mod manager;
-// pub mod factory;
-// pub mod data_flow_manager;
\ No newline at end of file
+// 行情录制与回放
+pub mod replay;
+
+// 模拟盘
+pub mod simulated;
+
+// 连接器注册表和工厂
+pub mod manager;
+pub mod factory;
+// pub mod data_flow_manager;
+
+// 测试共用的模拟服务器
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use manager::{ConnectorRegistry, ConnectorKey};
\ No newline at end of file
@@ -0,0 +1,15 @@
+//! OKX连接器模块
+//! 实现OKX V5 USDT永续合约（SWAP）的行情、校验和订单簿、私有数据流和交易接口
+
+pub mod adapter;
+pub mod orderbook;
+pub mod rest;
+pub mod websocket;
+
+#[cfg(test)]
+mod test;
+
+pub use adapter::OkxConnector;
+pub use orderbook::{OkxBookOutcome, OkxOrderBook};
+pub use rest::OkxRestClient;
+pub use websocket::{OkxChannel, OkxFrame, OkxStream, OkxWebSocketHandler};
@@ -0,0 +1,255 @@
+//! OKX本地订单簿
+//! 维护 `books` 频道的快照+增量，按 seqId 检查连续性，并用CRC32校验和验证前25档
+
+use rust_decimal::Decimal;
+use crate::connectors::common::local_orderbook::LocalOrderBook;
+use crate::exchange_types::{Exchange, StandardOrderBook};
+
+/// 校验和覆盖的档位数
+pub const CHECKSUM_DEPTH: usize = 25;
+
+/// 对外发布的深度档位数
+pub const PUBLISH_DEPTH: usize = 50;
+
+/// 订单簿推送类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OkxBookAction {
+    /// 全量快照（books的首条推送以及books5的每条推送）
+    Snapshot,
+    /// 增量更新
+    Update,
+}
+
+/// 解析后的订单簿推送
+#[derive(Debug, Clone)]
+pub struct OkxBookData {
+    pub inst_id: String,
+    pub action: OkxBookAction,
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+    /// 推送携带的校验和，books5没有该字段
+    pub checksum: Option<i32>,
+    pub seq_id: i64,
+    /// 上一条推送的seqId，快照为-1
+    pub prev_seq_id: i64,
+    pub timestamp: i64,
+}
+
+/// 应用推送后的结果
+#[derive(Debug, Clone)]
+pub enum OkxBookOutcome {
+    /// 已应用，返回最新订单簿
+    Applied(StandardOrderBook),
+    /// 尚未收到快照，忽略增量
+    AwaitingSnapshot,
+    /// seqId不连续，需要重新订阅
+    SequenceGap { expected: i64, received: i64 },
+    /// 校验和不一致，需要重新订阅
+    ChecksumMismatch { expected: i32, actual: i32 },
+}
+
+/// OKX单个合约的本地订单簿
+#[derive(Debug, Clone)]
+pub struct OkxOrderBook {
+    book: LocalOrderBook,
+    last_seq_id: Option<i64>,
+}
+
+impl OkxOrderBook {
+    /// 创建空订单簿
+    pub fn new(inst_id: &str) -> Self {
+        Self {
+            book: LocalOrderBook::new(inst_id),
+            last_seq_id: None,
+        }
+    }
+
+    /// 最后应用的seqId
+    pub fn last_seq_id(&self) -> Option<i64> {
+        self.last_seq_id
+    }
+
+    /// 应用一条推送
+    ///
+    /// 校验失败时订单簿被清空，必须等待重新订阅后的快照。
+    pub fn apply(&mut self, data: &OkxBookData, symbol: &str) -> OkxBookOutcome {
+        match data.action {
+            OkxBookAction::Snapshot => {
+                self.book.reset(&data.bids, &data.asks, data.seq_id, data.timestamp);
+            }
+            OkxBookAction::Update => {
+                let Some(last_seq_id) = self.last_seq_id else {
+                    return OkxBookOutcome::AwaitingSnapshot;
+                };
+                if data.prev_seq_id != last_seq_id {
+                    self.invalidate();
+                    return OkxBookOutcome::SequenceGap { expected: last_seq_id, received: data.prev_seq_id };
+                }
+                self.book.apply_diff(&data.bids, &data.asks, data.seq_id, data.timestamp);
+            }
+        }
+        self.last_seq_id = Some(data.seq_id);
+
+        if let Some(expected) = data.checksum {
+            let actual = self.checksum();
+            if actual != expected {
+                self.invalidate();
+                return OkxBookOutcome::ChecksumMismatch { expected, actual };
+            }
+        }
+
+        let mut orderbook = self.book.to_standardized(Exchange::OkxFutures, PUBLISH_DEPTH);
+        orderbook.symbol = symbol.to_string();
+        OkxBookOutcome::Applied(orderbook)
+    }
+
+    /// 当前订单簿的标准化快照，订单簿为空时返回None
+    pub fn snapshot(&self, symbol: &str) -> Option<StandardOrderBook> {
+        if self.book.is_empty() {
+            return None;
+        }
+        let mut orderbook = self.book.to_standardized(Exchange::OkxFutures, PUBLISH_DEPTH);
+        orderbook.symbol = symbol.to_string();
+        Some(orderbook)
+    }
+
+    /// 按OKX规则计算前25档校验和
+    pub fn checksum(&self) -> i32 {
+        checksum(&self.book.bid_levels(CHECKSUM_DEPTH), &self.book.ask_levels(CHECKSUM_DEPTH))
+    }
+
+    /// 清空订单簿，等待新快照
+    pub fn invalidate(&mut self) {
+        self.book.clear();
+        self.last_seq_id = None;
+    }
+}
+
+/// OKX订单簿校验和
+///
+/// 买卖档交替拼接为 `bid价:bid量:ask价:ask量:...`，某一侧档位不足时只拼接另一侧，
+/// 对结果取CRC32并按有符号32位整数比较。价格和数量保留推送中的原始精度。
+pub fn checksum(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> i32 {
+    let mut parts = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+    for i in 0..CHECKSUM_DEPTH {
+        if let Some((price, quantity)) = bids.get(i) {
+            parts.push(price.to_string());
+            parts.push(quantity.to_string());
+        }
+        if let Some((price, quantity)) = asks.get(i) {
+            parts.push(price.to_string());
+            parts.push(quantity.to_string());
+        }
+    }
+    crc32(parts.join(":").as_bytes()) as i32
+}
+
+/// CRC-32（IEEE 802.3，多项式0xEDB88320）
+pub fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc = TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn levels(raw: &[(&str, &str)]) -> Vec<(Decimal, Decimal)> {
+        raw.iter()
+            .map(|(p, q)| (Decimal::from_str(p).unwrap(), Decimal::from_str(q).unwrap()))
+            .collect()
+    }
+
+    fn snapshot(bids: &[(&str, &str)], asks: &[(&str, &str)], seq_id: i64) -> OkxBookData {
+        let bids = levels(bids);
+        let asks = levels(asks);
+        OkxBookData {
+            inst_id: "BTC-USDT-SWAP".to_string(),
+            action: OkxBookAction::Snapshot,
+            checksum: Some(checksum(&bids, &asks)),
+            bids,
+            asks,
+            seq_id,
+            prev_seq_id: -1,
+            timestamp: 1710755362722,
+        }
+    }
+
+    #[test]
+    fn test_crc32_and_checksum_string() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        // 交替拼接且保留原始精度："3366.1:7:3366.8:9:3366:6:3368:8"
+        let bids = levels(&[("3366.1", "7"), ("3366", "6")]);
+        let asks = levels(&[("3366.8", "9"), ("3368", "8")]);
+        assert_eq!(checksum(&bids, &asks), crc32(b"3366.1:7:3366.8:9:3366:6:3368:8") as i32);
+
+        // 卖盘档位不足时只拼接买盘
+        let bids = levels(&[("3366.1", "7"), ("3366", "6"), ("3365.50", "1.20")]);
+        let asks = levels(&[("3366.8", "9")]);
+        assert_eq!(checksum(&bids, &asks), crc32(b"3366.1:7:3366.8:9:3366:6:3365.50:1.20") as i32);
+    }
+
+    #[test]
+    fn test_snapshot_update_and_gap() {
+        let mut book = OkxOrderBook::new("BTC-USDT-SWAP");
+        let update = OkxBookData {
+            action: OkxBookAction::Update,
+            bids: levels(&[("3366.1", "0"), ("3366.5", "2")]),
+            asks: vec![],
+            checksum: None,
+            prev_seq_id: 100,
+            seq_id: 101,
+            ..snapshot(&[], &[], 0)
+        };
+        assert!(matches!(book.apply(&update, "BTCUSDT"), OkxBookOutcome::AwaitingSnapshot));
+
+        let snap = snapshot(&[("3366.1", "7"), ("3366", "6")], &[("3366.8", "9")], 100);
+        assert!(matches!(book.apply(&snap, "BTCUSDT"), OkxBookOutcome::Applied(_)));
+
+        let expected = checksum(&levels(&[("3366.5", "2"), ("3366", "6")]), &levels(&[("3366.8", "9")]));
+        let update = OkxBookData { checksum: Some(expected), ..update };
+        match book.apply(&update, "BTCUSDT") {
+            OkxBookOutcome::Applied(orderbook) => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 3366.5);
+            }
+            other => panic!("增量应该生效: {other:?}"),
+        }
+
+        let gap = OkxBookData { prev_seq_id: 105, seq_id: 106, ..update };
+        assert!(matches!(book.apply(&gap, "BTCUSDT"), OkxBookOutcome::SequenceGap { expected: 101, received: 105 }));
+        assert_eq!(book.last_seq_id(), None);
+    }
+
+    #[test]
+    fn test_checksum_mismatch_invalidates_book() {
+        let mut book = OkxOrderBook::new("BTC-USDT-SWAP");
+        let snap = OkxBookData {
+            checksum: Some(12345),
+            ..snapshot(&[("3366.1", "7")], &[("3366.8", "9")], 100)
+        };
+        assert!(matches!(book.apply(&snap, "BTCUSDT"), OkxBookOutcome::ChecksumMismatch { expected: 12345, .. }));
+        assert_eq!(book.last_seq_id(), None);
+    }
+}
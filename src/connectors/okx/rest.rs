@@ -0,0 +1,465 @@
+//! OKX V5 REST客户端
+//! 实现永续合约（SWAP）的签名下单、撤单、查询以及账户余额查询，签名需要API passphrase
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use log::debug;
+use reqwest::{Client, Method};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::config::get_config;
+use crate::connectors::common::clock_sync::{ExchangeClock, ServerTimeSource};
+use crate::connectors::common::instruments::{positive_decimal_field, InstrumentSource, InstrumentSpec, InstrumentStatus};
+use crate::exchange_types::Exchange;
+use crate::types::account::{AccountBalance, CurrencyBalance};
+use crate::types::config::ConnectorConfig;
+use crate::types::errors::ConnectorError;
+use crate::types::orders::{OrderRequest, OrderResponse, OrderSide, OrderStatus, OrderType, PositionSide, TimeInForce};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// OKX REST地址（模拟盘使用同一地址，通过请求头区分）
+pub const OKX_REST_URL: &str = "https://www.okx.com";
+
+/// 保证金模式：全仓
+const OKX_TD_MODE: &str = "cross";
+
+const ORDER_PATH: &str = "/api/v5/trade/order";
+const CANCEL_ORDER_PATH: &str = "/api/v5/trade/cancel-order";
+const BALANCE_PATH: &str = "/api/v5/account/balance";
+const SERVER_TIME_PATH: &str = "/api/v5/public/time";
+const INSTRUMENTS_PATH: &str = "/api/v5/public/instruments";
+
+/// 常见计价币种，用于把 BTCUSDT 拆成 BTC-USDT-SWAP
+const QUOTE_CURRENCIES: [&str; 3] = ["USDT", "USDC", "USD"];
+
+/// OKX V5 REST客户端
+#[derive(Clone)]
+pub struct OkxRestClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    passphrase: Option<String>,
+    simulated: bool,
+    /// 服务器时钟，签名时间戳和请求过期时间都取自这里
+    clock: Arc<ExchangeClock>,
+}
+
+impl OkxRestClient {
+    /// 根据连接器配置创建REST客户端，testnet对应OKX模拟盘
+    pub fn new(config: &ConnectorConfig) -> Self {
+        let base_url = config.rest_api_url.clone().unwrap_or_else(|| OKX_REST_URL.to_string());
+
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_millis(config.request_timeout.max(1)))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone().filter(|k| !k.is_empty()),
+            secret_key: config.secret_key.clone().filter(|k| !k.is_empty()),
+            passphrase: config.passphrase.clone().filter(|p| !p.is_empty()),
+            simulated: config.testnet,
+            clock: Arc::new(ExchangeClock::new(Exchange::OkxFutures, get_config().clock_sync.recv_window_ms)),
+        }
+    }
+
+    /// 服务器时钟
+    pub fn clock(&self) -> &Arc<ExchangeClock> {
+        &self.clock
+    }
+
+    /// 获取服务器时间（毫秒）
+    pub async fn get_server_time(&self) -> Result<i64, ConnectorError> {
+        let url = format!("{}{}", self.base_url, SERVER_TIME_PATH);
+        let data: Value = self.client.get(&url).send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("OKX请求失败: {e}")))?
+            .json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析OKX服务器时间失败: {e}")))?;
+        Self::parse_server_time(&data)
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("OKX服务器时间无效: {data}")))
+    }
+
+    /// 解析服务器时间 `data[0].ts`
+    pub fn parse_server_time(data: &Value) -> Option<i64> {
+        data.get("data")?.as_array()?.first()
+            .and_then(|item| Self::field_f64(item, "ts"))
+            .map(|ts| ts as i64)
+    }
+
+    /// 是否配置了完整的API密钥（含passphrase）
+    pub fn has_credentials(&self) -> bool {
+        self.api_key.is_some() && self.secret_key.is_some() && self.passphrase.is_some()
+    }
+
+    /// 计算V5签名：Base64(HMAC_SHA256(timestamp + method + request_path + body))
+    pub fn sign(secret_key: &str, timestamp: &str, method: &str, request_path: &str, body: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret_key.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(format!("{timestamp}{method}{request_path}{body}").as_bytes());
+        base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+    }
+
+    /// 获取全部永续合约的交易规则
+    pub async fn get_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+        let items = self.public_request(INSTRUMENTS_PATH, &[("instType", "SWAP".to_string())]).await?;
+        Ok(Self::parse_instruments(&items))
+    }
+
+    /// 解析 `/api/v5/public/instruments` 的 `data` 数组，只保留U本位（linear）合约
+    ///
+    /// OKX按合约张数下单，合约乘数为面值 `ctVal * ctMult`。
+    pub fn parse_instruments(items: &[Value]) -> Vec<InstrumentSpec> {
+        items.iter().filter_map(|item| {
+            if item.get("ctType").and_then(|t| t.as_str()).is_some_and(|t| t != "linear") {
+                return None;
+            }
+            let inst_id = item.get("instId")?.as_str()?;
+            let contract_value = positive_decimal_field(item, "ctVal").unwrap_or(Decimal::ONE);
+            let contract_multiplier = positive_decimal_field(item, "ctMult").unwrap_or(Decimal::ONE);
+
+            let mut spec = InstrumentSpec::new(
+                Exchange::OkxFutures,
+                inst_id,
+                positive_decimal_field(item, "tickSz")?,
+                positive_decimal_field(item, "lotSz")?,
+            );
+            spec.symbol = Self::inst_id_to_symbol(inst_id);
+            spec.base_asset = item.get("ctValCcy").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            spec.quote_asset = item.get("settleCcy").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            spec.status = InstrumentStatus::from_exchange(item.get("state").and_then(|s| s.as_str()).unwrap_or_default());
+            spec.min_qty = positive_decimal_field(item, "minSz").unwrap_or_default();
+            spec.max_qty = positive_decimal_field(item, "maxLmtSz");
+            spec.market_max_qty = positive_decimal_field(item, "maxMktSz");
+            spec.contract_multiplier = contract_value * contract_multiplier;
+            Some(spec)
+        }).collect()
+    }
+
+    /// 下单，数量单位为合约张数
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        let body = Self::build_order_body(order)?;
+        let data = self.signed_request(Method::POST, ORDER_PATH, &[], Some(body)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderPlacementFailed(msg),
+                other => other,
+            })?;
+
+        let result = data.first()
+            .ok_or_else(|| ConnectorError::InvalidResponse("OKX下单响应为空".to_string()))?;
+        let order_id = result.get("ordId").and_then(|v| v.as_str()).filter(|id| !id.is_empty())
+            .ok_or_else(|| ConnectorError::InvalidResponse("OKX下单响应缺少ordId".to_string()))?;
+        let client_order_id = result.get("clOrdId").and_then(|v| v.as_str())
+            .filter(|id| !id.is_empty())
+            .map(|id| id.to_string())
+            .or_else(|| order.client_order_id.clone());
+
+        // 下单接口只返回订单ID，成交信息需通过查询或私有推送获取
+        Ok(OrderResponse {
+            order_id: order_id.to_string(),
+            client_order_id,
+            symbol: Self::to_okx_inst_id(&order.symbol),
+            status: "NEW".to_string(),
+            filled_quantity: 0.0,
+            remaining_quantity: order.quantity,
+            average_price: None,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        })
+    }
+
+    /// 撤单
+    pub async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
+        let body = json!({
+            "instId": Self::to_okx_inst_id(symbol),
+            "ordId": order_id,
+        });
+        self.signed_request(Method::POST, CANCEL_ORDER_PATH, &[], Some(body)).await
+            .map_err(|e| match e {
+                ConnectorError::TradingError(msg) => ConnectorError::OrderCancellationFailed(msg),
+                other => other,
+            })?;
+        Ok(true)
+    }
+
+    /// 查询订单状态
+    pub async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        let inst_id = Self::to_okx_inst_id(symbol);
+        let query = [("instId", inst_id.clone()), ("ordId", order_id.to_string())];
+        let data = self.signed_request(Method::GET, ORDER_PATH, &query, None).await?;
+        let order = data.first()
+            .ok_or_else(|| ConnectorError::InvalidResponse(format!("OKX未找到订单 {order_id} ({inst_id})")))?;
+        Self::parse_order_status(order)
+    }
+
+    /// 查询交易账户余额
+    pub async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        let data = self.signed_request(Method::GET, BALANCE_PATH, &[], None).await?;
+        let account = data.first()
+            .ok_or_else(|| ConnectorError::InvalidResponse("OKX余额响应为空".to_string()))?;
+        Ok(Self::parse_account_balance(account))
+    }
+
+    /// 将统一订单请求转换为V5下单参数
+    pub fn build_order_body(order: &OrderRequest) -> Result<Value, ConnectorError> {
+        let ord_type = match (order.order_type, order.time_in_force) {
+            (OrderType::Market, _) => "market",
+            (OrderType::Limit, Some(TimeInForce::GTC) | None) => "limit",
+            (OrderType::Limit, Some(TimeInForce::IOC)) => "ioc",
+            (OrderType::Limit, Some(TimeInForce::FOK)) => "fok",
+            (OrderType::Limit, Some(TimeInForce::GTX)) => "post_only",
+            (OrderType::Limit, Some(TimeInForce::GTD)) => {
+                return Err(ConnectorError::InvalidOrderParameters("OKX不支持GTD订单".to_string()));
+            }
+            (OrderType::StopMarket | OrderType::StopLimit, _) => {
+                return Err(ConnectorError::InvalidOrderParameters(
+                    "OKX连接器暂不支持条件单".to_string(),
+                ));
+            }
+        };
+
+        let mut body = json!({
+            "instId": Self::to_okx_inst_id(&order.symbol),
+            "tdMode": OKX_TD_MODE,
+            "side": match order.side {
+                OrderSide::Buy => "buy",
+                OrderSide::Sell => "sell",
+            },
+            "ordType": ord_type,
+            "sz": order.quantity.to_string(),
+        });
+
+        if order.order_type == OrderType::Limit {
+            let price = order.price.ok_or_else(|| {
+                ConnectorError::InvalidOrderParameters("限价单必须指定价格".to_string())
+            })?;
+            body["px"] = json!(price.to_string());
+        }
+        // 单向持仓模式不传posSide
+        match order.position_side {
+            Some(PositionSide::Long) => body["posSide"] = json!("long"),
+            Some(PositionSide::Short) => body["posSide"] = json!("short"),
+            Some(PositionSide::Both) | None => {}
+        }
+        if let Some(reduce_only) = order.reduce_only {
+            body["reduceOnly"] = json!(reduce_only);
+        }
+        if let Some(ref client_order_id) = order.client_order_id {
+            body["clOrdId"] = json!(client_order_id);
+        }
+
+        Ok(body)
+    }
+
+    /// 解析订单详情
+    pub fn parse_order_status(order: &Value) -> Result<OrderStatus, ConnectorError> {
+        let order_id = order.get("ordId").and_then(|v| v.as_str())
+            .ok_or_else(|| ConnectorError::InvalidResponse("OKX订单缺少ordId".to_string()))?;
+        let size = Self::field_f64(order, "sz").unwrap_or(0.0);
+        let filled = Self::field_f64(order, "accFillSz").unwrap_or(0.0);
+
+        Ok(OrderStatus {
+            order_id: order_id.to_string(),
+            symbol: order.get("instId").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            status: Self::normalize_order_status(
+                order.get("state").and_then(|v| v.as_str()).unwrap_or_default(),
+            ).to_string(),
+            filled_quantity: filled,
+            remaining_quantity: (size - filled).max(0.0),
+            average_price: Self::field_f64(order, "avgPx").filter(|p| *p > 0.0),
+            timestamp: Self::field_f64(order, "uTime").map(|t| t as u64)
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64),
+        })
+    }
+
+    /// 解析账户余额：总权益为美元计价，可用部分扣除占用的初始保证金
+    pub fn parse_account_balance(account: &Value) -> AccountBalance {
+        let mut balances = HashMap::new();
+        for detail in account.get("details").and_then(|d| d.as_array()).into_iter().flatten() {
+            let Some(currency) = detail.get("ccy").and_then(|c| c.as_str()) else { continue };
+            let total = Self::field_f64(detail, "eq").unwrap_or(0.0);
+            let available = Self::field_f64(detail, "availBal").unwrap_or(total);
+            balances.insert(currency.to_string(), CurrencyBalance {
+                currency: currency.to_string(),
+                total,
+                available,
+                frozen: Self::field_f64(detail, "frozenBal").unwrap_or((total - available).max(0.0)),
+            });
+        }
+
+        let total = Self::field_f64(account, "totalEq").unwrap_or(0.0);
+        let frozen = Self::field_f64(account, "imr").unwrap_or(0.0);
+        AccountBalance {
+            total,
+            available: (total - frozen).max(0.0),
+            frozen,
+            balances,
+        }
+    }
+
+    /// OKX订单状态转换为统一的大写状态
+    pub fn normalize_order_status(state: &str) -> &'static str {
+        match state {
+            "live" => "NEW",
+            "partially_filled" => "PARTIALLY_FILLED",
+            "filled" => "FILLED",
+            "canceled" | "mmp_canceled" => "CANCELED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// 标准符号转换为OKX永续合约ID（BTCUSDT => BTC-USDT-SWAP）
+    pub fn to_okx_inst_id(symbol: &str) -> String {
+        let symbol = symbol.rsplit(':').next().unwrap_or(symbol).to_uppercase();
+        if symbol.ends_with("-SWAP") {
+            return symbol;
+        }
+
+        let compact = symbol.replace(['_', '-', '/'], "");
+        for quote in QUOTE_CURRENCIES {
+            if let Some(base) = compact.strip_suffix(quote).filter(|b| !b.is_empty()) {
+                return format!("{base}-{quote}-SWAP");
+            }
+        }
+        compact
+    }
+
+    /// OKX合约ID转换为标准符号（BTC-USDT-SWAP => BTCUSDT）
+    pub fn inst_id_to_symbol(inst_id: &str) -> String {
+        inst_id.strip_suffix("-SWAP").unwrap_or(inst_id).replace('-', "")
+    }
+
+    /// 读取字符串或数字格式的数值字段，空字符串视为缺失
+    pub fn field_f64(value: &Value, key: &str) -> Option<f64> {
+        let field = value.get(key)?;
+        field.as_f64().or_else(|| field.as_str().and_then(|s| s.parse::<f64>().ok()))
+    }
+
+    /// 发送公开请求并返回 `data` 数组
+    async fn public_request(&self, path: &str, query: &[(&str, String)]) -> Result<Vec<Value>, ConnectorError> {
+        let url = format!("{}{}", self.base_url, path);
+        debug!("[OKX] GET {url}");
+        let response = self.client.get(&url).query(query).send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("OKX请求失败: {e}")))?;
+        let status = response.status();
+        let data: Value = response.json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析OKX响应失败 (HTTP {status}): {e}")))?;
+        Self::into_items(data)
+    }
+
+    /// 发送签名请求并返回 `data` 数组
+    async fn signed_request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Value>,
+    ) -> Result<Vec<Value>, ConnectorError> {
+        let (Some(api_key), Some(secret_key), Some(passphrase)) = (&self.api_key, &self.secret_key, &self.passphrase) else {
+            return Err(ConnectorError::InvalidCredentials("OKX API密钥或passphrase未配置".to_string()));
+        };
+
+        let mut request_path = path.to_string();
+        if !query.is_empty() {
+            let query_string = query.iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join("&");
+            request_path.push('?');
+            request_path.push_str(&query_string);
+        }
+        let body_string = body.map(|b| b.to_string()).unwrap_or_default();
+
+        let now_ms = self.clock.now_ms();
+        let timestamp = chrono::DateTime::from_timestamp_millis(now_ms)
+            .unwrap_or_else(chrono::Utc::now)
+            .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            .to_string();
+        let signature = Self::sign(secret_key, &timestamp, method.as_str(), &request_path, &body_string);
+
+        let url = format!("{}{}", self.base_url, request_path);
+        debug!("[OKX] {method} {url}");
+
+        let mut request = self.client.request(method.clone(), &url)
+            .header("OK-ACCESS-KEY", api_key)
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", passphrase)
+            // 超过接收窗口仍未处理的请求由服务器拒绝
+            .header("expTime", (now_ms + self.clock.recv_window_ms() as i64).to_string())
+            .header("Content-Type", "application/json");
+        if self.simulated {
+            request = request.header("x-simulated-trading", "1");
+        }
+        if method != Method::GET {
+            request = request.body(body_string);
+        }
+
+        let response = request.send().await
+            .map_err(|e| ConnectorError::NetworkError(format!("OKX请求失败: {e}")))?;
+        let status = response.status();
+        let data: Value = response.json().await
+            .map_err(|e| ConnectorError::DataParsingError(format!("解析OKX响应失败 (HTTP {status}): {e}")))?;
+
+        Self::into_items(data)
+    }
+
+    /// 检查 `code` 并取出 `data` 数组
+    fn into_items(data: Value) -> Result<Vec<Value>, ConnectorError> {
+        let items = data.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+        match data.get("code").and_then(|c| c.as_str()) {
+            Some("0") => Ok(items),
+            Some(code) => {
+                // 批量/交易类接口的具体错误放在 data[].sCode/sMsg 中
+                let detail = items.first()
+                    .and_then(|item| Some((item.get("sCode")?.as_str()?, item.get("sMsg")?.as_str()?)))
+                    .filter(|(s_code, _)| !s_code.is_empty() && *s_code != "0");
+                let (code, message) = match detail {
+                    Some((s_code, s_msg)) => (s_code, s_msg),
+                    None => (code, data.get("msg").and_then(|m| m.as_str()).unwrap_or_default()),
+                };
+                Err(Self::map_error(code, message))
+            }
+            None => Err(ConnectorError::InvalidResponse(format!("OKX响应缺少code: {data}"))),
+        }
+    }
+
+    /// 将OKX错误码映射为连接器错误
+    fn map_error(code: &str, message: &str) -> ConnectorError {
+        let detail = format!("OKX错误 {code}: {message}");
+        match code {
+            "50100" | "50101" | "50102" | "50103" | "50104" | "50105" | "50111" | "50113" | "50114" => {
+                ConnectorError::AuthenticationFailed(detail)
+            }
+            "50011" | "50061" => ConnectorError::RateLimitExceeded(detail),
+            "51008" | "51131" => ConnectorError::InsufficientBalance(detail),
+            "51000" | "51001" | "51121" => ConnectorError::InvalidOrderParameters(detail),
+            _ => ConnectorError::TradingError(detail),
+        }
+    }
+}
+
+#[async_trait]
+impl ServerTimeSource for OkxRestClient {
+    async fn server_time_ms(&self) -> Result<i64, ConnectorError> {
+        self.get_server_time().await
+    }
+}
+
+#[async_trait]
+impl InstrumentSource for OkxRestClient {
+    fn exchange(&self) -> Exchange {
+        Exchange::OkxFutures
+    }
+
+    async fn load_instruments(&self) -> Result<Vec<InstrumentSpec>, ConnectorError> {
+        self.get_instruments().await
+    }
+}
@@ -0,0 +1,512 @@
+//! OKX连接器测试模块
+//! 使用录制的V5推送和REST响应，在本地模拟WebSocket/HTTP服务器上离线测试
+
+mod tests {
+    use super::super::adapter::OkxConnector;
+    use super::super::orderbook::OkxBookAction;
+    use super::super::rest::OkxRestClient;
+    use super::super::websocket::{OkxFrame, OkxWebSocketHandler};
+    use crate::connectors::common::data_channel::DataReceiver;
+    use crate::connectors::test_support::{MockHttpServer, MockResponse, MockWsServer, RecordedRequest};
+    use crate::connectors::traits::ExchangeConnector;
+    use crate::core::AppState;
+    use crate::types::{
+        config::{ConnectorConfig, ConnectionStatus},
+        errors::ConnectorError,
+        exchange::ExchangeType,
+        market_data::{StandardizedMessage, TradeSide, UserData},
+        orders::{OrderRequest, OrderSide, OrderType, PositionSide, TimeInForce},
+    };
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::timeout;
+    use tokio_tungstenite::tungstenite::Message;
+
+    const TEST_API_KEY: &str = "okx_test_key";
+    const TEST_SECRET_KEY: &str = "okx_test_secret";
+    const TEST_PASSPHRASE: &str = "okx_test_passphrase";
+
+    /// 录制的400档深度快照
+    const RECORDED_BOOK_SNAPSHOT: &str = r#"{"arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"action":"snapshot","data":[{"asks":[["64012.0","1.53","0","3"],["64012.5","0.2","0","1"],["64013.1","12.5","0","4"]],"bids":[["64011.9","5.32","0","2"],["64011.5","0.78","0","1"],["64010.0","20","0","5"]],"ts":"1710755362722","checksum":-1574237023,"seqId":123456,"prevSeqId":-1}]}"#;
+
+    /// 录制的深度增量：删除64011.9买档，新增64011.7买档，更新64012.0卖档
+    const RECORDED_BOOK_UPDATE: &str = r#"{"arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"action":"update","data":[{"asks":[["64012.0","1","0","2"]],"bids":[["64011.9","0","0","0"],["64011.7","3","0","1"]],"ts":"1710755362822","checksum":1607318019,"seqId":123460,"prevSeqId":123456}]}"#;
+
+    /// 校验和与本地订单簿不一致的增量，应触发重新订阅
+    const RECORDED_BAD_CHECKSUM_UPDATE: &str = r#"{"arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"action":"update","data":[{"asks":[],"bids":[["64011.6","1","0","1"]],"ts":"1710755362922","checksum":12345,"seqId":123465,"prevSeqId":123460}]}"#;
+
+    /// 录制的5档深度推送
+    const RECORDED_BOOKS5: &str = r#"{"arg":{"channel":"books5","instId":"BTC-USDT-SWAP"},"data":[{"asks":[["64012.0","1.53","0","3"]],"bids":[["64011.9","5.32","0","2"]],"instId":"BTC-USDT-SWAP","ts":"1710755362722","seqId":123456}]}"#;
+
+    /// 录制的成交推送（数量单位为张）
+    const RECORDED_TRADE: &str = r#"{"arg":{"channel":"trades","instId":"BTC-USDT-SWAP"},"data":[{"instId":"BTC-USDT-SWAP","tradeId":"242720720","px":"64012.0","sz":"15","side":"sell","ts":"1710755363460","count":"1"}]}"#;
+
+    /// 录制的私有订单推送
+    const RECORDED_ORDER: &str = r#"{"arg":{"channel":"orders","instType":"SWAP","uid":"77982378738415879"},"data":[{"instType":"SWAP","instId":"BTC-USDT-SWAP","ordId":"680800019749904384","clOrdId":"arb0001","px":"64000","sz":"10","ordType":"limit","side":"buy","posSide":"long","tdMode":"cross","accFillSz":"4","avgPx":"64000","state":"partially_filled","cTime":"1710755363990","uTime":"1710755364005"}]}"#;
+
+    /// 录制的私有持仓推送
+    const RECORDED_POSITION: &str = r#"{"arg":{"channel":"positions","instType":"SWAP","uid":"77982378738415879"},"data":[{"instType":"SWAP","instId":"BTC-USDT-SWAP","posSide":"short","pos":"4","avgPx":"64000","upl":"-0.42","mgnMode":"cross","uTime":"1710755364015"}]}"#;
+
+    /// 录制的私有账户推送
+    const RECORDED_ACCOUNT: &str = r#"{"arg":{"channel":"account","uid":"77982378738415879"},"data":[{"totalEq":"10250.5","imr":"204.8","uTime":"1710755364030","details":[{"ccy":"USDT","eq":"10250.5","availBal":"10045.7","frozenBal":"204.8"}]}]}"#;
+
+    /// 录制的下单响应
+    const RECORDED_ORDER_RESPONSE: &str = r#"{"code":"0","msg":"","data":[{"clOrdId":"arb0001","ordId":"680800019749904384","tag":"","ts":"1710755363991","sCode":"0","sMsg":"Order placed"}],"inTime":"1710755363990000","outTime":"1710755363992000"}"#;
+
+    /// 录制的订单查询响应
+    const RECORDED_ORDER_DETAIL: &str = r#"{"code":"0","msg":"","data":[{"instType":"SWAP","instId":"BTC-USDT-SWAP","ordId":"680800019749904384","clOrdId":"arb0001","px":"64000","sz":"10","ordType":"ioc","side":"buy","posSide":"long","accFillSz":"4","avgPx":"64000","state":"partially_filled","cTime":"1710755363990","uTime":"1710755364005"}]}"#;
+
+    /// 录制的撤单响应
+    const RECORDED_CANCEL_RESPONSE: &str = r#"{"code":"0","msg":"","data":[{"clOrdId":"arb0001","ordId":"680800019749904384","ts":"1710755364200","sCode":"0","sMsg":""}]}"#;
+
+    /// 录制的账户余额响应
+    const RECORDED_BALANCE_RESPONSE: &str = r#"{"code":"0","msg":"","data":[{"totalEq":"10250.5","imr":"204.8","uTime":"1710755364300","details":[{"ccy":"USDT","eq":"10250.5","availBal":"10045.7","frozenBal":"204.8"}]}]}"#;
+
+    /// 录制的余额不足错误响应，具体错误码在sCode中
+    const RECORDED_INSUFFICIENT_BALANCE: &str = r#"{"code":"1","msg":"All operations failed","data":[{"clOrdId":"arb0001","ordId":"","tag":"","sCode":"51008","sMsg":"Order failed. Insufficient USDT margin in account"}]}"#;
+
+    /// 录制的永续合约交易规则（节选）：一个U本位合约、一个币本位合约
+    const RECORDED_INSTRUMENTS_RESPONSE: &str = r#"{"code":"0","msg":"","data":[{"instType":"SWAP","instId":"BTC-USDT-SWAP","uly":"BTC-USDT","settleCcy":"USDT","ctValCcy":"BTC","ctVal":"0.01","ctMult":"1","ctType":"linear","tickSz":"0.1","lotSz":"0.01","minSz":"0.01","maxLmtSz":"100000000","maxMktSz":"5000","state":"live","listTime":"1611916828000"},{"instType":"SWAP","instId":"BTC-USD-SWAP","uly":"BTC-USD","settleCcy":"BTC","ctValCcy":"USD","ctVal":"100","ctMult":"1","ctType":"inverse","tickSz":"0.1","lotSz":"1","minSz":"1","maxLmtSz":"100000000","maxMktSz":"10000","state":"live","listTime":"1611916828000"}]}"#;
+
+    /// 启动模拟WebSocket服务器：校验登录签名，收到订阅后回放录制的推送。
+    /// 每条连接首次订阅books时回放快照、正常增量和校验和错误的增量，之后只回放快照。
+    /// `drop_first_connection` 为true时，第一条连接在回放后被服务器关闭，用于测试重连。
+    async fn start_mock_ws_server(drop_first_connection: bool) -> MockWsServer {
+        MockWsServer::start(move |mut ws, connection| async move {
+            let mut books_subscriptions = 0;
+
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if text == "ping" {
+                    ws.send(Message::Text("pong".to_string())).await.unwrap();
+                    continue;
+                }
+                let msg: Value = serde_json::from_str(&text).unwrap();
+                connection.record(&msg);
+
+                let mut replies = Vec::new();
+                match msg["op"].as_str() {
+                    Some("login") => {
+                        let args = &msg["args"][0];
+                        let timestamp = args["timestamp"].as_str().unwrap_or_default();
+                        let expected = OkxRestClient::sign(TEST_SECRET_KEY, timestamp, "GET", "/users/self/verify", "");
+                        if args["apiKey"] == TEST_API_KEY && args["passphrase"] == TEST_PASSPHRASE && args["sign"] == expected.as_str() {
+                            replies.push(r#"{"event":"login","code":"0","msg":"","connId":"mock"}"#.to_string());
+                        } else {
+                            replies.push(r#"{"event":"error","code":"60009","msg":"Login failed.","connId":"mock"}"#.to_string());
+                        }
+                    }
+                    Some(op @ ("subscribe" | "unsubscribe")) => {
+                        for arg in msg["args"].as_array().unwrap() {
+                            replies.push(json!({"event": op, "arg": arg, "connId": "mock"}).to_string());
+                            if op == "unsubscribe" {
+                                continue;
+                            }
+                            let frames: &[&str] = match arg["channel"].as_str().unwrap() {
+                                "books" => {
+                                    books_subscriptions += 1;
+                                    if books_subscriptions == 1 {
+                                        &[RECORDED_BOOK_SNAPSHOT, RECORDED_BOOK_UPDATE, RECORDED_BAD_CHECKSUM_UPDATE]
+                                    } else {
+                                        &[RECORDED_BOOK_SNAPSHOT]
+                                    }
+                                }
+                                "books5" => &[RECORDED_BOOKS5],
+                                "trades" => &[RECORDED_TRADE],
+                                "orders" => &[RECORDED_ORDER],
+                                "positions" => &[RECORDED_POSITION],
+                                "account" => &[RECORDED_ACCOUNT],
+                                _ => &[],
+                            };
+                            replies.extend(frames.iter().map(|f| f.to_string()));
+                        }
+                    }
+                    _ => {}
+                }
+
+                for reply in replies {
+                    ws.send(Message::Text(reply)).await.unwrap();
+                }
+                if drop_first_connection && connection.index == 0 && msg["op"] == "subscribe" {
+                    let _ = ws.close(None).await;
+                    return;
+                }
+            }
+        }).await
+    }
+
+    /// 按OKX V5规则校验签名：timestamp + method + 路径(含查询串) + body
+    fn assert_signed(request: &RecordedRequest) {
+        assert_eq!(request.headers["ok-access-key"], TEST_API_KEY);
+        assert_eq!(request.headers["ok-access-passphrase"], TEST_PASSPHRASE);
+        assert_eq!(request.headers["x-simulated-trading"], "1");
+        let timestamp = &request.headers["ok-access-timestamp"];
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok(), "时间戳应为ISO格式: {timestamp}");
+        let expected = OkxRestClient::sign(TEST_SECRET_KEY, timestamp, &request.method, &request.target, &request.body);
+        assert_eq!(request.headers["ok-access-sign"], expected, "签名不匹配: {} {}", request.method, request.target);
+    }
+
+    /// 启动模拟REST服务器，按路径返回录制的响应
+    async fn start_mock_http_server(routes: HashMap<&'static str, &'static str>) -> MockHttpServer {
+        MockHttpServer::start(move |request| {
+            MockResponse::ok(routes.get(request.path()).copied().unwrap_or(r#"{"code":"50000","msg":"unknown path","data":[]}"#))
+        }).await
+    }
+
+    /// 创建测试用的连接器配置
+    fn create_test_config(ws_url: Option<&str>, rest_url: Option<&str>) -> ConnectorConfig {
+        ConnectorConfig {
+            api_key: Some(TEST_API_KEY.to_string()),
+            secret_key: Some(TEST_SECRET_KEY.to_string()),
+            passphrase: Some(TEST_PASSPHRASE.to_string()),
+            testnet: true,
+            websocket_url: ws_url.map(|u| u.to_string()),
+            rest_api_url: rest_url.map(|u| u.to_string()),
+            reconnect_interval: 50,
+            max_reconnect_attempts: 3,
+            ping_interval: 30000,
+            request_timeout: 2000,
+        }
+    }
+
+    fn create_test_order() -> OrderRequest {
+        OrderRequest {
+            symbol: "BTC/USDT".to_string(),
+            exchange: ExchangeType::OkxFutures,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity: 10.0,
+            price: Some(64000.0),
+            time_in_force: Some(TimeInForce::IOC),
+            client_order_id: Some("arb0001".to_string()),
+            reduce_only: Some(false),
+            close_position: None,
+            position_side: Some(PositionSide::Long),
+        }
+    }
+
+    async fn next_message(receiver: &mut DataReceiver<StandardizedMessage>) -> StandardizedMessage {
+        timeout(Duration::from_secs(5), receiver.recv()).await
+            .expect("等待标准化消息超时")
+            .expect("消息通道已关闭")
+    }
+
+    #[test]
+    fn test_okx_parse_recorded_frames() {
+        match OkxWebSocketHandler::parse_message(RECORDED_BOOK_UPDATE).unwrap() {
+            OkxFrame::Book(data) => {
+                assert_eq!(data.inst_id, "BTC-USDT-SWAP");
+                assert_eq!(data.action, OkxBookAction::Update);
+                assert_eq!(data.checksum, Some(1607318019));
+                assert_eq!((data.prev_seq_id, data.seq_id), (123456, 123460));
+                assert_eq!(data.bids.len(), 2);
+                assert_eq!(data.timestamp, 1710755362822);
+            }
+            other => panic!("应该解析为订单簿: {other:?}"),
+        }
+
+        // books5没有action字段，按快照处理
+        match OkxWebSocketHandler::parse_message(RECORDED_BOOKS5).unwrap() {
+            OkxFrame::Book(data) => {
+                assert_eq!(data.action, OkxBookAction::Snapshot);
+                assert_eq!(data.checksum, None);
+            }
+            other => panic!("应该解析为订单簿: {other:?}"),
+        }
+
+        match OkxWebSocketHandler::parse_message(RECORDED_TRADE).unwrap() {
+            OkxFrame::Trades(trades) => {
+                assert_eq!(trades[0].symbol, "BTCUSDT");
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[0].price, 64012.0);
+                assert_eq!(trades[0].quantity, 15.0);
+                assert_eq!(trades[0].trade_id, "242720720");
+                assert_eq!(trades[0].exchange, ExchangeType::OkxFutures);
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        match OkxWebSocketHandler::parse_message(RECORDED_POSITION).unwrap() {
+            OkxFrame::Positions(positions) => {
+                assert_eq!(positions[0].symbol, "BTCUSDT");
+                assert_eq!(positions[0].size, -4.0);
+            }
+            other => panic!("应该解析为仓位: {other:?}"),
+        }
+
+        assert!(matches!(OkxWebSocketHandler::parse_message("pong").unwrap(), OkxFrame::Pong));
+        assert!(matches!(
+            OkxWebSocketHandler::parse_message(r#"{"event":"error","code":"60012","msg":"Invalid request","connId":"x"}"#).unwrap(),
+            OkxFrame::Event { ref event, ref code, .. } if event == "error" && code == "60012"
+        ));
+        assert!(OkxWebSocketHandler::parse_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_okx_symbol_and_order_body_mapping() {
+        assert_eq!(OkxRestClient::to_okx_inst_id("BTCUSDT"), "BTC-USDT-SWAP");
+        assert_eq!(OkxRestClient::to_okx_inst_id("eth/usdc"), "ETH-USDC-SWAP");
+        assert_eq!(OkxRestClient::to_okx_inst_id("OKX_FUTURES:SOLUSDT"), "SOL-USDT-SWAP");
+        assert_eq!(OkxRestClient::inst_id_to_symbol("BTC-USDT-SWAP"), "BTCUSDT");
+
+        let body = OkxRestClient::build_order_body(&create_test_order()).unwrap();
+        assert_eq!(body["instId"], "BTC-USDT-SWAP");
+        assert_eq!(body["tdMode"], "cross");
+        assert_eq!(body["side"], "buy");
+        assert_eq!(body["ordType"], "ioc");
+        assert_eq!(body["sz"], "10");
+        assert_eq!(body["px"], "64000");
+        assert_eq!(body["posSide"], "long");
+        assert_eq!(body["clOrdId"], "arb0001");
+
+        let market = OrderRequest { order_type: OrderType::Market, price: None, position_side: None, ..create_test_order() };
+        let body = OkxRestClient::build_order_body(&market).unwrap();
+        assert_eq!(body["ordType"], "market");
+        assert!(body.get("px").is_none());
+        assert!(body.get("posSide").is_none());
+
+        let stop = OrderRequest { order_type: OrderType::StopMarket, ..create_test_order() };
+        assert!(matches!(OkxRestClient::build_order_body(&stop), Err(ConnectorError::InvalidOrderParameters(_))));
+    }
+
+    #[tokio::test]
+    async fn test_okx_checksum_mismatch_triggers_resubscribe() {
+        let _ = env_logger::try_init();
+
+        let mut server = start_mock_ws_server(false).await;
+        let app_state = Arc::new(AppState::new());
+        let connector = OkxConnector::new(create_test_config(Some(&server.url), None), app_state);
+        let mut receiver = connector.get_market_data_stream();
+
+        connector.connect_websocket().await.expect("连接模拟服务器应该成功");
+        assert_eq!(connector.get_connection_status().await, ConnectionStatus::Connected);
+        connector.subscribe_orderbook("BTCUSDT").await.unwrap();
+
+        let (_, path, sub) = server.expect(|_, _, msg| msg["op"] == "subscribe").await;
+        assert_eq!(path, "/ws/v5/public");
+        assert_eq!(sub["args"][0], json!({"channel": "books", "instId": "BTC-USDT-SWAP"}));
+
+        // 快照和正常增量通过校验后发布；消费不及时时两次更新可能被合并为最新一条
+        let latest = loop {
+            match next_message(&mut receiver).await {
+                StandardizedMessage::OrderBookUpdate(orderbook) if orderbook.best_bid == 64011.7 => break orderbook,
+                StandardizedMessage::OrderBookUpdate(orderbook) => assert_eq!(orderbook.best_bid, 64011.9),
+                other => panic!("收到意外的消息: {other:?}"),
+            }
+        };
+        assert_eq!(latest.symbol, "BTCUSDT");
+        assert_eq!(latest.depth_asks[0], (64012.0, 1.0));
+
+        // 校验和错误的增量不发布，先退订再重新订阅深度频道
+        let (_, _, unsub) = server.expect(|_, _, msg| msg["op"] == "unsubscribe").await;
+        assert_eq!(unsub["args"][0]["instId"], "BTC-USDT-SWAP");
+        server.expect(|_, _, msg| msg["op"] == "subscribe").await;
+
+        // 重新订阅后的快照重建订单簿
+        match next_message(&mut receiver).await {
+            StandardizedMessage::OrderBookUpdate(orderbook) => assert_eq!(orderbook.best_bid, 64011.9),
+            other => panic!("收到意外的消息: {other:?}"),
+        }
+        let snapshot = connector.get_orderbook_snapshot("BTCUSDT").await.expect("应该有订单簿快照");
+        assert_eq!(snapshot.depth_bids.len(), 3);
+
+        let stats = connector.error_recovery().get_recovery_stats().await;
+        assert_eq!(stats.total_errors, 1);
+
+        connector.disconnect_websocket().await.unwrap();
+        assert_eq!(connector.get_connection_status().await, ConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_okx_books5_and_trades() {
+        let mut server = start_mock_ws_server(false).await;
+        let connector = OkxConnector::new(create_test_config(Some(&server.url), None), Arc::new(AppState::new()));
+        let mut receiver = connector.get_market_data_stream();
+
+        connector.connect_websocket().await.unwrap();
+        connector.subscribe_books5("BTCUSDT").await.unwrap();
+        connector.subscribe_trades("BTCUSDT").await.unwrap();
+        server.expect(|_, _, msg| msg["args"][0]["channel"] == "trades").await;
+
+        let mut got_book = false;
+        let mut got_trade = false;
+        while !(got_book && got_trade) {
+            match next_message(&mut receiver).await {
+                StandardizedMessage::OrderBookUpdate(orderbook) => {
+                    assert_eq!((orderbook.best_bid, orderbook.best_ask), (64011.9, 64012.0));
+                    got_book = true;
+                }
+                StandardizedMessage::TradeUpdate(trade) => {
+                    assert_eq!(trade.quantity, 15.0);
+                    got_trade = true;
+                }
+                other => panic!("收到意外的消息: {other:?}"),
+            }
+        }
+        assert_eq!(connector.get_recent_trades_snapshot("BTCUSDT", 10).await.len(), 1);
+
+        connector.disconnect_websocket().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_okx_private_stream_relogins_on_reconnect() {
+        let _ = env_logger::try_init();
+
+        let mut server = start_mock_ws_server(true).await;
+        let connector = OkxConnector::new(create_test_config(Some(&server.url), None), Arc::new(AppState::new()));
+        let mut receiver = connector.get_user_data_stream();
+
+        connector.subscribe_user_stream().await.expect("私有频道登录应该成功");
+
+        let (_, path, login) = server.expect(|conn, _, msg| conn == 0 && msg["op"] == "login").await;
+        assert_eq!(path, "/ws/v5/private");
+        assert_eq!(login["args"][0]["apiKey"], TEST_API_KEY);
+        let (_, _, sub) = server.expect(|conn, _, msg| conn == 0 && msg["op"] == "subscribe").await;
+        let mut channels: Vec<_> = sub["args"].as_array().unwrap().iter()
+            .map(|arg| arg["channel"].as_str().unwrap())
+            .collect();
+        channels.sort();
+        assert_eq!(channels, vec!["account", "orders", "positions"]);
+
+        let mut got_order = false;
+        let mut got_position = false;
+        let mut got_balance = false;
+        while !(got_order && got_position && got_balance) {
+            match next_message(&mut receiver).await {
+                StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(order)) => {
+                    assert_eq!(order.symbol, "BTCUSDT");
+                    assert_eq!(order.status, "PARTIALLY_FILLED");
+                    assert_eq!(order.filled_quantity, 4.0);
+                    assert_eq!(order.remaining_quantity, 6.0);
+                    got_order = true;
+                }
+                StandardizedMessage::UserDataUpdate(UserData::PositionUpdate(position)) => {
+                    assert_eq!(position.size, -4.0);
+                    assert_eq!(position.unrealized_pnl, -0.42);
+                    got_position = true;
+                }
+                StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(balance)) => {
+                    assert_eq!(balance.asset, "USDT");
+                    assert_eq!(balance.free, 10045.7);
+                    assert_eq!(balance.locked, 204.8);
+                    got_balance = true;
+                }
+                other => panic!("收到意外的消息: {other:?}"),
+            }
+        }
+
+        // 第一条连接被服务器关闭，重连后应重新登录再恢复订阅
+        server.expect(|conn, _, msg| conn == 1 && msg["op"] == "login").await;
+        server.expect(|conn, _, msg| conn == 1 && msg["op"] == "subscribe").await;
+
+        connector.disconnect_websocket().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_okx_private_stream_rejects_bad_credentials() {
+        let server = start_mock_ws_server(false).await;
+        let config = ConnectorConfig {
+            passphrase: Some("wrong_passphrase".to_string()),
+            ..create_test_config(Some(&server.url), None)
+        };
+        let connector = OkxConnector::new(config, Arc::new(AppState::new()));
+
+        let result = connector.subscribe_user_stream().await;
+        assert!(matches!(result, Err(ConnectorError::AuthenticationFailed(_))), "登录信息错误应该鉴权失败: {result:?}");
+
+        let no_passphrase = ConnectorConfig { passphrase: None, ..create_test_config(Some(&server.url), None) };
+        let connector = OkxConnector::new(no_passphrase, Arc::new(AppState::new()));
+        assert!(matches!(connector.subscribe_user_stream().await, Err(ConnectorError::InvalidCredentials(_))));
+    }
+
+    #[tokio::test]
+    async fn test_okx_rest_order_lifecycle() {
+        let _ = env_logger::try_init();
+
+        let routes = HashMap::from([
+            ("/api/v5/trade/order", RECORDED_ORDER_RESPONSE),
+            ("/api/v5/trade/cancel-order", RECORDED_CANCEL_RESPONSE),
+            ("/api/v5/account/balance", RECORDED_BALANCE_RESPONSE),
+        ]);
+        let server = start_mock_http_server(routes).await;
+        let connector = OkxConnector::new(create_test_config(None, Some(&server.url)), Arc::new(AppState::new()));
+
+        let response = connector.place_order(&create_test_order()).await.expect("下单应该成功");
+        assert_eq!(response.order_id, "680800019749904384");
+        assert_eq!(response.client_order_id.as_deref(), Some("arb0001"));
+
+        let request = server.request(0).await;
+        assert_eq!((request.method.as_str(), request.path()), ("POST", "/api/v5/trade/order"));
+        assert_signed(&request);
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(body["ordType"], "ioc");
+        assert_eq!(body["posSide"], "long");
+
+        let detail_routes = HashMap::from([("/api/v5/trade/order", RECORDED_ORDER_DETAIL)]);
+        let detail_server = start_mock_http_server(detail_routes).await;
+        let reader = OkxConnector::new(create_test_config(None, Some(&detail_server.url)), Arc::new(AppState::new()));
+        let status = reader.get_order_status(&response.order_id, "BTCUSDT").await.expect("查询订单应该成功");
+        assert_eq!(status.status, "PARTIALLY_FILLED");
+        assert_eq!(status.filled_quantity, 4.0);
+        assert_eq!(status.remaining_quantity, 6.0);
+        assert_eq!(status.average_price, Some(64000.0));
+        assert_eq!(status.timestamp, 1710755364005);
+
+        let request = detail_server.request(0).await;
+        assert_eq!((request.method.as_str(), request.path()), ("GET", "/api/v5/trade/order"));
+        assert_eq!(request.query(), "instId=BTC-USDT-SWAP&ordId=680800019749904384");
+        assert_signed(&request);
+
+        assert!(connector.cancel_order(&response.order_id, "BTCUSDT").await.expect("撤单应该成功"));
+        let request = server.request(1).await;
+        assert_eq!(request.path(), "/api/v5/trade/cancel-order");
+        assert_signed(&request);
+
+        let balance = connector.get_account_balance().await.expect("查询余额应该成功");
+        assert_eq!(balance.total, 10250.5);
+        assert_eq!(balance.frozen, 204.8);
+        assert_eq!(balance.balances["USDT"].available, 10045.7);
+        let request = server.request(2).await;
+        assert_eq!((request.method.as_str(), request.path()), ("GET", "/api/v5/account/balance"));
+        assert_signed(&request);
+    }
+
+    #[tokio::test]
+    async fn test_okx_rest_error_mapping() {
+        let routes = HashMap::from([("/api/v5/trade/order", RECORDED_INSUFFICIENT_BALANCE)]);
+        let server = start_mock_http_server(routes).await;
+        let connector = OkxConnector::new(create_test_config(None, Some(&server.url)), Arc::new(AppState::new()));
+
+        let result = connector.place_order(&create_test_order()).await;
+        assert!(matches!(result, Err(ConnectorError::InsufficientBalance(ref msg)) if msg.contains("51008")), "{result:?}");
+
+        let no_keys = ConnectorConfig { passphrase: None, ..create_test_config(None, Some(&server.url)) };
+        let connector = OkxConnector::new(no_keys, Arc::new(AppState::new()));
+        assert!(matches!(connector.get_account_balance().await, Err(ConnectorError::InvalidCredentials(_))));
+    }
+
+    #[tokio::test]
+    async fn test_okx_instruments_use_contract_value() {
+        let routes = HashMap::from([("/api/v5/public/instruments", RECORDED_INSTRUMENTS_RESPONSE)]);
+        let server = start_mock_http_server(routes).await;
+        let connector = OkxConnector::new(create_test_config(None, Some(&server.url)), Arc::new(AppState::new()));
+
+        let specs = connector.instrument_source().expect("OKX应该提供交易规则").load_instruments().await.unwrap();
+        let request = server.request(0).await;
+        assert_eq!(request.query(), "instType=SWAP");
+
+        // 币本位合约被过滤
+        assert_eq!(specs.len(), 1);
+        let spec = &specs[0];
+        assert_eq!((spec.symbol.as_str(), spec.exchange_symbol.as_str()), ("BTCUSDT", "BTC-USDT-SWAP"));
+        assert_eq!(spec.contract_multiplier.to_string(), "0.01");
+        assert_eq!(spec.market_max_qty.map(|q| q.to_string()).as_deref(), Some("5000"));
+        // 10张 = 0.1 BTC
+        assert_eq!(spec.base_quantity(10.0), 0.1);
+        assert_eq!(spec.round_qty(10.016), 10.01);
+        assert!(spec.validate(Some(64000.05), 1.0, false).is_err());
+        assert!(spec.validate(Some(64000.1), 1.0, false).is_ok());
+    }
+}
@@ -0,0 +1,984 @@
+//! OKX WebSocket处理器
+//! 实现OKX V5 WebSocket协议：books/books5/trades公共频道、登录签名的orders/positions/account私有频道、
+//! 文本心跳以及断线重连。订单簿校验失败时通过 `SmartErrorRecovery` 选择恢复动作并重新订阅。
+
+use crate::core::{AppState, AppError, OrderbookUpdate};
+use crate::connectors::common::data_channel::{ChannelStats, DataSender};
+use crate::connectors::common::local_orderbook::parse_price_levels;
+use crate::connectors::common::smart_error_recovery::{
+    ErrorContext, ErrorRecord, ErrorSeverity, ErrorType, RecoveryAction, SmartErrorRecovery,
+};
+use crate::exchange_types::Exchange;
+use crate::types::config::{ConnectorConfig, ConnectionStatus};
+use crate::types::exchange::ExchangeType;
+use crate::types::market_data::{
+    BalanceUpdate, OrderUpdate, PositionUpdate, StandardizedMessage, StandardizedOrderBook,
+    StandardizedTrade, TradeSide, UserData,
+};
+use super::orderbook::{OkxBookAction, OkxBookData, OkxBookOutcome, OkxOrderBook};
+use super::rest::OkxRestClient;
+use chrono::Utc;
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn, error, debug};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::timeout;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+/// OKX实盘WebSocket地址
+pub const OKX_WS_URL: &str = "wss://ws.okx.com:8443";
+
+/// OKX模拟盘WebSocket地址
+pub const OKX_DEMO_WS_URL: &str = "wss://wspap.okx.com:8443";
+
+/// 每个交易对保留的最近成交条数
+const RECENT_TRADES_CAPACITY: usize = 200;
+
+/// 单条订阅请求最多携带的频道数量
+const MAX_ARGS_PER_REQUEST: usize = 20;
+
+/// 登录签名使用的固定路径
+const LOGIN_VERIFY_PATH: &str = "/users/self/verify";
+
+type OkxWsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// 订阅项：频道及可选的instId
+pub type OkxSubscription = (OkxChannel, Option<String>);
+
+/// OKX WebSocket连接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OkxStream {
+    /// 公共行情
+    Public,
+    /// 私有数据（需登录）
+    Private,
+}
+
+impl OkxStream {
+    fn path(&self) -> &'static str {
+        match self {
+            OkxStream::Public => "/ws/v5/public",
+            OkxStream::Private => "/ws/v5/private",
+        }
+    }
+
+    fn connection_id(&self) -> &'static str {
+        match self {
+            OkxStream::Public => "okx-public",
+            OkxStream::Private => "okx-private",
+        }
+    }
+}
+
+/// OKX订阅频道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OkxChannel {
+    /// 400档深度，首条快照后推送增量，带校验和
+    Books,
+    /// 5档深度，每次推送全量
+    Books5,
+    /// 逐笔成交
+    Trades,
+    /// 订单
+    Orders,
+    /// 持仓
+    Positions,
+    /// 账户余额
+    Account,
+}
+
+impl OkxChannel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OkxChannel::Books => "books",
+            OkxChannel::Books5 => "books5",
+            OkxChannel::Trades => "trades",
+            OkxChannel::Orders => "orders",
+            OkxChannel::Positions => "positions",
+            OkxChannel::Account => "account",
+        }
+    }
+
+    fn is_book(&self) -> bool {
+        matches!(self, OkxChannel::Books | OkxChannel::Books5)
+    }
+
+    /// 生成订阅参数，公共频道需要instId，合约私有频道需要instType
+    fn arg(&self, inst_id: Option<&str>) -> Value {
+        match (self, inst_id) {
+            (OkxChannel::Orders | OkxChannel::Positions, _) => json!({"channel": self.as_str(), "instType": "SWAP"}),
+            (_, Some(inst_id)) => json!({"channel": self.as_str(), "instId": inst_id}),
+            (_, None) => json!({"channel": self.as_str()}),
+        }
+    }
+}
+
+/// 解析后的OKX消息帧
+#[derive(Debug, Clone)]
+pub enum OkxFrame {
+    /// 心跳回应
+    Pong,
+    /// 订阅、登录及错误事件
+    Event { event: String, code: String, message: String, arg: Option<Value> },
+    /// 订单簿快照或增量
+    Book(OkxBookData),
+    /// 成交记录
+    Trades(Vec<StandardizedTrade>),
+    /// 订单更新
+    Orders(Vec<OrderUpdate>),
+    /// 仓位更新
+    Positions(Vec<PositionUpdate>),
+    /// 账户余额更新
+    Account(Vec<BalanceUpdate>),
+    /// 其他消息
+    Other(Value),
+}
+
+/// 连接会话结束原因
+enum SessionEnd {
+    /// 调用方主动停止
+    Shutdown,
+    /// 连接断开或被要求重连
+    Lost,
+}
+
+/// OKX WebSocket处理器
+#[derive(Clone)]
+pub struct OkxWebSocketHandler {
+    app_state: Arc<AppState>,
+    stream: OkxStream,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    ws_url: String,
+    api_key: Option<String>,
+    secret_key: Option<String>,
+    passphrase: Option<String>,
+    ping_interval: Duration,
+    reconnect_interval: Duration,
+    max_reconnect_attempts: u32,
+    connect_timeout: Duration,
+    status: Arc<RwLock<ConnectionStatus>>,
+    should_run: Arc<RwLock<bool>>,
+    command_sender: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>>,
+    subscriptions: Arc<RwLock<HashSet<OkxSubscription>>>,
+    orderbooks: Arc<DashMap<String, OkxOrderBook>>,
+    recent_trades: Arc<DashMap<String, VecDeque<StandardizedTrade>>>,
+    error_recovery: Arc<SmartErrorRecovery>,
+}
+
+impl OkxWebSocketHandler {
+    /// 创建新的OKX WebSocket处理器
+    ///
+    /// `websocket_url` 配置视为基础地址，连接时追加对应频道的路径。
+    pub fn new(config: &ConnectorConfig, stream: OkxStream, app_state: Arc<AppState>) -> Self {
+        let base_url = config.websocket_url.clone().unwrap_or_else(|| {
+            if config.testnet { OKX_DEMO_WS_URL } else { OKX_WS_URL }.to_string()
+        });
+        if stream == OkxStream::Public {
+            // 行情连接登记到AppState，套利扫描器发现订单簿持续过期时据此发出重连信号
+            app_state.register_market_connection(Exchange::OkxFutures, stream.connection_id());
+        }
+
+        Self {
+            app_state,
+            stream,
+            message_sender: Arc::new(RwLock::new(None)),
+            ws_url: format!("{}{}", base_url.trim_end_matches('/'), stream.path()),
+            api_key: config.api_key.clone().filter(|k| !k.is_empty()),
+            secret_key: config.secret_key.clone().filter(|k| !k.is_empty()),
+            passphrase: config.passphrase.clone().filter(|p| !p.is_empty()),
+            ping_interval: Duration::from_millis(config.ping_interval.max(1)),
+            reconnect_interval: Duration::from_millis(config.reconnect_interval),
+            max_reconnect_attempts: config.max_reconnect_attempts,
+            connect_timeout: Duration::from_millis(config.request_timeout.max(1)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            should_run: Arc::new(RwLock::new(false)),
+            command_sender: Arc::new(RwLock::new(None)),
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            orderbooks: Arc::new(DashMap::new()),
+            recent_trades: Arc::new(DashMap::new()),
+            error_recovery: Arc::new(SmartErrorRecovery::with_default_config()),
+        }
+    }
+
+    /// 连接地址
+    pub fn url(&self) -> &str {
+        &self.ws_url
+    }
+
+    /// 订单簿校验失败时使用的错误恢复管理器
+    pub fn error_recovery(&self) -> Arc<SmartErrorRecovery> {
+        self.error_recovery.clone()
+    }
+
+    /// 设置消息发送器
+    pub async fn set_message_sender(&self, sender: DataSender<StandardizedMessage>) {
+        let mut message_sender = self.message_sender.write().await;
+        *message_sender = Some(sender);
+    }
+
+    /// 同步设置消息发送器（用于非异步上下文，例如获取数据流）
+    pub fn try_set_message_sender(&self, sender: DataSender<StandardizedMessage>) -> bool {
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => {
+                *message_sender = Some(sender);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 当前消息通道的计数快照
+    pub fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
+    /// 启动WebSocket连接
+    ///
+    /// 首次连接（私有频道含登录）失败直接返回错误；连接建立后在后台维护心跳和断线重连。
+    pub async fn start(&self) -> Result<(), AppError> {
+        {
+            let status = self.status.read().await;
+            if matches!(*status, ConnectionStatus::Connected | ConnectionStatus::Connecting | ConnectionStatus::Reconnecting) {
+                debug!("[OKX] {} 连接已在运行，跳过启动", self.connection_id());
+                return Ok(());
+            }
+        }
+
+        info!("[OKX] {} 连接到 {}", self.connection_id(), self.ws_url);
+        *self.status.write().await = ConnectionStatus::Connecting;
+        *self.should_run.write().await = true;
+
+        let ws_stream = match self.open().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                *self.status.write().await = ConnectionStatus::Error;
+                *self.should_run.write().await = false;
+                return Err(e);
+            }
+        };
+
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let command_tx_owner = command_tx.clone();
+        *self.command_sender.write().await = Some(command_tx);
+        *self.status.write().await = ConnectionStatus::Connected;
+        self.app_state.update_connection_timestamp(self.connection_id());
+        self.app_state.clear_reconnect_signal(self.connection_id());
+
+        let handler = self.clone();
+        tokio::spawn(async move {
+            handler.connection_loop(ws_stream, command_tx_owner, command_rx).await;
+        });
+
+        info!("[OKX] {} WebSocket连接成功", self.connection_id());
+        Ok(())
+    }
+
+    /// 停止WebSocket连接，不再重连
+    pub async fn stop(&self) -> Result<(), AppError> {
+        *self.should_run.write().await = false;
+
+        if let Some(sender) = self.command_sender.write().await.take() {
+            let _ = sender.send(Message::Close(None));
+        }
+
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        self.app_state.mark_connection_unhealthy(self.connection_id());
+        info!("[OKX] {} WebSocket已停止", self.connection_id());
+        Ok(())
+    }
+
+    /// 订阅400档增量深度（带校验和）
+    pub async fn subscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_public(OkxChannel::Books, symbols).await
+    }
+
+    /// 订阅5档全量深度
+    pub async fn subscribe_books5(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_public(OkxChannel::Books5, symbols).await
+    }
+
+    /// 订阅成交数据
+    pub async fn subscribe_trades(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        self.subscribe_public(OkxChannel::Trades, symbols).await
+    }
+
+    /// 订阅私有的订单、持仓和账户频道
+    pub async fn subscribe_private(&self) -> Result<(), AppError> {
+        if self.stream != OkxStream::Private {
+            return Err(AppError::ConfigError("公共连接不能订阅私有频道".to_string()));
+        }
+        let keys = [OkxChannel::Orders, OkxChannel::Positions, OkxChannel::Account]
+            .into_iter()
+            .map(|channel| (channel, None))
+            .collect();
+        self.subscribe_keys(keys).await
+    }
+
+    /// 取消订阅交易对的全部频道
+    pub async fn unsubscribe(&self, symbols: Vec<String>) -> Result<(), AppError> {
+        let mut removed = Vec::new();
+        {
+            let mut subscriptions = self.subscriptions.write().await;
+            for symbol in &symbols {
+                let inst_id = OkxRestClient::to_okx_inst_id(symbol);
+                for channel in [OkxChannel::Books, OkxChannel::Books5, OkxChannel::Trades] {
+                    let key = (channel, Some(inst_id.clone()));
+                    if subscriptions.remove(&key) {
+                        removed.push(key);
+                    }
+                }
+                self.orderbooks.remove(&inst_id);
+            }
+        }
+
+        for chunk in removed.chunks(MAX_ARGS_PER_REQUEST) {
+            self.send_command(Self::args_message("unsubscribe", chunk)).await?;
+        }
+        info!("[OKX] 已取消订阅: {symbols:?}");
+        Ok(())
+    }
+
+    /// 检查连接状态
+    pub async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    /// 获取当前连接状态
+    pub async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    /// 获取当前的订阅列表 (频道, instId)
+    pub async fn get_subscriptions(&self) -> Vec<OkxSubscription> {
+        self.subscriptions.read().await.iter().cloned().collect()
+    }
+
+    /// 获取本地维护的订单簿
+    pub fn get_orderbook(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        let inst_id = OkxRestClient::to_okx_inst_id(symbol);
+        self.orderbooks.get(&inst_id)?.snapshot(&OkxRestClient::inst_id_to_symbol(&inst_id))
+    }
+
+    /// 获取本地缓存的最近成交（按时间从新到旧）
+    pub fn get_recent_trades(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        let symbol = OkxRestClient::inst_id_to_symbol(&OkxRestClient::to_okx_inst_id(symbol));
+        self.recent_trades
+            .get(&symbol)
+            .map(|trades| trades.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 解析一条OKX V5文本消息
+    pub fn parse_message(text: &str) -> Result<OkxFrame, AppError> {
+        if text == "pong" {
+            return Ok(OkxFrame::Pong);
+        }
+
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("解析OKX消息失败: {e}")))?;
+
+        if let Some(event) = msg.get("event").and_then(|e| e.as_str()) {
+            return Ok(OkxFrame::Event {
+                event: event.to_string(),
+                code: msg.get("code").and_then(|c| c.as_str()).unwrap_or("0").to_string(),
+                message: msg.get("msg").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+                arg: msg.get("arg").cloned(),
+            });
+        }
+
+        let arg = msg.get("arg");
+        let channel = arg.and_then(|a| a.get("channel")).and_then(|c| c.as_str()).unwrap_or_default();
+        match channel {
+            "books" | "books5" => {
+                let inst_id = arg.and_then(|a| a.get("instId")).and_then(|i| i.as_str()).unwrap_or_default();
+                Self::parse_book(&msg, inst_id).map(OkxFrame::Book)
+            }
+            "trades" => Self::parse_trades(&msg).map(OkxFrame::Trades),
+            "orders" => Ok(OkxFrame::Orders(Self::parse_orders(&msg))),
+            "positions" => Ok(OkxFrame::Positions(Self::parse_positions(&msg))),
+            "account" => Ok(OkxFrame::Account(Self::parse_account(&msg))),
+            _ => Ok(OkxFrame::Other(msg)),
+        }
+    }
+
+    /// 解析订单簿推送，books5没有action字段，每条都是全量
+    fn parse_book(msg: &Value, inst_id: &str) -> Result<OkxBookData, AppError> {
+        let data = msg.get("data").and_then(|d| d.as_array()).and_then(|d| d.first())
+            .ok_or_else(|| AppError::ParseError("OKX深度消息缺少data字段".to_string()))?;
+
+        let action = match msg.get("action").and_then(|a| a.as_str()) {
+            Some("update") => OkxBookAction::Update,
+            _ => OkxBookAction::Snapshot,
+        };
+
+        Ok(OkxBookData {
+            inst_id: inst_id.to_string(),
+            action,
+            bids: parse_price_levels(data.get("bids")),
+            asks: parse_price_levels(data.get("asks")),
+            checksum: data.get("checksum").and_then(|c| c.as_i64()).map(|c| c as i32),
+            seq_id: data.get("seqId").and_then(|s| s.as_i64()).unwrap_or_default(),
+            prev_seq_id: data.get("prevSeqId").and_then(|s| s.as_i64()).unwrap_or(-1),
+            timestamp: Self::value_f64(data, "ts").map(|t| t as i64).unwrap_or_else(|| Utc::now().timestamp_millis()),
+        })
+    }
+
+    /// 解析成交记录，永续合约的数量单位为张
+    fn parse_trades(msg: &Value) -> Result<Vec<StandardizedTrade>, AppError> {
+        let trades = msg.get("data").and_then(|d| d.as_array())
+            .ok_or_else(|| AppError::ParseError("OKX成交消息缺少data字段".to_string()))?;
+
+        trades.iter()
+            .map(|trade| {
+                let price = Self::value_f64(trade, "px")
+                    .ok_or_else(|| AppError::ParseError("OKX成交消息缺少px".to_string()))?;
+                let quantity = Self::value_f64(trade, "sz")
+                    .ok_or_else(|| AppError::ParseError("OKX成交消息缺少sz".to_string()))?;
+                let inst_id = trade.get("instId").and_then(|i| i.as_str()).unwrap_or_default();
+                Ok(StandardizedTrade {
+                    symbol: OkxRestClient::inst_id_to_symbol(inst_id),
+                    exchange: ExchangeType::OkxFutures,
+                    price,
+                    quantity,
+                    side: match trade.get("side").and_then(|s| s.as_str()) {
+                        Some("sell") => TradeSide::Sell,
+                        _ => TradeSide::Buy,
+                    },
+                    timestamp: Self::value_f64(trade, "ts").map(|t| t as i64).unwrap_or_default(),
+                    trade_id: trade.get("tradeId").and_then(|i| i.as_str()).unwrap_or_default().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析订单推送
+    fn parse_orders(msg: &Value) -> Vec<OrderUpdate> {
+        Self::data_items(msg)
+            .filter_map(|order| {
+                let size = Self::value_f64(order, "sz").unwrap_or(0.0);
+                let filled = Self::value_f64(order, "accFillSz").unwrap_or(0.0);
+                Some(OrderUpdate {
+                    order_id: order.get("ordId")?.as_str()?.to_string(),
+                    symbol: OkxRestClient::inst_id_to_symbol(order.get("instId")?.as_str()?),
+                    exchange: ExchangeType::OkxFutures,
+                    status: OkxRestClient::normalize_order_status(
+                        order.get("state").and_then(|s| s.as_str()).unwrap_or_default(),
+                    ).to_string(),
+                    filled_quantity: filled,
+                    remaining_quantity: (size - filled).max(0.0),
+                    average_price: Self::value_f64(order, "avgPx").filter(|price| *price > 0.0),
+                    timestamp: Self::value_f64(order, "uTime").map(|t| t as i64)
+                        .unwrap_or_else(|| Utc::now().timestamp_millis()),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析持仓推送，双向持仓模式下空头数量为负
+    fn parse_positions(msg: &Value) -> Vec<PositionUpdate> {
+        Self::data_items(msg)
+            .filter_map(|position| {
+                let pos = Self::value_f64(position, "pos").unwrap_or(0.0);
+                let size = match position.get("posSide").and_then(|s| s.as_str()) {
+                    Some("short") => -pos.abs(),
+                    _ => pos,
+                };
+                Some(PositionUpdate {
+                    symbol: OkxRestClient::inst_id_to_symbol(position.get("instId")?.as_str()?),
+                    exchange: ExchangeType::OkxFutures,
+                    size,
+                    entry_price: Self::value_f64(position, "avgPx").unwrap_or(0.0),
+                    unrealized_pnl: Self::value_f64(position, "upl").unwrap_or(0.0),
+                    timestamp: Self::value_f64(position, "uTime").map(|t| t as i64)
+                        .unwrap_or_else(|| Utc::now().timestamp_millis()),
+                })
+            })
+            .collect()
+    }
+
+    /// 解析账户推送
+    fn parse_account(msg: &Value) -> Vec<BalanceUpdate> {
+        Self::data_items(msg)
+            .flat_map(|account| {
+                let timestamp = Self::value_f64(account, "uTime").map(|t| t as i64)
+                    .unwrap_or_else(|| Utc::now().timestamp_millis());
+                OkxRestClient::parse_account_balance(account)
+                    .balances
+                    .into_values()
+                    .map(move |balance| BalanceUpdate {
+                        asset: balance.currency,
+                        exchange: ExchangeType::OkxFutures,
+                        free: balance.available,
+                        locked: balance.frozen,
+                        timestamp,
+                    })
+            })
+            .collect()
+    }
+
+    fn data_items(msg: &Value) -> impl Iterator<Item = &Value> {
+        msg.get("data").and_then(|d| d.as_array()).into_iter().flatten()
+    }
+
+    fn value_f64(value: &Value, key: &str) -> Option<f64> {
+        OkxRestClient::field_f64(value, key)
+    }
+
+    fn connection_id(&self) -> &'static str {
+        self.stream.connection_id()
+    }
+
+    fn args_message(op: &str, keys: &[OkxSubscription]) -> Message {
+        let args: Vec<Value> = keys.iter().map(|(channel, inst_id)| channel.arg(inst_id.as_deref())).collect();
+        Message::Text(json!({"op": op, "args": args}).to_string())
+    }
+
+    async fn subscribe_public(&self, channel: OkxChannel, symbols: Vec<String>) -> Result<(), AppError> {
+        let keys = symbols.iter()
+            .map(|symbol| (channel, Some(OkxRestClient::to_okx_inst_id(symbol))))
+            .collect();
+        self.subscribe_keys(keys).await
+    }
+
+    async fn subscribe_keys(&self, keys: Vec<OkxSubscription>) -> Result<(), AppError> {
+        info!("[OKX] {} 订阅: {keys:?}", self.connection_id());
+
+        let mut added = Vec::new();
+        {
+            let mut subscriptions = self.subscriptions.write().await;
+            for key in keys {
+                if subscriptions.insert(key.clone()) {
+                    added.push(key);
+                }
+            }
+        }
+
+        // 未连接时只记录订阅，连接建立后统一发送
+        if self.is_connected().await {
+            for chunk in added.chunks(MAX_ARGS_PER_REQUEST) {
+                self.send_command(Self::args_message("subscribe", chunk)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_command(&self, message: Message) -> Result<(), AppError> {
+        match self.command_sender.read().await.as_ref() {
+            Some(sender) => sender
+                .send(message)
+                .map_err(|e| AppError::WebSocketError(format!("OKX发送队列已关闭: {e}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// 建立连接，私有频道在返回前完成登录
+    async fn open(&self) -> Result<OkxWsStream, AppError> {
+        let (mut ws_stream, _) = timeout(self.connect_timeout, connect_async(&self.ws_url))
+            .await
+            .map_err(|_| AppError::WebSocketError(format!("连接OKX WebSocket超时: {}", self.ws_url)))?
+            .map_err(|e| AppError::WebSocketError(format!("连接OKX WebSocket失败: {e}")))?;
+
+        if self.stream == OkxStream::Private {
+            self.login(&mut ws_stream).await?;
+        }
+        Ok(ws_stream)
+    }
+
+    /// 发送登录请求并等待服务器确认
+    async fn login(&self, ws_stream: &mut OkxWsStream) -> Result<(), AppError> {
+        let (Some(api_key), Some(secret_key), Some(passphrase)) = (&self.api_key, &self.secret_key, &self.passphrase) else {
+            return Err(AppError::ConfigError("OKX私有频道需要API密钥和passphrase".to_string()));
+        };
+
+        let timestamp = Utc::now().timestamp().to_string();
+        let login = json!({
+            "op": "login",
+            "args": [{
+                "apiKey": api_key,
+                "passphrase": passphrase,
+                "timestamp": timestamp,
+                "sign": OkxRestClient::sign(secret_key, &timestamp, "GET", LOGIN_VERIFY_PATH, ""),
+            }],
+        });
+        ws_stream
+            .send(Message::Text(login.to_string()))
+            .await
+            .map_err(|e| AppError::WebSocketError(format!("发送OKX登录请求失败: {e}")))?;
+
+        timeout(self.connect_timeout, async {
+            while let Some(message) = ws_stream.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(_) => continue,
+                    Err(e) => return Err(AppError::WebSocketError(format!("等待OKX登录回应失败: {e}"))),
+                };
+                match Self::parse_message(&text) {
+                    Ok(OkxFrame::Event { event, code, .. }) if event == "login" && code == "0" => {
+                        info!("[OKX] {} 登录成功", self.connection_id());
+                        return Ok(());
+                    }
+                    Ok(OkxFrame::Event { event, code, message, .. }) if event == "login" || event == "error" => {
+                        return Err(AppError::ConnectionError(format!("OKX登录失败 {code}: {message}")));
+                    }
+                    _ => {}
+                }
+            }
+            Err(AppError::WebSocketError("OKX登录前连接被关闭".to_string()))
+        })
+        .await
+        .map_err(|_| AppError::WebSocketError("等待OKX登录回应超时".to_string()))?
+    }
+
+    /// 连接维护循环：处理单次会话，断线后按配置重连（私有频道重新登录）并恢复订阅
+    async fn connection_loop(
+        self,
+        mut ws_stream: OkxWsStream,
+        command_tx: mpsc::UnboundedSender<Message>,
+        mut command_rx: mpsc::UnboundedReceiver<Message>,
+    ) {
+        loop {
+            if let Err(e) = self.resubscribe_all(&mut ws_stream).await {
+                warn!("[OKX] {} 恢复订阅失败: {e}", self.connection_id());
+            }
+
+            if let SessionEnd::Shutdown = self.run_session(ws_stream, &mut command_rx).await {
+                break;
+            }
+            if !*self.should_run.read().await {
+                break;
+            }
+
+            // 断线期间的增量无法补齐，等待新连接推送快照
+            self.orderbooks.clear();
+            self.app_state.mark_connection_unhealthy(self.connection_id());
+            *self.status.write().await = ConnectionStatus::Reconnecting;
+
+            let mut reconnected = None;
+            for attempt in 1..=self.max_reconnect_attempts {
+                tokio::time::sleep(self.reconnect_interval).await;
+                if !*self.should_run.read().await {
+                    break;
+                }
+
+                info!("[OKX] {} 重连 (尝试 {}/{})", self.connection_id(), attempt, self.max_reconnect_attempts);
+                match self.open().await {
+                    Ok(stream) => {
+                        reconnected = Some(stream);
+                        break;
+                    }
+                    Err(e) => warn!("[OKX] {} 重连失败: {e}", self.connection_id()),
+                }
+            }
+
+            match reconnected {
+                Some(stream) => {
+                    ws_stream = stream;
+                    *self.status.write().await = ConnectionStatus::Connected;
+                    self.app_state.update_connection_timestamp(self.connection_id());
+                    self.app_state.clear_reconnect_signal(self.connection_id());
+                    info!("[OKX] {} 重连成功", self.connection_id());
+                }
+                None => {
+                    if *self.should_run.read().await {
+                        error!("[OKX] {} 重连次数耗尽，停止连接", self.connection_id());
+                        *self.status.write().await = ConnectionStatus::Error;
+                        *self.should_run.write().await = false;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // 只清理属于本次连接的状态，避免影响停止后重新启动的新连接
+        let mut command_sender = self.command_sender.write().await;
+        if command_sender.as_ref().is_some_and(|sender| sender.same_channel(&command_tx)) {
+            command_sender.take();
+            let mut status = self.status.write().await;
+            if *status != ConnectionStatus::Error {
+                *status = ConnectionStatus::Disconnected;
+            }
+        }
+        drop(command_sender);
+        info!("[OKX] {} 连接循环已退出", self.connection_id());
+    }
+
+    async fn resubscribe_all(&self, ws_stream: &mut OkxWsStream) -> Result<(), AppError> {
+        let mut keys = self.get_subscriptions().await;
+        keys.sort();
+        for chunk in keys.chunks(MAX_ARGS_PER_REQUEST) {
+            ws_stream
+                .send(Self::args_message("subscribe", chunk))
+                .await
+                .map_err(|e| AppError::WebSocketError(format!("发送订阅失败: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// 运行单次连接会话，直到连接断开或收到停止指令
+    async fn run_session(&self, ws_stream: OkxWsStream, command_rx: &mut mpsc::UnboundedReceiver<Message>) -> SessionEnd {
+        let (mut write, mut read) = ws_stream.split();
+        let mut ping_timer = tokio::time::interval(self.ping_interval);
+        ping_timer.tick().await;
+        let mut last_message = Instant::now();
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => {
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            last_message = Instant::now();
+                            self.handle_text(&text).await;
+                        }
+                        Some(Ok(Message::Ping(payload))) => {
+                            last_message = Instant::now();
+                            if let Err(e) = write.send(Message::Pong(payload)).await {
+                                error!("[OKX] {} 发送Pong失败: {e}", self.connection_id());
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("[OKX] {} WebSocket连接被服务器关闭", self.connection_id());
+                            return SessionEnd::Lost;
+                        }
+                        Some(Ok(_)) => {
+                            last_message = Instant::now();
+                        }
+                        Some(Err(e)) => {
+                            error!("[OKX] {} WebSocket错误: {e}", self.connection_id());
+                            return SessionEnd::Lost;
+                        }
+                    }
+                }
+                command = command_rx.recv() => {
+                    match command {
+                        Some(Message::Close(frame)) => {
+                            let _ = write.send(Message::Close(frame)).await;
+                            return SessionEnd::Shutdown;
+                        }
+                        Some(message) => {
+                            if let Err(e) = write.send(message).await {
+                                error!("[OKX] {} 发送消息失败: {e}", self.connection_id());
+                                return SessionEnd::Lost;
+                            }
+                        }
+                        None => return SessionEnd::Shutdown,
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if !*self.should_run.read().await {
+                        return SessionEnd::Shutdown;
+                    }
+                    if self.app_state.should_reconnect(self.connection_id()) {
+                        warn!("[OKX] {} 收到重连信号", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+                    if last_message.elapsed() > self.ping_interval * 3 {
+                        warn!("[OKX] {} 长时间未收到消息，重连", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+
+                    // OKX使用纯文本ping/pong心跳
+                    if let Err(e) = write.send(Message::Text("ping".to_string())).await {
+                        error!("[OKX] {} 发送心跳失败: {e}", self.connection_id());
+                        return SessionEnd::Lost;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 处理文本消息
+    async fn handle_text(&self, text: &str) {
+        self.app_state.increment_websocket_messages(1);
+        self.app_state.update_connection_timestamp(self.connection_id());
+
+        let frame = match Self::parse_message(text) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("[OKX] {} {e}", self.connection_id());
+                return;
+            }
+        };
+
+        match frame {
+            OkxFrame::Pong => {
+                debug!("[OKX] {} 收到pong", self.connection_id());
+            }
+            OkxFrame::Event { event, code, message, arg } => {
+                if event == "error" {
+                    warn!("[OKX] {} 服务器返回错误 {code}: {message}", self.connection_id());
+                } else {
+                    debug!("[OKX] {} {event} {arg:?}", self.connection_id());
+                }
+            }
+            OkxFrame::Book(data) => {
+                self.handle_book(data).await;
+            }
+            OkxFrame::Trades(trades) => {
+                for trade in trades {
+                    self.publish_trade(trade).await;
+                }
+            }
+            OkxFrame::Orders(orders) => {
+                for order in orders {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(order))).await;
+                }
+            }
+            OkxFrame::Positions(positions) => {
+                for position in positions {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::PositionUpdate(position))).await;
+                }
+            }
+            OkxFrame::Account(balances) => {
+                for balance in balances {
+                    self.forward(StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(balance))).await;
+                }
+            }
+            OkxFrame::Other(msg) => {
+                debug!("[OKX] {} 收到其他消息: {msg}", self.connection_id());
+            }
+        }
+    }
+
+    /// 应用订单簿推送，校验失败时触发恢复
+    async fn handle_book(&self, data: OkxBookData) {
+        let symbol = OkxRestClient::inst_id_to_symbol(&data.inst_id);
+        let outcome = self.orderbooks
+            .entry(data.inst_id.clone())
+            .or_insert_with(|| OkxOrderBook::new(&data.inst_id))
+            .apply(&data, &symbol);
+
+        match outcome {
+            OkxBookOutcome::Applied(orderbook) => self.publish_orderbook(orderbook).await,
+            OkxBookOutcome::AwaitingSnapshot => {
+                debug!("[OKX] {} 尚未收到快照，丢弃增量 seqId={}", data.inst_id, data.seq_id);
+            }
+            OkxBookOutcome::SequenceGap { expected, received } => {
+                let reason = format!("{} seqId不连续: 期望prevSeqId={expected}, 收到{received}", data.inst_id);
+                self.recover_orderbook(&data.inst_id, reason).await;
+            }
+            OkxBookOutcome::ChecksumMismatch { expected, actual } => {
+                let reason = format!("{} 订单簿校验和不一致: 推送{expected}, 本地{actual}", data.inst_id);
+                self.recover_orderbook(&data.inst_id, reason).await;
+            }
+        }
+    }
+
+    /// 记录校验失败并执行 `SmartErrorRecovery` 给出的恢复动作
+    ///
+    /// 订单簿已被清空，无论选择哪种策略都必须重新拿到快照：
+    /// 重连动作通过重连信号交给会话循环处理，其余情况重新订阅该合约的深度频道。
+    async fn recover_orderbook(&self, inst_id: &str, reason: String) {
+        warn!("[OKX] {} {reason}，开始恢复", self.connection_id());
+
+        let context = ErrorContext {
+            exchange: "OKX".to_string(),
+            symbol: Some(inst_id.to_string()),
+            connection_status: self.get_connection_status().await,
+            network_quality: None,
+            system_load: None,
+            additional_info: HashMap::from([("channel".to_string(), "books".to_string())]),
+        };
+        self.error_recovery
+            .record_error(ErrorType::ChecksumMismatch, reason.clone(), ErrorSeverity::Medium, context.clone())
+            .await;
+
+        let strategy = self.error_recovery.select_recovery_strategy(ErrorType::ChecksumMismatch, &context).await;
+        let mut record = ErrorRecord {
+            error_type: ErrorType::ChecksumMismatch,
+            error_message: reason,
+            timestamp: SystemTime::now(),
+            severity: ErrorSeverity::Medium,
+            context,
+            recovery_attempts: Vec::new(),
+        };
+        let result = self.error_recovery.execute_recovery(strategy, &mut record).await;
+        debug!("[OKX] {} 恢复策略 {:?}: {}", self.connection_id(), strategy, result.message);
+
+        let reconnect = result.actions.iter()
+            .any(|action| matches!(action, RecoveryAction::CloseConnection | RecoveryAction::EstablishConnection));
+        if reconnect {
+            self.app_state.signal_reconnect(self.connection_id());
+            return;
+        }
+
+        let targets: Vec<String> = result.actions.iter()
+            .filter_map(|action| match action {
+                RecoveryAction::SendSubscription(target) => Some(target.clone()),
+                _ => None,
+            })
+            .collect();
+        let targets = if targets.is_empty() { vec![inst_id.to_string()] } else { targets };
+        for target in targets {
+            if let Err(e) = self.resubscribe_book(&target).await {
+                error!("[OKX] {} 重新订阅 {target} 失败: {e}", self.connection_id());
+            }
+        }
+    }
+
+    /// 退订并重新订阅合约的深度频道，服务器会重新推送快照
+    async fn resubscribe_book(&self, inst_id: &str) -> Result<(), AppError> {
+        let keys: Vec<_> = self.subscriptions.read().await
+            .iter()
+            .filter(|(channel, id)| channel.is_book() && id.as_deref() == Some(inst_id))
+            .cloned()
+            .collect();
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        info!("[OKX] {} 重新订阅深度: {inst_id}", self.connection_id());
+        self.send_command(Self::args_message("unsubscribe", &keys)).await?;
+        self.send_command(Self::args_message("subscribe", &keys)).await
+    }
+
+    async fn publish_orderbook(&self, orderbook: StandardizedOrderBook) {
+        self.app_state.book_store.update(orderbook.clone());
+
+        if let Some(tx) = &self.app_state.orderbook_queue {
+            let update = OrderbookUpdate {
+                symbol: format!("{}:{}", Exchange::OkxFutures, orderbook.symbol),
+                best_ask: orderbook.best_ask,
+                best_bid: orderbook.best_bid,
+                timestamp: orderbook.timestamp,
+                scale: 8,
+                is_synthetic: false,
+                leg1: None,
+                leg2: None,
+                depth_asks: Some(orderbook.depth_asks.clone()),
+                depth_bids: Some(orderbook.depth_bids.clone()),
+            };
+            if let Err(e) = tx.send(update) {
+                error!("[OKX] 发送订单簿更新失败: {e}");
+            }
+        }
+
+        self.forward(StandardizedMessage::OrderBookUpdate(orderbook)).await;
+    }
+
+    async fn publish_trade(&self, trade: StandardizedTrade) {
+        {
+            let mut trades = self.recent_trades.entry(trade.symbol.clone()).or_default();
+            if trades.len() >= RECENT_TRADES_CAPACITY {
+                trades.pop_front();
+            }
+            trades.push_back(trade.clone());
+        }
+
+        self.forward(StandardizedMessage::TradeUpdate(trade)).await;
+    }
+
+    async fn forward(&self, message: StandardizedMessage) {
+        // 先取出发送端再发送，阻塞策略等待消费时不占用锁
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[OKX] {} 消息接收端已关闭", self.connection_id());
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Phemex连接器模块
+//! USDT永续合约行情（orderbook_p / trade_p 频道）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::PhemexProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// Phemex行情连接器
+pub type PhemexConnector = MarketStreamConnector<PhemexProtocol>;
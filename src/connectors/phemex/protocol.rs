@@ -0,0 +1,112 @@
+//! Phemex行情协议
+//! 订阅格式：`{"id":1,"method":"orderbook_p.subscribe","params":["BTCUSDT"]}`，每条请求只能携带一个交易对；
+//! 深度以 snapshot + incremental 推送，时间戳单位为纳秒；心跳为 `server.ping`。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::local_orderbook::parse_price_levels;
+use crate::connectors::common::market_stream::{
+    value_to_f64, value_to_i64, BookDiff, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// Phemex WebSocket默认地址
+pub const PHEMEX_WS_URL: &str = "wss://ws.phemex.com";
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+/// Phemex行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhemexProtocol;
+
+impl MarketStreamProtocol for PhemexProtocol {
+    const NAME: &'static str = "Phemex";
+    const DEFAULT_WS_URL: &'static str = PHEMEX_WS_URL;
+    const EXCHANGE: Exchange = Exchange::Phemex;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::Phemex;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+    const MAX_SYMBOLS_PER_REQUEST: usize = 1;
+
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}{}", info.base_currency, info.quote_currency)
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        exchange_symbol.to_uppercase()
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], request_id: u64) -> String {
+        let topic = match channel {
+            MarketChannel::OrderBook => "orderbook_p",
+            MarketChannel::Trades => "trade_p",
+        };
+        let action = if subscribe { "subscribe" } else { "unsubscribe" };
+        json!({
+            "id": request_id,
+            "method": format!("{topic}.{action}"),
+            "params": symbols,
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        Some(json!({"id": 0, "method": "server.ping", "params": []}).to_string())
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析Phemex消息: {e}")))?;
+
+        if let Some(book) = msg.get("orderbook_p") {
+            let symbol = msg.get("symbol").and_then(|s| s.as_str())
+                .ok_or_else(|| AppError::ParseError("Phemex深度推送缺少symbol".to_string()))?;
+            return Ok(vec![MarketEvent::BookDiff(BookDiff {
+                symbol: Self::standard_symbol(symbol),
+                is_snapshot: msg.get("type").and_then(|t| t.as_str()) == Some("snapshot"),
+                bids: parse_price_levels(book.get("bids")),
+                asks: parse_price_levels(book.get("asks")),
+                update_id: msg.get("sequence").and_then(value_to_i64).unwrap_or(0),
+                timestamp: msg.get("timestamp").and_then(value_to_i64).unwrap_or(0) / NANOS_PER_MILLI,
+            })]);
+        }
+
+        if let Some(trades) = msg.get("trades_p").and_then(|t| t.as_array()) {
+            let symbol = msg.get("symbol").and_then(|s| s.as_str())
+                .ok_or_else(|| AppError::ParseError("Phemex成交推送缺少symbol".to_string()))?;
+            let symbol = Self::standard_symbol(symbol);
+            let sequence = msg.get("sequence").and_then(value_to_i64).unwrap_or(0);
+
+            // 成交推送没有成交ID，用 sequence-序号 标识
+            let trades = trades.iter()
+                .enumerate()
+                .filter_map(|(index, trade)| {
+                    let fields = trade.as_array()?;
+                    Some(StandardizedTrade {
+                        symbol: symbol.clone(),
+                        exchange: Self::EXCHANGE_TYPE,
+                        price: value_to_f64(fields.get(2)?)?,
+                        quantity: value_to_f64(fields.get(3)?)?,
+                        side: if fields.get(1)?.as_str()? == "Sell" { TradeSide::Sell } else { TradeSide::Buy },
+                        timestamp: value_to_i64(fields.first()?)? / NANOS_PER_MILLI,
+                        trade_id: format!("{sequence}-{index}"),
+                    })
+                })
+                .collect();
+            return Ok(vec![MarketEvent::Trades(trades)]);
+        }
+
+        if let Some(error) = msg.get("error").filter(|e| !e.is_null()) {
+            return Ok(vec![MarketEvent::Error(error.to_string())]);
+        }
+
+        if msg.get("result").and_then(|r| r.as_str()) == Some("pong") {
+            return Ok(vec![MarketEvent::Pong]);
+        }
+
+        Ok(vec![MarketEvent::Other(msg)])
+    }
+}
@@ -0,0 +1,231 @@
+//! Phemex连接器测试模块
+//! 使用录制的推送帧测试协议解析，并通过模拟服务器测试连接、订阅和重连
+
+mod tests {
+    use super::super::{PhemexConnector, PhemexProtocol};
+    use crate::config::ExchangeConfig;
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::connectors::test_support::MockWsServer;
+    use crate::connectors::traits::ExchangeConnector;
+    use crate::core::AppState;
+    use crate::exchange_types::Exchange;
+    use crate::types::{
+        config::{ConnectorConfig, ConnectionStatus},
+        market_data::{StandardizedMessage, TradeSide},
+    };
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::Value;
+    use tokio::time::timeout;
+    use tokio_tungstenite::tungstenite::Message;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use log::info;
+
+    /// 录制的Phemex深度快照帧
+    const RECORDED_SNAPSHOT_FRAME: &str = r#"{"depth":0,"orderbook_p":{"asks":[["64012.4","1.532"],["64012.9","0.200"],["64013.1","12.5"]],"bids":[["64011.9","5.321"],["64011.5","0.780"],["64010.0","20.0"]]},"sequence":78934129811,"symbol":"BTCUSDT","timestamp":1710755362722123456,"type":"snapshot"}"#;
+
+    /// 录制的Phemex深度增量帧
+    const RECORDED_INCREMENTAL_FRAME: &str = r#"{"depth":0,"orderbook_p":{"asks":[["64012.4","0"]],"bids":[["64012.1","0.450"]]},"sequence":78934129815,"symbol":"BTCUSDT","timestamp":1710755362801654321,"type":"incremental"}"#;
+
+    /// 录制的Phemex成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"sequence":78934130002,"symbol":"BTCUSDT","trades_p":[[1710755363460123456,"Sell","64011.9","0.015"],[1710755363461000000,"Buy","64012.9","0.200"]],"type":"incremental"}"#;
+
+    /// 录制的Phemex心跳回应和订阅确认
+    const RECORDED_PONG_FRAME: &str = r#"{"error":null,"id":0,"result":"pong"}"#;
+    const RECORDED_SUBSCRIBE_ACK_FRAME: &str = r#"{"error":null,"id":1,"result":{"status":"success"}}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"error":{"code":6001,"message":"invalid argument"},"id":2,"result":null}"#;
+
+    /// 启动模拟服务器：收到深度订阅后回放快照和增量帧。
+    /// `drop_first_connection` 为true时，第一条连接在回放后被服务器关闭，用于测试重连。
+    async fn start_mock_server(drop_first_connection: bool) -> MockWsServer {
+        MockWsServer::start(move |mut ws, connection| async move {
+
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let msg: Value = serde_json::from_str(&text).unwrap();
+                connection.record(&msg);
+
+                match msg["method"].as_str() {
+                    Some("server.ping") => {
+                        ws.send(Message::Text(RECORDED_PONG_FRAME.to_string())).await.unwrap();
+                    }
+                    Some("orderbook_p.subscribe") => {
+                        for frame in [RECORDED_SUBSCRIBE_ACK_FRAME, RECORDED_SNAPSHOT_FRAME, RECORDED_INCREMENTAL_FRAME] {
+                            ws.send(Message::Text(frame.to_string())).await.unwrap();
+                        }
+                        if drop_first_connection && connection.index == 0 {
+                            let _ = ws.close(None).await;
+                            return;
+                        }
+                    }
+                    Some("trade_p.subscribe") => {
+                        ws.send(Message::Text(RECORDED_TRADE_FRAME.to_string())).await.unwrap();
+                    }
+                    _ => {}
+                }
+            }
+        }).await
+    }
+
+    /// 创建指向模拟服务器的连接器配置
+    fn create_mock_config(url: &str) -> ConnectorConfig {
+        ConnectorConfig {
+            websocket_url: Some(url.to_string()),
+            reconnect_interval: 50,
+            max_reconnect_attempts: 3,
+            ping_interval: 3000,
+            request_timeout: 2000,
+            ..ConnectorConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_phemex_parse_recorded_frames() {
+        let events = PhemexProtocol::parse_message(RECORDED_SNAPSHOT_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::BookDiff(diff)] => {
+                assert_eq!(diff.symbol, "BTCUSDT");
+                assert!(diff.is_snapshot);
+                assert_eq!(diff.update_id, 78934129811);
+                assert_eq!(diff.timestamp, 1710755362722);
+                assert_eq!(diff.bids.len(), 3);
+                assert_eq!(diff.asks[0].0.to_string(), "64012.4");
+            }
+            other => panic!("应该解析为深度快照: {other:?}"),
+        }
+
+        let events = PhemexProtocol::parse_message(RECORDED_INCREMENTAL_FRAME).unwrap();
+        assert!(matches!(&events[..], [MarketEvent::BookDiff(diff)] if !diff.is_snapshot && diff.update_id == 78934129815));
+
+        let events = PhemexProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades.len(), 2);
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[0].price, 64011.9);
+                assert_eq!(trades[0].timestamp, 1710755363460);
+                assert_eq!(trades[1].side, TradeSide::Buy);
+                assert_eq!(trades[1].trade_id, "78934130002-1");
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        assert!(matches!(&PhemexProtocol::parse_message(RECORDED_PONG_FRAME).unwrap()[..], [MarketEvent::Pong]));
+        assert!(matches!(&PhemexProtocol::parse_message(RECORDED_SUBSCRIBE_ACK_FRAME).unwrap()[..], [MarketEvent::Other(_)]));
+        assert!(matches!(&PhemexProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+        assert!(PhemexProtocol::parse_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_phemex_subscription_message() {
+        let symbols = vec!["BTCUSDT".to_string()];
+        let msg: Value = serde_json::from_str(&PhemexProtocol::subscription_message(true, MarketChannel::OrderBook, &symbols, 7)).unwrap();
+        assert_eq!(msg["id"], 7);
+        assert_eq!(msg["method"], "orderbook_p.subscribe");
+        assert_eq!(msg["params"][0], "BTCUSDT");
+
+        let msg: Value = serde_json::from_str(&PhemexProtocol::subscription_message(false, MarketChannel::Trades, &symbols, 8)).unwrap();
+        assert_eq!(msg["method"], "trade_p.unsubscribe");
+
+        let ping: Value = serde_json::from_str(&PhemexProtocol::ping_message().unwrap()).unwrap();
+        assert_eq!(ping["method"], "server.ping");
+    }
+
+    #[tokio::test]
+    async fn test_phemex_symbol_conversion() {
+        let connector = PhemexConnector::new(ConnectorConfig::default(), 10, Arc::new(AppState::new()));
+        // Phemex每条订阅只能携带一个交易对
+        assert_eq!(connector.handler().batch_size(), 1);
+
+        for symbol in ["BTCUSDT", "BTC_USDT", "btc-usdt", "PHEMEX:BTC/USDT"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "BTCUSDT");
+        }
+    }
+
+    #[test]
+    fn test_phemex_from_exchange_config() {
+        // 对应config.toml中的 [exchanges.PHEMEX]
+        let exchange_config = ExchangeConfig {
+            websocket_url: "wss://ws.phemex.com".to_string(),
+            api_url: None,
+            api_key: None,
+            api_secret: None,
+            maker_fee_pct: 0.01,
+            taker_fee_pct: 0.06,
+            max_retries: 5,
+            connection_timeout_secs: 30,
+            ping_interval_secs: 3,
+            batch_size: 10,
+            supported_symbols: None,
+        };
+        let connector = PhemexConnector::from_exchange_config(&exchange_config, Arc::new(AppState::new()));
+
+        assert_eq!(connector.config().websocket_url.as_deref(), Some("wss://ws.phemex.com"));
+        assert_eq!(connector.config().ping_interval, 3000);
+        assert_eq!(connector.config().request_timeout, 30000);
+        assert_eq!(connector.config().max_reconnect_attempts, 5);
+        assert_eq!(connector.get_exchange_name(), "Phemex");
+    }
+
+    #[tokio::test]
+    async fn test_phemex_orderbook_and_trades_via_mock_server() {
+        let _ = env_logger::try_init();
+
+        let mut server = start_mock_server(false).await;
+        let app_state = Arc::new(AppState::new());
+        let connector = PhemexConnector::new(create_mock_config(&server.url), 10, app_state.clone());
+        let mut stream = connector.get_market_data_stream();
+        connector.connect_websocket().await.expect("连接模拟服务器应该成功");
+
+        connector.subscribe_orderbook("BTC_USDT").await.unwrap();
+        connector.subscribe_trades("BTCUSDT").await.unwrap();
+        let (_, _, subscribe) = server.expect(|_, _, msg| msg["method"] == "orderbook_p.subscribe").await;
+        assert_eq!(subscribe["params"][0], "BTCUSDT");
+
+        // 快照 + 增量应用后：64012.4卖档被删除，新增64012.1买档
+        // 订单簿更新可能被合并，合并掉的数量从通道统计中补上
+        let mut updates = 0;
+        let mut trades = 0;
+        timeout(Duration::from_secs(5), async {
+            while updates + connector.get_data_flow_stats().conflated_messages < 2 || trades < 2 {
+                match stream.recv().await.expect("数据流已关闭") {
+                    StandardizedMessage::OrderBookUpdate(_) => updates += 1,
+                    StandardizedMessage::TradeUpdate(_) => trades += 1,
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("等待行情推送超时");
+
+        let orderbook = connector.get_orderbook_snapshot("BTCUSDT").await.expect("应该缓存订单簿");
+        assert_eq!(orderbook.best_bid, 64012.1);
+        assert_eq!(orderbook.best_ask, 64012.9);
+
+        // 合并订单簿存储中保留同一份最新订单簿
+        let stored = app_state.book_store.get(Exchange::Phemex, "BTCUSDT").expect("订单簿应该写入合并存储");
+        assert_eq!(stored.best_bid, 64012.1);
+        assert_eq!(connector.get_recent_trades_snapshot("BTCUSDT", 10).await.len(), 2);
+
+        connector.disconnect_websocket().await.unwrap();
+        info!("✅ Phemex模拟服务器订阅测试通过");
+    }
+
+    #[tokio::test]
+    async fn test_phemex_reconnect_resubscribes() {
+        let _ = env_logger::try_init();
+
+        let mut server = start_mock_server(true).await;
+        let connector = PhemexConnector::new(create_mock_config(&server.url), 10, Arc::new(AppState::new()));
+        connector.connect_websocket().await.expect("连接模拟服务器应该成功");
+        connector.subscribe_orderbook("BTCUSDT").await.unwrap();
+
+        // 第一条连接在订阅后被服务器关闭，重连后应自动恢复订阅
+        server.expect(|conn, _, msg| conn == 0 && msg["method"] == "orderbook_p.subscribe").await;
+        let (_, _, resubscribe) = server.expect(|conn, _, msg| conn == 1 && msg["method"] == "orderbook_p.subscribe").await;
+        assert_eq!(resubscribe["params"][0], "BTCUSDT");
+        assert_eq!(connector.get_connection_status().await, ConnectionStatus::Connected);
+
+        connector.disconnect_websocket().await.unwrap();
+        info!("✅ Phemex重连测试通过");
+    }
+}
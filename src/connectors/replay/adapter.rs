@@ -0,0 +1,408 @@
+//! 回放连接器
+//! 读取录制的段文件，按原始节奏、加速或尽快推送 `StandardizedMessage`。
+//!
+//! 回放是确定性的：消息严格按落盘顺序输出，推送通道使用 `Block` 策略不做合并或丢弃，
+//! 因此同一份录制每次回放得到的消息序列完全一致，可以对比不同版本扫描器的输出。
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use log::{error, info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::config::get_config;
+use crate::core::{AppState, OrderbookUpdate};
+use crate::connectors::common::data_channel::{data_channel, ChannelSendError, ChannelStats, DataReceiver, DataSender};
+use crate::connectors::traits::{ExchangeConnector, DataFlowManager};
+use crate::types::{
+    config::{ChannelPolicy, ConnectionStatus, DataChannelConfig, SubscriptionStatus},
+    market_data::{StandardizedMessage, StandardizedOrderBook, StandardizedTrade},
+    orders::{OrderRequest, OrderResponse, OrderStatus},
+    account::AccountBalance,
+    exchange::{ExchangeType, MarketType},
+    errors::ConnectorError,
+    events::{SystemEvent, HighFrequencyData, DataFlowStats},
+};
+use super::segment::{RecordedMessage, SegmentReader};
+
+/// 每个交易对保留的最近成交条数
+const RECENT_TRADES_CAPACITY: usize = 200;
+
+/// 读取线程和回放任务之间的预读队列长度
+const READ_AHEAD: usize = 4096;
+
+/// 回放速度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// 按录制时的时间间隔推送
+    Original,
+    /// 时间间隔按倍数缩短，例如10.0表示10倍速
+    Accelerated(f64),
+    /// 不等待，读到即推送
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    /// 录制时间间隔对应的回放等待时间，None表示不等待
+    fn scale(&self, recorded: Duration) -> Option<Duration> {
+        match *self {
+            ReplaySpeed::Original => Some(recorded),
+            ReplaySpeed::Accelerated(factor) if factor > 0.0 => Some(recorded.div_f64(factor)),
+            ReplaySpeed::Accelerated(_) | ReplaySpeed::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// 回放配置
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    /// 段文件所在目录
+    pub directory: PathBuf,
+    pub speed: ReplaySpeed,
+    /// 连接器对外报告的交易所和市场类型
+    pub exchange: ExchangeType,
+    pub market_type: MarketType,
+    /// 只回放这些交易所的消息，为空时回放全部
+    pub exchanges: Vec<ExchangeType>,
+}
+
+impl ReplayConfig {
+    /// 以尽快速度回放目录中某个交易所的录制
+    pub fn new(directory: impl Into<PathBuf>, exchange: ExchangeType, market_type: MarketType) -> Self {
+        Self {
+            directory: directory.into(),
+            speed: ReplaySpeed::AsFastAsPossible,
+            exchange,
+            market_type,
+            exchanges: vec![exchange],
+        }
+    }
+}
+
+/// 回放结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// 推送的消息数
+    pub replayed: u64,
+    /// 被交易所过滤掉的消息数
+    pub skipped: u64,
+}
+
+/// 回放连接器
+#[derive(Clone)]
+pub struct ReplayConnector {
+    config: ReplayConfig,
+    app_state: Arc<AppState>,
+    message_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    orderbooks: Arc<DashMap<String, StandardizedOrderBook>>,
+    trades: Arc<DashMap<String, VecDeque<StandardizedTrade>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+    playback: Arc<Mutex<Option<JoinHandle<io::Result<ReplaySummary>>>>>,
+    replayed: Arc<AtomicU64>,
+    event_sender: broadcast::Sender<SystemEvent>,
+}
+
+impl ReplayConnector {
+    pub fn new(config: ReplayConfig, app_state: Arc<AppState>) -> Self {
+        let (event_sender, _) = broadcast::channel(1000);
+
+        Self {
+            config,
+            app_state,
+            message_sender: Arc::new(RwLock::new(None)),
+            orderbooks: Arc::new(DashMap::new()),
+            trades: Arc::new(DashMap::new()),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            playback: Arc::new(Mutex::new(None)),
+            replayed: Arc::new(AtomicU64::new(0)),
+            event_sender,
+        }
+    }
+
+    /// 回放配置
+    pub fn config(&self) -> &ReplayConfig {
+        &self.config
+    }
+
+    /// 已推送的消息数
+    pub fn replayed_count(&self) -> u64 {
+        self.replayed.load(Ordering::Relaxed)
+    }
+
+    /// 等待回放结束
+    pub async fn wait_finished(&self) -> Result<ReplaySummary, ConnectorError> {
+        let Some(playback) = self.playback.lock().await.take() else {
+            return Err(ConnectorError::ConnectionError("回放未启动".to_string()));
+        };
+        playback.await
+            .map_err(|e| ConnectorError::ConnectionError(format!("回放任务异常退出: {e}")))?
+            .map_err(|e| ConnectorError::DataParsingError(format!("读取录制文件失败: {e}")))
+    }
+
+    fn channel_stats(&self) -> Option<ChannelStats> {
+        self.message_sender.try_read().ok()?.as_ref().map(DataSender::stats)
+    }
+
+    async fn run_playback(self, records: DataReceiver<io::Result<RecordedMessage>>) -> io::Result<ReplaySummary> {
+        let result = self.play(records).await;
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        match &result {
+            Ok(summary) => info!("[Replay] 回放结束: 推送 {} 条, 跳过 {} 条", summary.replayed, summary.skipped),
+            Err(e) => error!("[Replay] 回放失败: {e}"),
+        }
+        result
+    }
+
+    async fn play(&self, mut records: DataReceiver<io::Result<RecordedMessage>>) -> io::Result<ReplaySummary> {
+        let mut summary = ReplaySummary::default();
+        let mut clock: Option<(i64, Instant)> = None;
+        let mut last_offset = Duration::ZERO;
+
+        while let Some(record) = records.recv().await {
+            let record = record?;
+            if !self.config.exchanges.is_empty() && !self.config.exchanges.contains(&record.exchange) {
+                summary.skipped += 1;
+                continue;
+            }
+
+            // 以第一条消息为时间原点；接收时间偶有回退时保持单调，不影响输出顺序
+            let (origin_us, started) = *clock.get_or_insert((record.received_at_us, Instant::now()));
+            let offset = Duration::from_micros((record.received_at_us - origin_us).max(0) as u64).max(last_offset);
+            last_offset = offset;
+            if let Some(wait) = self.config.speed.scale(offset) {
+                tokio::time::sleep_until(started + wait).await;
+            }
+
+            self.publish(record.message).await;
+            summary.replayed += 1;
+            self.replayed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Ok(summary)
+    }
+
+    /// 与实时连接器一致：更新本地缓存、合并订单簿存储和订单簿队列，再推送到数据流
+    async fn publish(&self, message: StandardizedMessage) {
+        match &message {
+            StandardizedMessage::OrderBookUpdate(orderbook) => {
+                self.orderbooks.insert(orderbook.symbol.clone(), orderbook.clone());
+                self.app_state.book_store.update(orderbook.clone());
+
+                if let Some(tx) = &self.app_state.orderbook_queue {
+                    let update = OrderbookUpdate {
+                        symbol: format!("{}:{}", orderbook.exchange, orderbook.symbol),
+                        best_ask: orderbook.best_ask,
+                        best_bid: orderbook.best_bid,
+                        timestamp: orderbook.timestamp,
+                        scale: 8,
+                        is_synthetic: false,
+                        leg1: None,
+                        leg2: None,
+                        depth_asks: Some(orderbook.depth_asks.clone()),
+                        depth_bids: Some(orderbook.depth_bids.clone()),
+                    };
+                    if let Err(e) = tx.send(update) {
+                        error!("[Replay] 发送订单簿更新失败: {e}");
+                    }
+                }
+            }
+            StandardizedMessage::TradeUpdate(trade) => {
+                let mut trades = self.trades.entry(trade.symbol.clone()).or_default();
+                if trades.len() >= RECENT_TRADES_CAPACITY {
+                    trades.pop_front();
+                }
+                trades.push_back(trade.clone());
+            }
+            _ => {}
+        }
+
+        let sender = self.message_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                warn!("[Replay] 消息接收端已关闭");
+            }
+        }
+    }
+}
+
+/// 在阻塞线程中读取段文件
+fn read_segments(mut reader: SegmentReader, sender: DataSender<io::Result<RecordedMessage>>) {
+    let runtime = tokio::runtime::Handle::current();
+    loop {
+        let next = reader.next_record().transpose();
+        let stop = !matches!(next, Some(Ok(_)));
+        if let Some(record) = next {
+            if runtime.block_on(sender.send(record)).is_err() {
+                return;
+            }
+        }
+        if stop {
+            return;
+        }
+    }
+}
+
+impl crate::connectors::common::data_channel::ConflationKey for io::Result<RecordedMessage> {
+    fn conflation_key(&self) -> Option<String> {
+        None
+    }
+}
+
+#[async_trait]
+impl ExchangeConnector for ReplayConnector {
+    // 基础信息
+    fn get_exchange_type(&self) -> ExchangeType {
+        self.config.exchange
+    }
+
+    fn get_market_type(&self) -> MarketType {
+        self.config.market_type
+    }
+
+    fn get_exchange_name(&self) -> &str {
+        "Replay"
+    }
+
+    // 连接即开始回放
+    async fn connect_websocket(&self) -> Result<(), ConnectorError> {
+        let mut playback = self.playback.lock().await;
+        if playback.as_ref().is_some_and(|task| !task.is_finished()) {
+            return Ok(());
+        }
+
+        let reader = SegmentReader::open_directory(&self.config.directory)
+            .map_err(|e| ConnectorError::ConnectionFailed(format!("无法打开录制目录 {}: {e}", self.config.directory.display())))?;
+        info!("[Replay] 开始回放 {} ({:?})", self.config.directory.display(), self.config.speed);
+
+        let (record_sender, record_receiver) = data_channel(DataChannelConfig {
+            capacity: READ_AHEAD,
+            policy: ChannelPolicy::Block,
+        });
+        tokio::task::spawn_blocking(move || read_segments(reader, record_sender));
+
+        *self.status.write().await = ConnectionStatus::Connected;
+        *playback = Some(tokio::spawn(self.clone().run_playback(record_receiver)));
+        Ok(())
+    }
+
+    async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
+        if let Some(playback) = self.playback.lock().await.take() {
+            playback.abort();
+        }
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        Ok(())
+    }
+
+    // 回放录制中的全部消息，订阅请求不影响输出
+    async fn subscribe_orderbook(&self, _symbol: &str) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    async fn subscribe_trades(&self, _symbol: &str) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    // 推送式数据流接口
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        // 回放不能合并或丢弃消息，否则输出依赖消费速度
+        let (sender, receiver) = data_channel(DataChannelConfig {
+            capacity: get_config().websocket_optimization.market_data_channel.capacity,
+            policy: ChannelPolicy::Block,
+        });
+        match self.message_sender.try_write() {
+            Ok(mut message_sender) => *message_sender = Some(sender),
+            Err(_) => warn!("[Replay] 消息发送器正被占用，返回的数据流不会收到消息"),
+        }
+        receiver
+    }
+
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (_, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        receiver
+    }
+
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        if let Some(channel) = self.channel_stats() {
+            channel.apply_to(&mut stats);
+        }
+        stats
+    }
+
+    // 本地缓存快照读取
+    async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.orderbooks.get(symbol).map(|orderbook| orderbook.clone())
+    }
+
+    async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        self.trades
+            .get(symbol)
+            .map(|trades| trades.iter().rev().take(limit).rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // 回放不支持交易
+    async fn place_order(&self, _order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        Err(ConnectorError::TradingNotImplemented)
+    }
+
+    async fn cancel_order(&self, _order_id: &str, _symbol: &str) -> Result<bool, ConnectorError> {
+        Err(ConnectorError::TradingNotImplemented)
+    }
+
+    async fn get_order_status(&self, _order_id: &str, _symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        Err(ConnectorError::TradingNotImplemented)
+    }
+
+    async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        Err(ConnectorError::TradingNotImplemented)
+    }
+
+    // 连接状态
+    async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    async fn is_websocket_connected(&self) -> bool {
+        self.is_connected().await
+    }
+
+    async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    async fn get_subscription_status(&self) -> Result<HashMap<String, SubscriptionStatus>, ConnectorError> {
+        Ok(HashMap::new())
+    }
+}
+
+#[async_trait]
+impl DataFlowManager for ReplayConnector {
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>> {
+        // 行情通过 get_market_data_stream 推送
+        None
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent> {
+        self.event_sender.subscribe()
+    }
+
+    fn send_market_data(&self, _data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>> {
+        Ok(())
+    }
+
+    async fn send_event(&self, event: SystemEvent) {
+        let _ = self.event_sender.send(event);
+    }
+}
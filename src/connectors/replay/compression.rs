@@ -0,0 +1,209 @@
+//! 段文件使用的块压缩
+//! LZ4块格式的精简实现：哈希表查找4字节匹配，输出 token/字面量/偏移/匹配长度序列。
+//! 行情JSON中重复的字段名和交易对很多，按块压缩后体积通常只有原来的十分之一左右。
+
+use std::fmt;
+
+/// 最短匹配长度
+const MIN_MATCH: usize = 4;
+/// 最后一个匹配必须在距离末尾至少这么多字节之前开始
+const MF_LIMIT: usize = 12;
+/// 块末尾必须保留为字面量的字节数
+const LAST_LITERALS: usize = 5;
+/// 匹配偏移使用u16表示
+const MAX_OFFSET: usize = u16::MAX as usize;
+const HASH_LOG: u32 = 12;
+
+/// 解压失败：数据损坏或与声明的长度不符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecompressError(pub String);
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "压缩块损坏: {}", self.0)
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// 写入长度的扩展字节（每个255表示继续）
+fn write_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+    output.push(length as u8);
+}
+
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], match_info: Option<(usize, usize)>) {
+    let literal_len = literals.len();
+    let match_len = match_info.map_or(0, |(_, len)| len - MIN_MATCH);
+
+    let token = ((literal_len.min(15) as u8) << 4) | match_len.min(15) as u8;
+    output.push(token);
+    if literal_len >= 15 {
+        write_length(output, literal_len - 15);
+    }
+    output.extend_from_slice(literals);
+
+    if let Some((offset, _)) = match_info {
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_len >= 15 {
+            write_length(output, match_len - 15);
+        }
+    }
+}
+
+/// 压缩一个数据块
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + 16);
+    let mut table = vec![0usize; 1 << HASH_LOG];
+    let mut anchor = 0;
+    let mut pos = 0;
+
+    if input.len() > MF_LIMIT {
+        let match_limit = input.len() - MF_LIMIT;
+        let extend_limit = input.len() - LAST_LITERALS;
+
+        while pos < match_limit {
+            let sequence = read_u32(input, pos);
+            let slot = hash(sequence);
+            // 表中保存 位置+1，0表示空槽
+            let candidate = table[slot];
+            table[slot] = pos + 1;
+
+            if candidate == 0 {
+                pos += 1;
+                continue;
+            }
+            let candidate = candidate - 1;
+            if pos - candidate > MAX_OFFSET || read_u32(input, candidate) != sequence {
+                pos += 1;
+                continue;
+            }
+
+            let mut match_len = MIN_MATCH;
+            while pos + match_len < extend_limit && input[candidate + match_len] == input[pos + match_len] {
+                match_len += 1;
+            }
+
+            write_sequence(&mut output, &input[anchor..pos], Some((pos - candidate, match_len)));
+            pos += match_len;
+            anchor = pos;
+        }
+    }
+
+    write_sequence(&mut output, &input[anchor..], None);
+    output
+}
+
+/// 读取长度的扩展字节
+fn read_length(input: &[u8], pos: &mut usize, base: usize) -> Result<usize, DecompressError> {
+    let mut length = base;
+    if base == 15 {
+        loop {
+            let byte = *input.get(*pos).ok_or_else(|| DecompressError("长度字段被截断".to_string()))?;
+            *pos += 1;
+            length += byte as usize;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(length)
+}
+
+/// 解压一个数据块，`raw_len` 为压缩前的长度
+pub fn decompress(input: &[u8], raw_len: usize) -> Result<Vec<u8>, DecompressError> {
+    let mut output = Vec::with_capacity(raw_len);
+    let mut pos = 0;
+
+    while pos < input.len() {
+        let token = input[pos];
+        pos += 1;
+
+        let literal_len = read_length(input, &mut pos, (token >> 4) as usize)?;
+        let literals = input
+            .get(pos..pos + literal_len)
+            .ok_or_else(|| DecompressError("字面量被截断".to_string()))?;
+        output.extend_from_slice(literals);
+        pos += literal_len;
+
+        // 最后一个序列只有字面量
+        if pos == input.len() {
+            break;
+        }
+
+        let offset_bytes = input
+            .get(pos..pos + 2)
+            .ok_or_else(|| DecompressError("偏移被截断".to_string()))?;
+        let offset = u16::from_le_bytes([offset_bytes[0], offset_bytes[1]]) as usize;
+        pos += 2;
+        if offset == 0 || offset > output.len() {
+            return Err(DecompressError(format!("无效的匹配偏移 {offset}")));
+        }
+
+        let match_len = read_length(input, &mut pos, (token & 0x0F) as usize)? + MIN_MATCH;
+        if output.len() + match_len > raw_len {
+            return Err(DecompressError("解压后长度超过声明值".to_string()));
+        }
+        // 偏移可能小于匹配长度（重复模式），逐字节复制
+        let start = output.len() - offset;
+        for i in 0..match_len {
+            output.push(output[start + i]);
+        }
+    }
+
+    if output.len() != raw_len {
+        return Err(DecompressError(format!("解压后长度 {} 与声明的 {raw_len} 不符", output.len())));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(input: &[u8]) -> Vec<u8> {
+        let compressed = compress(input);
+        let restored = decompress(&compressed, input.len()).expect("解压应该成功");
+        assert_eq!(restored, input);
+        compressed
+    }
+
+    #[test]
+    fn test_round_trip_edge_cases() {
+        round_trip(b"");
+        round_trip(b"a");
+        round_trip(b"abcdefghijkl");
+        round_trip(&[0u8; 1000]);
+        round_trip(&(0..=255u8).cycle().take(70_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_repetitive_json_compresses() {
+        let input: String = (0..500)
+            .map(|i| format!("{{\"symbol\":\"BTCUSDT\",\"exchange\":\"Phemex\",\"price\":{}.5}}", 64000 + i % 7))
+            .collect();
+        let compressed = round_trip(input.as_bytes());
+        assert!(compressed.len() * 5 < input.len(), "压缩率过低: {} -> {}", input.len(), compressed.len());
+    }
+
+    #[test]
+    fn test_corrupted_input_is_rejected() {
+        let input = b"orderbook orderbook orderbook orderbook".repeat(10);
+        let compressed = compress(&input);
+
+        assert!(decompress(&compressed, input.len() + 1).is_err());
+        assert!(decompress(&compressed[..compressed.len() / 2], input.len()).is_err());
+        assert!(decompress(&[0x0F, 0x05, 0x00], 20).is_err());
+    }
+}
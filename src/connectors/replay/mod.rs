@@ -0,0 +1,15 @@
+//! 行情录制与回放模块
+//! 录制器把连接器推送的标准化消息写入压缩段文件；回放连接器按原始节奏、加速或尽快重放，
+//! 输出顺序固定，便于对比扫描器在同一份行情上的结果
+
+pub mod adapter;
+pub mod compression;
+pub mod recorder;
+pub mod segment;
+
+#[cfg(test)]
+mod test;
+
+pub use adapter::{ReplayConfig, ReplayConnector, ReplaySpeed, ReplaySummary};
+pub use recorder::{MarketDataRecorder, RecordingSummary};
+pub use segment::{RecordedMessage, SegmentOptions, SegmentReader, SegmentWriter};
@@ -0,0 +1,142 @@
+//! 行情录制器
+//! 把连接器推送的 `StandardizedMessage` 连同本地接收时间写入段文件。
+//! 写盘在阻塞线程中进行，录制通道使用 `Block` 策略，保证不丢消息。
+
+use log::{error, info};
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::config::RecordingConfig;
+use crate::connectors::common::data_channel::{data_channel, DataReceiver, DataSender};
+use crate::types::config::{ChannelPolicy, DataChannelConfig};
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::StandardizedMessage;
+use super::segment::{RecordedMessage, SegmentOptions, SegmentWriter};
+
+/// 录制通道容量
+const RECORDER_CHANNEL_CAPACITY: usize = 65_536;
+
+/// 空闲多久后把未满的块写入磁盘
+const IDLE_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 录制结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordingSummary {
+    pub records: u64,
+    pub segments: Vec<PathBuf>,
+}
+
+/// 行情录制器，可在多个转发任务间共享
+pub struct MarketDataRecorder {
+    directory: PathBuf,
+    sender: RwLock<Option<DataSender<RecordedMessage>>>,
+    writer_task: Mutex<Option<JoinHandle<io::Result<RecordingSummary>>>>,
+}
+
+impl MarketDataRecorder {
+    /// 在目录中开始录制，目录中已有段文件时接着往后写
+    pub fn start(directory: impl Into<PathBuf>, options: SegmentOptions) -> io::Result<Self> {
+        let directory = directory.into();
+        let writer = SegmentWriter::create(&directory, options)?;
+        let (sender, receiver) = data_channel(DataChannelConfig {
+            capacity: RECORDER_CHANNEL_CAPACITY,
+            policy: ChannelPolicy::Block,
+        });
+
+        let runtime = tokio::runtime::Handle::current();
+        let writer_task = tokio::task::spawn_blocking(move || run_writer(runtime, writer, receiver));
+        info!("[Recorder] 开始录制行情到 {}", directory.display());
+
+        Ok(Self {
+            directory,
+            sender: RwLock::new(Some(sender)),
+            writer_task: Mutex::new(Some(writer_task)),
+        })
+    }
+
+    /// 按配置文件中的 `[recording]` 开始录制
+    pub fn from_config(config: &RecordingConfig) -> io::Result<Self> {
+        Self::start(&config.directory, SegmentOptions {
+            segment_max_bytes: config.segment_max_bytes,
+            block_bytes: config.block_bytes,
+        })
+    }
+
+    /// 录制目录
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    /// 录制一条消息，接收时间取调用时刻；录制结束后调用会被忽略
+    pub async fn record(&self, exchange: ExchangeType, market_type: MarketType, message: StandardizedMessage) {
+        let sender = self.sender.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(sender) = sender else {
+            return;
+        };
+
+        let record = RecordedMessage {
+            // 序号由写入线程按落盘顺序分配
+            sequence: 0,
+            received_at_us: chrono::Utc::now().timestamp_micros(),
+            exchange,
+            market_type,
+            message,
+        };
+        if sender.send(record).await.is_err() {
+            error!("[Recorder] 写入线程已停止，消息未录制");
+        }
+    }
+
+    /// 录制整个数据流直到其关闭
+    pub fn record_stream(
+        self: &std::sync::Arc<Self>,
+        exchange: ExchangeType,
+        market_type: MarketType,
+        mut stream: DataReceiver<StandardizedMessage>,
+    ) -> JoinHandle<()> {
+        let recorder = self.clone();
+        tokio::spawn(async move {
+            while let Some(message) = stream.recv().await {
+                recorder.record(exchange, market_type, message).await;
+            }
+        })
+    }
+
+    /// 停止录制，等待剩余消息写入磁盘
+    pub async fn finish(&self) -> io::Result<RecordingSummary> {
+        // 丢弃发送端后写入线程会在取完队列后退出
+        self.sender.write().unwrap_or_else(|e| e.into_inner()).take();
+
+        let Some(writer_task) = self.writer_task.lock().await.take() else {
+            return Ok(RecordingSummary::default());
+        };
+        let summary = writer_task.await.map_err(io::Error::other)??;
+        info!("[Recorder] 录制结束: {} 条消息, {} 个段文件", summary.records, summary.segments.len());
+        Ok(summary)
+    }
+}
+
+fn run_writer(
+    runtime: tokio::runtime::Handle,
+    mut writer: SegmentWriter,
+    mut receiver: DataReceiver<RecordedMessage>,
+) -> io::Result<RecordingSummary> {
+    loop {
+        match runtime.block_on(tokio::time::timeout(IDLE_FLUSH_INTERVAL, receiver.recv())) {
+            Ok(Some(mut record)) => {
+                record.sequence = writer.records_written();
+                writer.append(&record)?;
+            }
+            Ok(None) => break,
+            Err(_) => writer.flush()?,
+        }
+    }
+
+    let records = writer.records_written();
+    let segments = writer.finish()?;
+    Ok(RecordingSummary { records, segments })
+}
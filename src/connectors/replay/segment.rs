@@ -0,0 +1,313 @@
+//! 行情录制段文件
+//!
+//! 文件布局：
+//! - 8字节文件头 `CFSEG001`
+//! - 若干压缩块：`原始长度 u32 | 压缩长度 u32 | 校验和 u32 | 压缩数据`
+//! - 块解压后是连续的记录：`记录长度 u32 | JSON编码的 RecordedMessage`
+//!
+//! 所有整数均为小端。写满 `segment_max_bytes` 后切换到下一个段文件，
+//! 段文件名中的序号保证按文件名排序即为录制顺序。
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::connectors::common::data_channel::ConflationKey;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::StandardizedMessage;
+use super::compression::{compress, decompress};
+
+/// 段文件头
+pub const SEGMENT_MAGIC: &[u8; 8] = b"CFSEG001";
+
+/// 段文件扩展名
+pub const SEGMENT_EXTENSION: &str = "cfseg";
+
+/// 单个块解压后的最大长度，超过即视为文件损坏
+const MAX_BLOCK_BYTES: usize = 64 * 1024 * 1024;
+
+/// 一条录制的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    /// 本次录制中的写入序号，与落盘顺序一致
+    pub sequence: u64,
+    /// 本地收到消息的时间（微秒时间戳）
+    pub received_at_us: i64,
+    pub exchange: ExchangeType,
+    pub market_type: MarketType,
+    pub message: StandardizedMessage,
+}
+
+impl ConflationKey for RecordedMessage {
+    fn conflation_key(&self) -> Option<String> {
+        // 录制必须保留每一条消息
+        None
+    }
+}
+
+/// 段文件写入参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentOptions {
+    /// 单个段文件的大小上限（压缩后）
+    pub segment_max_bytes: u64,
+    /// 攒够这么多原始字节后压缩成一个块
+    pub block_bytes: usize,
+}
+
+impl Default for SegmentOptions {
+    fn default() -> Self {
+        Self {
+            segment_max_bytes: 256 * 1024 * 1024,
+            block_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// FNV-1a校验和，用于发现截断或损坏的块
+fn checksum(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5u32, |hash, byte| (hash ^ *byte as u32).wrapping_mul(0x0100_0193))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// 目录中的段文件，按文件名（即录制顺序）排序
+pub fn list_segments(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION))
+        .collect();
+    segments.sort();
+    Ok(segments)
+}
+
+fn segment_index(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.strip_prefix("segment-")?.parse().ok()
+}
+
+/// 段文件写入器，负责分块压缩和段文件轮换
+pub struct SegmentWriter {
+    directory: PathBuf,
+    options: SegmentOptions,
+    next_index: u64,
+    file: Option<BufWriter<File>>,
+    file_bytes: u64,
+    block: Vec<u8>,
+    records_written: u64,
+    segments_written: Vec<PathBuf>,
+}
+
+impl SegmentWriter {
+    /// 在目录中创建写入器；目录中已有段文件时从下一个序号继续
+    pub fn create(directory: impl Into<PathBuf>, options: SegmentOptions) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        let next_index = list_segments(&directory)?
+            .iter()
+            .filter_map(|path| segment_index(path))
+            .max()
+            .map_or(0, |index| index + 1);
+
+        Ok(Self {
+            directory,
+            options,
+            next_index,
+            file: None,
+            file_bytes: 0,
+            block: Vec::with_capacity(options.block_bytes),
+            records_written: 0,
+            segments_written: Vec::new(),
+        })
+    }
+
+    /// 追加一条记录，块写满时压缩落盘
+    pub fn append(&mut self, record: &RecordedMessage) -> io::Result<()> {
+        let encoded = serde_json::to_vec(record).map_err(|e| invalid_data(e.to_string()))?;
+        self.block.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        self.block.extend_from_slice(&encoded);
+        self.records_written += 1;
+
+        if self.block.len() >= self.options.block_bytes {
+            self.flush_block()?;
+        }
+        Ok(())
+    }
+
+    /// 把未满的块也写入磁盘
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.flush_block()?;
+        if let Some(file) = self.file.as_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 写完剩余数据并关闭当前段文件，返回本次写入的段文件
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        self.flush()?;
+        if let Some(file) = self.file.take() {
+            file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        }
+        Ok(std::mem::take(&mut self.segments_written))
+    }
+
+    /// 已写入的记录数
+    pub fn records_written(&self) -> u64 {
+        self.records_written
+    }
+
+    fn flush_block(&mut self) -> io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+
+        let compressed = compress(&self.block);
+        let block_bytes = 12 + compressed.len() as u64;
+        if self.file.is_some() && self.file_bytes + block_bytes > self.options.segment_max_bytes {
+            if let Some(mut file) = self.file.take() {
+                file.flush()?;
+            }
+        }
+        if self.file.is_none() {
+            self.open_next_segment()?;
+        }
+
+        let file = self.file.as_mut().expect("段文件已打开");
+        file.write_all(&(self.block.len() as u32).to_le_bytes())?;
+        file.write_all(&(compressed.len() as u32).to_le_bytes())?;
+        file.write_all(&checksum(&self.block).to_le_bytes())?;
+        file.write_all(&compressed)?;
+        self.file_bytes += block_bytes;
+        self.block.clear();
+        Ok(())
+    }
+
+    fn open_next_segment(&mut self) -> io::Result<()> {
+        let path = self.directory.join(format!("segment-{:06}.{SEGMENT_EXTENSION}", self.next_index));
+        self.next_index += 1;
+
+        let mut file = BufWriter::new(File::create(&path)?);
+        file.write_all(SEGMENT_MAGIC)?;
+        self.file = Some(file);
+        self.file_bytes = SEGMENT_MAGIC.len() as u64;
+        self.segments_written.push(path);
+        Ok(())
+    }
+}
+
+/// 段文件读取器，按录制顺序逐条返回记录
+pub struct SegmentReader {
+    segments: std::vec::IntoIter<PathBuf>,
+    current: Option<BufReader<File>>,
+    block: Vec<u8>,
+    block_pos: usize,
+}
+
+impl SegmentReader {
+    /// 打开目录中的全部段文件
+    pub fn open_directory(directory: &Path) -> io::Result<Self> {
+        Ok(Self::open_segments(list_segments(directory)?))
+    }
+
+    /// 按给定顺序读取段文件
+    pub fn open_segments(segments: Vec<PathBuf>) -> Self {
+        Self {
+            segments: segments.into_iter(),
+            current: None,
+            block: Vec::new(),
+            block_pos: 0,
+        }
+    }
+
+    /// 读取下一条记录，全部读完返回 `Ok(None)`
+    pub fn next_record(&mut self) -> io::Result<Option<RecordedMessage>> {
+        while self.block_pos >= self.block.len() {
+            if !self.load_next_block()? {
+                return Ok(None);
+            }
+        }
+
+        let header = self
+            .block
+            .get(self.block_pos..self.block_pos + 4)
+            .ok_or_else(|| invalid_data("记录长度被截断"))?;
+        let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let start = self.block_pos + 4;
+        let payload = self
+            .block
+            .get(start..start + length)
+            .ok_or_else(|| invalid_data("记录内容被截断"))?;
+        self.block_pos = start + length;
+
+        serde_json::from_slice(payload).map(Some).map_err(|e| invalid_data(e.to_string()))
+    }
+
+    /// 读取下一个块；当前段读完时打开下一个段文件
+    fn load_next_block(&mut self) -> io::Result<bool> {
+        loop {
+            let Some(file) = self.current.as_mut() else {
+                let Some(path) = self.segments.next() else {
+                    return Ok(false);
+                };
+                let mut file = BufReader::new(File::open(&path)?);
+                let mut magic = [0u8; 8];
+                file.read_exact(&mut magic)?;
+                if &magic != SEGMENT_MAGIC {
+                    return Err(invalid_data(format!("{} 不是段文件", path.display())));
+                }
+                self.current = Some(file);
+                continue;
+            };
+
+            let mut header = [0u8; 12];
+            match read_full(file, &mut header)? {
+                0 => {
+                    self.current = None;
+                    continue;
+                }
+                12 => {}
+                _ => return Err(invalid_data("块头被截断")),
+            }
+
+            let raw_len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            let compressed_len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            let expected_checksum = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+            if raw_len > MAX_BLOCK_BYTES || compressed_len > MAX_BLOCK_BYTES {
+                return Err(invalid_data(format!("块长度异常: {raw_len}/{compressed_len}")));
+            }
+
+            let mut compressed = vec![0u8; compressed_len];
+            file.read_exact(&mut compressed)?;
+            let block = decompress(&compressed, raw_len).map_err(|e| invalid_data(e.to_string()))?;
+            if checksum(&block) != expected_checksum {
+                return Err(invalid_data("块校验和不匹配"));
+            }
+
+            self.block = block;
+            self.block_pos = 0;
+            return Ok(true);
+        }
+    }
+}
+
+impl Iterator for SegmentReader {
+    type Item = io::Result<RecordedMessage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// 尽量读满缓冲区，返回实际读取的字节数（文件结束时可能小于缓冲区长度）
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,252 @@
+//! 行情录制与回放测试模块
+//! 在临时目录中录制合成行情，验证段文件轮换、损坏检测以及回放的确定性和节奏
+
+mod tests {
+    use super::super::adapter::{ReplayConfig, ReplayConnector, ReplaySpeed};
+    use super::super::recorder::MarketDataRecorder;
+    use super::super::segment::{list_segments, RecordedMessage, SegmentOptions, SegmentReader, SegmentWriter};
+    use crate::connectors::common::data_channel::DataReceiver;
+    use crate::connectors::traits::ExchangeConnector;
+    use crate::core::AppState;
+    use crate::exchange_types::{Exchange, StandardOrderBook};
+    use crate::types::{
+        config::ConnectionStatus,
+        exchange::{ExchangeType, MarketType},
+        market_data::{StandardizedMessage, StandardizedTrade, TradeSide},
+    };
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tokio::time::timeout;
+
+    /// 每个测试使用独立的临时目录
+    fn scratch_dir(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("trifury-replay-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        directory
+    }
+
+    fn orderbook(exchange: Exchange, symbol: &str, best_bid: f64, timestamp: i64) -> StandardizedMessage {
+        let mut book = StandardOrderBook::new_minimal(symbol, exchange, best_bid, best_bid + 0.5, timestamp);
+        book.depth_bids = vec![(best_bid, 1.5), (best_bid - 0.5, 3.0)];
+        book.depth_asks = vec![(best_bid + 0.5, 2.0), (best_bid + 1.0, 4.0)];
+        StandardizedMessage::OrderBookUpdate(book)
+    }
+
+    fn trade(exchange: ExchangeType, symbol: &str, price: f64, timestamp: i64) -> StandardizedMessage {
+        StandardizedMessage::TradeUpdate(StandardizedTrade {
+            symbol: symbol.to_string(),
+            exchange,
+            price,
+            quantity: 0.25,
+            side: TradeSide::Buy,
+            timestamp,
+            trade_id: format!("{timestamp}"),
+        })
+    }
+
+    /// 交替生成两个交易所的订单簿和成交
+    fn synthetic_messages(count: usize) -> Vec<(ExchangeType, StandardizedMessage)> {
+        (0..count)
+            .map(|i| {
+                let timestamp = 1_710_755_362_000 + i as i64;
+                let price = 64_000.0 + (i % 13) as f64;
+                match i % 3 {
+                    0 => (ExchangeType::Phemex, orderbook(Exchange::Phemex, "BTCUSDT", price, timestamp)),
+                    1 => (ExchangeType::LBank, orderbook(Exchange::LBank, "BTCUSDT", price - 1.0, timestamp)),
+                    _ => (ExchangeType::Phemex, trade(ExchangeType::Phemex, "BTCUSDT", price, timestamp)),
+                }
+            })
+            .collect()
+    }
+
+    /// 直接写段文件，接收时间从0开始每条间隔 `spacing_us`
+    fn write_recording(directory: &PathBuf, messages: &[(ExchangeType, StandardizedMessage)], spacing_us: i64, options: SegmentOptions) -> Vec<PathBuf> {
+        let mut writer = SegmentWriter::create(directory, options).unwrap();
+        for (i, (exchange, message)) in messages.iter().enumerate() {
+            writer.append(&RecordedMessage {
+                sequence: i as u64,
+                received_at_us: i as i64 * spacing_us,
+                exchange: *exchange,
+                market_type: MarketType::Futures,
+                message: message.clone(),
+            }).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn encode(message: &StandardizedMessage) -> String {
+        serde_json::to_string(message).unwrap()
+    }
+
+    /// 回放整个目录，返回推送的消息（JSON编码，便于逐条比较）
+    async fn replay_all(config: ReplayConfig, app_state: Arc<AppState>) -> Vec<String> {
+        let connector = ReplayConnector::new(config, app_state);
+        let mut stream: DataReceiver<StandardizedMessage> = connector.get_market_data_stream();
+        connector.connect_websocket().await.unwrap();
+
+        let collector = tokio::spawn(async move {
+            let mut messages = Vec::new();
+            while let Some(message) = stream.recv().await {
+                messages.push(encode(&message));
+            }
+            messages
+        });
+
+        let summary = timeout(Duration::from_secs(10), connector.wait_finished())
+            .await
+            .expect("回放超时")
+            .expect("回放应该成功");
+        assert_eq!(summary.replayed, connector.replayed_count());
+        assert_eq!(connector.get_connection_status().await, ConnectionStatus::Disconnected);
+
+        // 断开后丢弃发送端，收集任务随之结束
+        drop(connector);
+        let messages = collector.await.unwrap();
+        assert_eq!(messages.len() as u64, summary.replayed);
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_recorder_round_trip_and_deterministic_replay() {
+        let directory = scratch_dir("round-trip");
+        let messages = synthetic_messages(300);
+
+        let recorder = Arc::new(MarketDataRecorder::start(&directory, SegmentOptions::default()).unwrap());
+        for (exchange, message) in &messages {
+            recorder.record(*exchange, MarketType::Futures, message.clone()).await;
+        }
+        let summary = recorder.finish().await.unwrap();
+        assert_eq!(summary.records, 300);
+        assert_eq!(summary.segments.len(), 1);
+
+        // 录制的记录按写入顺序编号，接收时间单调
+        let recorded: Vec<RecordedMessage> = SegmentReader::open_directory(&directory).unwrap().map(Result::unwrap).collect();
+        assert_eq!(recorded.len(), 300);
+        assert!(recorded.iter().enumerate().all(|(i, record)| record.sequence == i as u64));
+        assert!(recorded.windows(2).all(|pair| pair[0].received_at_us <= pair[1].received_at_us));
+
+        let mut config = ReplayConfig::new(&directory, ExchangeType::Phemex, MarketType::Futures);
+        config.exchanges.clear();
+
+        let app_state = Arc::new(AppState::new());
+        let first = replay_all(config.clone(), app_state.clone()).await;
+        let second = replay_all(config, Arc::new(AppState::new())).await;
+
+        let expected: Vec<String> = messages.iter().map(|(_, message)| encode(message)).collect();
+        assert_eq!(first, expected);
+        assert_eq!(second, first);
+
+        // 回放的订单簿进入合并订单簿存储
+        assert_eq!(app_state.book_store.books_for("BTCUSDT").len(), 2);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_filters_exchange_and_serves_snapshots() {
+        let directory = scratch_dir("filter");
+        let messages = synthetic_messages(30);
+        write_recording(&directory, &messages, 1_000, SegmentOptions::default());
+
+        let connector = ReplayConnector::new(
+            ReplayConfig::new(&directory, ExchangeType::LBank, MarketType::Futures),
+            Arc::new(AppState::new()),
+        );
+        let _stream = connector.get_market_data_stream();
+        connector.connect_websocket().await.unwrap();
+        let summary = connector.wait_finished().await.unwrap();
+        assert_eq!(summary.replayed, 10);
+        assert_eq!(summary.skipped, 20);
+
+        let snapshot = connector.get_orderbook_snapshot("BTCUSDT").await.expect("应该缓存LBank订单簿");
+        assert_eq!(snapshot.exchange, Exchange::LBank);
+        assert!(connector.get_recent_trades_snapshot("BTCUSDT", 10).await.is_empty());
+        assert!(connector.get_account_balance().await.is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_pacing_follows_recorded_timestamps() {
+        let directory = scratch_dir("pacing");
+        // 6条消息间隔40ms，录制时长200ms
+        write_recording(&directory, &synthetic_messages(6), 40_000, SegmentOptions::default());
+
+        let mut config = ReplayConfig::new(&directory, ExchangeType::Phemex, MarketType::Futures);
+        config.exchanges.clear();
+
+        let replay_duration = |speed| {
+            let mut config = config.clone();
+            config.speed = speed;
+            async move {
+                let started = Instant::now();
+                replay_all(config, Arc::new(AppState::new())).await;
+                started.elapsed()
+            }
+        };
+
+        let original = replay_duration(ReplaySpeed::Original).await;
+        assert!(original >= Duration::from_millis(195), "原速回放过快: {original:?}");
+
+        let accelerated = replay_duration(ReplaySpeed::Accelerated(4.0)).await;
+        assert!(accelerated >= Duration::from_millis(45), "4倍速回放过快: {accelerated:?}");
+        assert!(accelerated < original, "加速回放应该更快: {accelerated:?} / {original:?}");
+
+        let fastest = replay_duration(ReplaySpeed::AsFastAsPossible).await;
+        assert!(fastest < Duration::from_millis(45), "尽快回放不应等待: {fastest:?}");
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_segments_rotate_and_continue_numbering() {
+        let directory = scratch_dir("rotate");
+        let options = SegmentOptions { segment_max_bytes: 4 * 1024, block_bytes: 1024 };
+        let messages = synthetic_messages(500);
+
+        let first = write_recording(&directory, &messages[..250], 1, options);
+        let second = write_recording(&directory, &messages[250..], 1, options);
+        assert!(first.len() > 1, "应该切换出多个段文件");
+        assert!(first.iter().chain(&second).all(|path| std::fs::metadata(path).unwrap().len() <= 4 * 1024));
+
+        // 第二次写入接在已有段文件之后
+        let segments = list_segments(&directory).unwrap();
+        assert_eq!(segments, first.iter().chain(&second).cloned().collect::<Vec<_>>());
+
+        let replayed: Vec<String> = SegmentReader::open_directory(&directory)
+            .unwrap()
+            .map(|record| encode(&record.unwrap().message))
+            .collect();
+        let expected: Vec<String> = messages.iter().map(|(_, message)| encode(message)).collect();
+        assert_eq!(replayed, expected);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_segment_is_reported() {
+        let directory = scratch_dir("corrupt");
+        let segments = write_recording(&directory, &synthetic_messages(50), 1, SegmentOptions::default());
+
+        let mut bytes = std::fs::read(&segments[0]).unwrap();
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0xFF;
+        std::fs::write(&segments[0], &bytes).unwrap();
+
+        assert!(SegmentReader::open_directory(&directory).unwrap().any(|record| record.is_err()));
+
+        let mut config = ReplayConfig::new(&directory, ExchangeType::Phemex, MarketType::Futures);
+        config.exchanges.clear();
+        let connector = ReplayConnector::new(config, Arc::new(AppState::new()));
+        let _stream = connector.get_market_data_stream();
+        connector.connect_websocket().await.unwrap();
+        assert!(timeout(Duration::from_secs(5), connector.wait_finished()).await.unwrap().is_err());
+
+        // 截断的段文件同样报错
+        std::fs::write(&segments[0], &bytes[..middle]).unwrap();
+        assert!(SegmentReader::open_directory(&directory).unwrap().any(|record| record.is_err()));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
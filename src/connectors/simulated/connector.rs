@@ -0,0 +1,280 @@
+//! 模拟交易所连接器
+//! 从行情来源连接器（实时或回放）读取订单簿和成交，原样转发到自身的行情流并驱动撮合引擎；
+//! 下单、撤单、查询订单和余额由撮合引擎完成，订单、余额与持仓变化以与实时连接器相同的
+//! `StandardizedMessage::UserDataUpdate` 推送到用户数据流，策略无需修改即可在模拟盘上运行。
+
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, MutexGuard};
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+use crate::config::get_config;
+use crate::connectors::binance::futures::advanced_features::OrderExecutor;
+use crate::connectors::common::data_channel::{data_channel, DataReceiver, DataSender};
+use crate::connectors::traits::ExchangeConnector;
+use crate::types::{
+    account::AccountBalance,
+    config::{ConnectionStatus, SubscriptionStatus},
+    errors::ConnectorError,
+    events::DataFlowStats,
+    exchange::{ExchangeType, MarketType},
+    market_data::{StandardizedMessage, StandardizedOrderBook, StandardizedTrade},
+    orders::{OrderRequest, OrderResponse, OrderStatus, Position},
+    trading,
+};
+use super::engine::{MatchingEngine, SimulatedExchangeConfig, SimulatedFill};
+
+/// 模拟交易所连接器
+#[derive(Clone)]
+pub struct SimulatedExchangeConnector {
+    exchange: ExchangeType,
+    market_type: MarketType,
+    engine: Arc<std::sync::Mutex<MatchingEngine>>,
+    source: Option<Arc<dyn ExchangeConnector>>,
+    market_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    user_sender: Arc<RwLock<Option<DataSender<StandardizedMessage>>>>,
+    status: Arc<RwLock<ConnectionStatus>>,
+    pump: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl SimulatedExchangeConnector {
+    /// `source` 为行情来源；为 None 时行情通过 `feed` 手动推入，例如由回测驱动
+    pub fn new(config: SimulatedExchangeConfig, source: Option<Arc<dyn ExchangeConnector>>) -> Self {
+        Self {
+            exchange: config.exchange,
+            market_type: config.market_type,
+            engine: Arc::new(std::sync::Mutex::new(MatchingEngine::new(config))),
+            source,
+            market_sender: Arc::new(RwLock::new(None)),
+            user_sender: Arc::new(RwLock::new(None)),
+            status: Arc::new(RwLock::new(ConnectionStatus::Disconnected)),
+            pump: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn engine(&self) -> MutexGuard<'_, MatchingEngine> {
+        self.engine.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 处理一条行情消息：先撮合挂单，再转发行情并推送撮合产生的用户数据
+    pub async fn feed(&self, message: StandardizedMessage) {
+        let events = match &message {
+            StandardizedMessage::OrderBookUpdate(orderbook) => self.engine().on_orderbook(orderbook.clone()),
+            StandardizedMessage::TradeUpdate(trade) => self.engine().on_trade(trade),
+            _ => Vec::new(),
+        };
+
+        let sender = self.market_sender.read().await.clone();
+        if let Some(sender) = sender {
+            if sender.send(message).await.is_err() {
+                debug!("[Simulated] {} 行情接收端已关闭", self.exchange);
+            }
+        }
+        self.publish_user_data(events).await;
+    }
+
+    async fn publish_user_data(&self, events: Vec<StandardizedMessage>) {
+        if events.is_empty() {
+            return;
+        }
+        let sender = self.user_sender.read().await.clone();
+        let Some(sender) = sender else { return };
+        for event in events {
+            if sender.send(event).await.is_err() {
+                debug!("[Simulated] {} 用户数据接收端已关闭", self.exchange);
+                return;
+            }
+        }
+    }
+
+    /// 当前合约持仓
+    pub fn positions(&self) -> Vec<Position> {
+        self.engine().positions(chrono::Utc::now().timestamp_millis())
+    }
+
+    /// 未结束的订单
+    pub fn open_orders(&self) -> Vec<OrderResponse> {
+        self.engine().open_orders()
+    }
+
+    /// 全部模拟成交
+    pub fn fills(&self) -> Vec<SimulatedFill> {
+        self.engine().fills().to_vec()
+    }
+
+    async fn run_pump(self, mut stream: DataReceiver<StandardizedMessage>) {
+        while let Some(message) = stream.recv().await {
+            self.feed(message).await;
+        }
+        info!("[Simulated] {} 行情来源已结束", self.exchange);
+        *self.status.write().await = ConnectionStatus::Disconnected;
+    }
+}
+
+#[async_trait]
+impl ExchangeConnector for SimulatedExchangeConnector {
+    // 基础信息
+    fn get_exchange_type(&self) -> ExchangeType {
+        self.exchange
+    }
+
+    fn get_market_type(&self) -> MarketType {
+        self.market_type
+    }
+
+    fn get_exchange_name(&self) -> &str {
+        "Simulated"
+    }
+
+    // 连接即开始读取行情来源
+    async fn connect_websocket(&self) -> Result<(), ConnectorError> {
+        let mut pump = self.pump.lock().await;
+        if pump.as_ref().is_some_and(|task| !task.is_finished()) {
+            return Ok(());
+        }
+        if let Some(source) = &self.source {
+            // 先取得数据流再连接，回放来源连接后立即开始推送
+            let stream = source.get_market_data_stream();
+            source.connect_websocket().await?;
+            *pump = Some(tokio::spawn(self.clone().run_pump(stream)));
+            info!("[Simulated] {} 使用 {} 的行情进行模拟撮合", self.exchange, source.get_exchange_name());
+        }
+        *self.status.write().await = ConnectionStatus::Connected;
+        Ok(())
+    }
+
+    async fn disconnect_websocket(&self) -> Result<(), ConnectorError> {
+        if let Some(pump) = self.pump.lock().await.take() {
+            pump.abort();
+        }
+        if let Some(source) = &self.source {
+            source.disconnect_websocket().await?;
+        }
+        *self.status.write().await = ConnectionStatus::Disconnected;
+        Ok(())
+    }
+
+    async fn subscribe_orderbook(&self, symbol: &str) -> Result<(), ConnectorError> {
+        match &self.source {
+            Some(source) => source.subscribe_orderbook(symbol).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn subscribe_trades(&self, symbol: &str) -> Result<(), ConnectorError> {
+        match &self.source {
+            Some(source) => source.subscribe_trades(symbol).await,
+            None => Ok(()),
+        }
+    }
+
+    // 用户数据由撮合引擎产生，无需订阅
+    async fn subscribe_user_stream(&self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+
+    // 推送式数据流接口
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.market_data_channel);
+        match self.market_sender.try_write() {
+            Ok(mut market_sender) => *market_sender = Some(sender),
+            Err(_) => warn!("[Simulated] 行情发送器正被占用，返回的数据流不会收到消息"),
+        }
+        receiver
+    }
+
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage> {
+        let (sender, receiver) = data_channel(get_config().websocket_optimization.user_data_channel);
+        match self.user_sender.try_write() {
+            Ok(mut user_sender) => *user_sender = Some(sender),
+            Err(_) => warn!("[Simulated] 用户数据发送器正被占用，返回的数据流不会收到消息"),
+        }
+        receiver
+    }
+
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        let mut stats = DataFlowStats::default();
+        if let Ok(sender) = self.market_sender.try_read() {
+            if let Some(sender) = sender.as_ref() {
+                sender.stats().apply_to(&mut stats);
+            }
+        }
+        stats
+    }
+
+    // 本地缓存快照读取
+    async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook> {
+        self.engine().orderbook(symbol).cloned()
+    }
+
+    async fn get_recent_trades_snapshot(&self, symbol: &str, limit: usize) -> Vec<StandardizedTrade> {
+        match &self.source {
+            Some(source) => source.get_recent_trades_snapshot(symbol, limit).await,
+            None => Vec::new(),
+        }
+    }
+
+    // 交易接口由撮合引擎完成
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, ConnectorError> {
+        let (response, events) = self.engine().place_order(order, chrono::Utc::now().timestamp_millis())?;
+        self.publish_user_data(events).await;
+        Ok(response)
+    }
+
+    async fn cancel_order(&self, order_id: &str, symbol: &str) -> Result<bool, ConnectorError> {
+        let (canceled, events) = self.engine().cancel_order(order_id, symbol, chrono::Utc::now().timestamp_millis())?;
+        self.publish_user_data(events).await;
+        Ok(canceled)
+    }
+
+    async fn get_order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        self.engine().order_status(order_id, symbol)
+    }
+
+    async fn get_account_balance(&self) -> Result<AccountBalance, ConnectorError> {
+        Ok(self.engine().account_balance())
+    }
+
+    // 连接状态
+    async fn is_connected(&self) -> bool {
+        *self.status.read().await == ConnectionStatus::Connected
+    }
+
+    async fn is_websocket_connected(&self) -> bool {
+        self.is_connected().await
+    }
+
+    async fn get_connection_status(&self) -> ConnectionStatus {
+        *self.status.read().await
+    }
+
+    async fn get_subscription_status(&self) -> Result<HashMap<String, SubscriptionStatus>, ConnectorError> {
+        match &self.source {
+            Some(source) => source.get_subscription_status().await,
+            None => Ok(HashMap::new()),
+        }
+    }
+}
+
+/// 供算法交易引擎使用
+#[async_trait]
+impl OrderExecutor for SimulatedExchangeConnector {
+    async fn submit_order(&self, order: OrderRequest) -> Result<String, String> {
+        self.place_order(&order).await.map(|response| response.order_id).map_err(|e| e.to_string())
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<(), String> {
+        match ExchangeConnector::cancel_order(self, order_id, "").await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("订单 {order_id} 已结束")),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn get_order_status(&self, order_id: &str) -> Result<trading::OrderStatus, String> {
+        let status = ExchangeConnector::get_order_status(self, order_id, "").await.map_err(|e| e.to_string())?;
+        trading::OrderStatus::from_api_string(&status.status).ok_or_else(|| format!("未知订单状态 {}", status.status))
+    }
+}
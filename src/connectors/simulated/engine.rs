@@ -0,0 +1,773 @@
+//! 模拟撮合引擎
+//! 以外部行情的订单簿为对手盘撮合模拟订单，不修改行情本身：
+//! 吃单按档位逐档成交，同一份订单簿快照中已被模拟订单吃掉的数量不会重复使用；
+//! 挂单以下单时同价位的可见挂单量作为排在前面的队列，随该价位挂单减少和成交推送向前移动，
+//! 对手价穿过挂单价或成交价穿过挂单价时以挂单价成交。
+//!
+//! 手续费统一以计价货币（合约为结算货币）收取。现货按资产记账；
+//! 合约按净持仓记账，保证金为持仓名义价值除以杠杆，平仓盈亏计入结算货币。
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::arbitrage_graph::extract_trading_pair;
+use crate::exchange_types::ExchangeFees;
+use crate::types::account::{AccountBalance, CurrencyBalance};
+use crate::types::errors::ConnectorError;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{
+    BalanceUpdate, OrderUpdate, PositionUpdate, StandardizedMessage, StandardizedOrderBook, StandardizedTrade, TradeSide, UserData,
+};
+use crate::types::orders::{OrderRequest, OrderResponse, OrderSide, OrderState, OrderStatus, OrderType, Position, PositionSide, TimeInForce};
+
+const QTY_EPSILON: f64 = 1e-12;
+
+/// 模拟交易所配置
+#[derive(Debug, Clone)]
+pub struct SimulatedExchangeConfig {
+    /// 对外报告的交易所和市场类型，通常与行情来源一致
+    pub exchange: ExchangeType,
+    pub market_type: MarketType,
+    /// 挂单/吃单费率（小数，0.001 表示 0.1%）
+    pub fees: ExchangeFees,
+    /// 初始余额
+    pub initial_balances: HashMap<String, f64>,
+    /// 合约的保证金与结算货币，也是账户总权益的计价货币
+    pub settlement_asset: String,
+    /// 合约杠杆倍数
+    pub leverage: f64,
+}
+
+impl SimulatedExchangeConfig {
+    pub fn new(exchange: ExchangeType, market_type: MarketType, fees: ExchangeFees) -> Self {
+        Self {
+            exchange,
+            market_type,
+            fees,
+            initial_balances: HashMap::new(),
+            settlement_asset: "USDT".to_string(),
+            leverage: 1.0,
+        }
+    }
+
+    /// 设置某个资产的初始余额
+    pub fn with_balance(mut self, asset: &str, amount: f64) -> Self {
+        self.initial_balances.insert(asset.to_string(), amount);
+        self
+    }
+
+    fn is_futures(&self) -> bool {
+        self.market_type == MarketType::Futures
+    }
+}
+
+/// 一笔模拟成交
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedFill {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    /// 以计价货币收取的手续费
+    pub fee: f64,
+    pub is_maker: bool,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+struct SimOrder {
+    sequence: u64,
+    order_id: String,
+    client_order_id: Option<String>,
+    symbol: String,
+    side: OrderSide,
+    price: Option<f64>,
+    quantity: f64,
+    filled: f64,
+    notional: f64,
+    state: OrderState,
+    reduce_only: bool,
+    /// 排在本订单前面的挂单数量
+    queue_ahead: f64,
+    /// 上次看到的本价位挂单量
+    level_qty: f64,
+    /// 为挂单冻结的资金：现货买单为计价货币，现货卖单为基础货币，合约为开仓保证金
+    locked: f64,
+    updated_at: i64,
+}
+
+impl SimOrder {
+    fn remaining(&self) -> f64 {
+        (self.quantity - self.filled).max(0.0)
+    }
+
+    fn average_price(&self) -> Option<f64> {
+        (self.filled > QTY_EPSILON).then(|| self.notional / self.filled)
+    }
+
+    fn response(&self) -> OrderResponse {
+        OrderResponse {
+            order_id: self.order_id.clone(),
+            client_order_id: self.client_order_id.clone(),
+            symbol: self.symbol.clone(),
+            status: self.state.to_api_string().to_string(),
+            filled_quantity: self.filled,
+            remaining_quantity: self.remaining(),
+            average_price: self.average_price(),
+            timestamp: self.updated_at.max(0) as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Balance {
+    free: f64,
+    locked: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SimPosition {
+    /// 净持仓，多头为正
+    size: f64,
+    entry_price: f64,
+    realized_pnl: f64,
+    /// 已占用的持仓保证金
+    margin: f64,
+}
+
+/// 撮合引擎，所有操作同步完成并返回需要推送的用户数据消息
+#[derive(Debug)]
+pub struct MatchingEngine {
+    config: SimulatedExchangeConfig,
+    books: HashMap<String, StandardizedOrderBook>,
+    /// 当前订单簿快照中被吃掉的数量，按 (交易对, 档位方向, 价格) 记录，新快照到达时清空
+    consumed: HashMap<(String, OrderSide, u64), f64>,
+    orders: HashMap<String, SimOrder>,
+    next_sequence: u64,
+    balances: BTreeMap<String, Balance>,
+    positions: HashMap<String, SimPosition>,
+    fills: Vec<SimulatedFill>,
+}
+
+impl MatchingEngine {
+    pub fn new(config: SimulatedExchangeConfig) -> Self {
+        let balances = config
+            .initial_balances
+            .iter()
+            .map(|(asset, amount)| (asset.clone(), Balance { free: *amount, locked: 0.0 }))
+            .collect();
+        Self {
+            config,
+            books: HashMap::new(),
+            consumed: HashMap::new(),
+            orders: HashMap::new(),
+            next_sequence: 1,
+            balances,
+            positions: HashMap::new(),
+            fills: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &SimulatedExchangeConfig {
+        &self.config
+    }
+
+    pub fn orderbook(&self, symbol: &str) -> Option<&StandardizedOrderBook> {
+        self.books.get(symbol)
+    }
+
+    /// 新的订单簿快照：重置吃单占用，撮合被穿过的挂单并推进其余挂单的队列位置
+    pub fn on_orderbook(&mut self, book: StandardizedOrderBook) -> Vec<StandardizedMessage> {
+        let symbol = book.symbol.clone();
+        let now = book.timestamp;
+        self.consumed.retain(|(s, _, _), _| *s != symbol);
+        self.books.insert(symbol.clone(), book);
+
+        let mut events = Vec::new();
+        for order_id in self.resting_orders(&symbol) {
+            let Some(order) = self.orders.get(&order_id) else { continue };
+            let (side, price, remaining) = (order.side, order.price.unwrap_or(0.0), order.remaining());
+
+            let takes = self.sweep(&symbol, side, Some(price), remaining);
+            let crossed: f64 = takes.iter().map(|(_, qty)| qty).sum();
+            if crossed > QTY_EPSILON {
+                // 对手价已穿过挂单价，挂单在此之前就会以自身价格成交
+                for (level, qty) in takes {
+                    self.consume(&symbol, side, level, qty);
+                }
+                self.apply_fill(&order_id, price, crossed, true, now, &mut events);
+                continue;
+            }
+            self.advance_queue(&order_id);
+        }
+        events
+    }
+
+    /// 成交推送：成交价穿过挂单价时直接成交，等于挂单价时先消耗排在前面的队列
+    pub fn on_trade(&mut self, trade: &StandardizedTrade) -> Vec<StandardizedMessage> {
+        let mut events = Vec::new();
+        let mut left = trade.quantity;
+        for order_id in self.resting_orders(&trade.symbol) {
+            if left <= QTY_EPSILON {
+                break;
+            }
+            let Some(order) = self.orders.get_mut(&order_id) else { continue };
+            let price = order.price.unwrap_or(0.0);
+            let (through, at_price) = match order.side {
+                OrderSide::Buy => (trade.price < price, trade.price == price && trade.side == TradeSide::Sell),
+                OrderSide::Sell => (trade.price > price, trade.price == price && trade.side == TradeSide::Buy),
+            };
+            let available = if through {
+                left
+            } else if at_price {
+                let available = (left - order.queue_ahead).max(0.0);
+                order.queue_ahead = (order.queue_ahead - left).max(0.0);
+                available
+            } else {
+                continue;
+            };
+            let quantity = available.min(order.remaining());
+            if quantity > QTY_EPSILON {
+                left -= quantity;
+                self.apply_fill(&order_id, price, quantity, true, trade.timestamp, &mut events);
+            }
+        }
+        events
+    }
+
+    /// 下单。参数或资金不满足时返回错误（对应交易所直接拒绝请求），
+    /// 否则返回下单响应和需要推送的消息；post-only 会立即成交、FOK 无法全部成交时订单为 EXPIRED
+    pub fn place_order(&mut self, request: &OrderRequest, now: i64) -> Result<(OrderResponse, Vec<StandardizedMessage>), ConnectorError> {
+        let (base, quote) = self.assets(&request.symbol)?;
+        if request.quantity.is_nan() || request.quantity <= QTY_EPSILON {
+            return Err(ConnectorError::InvalidOrderParameters(format!("数量无效: {}", request.quantity)));
+        }
+        let limit = match request.order_type {
+            OrderType::Market => None,
+            OrderType::Limit => match request.price {
+                Some(price) if price > 0.0 => Some(price),
+                _ => return Err(ConnectorError::InvalidOrderParameters("限价单必须指定价格".to_string())),
+            },
+            OrderType::StopMarket | OrderType::StopLimit => {
+                return Err(ConnectorError::InvalidOrderParameters("模拟交易所不支持条件单".to_string()));
+            }
+        };
+        if let Some(client_order_id) = &request.client_order_id {
+            if self.orders.values().any(|o| !o.state.is_terminal() && o.client_order_id.as_ref() == Some(client_order_id)) {
+                return Err(ConnectorError::OrderPlacementFailed(format!("客户端订单ID重复: {client_order_id}")));
+            }
+        }
+        if limit.is_none() && !self.books.contains_key(&request.symbol) {
+            return Err(ConnectorError::OrderPlacementFailed(format!("{} 没有行情，无法下市价单", request.symbol)));
+        }
+
+        // 只减仓订单的数量不超过反向持仓
+        let reduce_only = request.reduce_only.unwrap_or(false) || request.close_position.unwrap_or(false);
+        let position = self.positions.get(&request.symbol).map_or(0.0, |p| p.size);
+        let mut quantity = request.quantity;
+        if reduce_only {
+            if !self.config.is_futures() {
+                return Err(ConnectorError::InvalidOrderParameters("现货不支持只减仓订单".to_string()));
+            }
+            let reducible = match request.side {
+                OrderSide::Buy => (-position).max(0.0),
+                OrderSide::Sell => position.max(0.0),
+            };
+            if reducible <= QTY_EPSILON {
+                return Err(ConnectorError::OrderPlacementFailed("只减仓订单被拒绝：没有可减少的持仓".to_string()));
+            }
+            quantity = quantity.min(reducible);
+        }
+
+        let tif = match request.order_type {
+            OrderType::Market => TimeInForce::IOC,
+            _ => request.time_in_force.unwrap_or(TimeInForce::GTC),
+        };
+        let takes = self.sweep(&request.symbol, request.side, limit, quantity);
+        let taken: f64 = takes.iter().map(|(_, qty)| qty).sum();
+        let crosses = self.books.get(&request.symbol).is_some_and(|book| match (request.side, limit) {
+            (OrderSide::Buy, Some(price)) => book.best_ask > 0.0 && price >= book.best_ask,
+            (OrderSide::Sell, Some(price)) => book.best_bid > 0.0 && price <= book.best_bid,
+            _ => true,
+        });
+        let expire_without_trading = match tif {
+            TimeInForce::GTX => crosses,
+            TimeInForce::FOK => taken < quantity - QTY_EPSILON,
+            _ => false,
+        };
+        let takes = if expire_without_trading { Vec::new() } else { takes };
+        let rests = matches!(tif, TimeInForce::GTC | TimeInForce::GTD | TimeInForce::GTX) && !expire_without_trading;
+        let resting = if rests { (quantity - taken).max(0.0) } else { 0.0 };
+        let resting_price = limit.unwrap_or(0.0);
+
+        // 资金检查
+        let fees = &self.config.fees;
+        let taker_notional: f64 = takes.iter().map(|(price, qty)| price * qty).sum();
+        let resting_lock = if self.config.is_futures() {
+            let opening = Self::opening_quantity(position, request.side, quantity);
+            let opening_resting = (opening - taken).clamp(0.0, resting);
+            opening_resting * resting_price / self.config.leverage
+        } else {
+            match request.side {
+                OrderSide::Buy => resting * resting_price * (1.0 + fees.maker_fee),
+                OrderSide::Sell => resting,
+            }
+        };
+        let (required, asset) = if self.config.is_futures() {
+            let opening = Self::opening_quantity(position, request.side, quantity).min(taken);
+            let average = if taken > QTY_EPSILON { taker_notional / taken } else { 0.0 };
+            (opening * average / self.config.leverage + taker_notional * fees.taker_fee + resting_lock, self.config.settlement_asset.clone())
+        } else {
+            match request.side {
+                OrderSide::Buy => (taker_notional * (1.0 + fees.taker_fee) + resting_lock, quote.clone()),
+                // 与交易所一致，卖单按全部下单数量检查可用余额
+                OrderSide::Sell => (quantity, base.clone()),
+            }
+        };
+        let free = self.balances.get(&asset).map_or(0.0, |b| b.free);
+        if required > free + QTY_EPSILON {
+            return Err(ConnectorError::InsufficientBalance(format!("{asset} 可用 {free:.8}，需要 {required:.8}")));
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let order_id = sequence.to_string();
+        self.orders.insert(order_id.clone(), SimOrder {
+            sequence,
+            order_id: order_id.clone(),
+            client_order_id: request.client_order_id.clone(),
+            symbol: request.symbol.clone(),
+            side: request.side,
+            price: limit,
+            quantity,
+            filled: 0.0,
+            notional: 0.0,
+            state: OrderState::New,
+            reduce_only,
+            queue_ahead: 0.0,
+            level_qty: 0.0,
+            locked: 0.0,
+            updated_at: now,
+        });
+        let mut events = Vec::new();
+        if let Some(order) = self.orders.get(&order_id) {
+            events.push(self.order_event(order));
+        }
+
+        for (price, qty) in takes {
+            self.consume(&request.symbol, request.side, price, qty);
+            self.apply_fill(&order_id, price, qty, false, now, &mut events);
+        }
+
+        let remaining = self.orders.get(&order_id).map_or(0.0, SimOrder::remaining);
+        if remaining > QTY_EPSILON {
+            if rests {
+                let level_qty = self.level_quantity(&request.symbol, request.side, resting_price);
+                if let Some(order) = self.orders.get_mut(&order_id) {
+                    order.queue_ahead = level_qty.unwrap_or(0.0);
+                    order.level_qty = level_qty.unwrap_or(0.0);
+                    order.locked = resting_lock;
+                }
+                let lock_asset = if self.config.is_futures() {
+                    self.config.settlement_asset.clone()
+                } else if request.side == OrderSide::Buy {
+                    quote
+                } else {
+                    base
+                };
+                let balance = self.balances.entry(lock_asset.clone()).or_default();
+                balance.free -= resting_lock;
+                balance.locked += resting_lock;
+                if resting_lock > 0.0 {
+                    events.push(self.balance_event(&lock_asset, now));
+                }
+            } else {
+                self.finish(&order_id, OrderState::Expired, now, &mut events);
+            }
+        }
+
+        let response = self.orders.get(&order_id).map(SimOrder::response).ok_or_else(|| {
+            ConnectorError::InternalError(format!("订单 {order_id} 丢失"))
+        })?;
+        Ok((response, events))
+    }
+
+    /// 按订单号或客户端订单ID撤单，订单已结束时返回 false
+    pub fn cancel_order(&mut self, order_id: &str, symbol: &str, now: i64) -> Result<(bool, Vec<StandardizedMessage>), ConnectorError> {
+        let order_id = self.find(order_id, symbol)?;
+        let mut events = Vec::new();
+        if self.orders.get(&order_id).is_some_and(|o| o.state.is_terminal()) {
+            return Ok((false, events));
+        }
+        self.finish(&order_id, OrderState::Canceled, now, &mut events);
+        Ok((true, events))
+    }
+
+    pub fn order_status(&self, order_id: &str, symbol: &str) -> Result<OrderStatus, ConnectorError> {
+        let order_id = self.find(order_id, symbol)?;
+        let response = self.orders[&order_id].response();
+        Ok(OrderStatus {
+            order_id: response.order_id,
+            symbol: response.symbol,
+            status: response.status,
+            filled_quantity: response.filled_quantity,
+            remaining_quantity: response.remaining_quantity,
+            average_price: response.average_price,
+            timestamp: response.timestamp,
+        })
+    }
+
+    /// 未结束的订单
+    pub fn open_orders(&self) -> Vec<OrderResponse> {
+        let mut orders: Vec<&SimOrder> = self.orders.values().filter(|o| !o.state.is_terminal()).collect();
+        orders.sort_by_key(|o| o.sequence);
+        orders.into_iter().map(SimOrder::response).collect()
+    }
+
+    /// 账户余额，总权益以结算货币计价：其他资产按当前中间价折算，合约加上未实现盈亏
+    pub fn account_balance(&self) -> AccountBalance {
+        let settlement = &self.config.settlement_asset;
+        let mut total = 0.0;
+        let mut balances = HashMap::new();
+        for (asset, balance) in &self.balances {
+            let amount = balance.free + balance.locked;
+            total += if asset == settlement {
+                amount
+            } else {
+                self.mid_price(&format!("{asset}{settlement}")).map_or(0.0, |price| amount * price)
+            };
+            balances.insert(asset.clone(), CurrencyBalance {
+                currency: asset.clone(),
+                total: amount,
+                available: balance.free,
+                frozen: balance.locked,
+            });
+        }
+        total += self.positions.iter().map(|(symbol, p)| self.unrealized_pnl(symbol, p)).sum::<f64>();
+
+        let settlement_balance = self.balances.get(settlement).copied().unwrap_or_default();
+        AccountBalance {
+            total,
+            available: settlement_balance.free,
+            frozen: settlement_balance.locked,
+            balances,
+        }
+    }
+
+    /// 合约持仓（净持仓模式）
+    pub fn positions(&self, now: i64) -> Vec<Position> {
+        let mut positions: Vec<Position> = self
+            .positions
+            .iter()
+            .filter(|(_, p)| p.size.abs() > QTY_EPSILON)
+            .map(|(symbol, p)| Position {
+                symbol: symbol.clone(),
+                exchange: self.config.exchange,
+                side: if p.size > 0.0 { PositionSide::Long } else { PositionSide::Short },
+                size: p.size.abs(),
+                entry_price: p.entry_price,
+                mark_price: self.mid_price(symbol).unwrap_or(p.entry_price),
+                unrealized_pnl: self.unrealized_pnl(symbol, p),
+                realized_pnl: p.realized_pnl,
+                margin: p.margin,
+                timestamp: now,
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        positions
+    }
+
+    /// 全部模拟成交
+    pub fn fills(&self) -> &[SimulatedFill] {
+        &self.fills
+    }
+
+    fn assets(&self, symbol: &str) -> Result<(String, String), ConnectorError> {
+        extract_trading_pair(symbol).ok_or_else(|| ConnectorError::InvalidSymbol(format!("无法识别交易对 {symbol}")))
+    }
+
+    fn find(&self, id: &str, symbol: &str) -> Result<String, ConnectorError> {
+        if self.orders.get(id).is_some_and(|o| symbol.is_empty() || o.symbol == symbol) {
+            return Ok(id.to_string());
+        }
+        self.orders
+            .values()
+            .filter(|o| o.client_order_id.as_deref() == Some(id) && (symbol.is_empty() || o.symbol == symbol))
+            .max_by_key(|o| o.sequence)
+            .map(|o| o.order_id.clone())
+            .ok_or_else(|| ConnectorError::TradingError(format!("订单 {id} 不存在")))
+    }
+
+    fn resting_orders(&self, symbol: &str) -> Vec<String> {
+        let mut orders: Vec<&SimOrder> = self
+            .orders
+            .values()
+            .filter(|o| o.symbol == symbol && !o.state.is_terminal() && o.price.is_some())
+            .collect();
+        orders.sort_by_key(|o| o.sequence);
+        orders.into_iter().map(|o| o.order_id.clone()).collect()
+    }
+
+    /// 买单吃卖盘、卖单吃买盘，按价格优先返回可成交的 (价格, 数量)
+    ///
+    /// 只有最优价没有深度的订单簿（例如只推送最优报价的来源）视为最优价上数量不限。
+    fn sweep(&self, symbol: &str, side: OrderSide, limit: Option<f64>, quantity: f64) -> Vec<(f64, f64)> {
+        let Some(book) = self.books.get(symbol) else { return Vec::new() };
+        let (mut levels, best) = match side {
+            OrderSide::Buy => (book.depth_asks.clone(), book.best_ask),
+            OrderSide::Sell => (book.depth_bids.clone(), book.best_bid),
+        };
+        if levels.is_empty() && best > 0.0 {
+            levels.push((best, f64::INFINITY));
+        }
+        match side {
+            OrderSide::Buy => levels.sort_by(|a, b| a.0.total_cmp(&b.0)),
+            OrderSide::Sell => levels.sort_by(|a, b| b.0.total_cmp(&a.0)),
+        }
+
+        let mut left = quantity;
+        let mut takes = Vec::new();
+        for (price, size) in levels {
+            if left <= QTY_EPSILON {
+                break;
+            }
+            let acceptable = match (side, limit) {
+                (_, None) => true,
+                (OrderSide::Buy, Some(limit)) => price <= limit,
+                (OrderSide::Sell, Some(limit)) => price >= limit,
+            };
+            if !acceptable {
+                break;
+            }
+            if price <= 0.0 || size <= 0.0 {
+                continue;
+            }
+            let used = self.consumed.get(&(symbol.to_string(), side, price.to_bits())).copied().unwrap_or(0.0);
+            let take = (size - used).min(left);
+            if take > QTY_EPSILON {
+                takes.push((price, take));
+                left -= take;
+            }
+        }
+        takes
+    }
+
+    fn consume(&mut self, symbol: &str, side: OrderSide, price: f64, quantity: f64) {
+        *self.consumed.entry((symbol.to_string(), side, price.to_bits())).or_insert(0.0) += quantity;
+    }
+
+    /// 与订单同方向、同价位的可见挂单量；价位不在可见深度内时返回 None
+    fn level_quantity(&self, symbol: &str, side: OrderSide, price: f64) -> Option<f64> {
+        let book = self.books.get(symbol)?;
+        let levels = match side {
+            OrderSide::Buy => &book.depth_bids,
+            OrderSide::Sell => &book.depth_asks,
+        };
+        levels.iter().find(|(level, _)| *level == price).map(|(_, qty)| *qty)
+    }
+
+    /// 本价位挂单减少时视为排在前面的挂单成交或撤单；
+    /// 价位从可见深度中消失（且未被穿过）说明前面已无挂单
+    fn advance_queue(&mut self, order_id: &str) {
+        let Some(order) = self.orders.get(order_id) else { return };
+        let (symbol, side, price) = (order.symbol.clone(), order.side, order.price.unwrap_or(0.0));
+        let level_qty = self.level_quantity(&symbol, side, price);
+        let within_depth = self.books.get(&symbol).is_some_and(|book| {
+            let levels = match side {
+                OrderSide::Buy => &book.depth_bids,
+                OrderSide::Sell => &book.depth_asks,
+            };
+            levels.iter().any(|(level, _)| match side {
+                OrderSide::Buy => *level <= price,
+                OrderSide::Sell => *level >= price,
+            })
+        });
+        let Some(order) = self.orders.get_mut(order_id) else { return };
+        match level_qty {
+            Some(qty) => {
+                if qty < order.level_qty {
+                    order.queue_ahead = (order.queue_ahead - (order.level_qty - qty)).max(0.0);
+                }
+                order.queue_ahead = order.queue_ahead.min(qty);
+                order.level_qty = qty;
+            }
+            None if within_depth => {
+                order.queue_ahead = 0.0;
+                order.level_qty = 0.0;
+            }
+            None => {}
+        }
+    }
+
+    /// 多空反向的部分先平仓，剩余部分为开仓
+    fn opening_quantity(position: f64, side: OrderSide, quantity: f64) -> f64 {
+        let closing = match side {
+            OrderSide::Buy => (-position).max(0.0),
+            OrderSide::Sell => position.max(0.0),
+        };
+        (quantity - closing).max(0.0)
+    }
+
+    fn apply_fill(&mut self, order_id: &str, price: f64, quantity: f64, is_maker: bool, now: i64, events: &mut Vec<StandardizedMessage>) {
+        let Some(order) = self.orders.get(order_id) else { return };
+        let mut quantity = quantity.min(order.remaining());
+        if order.reduce_only {
+            // 持仓已被其他订单减少时，只减仓订单只成交剩余可减部分，其余过期
+            let position = self.positions.get(&order.symbol).map_or(0.0, |p| p.size);
+            quantity = quantity.min(match order.side {
+                OrderSide::Buy => (-position).max(0.0),
+                OrderSide::Sell => position.max(0.0),
+            });
+            if quantity <= QTY_EPSILON {
+                self.finish(order_id, OrderState::Expired, now, events);
+                return;
+            }
+        }
+        if quantity <= QTY_EPSILON {
+            return;
+        }
+        let Some(order) = self.orders.get_mut(order_id) else { return };
+        let fee_rate = if is_maker { self.config.fees.maker_fee } else { self.config.fees.taker_fee };
+        let fee = price * quantity * fee_rate;
+
+        // 按成交比例释放挂单冻结的资金
+        let release = order.locked * quantity / order.remaining();
+        order.locked -= release;
+        order.filled += quantity;
+        order.notional += price * quantity;
+        order.updated_at = now;
+        order.state = if order.remaining() <= QTY_EPSILON { OrderState::Filled } else { OrderState::PartiallyFilled };
+        let (symbol, side) = (order.symbol.clone(), order.side);
+        let order_event = self.order_event(&self.orders[order_id]);
+        events.push(order_event);
+
+        let Ok((base, quote)) = self.assets(&symbol) else { return };
+        if self.config.is_futures() {
+            let settlement = self.config.settlement_asset.clone();
+            let leverage = self.config.leverage;
+            let position = self.positions.entry(symbol.clone()).or_default();
+            let delta = if side == OrderSide::Buy { quantity } else { -quantity };
+            let mut realized = 0.0;
+            if position.size == 0.0 || position.size.signum() == delta.signum() {
+                let size = position.size.abs() + quantity;
+                position.entry_price = (position.entry_price * position.size.abs() + price * quantity) / size;
+            } else {
+                let closing = quantity.min(position.size.abs());
+                realized = closing * (price - position.entry_price) * position.size.signum();
+                if quantity > position.size.abs() + QTY_EPSILON {
+                    position.entry_price = price;
+                }
+            }
+            position.size += delta;
+            if position.size.abs() <= QTY_EPSILON {
+                position.size = 0.0;
+                position.entry_price = 0.0;
+            }
+            position.realized_pnl += realized;
+            let margin = position.size.abs() * position.entry_price / leverage;
+            let margin_change = margin - position.margin;
+            position.margin = margin;
+            let position = *position;
+
+            let balance = self.balances.entry(settlement.clone()).or_default();
+            balance.locked += margin_change - release;
+            balance.free += release - margin_change + realized - fee;
+            events.push(self.balance_event(&settlement, now));
+            events.push(StandardizedMessage::UserDataUpdate(UserData::PositionUpdate(PositionUpdate {
+                symbol: symbol.clone(),
+                exchange: self.config.exchange,
+                size: position.size,
+                entry_price: position.entry_price,
+                unrealized_pnl: self.unrealized_pnl(&symbol, &position),
+                timestamp: now,
+            })));
+        } else {
+            match side {
+                OrderSide::Buy => {
+                    let quote_balance = self.balances.entry(quote.clone()).or_default();
+                    quote_balance.locked -= release;
+                    quote_balance.free += release - price * quantity - fee;
+                    self.balances.entry(base.clone()).or_default().free += quantity;
+                }
+                OrderSide::Sell => {
+                    let base_balance = self.balances.entry(base.clone()).or_default();
+                    base_balance.locked -= release;
+                    base_balance.free += release - quantity;
+                    self.balances.entry(quote.clone()).or_default().free += price * quantity - fee;
+                }
+            }
+            events.push(self.balance_event(&base, now));
+            events.push(self.balance_event(&quote, now));
+        }
+
+        self.fills.push(SimulatedFill {
+            order_id: order_id.to_string(),
+            symbol,
+            side,
+            price,
+            quantity,
+            fee,
+            is_maker,
+            timestamp: now,
+        });
+    }
+
+    /// 结束订单（撤单或过期），释放剩余冻结资金
+    fn finish(&mut self, order_id: &str, state: OrderState, now: i64, events: &mut Vec<StandardizedMessage>) {
+        let Some(order) = self.orders.get_mut(order_id) else { return };
+        let release = std::mem::take(&mut order.locked);
+        order.state = state;
+        order.updated_at = now;
+        let (symbol, side) = (order.symbol.clone(), order.side);
+        let order_event = self.order_event(&self.orders[order_id]);
+        events.push(order_event);
+
+        if release > 0.0 {
+            let asset = if self.config.is_futures() {
+                Some(self.config.settlement_asset.clone())
+            } else {
+                self.assets(&symbol).ok().map(|(base, quote)| if side == OrderSide::Buy { quote } else { base })
+            };
+            if let Some(asset) = asset {
+                let balance = self.balances.entry(asset.clone()).or_default();
+                balance.locked -= release;
+                balance.free += release;
+                events.push(self.balance_event(&asset, now));
+            }
+        }
+    }
+
+    fn mid_price(&self, symbol: &str) -> Option<f64> {
+        let book = self.books.get(symbol)?;
+        (book.best_bid > 0.0 && book.best_ask > 0.0).then(|| (book.best_bid + book.best_ask) / 2.0)
+    }
+
+    fn unrealized_pnl(&self, symbol: &str, position: &SimPosition) -> f64 {
+        self.mid_price(symbol).map_or(0.0, |mark| (mark - position.entry_price) * position.size)
+    }
+
+    fn order_event(&self, order: &SimOrder) -> StandardizedMessage {
+        StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(OrderUpdate {
+            order_id: order.order_id.clone(),
+            symbol: order.symbol.clone(),
+            exchange: self.config.exchange,
+            status: order.state.to_api_string().to_string(),
+            filled_quantity: order.filled,
+            remaining_quantity: order.remaining(),
+            average_price: order.average_price(),
+            timestamp: order.updated_at,
+        }))
+    }
+
+    fn balance_event(&self, asset: &str, now: i64) -> StandardizedMessage {
+        let balance = self.balances.get(asset).copied().unwrap_or_default();
+        StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(BalanceUpdate {
+            asset: asset.to_string(),
+            exchange: self.config.exchange,
+            free: balance.free,
+            locked: balance.locked,
+            timestamp: now,
+        }))
+    }
+}
@@ -0,0 +1,12 @@
+//! 模拟交易所模块
+//! 用实时或回放行情驱动内部撮合引擎，提供与实时连接器一致的交易接口和用户数据推送，
+//! 无需真实密钥即可端到端运行策略（模拟盘）
+
+pub mod connector;
+pub mod engine;
+
+#[cfg(test)]
+mod test;
+
+pub use connector::SimulatedExchangeConnector;
+pub use engine::{MatchingEngine, SimulatedExchangeConfig, SimulatedFill};
@@ -0,0 +1,286 @@
+//! 模拟交易所测试模块
+//! 验证吃单、挂单队列、FOK/post-only/只减仓规则、合约持仓记账，以及连接器的数据流与执行器对接
+
+mod tests {
+    use super::super::connector::SimulatedExchangeConnector;
+    use super::super::engine::{MatchingEngine, SimulatedExchangeConfig};
+    use crate::arbitrage_executor::{ArbitrageExecutor, ExecutionStatus};
+    use crate::config::ExecutionConfig;
+    use crate::connectors::traits::ExchangeConnector;
+    use crate::core::AppState;
+    use crate::exchange_types::{CrossExchangeArb, Exchange, ExchangeFees, StandardOrderBook};
+    use crate::types::{
+        errors::ConnectorError,
+        exchange::{ExchangeType, MarketType},
+        market_data::{StandardizedMessage, StandardizedTrade, TradeSide, UserData},
+        orders::{OrderRequest, OrderSide, OrderType, PositionSide, TimeInForce},
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn book(exchange: Exchange, symbol: &str, bids: &[(f64, f64)], asks: &[(f64, f64)], timestamp: i64) -> StandardOrderBook {
+        let mut book = StandardOrderBook::new_minimal(symbol, exchange, bids[0].0, asks[0].0, timestamp);
+        book.depth_bids = bids.to_vec();
+        book.depth_asks = asks.to_vec();
+        book
+    }
+
+    fn trade(symbol: &str, price: f64, quantity: f64, side: TradeSide) -> StandardizedTrade {
+        StandardizedTrade {
+            symbol: symbol.to_string(),
+            exchange: ExchangeType::XtCom,
+            price,
+            quantity,
+            side,
+            timestamp: 2_000,
+            trade_id: "1".to_string(),
+        }
+    }
+
+    fn order(symbol: &str, side: OrderSide, quantity: f64, price: Option<f64>, time_in_force: Option<TimeInForce>) -> OrderRequest {
+        OrderRequest {
+            symbol: symbol.to_string(),
+            exchange: ExchangeType::XtCom,
+            side,
+            order_type: if price.is_some() { OrderType::Limit } else { OrderType::Market },
+            quantity,
+            price,
+            time_in_force,
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: None,
+        }
+    }
+
+    fn spot_engine() -> MatchingEngine {
+        MatchingEngine::new(
+            SimulatedExchangeConfig::new(ExchangeType::XtCom, MarketType::Spot, ExchangeFees::new(Exchange::XtCom, 0.001, 0.002))
+                .with_balance("USDT", 10_000.0)
+                .with_balance("BTC", 1.0),
+        )
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_taker_orders_sweep_levels_and_respect_time_in_force() {
+        let mut engine = spot_engine();
+        engine.on_orderbook(book(Exchange::XtCom, "BTCUSDT", &[(99.0, 1.0), (98.0, 2.0)], &[(100.0, 1.0), (101.0, 2.0)], 1_000));
+
+        // IOC 只吃到限价以内的一档，剩余部分过期
+        let (response, events) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 2.0, Some(100.5), Some(TimeInForce::IOC)), 1_000).unwrap();
+        assert_eq!(response.status, "EXPIRED");
+        assert_eq!(response.filled_quantity, 1.0);
+        assert_eq!(response.average_price, Some(100.0));
+        let statuses: Vec<String> = events
+            .iter()
+            .filter_map(|event| match event {
+                StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(update)) => Some(update.status.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(statuses, vec!["NEW", "PARTIALLY_FILLED", "EXPIRED"]);
+
+        // 同一份快照中已被吃掉的数量不再可用
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 2.0, None, None), 1_000).unwrap();
+        assert_eq!(response.status, "FILLED");
+        assert_eq!(response.average_price, Some(101.0));
+
+        let balance = engine.account_balance();
+        assert_close(balance.balances["USDT"].available, 10_000.0 - 100.0 * 1.002 - 202.0 * 1.002);
+        assert_close(balance.balances["BTC"].available, 4.0);
+
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 5.0, Some(102.0), Some(TimeInForce::FOK)), 1_000).unwrap();
+        assert_eq!((response.status.as_str(), response.filled_quantity), ("EXPIRED", 0.0));
+
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 1.0, Some(100.0), Some(TimeInForce::GTX)), 1_000).unwrap();
+        assert_eq!(response.status, "EXPIRED");
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 1.0, Some(99.5), Some(TimeInForce::GTX)), 1_000).unwrap();
+        assert_eq!(response.status, "NEW");
+        assert_close(engine.account_balance().balances["USDT"].frozen, 99.5 * 1.001);
+
+        assert!(matches!(
+            engine.place_order(&order("BTCUSDT", OrderSide::Sell, 100.0, None, None), 1_000),
+            Err(ConnectorError::InsufficientBalance(_))
+        ));
+        let reduce_only = OrderRequest { reduce_only: Some(true), ..order("BTCUSDT", OrderSide::Sell, 1.0, None, None) };
+        assert!(engine.place_order(&reduce_only, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_resting_order_advances_through_queue() {
+        let mut engine = spot_engine();
+        engine.on_orderbook(book(Exchange::XtCom, "BTCUSDT", &[(99.0, 1.5), (98.0, 3.0)], &[(100.0, 2.0)], 1_000));
+
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Buy, 1.0, Some(99.0), None), 1_000).unwrap();
+        assert_eq!(response.status, "NEW");
+        let order_id = response.order_id;
+
+        // 排在前面 1.5，成交 1.0 后剩 0.5；挂单量从 1.5 降到 1.2 再前进 0.3
+        assert!(engine.on_trade(&trade("BTCUSDT", 99.0, 1.0, TradeSide::Sell)).is_empty());
+        engine.on_orderbook(book(Exchange::XtCom, "BTCUSDT", &[(99.0, 1.2), (98.0, 3.0)], &[(100.0, 2.0)], 1_500));
+        assert!(!engine.on_trade(&trade("BTCUSDT", 99.0, 0.5, TradeSide::Sell)).is_empty());
+        let status = engine.order_status(&order_id, "BTCUSDT").unwrap();
+        assert_eq!(status.status, "PARTIALLY_FILLED");
+        assert_close(status.filled_quantity, 0.3);
+
+        // 卖盘下移穿过挂单价，剩余部分以挂单价成交
+        engine.on_orderbook(book(Exchange::XtCom, "BTCUSDT", &[(98.0, 3.0)], &[(98.5, 3.0)], 3_000));
+        let status = engine.order_status(&order_id, "BTCUSDT").unwrap();
+        assert_eq!(status.status, "FILLED");
+        assert_eq!(status.average_price, Some(99.0));
+        assert!(engine.fills().iter().all(|fill| fill.is_maker && fill.price == 99.0));
+        assert_close(engine.account_balance().balances["USDT"].available, 10_000.0 - 99.0 * 1.001);
+        assert!(!engine.cancel_order(&order_id, "BTCUSDT", 3_000).unwrap().0);
+
+        // 撤单释放冻结的资产
+        let (response, _) = engine.place_order(&order("BTCUSDT", OrderSide::Sell, 1.0, Some(105.0), None), 3_000).unwrap();
+        assert_close(engine.account_balance().balances["BTC"].frozen, 1.0);
+        assert!(engine.cancel_order(&response.order_id, "BTCUSDT", 3_100).unwrap().0);
+        let btc = &engine.account_balance().balances["BTC"];
+        assert_eq!((btc.available, btc.frozen), (2.0, 0.0));
+        assert!(engine.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_futures_positions_margin_and_reduce_only() {
+        let mut config = SimulatedExchangeConfig::new(
+            ExchangeType::BinanceFutures,
+            MarketType::Futures,
+            ExchangeFees::new(Exchange::BinanceFutures, 0.0002, 0.0005),
+        )
+        .with_balance("USDT", 1_000.0);
+        config.leverage = 10.0;
+        let mut engine = MatchingEngine::new(config);
+        engine.on_orderbook(book(Exchange::BinanceFutures, "BTCUSDT", &[(100.0, 10.0)], &[(101.0, 10.0)], 1_000));
+
+        let reduce_only = OrderRequest { reduce_only: Some(true), ..order("BTCUSDT", OrderSide::Sell, 1.0, None, None) };
+        assert!(engine.place_order(&reduce_only, 1_000).is_err());
+
+        engine.place_order(&order("BTCUSDT", OrderSide::Buy, 2.0, None, None), 1_000).unwrap();
+        let positions = engine.positions(1_000);
+        assert_eq!(positions.len(), 1);
+        assert_eq!((positions[0].side, positions[0].size, positions[0].entry_price), (PositionSide::Long, 2.0, 101.0));
+        assert_close(positions[0].unrealized_pnl, -1.0);
+        let usdt = &engine.account_balance().balances["USDT"];
+        assert_close(usdt.frozen, 20.2);
+        assert_close(usdt.available, 1_000.0 - 20.2 - 0.101);
+
+        // 只减仓订单的数量被限制在持仓以内，平仓亏损计入结算货币
+        let close = OrderRequest {
+            reduce_only: Some(true),
+            ..order("BTCUSDT", OrderSide::Sell, 5.0, Some(100.0), Some(TimeInForce::IOC))
+        };
+        let (response, events) = engine.place_order(&close, 1_000).unwrap();
+        assert_eq!((response.status.as_str(), response.filled_quantity), ("FILLED", 2.0));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            StandardizedMessage::UserDataUpdate(UserData::PositionUpdate(position)) if position.size == 0.0
+        )));
+        assert!(engine.positions(1_000).is_empty());
+        let balance = engine.account_balance();
+        assert_close(balance.frozen, 0.0);
+        assert_close(balance.available, 1_000.0 - 0.101 - 2.0 - 0.1);
+        assert_close(balance.total, balance.available);
+    }
+
+    #[tokio::test]
+    async fn test_connector_pumps_source_and_streams_user_data() {
+        let fees = ExchangeFees::new(Exchange::XtCom, 0.001, 0.001);
+        let source = Arc::new(SimulatedExchangeConnector::new(
+            SimulatedExchangeConfig::new(ExchangeType::XtCom, MarketType::Spot, fees.clone()),
+            None,
+        ));
+        let simulator = SimulatedExchangeConnector::new(
+            SimulatedExchangeConfig::new(ExchangeType::XtCom, MarketType::Spot, fees).with_balance("USDT", 1_000.0),
+            Some(source.clone()),
+        );
+        let mut market = simulator.get_market_data_stream();
+        let mut user = simulator.get_user_data_stream();
+        simulator.connect_websocket().await.unwrap();
+        assert!(simulator.is_connected().await);
+
+        let snapshot = book(Exchange::XtCom, "ETHUSDT", &[(9.0, 5.0)], &[(10.0, 5.0)], 1_000);
+        source.feed(StandardizedMessage::OrderBookUpdate(snapshot)).await;
+        let forwarded = tokio::time::timeout(Duration::from_secs(1), market.recv()).await.unwrap();
+        assert!(matches!(forwarded, Some(StandardizedMessage::OrderBookUpdate(_))));
+        assert!(simulator.get_orderbook_snapshot("ETHUSDT").await.is_some());
+
+        let response = simulator.place_order(&order("ETHUSDT", OrderSide::Buy, 2.0, None, None)).await.unwrap();
+        assert_eq!(response.status, "FILLED");
+        let mut statuses = Vec::new();
+        let mut balances = Vec::new();
+        while let Some(message) = user.try_recv() {
+            match message {
+                StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(update)) => {
+                    assert_eq!(update.exchange, ExchangeType::XtCom);
+                    statuses.push(update.status);
+                }
+                StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(update)) => balances.push(update.asset),
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+        assert_eq!(statuses, vec!["NEW".to_string(), "FILLED".to_string()]);
+        assert!(balances.contains(&"ETH".to_string()) && balances.contains(&"USDT".to_string()));
+
+        simulator.disconnect_websocket().await.unwrap();
+        assert!(!simulator.is_connected().await);
+    }
+
+    #[tokio::test]
+    async fn test_arbitrage_executor_runs_against_simulators() {
+        let buy = Arc::new(SimulatedExchangeConnector::new(
+            SimulatedExchangeConfig::new(ExchangeType::XtCom, MarketType::Spot, ExchangeFees::new(Exchange::XtCom, 0.001, 0.001))
+                .with_balance("USDT", 10_000.0),
+            None,
+        ));
+        let sell = Arc::new(SimulatedExchangeConnector::new(
+            SimulatedExchangeConfig::new(ExchangeType::TapBit, MarketType::Spot, ExchangeFees::new(Exchange::TapBit, 0.001, 0.001))
+                .with_balance("SIMTEST", 10.0),
+            None,
+        ));
+        for (connector, exchange) in [(&buy, Exchange::XtCom), (&sell, Exchange::TapBit)] {
+            connector.connect_websocket().await.unwrap();
+            connector
+                .feed(StandardizedMessage::OrderBookUpdate(book(exchange, "SIMTESTUSDT", &[(99.0, 5.0)], &[(100.0, 5.0)], 1_000)))
+                .await;
+        }
+        sell.feed(StandardizedMessage::OrderBookUpdate(book(Exchange::TapBit, "SIMTESTUSDT", &[(101.0, 5.0)], &[(102.0, 5.0)], 1_000)))
+            .await;
+
+        // 风控要求买入所的报价货币和现货卖出所的基础货币都已同步
+        let app_state = AppState::new();
+        app_state.apply_account_balance(Exchange::XtCom, &buy.get_account_balance().await.unwrap());
+        app_state.apply_account_balance(Exchange::TapBit, &sell.get_account_balance().await.unwrap());
+        let mut executor = ArbitrageExecutor::new(
+            Arc::new(app_state),
+            ExecutionConfig { fill_timeout_ms: 50, ..ExecutionConfig::default() },
+        );
+        executor.add_connector(buy.clone());
+        executor.add_connector(sell.clone());
+        let report = executor
+            .execute(&CrossExchangeArb {
+                symbol: "SIMTESTUSDT".to_string(),
+                buy_exchange: Exchange::XtCom,
+                sell_exchange: Exchange::TapBit,
+                buy_price: 100.0,
+                sell_price: 101.0,
+                timestamp: chrono::Utc::now().timestamp_millis(),
+                profit_pct: 1.0,
+                net_profit_pct: 0.8,
+                total_fees_pct: 0.2,
+                optimal_size: 2.0,
+                expected_pnl: 1.6,
+                profit_curve: Vec::new(),
+            })
+            .await;
+
+        assert_eq!(report.status, ExecutionStatus::Completed);
+        assert_close(report.cash_flow, 2.0);
+        assert_close(buy.get_account_balance().await.unwrap().balances["SIMTEST"].available, 2.0);
+        assert_close(sell.get_account_balance().await.unwrap().balances["USDT"].available, 202.0 * 0.999);
+    }
+}
@@ -0,0 +1,14 @@
+//! TapBit连接器模块
+//! USDT永续合约行情（usdt/orderBook / usdt/tradeList 频道）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::TapBitProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// TapBit行情连接器
+pub type TapBitConnector = MarketStreamConnector<TapBitProtocol>;
@@ -0,0 +1,114 @@
+//! TapBit USDT永续合约行情协议
+//! 订阅格式：`{"op":"subscribe","args":["usdt/orderBook.BTC-SWAP.10","usdt/tradeList.BTC-SWAP"]}`；
+//! 合约名只包含基础币种（BTC-SWAP），深度每次推送完整的前N档；心跳为纯文本 `ping` / `pong`。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::market_stream::{
+    build_orderbook, value_to_f64, value_to_i64, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// TapBit WebSocket默认地址
+pub const TAPBIT_WS_URL: &str = "wss://ws-openapi.tapbit.com/stream/ws";
+
+/// 订阅的深度档位
+const DEPTH_LEVELS: u32 = 10;
+
+const ORDERBOOK_TOPIC: &str = "usdt/orderBook.";
+const TRADE_TOPIC: &str = "usdt/tradeList.";
+
+/// TapBit USDT永续合约行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TapBitProtocol;
+
+impl MarketStreamProtocol for TapBitProtocol {
+    const NAME: &'static str = "TapBit";
+    const DEFAULT_WS_URL: &'static str = TAPBIT_WS_URL;
+    const EXCHANGE: Exchange = Exchange::TapBit;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::TapBit;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+
+    /// USDT永续合约名不带报价币种
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}-SWAP", info.base_currency)
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        match exchange_symbol.to_uppercase().strip_suffix("-SWAP") {
+            Some(base) => format!("{base}USDT"),
+            None => exchange_symbol.replace(['-', '_', '/'], "").to_uppercase(),
+        }
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], _request_id: u64) -> String {
+        let args: Vec<String> = symbols.iter()
+            .map(|symbol| match channel {
+                MarketChannel::OrderBook => format!("{ORDERBOOK_TOPIC}{symbol}.{DEPTH_LEVELS}"),
+                MarketChannel::Trades => format!("{TRADE_TOPIC}{symbol}"),
+            })
+            .collect();
+        json!({
+            "op": if subscribe { "subscribe" } else { "unsubscribe" },
+            "args": args,
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        Some("ping".to_string())
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        if text == "pong" {
+            return Ok(vec![MarketEvent::Pong]);
+        }
+
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析TapBit消息: {e}")))?;
+
+        let Some(topic) = msg.get("topic").and_then(|t| t.as_str()) else {
+            if msg.get("event").and_then(|e| e.as_str()) == Some("error") {
+                return Ok(vec![MarketEvent::Error(msg.to_string())]);
+            }
+            return Ok(vec![MarketEvent::Other(msg)]);
+        };
+        let data = msg.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+
+        if let Some(rest) = topic.strip_prefix(ORDERBOOK_TOPIC) {
+            // usdt/orderBook.BTC-SWAP.10
+            let instrument = rest.rsplit_once('.').map(|(instrument, _)| instrument).unwrap_or(rest);
+            let symbol = Self::standard_symbol(instrument);
+            return Ok(data.iter()
+                .map(|book| {
+                    let timestamp = book.get("timestamp").and_then(value_to_i64).unwrap_or(0);
+                    MarketEvent::Book(build_orderbook(Self::EXCHANGE, symbol.clone(), book.get("bids"), book.get("asks"), timestamp))
+                })
+                .collect());
+        }
+
+        if let Some(instrument) = topic.strip_prefix(TRADE_TOPIC) {
+            let symbol = Self::standard_symbol(instrument);
+            let trades = data.iter()
+                .filter_map(|trade| {
+                    Some(StandardizedTrade {
+                        symbol: symbol.clone(),
+                        exchange: Self::EXCHANGE_TYPE,
+                        price: value_to_f64(trade.get("price")?)?,
+                        quantity: value_to_f64(trade.get("size")?)?,
+                        side: if trade.get("side")?.as_str()?.eq_ignore_ascii_case("sell") { TradeSide::Sell } else { TradeSide::Buy },
+                        timestamp: value_to_i64(trade.get("timestamp")?)?,
+                        trade_id: trade.get("tradeId").and_then(value_to_i64).map(|id| id.to_string()).unwrap_or_default(),
+                    })
+                })
+                .collect();
+            return Ok(vec![MarketEvent::Trades(trades)]);
+        }
+
+        Ok(vec![MarketEvent::Other(msg)])
+    }
+}
@@ -0,0 +1,81 @@
+//! TapBit连接器测试模块
+//! 使用录制的推送帧测试协议解析、符号转换和批量订阅
+
+mod tests {
+    use super::super::{TapBitConnector, TapBitProtocol};
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::core::AppState;
+    use crate::types::{config::ConnectorConfig, market_data::TradeSide};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// 录制的TapBit深度帧
+    const RECORDED_DEPTH_FRAME: &str = r#"{"topic":"usdt/orderBook.BTC-SWAP.10","action":"insert","data":[{"asks":[["64012.4","1.532"],["64012.9","0.2"]],"bids":[["64011.9","5.321"],["64011.5","0.78"]],"version":"1710755362722","timestamp":1710755362722}]}"#;
+
+    /// 录制的TapBit成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"topic":"usdt/tradeList.BTC-SWAP","data":[{"tradeId":90231455,"price":"64011.9","size":"0.015","side":"SELL","timestamp":1710755363460},{"tradeId":90231456,"price":"64012.9","size":"0.2","side":"BUY","timestamp":1710755363461}]}"#;
+
+    /// 录制的TapBit订阅确认和错误
+    const RECORDED_SUBSCRIBE_ACK_FRAME: &str = r#"{"event":"subscribe","args":["usdt/orderBook.BTC-SWAP.10"]}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"event":"error","code":"30040","msg":"channel doesn't exist"}"#;
+
+    #[test]
+    fn test_tapbit_parse_recorded_frames() {
+        let events = TapBitProtocol::parse_message(RECORDED_DEPTH_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Book(orderbook)] => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 64011.9);
+                assert_eq!(orderbook.best_ask, 64012.4);
+                assert_eq!(orderbook.timestamp, 1710755362722);
+            }
+            other => panic!("应该解析为深度: {other:?}"),
+        }
+
+        let events = TapBitProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades.len(), 2);
+                assert_eq!(trades[0].symbol, "BTCUSDT");
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[1].side, TradeSide::Buy);
+                assert_eq!(trades[1].trade_id, "90231456");
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        assert!(matches!(&TapBitProtocol::parse_message("pong").unwrap()[..], [MarketEvent::Pong]));
+        assert!(matches!(&TapBitProtocol::parse_message(RECORDED_SUBSCRIBE_ACK_FRAME).unwrap()[..], [MarketEvent::Other(_)]));
+        assert!(matches!(&TapBitProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_tapbit_symbol_conversion() {
+        let connector = TapBitConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        for symbol in ["BTCUSDT", "BTC_USDT", "BTC/USDT", "TAPBIT:btc-usdt"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "BTC-SWAP");
+        }
+        assert_eq!(TapBitProtocol::standard_symbol("ETH-SWAP"), "ETHUSDT");
+        assert_eq!(TapBitProtocol::standard_symbol("ETHUSDT"), "ETHUSDT");
+    }
+
+    #[test]
+    fn test_tapbit_batched_subscription() {
+        let connector = TapBitConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        let symbols: Vec<String> = ["BTC", "ETH", "SOL", "XRP", "DOGE", "ADA"].iter()
+            .map(|base| format!("{base}-SWAP"))
+            .collect();
+
+        let requests = connector.handler().subscription_requests(true, MarketChannel::OrderBook, &symbols, connector.handler().batch_size());
+        assert_eq!(requests.len(), 2);
+
+        let first: Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(first["op"], "subscribe");
+        assert_eq!(first["args"].as_array().unwrap().len(), 5);
+        assert_eq!(first["args"][0], "usdt/orderBook.BTC-SWAP.10");
+
+        let trades: Value = serde_json::from_str(&TapBitProtocol::subscription_message(false, MarketChannel::Trades, &symbols[..1], 0)).unwrap();
+        assert_eq!(trades["op"], "unsubscribe");
+        assert_eq!(trades["args"][0], "usdt/tradeList.BTC-SWAP");
+    }
+}
@@ -0,0 +1,278 @@
+//! 连接器测试共用的本地模拟服务器
+//!
+//! `MockHttpServer` 完整读取每个HTTP请求（请求头和 `Content-Length` 指定的请求体），
+//! 记录后交给测试提供的闭包生成响应；`MockWsServer` 接受WebSocket连接并记录握手路径，
+//! 每条连接的应答逻辑由测试提供，客户端消息通过 `WsConnection::record` 交给测试断言。
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tokio_tungstenite::WebSocketStream;
+
+/// 等待请求或消息的超时
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 模拟HTTP服务器收到的请求
+#[derive(Debug, Clone, Default)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// 路径和查询串
+    pub target: String,
+    /// 请求头，名称统一为小写
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    pub fn query(&self) -> &str {
+        self.target.split_once('?').map(|(_, query)| query).unwrap_or_default()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+}
+
+/// 模拟HTTP响应，默认 `Content-Type: application/json`
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::with_status("200 OK", body)
+    }
+
+    /// `status` 为状态码和原因短语，例如 `429 Too Many Requests`
+    pub fn with_status(status: &str, body: impl Into<String>) -> Self {
+        Self {
+            status: status.to_string(),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn to_http(&self) -> String {
+        let headers: String = self.headers.iter().map(|(name, value)| format!("{name}: {value}\r\n")).collect();
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// 记录请求的模拟HTTP服务器
+pub struct MockHttpServer {
+    /// `http://127.0.0.1:<port>`
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    recorded: Arc<Notify>,
+}
+
+impl MockHttpServer {
+    /// 启动服务器：每个连接处理一个请求，先记录请求再返回 `respond` 生成的响应
+    pub async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::new(Notify::new());
+
+        let respond = Arc::new(respond);
+        let (log, notify) = (requests.clone(), recorded.clone());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (respond, log, notify) = (respond.clone(), log.clone(), notify.clone());
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else { return };
+                    let response = respond(&request);
+                    log.lock().unwrap().push(request);
+                    notify.notify_waiters();
+                    let _ = stream.write_all(response.to_http().as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Self { url, requests, recorded }
+    }
+
+    /// 所有请求都返回同一个响应
+    pub async fn respond_with(response: MockResponse) -> Self {
+        Self::start(move |_| response.clone()).await
+    }
+
+    /// 依次返回 `bodies` 中的响应体，用完后返回 `{}`
+    pub async fn sequence(bodies: Vec<String>) -> Self {
+        let bodies = Mutex::new(bodies.into_iter());
+        Self::start(move |_| MockResponse::ok(bodies.lock().unwrap().next().unwrap_or_else(|| "{}".to_string()))).await
+    }
+
+    /// 已收到的全部请求（按记录顺序）
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 等待第 `index` 个请求（从0开始）
+    pub async fn request(&self, index: usize) -> RecordedRequest {
+        timeout(WAIT_TIMEOUT, async {
+            loop {
+                let notified = self.recorded.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if let Some(request) = self.requests.lock().unwrap().get(index) {
+                    return request.clone();
+                }
+                notified.await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("等待第{index}个HTTP请求超时"))
+    }
+}
+
+/// 读取一个完整的请求，连接在请求头结束前关闭时返回 None
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+        if let Some(pos) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers.get("content-length").and_then(|length| length.parse().ok()).unwrap_or(0usize);
+    while buffer.len() < header_end + content_length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    }
+    let body = String::from_utf8_lossy(&buffer[header_end..]).to_string();
+
+    Some(RecordedRequest { method, target, headers, body })
+}
+
+/// 握手回调：记录客户端请求的路径和查询串
+struct RecordRequestPath(Arc<Mutex<String>>);
+
+impl Callback for RecordRequestPath {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        *self.0.lock().unwrap() = request.uri().to_string();
+        Ok(response)
+    }
+}
+
+/// 模拟WebSocket服务器上的一条连接
+#[derive(Clone)]
+pub struct WsConnection {
+    /// 连接序号，从0开始
+    pub index: usize,
+    /// 握手请求的路径和查询串
+    pub path: String,
+    received: mpsc::UnboundedSender<(usize, String, Value)>,
+}
+
+impl WsConnection {
+    /// 记录一条客户端消息，供 `MockWsServer::expect` 断言
+    pub fn record(&self, msg: &Value) {
+        let _ = self.received.send((self.index, self.path.clone(), msg.clone()));
+    }
+}
+
+/// 记录连接和客户端消息的模拟WebSocket服务器
+pub struct MockWsServer {
+    /// `ws://127.0.0.1:<port>`
+    pub url: String,
+    connections: mpsc::UnboundedReceiver<String>,
+    received: mpsc::UnboundedReceiver<(usize, String, Value)>,
+}
+
+impl MockWsServer {
+    /// 启动服务器：每条连接完成握手后交给 `handler` 处理
+    pub async fn start<F, Fut>(handler: F) -> Self
+    where
+        F: Fn(WebSocketStream<TcpStream>, WsConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (connection_tx, connections) = mpsc::unbounded_channel();
+        let (received_tx, received) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut index = 0;
+            while let Ok((stream, _)) = listener.accept().await {
+                let path = Arc::new(Mutex::new(String::new()));
+                let Ok(ws) = tokio_tungstenite::accept_hdr_async(stream, RecordRequestPath(path.clone())).await else { continue };
+                let path = path.lock().unwrap().clone();
+                let _ = connection_tx.send(path.clone());
+                tokio::spawn(handler(ws, WsConnection { index, path, received: received_tx.clone() }));
+                index += 1;
+            }
+        });
+
+        Self { url, connections, received }
+    }
+
+    /// 等待下一条连接完成握手，返回其路径和查询串
+    pub async fn next_connection(&mut self) -> String {
+        timeout(WAIT_TIMEOUT, self.connections.recv())
+            .await
+            .expect("等待WebSocket连接超时")
+            .expect("模拟服务器已关闭")
+    }
+
+    /// 等待满足条件的客户端消息：(连接序号, 握手路径, 消息内容)
+    pub async fn expect<P>(&mut self, mut predicate: P) -> (usize, String, Value)
+    where
+        P: FnMut(usize, &str, &Value) -> bool,
+    {
+        timeout(WAIT_TIMEOUT, async {
+            loop {
+                let (conn, path, msg) = self.received.recv().await.expect("模拟服务器已关闭");
+                if predicate(conn, &path, &msg) {
+                    return (conn, path, msg);
+                }
+            }
+        })
+        .await
+        .expect("等待客户端消息超时")
+    }
+}
@@ -1,12 +1,16 @@
 // src/connectors/traits/mod.rs - 严格按照核心Trait定义实现
 
 use async_trait::async_trait;
-use tokio::sync::{mpsc, broadcast};
+use tokio::sync::broadcast;
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 use chrono;
 use crate::types::*;
 use crate::types::config::BatchSubscriptionResult;
+use crate::connectors::common::clock_sync::ClockSource;
+use crate::connectors::common::instruments::InstrumentSource;
+use crate::connectors::common::data_channel::{ChannelSendError, DataReceiver};
 
 /// ExchangeConnector trait - 完全按照CrossFury_核心Trait定义.md实现
 #[async_trait]
@@ -24,8 +28,23 @@ pub trait ExchangeConnector: Send + Sync {
     async fn subscribe_user_stream(&self) -> Result<(), ConnectorError>;
     
     // 推送式数据流接口
-    fn get_market_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage>;
-    fn get_user_data_stream(&self) -> mpsc::UnboundedReceiver<StandardizedMessage>;
+    fn get_market_data_stream(&self) -> DataReceiver<StandardizedMessage>;
+    fn get_user_data_stream(&self) -> DataReceiver<StandardizedMessage>;
+
+    /// 数据通道统计（发送、丢弃、合并和排队数量）
+    fn get_data_flow_stats(&self) -> DataFlowStats {
+        DataFlowStats::default()
+    }
+
+    /// 服务器时钟及其时间来源，注册到时间同步服务后签名时间戳会被校正
+    fn clock_source(&self) -> Option<ClockSource> {
+        None
+    }
+
+    /// 交易品种元数据来源，用于加载价格步长、数量步长和最小名义价值
+    fn instrument_source(&self) -> Option<Arc<dyn InstrumentSource>> {
+        None
+    }
     
     // 本地缓存快照读取
     async fn get_orderbook_snapshot(&self, symbol: &str) -> Option<StandardizedOrderBook>;
@@ -95,9 +114,9 @@ pub trait ExchangeConnector: Send + Sync {
 #[async_trait]
 pub trait DataFlowManager: Send + Sync {
     // 高频数据流管理
-    fn take_market_data_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<HighFrequencyData>>;
+    fn take_market_data_receiver(&mut self) -> Option<DataReceiver<HighFrequencyData>>;
     fn subscribe_events(&self) -> broadcast::Receiver<SystemEvent>;
-    fn send_market_data(&self, data: HighFrequencyData) -> Result<(), mpsc::error::SendError<HighFrequencyData>>;
+    fn send_market_data(&self, data: HighFrequencyData) -> Result<(), ChannelSendError<HighFrequencyData>>;
     async fn send_event(&self, event: SystemEvent);
 }
 
@@ -0,0 +1,14 @@
+//! XT.com连接器模块
+//! USDT永续合约行情（depth / trade 频道）
+
+pub mod protocol;
+
+#[cfg(test)]
+mod test;
+
+pub use protocol::XtComProtocol;
+
+use crate::connectors::common::market_stream::MarketStreamConnector;
+
+/// XT.com行情连接器
+pub type XtComConnector = MarketStreamConnector<XtComProtocol>;
@@ -0,0 +1,109 @@
+//! XT.com合约行情协议
+//! 订阅格式：`{"method":"subscribe","params":["depth@btc_usdt,20","trade@btc_usdt"],"id":"1"}`；
+//! 深度每次推送完整的前N档；心跳为纯文本 `ping` / `pong`。成交数量单位为张。
+
+use serde_json::{json, Value};
+
+use crate::connectors::common::market_stream::{
+    build_orderbook, value_to_f64, value_to_i64, MarketChannel, MarketEvent, MarketStreamProtocol,
+};
+use crate::connectors::common::symbol_converter::SymbolInfo;
+use crate::core::AppError;
+use crate::exchange_types::Exchange;
+use crate::types::exchange::{ExchangeType, MarketType};
+use crate::types::market_data::{StandardizedTrade, TradeSide};
+
+/// XT.com合约WebSocket默认地址
+pub const XTCOM_WS_URL: &str = "wss://fstream.xt.com/ws/market";
+
+/// 订阅的深度档位
+const DEPTH_LEVELS: u32 = 20;
+
+/// XT.com合约行情协议
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XtComProtocol;
+
+impl MarketStreamProtocol for XtComProtocol {
+    const NAME: &'static str = "XT.com";
+    const DEFAULT_WS_URL: &'static str = XTCOM_WS_URL;
+    const EXCHANGE: Exchange = Exchange::XtCom;
+    const EXCHANGE_TYPE: ExchangeType = ExchangeType::XtCom;
+    const MARKET_TYPE: MarketType = MarketType::Futures;
+
+    fn exchange_symbol(info: &SymbolInfo) -> String {
+        format!("{}_{}", info.base_currency, info.quote_currency).to_lowercase()
+    }
+
+    fn standard_symbol(exchange_symbol: &str) -> String {
+        exchange_symbol.replace('_', "").to_uppercase()
+    }
+
+    fn subscription_message(subscribe: bool, channel: MarketChannel, symbols: &[String], request_id: u64) -> String {
+        let params: Vec<String> = symbols.iter()
+            .map(|symbol| match channel {
+                MarketChannel::OrderBook => format!("depth@{symbol},{DEPTH_LEVELS}"),
+                MarketChannel::Trades => format!("trade@{symbol}"),
+            })
+            .collect();
+        json!({
+            "method": if subscribe { "subscribe" } else { "unsubscribe" },
+            "params": params,
+            "id": request_id.to_string(),
+        })
+        .to_string()
+    }
+
+    fn ping_message() -> Option<String> {
+        Some("ping".to_string())
+    }
+
+    fn parse_message(text: &str) -> Result<Vec<MarketEvent>, AppError> {
+        if text == "pong" {
+            return Ok(vec![MarketEvent::Pong]);
+        }
+
+        let msg: Value = serde_json::from_str(text)
+            .map_err(|e| AppError::ParseError(format!("无法解析XT.com消息: {e}")))?;
+
+        match msg.get("topic").and_then(|t| t.as_str()) {
+            Some("depth") => {
+                let data = &msg["data"];
+                let symbol = data.get("s").and_then(|s| s.as_str())
+                    .ok_or_else(|| AppError::ParseError("XT.com深度推送缺少symbol".to_string()))?;
+                let timestamp = data.get("t").and_then(value_to_i64).unwrap_or(0);
+                Ok(vec![MarketEvent::Book(build_orderbook(
+                    Self::EXCHANGE,
+                    Self::standard_symbol(symbol),
+                    data.get("b"),
+                    data.get("a"),
+                    timestamp,
+                ))])
+            }
+            Some("trade") => {
+                let data = &msg["data"];
+                let symbol = data.get("s").and_then(|s| s.as_str())
+                    .ok_or_else(|| AppError::ParseError("XT.com成交推送缺少symbol".to_string()))?;
+                let trade = (|| {
+                    Some(StandardizedTrade {
+                        symbol: Self::standard_symbol(symbol),
+                        exchange: Self::EXCHANGE_TYPE,
+                        price: value_to_f64(data.get("p")?)?,
+                        quantity: value_to_f64(data.get("a")?)?,
+                        side: if data.get("m")?.as_str()? == "ASK" { TradeSide::Sell } else { TradeSide::Buy },
+                        timestamp: value_to_i64(data.get("t")?)?,
+                        trade_id: data.get("i").map(|i| i.as_str().map(str::to_string).unwrap_or_else(|| i.to_string())).unwrap_or_default(),
+                    })
+                })();
+                trade
+                    .map(|trade| vec![MarketEvent::Trades(vec![trade])])
+                    .ok_or_else(|| AppError::ParseError(format!("XT.com成交推送格式错误: {text}")))
+            }
+            _ => {
+                if msg.get("code").and_then(|c| c.as_i64()).is_some_and(|code| code != 0) {
+                    return Ok(vec![MarketEvent::Error(msg.to_string())]);
+                }
+                Ok(vec![MarketEvent::Other(msg)])
+            }
+        }
+    }
+}
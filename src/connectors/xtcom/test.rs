@@ -0,0 +1,85 @@
+//! XT.com连接器测试模块
+//! 使用录制的推送帧测试协议解析、符号转换和批量订阅
+
+mod tests {
+    use super::super::{XtComConnector, XtComProtocol};
+    use crate::connectors::common::market_stream::{MarketChannel, MarketEvent, MarketStreamProtocol};
+    use crate::core::AppState;
+    use crate::types::{config::ConnectorConfig, market_data::TradeSide};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    /// 录制的XT.com合约深度帧
+    const RECORDED_DEPTH_FRAME: &str = r#"{"topic":"depth","event":"depth@btc_usdt,20","data":{"s":"btc_usdt","id":"340912453","a":[["64012.4","1532"],["64012.9","200"]],"b":[["64011.9","5321"],["64011.5","780"]],"t":1710755362722}}"#;
+
+    /// 录制的XT.com合约成交帧
+    const RECORDED_TRADE_FRAME: &str = r#"{"topic":"trade","event":"trade@btc_usdt","data":{"s":"btc_usdt","i":"7164093429862375424","p":"64011.9","a":"15","m":"ASK","t":1710755363460}}"#;
+
+    /// 录制的XT.com订阅确认
+    const RECORDED_SUBSCRIBE_ACK_FRAME: &str = r#"{"id":"1","code":0,"msg":"success"}"#;
+    const RECORDED_ERROR_FRAME: &str = r#"{"id":"2","code":1,"msg":"invalid params"}"#;
+
+    #[test]
+    fn test_xtcom_parse_recorded_frames() {
+        let events = XtComProtocol::parse_message(RECORDED_DEPTH_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Book(orderbook)] => {
+                assert_eq!(orderbook.symbol, "BTCUSDT");
+                assert_eq!(orderbook.best_bid, 64011.9);
+                assert_eq!(orderbook.best_ask, 64012.4);
+                assert_eq!(orderbook.depth_asks.len(), 2);
+                assert_eq!(orderbook.timestamp, 1710755362722);
+            }
+            other => panic!("应该解析为深度: {other:?}"),
+        }
+
+        let events = XtComProtocol::parse_message(RECORDED_TRADE_FRAME).unwrap();
+        match &events[..] {
+            [MarketEvent::Trades(trades)] => {
+                assert_eq!(trades[0].symbol, "BTCUSDT");
+                assert_eq!(trades[0].side, TradeSide::Sell);
+                assert_eq!(trades[0].quantity, 15.0);
+                assert_eq!(trades[0].trade_id, "7164093429862375424");
+            }
+            other => panic!("应该解析为成交: {other:?}"),
+        }
+
+        assert!(matches!(&XtComProtocol::parse_message("pong").unwrap()[..], [MarketEvent::Pong]));
+        assert!(matches!(&XtComProtocol::parse_message(RECORDED_SUBSCRIBE_ACK_FRAME).unwrap()[..], [MarketEvent::Other(_)]));
+        assert!(matches!(&XtComProtocol::parse_message(RECORDED_ERROR_FRAME).unwrap()[..], [MarketEvent::Error(_)]));
+    }
+
+    #[tokio::test]
+    async fn test_xtcom_symbol_conversion() {
+        let connector = XtComConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        for symbol in ["BTCUSDT", "BTC_USDT", "BTC/USDT", "XTCOM:btc-usdt"] {
+            assert_eq!(connector.handler().to_exchange_symbol(symbol).await.unwrap(), "btc_usdt");
+        }
+        assert_eq!(XtComProtocol::standard_symbol("eth_usdt"), "ETHUSDT");
+    }
+
+    #[test]
+    fn test_xtcom_batched_subscription() {
+        // config.toml中XTCOM的batch_size为5：7个交易对拆成两条请求
+        let connector = XtComConnector::new(ConnectorConfig::default(), 5, Arc::new(AppState::new()));
+        let symbols: Vec<String> = ["btc", "eth", "sol", "xrp", "doge", "ada", "ltc"].iter()
+            .map(|base| format!("{base}_usdt"))
+            .collect();
+
+        let requests = connector.handler().subscription_requests(true, MarketChannel::OrderBook, &symbols, connector.handler().batch_size());
+        assert_eq!(requests.len(), 2);
+
+        let first: Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(first["method"], "subscribe");
+        assert_eq!(first["params"].as_array().unwrap().len(), 5);
+        assert_eq!(first["params"][0], "depth@btc_usdt,20");
+
+        let second: Value = serde_json::from_str(&requests[1]).unwrap();
+        assert_eq!(second["params"].as_array().unwrap().len(), 2);
+        assert_ne!(first["id"], second["id"]);
+
+        let trades: Value = serde_json::from_str(&XtComProtocol::subscription_message(false, MarketChannel::Trades, &symbols[..1], 9)).unwrap();
+        assert_eq!(trades["method"], "unsubscribe");
+        assert_eq!(trades["params"][0], "trade@btc_usdt");
+    }
+}
@@ -7,10 +7,28 @@ use std::sync::atomic::{AtomicU64,Ordering};
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
 
+use crate::arbitrage_graph::ArbitrageGraph;
+use crate::basis::FundingStore;
+use crate::book_guard::RejectionStats;
+use crate::book_store::BookStore;
+use crate::exchange_types::Exchange;
+use crate::opportunity_tracker::OpportunityTracker;
+use crate::types::account::AccountBalance;
+use crate::types::exchange::MarketType;
+use crate::types::market_data::BalanceUpdate;
+
 //
 // CONSTANTS
 //
 
+// Quote asset that pays for buy legs and margins futures legs
+pub const BALANCE_QUOTE_ASSET: &str = "USDT";
+
+// Base asset of a canonical quote-asset symbol, e.g. "BTCUSDT" -> "BTC"
+pub fn base_asset(symbol: &str) -> Option<&str> {
+    symbol.strip_suffix(BALANCE_QUOTE_ASSET).filter(|base| !base.is_empty())
+}
+
 // Fee constants
 pub const PERP_MAKER_FEE: f64 = 0.0001; // 0.01%
 pub const PERP_TAKER_FEE: f64 = 0.0006; // 0.06%
@@ -146,6 +164,31 @@ pub struct AppState {
     
     // Reconnection signals for connections
     pub reconnect_signals: Arc<DashMap<String, bool>>, // Connection ID -> should reconnect flag
+    
+    // Latest orderbook per (exchange, symbol), read by the scanners
+    pub book_store: Arc<BookStore>,
+    
+    // Available balance per (exchange, asset): quote cash caps buy legs and futures margin,
+    // base-asset inventory caps spot sell legs
+    pub available_balances: Arc<DashMap<(Exchange, String), f64>>,
+    
+    // Whether each exchange's trading account is spot or futures, recorded with its balances
+    pub account_markets: Arc<DashMap<Exchange, MarketType>>,
+    
+    // Latest mark price and funding rate per perpetual, read by the basis scanner
+    pub funding_store: Arc<FundingStore>,
+    
+    // Multi-hop arbitrage graph, kept between scans for incremental cycle detection
+    pub arbitrage_graph: Arc<std::sync::Mutex<ArbitrageGraph>>,
+    
+    // Lifecycle of each cross-exchange opportunity, from first seen to close
+    pub opportunity_tracker: Arc<OpportunityTracker>,
+    
+    // Sanity-check rejections per reason and exchange, with staleness streaks
+    pub rejection_stats: Arc<RejectionStats>,
+    
+    // Market data connection ID per exchange, used to reconnect feeds that keep going stale
+    pub market_connections: Arc<DashMap<Exchange, String>>,
 }
 
 impl Default for AppState {
@@ -185,6 +228,28 @@ impl AppState {
             
             // Initialize reconnection signals
             reconnect_signals: Arc::new(DashMap::new()),
+            
+            // Initialize conflated orderbook store
+            book_store: Arc::new(BookStore::new()),
+            
+            // Balances are unknown (unlimited) until an account reports them
+            available_balances: Arc::new(DashMap::new()),
+            account_markets: Arc::new(DashMap::new()),
+            
+            // Filled by perpetual connectors that track funding
+            funding_store: Arc::new(FundingStore::new()),
+            
+            // Loads all books on the first multi-hop scan
+            arbitrage_graph: Arc::new(std::sync::Mutex::new(ArbitrageGraph::new())),
+            
+            // No opportunities are open at startup
+            opportunity_tracker: Arc::new(OpportunityTracker::new()),
+            
+            // No rejections recorded yet
+            rejection_stats: Arc::new(RejectionStats::new()),
+            
+            // Registered by the connectors that honour reconnect signals
+            market_connections: Arc::new(DashMap::new()),
         }
     }
     
@@ -277,6 +342,16 @@ impl AppState {
         self.reconnect_signals.insert(connection_id.to_string(), false);
     }
     
+    // Register the connection that carries an exchange's order books
+    pub fn register_market_connection(&self, exchange: Exchange, connection_id: &str) {
+        self.market_connections.insert(exchange, connection_id.to_string());
+    }
+    
+    // Connection ID of an exchange's order book feed, if its connector registered one
+    pub fn market_connection(&self, exchange: Exchange) -> Option<String> {
+        self.market_connections.get(&exchange).map(|entry| entry.value().clone())
+    }
+    
     // Method to get price update count and reset counter
     pub fn get_and_reset_price_update_count(&self) -> u64 {
         self.price_updates.swap(0, Ordering::Relaxed)
@@ -312,6 +387,43 @@ impl AppState {
         self.cross_exchange_checks.fetch_add(count, Ordering::Relaxed);
     }
     
+    // Record the available balance of one asset on an exchange
+    pub fn set_available_balance(&self, exchange: Exchange, asset: &str, balance: f64) {
+        self.available_balances.insert((exchange, asset.to_ascii_uppercase()), balance.max(0.0));
+    }
+    
+    // Available balance of one asset on an exchange, None if it has not been reported
+    pub fn available_balance(&self, exchange: Exchange, asset: &str) -> Option<f64> {
+        self.available_balances
+            .get(&(exchange, asset.to_ascii_uppercase()))
+            .map(|balance| *balance)
+    }
+    
+    // Record whether an exchange's trading account is spot or futures
+    pub fn set_account_market(&self, exchange: Exchange, market_type: MarketType) {
+        self.account_markets.insert(exchange, market_type);
+    }
+    
+    // Market type of an exchange's trading account, None if no account has been synced
+    pub fn account_market(&self, exchange: Exchange) -> Option<MarketType> {
+        self.account_markets.get(&exchange).map(|market_type| *market_type)
+    }
+    
+    // Record every asset from an account query; the quote asset falls back to the account-wide figure
+    pub fn apply_account_balance(&self, exchange: Exchange, account: &AccountBalance) {
+        for (asset, balance) in &account.balances {
+            self.set_available_balance(exchange, asset, balance.available);
+        }
+        if !account.balances.contains_key(BALANCE_QUOTE_ASSET) {
+            self.set_available_balance(exchange, BALANCE_QUOTE_ASSET, account.available);
+        }
+    }
+    
+    // Record an asset balance pushed by a user data stream
+    pub fn apply_balance_update(&self, update: &BalanceUpdate) {
+        self.set_available_balance(update.exchange.into(), &update.asset, update.free);
+    }
+    
     // Increment the profitable opportunities counter
     pub fn increment_profitable_opportunities(&self, count: u64) {
         self.profitable_opportunities.fetch_add(count, Ordering::Relaxed);
@@ -1,29 +1,35 @@
-// cross_exchange.rs - Optimized cross-exchange arbitrage calculation logic with enhanced scaling detection
-// and multi-hop arbitrage support
+// cross_exchange.rs - Optimized cross-exchange arbitrage calculation logic with contract multiplier
+// normalization, scaling anomaly detection and multi-hop arbitrage support
 
 use crate::core::*;
-use crate::exchange_types::{Exchange, ExchangeFees, CrossExchangeArb, StandardOrderBook, MultiHopArbitragePath};
+use crate::book_store::normalize_book_symbol;
+use crate::exchange_types::{Exchange, ExchangeFees, CrossExchangeArb, StandardOrderBook, MultiHopArbitragePath, BasisArb};
 use crate::token_lists::TARGET_TOKENS;
 use crate::config::get_config;
 use log::{info, warn};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 use lazy_static::lazy_static;
 use tokio::sync::{Mutex, RwLock};
 use csv::Writer;
 use crate::utils::ensure_exchange_prefix;
 use crate::error_handling::init_error_tracker;
+use crate::symbol_mapper::contract_multipliers;
+use crate::depth_sizing::{book_levels, solve_optimal_size, SizingLimits};
+use crate::opportunity_tracker::{OpportunityLifecycle, OpportunityTracker};
+use crate::book_guard::{check_evaluation, RejectReason, Rejection};
+use crate::types::events::CloseReason;
 use chrono::Utc;
 
 // Add missing MIN_PROFIT_THRESHOLD constant
 pub const MIN_PROFIT_THRESHOLD: f64 = 0.10; // 0.10% minimum profit threshold
 
+// Learned factors closer to 1.0 than this are treated as ordinary price differences
+const SCALING_ANOMALY_MIN_DEVIATION: f64 = 0.5;
+
 // Global buffer for cross-exchange opportunities
 lazy_static! {
     static ref CROSS_EX_BUFFER: Mutex<Vec<CrossExchangeArb>> = Mutex::new(Vec::new());
     
-    // Track recently detected opportunities to avoid duplicates
-    static ref RECENT_OPPORTUNITIES: Mutex<HashSet<String>> = Mutex::new(HashSet::with_capacity(10000));
-    
     // Track suspicious symbols with too many opportunities
     static ref SUSPICIOUS_SYMBOLS: Mutex<HashMap<String, u32>> = Mutex::new(HashMap::new());
     
@@ -34,9 +40,13 @@ lazy_static! {
         
     // Buffer for multi-hop arbitrage opportunities
     static ref MULTI_HOP_BUFFER: Mutex<Vec<MultiHopArbitragePath>> = Mutex::new(Vec::new());
+    
+    // Buffer for spot/perpetual basis opportunities
+    static ref BASIS_BUFFER: Mutex<Vec<BasisArb>> = Mutex::new(Vec::new());
 }
 
-// Add this structure for tracking scaling relationships
+// Tracks the ratio of multiplier-normalized prices between two exchanges.
+// A consistent ratio far from 1.0 means a listing's contract multiplier is missing or wrong.
 #[derive(Debug, Clone)]
 struct ScalingRelationship {
     symbol: String,
@@ -183,49 +193,32 @@ async fn get_learned_scaling_factor(symbol: &str, from_exchange: Exchange, to_ex
     None
 }
 
-/// Enhanced detection of scaling differences between exchanges
-async fn detect_scaling_difference(
+/// Detect a scaling anomaly between two exchanges.
+///
+/// Contract multipliers are applied deterministically by `normalize_price`, so the learned
+/// ratio of normalized prices should settle near 1.0. It is only used to catch listings whose
+/// multiplier is missing or wrong: returns the learned factor when it is far from 1.0 and the
+/// current ratio still matches it.
+async fn detect_scaling_anomaly(
     symbol: &str,
     buy_exchange: Exchange,
     sell_exchange: Exchange,
-    buy_price: f64,
-    sell_price: f64
+    normalized_buy_price: f64,
+    normalized_sell_price: f64
 ) -> Option<f64> {
-    // First check if we have a learned scaling factor
-    if let Some(learned_factor) = get_learned_scaling_factor(symbol, buy_exchange, sell_exchange).await {
-        // Check if the current ratio is close to our learned factor
-        let current_ratio = sell_price / buy_price;
-        if (current_ratio / learned_factor - 1.0).abs() < 0.05 {
-            // The current ratio matches our learned factor
-            return Some(learned_factor);
-        }
-    }
-    
-    // If we don't have a learned factor or it doesn't match, check common patterns
-    let ratio = sell_price / buy_price;
-    
-    // Check if it's close to a power of 10 (most common case)
-    let log10_ratio = ratio.log10().abs();
-    let nearest_power = log10_ratio.round();
+    let learned_factor = get_learned_scaling_factor(symbol, buy_exchange, sell_exchange).await?;
     
-    if (log10_ratio - nearest_power).abs() < 0.05 {
-        // It's a power of 10 scaling factor
-        return Some(10.0_f64.powf(nearest_power));
+    // Normalized prices agree, nothing to report
+    if (learned_factor - 1.0).abs() < SCALING_ANOMALY_MIN_DEVIATION {
+        return None;
     }
     
-    // Check if it's close to common scaling factors (e.g., 1000/3, 1000/4, etc.)
-    let common_divisors = [2.0, 3.0, 4.0, 5.0, 8.0, 16.0, 32.0, 64.0, 100.0, 1000.0];
-    
-    for &power in &[1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0] {
-        for &divisor in &common_divisors {
-            let check_ratio = power / divisor;
-            if (ratio / check_ratio - 1.0).abs() < 0.05 {
-                return Some(check_ratio);
-            }
-        }
+    // Only flag the opportunity if the current ratio is explained by the learned factor
+    let current_ratio = normalized_sell_price / normalized_buy_price;
+    if (current_ratio / learned_factor - 1.0).abs() < 0.05 {
+        return Some(learned_factor);
     }
     
-    // Not a recognized scaling pattern
     None
 }
 
@@ -278,25 +271,10 @@ fn extract_base_token(symbol: &str) -> &str {
     symbol
 }
 
-// Helper function to normalize price between exchanges
-async fn normalize_price(exchange: Exchange, symbol: &str, price: f64) -> f64 {
-    let base_token = extract_base_token(symbol);
-    
-    // Get scaling factor from learned relationships
-    if let Some(scaling_factor) = get_learned_scaling_factor(symbol, exchange, Exchange::Phemex).await {
-        return price * scaling_factor;
-    }
-    
-    // Use configured exchange-specific scaling if available
-    let config = get_config();
-    if let Some(exchange_config) = config.get_exchange_config(&exchange) {
-        if let Some(token_config) = config.get_token_config(base_token) {
-            return price * token_config.slippage_factor;
-        }
-    }
-    
-    // Default to 1.0 (no scaling)
-    price
+// Convert a listing price to the price of one unit of the canonical base asset,
+// using the explicit contract multiplier for that listing (1.0 if it has none)
+fn normalize_price(exchange: Exchange, symbol: &str, price: f64) -> f64 {
+    contract_multipliers().normalize_price(exchange, symbol, price)
 }
 
 /// Buffer a profitable cross-exchange opportunity.
@@ -311,6 +289,12 @@ pub async fn buffer_multi_hop_opportunity(arb: MultiHopArbitragePath) {
     buf.push(arb);
 }
 
+/// Buffer a spot/perpetual basis opportunity
+pub async fn buffer_basis_opportunity(arb: BasisArb) {
+    let mut buf = BASIS_BUFFER.lock().await;
+    buf.push(arb);
+}
+
 /// Flush the cross-exchange opportunity buffer to a CSV file.
 pub async fn flush_cross_ex_buffer(filename: &str) -> Result<(), AppError> {
     let mut buf = CROSS_EX_BUFFER.lock().await;
@@ -333,7 +317,8 @@ pub async fn flush_cross_ex_buffer(filename: &str) -> Result<(), AppError> {
     if metadata.len() == 0 {
         writer.write_record([
             "timestamp", "symbol", "buy_exchange", "sell_exchange",
-            "buy_price", "sell_price", "profit_pct", "net_profit_pct"
+            "buy_price", "sell_price", "profit_pct", "net_profit_pct",
+            "optimal_size", "expected_pnl"
         ])?;
     }
     
@@ -347,6 +332,8 @@ pub async fn flush_cross_ex_buffer(filename: &str) -> Result<(), AppError> {
             &format!("{:.6}", record.sell_price),
             &format!("{:.4}", record.profit_pct),
             &format!("{:.4}", record.net_profit_pct),
+            &format!("{:.6}", record.optimal_size),
+            &format!("{:.4}", record.expected_pnl),
         ])?;
     }
     
@@ -354,12 +341,72 @@ pub async fn flush_cross_ex_buffer(filename: &str) -> Result<(), AppError> {
     info!("Flushed {} cross-exchange opportunities to {}", buf.len(), filename);
     buf.clear();
     
-    // Also clean out stale tracked opportunities
-    let mut recent_opps = RECENT_OPPORTUNITIES.lock().await;
-    if recent_opps.len() > 2000 {
-        info!("Clearing opportunity tracking cache of {} items", recent_opps.len());
-        recent_opps.clear();
+    Ok(())
+}
+
+/// Close opportunities that no scanner re-observed within the stale window, then write all
+/// closed lifecycles as CSV rows to `csv_filename` and JSON lines to `json_filename`.
+pub async fn flush_opportunity_lifecycles(
+    tracker: &OpportunityTracker,
+    csv_filename: &str,
+    json_filename: &str,
+) -> Result<(), AppError> {
+    let stale_after = std::time::Duration::from_millis(get_config().arbitrage.opportunity_stale_ms);
+    tracker.close_stale(stale_after);
+    
+    let records: Vec<OpportunityLifecycle> = tracker.take_closed();
+    if records.is_empty() {
+        return Ok(());
+    }
+    
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(csv_filename).await?;
+
+    let mut writer = Writer::from_writer(file.into_std().await);
+
+    // If the file is empty, write a header
+    let metadata = tokio::fs::metadata(csv_filename).await?;
+    if metadata.len() == 0 {
+        writer.write_record([
+            "first_seen", "last_seen", "closed_at", "symbol", "buy_exchange", "sell_exchange",
+            "duration_ms", "updates", "peak_net_profit_pct", "avg_net_profit_pct",
+            "last_net_profit_pct", "peak_optimal_size", "peak_expected_pnl", "close_reason"
+        ])?;
+    }
+    
+    let mut json_lines = String::new();
+    for record in &records {
+        writer.write_record([
+            &format!("{}", record.first_seen),
+            &format!("{}", record.last_seen),
+            &format!("{}", record.closed_at),
+            &record.symbol,
+            &format!("{:?}", record.buy_exchange),
+            &format!("{:?}", record.sell_exchange),
+            &format!("{}", record.duration_ms),
+            &format!("{}", record.updates),
+            &format!("{:.4}", record.peak_net_profit_pct),
+            &format!("{:.4}", record.avg_net_profit_pct),
+            &format!("{:.4}", record.last_net_profit_pct),
+            &format!("{:.6}", record.peak_optimal_size),
+            &format!("{:.4}", record.peak_expected_pnl),
+            &record.close_reason.to_string(),
+        ])?;
+        json_lines.push_str(&serde_json::to_string(record)?);
+        json_lines.push('\n');
     }
+    writer.flush()?;
+    
+    use tokio::io::AsyncWriteExt;
+    let mut json_file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(json_filename).await?;
+    json_file.write_all(json_lines.as_bytes()).await?;
+    
+    info!("Flushed {} opportunity lifecycles to {} and {}", records.len(), csv_filename, json_filename);
     
     Ok(())
 }
@@ -386,7 +433,8 @@ pub async fn flush_multi_hop_buffer(filename: &str) -> Result<(), AppError> {
     if metadata.len() == 0 {
         writer.write_record([
             "timestamp", "path_id", "hop_count", "symbols", "exchanges", 
-            "profit_pct", "net_profit_pct", "fees_pct", "slippage_pct"
+            "profit_pct", "net_profit_pct", "fees_pct", "slippage_pct",
+            "assets", "capacity", "expected_profit"
         ])?;
     }
     
@@ -401,6 +449,9 @@ pub async fn flush_multi_hop_buffer(filename: &str) -> Result<(), AppError> {
             &format!("{:.4}", record.net_profit_pct),
             &format!("{:.4}", record.total_fees_pct),
             &format!("{:.4}", record.total_slippage_pct),
+            &record.asset_path.join(":"),
+            &format!("{:.6}", record.capacity),
+            &format!("{:.6}", record.expected_profit),
         ])?;
     }
     
@@ -411,26 +462,112 @@ pub async fn flush_multi_hop_buffer(filename: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Flush spot/perpetual basis opportunities to CSV file
+pub async fn flush_basis_buffer(filename: &str) -> Result<(), AppError> {
+    let mut buf = BASIS_BUFFER.lock().await;
+    if buf.is_empty() {
+        return Ok(());
+    }
+    
+    // Sort by annualized net carry
+    buf.sort_by(|a, b| b.annualized_net_pct.partial_cmp(&a.annualized_net_pct).unwrap_or(std::cmp::Ordering::Equal));
+    
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename).await?;
+
+    let mut writer = Writer::from_writer(file.into_std().await);
+
+    // If the file is empty, write a header
+    let metadata = tokio::fs::metadata(filename).await?;
+    if metadata.len() == 0 {
+        writer.write_record([
+            "timestamp", "symbol", "spot_exchange", "perp_exchange", "direction",
+            "spot_price", "perp_price", "basis_pct", "funding_rate", "funding_events",
+            "expected_funding_pct", "fees_pct", "net_carry_pct", "annualized_net_pct",
+            "holding_hours", "exit_threshold_pct"
+        ])?;
+    }
+    
+    for record in buf.iter() {
+        writer.write_record([
+            &format!("{}", record.timestamp),
+            &record.symbol,
+            &format!("{:?}", record.spot_exchange),
+            &format!("{:?}", record.perp_exchange),
+            &record.direction.to_string(),
+            &format!("{:.6}", record.spot_price),
+            &format!("{:.6}", record.perp_price),
+            &format!("{:.4}", record.basis_pct),
+            &format!("{:.6}", record.funding_rate),
+            &format!("{}", record.funding_events),
+            &format!("{:.4}", record.expected_funding_pct),
+            &format!("{:.4}", record.total_fees_pct),
+            &format!("{:.4}", record.net_carry_pct),
+            &format!("{:.2}", record.annualized_net_pct),
+            &format!("{:.1}", record.holding_hours),
+            &format!("{:.4}", record.exit_threshold_pct),
+        ])?;
+    }
+    
+    writer.flush()?;
+    info!("Flushed {} basis opportunities to {}", buf.len(), filename);
+    buf.clear();
+    
+    Ok(())
+}
+
 /// Create a unique key for an arbitrage opportunity
 #[inline(always)]
-fn get_opportunity_key(symbol: &str, buy_exchange: &Exchange, sell_exchange: &Exchange) -> String {
+pub fn get_opportunity_key(symbol: &str, buy_exchange: &Exchange, sell_exchange: &Exchange) -> String {
     format!("{symbol}:{buy_exchange:?}:{sell_exchange:?}")
 }
 
-/// Check if this opportunity was recently detected (to avoid duplicates)
-#[inline(always)]
-async fn is_new_opportunity(symbol: &str, buy_exchange: Exchange, sell_exchange: Exchange) -> bool {
-    let key = get_opportunity_key(symbol, &buy_exchange, &sell_exchange);
+/// Record the outcome of evaluating one buy/sell pair in the opportunity tracker.
+/// Returns the opportunity only when it newly opened, so each lifecycle is reported once.
+///
+/// `buy_book_ts` and `sell_book_ts` are the millisecond timestamps of the data each leg was
+/// priced from. Rejections are counted per reason and exchange, and an exchange whose books
+/// keep failing the age check has its market data connection told to reconnect.
+async fn track_evaluation(
+    app_state: &AppState,
+    symbol: &str,
+    buy_exchange: Exchange,
+    sell_exchange: Exchange,
+    buy_book_ts: i64,
+    sell_book_ts: i64,
+    evaluated: Option<CrossExchangeArb>,
+) -> Option<CrossExchangeArb> {
+    let tracker = &app_state.opportunity_tracker;
+    let Some(opportunity) = evaluated else {
+        tracker.close(symbol, buy_exchange, sell_exchange, CloseReason::SpreadCollapsed);
+        return None;
+    };
+    
+    let checked = passes_sanity_checks(&opportunity, buy_book_ts, sell_book_ts).await;
+    let reconnect_after = get_config().arbitrage.stale_rejections_before_reconnect;
+    for exchange in app_state.rejection_stats.record(buy_exchange, sell_exchange, checked.as_ref().map(|_| ()), reconnect_after) {
+        match app_state.market_connection(exchange) {
+            Some(connection_id) => {
+                warn!("{exchange} books rejected as stale {reconnect_after} times in a row, signalling reconnect of {connection_id}");
+                app_state.signal_reconnect(&connection_id);
+            }
+            None => warn!("{exchange} books rejected as stale {reconnect_after} times in a row, but no market connection is registered"),
+        }
+    }
     
-    let mut recent_opps = RECENT_OPPORTUNITIES.lock().await;
-    if recent_opps.contains(&key) {
-        // We've already seen this opportunity recently
-        return false;
+    if let Err(rejection) = checked {
+        let reason = match rejection.reason {
+            RejectReason::BelowThreshold => CloseReason::SpreadCollapsed,
+            RejectReason::BookStale | RejectReason::BookSkew => CloseReason::BookStale,
+            _ => CloseReason::SanityCheckFailed,
+        };
+        tracker.close(symbol, buy_exchange, sell_exchange, reason);
+        return None;
     }
     
-    // Add to recent opportunities
-    recent_opps.insert(key);    
-    true
+    tracker.observe(&opportunity).then_some(opportunity)
 }
 
 /// Check if prices are reasonable and not likely to be data errors
@@ -464,31 +601,34 @@ async fn is_symbol_suspicious(symbol: &str) -> bool {
 }
 
 /// Check if an arbitrage opportunity passes sanity checks
+/// Prices are expected to be normalized with `normalize_price`
+///
+/// Both books must be younger than their exchange's `max_book_age_ms` and within
+/// `max_book_skew_ms` of each other, otherwise a frozen feed shows up as a phantom spread.
+/// Returns the reason and the exchanges blamed for a rejection.
 #[inline(always)]
 async fn passes_sanity_checks(
-    symbol: &str,
-    buy_exchange: Exchange,
-    sell_exchange: Exchange,
-    buy_price: f64,
-    sell_price: f64,
-    net_profit_pct: f64
-) -> bool {
-    let config = get_config();
-    
-    // Skip complex checks for obvious non-profitable cases
-    if net_profit_pct < config.arbitrage.min_profit_threshold_pct || buy_price <= 0.0 || sell_price <= 0.0 {
-        return false;
-    }
-    
-    // Update exchange scaling knowledge (for future use)
+    opportunity: &CrossExchangeArb,
+    buy_book_ts: i64,
+    sell_book_ts: i64,
+) -> Result<(), Rejection> {
+    let symbol = opportunity.symbol.as_str();
+    let (buy_exchange, sell_exchange) = (opportunity.buy_exchange, opportunity.sell_exchange);
+    let (buy_price, sell_price) = (opportunity.buy_price, opportunity.sell_price);
+    let net_profit_pct = opportunity.net_profit_pct;
+    
+    // Invalid prices, stale or skewed books and sub-threshold spreads, in that order, so a
+    // frozen feed is counted as stale even while its spread is quiet
+    check_evaluation(&get_config().arbitrage, opportunity, buy_book_ts, sell_book_ts, Utc::now().timestamp_millis())?;
+    
+    // Feed the anomaly detector with normalized prices
     update_exchange_scaling(symbol, buy_exchange, sell_exchange, buy_price, sell_price).await;
     
-    // Check for scaling differences between exchanges
-    if let Some(scale_factor) = detect_scaling_difference(symbol, buy_exchange, sell_exchange, buy_price, sell_price).await {
-        // This is likely just a scaling difference, not a real opportunity
-        info!("Ignoring exchange scaling difference for {}: ratio {:.4} (detected factor {:.4})", 
-              symbol, sell_price / buy_price, scale_factor);
-        return false;
+    // A persistent scaling factor after normalization points at a missing contract multiplier
+    if let Some(scale_factor) = detect_scaling_anomaly(symbol, buy_exchange, sell_exchange, buy_price, sell_price).await {
+        warn!("Scaling anomaly for {} between {} and {}: ratio {:.4} (learned factor {:.4}), check its contract multiplier",
+              symbol, buy_exchange, sell_exchange, sell_price / buy_price, scale_factor);
+        return Err(Rejection::pair(RejectReason::ScalingAnomaly, buy_exchange, sell_exchange));
     }
     
     // Get token-specific validation parameters
@@ -499,18 +639,25 @@ async fn passes_sanity_checks(
         // If we're still seeing suspiciously high profits after normalization,
         // it might be a data error
         info!("Rejecting opportunity with abnormally high profit: {symbol} with profit {net_profit_pct:.2}%");
-        return false;
+        return Err(Rejection::pair(RejectReason::ProfitTooHigh, buy_exchange, sell_exchange));
     }
 
     // Check if this symbol is producing too many opportunities
     if is_symbol_suspicious(symbol).await {
-        return false;
+        return Err(Rejection::pair(RejectReason::SuspiciousSymbol, buy_exchange, sell_exchange));
     }
     
-    true
+    Ok(())
 }
 
 /// Compute cross-exchange profit with slippage
+///
+/// `buy_price` and `sell_price` must already be normalized with `normalize_price`
+/// so that both legs are quoted per unit of the canonical base asset.
+///
+/// When both books carry depth, the asks of the buy venue and the bids of the sell venue
+/// are walked together to find the size with the highest absolute net profit (see
+/// `depth_sizing`). Otherwise the configured default trade size is evaluated.
 #[inline(always)]
 pub async fn compute_cross_exchange_profit_with_slippage(
     symbol: &str,
@@ -521,6 +668,7 @@ pub async fn compute_cross_exchange_profit_with_slippage(
     buy_orderbook: Option<&StandardOrderBook>,
     sell_orderbook: Option<&StandardOrderBook>,
     _exchange_fees: &HashMap<Exchange, ExchangeFees>,
+    limits: &SizingLimits,
 ) -> Option<CrossExchangeArb> {
     let config = get_config();
     
@@ -529,18 +677,8 @@ pub async fn compute_cross_exchange_profit_with_slippage(
         return None;
     }
     
-    // First check if this is just a scaling difference
-    if detect_scaling_difference(symbol, buy_exchange, sell_exchange, buy_price, sell_price).await.is_some() {
-        // It's a scaling difference, not a real opportunity
-        return None;
-    }
-    
-    // Normalize prices to account for exchange-specific scaling
-    let normalized_buy_price = normalize_price(buy_exchange, symbol, buy_price).await;
-    let normalized_sell_price = normalize_price(sell_exchange, symbol, sell_price).await;
-    
-    // Skip if no profit after normalization
-    if normalized_sell_price <= normalized_buy_price {
+    // Skip if no gross profit
+    if sell_price <= buy_price {
         return None;
     }
     
@@ -580,6 +718,41 @@ pub async fn compute_cross_exchange_profit_with_slippage(
     // Apply fees on both opening and closing positions (2x per exchange)
     let total_fees_pct = (buy_fee * 2.0 + sell_fee * 2.0) * 100.0;
     
+    // Get token-specific validation parameters
+    let (max_reasonable_profit, _) = get_token_validation_params(symbol);
+    
+    // Depth-optimal sizing when both books carry depth
+    if let (Some(buy_book), Some(sell_book)) = (buy_orderbook, sell_orderbook) {
+        if !buy_book.depth_asks.is_empty() && !sell_book.depth_bids.is_empty() {
+            let (asks, bids) = {
+                let multipliers = contract_multipliers();
+                (book_levels(buy_book, &multipliers, true), book_levels(sell_book, &multipliers, false))
+            };
+            
+            let sizing = solve_optimal_size(&asks, &bids, buy_fee * 2.0, sell_fee * 2.0, limits)?;
+            if sizing.net_profit_pct < config.arbitrage.min_profit_threshold_pct || sizing.net_profit_pct > max_reasonable_profit {
+                return None;
+            }
+            
+            let gross_pct = (sizing.avg_sell_price / sizing.avg_buy_price - 1.0) * 100.0;
+            
+            return Some(CrossExchangeArb {
+                symbol: symbol.to_string(),
+                buy_exchange,
+                sell_exchange,
+                buy_price,  // Normalized price per unit of the base asset
+                sell_price, // Normalized price per unit of the base asset
+                timestamp: chrono::Utc::now().timestamp_millis(),
+                profit_pct: gross_pct,
+                net_profit_pct: sizing.net_profit_pct,
+                total_fees_pct: gross_pct - sizing.net_profit_pct, // Fees plus depth slippage
+                optimal_size: sizing.size,
+                expected_pnl: sizing.net_pnl,
+                profit_curve: sizing.curve,
+            });
+        }
+    }
+    
     // Define trade size - use config value
     let base_trade_size = config.arbitrage.default_trade_size_usd;
     
@@ -591,13 +764,13 @@ pub async fn compute_cross_exchange_profit_with_slippage(
                 true, // is_buy
                 symbol
             );
-            let effective_price = normalized_buy_price * (1.0 + slippage);
+            let effective_price = buy_price * (1.0 + slippage);
             (effective_price, slippage, has_liquidity)
         },
         None => {
             // Apply default slippage estimate from config
             let slippage_pct = config.arbitrage.default_slippage_pct;
-            (normalized_buy_price * (1.0 + slippage_pct), slippage_pct, false)
+            (buy_price * (1.0 + slippage_pct), slippage_pct, false)
         }
     };
     
@@ -608,17 +781,17 @@ pub async fn compute_cross_exchange_profit_with_slippage(
                 false, // is_sell
                 symbol
             );
-            let effective_price = normalized_sell_price * (1.0 - slippage);
+            let effective_price = sell_price * (1.0 - slippage);
             (effective_price, slippage, has_liquidity)
         },
         None => {
             // Apply default slippage estimate from config
             let slippage_pct = config.arbitrage.default_slippage_pct;
-            (normalized_sell_price * (1.0 - slippage_pct), slippage_pct, false)
+            (sell_price * (1.0 - slippage_pct), slippage_pct, false)
         }
     };
     
-    // Skip if no profit after normalization, slippage, and fees
+    // Skip if no profit after slippage and fees
     if effective_sell_price <= effective_buy_price {
         return None;
     }
@@ -641,9 +814,6 @@ pub async fn compute_cross_exchange_profit_with_slippage(
     // Calculate net profit after fees and slippage
     let net_pct = gross_pct - total_fees_pct - total_slippage_pct;
     
-    // Get token-specific validation parameters
-    let (max_reasonable_profit, _) = get_token_validation_params(symbol);
-    
     // Only return if profitable at least MIN_PROFIT_THRESHOLD AND not suspiciously high
     if net_pct >= config.arbitrage.min_profit_threshold_pct && net_pct <= max_reasonable_profit {
         let timestamp = chrono::Utc::now().timestamp_millis();
@@ -652,12 +822,15 @@ pub async fn compute_cross_exchange_profit_with_slippage(
             symbol: symbol.to_string(),
             buy_exchange,
             sell_exchange,
-            buy_price,  // Store original price for reference
-            sell_price, // Store original price for reference
+            buy_price,  // Normalized price per unit of the base asset
+            sell_price, // Normalized price per unit of the base asset
             timestamp,
             profit_pct: gross_pct,
             net_profit_pct: net_pct,
             total_fees_pct: total_fees_pct + total_slippage_pct, // Include slippage in fees
+            optimal_size: base_trade_size / effective_buy_price,
+            expected_pnl: base_trade_size * net_pct / 100.0,
+            profit_curve: Vec::new(),
         });
     }
     
@@ -665,6 +838,7 @@ pub async fn compute_cross_exchange_profit_with_slippage(
 }
 
 /// Compute potential profit for a cross-exchange arbitrage opportunity (Simple version)
+/// Prices must already be normalized with `normalize_price`
 #[inline(always)]
 pub async fn compute_cross_exchange_profit(
     symbol: &str,
@@ -680,12 +854,8 @@ pub async fn compute_cross_exchange_profit(
         return None;
     }
     
-    // NEW: Normalize prices to account for exchange-specific scaling
-    let normalized_buy_price = normalize_price(buy_exchange, symbol, buy_price).await;
-    let normalized_sell_price = normalize_price(sell_exchange, symbol, sell_price).await;
-    
-    // Skip if no profit after normalization
-    if normalized_sell_price <= normalized_buy_price {
+    // Skip if no gross profit
+    if sell_price <= buy_price {
         return None;
     }
     
@@ -723,7 +893,7 @@ pub async fn compute_cross_exchange_profit(
     }; 
     
     // Calculate gross profit percentage (before fees)
-    let gross_pct = (normalized_sell_price / normalized_buy_price - 1.0) * 100.0;
+    let gross_pct = (sell_price / buy_price - 1.0) * 100.0;
     
     // Calculate fees as percentage, accounting for both opening and closing positions
     let total_fees_pct = (buy_fee * 2.0 + sell_fee * 2.0) * 100.0;
@@ -739,345 +909,48 @@ pub async fn compute_cross_exchange_profit(
             symbol: symbol.to_string(),
             buy_exchange,
             sell_exchange,
-            buy_price,  // Store original price for reference
-            sell_price, // Store original price for reference
+            buy_price,  // Normalized price per unit of the base asset
+            sell_price, // Normalized price per unit of the base asset
             timestamp,
             profit_pct: gross_pct,
             net_profit_pct: net_pct,
             total_fees_pct,
+            optimal_size: config.arbitrage.default_trade_size_usd / buy_price,
+            expected_pnl: config.arbitrage.default_trade_size_usd * net_pct / 100.0,
+            profit_curve: Vec::new(),
         });
     }
     
     None
 }
 
-/// Find multi-hop arbitrage opportunities using graph-based algorithm
+/// Find multi-hop arbitrage opportunities as negative cycles in the log-price graph.
+/// The graph is kept in `AppState` and only edges touched by updated books are re-relaxed.
 pub fn find_multi_hop_arbitrage_opportunities(
     app_state: &AppState,
     exchange_fees: &HashMap<Exchange, ExchangeFees>,
 ) -> Vec<MultiHopArbitragePath> {
     let config = get_config();
-    let mut opportunities = Vec::new();
     
     // Skip if multi-hop arbitrage is disabled
     if !config.features.enable_multi_hop_arbitrage {
-        return opportunities;
-    }
-    
-    // Get the maximum path length from config
-    let max_path_length = config.arbitrage.max_path_length;
-    
-    // Get default trade size
-    let default_trade_size = config.arbitrage.default_trade_size_usd;
-    
-    // Get the minimum profit threshold
-    let min_profit_threshold = config.arbitrage.min_profit_threshold_pct;
-    
-    // Build a graph representation for path finding
-    let (graph, symbol_indices, exchange_indices) = build_arbitrage_graph(app_state);
-    
-    // Reverse mappings
-    let reverse_symbol_indices: HashMap<usize, String> = symbol_indices.iter()
-        .map(|(k, v)| (*v, k.clone()))
-        .collect();
-        
-    let reverse_exchange_indices: HashMap<usize, Exchange> = exchange_indices.iter()
-        .map(|(k, v)| (*v, *k))
-        .collect();
-    
-    // For each starting currency, find profitable paths
-    for (start_symbol, start_idx) in &symbol_indices {
-        // Skip synthetic currencies or ones without price data
-        if start_symbol.contains(':') || start_symbol == "USDT" {
-            continue; // Skip prefixed symbols and USDT (base currency)
-        }
-        
-        // Use modified Bellman-Ford or BFS for each source
-        let profitable_paths = find_profitable_paths(
-            &graph,
-            *start_idx,
-            max_path_length,
-            &reverse_symbol_indices,
-            &reverse_exchange_indices,
-            app_state,
-            exchange_fees,
-            default_trade_size,
-            min_profit_threshold,
-        );
-        
-        // Add to opportunities
-        opportunities.extend(profitable_paths);
+        return Vec::new();
     }
     
-    // Sort by net profit (descending)
-    opportunities.sort_by(|a, b| {
-        b.net_profit_pct.partial_cmp(&a.net_profit_pct).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    let mut graph = app_state.arbitrage_graph.lock().unwrap_or_else(|e| e.into_inner());
+    graph.refresh(
+        &app_state.book_store,
+        exchange_fees,
+        config.arbitrage.transfer_cost_pct / 100.0,
+    );
     
     // Only keep top 100 opportunities to avoid memory issues
-    if opportunities.len() > 100 {
-        opportunities.truncate(100);
-    }
+    let mut opportunities = graph.find_cycles(config.arbitrage.max_path_length, 100);
+    opportunities.retain(|path| path.net_profit_pct >= config.arbitrage.min_profit_threshold_pct);
     
     opportunities
 }
 
-/// Build a weighted directed graph for arbitrage path finding
-fn build_arbitrage_graph(
-    app_state: &AppState,
-) -> (
-    Vec<Vec<(usize, usize, f64)>>, // graph[from_node] = [(to_node, exchange_idx, rate)]
-    HashMap<String, usize>,        // symbol_indices
-    HashMap<Exchange, usize>,      // exchange_indices
-) {
-    let mut symbol_indices = HashMap::new();
-    let mut exchange_indices = HashMap::new();
-    
-    // First pass: collect all unique symbols and exchanges
-    for entry in app_state.price_data.iter() {
-        let full_symbol = entry.key();
-        
-        // Extract exchange and symbol
-        if let Some((exchange_str, symbol)) = full_symbol.split_once(':') {
-            // Parse exchange
-            if let Ok(exchange) = exchange_str.parse::<Exchange>() {
-                let next_idx = exchange_indices.len();
-                let exchange_idx = *exchange_indices
-                    .entry(exchange)
-                    .or_insert(next_idx);
-                
-                // Extract base and quote tokens
-                if let Some((base, quote)) = extract_trading_pair(symbol) {
-                    // Add tokens to symbol indices
-                    let next_idx = symbol_indices.len();
-                    let base_idx = *symbol_indices
-                        .entry(base.to_string())
-                        .or_insert(next_idx);
-                        
-                    let next_idx = symbol_indices.len();
-                    let quote_idx = *symbol_indices
-                        .entry(quote.to_string())
-                        .or_insert(next_idx);
-                }
-            }
-        }
-    }
-    
-    // Initialize graph with empty adjacency lists
-    let mut graph = vec![Vec::new(); symbol_indices.len()];
-    
-    // Second pass: build graph edges
-    for entry in app_state.price_data.iter() {
-        let full_symbol = entry.key();
-        
-        // Extract exchange and symbol
-        if let Some((exchange_str, symbol)) = full_symbol.split_once(':') {
-            if let Ok(exchange) = exchange_str.parse::<Exchange>() {
-                if let Some(&exchange_idx) = exchange_indices.get(&exchange) {
-                    let price_data = entry.value();
-                    
-                    // Extract base and quote tokens
-                    if let Some((base, quote)) = extract_trading_pair(symbol) {
-                        if let (Some(&base_idx), Some(&quote_idx)) = (
-                            symbol_indices.get(&base),
-                            symbol_indices.get(&quote)
-                        ) {
-                            // Add edges for the trading pair
-                            // Buy: QUOTE -> BASE at ASK price
-                            if price_data.best_ask > 0.0 {
-                                let buy_rate = 1.0 / price_data.best_ask; // How much base you get per quote
-                                graph[quote_idx].push((base_idx, exchange_idx, buy_rate));
-                            }
-                            
-                            // Sell: BASE -> QUOTE at BID price
-                            if price_data.best_bid > 0.0 {
-                                let sell_rate = price_data.best_bid; // How much quote you get per base
-                                graph[base_idx].push((quote_idx, exchange_idx, sell_rate));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    (graph, symbol_indices, exchange_indices)
-}
-
-/// Extract base and quote tokens from a trading pair symbol
-fn extract_trading_pair(symbol: &str) -> Option<(String, String)> {
-    // Common quote currencies
-    let quote_currencies = ["USDT", "USD", "BTC", "ETH", "USDC", "BNB"];
-    
-    for &quote in &quote_currencies {
-        if symbol.ends_with(quote) && symbol.len() > quote.len() {
-            let base = &symbol[..symbol.len() - quote.len()];
-            return Some((base.to_string(), quote.to_string()));
-        }
-    }
-    
-    None
-}
-
-/// Find profitable paths from a starting node using BFS with cycle detection
-fn find_profitable_paths(
-    graph: &[Vec<(usize, usize, f64)>],
-    start_idx: usize,
-    max_path_length: usize,
-    symbol_map: &HashMap<usize, String>,
-    exchange_map: &HashMap<usize, Exchange>,
-    app_state: &AppState,
-    exchange_fees: &HashMap<Exchange, ExchangeFees>,
-    trade_size: f64,
-    min_profit_threshold: f64,
-) -> Vec<MultiHopArbitragePath> {
-    let mut profitable_paths = Vec::new();
-    
-    // Use BFS to find potential arbitrage cycles
-    struct PathState {
-        node: usize,
-        path: Vec<usize>,
-        exchanges: Vec<usize>,
-        rates: Vec<f64>,
-        cumulative_rate: f64,
-    }
-    
-    let mut queue = VecDeque::new();
-    
-    // Add starting node
-    queue.push_back(PathState {
-        node: start_idx,
-        path: vec![start_idx],
-        exchanges: Vec::new(),
-        rates: Vec::new(),
-        cumulative_rate: 1.0,
-    });
-    
-    // Track visited nodes to avoid simple loops
-    let mut visited = HashSet::new();
-    visited.insert(start_idx);
-    
-    while let Some(state) = queue.pop_front() {
-        let current_node = state.node;
-        
-        // For paths longer than 2, check if we've found a cycle back to start
-        if state.path.len() > 2 && current_node == start_idx {
-            // We've found a potential arbitrage cycle
-            let profit_pct = (state.cumulative_rate - 1.0) * 100.0;
-            
-            // Only consider profitable paths above threshold
-            if profit_pct >= min_profit_threshold {
-                // Create a multi-hop path
-                let mut path = MultiHopArbitragePath::new(state.path.len());
-                
-                // Populate path data
-                for i in 0..state.path.len() {
-                    let symbol = symbol_map.get(&state.path[i])
-                        .cloned()
-                        .unwrap_or_default();
-                        
-                    let exchange = if i > 0 {
-                        *exchange_map.get(&state.exchanges[i-1]).unwrap_or(&Exchange::Phemex)
-                    } else {
-                        // For the first hop, use the last exchange (completing the cycle)
-                        if !state.exchanges.is_empty() {
-                            *exchange_map.get(state.exchanges.last().unwrap()).unwrap_or(&Exchange::Phemex)
-                        } else {
-                            Exchange::Phemex // Default
-                        }
-                    };
-                    
-                    let price = if i > 0 { state.rates[i-1] } else { 1.0 };
-                    
-                    path.add_hop(symbol, exchange, price);
-                }
-                
-                // Generate unique path ID
-                path.generate_path_id();
-                
-                // Get orderbooks for slippage calculation
-                let orderbooks: Vec<Option<&StandardOrderBook>> = (0..path.exchange_path.len())
-                    .map(|i| {
-                        if i >= path.symbol_path.len() || i >= path.exchange_path.len() {
-                            return None;
-                        }
-                        
-                        // Form trading pair from tokens
-                        let symbol_str = &path.symbol_path[i];
-                        let exchange = path.exchange_path[i];
-                        
-                        let next_symbol = if i + 1 < path.symbol_path.len() {
-                            &path.symbol_path[i+1]
-                        } else {
-                            &path.symbol_path[0] // Wrap around for last hop
-                        };
-                        
-                        // Create trading pair symbol
-                        let trading_pair = format!("{symbol_str}{next_symbol}");
-                        
-                        // Get orderbook
-                        let full_symbol = format!("{exchange}:{trading_pair}");
-                        get_orderbook_for_exchange(app_state, &full_symbol)
-                    })
-                    .collect();
-                
-                // Calculate fees and slippage
-                path.calculate_fees_and_slippage(exchange_fees, &orderbooks, trade_size);
-                
-                // Only add if still profitable after fees and slippage
-                if path.net_profit_pct >= min_profit_threshold {
-                    profitable_paths.push(path);
-                }
-            }
-            
-            // Don't explore further from this cycle
-            continue;
-        }
-        
-        // Don't exceed max path length
-        if state.path.len() >= max_path_length {
-            continue;
-        }
-        
-        // Explore neighbors
-        for &(next_node, exchange_idx, rate) in &graph[current_node] {
-            // Skip if this would create a simple loop (not back to start)
-            if next_node != start_idx && state.path.contains(&next_node) {
-                continue;
-            }
-            
-            // Create new path state
-            let mut new_path = state.path.clone();
-            new_path.push(next_node);
-            
-            let mut new_exchanges = state.exchanges.clone();
-            new_exchanges.push(exchange_idx);
-            
-            let mut new_rates = state.rates.clone();
-            new_rates.push(rate);
-            
-            let new_cumulative_rate = state.cumulative_rate * rate;
-            
-            // Only continue if path has potential to be profitable
-            // (early pruning based on cumulative rate)
-            if state.path.len() >= 2 && new_cumulative_rate < 1.0 {
-                continue;
-            }
-            
-            // Add to queue
-            queue.push_back(PathState {
-                node: next_node,
-                path: new_path,
-                exchanges: new_exchanges,
-                rates: new_rates,
-                cumulative_rate: new_cumulative_rate,
-            });
-        }
-    }
-    
-    profitable_paths
-}
-
 /// Helper function to get orderbook for a symbol on a specific exchange
 #[inline]
 fn get_orderbook_for_exchange<'a>(app_state: &'a AppState, symbol: &str) -> Option<&'a StandardOrderBook> {
@@ -1099,6 +972,7 @@ fn get_orderbook_for_exchange<'a>(app_state: &'a AppState, symbol: &str) -> Opti
             "HBIT" => Exchange::Hbit,
             "BATONEX" => Exchange::Batonex,
             "COINCATCH" => Exchange::CoinCatch,
+            "OKX_FUTURES" => Exchange::OkxFutures,
             _ => return None
         };
         
@@ -1354,12 +1228,13 @@ pub async fn find_cross_exchange_opportunities(
                 let buy_price = buy_data.best_ask;  // Buy at ask price
                 let sell_price = sell_data.best_bid; // Sell at bid price
                 
-                // Fast path: Apply normalization for initial check
-                let normalized_buy_price = normalize_price(buy_exchange, symbol, buy_price).await;
-                let normalized_sell_price = normalize_price(sell_exchange, symbol, sell_price).await;
+                // Fast path: Apply contract multipliers for initial check
+                let normalized_buy_price = normalize_price(buy_exchange, symbol, buy_price);
+                let normalized_sell_price = normalize_price(sell_exchange, symbol, sell_price);
                 
                 // Skip if no gross profit possible after normalization
                 if normalized_sell_price <= normalized_buy_price {
+                    app_state.opportunity_tracker.close(symbol, buy_exchange, sell_exchange, CloseReason::SpreadCollapsed);
                     continue;
                 }
                 
@@ -1368,30 +1243,29 @@ pub async fn find_cross_exchange_opportunities(
                 let sell_orderbook = get_orderbook_for_exchange(app_state, &sell_symbol);
                 
                 // Compute potential profit with slippage consideration
-                if let Some(opportunity) = compute_cross_exchange_profit_with_slippage(
+                let evaluated = compute_cross_exchange_profit_with_slippage(
                     symbol,
                     buy_exchange,
                     sell_exchange,
-                    buy_price,
-                    sell_price,
+                    normalized_buy_price,
+                    normalized_sell_price,
                     buy_orderbook,
                     sell_orderbook,
-                    exchange_fees
+                    exchange_fees,
+                    &SizingLimits::for_pair(app_state, symbol, buy_exchange, sell_exchange)
+                ).await;
+                
+                // Validate, update the opportunity's lifecycle and keep it only when newly opened
+                if let Some(opportunity) = track_evaluation(
+                    app_state,
+                    symbol,
+                    buy_exchange,
+                    sell_exchange,
+                    buy_data.timestamp,
+                    sell_data.timestamp,
+                    evaluated
                 ).await {
-                    // Add additional validation
-                    if passes_sanity_checks(
-                        symbol,
-                        buy_exchange,
-                        sell_exchange,
-                        opportunity.buy_price,
-                        opportunity.sell_price,
-                        opportunity.net_profit_pct
-                    ).await && is_new_opportunity(symbol, buy_exchange, sell_exchange).await {
-                        // Ensure this is a significant opportunity worth noting
-                        if opportunity.net_profit_pct >= get_config().arbitrage.min_profit_threshold_pct {
-                            opportunities.push(opportunity);
-                        }
-                    }
+                    opportunities.push(opportunity);
                 }
             }
         }
@@ -1416,10 +1290,12 @@ pub async fn process_mapped_cross_exchange_arbitrage(
         let parts: Vec<&str> = full_symbol.split(':').collect();
         
         if parts.len() == 2 {
-            // Normalize by removing '-' and '_' and converting to uppercase
-            let normalized = parts[1]
-                .replace(['-', '_'], "")
-                .to_uppercase();
+            // Group by canonical symbol so bundled listings (e.g. 1000PEPEUSDT) meet their
+            // per-unit counterparts; other symbols just drop '-' and '_' and go uppercase
+            let normalized = match parts[0].parse::<Exchange>() {
+                Ok(exchange) => contract_multipliers().canonical_symbol(exchange, parts[1]),
+                Err(_) => parts[1].replace(['-', '_'], "").to_uppercase(),
+            };
             
             normalized_symbols
                 .entry(normalized)
@@ -1475,6 +1351,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                 "HBIT" => Exchange::Hbit,
                 "BATONEX" => Exchange::Batonex,
                 "COINCATCH" => Exchange::CoinCatch,
+                "OKX_FUTURES" => Exchange::OkxFutures,
                 _ => continue,
             };
             
@@ -1509,6 +1386,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                     "HBIT" => Exchange::Hbit,
                     "BATONEX" => Exchange::Batonex,
                     "COINCATCH" => Exchange::CoinCatch,
+                    "OKX_FUTURES" => Exchange::OkxFutures,
                     _ => continue,
                 };
                 
@@ -1517,12 +1395,15 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                     continue;
                 }
                 
-                // Normalize prices for fast path check
-                let normalized_buy_price = normalize_price(buy_exchange, &normalized, buy_price).await;
-                let normalized_sell_price = normalize_price(sell_exchange, &normalized, sell_price).await;
+                // Normalize prices with each listing's own contract multiplier
+                let buy_listing = buy_symbol.split(':').nth(1).unwrap_or("");
+                let sell_listing = sell_symbol.split(':').nth(1).unwrap_or("");
+                let normalized_buy_price = normalize_price(buy_exchange, buy_listing, buy_price);
+                let normalized_sell_price = normalize_price(sell_exchange, sell_listing, sell_price);
                 
                 // Skip if no gross profit possible after normalization
                 if normalized_sell_price <= normalized_buy_price {
+                    app_state.opportunity_tracker.close(&normalized, buy_exchange, sell_exchange, CloseReason::SpreadCollapsed);
                     continue;
                 }
                 
@@ -1530,27 +1411,29 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                 let buy_orderbook = get_orderbook_for_exchange(app_state, buy_symbol);
                 let sell_orderbook = get_orderbook_for_exchange(app_state, sell_symbol);
                 
-                if let Some(opportunity) = compute_cross_exchange_profit_with_slippage(
+                let evaluated = compute_cross_exchange_profit_with_slippage(
                     &normalized,
                     buy_exchange,
                     sell_exchange,
-                    buy_price,
-                    sell_price,
+                    normalized_buy_price,
+                    normalized_sell_price,
                     buy_orderbook,
                     sell_orderbook,
-                    exchange_fees
+                    exchange_fees,
+                    &SizingLimits::for_pair(app_state, &normalized, buy_exchange, sell_exchange)
+                ).await;
+                
+                // Validate, update the opportunity's lifecycle and keep it only when newly opened
+                if let Some(opportunity) = track_evaluation(
+                    app_state,
+                    &normalized,
+                    buy_exchange,
+                    sell_exchange,
+                    price_data[buy_symbol].timestamp,
+                    price_data[sell_symbol].timestamp,
+                    evaluated
                 ).await {
-                    // Add the new validation steps
-                    if passes_sanity_checks(
-                        &normalized,
-                        buy_exchange,
-                        sell_exchange,
-                        opportunity.buy_price,
-                        opportunity.sell_price,
-                        opportunity.net_profit_pct
-                    ).await && is_new_opportunity(&normalized, buy_exchange, sell_exchange).await {
-                        all_opportunities.push(opportunity);
-                    }
+                    all_opportunities.push(opportunity);
                 }
             }
         }
@@ -1564,6 +1447,10 @@ pub async fn process_mapped_cross_exchange_arbitrage(
 }
 
 /// Process a subset of symbols for parallel scanning
+///
+/// Books are read from `app_state.book_store`, which keeps only the newest book per
+/// (exchange, symbol). Callers pass the symbols that changed since their last pass
+/// (see `BookWatcher::take_changed`) so unchanged symbols are not re-evaluated.
 pub async fn process_mapped_cross_exchange_arbitrage_subset(
     app_state: &AppState,
     exchange_fees: &HashMap<Exchange, ExchangeFees>,
@@ -1571,35 +1458,12 @@ pub async fn process_mapped_cross_exchange_arbitrage_subset(
 ) -> Vec<CrossExchangeArb> {
     let mut all_opportunities = Vec::with_capacity(10);
     
-    // Group symbols by normalized representation
-    let mut normalized_symbols: HashMap<String, Vec<String>> = HashMap::with_capacity(100);
-    
-    // First, gather data from all exchanges and normalize symbols by removing separators
-    for entry in app_state.price_data.iter() {
-        let full_symbol = entry.key().clone();
-        let parts: Vec<&str> = full_symbol.split(':').collect();
-        
-        if parts.len() == 2 {
-            // Normalize by removing '-' and '_' and converting to uppercase
-            let normalized = parts[1]
-                .replace(['-', '_'], "")
-                .to_uppercase();
-            
-            // Only process symbols in our subset
-            if symbols.contains(&normalized) {
-                normalized_symbols
-                    .entry(normalized)
-                    .or_default()
-                    .push(full_symbol);
-            }
-        }
-    }
-
-    // Find symbols that exist on multiple exchanges
+    // Collect the latest books for symbols that exist on multiple exchanges
     let mut multi_exchange_symbols = Vec::new();
-    for (normalized, symbols) in &normalized_symbols {
-        if symbols.len() >= 2 {
-            multi_exchange_symbols.push((normalized.clone(), symbols.clone()));
+    for normalized in symbols {
+        let books = app_state.book_store.books_for(normalized);
+        if books.len() >= 2 {
+            multi_exchange_symbols.push((normalize_book_symbol(normalized), books));
         }
     }
     
@@ -1611,113 +1475,70 @@ pub async fn process_mapped_cross_exchange_arbitrage_subset(
     app_state.increment_cross_exchange_checks(multi_exchange_symbols.len() as u64);
     
     // Process each normalized symbol
-    for (normalized, exchange_symbols) in multi_exchange_symbols {
-        let mut price_data = HashMap::new();
-        
-        for exchange_symbol in &exchange_symbols {
-            if let Some(data) = app_state.price_data.get(exchange_symbol) {
-                price_data.insert(exchange_symbol.clone(), data.value().clone());
-            }
-        }
-        
-        for i in 0..exchange_symbols.len() {
-            let buy_symbol = &exchange_symbols[i];
-            
-            if !price_data.contains_key(buy_symbol) {
-                continue;
-            }
-            
-            let buy_exchange_str = buy_symbol.split(':').next().unwrap_or("");
-            let buy_price = price_data[buy_symbol].best_ask;
+    for (normalized, books) in multi_exchange_symbols {
+        for (i, buy_book) in books.iter().enumerate() {
+            let buy_exchange = buy_book.exchange;
+            let buy_price = buy_book.best_ask;
             
             if buy_price <= 0.0 {
                 continue;
             }
             
-            let buy_exchange = match buy_exchange_str {
-                "PHEMEX" => Exchange::Phemex,
-                "LBANK" => Exchange::LBank,
-                "XTCOM" => Exchange::XtCom,
-                "TAPBIT" => Exchange::TapBit,
-                "HBIT" => Exchange::Hbit,
-                "BATONEX" => Exchange::Batonex,
-                "COINCATCH" => Exchange::CoinCatch,
-                _ => continue,
-            };
-            
             // Skip exchanges with open circuit breakers
             if init_error_tracker().is_circuit_open(buy_exchange) {
                 continue;
             }
             
-            for j in 0..exchange_symbols.len() {
+            for (j, sell_book) in books.iter().enumerate() {
                 if i == j {
                     continue;
                 }
                 
-                let sell_symbol = &exchange_symbols[j];
-                
-                if !price_data.contains_key(sell_symbol) {
-                    continue;
-                }
-                
-                let sell_exchange_str = sell_symbol.split(':').next().unwrap_or("");
-                let sell_price = price_data[sell_symbol].best_bid;
+                let sell_exchange = sell_book.exchange;
+                let sell_price = sell_book.best_bid;
                 
                 if sell_price <= 0.0 {
                     continue;
                 }
                 
-                let sell_exchange = match sell_exchange_str {
-                    "PHEMEX" => Exchange::Phemex,
-                    "LBANK" => Exchange::LBank,
-                    "XTCOM" => Exchange::XtCom,
-                    "TAPBIT" => Exchange::TapBit,
-                    "HBIT" => Exchange::Hbit,
-                    "BATONEX" => Exchange::Batonex,
-                    "COINCATCH" => Exchange::CoinCatch,
-                    _ => continue,
-                };
-                
                 // Skip exchanges with open circuit breakers
                 if init_error_tracker().is_circuit_open(sell_exchange) {
                     continue;
                 }
                 
-                // Normalize prices for fast path check
-                let normalized_buy_price = normalize_price(buy_exchange, &normalized, buy_price).await;
-                let normalized_sell_price = normalize_price(sell_exchange, &normalized, sell_price).await;
+                // Books keep the listing's own prices; normalize with its contract multiplier
+                let normalized_buy_price = normalize_price(buy_exchange, &buy_book.symbol, buy_price);
+                let normalized_sell_price = normalize_price(sell_exchange, &sell_book.symbol, sell_price);
                 
                 // Skip if no gross profit possible after normalization
                 if normalized_sell_price <= normalized_buy_price {
+                    app_state.opportunity_tracker.close(&normalized, buy_exchange, sell_exchange, CloseReason::SpreadCollapsed);
                     continue;
                 }
                 
-                // Get orderbook data for slippage calculation 
-                let buy_orderbook = get_orderbook_for_exchange(app_state, buy_symbol);
-                let sell_orderbook = get_orderbook_for_exchange(app_state, sell_symbol);
+                let evaluated = compute_cross_exchange_profit_with_slippage(
+                    &normalized,
+                    buy_exchange,
+                    sell_exchange,
+                    normalized_buy_price,
+                    normalized_sell_price,
+                    Some(buy_book),
+                    Some(sell_book),
+                    exchange_fees,
+                    &SizingLimits::for_pair(app_state, &normalized, buy_exchange, sell_exchange)
+                ).await;
                 
-                if let Some(opportunity) = compute_cross_exchange_profit_with_slippage(
+                // Validate, update the opportunity's lifecycle and keep it only when newly opened
+                if let Some(opportunity) = track_evaluation(
+                    app_state,
                     &normalized,
                     buy_exchange,
                     sell_exchange,
-                    buy_price,
-                    sell_price,
-                    buy_orderbook,
-                    sell_orderbook,
-                    exchange_fees
+                    buy_book.timestamp,
+                    sell_book.timestamp,
+                    evaluated
                 ).await {
-                    // Add the new validation steps
-                    if passes_sanity_checks(
-                        &normalized,
-                        buy_exchange,
-                        sell_exchange,
-                        opportunity.buy_price,
-                        opportunity.sell_price,
-                        opportunity.net_profit_pct
-                    ).await && is_new_opportunity(&normalized, buy_exchange, sell_exchange).await {
-                        all_opportunities.push(opportunity);
-                    }
+                    all_opportunities.push(opportunity);
                 }
             }
         }
@@ -1769,5 +1590,11 @@ pub fn build_exchange_fees() -> HashMap<Exchange, ExchangeFees> {
                 ));
     }
     
+    fees.entry(Exchange::OkxFutures).or_insert_with(|| ExchangeFees::new(
+                Exchange::OkxFutures,
+                0.0002, // 0.02% maker
+                0.0005  // 0.05% taker
+            ));
+    
     fees
 }
\ No newline at end of file
@@ -0,0 +1,373 @@
+//! 跨所套利的深度最优下单量
+//! 同时沿买入所的卖盘（depth_asks）和卖出所的买盘（depth_bids）逐档成交，
+//! 找出扣除吃单手续费后绝对净利润最大的数量，并记录每一段的边际利润曲线。
+//!
+//! 两侧的价格和数量需先换算到规范交易对的每单位基础货币（见 `book_levels`），
+//! 否则 1000PEPEUSDT 与 PEPE_USDT 的档位无法直接对齐。
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_config;
+use crate::core::{base_asset, AppState, BALANCE_QUOTE_ASSET};
+use crate::exchange_types::{Exchange, StandardOrderBook};
+use crate::symbol_mapper::ContractMultipliers;
+use crate::types::exchange::MarketType;
+
+// 数量与名义价值比较的容差，避免浮点残量产生空的成交段
+const QTY_EPSILON: f64 = 1e-12;
+const NOTIONAL_EPSILON: f64 = 1e-9;
+
+/// 下单量约束
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizingLimits {
+    /// 每条腿的最小名义价值（报价货币）
+    pub min_notional: f64,
+    /// 买入所可用余额（报价货币），None 表示不限制
+    pub buy_balance: Option<f64>,
+    /// 卖出所可用保证金（报价货币，合约账户），None 表示不限制
+    pub sell_balance: Option<f64>,
+    /// 卖出所可卖的基础货币数量（现货账户），None 表示不限制
+    pub sell_inventory: Option<f64>,
+}
+
+impl SizingLimits {
+    /// 不限余额，只要求最小名义价值
+    pub fn unlimited(min_notional: f64) -> Self {
+        Self {
+            min_notional,
+            buy_balance: None,
+            sell_balance: None,
+            sell_inventory: None,
+        }
+    }
+
+    /// 使用配置中的最小名义价值和 `AppState` 中记录的两所可用余额
+    ///
+    /// 买入腿按报价货币余额限制；卖出所为现货账户时按 `symbol` 的基础货币持仓限制，
+    /// 否则（合约账户或未同步账户类型）按报价货币保证金限制。
+    pub fn for_pair(app_state: &AppState, symbol: &str, buy_exchange: Exchange, sell_exchange: Exchange) -> Self {
+        let spot_sell = app_state.account_market(sell_exchange) == Some(MarketType::Spot);
+        Self {
+            min_notional: get_config().arbitrage.min_trade_notional_usd,
+            buy_balance: app_state.available_balance(buy_exchange, BALANCE_QUOTE_ASSET),
+            sell_balance: (!spot_sell)
+                .then(|| app_state.available_balance(sell_exchange, BALANCE_QUOTE_ASSET))
+                .flatten(),
+            sell_inventory: spot_sell
+                .then(|| base_asset(symbol).and_then(|asset| app_state.available_balance(sell_exchange, asset)))
+                .flatten(),
+        }
+    }
+}
+
+/// 利润曲线上的一点：累计成交到 `size` 时的结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProfitPoint {
+    /// 累计数量（基础货币）
+    pub size: f64,
+    /// 累计买入成本（含手续费）
+    pub cost: f64,
+    /// 累计卖出所得（已扣手续费）
+    pub proceeds: f64,
+    /// 累计净利润（报价货币）
+    pub net_pnl: f64,
+    /// 这一段的边际利润率（%），随数量增加单调不增
+    pub marginal_profit_pct: f64,
+}
+
+/// 求解停止的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeLimit {
+    /// 下一段的边际利润不为正
+    Unprofitable,
+    /// 某一侧订单簿深度耗尽
+    DepthExhausted,
+    /// 买入所余额不足
+    BuyBalance,
+    /// 卖出所保证金或基础货币持仓不足
+    SellBalance,
+}
+
+/// 深度最优下单量
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizingResult {
+    /// 最优数量（基础货币）
+    pub size: f64,
+    /// 买入成交均价（不含手续费）
+    pub avg_buy_price: f64,
+    /// 卖出成交均价（不含手续费）
+    pub avg_sell_price: f64,
+    pub buy_notional: f64,
+    pub sell_notional: f64,
+    /// 预期净利润（报价货币）
+    pub net_pnl: f64,
+    /// 净利润占买入成本的百分比
+    pub net_profit_pct: f64,
+    pub limited_by: SizeLimit,
+    /// 每个成交段结束时的累计结果，最后一点即最优解
+    pub curve: Vec<ProfitPoint>,
+}
+
+/// 订单簿档位换算到每单位基础货币：价格除以合约乘数，数量乘以合约乘数
+pub fn book_levels(book: &StandardOrderBook, multipliers: &ContractMultipliers, asks: bool) -> Vec<(f64, f64)> {
+    let multiplier = multipliers.multiplier(book.exchange, &book.symbol);
+    let levels = if asks { &book.depth_asks } else { &book.depth_bids };
+    levels
+        .iter()
+        .map(|&(price, quantity)| (price / multiplier, quantity * multiplier))
+        .collect()
+}
+
+/// 求使绝对净利润最大的下单量
+///
+/// 卖盘价格递增、买盘价格递减，所以边际利润随数量单调不增，净利润是分段线性的凹函数：
+/// 一直成交到边际利润不为正、深度耗尽或余额用完即为最优。若此时名义价值仍低于最小值，
+/// 继续成交到刚好满足最小名义价值，只要总利润仍为正。
+///
+/// `buy_fee` / `sell_fee` 为小数费率；无法满足最小名义价值或没有正利润时返回 None。
+pub fn solve_optimal_size(
+    asks: &[(f64, f64)],
+    bids: &[(f64, f64)],
+    buy_fee: f64,
+    sell_fee: f64,
+    limits: &SizingLimits,
+) -> Option<SizingResult> {
+    let mut asks = sorted_levels(asks, true);
+    let mut bids = sorted_levels(bids, false);
+    let (mut ask_index, mut bid_index) = (0, 0);
+
+    let mut size = 0.0;
+    let mut buy_notional = 0.0;
+    let mut sell_notional = 0.0;
+    let mut cost = 0.0;
+    let mut proceeds = 0.0;
+    let mut curve = Vec::new();
+
+    let limited_by = loop {
+        let (Some(&(ask_price, ask_qty)), Some(&(bid_price, bid_qty))) = (asks.get(ask_index), bids.get(bid_index)) else {
+            break SizeLimit::DepthExhausted;
+        };
+
+        let unit_cost = ask_price * (1.0 + buy_fee);
+        let unit_proceeds = bid_price * (1.0 - sell_fee);
+        let marginal = unit_proceeds - unit_cost;
+        let below_min = buy_notional + NOTIONAL_EPSILON < limits.min_notional
+            || sell_notional + NOTIONAL_EPSILON < limits.min_notional;
+
+        if marginal <= 0.0 && !below_min {
+            break SizeLimit::Unprofitable;
+        }
+
+        let mut quantity = ask_qty.min(bid_qty);
+        let mut balance_limit = None;
+        if let Some(balance) = limits.buy_balance {
+            let room = (balance - buy_notional) / ask_price;
+            if room <= quantity {
+                quantity = room;
+                balance_limit = Some(SizeLimit::BuyBalance);
+            }
+        }
+        if let Some(balance) = limits.sell_balance {
+            let room = (balance - sell_notional) / bid_price;
+            if room <= quantity {
+                quantity = room;
+                balance_limit = Some(SizeLimit::SellBalance);
+            }
+        }
+        if let Some(inventory) = limits.sell_inventory {
+            let room = inventory - size;
+            if room <= quantity {
+                quantity = room;
+                balance_limit = Some(SizeLimit::SellBalance);
+            }
+        }
+        if marginal <= 0.0 {
+            // 亏损段只成交到满足最小名义价值为止
+            let needed = ((limits.min_notional - buy_notional) / ask_price)
+                .max((limits.min_notional - sell_notional) / bid_price);
+            quantity = quantity.min(needed);
+        }
+
+        if quantity <= QTY_EPSILON {
+            break balance_limit.unwrap_or(SizeLimit::DepthExhausted);
+        }
+
+        size += quantity;
+        buy_notional += quantity * ask_price;
+        sell_notional += quantity * bid_price;
+        cost += quantity * unit_cost;
+        proceeds += quantity * unit_proceeds;
+        curve.push(ProfitPoint {
+            size,
+            cost,
+            proceeds,
+            net_pnl: proceeds - cost,
+            marginal_profit_pct: marginal / unit_cost * 100.0,
+        });
+
+        asks[ask_index].1 -= quantity;
+        bids[bid_index].1 -= quantity;
+        if asks[ask_index].1 <= QTY_EPSILON {
+            ask_index += 1;
+        }
+        if bids[bid_index].1 <= QTY_EPSILON {
+            bid_index += 1;
+        }
+
+        if let Some(limit) = balance_limit {
+            break limit;
+        }
+    };
+
+    let net_pnl = proceeds - cost;
+    let meets_min = buy_notional + NOTIONAL_EPSILON >= limits.min_notional
+        && sell_notional + NOTIONAL_EPSILON >= limits.min_notional;
+    if size <= QTY_EPSILON || !meets_min || net_pnl <= 0.0 {
+        return None;
+    }
+
+    Some(SizingResult {
+        size,
+        avg_buy_price: buy_notional / size,
+        avg_sell_price: sell_notional / size,
+        buy_notional,
+        sell_notional,
+        net_pnl,
+        net_profit_pct: net_pnl / cost * 100.0,
+        limited_by,
+        curve,
+    })
+}
+
+/// 去掉无效档位并排序：卖盘价格升序，买盘价格降序
+fn sorted_levels(levels: &[(f64, f64)], ascending: bool) -> Vec<(f64, f64)> {
+    let mut levels: Vec<(f64, f64)> = levels
+        .iter()
+        .copied()
+        .filter(|&(price, quantity)| price.is_finite() && quantity.is_finite() && price > 0.0 && quantity > 0.0)
+        .collect();
+    levels.sort_by(|a, b| {
+        let ordering = a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal);
+        if ascending { ordering } else { ordering.reverse() }
+    });
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE: f64 = 0.0005;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn test_stops_where_marginal_profit_turns_negative() {
+        // 前两档有利可图，第三档卖价低于买价
+        let asks = [(100.0, 1.0), (100.5, 2.0), (101.5, 5.0)];
+        let bids = [(101.0, 1.5), (100.2, 10.0), (101.8, 1.0)];
+
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &SizingLimits::unlimited(10.0)).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::Unprofitable);
+
+        // 最优解是曲线上净利润最大的点，且边际利润单调不增
+        let best = result.curve.iter().map(|point| point.net_pnl).fold(f64::MIN, f64::max);
+        assert_close(result.net_pnl, best);
+        assert!(result.curve.windows(2).all(|pair| pair[1].marginal_profit_pct <= pair[0].marginal_profit_pct));
+        assert!(result.curve.iter().all(|point| point.marginal_profit_pct > 0.0));
+
+        // 卖出 1 @ 101.8 + 1.5 @ 101，买入 1 @ 100 + 1.5 @ 100.5
+        assert_close(result.size, 2.5);
+        assert_close(result.avg_buy_price, (100.0 + 1.5 * 100.5) / 2.5);
+        assert_close(result.avg_sell_price, (101.8 + 1.5 * 101.0) / 2.5);
+        let expected = (101.8 + 1.5 * 101.0) * (1.0 - FEE) - (100.0 + 1.5 * 100.5) * (1.0 + FEE);
+        assert_close(result.net_pnl, expected);
+    }
+
+    #[test]
+    fn test_balances_cap_each_leg() {
+        let asks = [(100.0, 10.0)];
+        let bids = [(102.0, 10.0)];
+
+        let limits = SizingLimits { buy_balance: Some(250.0), ..SizingLimits::unlimited(10.0) };
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &limits).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::BuyBalance);
+        assert_close(result.buy_notional, 250.0);
+
+        let limits = SizingLimits { buy_balance: Some(1000.0), sell_balance: Some(204.0), ..SizingLimits::unlimited(10.0) };
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &limits).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::SellBalance);
+        assert_close(result.size, 2.0);
+
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &SizingLimits::unlimited(10.0)).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::DepthExhausted);
+        assert_close(result.size, 10.0);
+    }
+
+    #[test]
+    fn test_spot_sell_capped_by_base_inventory() {
+        let asks = [(100.0, 10.0)];
+        let bids = [(102.0, 10.0)];
+        let app_state = AppState::new();
+        app_state.set_available_balance(Exchange::XtCom, "USDT", 1_000_000.0);
+        app_state.set_available_balance(Exchange::TapBit, "USDT", 1_000_000.0);
+        app_state.set_available_balance(Exchange::TapBit, "BTC", 1.5);
+
+        // 现货卖出所的报价货币再多也卖不出没有的币
+        app_state.set_account_market(Exchange::TapBit, MarketType::Spot);
+        let limits = SizingLimits::for_pair(&app_state, "BTCUSDT", Exchange::XtCom, Exchange::TapBit);
+        assert_eq!((limits.sell_balance, limits.sell_inventory), (None, Some(1.5)));
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &limits).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::SellBalance);
+        assert_close(result.size, 1.5);
+
+        // 合约卖出所按保证金限制，不看基础货币持仓
+        app_state.set_account_market(Exchange::TapBit, MarketType::Futures);
+        let limits = SizingLimits::for_pair(&app_state, "BTCUSDT", Exchange::XtCom, Exchange::TapBit);
+        assert_eq!((limits.sell_balance, limits.sell_inventory), (Some(1_000_000.0), None));
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &limits).unwrap();
+        assert_eq!(result.limited_by, SizeLimit::DepthExhausted);
+    }
+
+    #[test]
+    fn test_min_notional() {
+        // 有利可图的数量只有 0.05 个（名义价值约 5），不足最小名义价值 10
+        let asks = [(100.0, 0.05), (100.05, 5.0)];
+        let bids = [(100.5, 0.05), (100.1, 5.0)];
+
+        // 补足到 10 后总利润仍为正
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &SizingLimits::unlimited(10.0)).unwrap();
+        assert!(result.buy_notional >= 10.0 - 1e-9);
+        assert!(result.buy_notional < 10.1);
+        assert!(result.net_pnl > 0.0);
+        assert!(result.curve.last().unwrap().marginal_profit_pct < 0.0);
+
+        // 要求 100 时补足部分的亏损吃掉利润
+        assert!(solve_optimal_size(&asks, &bids, FEE, FEE, &SizingLimits::unlimited(100.0)).is_none());
+
+        // 没有交叉的盘口
+        assert!(solve_optimal_size(&[(101.0, 1.0)], &[(100.0, 1.0)], FEE, FEE, &SizingLimits::unlimited(0.0)).is_none());
+    }
+
+    #[test]
+    fn test_book_levels_apply_contract_multiplier() {
+        let mut multipliers = ContractMultipliers::new();
+        multipliers.insert(Exchange::BinanceFutures, "1000PEPEUSDT", "PEPE", "USDT", 1000.0);
+
+        let mut bundled = StandardOrderBook::new_minimal("1000PEPEUSDT", Exchange::BinanceFutures, 0.0119, 0.0120, 0);
+        bundled.depth_asks = vec![(0.0120, 50_000.0)];
+        let mut unit = StandardOrderBook::new_minimal("PEPE_USDT", Exchange::Phemex, 0.0000122, 0.0000123, 0);
+        unit.depth_bids = vec![(0.0000122, 10_000_000.0)];
+
+        let asks = book_levels(&bundled, &multipliers, true);
+        assert_close(asks[0].0, 0.000012);
+        assert_close(asks[0].1, 50_000_000.0);
+
+        let bids = book_levels(&unit, &multipliers, false);
+        let result = solve_optimal_size(&asks, &bids, FEE, FEE, &SizingLimits::unlimited(10.0)).unwrap();
+        assert_close(result.size, 10_000_000.0);
+        assert_eq!(result.limited_by, SizeLimit::DepthExhausted);
+    }
+}
@@ -285,6 +285,33 @@ impl ErrorTracker {
         count
     }
     
+    /// Open the circuit breaker immediately for a known duration, e.g. when the
+    /// exchange has banned us until a specific time
+    pub fn trip_circuit_breaker(&self, exchange: Exchange, category: ErrorCategory, duration: Duration) {
+        if !get_config().features.enable_circuit_breakers {
+            return;
+        }
+        
+        let now = Instant::now();
+        let reopening_at = now + duration;
+        if let Some(breaker) = self.circuit_breakers.get(&exchange) {
+            // Never shorten a breaker that is already open for longer
+            if breaker.is_open && breaker.reopening_at >= reopening_at {
+                return;
+            }
+        }
+        
+        self.circuit_breakers.insert(exchange, CircuitBreakerStatus {
+            is_open: true,
+            opened_at: now,
+            reopening_at,
+            error_threshold_crossed: false,
+            trigger_category: category,
+        });
+        
+        error!("Circuit breaker OPENED for {exchange:?} due to {category:?}. Will reopen in {duration:?}");
+    }
+    
     /// Check if a circuit breaker is open for an exchange
     pub fn is_circuit_open(&self, exchange: Exchange) -> bool {
         if let Some(breaker) = self.circuit_breakers.get(&exchange) {
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, str::FromStr};
 
+use crate::depth_sizing::ProfitPoint;
+
 /// Exchange identifiers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Exchange {
@@ -40,6 +42,13 @@ impl fmt::Display for Exchange {
     }
 }
 
+impl Exchange {
+    /// True for spot venues; every other connector trades perpetual swaps
+    pub fn is_spot(&self) -> bool {
+        matches!(self, Exchange::Binance)
+    }
+}
+
 // Added FromStr implementation for Exchange enum
 impl FromStr for Exchange {
     type Err = String;
@@ -636,6 +645,63 @@ pub struct CrossExchangeArb {
     pub profit_pct: f64,
     pub net_profit_pct: f64,
     pub total_fees_pct: f64,
+    /// Size that maximizes net profit, in units of the base asset
+    pub optimal_size: f64,
+    /// Expected net PnL at `optimal_size`, in quote currency
+    pub expected_pnl: f64,
+    /// Cumulative PnL after each fill segment; empty when no depth was available
+    pub profit_curve: Vec<ProfitPoint>,
+}
+
+/// Which side of a spot/perpetual basis trade is held long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasisDirection {
+    /// Buy spot, short the perpetual: earns positive basis and positive funding
+    LongSpotShortPerp,
+    /// Short (borrowed) spot, long the perpetual: earns negative basis and negative funding
+    ShortSpotLongPerp,
+}
+
+impl fmt::Display for BasisDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BasisDirection::LongSpotShortPerp => write!(f, "LONG_SPOT_SHORT_PERP"),
+            BasisDirection::ShortSpotLongPerp => write!(f, "SHORT_SPOT_LONG_PERP"),
+        }
+    }
+}
+
+/// Represents a funding-aware spot/perpetual basis opportunity.
+/// All `_pct` fields are percentages; prices are normalized by contract multipliers.
+#[derive(Debug, Clone)]
+pub struct BasisArb {
+    pub symbol: String,
+    pub spot_exchange: Exchange,
+    pub perp_exchange: Exchange,
+    pub direction: BasisDirection,
+    /// Entry price of the spot leg (ask when buying, bid when shorting)
+    pub spot_price: f64,
+    /// Entry price of the perpetual leg (bid when shorting, ask when buying)
+    pub perp_price: f64,
+    /// (perp - spot) / spot at entry prices
+    pub basis_pct: f64,
+    /// Basis captured by converging to `exit_threshold_pct`, annualized over the holding horizon
+    pub annualized_basis_pct: f64,
+    /// Latest funding rate per interval, as a fraction
+    pub funding_rate: f64,
+    /// Funding settlements expected within the holding horizon
+    pub funding_events: u32,
+    /// Funding collected (negative when paid) over the holding horizon
+    pub expected_funding_pct: f64,
+    pub holding_hours: f64,
+    /// Entry plus exit taker fees on both legs
+    pub total_fees_pct: f64,
+    /// Basis convergence + funding - fees over the holding horizon
+    pub net_carry_pct: f64,
+    pub annualized_net_pct: f64,
+    pub entry_threshold_pct: f64,
+    pub exit_threshold_pct: f64,
+    pub timestamp: i64,
 }
 
 /// Represents a three-way cross-exchange arbitrage path
@@ -659,14 +725,23 @@ pub struct ThreeExchangePath {
 /// Represents a multi-hop arbitrage path across exchanges
 #[derive(Debug, Clone)]
 pub struct MultiHopArbitragePath {
+    /// Market traded at each hop (the asset name for a transfer between exchanges)
     pub symbol_path: Vec<String>,
+    /// Exchange each hop executes on (the destination for transfers)
     pub exchange_path: Vec<Exchange>,
+    /// Top-of-book price used for each hop
     pub price_path: Vec<f64>,
+    /// Assets held along the cycle, starting and ending with the same asset
+    pub asset_path: Vec<String>,
     pub timestamp: i64,
     pub total_profit_pct: f64,
     pub net_profit_pct: f64,
     pub total_fees_pct: f64,
     pub total_slippage_pct: f64,
+    /// Input that maximizes profit given the depth at every hop, in units of the starting asset
+    pub capacity: f64,
+    /// Profit at `capacity`, in units of the starting asset; zero when depth is unknown
+    pub expected_profit: f64,
     pub path_id: String,
 }
 
@@ -677,11 +752,14 @@ impl MultiHopArbitragePath {
             symbol_path: Vec::with_capacity(path_length),
             exchange_path: Vec::with_capacity(path_length),
             price_path: Vec::with_capacity(path_length),
+            asset_path: Vec::with_capacity(path_length + 1),
             timestamp: chrono::Utc::now().timestamp_millis(),
             total_profit_pct: 0.0,
             net_profit_pct: 0.0,
             total_fees_pct: 0.0,
             total_slippage_pct: 0.0,
+            capacity: 0.0,
+            expected_profit: 0.0,
             path_id: String::new(),
         }
     }
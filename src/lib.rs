@@ -9,6 +9,14 @@ pub mod symbol_mapper;  // Symbol mapper for cross-exchange arbitrage
 pub mod config;  // Added missing config module export
 pub mod error_handling;  // Added missing error_handling module export
 pub mod json_parser;
+pub mod book_store;  // Conflated latest-orderbook store for the scanners
+pub mod depth_sizing;  // Depth-optimal trade sizing for cross-exchange opportunities
+pub mod basis;  // Funding-aware spot/perpetual basis scanner
+pub mod arbitrage_graph;  // Log-price graph with incremental negative-cycle detection
+pub mod opportunity_tracker;  // Open/update/close lifecycle of cross-exchange opportunities
+pub mod book_guard;  // Book age/skew checks and per-exchange rejection counters
+pub mod arbitrage_executor;  // Two-leg execution of cross-exchange opportunities with partial-fill hedging
+pub mod order_manager;  // Order state machine reconciling REST responses with user-stream updates
 
 // 新增重构模块
 pub mod types;  // 新的类型系统
@@ -50,6 +58,9 @@ pub use cross_exchange::{
     buffer_cross_exchange_opportunity,
     process_cross_exchange_arbitrage,
     flush_cross_ex_buffer,
+    buffer_basis_opportunity,
+    flush_basis_buffer,
+    flush_opportunity_lifecycles,
     build_exchange_fees,
     MIN_PROFIT_THRESHOLD,
     get_target_cross_exchange_symbols,
@@ -2,14 +2,18 @@
 // and enhanced error handling
 
 use env_logger::Env;
-use log::{error, info, debug, LevelFilter};
+use log::{error, info, debug, warn, LevelFilter};
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 use std::io::Write;
 
 use trifury::cross_exchange::{
     buffer_cross_exchange_opportunity, 
     buffer_multi_hop_opportunity,
+    buffer_basis_opportunity,
+    flush_basis_buffer,
+    flush_opportunity_lifecycles,
     find_multi_hop_arbitrage_opportunities
 };
 use trifury::{
@@ -28,8 +32,12 @@ use trifury::{
 // use trifury::connectors::binance::futures::BinanceFuturesConnector;
 // use trifury::connectors::bybit::futures::BybitFuturesConnector;
 // use trifury::connectors::okx::futures::OkxFuturesConnector;
+use trifury::arbitrage_executor::{ArbitrageExecutor, write_execution_reports};
 use trifury::config::{Config, init_config, get_config};
+use trifury::connectors::{ConnectorRegistry, ConnectorManager, DataFlowManager};
 use trifury::error_handling::{init_error_tracker, record_error};
+use trifury::types::{OpportunityStage, StandardizedMessage, SystemEvent};
+use trifury::types::market_data::UserData;
 
 
 /// Build exchange fees map from configuration
@@ -373,7 +381,69 @@ async fn main() -> Result<(), AppError> {
     let coincatch_chunks = distribute_even_chunks(&coincatch_symbols, max_ws_connections / 10, 10); // Max 10 per connection
     */
     
-    info!("WebSocket连接器已暂时禁用，等待重构完成");
+    // 从配置创建所有连接器，统一连接并订阅配置中的交易对
+    let mut connector_registry = match ConnectorRegistry::from_config(get_config(), Arc::new(app_state.clone())).await {
+        Ok(registry) => registry,
+        Err(e) => {
+            error!("Failed to build connectors from configuration: {e}");
+            return Err(AppError::Other(format!("Failed to build connectors: {e}")));
+        }
+    };
+
+    if let Err(e) = connector_registry.connect_all().await {
+        error!("Some connectors failed to connect: {e}");
+    }
+    for ((exchange, market_type), result) in connector_registry.subscribe_configured().await {
+        match result {
+            Ok(summary) => info!("{exchange}/{market_type}: subscribed {}/{} symbols", summary.successful, summary.total_requested),
+            Err(e) => error!("{exchange}/{market_type}: subscription failed: {e}"),
+        }
+    }
+
+    // 账户余额限制每条腿的下单量：先查询一次，之后由用户数据流推送并按间隔重新查询
+    let refresh = Duration::from_secs(get_config().arbitrage.balance_refresh_secs);
+    connector_registry.start_balance_sync(Arc::new(app_state.clone()), refresh).await;
+
+    // 两腿下单执行器只在 [execution] 启用时创建，下单使用注册表中的连接器和交易规则
+    let executor = get_config().execution.enabled.then(|| {
+        let mut executor = ArbitrageExecutor::new(Arc::new(app_state.clone()), get_config().execution.clone());
+        for connector in connector_registry.connector_handles() {
+            executor.add_connector(connector);
+        }
+        if let Some(instruments) = connector_registry.instruments() {
+            executor.set_instrument_registry(instruments.clone());
+        }
+        info!("Arbitrage execution enabled (min net profit {:.4}%)", get_config().execution.min_net_profit_pct);
+        Arc::new(executor)
+    });
+
+    // 用户数据流：余额更新写入可用余额，订单更新交给执行器确认成交
+    if let Some(mut user_data) = connector_registry.take_user_data_receiver() {
+        let state = app_state.clone();
+        let fills = executor.as_ref().map(|executor| executor.fills());
+        tokio::spawn(async move {
+            while let Some(message) = user_data.recv().await {
+                if let StandardizedMessage::UserDataUpdate(UserData::BalanceUpdate(update)) = &message {
+                    state.apply_balance_update(update);
+                } else if let Some(fills) = &fills {
+                    fills.apply(&message);
+                }
+            }
+        });
+    }
+
+    // 订单簿已由各连接器写入orderbook_queue，合并流只用于统计
+    if let Some(mut market_data) = connector_registry.take_market_data_receiver() {
+        tokio::spawn(async move {
+            let mut received: u64 = 0;
+            while market_data.recv().await.is_some() {
+                received += 1;
+                if received.is_multiple_of(100_000) {
+                    debug!("Merged market data stream: {received} messages");
+                }
+            }
+        });
+    }
 
     // Mark initialization as complete
     *app_state.is_initializing.write().await = false;
@@ -596,6 +666,8 @@ async fn main() -> Result<(), AppError> {
 
     // Add a CSV flush task for cross-exchange opportunities
     let flush_interval_secs = get_config().general.csv_flush_interval_secs;
+    let flush_tracker = app_state.opportunity_tracker.clone();
+    let flush_executor = executor.clone();
     let flush_task = tokio::spawn(async move {
         info!("Starting arbitrage opportunity flush task");
         let mut interval = tokio::time::interval(Duration::from_secs(flush_interval_secs));
@@ -608,6 +680,27 @@ async fn main() -> Result<(), AppError> {
                 error!("Error flushing cross-exchange buffer: {e}");
             }
             
+            // Close stale opportunities and flush finished lifecycles
+            if let Err(e) = flush_opportunity_lifecycles(
+                &flush_tracker,
+                "opportunity_lifecycle.csv",
+                "opportunity_lifecycle.jsonl"
+            ).await {
+                error!("Error flushing opportunity lifecycles: {e}");
+            }
+            
+            // Flush execution reports
+            if let Some(executor) = &flush_executor {
+                if let Err(e) = write_execution_reports(&executor.take_reports(), "execution_reports.jsonl").await {
+                    error!("Error writing execution reports: {e}");
+                }
+            }
+            
+            // Flush spot/perpetual basis opportunities
+            if let Err(e) = flush_basis_buffer("basis_arb.csv").await {
+                error!("Error flushing basis buffer: {e}");
+            }
+            
             // Flush multi-hop opportunities if feature is enabled
             if get_config().features.enable_multi_hop_arbitrage {
                 // Multi-hop buffer flushing disabled for now
@@ -621,16 +714,80 @@ async fn main() -> Result<(), AppError> {
     });
     websocket_tasks.push(flush_task);
 
+    // Publish opportunity lifecycle events on the system event channel and log them
+    connector_registry.forward_events(app_state.opportunity_tracker.subscribe());
+    let mut system_events = connector_registry.subscribe_events();
+    let event_log_task = tokio::spawn(async move {
+        loop {
+            match system_events.recv().await {
+                Ok(SystemEvent::ArbitrageOpportunity { symbol, buy_exchange, sell_exchange, stage, peak_profit_percentage, updates, duration_ms, close_reason, .. }) => {
+                    match stage {
+                        OpportunityStage::Opened => debug!("Opportunity opened: {symbol} {buy_exchange:?} -> {sell_exchange:?}"),
+                        OpportunityStage::Updated => {}
+                        OpportunityStage::Closed => info!(
+                            "Opportunity closed: {symbol} {buy_exchange:?} -> {sell_exchange:?} after {duration_ms}ms, {updates} updates, peak {peak_profit_percentage:.4}% ({})",
+                            close_reason.map(|reason| reason.to_string()).unwrap_or_default()
+                        ),
+                    }
+                }
+                Ok(SystemEvent::Error { exchange, market_type, error, .. }) => error!("{exchange:?}/{market_type:?}: {error}"),
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => warn!("System event log lagged, skipped {skipped} events"),
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    websocket_tasks.push(event_log_task);
+
+    // Start the funding-aware spot/perpetual basis scanner
+    let basis_config = get_config().basis.clone();
+    if basis_config.enabled {
+        let basis_state = app_state.clone();
+        let basis_fees = exchange_fees.clone();
+        let basis_task = tokio::spawn(async move {
+            info!("Starting basis scanner (holding {:.0}h, entry {:.2}% annualized)",
+                basis_config.holding_hours, basis_config.entry_threshold_pct);
+            let mut interval = tokio::time::interval(Duration::from_millis(basis_config.scan_interval_ms));
+            
+            loop {
+                interval.tick().await;
+                
+                let opportunities = trifury::basis::scan_basis_opportunities(&basis_state, &basis_fees, &basis_config);
+                for opportunity in opportunities.iter().take(3) {
+                    info!(
+                        "  Basis: {} {} spot {} (${:.4}) / perp {} (${:.4}): basis {:.4}%, funding {:.4}% x{}, net {:.4}% ({:.2}% annualized)",
+                        opportunity.symbol,
+                        opportunity.direction,
+                        opportunity.spot_exchange,
+                        opportunity.spot_price,
+                        opportunity.perp_exchange,
+                        opportunity.perp_price,
+                        opportunity.basis_pct,
+                        opportunity.funding_rate * 100.0,
+                        opportunity.funding_events,
+                        opportunity.net_carry_pct,
+                        opportunity.annualized_net_pct
+                    );
+                }
+                for opportunity in opportunities {
+                    buffer_basis_opportunity(opportunity).await;
+                }
+            }
+        });
+        websocket_tasks.push(basis_task);
+    }
+
     // Launch multiple scanner tasks for parallel processing
     let total_scanners = get_config().general.scanner_threads;  // Get from config
 
     for i in 0..total_scanners {
         let state_clone = cross_exchange_state.clone();
         let fees_clone = exchange_fees_clone.clone();
+        let executor = executor.clone();
         
         let scanner_task = scanner_handle.spawn(async move {
             // Scanner-specific configuration
-            let mut interval = tokio::time::interval(Duration::from_micros(1));
+            let book_watcher = state_clone.book_store.watch();
             let mut scan_counter = 0;
             let mut last_symbol_update = std::time::Instant::now();
             let mut cached_symbols = HashSet::with_capacity(1000);
@@ -639,7 +796,8 @@ async fn main() -> Result<(), AppError> {
             tokio::time::sleep(Duration::from_secs(2)).await;
             
             loop {
-                interval.tick().await;
+                // Wake on orderbook changes, but keep refreshing the symbol cache while idle
+                let _ = tokio::time::timeout(Duration::from_millis(100), book_watcher.changed()).await;
                 scan_counter += 1;
                 
                 // Refresh symbol cache periodically
@@ -667,8 +825,18 @@ async fn main() -> Result<(), AppError> {
                     state_clone.increment_cross_exchange_checks(1);
                 }
                 
+                // Only evaluate symbols in our subset whose books changed since the last pass,
+                // plus those with open opportunities so they stay open while their books are quiet
+                let mut changed_symbols: HashSet<String> = book_watcher.take_changed()
+                    .into_iter()
+                    .filter(|symbol| cached_symbols.contains(symbol))
+                    .collect();
+                changed_symbols.extend(state_clone.opportunity_tracker.open_symbols()
+                    .into_iter()
+                    .filter(|symbol| cached_symbols.contains(symbol)));
+                
                 // Skip if no symbols to process
-                if cached_symbols.is_empty() {
+                if changed_symbols.is_empty() {
                     continue;
                 }
                 
@@ -676,9 +844,21 @@ async fn main() -> Result<(), AppError> {
                 let opportunities = process_mapped_cross_exchange_arbitrage_subset(
                     &state_clone,
                     &fees_clone,
-                    &cached_symbols
+                    &changed_symbols
                 ).await;
                 
+                // Execute newly opened opportunities that clear the execution threshold
+                if let Some(executor) = &executor {
+                    let min_execution_profit = get_config().execution.min_net_profit_pct;
+                    for opportunity in opportunities.iter().filter(|opp| opp.net_profit_pct >= min_execution_profit) {
+                        let executor = executor.clone();
+                        let opportunity = opportunity.clone();
+                        tokio::spawn(async move {
+                            executor.execute(&opportunity).await;
+                        });
+                    }
+                }
+                
                 // Handle profitable opportunities
                 if !opportunities.is_empty() {
                     // Only count truly profitable opportunities (above threshold)
@@ -740,12 +920,13 @@ async fn main() -> Result<(), AppError> {
                                 .filter(|opp| opp.net_profit_pct >= min_profit)
                                 .take(3).enumerate()
                             {
-                                let path_str = opportunity.symbol_path.join("→");
+                                let path_str = opportunity.asset_path.join("→");
                                 
                                 info!(
-                                    "  Scanner {} - Multi-hop #{}: Path {} with {} hops: +{:.4}% (net: {:.4}%)",
-                                    i, j + 1, path_str, opportunity.symbol_path.len() - 1,
-                                    opportunity.total_profit_pct, opportunity.net_profit_pct
+                                    "  Scanner {} - Multi-hop #{}: Path {} with {} hops: +{:.4}% (net: {:.4}%, capacity {:.4} {})",
+                                    i, j + 1, path_str, opportunity.symbol_path.len(),
+                                    opportunity.total_profit_pct, opportunity.net_profit_pct,
+                                    opportunity.capacity, opportunity.asset_path.first().map(String::as_str).unwrap_or("")
                                 );
                                 
                                 // Buffer the opportunity for CSV logging
@@ -791,6 +972,20 @@ async fn main() -> Result<(), AppError> {
     }
     websocket_runtime.shutdown_timeout(Duration::from_secs(5));
 
+    if let Err(e) = connector_registry.disconnect_all().await {
+        error!("Error disconnecting connectors: {e}");
+    }
+    match connector_registry.finish_recording().await {
+        Some(Ok(summary)) => info!("Recorded {} messages into {} segment files", summary.records, summary.segments.len()),
+        Some(Err(e)) => error!("Error finishing market data recording: {e}"),
+        None => {}
+    }
+    if let Some(executor) = &executor {
+        if let Err(e) = write_execution_reports(&executor.take_reports(), "execution_reports.jsonl").await {
+            error!("Error writing execution reports: {e}");
+        }
+    }
+
     info!("Application shutting down.");
     Ok(())
     }
@@ -0,0 +1,315 @@
+//! 套利机会生命周期跟踪
+//! 按 `get_opportunity_key` 记录每个跨交易所机会从出现到消失的过程：首次/最后出现时间、
+//! 峰值与平均净利润、观测次数和关闭原因。关闭后的记录等待写入 CSV/JSON，
+//! 开启、更新与关闭同时以 `SystemEvent::ArbitrageOpportunity` 广播。
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+use crate::cross_exchange::get_opportunity_key;
+use crate::exchange_types::{CrossExchangeArb, Exchange};
+use crate::types::events::{CloseReason, OpportunityStage, SystemEvent};
+use crate::types::exchange::ExchangeType;
+
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 已关闭机会的生命周期记录
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityLifecycle {
+    pub key: String,
+    pub symbol: String,
+    pub buy_exchange: Exchange,
+    pub sell_exchange: Exchange,
+    /// 首次出现时间（毫秒）
+    pub first_seen: i64,
+    /// 最后一次观测到时间（毫秒）
+    pub last_seen: i64,
+    pub closed_at: i64,
+    /// 首次出现到最后一次观测的时长（毫秒）
+    pub duration_ms: i64,
+    /// 观测次数（包含开启那一次）
+    pub updates: u64,
+    pub peak_net_profit_pct: f64,
+    pub avg_net_profit_pct: f64,
+    pub last_net_profit_pct: f64,
+    /// 峰值时按深度求得的下单量与预期盈亏，用于判断机会是否可执行
+    pub peak_optimal_size: f64,
+    pub peak_expected_pnl: f64,
+    pub close_reason: CloseReason,
+}
+
+#[derive(Debug, Clone)]
+struct OpenOpportunity {
+    symbol: String,
+    buy_exchange: Exchange,
+    sell_exchange: Exchange,
+    first_seen: i64,
+    last_seen: i64,
+    updates: u64,
+    profit_sum: f64,
+    peak_net_profit_pct: f64,
+    last_net_profit_pct: f64,
+    peak_optimal_size: f64,
+    peak_expected_pnl: f64,
+}
+
+impl OpenOpportunity {
+    fn into_lifecycle(self, key: String, reason: CloseReason, now_ms: i64) -> OpportunityLifecycle {
+        OpportunityLifecycle {
+            key,
+            symbol: self.symbol,
+            buy_exchange: self.buy_exchange,
+            sell_exchange: self.sell_exchange,
+            first_seen: self.first_seen,
+            last_seen: self.last_seen,
+            closed_at: now_ms,
+            duration_ms: self.last_seen - self.first_seen,
+            updates: self.updates,
+            peak_net_profit_pct: self.peak_net_profit_pct,
+            avg_net_profit_pct: self.profit_sum / self.updates as f64,
+            last_net_profit_pct: self.last_net_profit_pct,
+            peak_optimal_size: self.peak_optimal_size,
+            peak_expected_pnl: self.peak_expected_pnl,
+            close_reason: reason,
+        }
+    }
+}
+
+/// 跨交易所机会跟踪器
+#[derive(Debug)]
+pub struct OpportunityTracker {
+    open: Mutex<HashMap<String, OpenOpportunity>>,
+    closed: Mutex<Vec<OpportunityLifecycle>>,
+    events: broadcast::Sender<SystemEvent>,
+}
+
+impl Default for OpportunityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpportunityTracker {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            open: Mutex::new(HashMap::new()),
+            closed: Mutex::new(Vec::new()),
+            events,
+        }
+    }
+
+    /// 订阅机会的开启、更新与关闭事件
+    pub fn subscribe(&self) -> broadcast::Receiver<SystemEvent> {
+        self.events.subscribe()
+    }
+
+    /// 记录一次有效观测，返回是否为新开启的机会
+    pub fn observe(&self, arb: &CrossExchangeArb) -> bool {
+        self.observe_at(arb, chrono::Utc::now().timestamp_millis())
+    }
+
+    pub fn observe_at(&self, arb: &CrossExchangeArb, now_ms: i64) -> bool {
+        let key = get_opportunity_key(&arb.symbol, &arb.buy_exchange, &arb.sell_exchange);
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        let opened = !open.contains_key(&key);
+        let entry = open.entry(key).or_insert_with(|| OpenOpportunity {
+            symbol: arb.symbol.clone(),
+            buy_exchange: arb.buy_exchange,
+            sell_exchange: arb.sell_exchange,
+            first_seen: now_ms,
+            last_seen: now_ms,
+            updates: 0,
+            profit_sum: 0.0,
+            peak_net_profit_pct: f64::NEG_INFINITY,
+            last_net_profit_pct: 0.0,
+            peak_optimal_size: 0.0,
+            peak_expected_pnl: 0.0,
+        });
+        entry.last_seen = now_ms;
+        entry.updates += 1;
+        entry.profit_sum += arb.net_profit_pct;
+        entry.last_net_profit_pct = arb.net_profit_pct;
+        if arb.net_profit_pct > entry.peak_net_profit_pct {
+            entry.peak_net_profit_pct = arb.net_profit_pct;
+            entry.peak_optimal_size = arb.optimal_size;
+            entry.peak_expected_pnl = arb.expected_pnl;
+        }
+
+        let stage = if opened { OpportunityStage::Opened } else { OpportunityStage::Updated };
+        let event = Self::event(entry, stage, None, now_ms);
+        drop(open);
+        let _ = self.events.send(event);
+        opened
+    }
+
+    /// 关闭一个机会；未开启时返回 None
+    pub fn close(&self, symbol: &str, buy_exchange: Exchange, sell_exchange: Exchange, reason: CloseReason) -> Option<OpportunityLifecycle> {
+        self.close_at(symbol, buy_exchange, sell_exchange, reason, chrono::Utc::now().timestamp_millis())
+    }
+
+    pub fn close_at(
+        &self,
+        symbol: &str,
+        buy_exchange: Exchange,
+        sell_exchange: Exchange,
+        reason: CloseReason,
+        now_ms: i64,
+    ) -> Option<OpportunityLifecycle> {
+        let key = get_opportunity_key(symbol, &buy_exchange, &sell_exchange);
+        let entry = self.open.lock().unwrap_or_else(|e| e.into_inner()).remove(&key)?;
+        Some(self.finish(key, entry, reason, now_ms))
+    }
+
+    /// 关闭超过 `max_age` 未再观测到的机会，返回关闭数量
+    ///
+    /// 扫描器每一轮都会重新评估 `open_symbols` 中的交易对，订单簿过期时由时效检查以
+    /// `BookStale` 关闭；这里只兜底已无人评估的机会（订单簿被移除、交易对不再被扫描）。
+    pub fn close_stale(&self, max_age: Duration) -> usize {
+        self.close_stale_at(max_age, chrono::Utc::now().timestamp_millis())
+    }
+
+    pub fn close_stale_at(&self, max_age: Duration, now_ms: i64) -> usize {
+        let max_age_ms = max_age.as_millis() as i64;
+        let stale: Vec<(String, OpenOpportunity)> = {
+            let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+            let keys: Vec<String> = open
+                .iter()
+                .filter(|(_, entry)| now_ms - entry.last_seen > max_age_ms)
+                .map(|(key, _)| key.clone())
+                .collect();
+            keys.into_iter()
+                .filter_map(|key| open.remove(&key).map(|entry| (key, entry)))
+                .collect()
+        };
+        let count = stale.len();
+        for (key, entry) in stale {
+            self.finish(key, entry, CloseReason::BookStale, now_ms);
+        }
+        count
+    }
+
+    /// 存在开启中机会的交易对
+    pub fn open_symbols(&self) -> HashSet<String> {
+        self.open
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|entry| entry.symbol.clone())
+            .collect()
+    }
+
+    /// 当前开启中的机会数量
+    pub fn open_count(&self) -> usize {
+        self.open.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// 取出尚未写出的已关闭记录
+    pub fn take_closed(&self) -> Vec<OpportunityLifecycle> {
+        std::mem::take(&mut *self.closed.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn finish(&self, key: String, entry: OpenOpportunity, reason: CloseReason, now_ms: i64) -> OpportunityLifecycle {
+        let _ = self.events.send(Self::event(&entry, OpportunityStage::Closed, Some(reason), now_ms));
+        let lifecycle = entry.into_lifecycle(key, reason, now_ms);
+        self.closed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(lifecycle.clone());
+        lifecycle
+    }
+
+    fn event(entry: &OpenOpportunity, stage: OpportunityStage, close_reason: Option<CloseReason>, now_ms: i64) -> SystemEvent {
+        SystemEvent::ArbitrageOpportunity {
+            symbol: entry.symbol.clone(),
+            buy_exchange: ExchangeType::from(entry.buy_exchange).into(),
+            sell_exchange: ExchangeType::from(entry.sell_exchange).into(),
+            profit_percentage: entry.last_net_profit_pct,
+            stage,
+            peak_profit_percentage: entry.peak_net_profit_pct,
+            updates: entry.updates,
+            duration_ms: (entry.last_seen - entry.first_seen).max(0) as u64,
+            close_reason,
+            timestamp: UNIX_EPOCH + Duration::from_millis(now_ms.max(0) as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arb(net_profit_pct: f64, expected_pnl: f64) -> CrossExchangeArb {
+        CrossExchangeArb {
+            symbol: "BTCUSDT".to_string(),
+            buy_exchange: Exchange::Phemex,
+            sell_exchange: Exchange::LBank,
+            buy_price: 100.0,
+            sell_price: 101.0,
+            timestamp: 0,
+            profit_pct: net_profit_pct + 0.2,
+            net_profit_pct,
+            total_fees_pct: 0.2,
+            optimal_size: 1.0,
+            expected_pnl,
+            profit_curve: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_tracks_peak_average_and_close_reason() {
+        let tracker = OpportunityTracker::new();
+        let mut events = tracker.subscribe();
+
+        assert!(tracker.observe_at(&arb(0.5, 5.0), 1_000));
+        assert!(!tracker.observe_at(&arb(0.9, 9.0), 1_500));
+        assert!(!tracker.observe_at(&arb(0.4, 4.0), 2_000));
+        assert_eq!(tracker.open_count(), 1);
+        assert_eq!(tracker.open_symbols(), HashSet::from(["BTCUSDT".to_string()]));
+
+        let closed = tracker
+            .close_at("BTCUSDT", Exchange::Phemex, Exchange::LBank, CloseReason::SpreadCollapsed, 2_500)
+            .unwrap();
+        assert_eq!(closed.duration_ms, 1_000);
+        assert_eq!(closed.updates, 3);
+        assert_eq!(closed.peak_net_profit_pct, 0.9);
+        assert_eq!(closed.peak_expected_pnl, 9.0);
+        assert!((closed.avg_net_profit_pct - 0.6).abs() < 1e-9);
+        assert_eq!(closed.close_reason, CloseReason::SpreadCollapsed);
+        assert_eq!(tracker.open_count(), 0);
+        assert_eq!(tracker.take_closed().len(), 1);
+        assert!(tracker.take_closed().is_empty());
+
+        let stages: Vec<OpportunityStage> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| match event {
+                SystemEvent::ArbitrageOpportunity { stage, .. } => stage,
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            stages,
+            vec![OpportunityStage::Opened, OpportunityStage::Updated, OpportunityStage::Updated, OpportunityStage::Closed]
+        );
+
+        // 关闭后再次出现视为新机会
+        assert!(tracker.observe_at(&arb(0.5, 5.0), 3_000));
+    }
+
+    #[test]
+    fn test_closes_stale_opportunities() {
+        let tracker = OpportunityTracker::new();
+        tracker.observe_at(&arb(0.5, 5.0), 1_000);
+        assert!(tracker
+            .close_at("BTCUSDT", Exchange::LBank, Exchange::Phemex, CloseReason::SanityCheckFailed, 1_100)
+            .is_none());
+
+        assert_eq!(tracker.close_stale_at(Duration::from_secs(5), 5_000), 0);
+        assert_eq!(tracker.close_stale_at(Duration::from_secs(5), 7_000), 1);
+        let closed = tracker.take_closed();
+        assert_eq!(closed[0].close_reason, CloseReason::BookStale);
+        assert_eq!(closed[0].closed_at, 7_000);
+    }
+}
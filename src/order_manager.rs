@@ -0,0 +1,701 @@
+//! 订单管理（OMS）
+//! 统一几种来源不同的订单回报：下单与查单的 REST 响应（`orders::OrderResponse`/`OrderStatus`）、
+//! 用户数据流推送的 `market_data::OrderUpdate` 与 `trading::OrderUpdate`，以及币安期货的 `FuturesOrder`。
+//! 每个订单由 OMS 分配客户端订单ID，状态按 New→PartiallyFilled→Filled/Canceled/Rejected/Expired 校验转换。
+//! 回报到达顺序不定：累计成交只增不减，迟到的旧状态被忽略，终态不可回退；
+//! 下单响应尚未关联订单号之前到达的推送先缓存，关联后重放。
+
+use log::warn;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::connectors::binance::futures::websocket::FuturesOrder;
+use crate::core::AppError;
+use crate::types::exchange::ExchangeType;
+use crate::types::market_data::{self, StandardizedMessage, UserData};
+use crate::types::orders::{OrderRequest, OrderResponse, OrderSide, OrderState, OrderStatus, OrderType};
+use crate::types::trading::{self, TradeEvent};
+
+const QTY_EPSILON: f64 = 1e-12;
+/// 等待关联订单号的推送最多缓存的订单数
+const MAX_PENDING_ORDERS: usize = 1024;
+/// 客户端订单ID中策略标识的最大长度（OKX 的 clOrdId 限 32 位字母数字）
+const MAX_STRATEGY_TAG_LEN: usize = 8;
+
+/// 订单回报的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReportSource {
+    /// 下单、查单等 REST 响应
+    Rest,
+    /// 用户数据流推送
+    UserStream,
+}
+
+/// 归一化后的订单回报，各字段缺失表示该来源不提供
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderReport {
+    pub exchange: ExchangeType,
+    pub order_id: Option<String>,
+    pub client_order_id: Option<String>,
+    pub state: Option<OrderState>,
+    /// 累计成交数量
+    pub filled_quantity: Option<f64>,
+    /// 累计成交均价
+    pub average_price: Option<f64>,
+    /// 最近一笔成交价，只有逐笔推送提供，用于在缺少均价时累计均价
+    pub last_fill_price: Option<f64>,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+    pub source: ReportSource,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn positive(value: f64) -> Option<f64> {
+    (value > 0.0).then_some(value)
+}
+
+impl OrderReport {
+    /// 下单响应
+    pub fn from_response(exchange: ExchangeType, response: &OrderResponse) -> Self {
+        Self {
+            exchange,
+            order_id: non_empty(&response.order_id),
+            client_order_id: response.client_order_id.as_deref().and_then(non_empty),
+            state: OrderState::from_api_string(&response.status),
+            filled_quantity: Some(response.filled_quantity),
+            average_price: response.average_price.and_then(positive),
+            last_fill_price: None,
+            timestamp: response.timestamp as i64,
+            source: ReportSource::Rest,
+        }
+    }
+
+    /// 查单响应
+    pub fn from_status(exchange: ExchangeType, status: &OrderStatus) -> Self {
+        Self {
+            exchange,
+            order_id: non_empty(&status.order_id),
+            client_order_id: None,
+            state: OrderState::from_api_string(&status.status),
+            filled_quantity: Some(status.filled_quantity),
+            average_price: status.average_price.and_then(positive),
+            last_fill_price: None,
+            timestamp: status.timestamp as i64,
+            source: ReportSource::Rest,
+        }
+    }
+
+    /// 期货连接器解析的订单推送，`executed_price` 是最近一笔成交价
+    pub fn from_trading_update(exchange: ExchangeType, update: &trading::OrderUpdate) -> Self {
+        Self {
+            exchange,
+            order_id: non_empty(&update.order_id),
+            client_order_id: non_empty(&update.client_order_id),
+            state: Some(update.status.into()),
+            filled_quantity: Some(update.executed_quantity),
+            average_price: None,
+            last_fill_price: positive(update.executed_price),
+            timestamp: update.timestamp as i64,
+            source: ReportSource::UserStream,
+        }
+    }
+}
+
+impl From<&market_data::OrderUpdate> for OrderReport {
+    fn from(update: &market_data::OrderUpdate) -> Self {
+        Self {
+            exchange: update.exchange,
+            order_id: non_empty(&update.order_id),
+            client_order_id: None,
+            state: OrderState::from_api_string(&update.status),
+            filled_quantity: Some(update.filled_quantity),
+            average_price: update.average_price.and_then(positive),
+            last_fill_price: None,
+            timestamp: update.timestamp,
+            source: ReportSource::UserStream,
+        }
+    }
+}
+
+/// 币安期货 REST 查询到的订单，均价由累计成交额推算
+impl From<&FuturesOrder> for OrderReport {
+    fn from(order: &FuturesOrder) -> Self {
+        Self {
+            exchange: ExchangeType::BinanceFutures,
+            order_id: Some(order.order_id.to_string()),
+            client_order_id: non_empty(&order.client_order_id),
+            state: OrderState::from_api_string(&order.status),
+            filled_quantity: Some(order.executed_qty),
+            average_price: (order.executed_qty > 0.0)
+                .then(|| order.cumulative_quote_qty / order.executed_qty)
+                .and_then(positive),
+            last_fill_price: None,
+            timestamp: order.update_time,
+            source: ReportSource::Rest,
+        }
+    }
+}
+
+/// 合并一条回报的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApplyOutcome {
+    /// 状态或成交有变化
+    Updated { from: OrderState, to: OrderState },
+    /// 重复或迟到的回报，没有新信息
+    Unchanged,
+    /// 回报的状态与当前状态冲突（如终态之间、已部分成交后被拒），已忽略该状态
+    Conflict { current: OrderState, reported: OrderState },
+    /// 尚未关联到订单，已缓存等待下单响应
+    Pending,
+    /// 无法关联的回报（既无已知的客户端订单ID，也没有订单号）
+    Unknown,
+}
+
+/// OMS 跟踪的订单
+#[derive(Debug, Clone, Serialize)]
+pub struct ManagedOrder {
+    pub client_order_id: String,
+    pub order_id: Option<String>,
+    pub strategy: String,
+    pub exchange: ExchangeType,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    pub price: Option<f64>,
+    pub state: OrderState,
+    /// 累计成交数量
+    pub filled_quantity: f64,
+    /// 累计成交均价
+    pub average_price: Option<f64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// 依次经历的状态，首个为 New
+    pub history: Vec<OrderState>,
+    /// 被忽略的冲突回报次数
+    pub conflicts: u32,
+}
+
+impl ManagedOrder {
+    pub fn remaining_quantity(&self) -> f64 {
+        (self.quantity - self.filled_quantity).max(0.0)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.state.is_terminal()
+    }
+
+    /// 合并一条回报：累计成交只增不减，状态只按合法转换前进
+    fn merge(&mut self, report: &OrderReport) -> ApplyOutcome {
+        let from = self.state;
+        let mut changed = false;
+
+        if self.order_id.is_none() && report.order_id.is_some() {
+            self.order_id = report.order_id.clone();
+            changed = true;
+        }
+
+        if let Some(filled) = report.filled_quantity {
+            if filled > self.filled_quantity + QTY_EPSILON {
+                self.average_price = match (report.average_price, report.last_fill_price) {
+                    (Some(average), _) => Some(average),
+                    // 只有最近成交价时，把新增部分按该价格计入均价
+                    (None, Some(last)) => {
+                        let previous = self.average_price.unwrap_or(last) * self.filled_quantity;
+                        Some((previous + (filled - self.filled_quantity) * last) / filled)
+                    }
+                    (None, None) => self.average_price,
+                };
+                self.filled_quantity = filled;
+                changed = true;
+            } else if self.average_price.is_none() && report.average_price.is_some() && filled > QTY_EPSILON {
+                self.average_price = report.average_price;
+                changed = true;
+            }
+        }
+
+        // 成交量本身也说明了状态，补上可能丢失的部分成交/完全成交推送
+        let implied = if self.quantity > 0.0 && self.filled_quantity >= self.quantity - QTY_EPSILON {
+            Some(OrderState::Filled)
+        } else if self.filled_quantity > QTY_EPSILON {
+            Some(OrderState::PartiallyFilled)
+        } else {
+            None
+        };
+        let reported = match (report.state, implied) {
+            (Some(state), Some(implied)) if !state.is_terminal() => Some(Self::later(state, implied)),
+            (Some(state), _) => Some(state),
+            (None, implied) => implied,
+        };
+
+        let mut outcome = None;
+        if let Some(next) = reported {
+            if next != self.state {
+                if self.state.can_transition_to(next) {
+                    self.state = next;
+                    self.history.push(next);
+                    changed = true;
+                } else if next.is_terminal() {
+                    self.conflicts += 1;
+                    outcome = Some(ApplyOutcome::Conflict { current: self.state, reported: next });
+                }
+                // 其余为迟到的旧状态，直接忽略
+            }
+        }
+
+        if changed {
+            self.updated_at = self.updated_at.max(report.timestamp);
+        }
+        outcome.unwrap_or(if changed {
+            ApplyOutcome::Updated { from, to: self.state }
+        } else {
+            ApplyOutcome::Unchanged
+        })
+    }
+
+    fn rank(state: OrderState) -> u8 {
+        match state {
+            OrderState::New => 0,
+            OrderState::PartiallyFilled => 1,
+            _ => 2,
+        }
+    }
+
+    fn later(a: OrderState, b: OrderState) -> OrderState {
+        if Self::rank(b) > Self::rank(a) { b } else { a }
+    }
+}
+
+#[derive(Debug, Default)]
+struct OrderBook {
+    /// 客户端订单ID → 订单
+    orders: HashMap<String, ManagedOrder>,
+    /// (交易所, 订单号) → 客户端订单ID
+    by_order_id: HashMap<(ExchangeType, String), String>,
+    /// 尚未关联的推送，按 (交易所, 订单号) 缓存
+    pending: HashMap<(ExchangeType, String), Vec<OrderReport>>,
+}
+
+impl OrderBook {
+    fn resolve(&self, report: &OrderReport) -> Option<String> {
+        if let Some(client_order_id) = report.client_order_id.as_ref().filter(|id| self.orders.contains_key(*id)) {
+            return Some(client_order_id.clone());
+        }
+        let order_id = report.order_id.as_ref()?;
+        self.by_order_id.get(&(report.exchange, order_id.clone())).cloned()
+    }
+
+    fn buffer(&mut self, report: OrderReport, order_id: String) {
+        let key = (report.exchange, order_id);
+        if !self.pending.contains_key(&key) && self.pending.len() >= MAX_PENDING_ORDERS {
+            // 丢弃最早的一组，防止外部订单的推送无限堆积
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, reports)| reports.iter().map(|r| r.timestamp).min().unwrap_or(0))
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        self.pending.entry(key).or_default().push(report);
+    }
+}
+
+/// 订单管理器
+#[derive(Debug)]
+pub struct OrderManager {
+    prefix: String,
+    sequence: AtomicU64,
+    book: Mutex<OrderBook>,
+}
+
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self::new("tf")
+    }
+}
+
+impl OrderManager {
+    /// `prefix` 用于区分本进程下的订单，只保留字母数字
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.chars().filter(|c| c.is_ascii_alphanumeric()).take(4).collect(),
+            sequence: AtomicU64::new(0),
+            book: Mutex::new(OrderBook::default()),
+        }
+    }
+
+    /// 生成客户端订单ID：前缀 + 策略标识 + 毫秒时间 + 序号，只含字母数字且不超过 32 位，
+    /// 满足币安、Bybit 与 OKX 的格式要求
+    pub fn next_client_order_id(&self, strategy: &str) -> String {
+        let tag: String = strategy
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(MAX_STRATEGY_TAG_LEN)
+            .collect();
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) % 1_000_000;
+        format!("{}{}{}{:06}", self.prefix, tag, chrono::Utc::now().timestamp_millis(), sequence)
+    }
+
+    /// 登记一个待发送的订单，返回带有客户端订单ID的请求
+    ///
+    /// 请求自带的客户端订单ID会被沿用，与已跟踪的订单重复时报错。
+    pub fn submit(&self, strategy: &str, mut request: OrderRequest) -> Result<OrderRequest, AppError> {
+        let client_order_id = match request.client_order_id.take() {
+            Some(id) if !id.is_empty() => id,
+            _ => self.next_client_order_id(strategy),
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        if book.orders.contains_key(&client_order_id) {
+            return Err(AppError::Other(format!("客户端订单ID重复: {client_order_id}")));
+        }
+        book.orders.insert(
+            client_order_id.clone(),
+            ManagedOrder {
+                client_order_id: client_order_id.clone(),
+                order_id: None,
+                strategy: strategy.to_string(),
+                exchange: request.exchange,
+                symbol: request.symbol.clone(),
+                side: request.side,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                price: request.price,
+                state: OrderState::New,
+                filled_quantity: 0.0,
+                average_price: None,
+                created_at: now,
+                updated_at: now,
+                history: vec![OrderState::New],
+                conflicts: 0,
+            },
+        );
+        request.client_order_id = Some(client_order_id);
+        Ok(request)
+    }
+
+    /// 合并一条回报
+    pub fn apply(&self, report: OrderReport) -> ApplyOutcome {
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(client_order_id) = book.resolve(&report) else {
+            return match report.order_id.clone() {
+                Some(order_id) => {
+                    book.buffer(report, order_id);
+                    ApplyOutcome::Pending
+                }
+                None => ApplyOutcome::Unknown,
+            };
+        };
+
+        let Some(order) = book.orders.get_mut(&client_order_id) else {
+            return ApplyOutcome::Unknown;
+        };
+        let bound = order.order_id.is_none();
+        let mut outcome = order.merge(&report);
+        if matches!(outcome, ApplyOutcome::Conflict { .. }) {
+            warn!("[OMS] {} {} 忽略冲突回报: {outcome:?}", order.exchange, client_order_id);
+        }
+        let Some(order_id) = order.order_id.clone().filter(|_| bound) else {
+            return outcome;
+        };
+
+        // 刚关联上订单号：建立索引并重放之前缓存的推送
+        let key = (report.exchange, order_id);
+        let buffered = book.pending.remove(&key).unwrap_or_default();
+        book.by_order_id.insert(key, client_order_id.clone());
+        if let Some(order) = book.orders.get_mut(&client_order_id) {
+            let from = match outcome {
+                ApplyOutcome::Updated { from, .. } => from,
+                _ => order.state,
+            };
+            for buffered in &buffered {
+                if let conflict @ ApplyOutcome::Conflict { .. } = order.merge(buffered) {
+                    warn!("[OMS] {} {} 忽略冲突回报: {conflict:?}", order.exchange, client_order_id);
+                }
+            }
+            if !buffered.is_empty() && !matches!(outcome, ApplyOutcome::Conflict { .. }) {
+                outcome = ApplyOutcome::Updated { from, to: order.state };
+            }
+        }
+        outcome
+    }
+
+    /// 合并下单响应
+    pub fn apply_response(&self, exchange: ExchangeType, response: &OrderResponse) -> ApplyOutcome {
+        self.apply(OrderReport::from_response(exchange, response))
+    }
+
+    /// 处理一条用户数据消息，非订单更新返回 None
+    pub fn apply_message(&self, message: &StandardizedMessage) -> Option<ApplyOutcome> {
+        match message {
+            StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(update)) => Some(self.apply(update.into())),
+            _ => None,
+        }
+    }
+
+    /// 处理期货连接器的交易事件，非订单更新返回 None
+    pub fn apply_trade_event(&self, exchange: ExchangeType, event: &TradeEvent) -> Option<ApplyOutcome> {
+        match event {
+            TradeEvent::OrderUpdate(update) => Some(self.apply(OrderReport::from_trading_update(exchange, update))),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, client_order_id: &str) -> Option<ManagedOrder> {
+        self.book.lock().unwrap_or_else(|e| e.into_inner()).orders.get(client_order_id).cloned()
+    }
+
+    pub fn get_by_order_id(&self, exchange: ExchangeType, order_id: &str) -> Option<ManagedOrder> {
+        let book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let client_order_id = book.by_order_id.get(&(exchange, order_id.to_string()))?;
+        book.orders.get(client_order_id).cloned()
+    }
+
+    pub fn by_strategy(&self, strategy: &str) -> Vec<ManagedOrder> {
+        self.filter(|order| order.strategy == strategy)
+    }
+
+    pub fn by_symbol(&self, symbol: &str) -> Vec<ManagedOrder> {
+        self.filter(|order| order.symbol == symbol)
+    }
+
+    pub fn by_exchange(&self, exchange: ExchangeType) -> Vec<ManagedOrder> {
+        self.filter(|order| order.exchange == exchange)
+    }
+
+    /// 尚未进入终态的订单
+    pub fn open_orders(&self) -> Vec<ManagedOrder> {
+        self.filter(|order| !order.is_terminal())
+    }
+
+    /// 移除已进入终态的订单，返回移除数量
+    pub fn prune_terminal(&self) -> usize {
+        let mut book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let before = book.orders.len();
+        book.orders.retain(|_, order| !order.is_terminal());
+        let OrderBook { orders, by_order_id, .. } = &mut *book;
+        by_order_id.retain(|_, client_order_id| orders.contains_key(client_order_id));
+        before - book.orders.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.book.lock().unwrap_or_else(|e| e.into_inner()).orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按创建时间排列的订单
+    fn filter(&self, predicate: impl Fn(&ManagedOrder) -> bool) -> Vec<ManagedOrder> {
+        let book = self.book.lock().unwrap_or_else(|e| e.into_inner());
+        let mut orders: Vec<ManagedOrder> = book.orders.values().filter(|order| predicate(order)).cloned().collect();
+        orders.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.client_order_id.cmp(&b.client_order_id)));
+        orders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(exchange: ExchangeType, symbol: &str, quantity: f64) -> OrderRequest {
+        OrderRequest {
+            symbol: symbol.to_string(),
+            exchange,
+            side: OrderSide::Buy,
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(100.0),
+            time_in_force: None,
+            reduce_only: None,
+            close_position: None,
+            position_side: None,
+            client_order_id: None,
+        }
+    }
+
+    fn response(order_id: &str, client_order_id: &str, status: &str, filled: f64, average_price: Option<f64>) -> OrderResponse {
+        OrderResponse {
+            order_id: order_id.to_string(),
+            client_order_id: Some(client_order_id.to_string()),
+            symbol: "BTCUSDT".to_string(),
+            status: status.to_string(),
+            filled_quantity: filled,
+            remaining_quantity: 0.0,
+            average_price,
+            timestamp: 1_000,
+        }
+    }
+
+    fn stream(order_id: &str, status: &str, filled: f64, timestamp: i64) -> StandardizedMessage {
+        StandardizedMessage::UserDataUpdate(UserData::OrderUpdate(market_data::OrderUpdate {
+            order_id: order_id.to_string(),
+            symbol: "BTCUSDT".to_string(),
+            exchange: ExchangeType::Binance,
+            status: status.to_string(),
+            filled_quantity: filled,
+            remaining_quantity: 0.0,
+            average_price: None,
+            timestamp,
+        }))
+    }
+
+    #[test]
+    fn test_transitions() {
+        assert!(OrderState::New.can_transition_to(OrderState::Rejected));
+        assert!(OrderState::PartiallyFilled.can_transition_to(OrderState::Canceled));
+        assert!(!OrderState::PartiallyFilled.can_transition_to(OrderState::Rejected));
+        assert!(!OrderState::PartiallyFilled.can_transition_to(OrderState::New));
+        assert!(!OrderState::Filled.can_transition_to(OrderState::Canceled));
+        assert_eq!(OrderState::from_api_string("cancelled"), Some(OrderState::Canceled));
+        assert_eq!(OrderState::from_api_string("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn test_assigns_unique_client_order_ids() {
+        let oms = OrderManager::new("tf-");
+        let first = oms.submit("x-arb", request(ExchangeType::Binance, "BTCUSDT", 1.0)).unwrap();
+        let second = oms.submit("x-arb", request(ExchangeType::Binance, "BTCUSDT", 1.0)).unwrap();
+        let first_id = first.client_order_id.unwrap();
+        assert!(first_id.starts_with("tfxarb"));
+        assert!(first_id.len() <= 32 && first_id.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(Some(first_id.clone()), second.client_order_id);
+
+        let mut duplicate = request(ExchangeType::Binance, "BTCUSDT", 1.0);
+        duplicate.client_order_id = Some(first_id);
+        assert!(oms.submit("x-arb", duplicate).is_err());
+    }
+
+    #[test]
+    fn test_stream_updates_before_rest_response_are_replayed() {
+        let oms = OrderManager::default();
+        let placed = oms.submit("xarb", request(ExchangeType::Binance, "BTCUSDT", 1.0)).unwrap();
+        let client_order_id = placed.client_order_id.unwrap();
+
+        // 推送先于下单响应到达，且彼此乱序
+        assert_eq!(oms.apply_message(&stream("42", "FILLED", 1.0, 3_000)), Some(ApplyOutcome::Pending));
+        assert_eq!(oms.apply_message(&stream("42", "PARTIALLY_FILLED", 0.4, 2_000)), Some(ApplyOutcome::Pending));
+
+        let outcome = oms.apply_response(ExchangeType::Binance, &response("42", &client_order_id, "NEW", 0.0, None));
+        assert_eq!(outcome, ApplyOutcome::Updated { from: OrderState::New, to: OrderState::Filled });
+
+        let order = oms.get_by_order_id(ExchangeType::Binance, "42").unwrap();
+        assert_eq!(order.client_order_id, client_order_id);
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.filled_quantity, 1.0);
+        assert_eq!(order.history, vec![OrderState::New, OrderState::Filled]);
+
+        // 迟到的旧状态不改变订单
+        assert_eq!(oms.apply_message(&stream("42", "NEW", 0.0, 1_500)), Some(ApplyOutcome::Unchanged));
+        // 终态之间的冲突被忽略并计数
+        assert_eq!(
+            oms.apply_message(&stream("42", "CANCELED", 1.0, 4_000)),
+            Some(ApplyOutcome::Conflict { current: OrderState::Filled, reported: OrderState::Canceled })
+        );
+        assert_eq!(oms.get(&client_order_id).unwrap().conflicts, 1);
+        assert!(oms.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_accumulates_fills_and_average_price() {
+        let oms = OrderManager::default();
+        let placed = oms.submit("basis", request(ExchangeType::BinanceFutures, "ETHUSDT", 3.0)).unwrap();
+        let client_order_id = placed.client_order_id.unwrap();
+        oms.apply_response(ExchangeType::BinanceFutures, &response("7", &client_order_id, "NEW", 0.0, None));
+
+        let mut update = trading::OrderUpdate {
+            symbol: "ETHUSDT".to_string(),
+            order_id: "7".to_string(),
+            client_order_id: client_order_id.clone(),
+            side: trading::OrderSide::Buy,
+            order_type: trading::OrderType::Limit,
+            status: trading::OrderStatus::PartiallyFilled,
+            quantity: 3.0,
+            price: 100.0,
+            executed_quantity: 1.0,
+            executed_price: 100.0,
+            timestamp: 2_000,
+            time_in_force: trading::TimeInForce::GTC,
+            reduce_only: false,
+            close_position: false,
+        };
+        let event = TradeEvent::OrderUpdate(update.clone());
+        assert_eq!(
+            oms.apply_trade_event(ExchangeType::BinanceFutures, &event),
+            Some(ApplyOutcome::Updated { from: OrderState::New, to: OrderState::PartiallyFilled })
+        );
+        update.executed_quantity = 2.0;
+        update.executed_price = 103.0;
+        oms.apply_trade_event(ExchangeType::BinanceFutures, &TradeEvent::OrderUpdate(update.clone()));
+        let order = oms.get(&client_order_id).unwrap();
+        assert!((order.average_price.unwrap() - 101.5).abs() < 1e-9);
+        assert_eq!(order.remaining_quantity(), 1.0);
+
+        // 已部分成交的订单不能再被拒绝
+        let mut rejected = update.clone();
+        rejected.status = trading::OrderStatus::Rejected;
+        assert!(matches!(
+            oms.apply_trade_event(ExchangeType::BinanceFutures, &TradeEvent::OrderUpdate(rejected)),
+            Some(ApplyOutcome::Conflict { .. })
+        ));
+
+        // 查询到的期货订单带累计成交额，均价以其为准；成交量补齐即视为完全成交
+        let futures_order = FuturesOrder {
+            symbol: "ETHUSDT".to_string(),
+            order_id: 7,
+            client_order_id: client_order_id.clone(),
+            price: 100.0,
+            orig_qty: 3.0,
+            executed_qty: 3.0,
+            cumulative_quote_qty: 306.0,
+            status: "PARTIALLY_FILLED".to_string(),
+            time_in_force: "GTC".to_string(),
+            order_type: "LIMIT".to_string(),
+            side: "BUY".to_string(),
+            stop_price: 0.0,
+            iceberg_qty: 0.0,
+            time: 1_000,
+            update_time: 5_000,
+            is_working: true,
+            orig_quote_order_qty: 0.0,
+            position_side: crate::connectors::binance::futures::PositionSide::Both,
+            close_position: false,
+            activation_price: 0.0,
+            callback_rate: 0.0,
+            working_type: "CONTRACT_PRICE".to_string(),
+            price_protect: false,
+        };
+        oms.apply(OrderReport::from(&futures_order));
+        let order = oms.get(&client_order_id).unwrap();
+        assert_eq!(order.state, OrderState::Filled);
+        assert!((order.average_price.unwrap() - 102.0).abs() < 1e-9);
+        assert_eq!(order.conflicts, 1);
+    }
+
+    #[test]
+    fn test_queries_by_strategy_symbol_and_exchange() {
+        let oms = OrderManager::default();
+        oms.submit("xarb", request(ExchangeType::Binance, "BTCUSDT", 1.0)).unwrap();
+        oms.submit("xarb", request(ExchangeType::OkxFutures, "BTCUSDT", 1.0)).unwrap();
+        let basis = oms.submit("basis", request(ExchangeType::OkxFutures, "ETHUSDT", 1.0)).unwrap();
+
+        assert_eq!(oms.by_strategy("xarb").len(), 2);
+        assert_eq!(oms.by_symbol("BTCUSDT").len(), 2);
+        assert_eq!(oms.by_exchange(ExchangeType::OkxFutures).len(), 2);
+        assert_eq!(oms.open_orders().len(), 3);
+
+        let client_order_id = basis.client_order_id.unwrap();
+        oms.apply_response(ExchangeType::OkxFutures, &response("9", &client_order_id, "CANCELED", 0.0, None));
+        assert_eq!(oms.prune_terminal(), 1);
+        assert_eq!(oms.len(), 2);
+        assert!(oms.get_by_order_id(ExchangeType::OkxFutures, "9").is_none());
+    }
+}
@@ -1,9 +1,208 @@
 // symbol_mapper.rs - Optimized version for cross-exchange mapping
 
 use std::collections::{HashMap, HashSet};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use lazy_static::lazy_static;
+use log::warn;
 use crate::{core::*, CrossExchangeArb, ExchangeFees};
+use crate::book_store::normalize_book_symbol;
+use crate::config::{get_config, Config};
+use crate::connectors::common::instruments::InstrumentSpec;
 use crate::exchange_types::Exchange;
 
+lazy_static! {
+    // Per-listing price multipliers shared by the book store and the cross-exchange scanners
+    static ref CONTRACT_MULTIPLIERS: RwLock<ContractMultipliers> =
+        RwLock::new(ContractMultipliers::from_config(get_config()));
+}
+
+/// Read access to the global contract multiplier table
+pub fn contract_multipliers() -> RwLockReadGuard<'static, ContractMultipliers> {
+    CONTRACT_MULTIPLIERS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write access to the global contract multiplier table (e.g. after an instrument refresh)
+pub fn contract_multipliers_mut() -> RwLockWriteGuard<'static, ContractMultipliers> {
+    CONTRACT_MULTIPLIERS.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Explicit price multiplier for a single exchange listing.
+///
+/// A listing such as `1000PEPEUSDT` quotes the price of 1000 PEPE, so its prices are
+/// divided by `multiplier` before being compared with `PEPE_USDT` on another exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingMultiplier {
+    pub base: String,
+    pub quote: String,
+    pub multiplier: f64,
+}
+
+impl ListingMultiplier {
+    /// Canonical symbol in the `BASE/QUOTE` form used by `SymbolMapper`
+    pub fn canonical(&self) -> String {
+        format!("{}/{}", self.base, self.quote)
+    }
+
+    /// Canonical symbol without separators, as used by the book store
+    pub fn book_symbol(&self) -> String {
+        format!("{}{}", self.base, self.quote)
+    }
+}
+
+/// Table of listings whose price is not quoted per unit of the canonical base asset.
+///
+/// Listings that are not in the table have a multiplier of 1.0 and keep their own symbol.
+#[derive(Debug, Clone, Default)]
+pub struct ContractMultipliers {
+    listings: HashMap<(Exchange, String), ListingMultiplier>,
+    // Listings from the config table are never overwritten by instrument metadata
+    configured: HashSet<(Exchange, String)>,
+}
+
+impl ContractMultipliers {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the table from the `[[contract_multipliers]]` config entries
+    pub fn from_config(config: &Config) -> Self {
+        let mut table = Self::new();
+
+        for entry in &config.contract_multipliers {
+            let exchange = match entry.exchange.parse::<Exchange>() {
+                Ok(exchange) => exchange,
+                Err(e) => {
+                    warn!("Skipping contract multiplier for {}: {e}", entry.symbol);
+                    continue;
+                }
+            };
+
+            let Some((base, quote)) = split_canonical(&entry.canonical) else {
+                warn!("Skipping contract multiplier for {}: canonical symbol {} needs a separator",
+                      entry.symbol, entry.canonical);
+                continue;
+            };
+
+            if table.insert(exchange, &entry.symbol, &base, &quote, entry.multiplier) {
+                table.configured.insert((exchange, normalize_book_symbol(&entry.symbol)));
+            } else {
+                warn!("Skipping contract multiplier for {}: invalid multiplier {}", entry.symbol, entry.multiplier);
+            }
+        }
+
+        table
+    }
+
+    /// Register a listing; returns false if the multiplier is not a positive finite number
+    pub fn insert(&mut self, exchange: Exchange, exchange_symbol: &str, base: &str, quote: &str, multiplier: f64) -> bool {
+        if !multiplier.is_finite() || multiplier <= 0.0 {
+            return false;
+        }
+
+        self.listings.insert(
+            (exchange, normalize_book_symbol(exchange_symbol)),
+            ListingMultiplier {
+                base: base.to_uppercase(),
+                quote: quote.to_uppercase(),
+                multiplier,
+            },
+        );
+        true
+    }
+
+    /// Derive multipliers from instrument metadata.
+    ///
+    /// Exchanges report bundled listings with a power-of-ten prefix on the base asset
+    /// (`1000PEPE`, `1000000MOG`). Returns the number of listings registered.
+    pub fn merge_instruments<'a>(&mut self, specs: impl IntoIterator<Item = &'a InstrumentSpec>) -> usize {
+        let mut merged = 0;
+
+        for spec in specs {
+            let key = (spec.exchange, normalize_book_symbol(&spec.symbol));
+            if self.configured.contains(&key) {
+                continue;
+            }
+
+            if let Some((base, multiplier)) = parse_bundle_prefix(&spec.base_asset) {
+                if self.insert(spec.exchange, &spec.symbol, &base, &spec.quote_asset, multiplier) {
+                    merged += 1;
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Look up the multiplier entry for a listing
+    pub fn get(&self, exchange: Exchange, symbol: &str) -> Option<&ListingMultiplier> {
+        self.listings.get(&(exchange, normalize_book_symbol(symbol)))
+    }
+
+    /// Price multiplier for a listing, 1.0 when the listing is quoted per unit
+    pub fn multiplier(&self, exchange: Exchange, symbol: &str) -> f64 {
+        self.get(exchange, symbol).map_or(1.0, |listing| listing.multiplier)
+    }
+
+    /// Canonical book symbol (no separators) for a listing
+    pub fn canonical_symbol(&self, exchange: Exchange, symbol: &str) -> String {
+        match self.get(exchange, symbol) {
+            Some(listing) => listing.book_symbol(),
+            None => normalize_book_symbol(symbol),
+        }
+    }
+
+    /// Convert a listing price to the price of one unit of the canonical base asset
+    pub fn normalize_price(&self, exchange: Exchange, symbol: &str, price: f64) -> f64 {
+        price / self.multiplier(exchange, symbol)
+    }
+
+    /// Number of registered listings
+    pub fn len(&self) -> usize {
+        self.listings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listings.is_empty()
+    }
+}
+
+/// Split a base asset such as `1000PEPE` into (`PEPE`, 1000.0).
+///
+/// Only powers of ten from 10 upwards count as bundle prefixes, so `1INCH` stays as is.
+pub fn parse_bundle_prefix(base_asset: &str) -> Option<(String, f64)> {
+    let digits = base_asset.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (prefix, base) = base_asset.split_at(digits);
+
+    if prefix.is_empty() || !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut value: u64 = prefix.parse().ok()?;
+    if value < 10 {
+        return None;
+    }
+    let multiplier = value as f64;
+    while value.is_multiple_of(10) {
+        value /= 10;
+    }
+
+    (value == 1).then(|| (base.to_uppercase(), multiplier))
+}
+
+/// Split a canonical symbol written with a separator (`PEPE_USDT`, `PEPE/USDT`, `PEPE-USDT`)
+fn split_canonical(canonical: &str) -> Option<(String, String)> {
+    let mut parts = canonical.split(['_', '/', '-']);
+    let base = parts.next()?.trim();
+    let quote = parts.next()?.trim();
+
+    if base.is_empty() || quote.is_empty() || parts.next().is_some() {
+        return None;
+    }
+
+    Some((base.to_uppercase(), quote.to_uppercase()))
+}
+
 /// A structure to manage symbol mappings across exchanges
 pub struct SymbolMapper {
     // Map from canonical symbol (e.g., BTC/USDT) to exchange-specific symbols
@@ -14,6 +213,9 @@ pub struct SymbolMapper {
     
     // Pre-computed full symbol mappings to avoid string concatenation in hot paths
     full_symbol_mappings: HashMap<(String, String), String>, // (canonical, exchange) -> full_prefixed_symbol
+    
+    // Price multipliers for listings quoted per bundle of the base asset
+    multipliers: HashMap<String, f64>, // full_prefixed_symbol -> multiplier
 }
 
 impl Default for SymbolMapper {
@@ -29,6 +231,7 @@ impl SymbolMapper {
             canonical_to_exchange: HashMap::new(),
             exchange_to_canonical: HashMap::new(),
             full_symbol_mappings: HashMap::new(),
+            multipliers: HashMap::new(),
         }
     }
     
@@ -51,9 +254,20 @@ impl SymbolMapper {
             }
         }
 
+        let contract_multipliers = contract_multipliers();
+        
         // Extract base and quote currencies from each symbol
         for (exchange, symbols) in &exchange_symbols {
             for symbol in symbols {
+                // Listings with an explicit multiplier map straight to their canonical symbol
+                let listing = exchange.parse::<Exchange>().ok()
+                    .and_then(|exchange_enum| contract_multipliers.get(exchange_enum, symbol));
+                if let Some(listing) = listing {
+                    mapper.add_mapping(&listing.canonical(), exchange, symbol);
+                    mapper.multipliers.insert(format!("{exchange}:{symbol}"), listing.multiplier);
+                    continue;
+                }
+                
                 // Try to extract base/quote using different formats
                 let (base, quote) = if symbol.contains('_') {
                     // Format: BASE_QUOTE
@@ -158,6 +372,11 @@ impl SymbolMapper {
         self.exchange_to_canonical.get(exchange_symbol).cloned()
     }
     
+    /// Get the price multiplier for a full prefixed exchange symbol (1.0 if quoted per unit)
+    pub fn get_multiplier(&self, exchange_symbol: &str) -> f64 {
+        self.multipliers.get(exchange_symbol).copied().unwrap_or(1.0)
+    }
+    
     /// Get exchange symbols for a canonical symbol across all exchanges
     pub fn get_all_exchange_symbols(&self, canonical: &str) -> Vec<String> {
         let mut result = Vec::new();
@@ -182,6 +401,9 @@ pub async fn process_mapped_cross_exchange_arbitrage(
     // Build the symbol mapper
     let symbol_mapper = SymbolMapper::build_from_price_data(app_state);
     
+    // Top-of-book prices only, so opportunities are reported at the default size
+    let trade_size_usd = get_config().arbitrage.default_trade_size_usd;
+    
     // Get canonical symbols that exist on multiple exchanges
     let multi_exchange_symbols = symbol_mapper.get_multi_exchange_symbols();
     
@@ -202,7 +424,12 @@ pub async fn process_mapped_cross_exchange_arbitrage(
             for exchange_symbol in &exchange_symbols {
                 if let Some(price_data) = app_state.price_data.get(exchange_symbol) {
                     let exchange = exchange_symbol.split(':').next().unwrap_or("UNKNOWN");
-                    exchange_prices.insert(exchange.to_string(), (price_data.best_bid, price_data.best_ask));
+                    // Compare prices per unit of the canonical base asset
+                    let multiplier = symbol_mapper.get_multiplier(exchange_symbol);
+                    exchange_prices.insert(
+                        exchange.to_string(),
+                        (price_data.best_bid / multiplier, price_data.best_ask / multiplier),
+                    );
                 }
             }
             
@@ -231,6 +458,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                         "BATONEX" => 0.0006,  // 0.06%
                         "TAPBIT" => 0.0006,  // 0.06%
                         "COINCATCH" => 0.0006,  // 0.06%
+                        "OKX_FUTURES" => 0.0005,  // 0.05%
                         _ => 0.001,         // Default 0.1%
                     };
                     
@@ -242,6 +470,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                         "BATONEX" => 0.0006,  // 0.06%
                         "TAPBIT" => 0.0006,  // 0.06%
                         "COINCATCH" => 0.0006,  // 0.06%
+                        "OKX_FUTURES" => 0.0005,  // 0.05%
                         _ => 0.001,         // Default 0.1%
                     };
                     
@@ -262,6 +491,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                             "BATONEX" => Exchange::Batonex,
                             "TAPBIT" => Exchange::TapBit,
                             "COINCATCH" => Exchange::CoinCatch,
+                            "OKX_FUTURES" => Exchange::OkxFutures,
                             _ => continue, // Skip unknown exchange
                         };
                         
@@ -273,6 +503,7 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                             "BATONEX" => Exchange::Batonex,
                             "TAPBIT" => Exchange::TapBit,
                             "COINCATCH" => Exchange::CoinCatch,
+                            "OKX_FUTURES" => Exchange::OkxFutures,
                             _ => continue, // Skip unknown exchange
                         };
                         
@@ -287,6 +518,9 @@ pub async fn process_mapped_cross_exchange_arbitrage(
                             profit_pct: gross_profit_pct,
                             net_profit_pct,
                             total_fees_pct,
+                            optimal_size: trade_size_usd / *buy_ask,
+                            expected_pnl: trade_size_usd * net_profit_pct / 100.0,
+                            profit_curve: Vec::new(),
                         };
                         
                         all_opportunities.push(opportunity);
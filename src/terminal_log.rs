@@ -189,6 +189,18 @@ pub async fn run_clean_metrics_display(app_state: AppState) {
                 "Total Profitable Opportunities".bold(), 
                 current_profitable_opps.to_string().green().bold());
 
+        // Most frequent sanity-check rejections since startup
+        let rejections = app_state.rejection_stats.snapshot();
+        if !rejections.is_empty() {
+            println!("\n{}", "SANITY REJECTIONS:".bold().yellow());
+            for (reason, exchange, count) in rejections.into_iter().take(5) {
+                println!("- {} {}: {}", 
+                        reason.to_string().bold(), 
+                        exchange, 
+                        count.to_string().red());
+            }
+        }
+
         println!("\n{}", "Press Ctrl+C to exit".dimmed());
         
         // Force flush stdout to ensure display updates
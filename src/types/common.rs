@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 /// 交易所类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExchangeType {
+    /// Phemex
+    Phemex,
     /// Binance
     Binance,
     /// Bybit
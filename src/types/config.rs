@@ -309,3 +309,41 @@ impl Default for AdvancedConnectorConfig {
     }
 }
 
+
+/// 有界数据通道满载时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelPolicy {
+    /// 丢弃队列中最旧的消息
+    DropOldest,
+    /// 订单簿等快照类消息只保留每个交易对的最新一条，其余消息按DropOldest处理
+    ConflateLatest,
+    /// 等待消费者腾出空间（用户数据等不能丢失的消息）
+    Block,
+}
+
+/// 有界数据通道配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataChannelConfig {
+    /// 队列容量（条）
+    pub capacity: usize,
+    /// 满载策略
+    pub policy: ChannelPolicy,
+}
+
+impl DataChannelConfig {
+    /// 行情数据流默认配置：订单簿按交易对合并
+    pub fn market_data() -> Self {
+        Self { capacity: 10_000, policy: ChannelPolicy::ConflateLatest }
+    }
+
+    /// 用户数据流默认配置：订单和成交回报不能丢失
+    pub fn user_data() -> Self {
+        Self { capacity: 1_000, policy: ChannelPolicy::Block }
+    }
+
+    /// 多个连接器合并后的高频数据流默认配置
+    pub fn merged_data() -> Self {
+        Self { capacity: 50_000, policy: ChannelPolicy::ConflateLatest }
+    }
+}
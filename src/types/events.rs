@@ -1,6 +1,7 @@
 //! 事件和高频数据类型定义
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::time::SystemTime;
 use super::common::{ExchangeType, MarketType};
 use super::market_data::{StandardizedOrderBook, StandardizedTrade};
@@ -38,12 +39,22 @@ pub enum SystemEvent {
         latency_ms: u64,
         timestamp: SystemTime,
     },
-    /// 套利机会事件
+    /// 套利机会事件（开启、更新、关闭时各发送一次）
     ArbitrageOpportunity {
         symbol: String,
         buy_exchange: ExchangeType,
         sell_exchange: ExchangeType,
+        /// 最新净利润百分比
         profit_percentage: f64,
+        stage: OpportunityStage,
+        /// 存续期间的最高净利润百分比
+        peak_profit_percentage: f64,
+        /// 开启以来的观测次数
+        updates: u64,
+        /// 开启以来的时长（毫秒）
+        duration_ms: u64,
+        /// 仅在关闭时有值
+        close_reason: Option<CloseReason>,
         timestamp: SystemTime,
     },
     /// 服务暂停事件
@@ -62,6 +73,35 @@ pub enum SystemEvent {
     },
 }
 
+/// 套利机会生命周期阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpportunityStage {
+    Opened,
+    Updated,
+    Closed,
+}
+
+/// 套利机会关闭原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CloseReason {
+    /// 价差收窄到阈值以下
+    SpreadCollapsed,
+    /// 订单簿在过期时间内没有再次确认该机会
+    BookStale,
+    /// 未通过合理性检查（异常利润、缩放异常等）
+    SanityCheckFailed,
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CloseReason::SpreadCollapsed => write!(f, "SPREAD_COLLAPSED"),
+            CloseReason::BookStale => write!(f, "BOOK_STALE"),
+            CloseReason::SanityCheckFailed => write!(f, "SANITY_CHECK_FAILED"),
+        }
+    }
+}
+
 /// 高频数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HighFrequencyData {
@@ -101,7 +141,7 @@ pub enum HighFrequencyData {
 }
 
 /// 数据流统计
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DataFlowStats {
     /// 消息总数
     pub total_messages: u64,
@@ -115,4 +155,13 @@ pub struct DataFlowStats {
     pub error_count: u64,
     /// 连接时长（秒）
     pub uptime_seconds: u64,
+    /// 有界通道满载时丢弃的消息数（包含被合并的消息）
+    #[serde(default)]
+    pub dropped_messages: u64,
+    /// 被同一交易对更新消息合并掉的消息数
+    #[serde(default)]
+    pub conflated_messages: u64,
+    /// 当前排队等待消费的消息数
+    #[serde(default)]
+    pub queued_messages: usize,
 }
\ No newline at end of file
@@ -92,6 +92,34 @@ impl From<ExchangeType> for crate::exchange_types::Exchange {
     }
 }
 
+// 与事件/高频数据使用的通用类型之间的转换（通用类型不区分现货和期货交易所）
+impl From<ExchangeType> for super::common::ExchangeType {
+    fn from(exchange_type: ExchangeType) -> Self {
+        use super::common::ExchangeType as Common;
+        match exchange_type {
+            ExchangeType::Phemex => Common::Phemex,
+            ExchangeType::LBank => Common::LBank,
+            ExchangeType::XtCom => Common::XTcom,
+            ExchangeType::TapBit => Common::Tapbit,
+            ExchangeType::Hbit => Common::HBit,
+            ExchangeType::Batonex => Common::Batonex,
+            ExchangeType::CoinCatch => Common::Coincatch,
+            ExchangeType::Binance | ExchangeType::BinanceFutures => Common::Binance,
+            ExchangeType::BybitFutures => Common::Bybit,
+            ExchangeType::OkxFutures => Common::OKX,
+        }
+    }
+}
+
+impl From<MarketType> for super::common::MarketType {
+    fn from(market_type: MarketType) -> Self {
+        match market_type {
+            MarketType::Spot => super::common::MarketType::Spot,
+            MarketType::Futures => super::common::MarketType::Futures,
+        }
+    }
+}
+
 // 根据交易所类型判断市场类型
 impl ExchangeType {
     pub fn get_market_type(&self) -> MarketType {
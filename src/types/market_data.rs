@@ -79,6 +79,9 @@ pub struct OrderUpdate {
     pub status: String,
     pub filled_quantity: f64,
     pub remaining_quantity: f64,
+    /// 累计成交均价，交易所未推送或尚未成交时为 None
+    #[serde(default)]
+    pub average_price: Option<f64>,
     pub timestamp: i64,
 }
 
@@ -27,7 +27,7 @@ pub use account::{AccountBalance, Position, PositionSide, CurrencyBalance};
 pub use common::{ExchangeType as CommonExchangeType, MarketType as CommonMarketType, ConnectionStatus as CommonConnectionStatus, DataType, UpdateSpeed};
 
 // 事件和高频数据类型
-pub use events::{SystemEvent, HighFrequencyData, DataFlowStats};
+pub use events::{SystemEvent, HighFrequencyData, DataFlowStats, OpportunityStage, CloseReason};
 
 // 交易相关类型
 pub use trading::*;
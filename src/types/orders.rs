@@ -81,7 +81,7 @@ pub struct DetailedOrderStatus {
 }
 
 /// 订单方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
     Sell,
@@ -127,6 +127,63 @@ pub enum OrderState {
     Expired,
 }
 
+impl OrderState {
+    /// 解析各连接器归一化后的状态字符串；UNKNOWN 等无法识别的状态返回 None
+    pub fn from_api_string(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "NEW" => Some(OrderState::New),
+            "PARTIALLY_FILLED" => Some(OrderState::PartiallyFilled),
+            "FILLED" => Some(OrderState::Filled),
+            "CANCELED" | "CANCELLED" => Some(OrderState::Canceled),
+            "REJECTED" => Some(OrderState::Rejected),
+            "EXPIRED" | "EXPIRED_IN_MATCH" => Some(OrderState::Expired),
+            _ => None,
+        }
+    }
+
+    pub fn to_api_string(&self) -> &'static str {
+        match self {
+            OrderState::New => "NEW",
+            OrderState::PartiallyFilled => "PARTIALLY_FILLED",
+            OrderState::Filled => "FILLED",
+            OrderState::Canceled => "CANCELED",
+            OrderState::Rejected => "REJECTED",
+            OrderState::Expired => "EXPIRED",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, OrderState::New | OrderState::PartiallyFilled)
+    }
+
+    /// 合法的状态转换：New→PartiallyFilled→Filled，未结束的订单可被撤销或过期，
+    /// 只有尚无成交的订单会被拒绝；终态不再转换
+    pub fn can_transition_to(&self, next: OrderState) -> bool {
+        match self {
+            OrderState::New => next != OrderState::New,
+            OrderState::PartiallyFilled => matches!(
+                next,
+                OrderState::Filled | OrderState::Canceled | OrderState::Expired
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl From<super::trading::OrderStatus> for OrderState {
+    fn from(status: super::trading::OrderStatus) -> Self {
+        use super::trading::OrderStatus;
+        match status {
+            OrderStatus::New => OrderState::New,
+            OrderStatus::PartiallyFilled => OrderState::PartiallyFilled,
+            OrderStatus::Filled => OrderState::Filled,
+            OrderStatus::Canceled => OrderState::Canceled,
+            OrderStatus::Rejected => OrderState::Rejected,
+            OrderStatus::Expired => OrderState::Expired,
+        }
+    }
+}
+
 /// 订单有效期
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TimeInForce {
@@ -134,6 +191,7 @@ pub enum TimeInForce {
     IOC, // Immediate Or Cancel
     FOK, // Fill Or Kill
     GTD, // Good Till Date
+    GTX, // Good Till Crossing，只做挂单（post-only），会立即成交时被拒绝
 }
 
 impl TimeInForce {
@@ -143,6 +201,7 @@ impl TimeInForce {
             TimeInForce::IOC => "IOC",
             TimeInForce::FOK => "FOK",
             TimeInForce::GTD => "GTD",
+            TimeInForce::GTX => "GTX",
         }
     }
 }